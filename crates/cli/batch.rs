@@ -0,0 +1,148 @@
+//! `--batch` mode: read one query per line and emit one NDJSON line per query.
+
+use std::io::{BufRead, Write};
+use std::sync::Arc;
+
+use anyhow::Result;
+use serde_json::json;
+use tokio::sync::Mutex;
+
+use website_searcher_core::cache::SearchCache;
+use website_searcher_core::fetcher::build_http_client;
+use website_searcher_core::models::{SearchResult, SiteConfig};
+use website_searcher_core::rate_limiter::RateLimiter;
+
+use crate::search::{self, SearchOptions};
+
+/// Run every query from `input` sequentially, sharing one HTTP client and rate limiter.
+///
+/// Blank lines and lines starting with `#` are skipped. Each query produces one line
+/// on `out` as soon as it finishes. Returns the number of queries processed.
+pub async fn run_batch<R: BufRead, W: Write>(
+    input: R,
+    out: &mut W,
+    sites: &[SiteConfig],
+    opts: &SearchOptions,
+    mut cache: Option<&mut SearchCache>,
+    rate_limiter: Option<Arc<Mutex<RateLimiter>>>,
+) -> Result<usize> {
+    let client = build_http_client();
+    let mut processed = 0usize;
+    for line in input.lines() {
+        let line = line?;
+        let query = line.trim();
+        if query.is_empty() || query.starts_with('#') {
+            continue;
+        }
+        let results = search::search_query(
+            &client,
+            sites,
+            query,
+            opts,
+            cache.as_deref_mut(),
+            rate_limiter.clone(),
+        )
+        .await;
+
+        writeln!(out, "{}", batch_line(query, &results))?;
+        out.flush()?;
+        processed += 1;
+    }
+    Ok(processed)
+}
+
+/// Single NDJSON record for one query
+fn batch_line(query: &str, results: &[SearchResult]) -> String {
+    json!({
+        "query": query,
+        "results": results,
+        "count": results.len(),
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use website_searcher_core::limits::ResultCaps;
+    use website_searcher_core::route::Routes;
+
+    #[test]
+    fn batch_line_is_single_line_json_keyed_by_query() {
+        let results = vec![SearchResult {
+            site: "fitgirl".into(),
+            title: "Elden Ring".into(),
+            url: "https://fitgirl-repacks.site/elden-ring/".into(),
+            ..Default::default()
+        }];
+        let line = batch_line("elden ring", &results);
+        assert!(!line.contains('\n'));
+        let v: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(v["query"], "elden ring");
+        assert_eq!(v["count"], 1);
+        assert_eq!(v["results"][0]["site"], "fitgirl");
+    }
+
+    #[tokio::test]
+    async fn skips_blank_and_comment_lines_and_uses_cache() {
+        // The ignore list and notes applied to results come from these, not
+        // the files of the machine running the tests
+        let dir = std::env::temp_dir().join(format!("ws-batch-{}", std::process::id()));
+        for (var, sub) in [
+            ("WEBSITE_SEARCHER_CONFIG_DIR", "config"),
+            ("WEBSITE_SEARCHER_DATA_DIR", "data"),
+            ("WEBSITE_SEARCHER_STATE_DIR", "state"),
+            ("WEBSITE_SEARCHER_CACHE_DIR", "cache"),
+        ] {
+            unsafe { std::env::set_var(var, dir.join(sub)) };
+        }
+        let mut cache = SearchCache::new(3);
+        cache.add(
+            "elden ring".into(),
+            vec![SearchResult {
+                site: "fitgirl".into(),
+                title: "Elden Ring".into(),
+                url: "https://fitgirl-repacks.site/elden-ring/".into(),
+                ..Default::default()
+            }],
+        );
+        let opts = SearchOptions {
+            limit: 10,
+            caps: ResultCaps::default(),
+            debug: false,
+            use_cf: false,
+            cf_url: String::new(),
+            cookie_headers: None,
+            accounts: None,
+            no_playwright: true,
+            show_progress: false,
+            site_timeout: None,
+            deterministic: false,
+            routes: Routes::default(),
+            steam_appids: false,
+            retry_failed: false,
+            hide_status: Vec::new(),
+            profile: Default::default(),
+            budget: None,
+            timings: None,
+            stream: None,
+        };
+        let input = "\n# wishlist\n  elden ring  \n";
+        let mut out = Vec::new();
+        let n = run_batch(
+            input.as_bytes(),
+            &mut out,
+            &[],
+            &opts,
+            Some(&mut cache),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(n, 1);
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text.lines().count(), 1);
+        assert!(text.contains("\"query\":\"elden ring\""));
+        assert!(text.contains("\"count\":1"));
+    }
+}
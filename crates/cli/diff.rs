@@ -0,0 +1,99 @@
+//! `diff` subcommand and `--diff-cache`: report what changed between two runs.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+
+use website_searcher_core::diff::ResultDiff;
+use website_searcher_core::models::SearchResult;
+
+/// Load results saved from a previous run.
+///
+/// Accepts the CLI's JSON output (`{"results": [...], "count": N}`) or a bare array.
+pub fn load_results_file(path: &Path) -> Result<Vec<SearchResult>> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    parse_results_json(&raw).with_context(|| format!("{} is not a results file", path.display()))
+}
+
+fn parse_results_json(raw: &str) -> Result<Vec<SearchResult>> {
+    let v: Value = serde_json::from_str(raw)?;
+    let list = match v {
+        Value::Object(mut o) => o.remove("results").unwrap_or(Value::Null),
+        other => other,
+    };
+    Ok(serde_json::from_value(list)?)
+}
+
+/// JSON form of a diff with a count summary
+pub fn diff_json(diff: &ResultDiff) -> Value {
+    json!({
+        "added": diff.added,
+        "removed": diff.removed,
+        "changed": diff.changed,
+        "summary": {
+            "added": diff.added.len(),
+            "removed": diff.removed.len(),
+            "changed": diff.changed.len(),
+        },
+    })
+}
+
+/// Human-readable diff: `+` added, `-` removed, `~` changed
+pub fn print_diff_table(diff: &ResultDiff) {
+    if diff.is_empty() {
+        println!("{}", t!("diff-no-changes"));
+        return;
+    }
+    for r in &diff.added {
+        println!("+ [{}] {} ({})", r.site, r.title, r.url);
+    }
+    for r in &diff.removed {
+        println!("- [{}] {} ({})", r.site, r.title, r.url);
+    }
+    for c in &diff.changed {
+        println!(
+            "~ [{}] {} -> {} ({})",
+            c.new.site, c.old.title, c.new.title, c.new.url
+        );
+    }
+    println!();
+    println!(
+        "{}",
+        t!(
+            "diff-summary",
+            added = diff.added.len(),
+            removed = diff.removed.len(),
+            changed = diff.changed.len()
+        )
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use website_searcher_core::diff::diff_results;
+
+    #[test]
+    fn parses_cli_output_and_bare_arrays() {
+        let wrapped =
+            r#"{"results":[{"site":"fitgirl","title":"A","url":"https://x/a"}],"count":1}"#;
+        let bare = r#"[{"site":"fitgirl","title":"A","url":"https://x/a"}]"#;
+        assert_eq!(
+            parse_results_json(wrapped).unwrap(),
+            parse_results_json(bare).unwrap()
+        );
+        assert!(parse_results_json(r#"{"count":1}"#).is_err());
+    }
+
+    #[test]
+    fn diff_json_has_summary_counts() {
+        let old = parse_results_json(r#"[{"site":"s","title":"A","url":"u1"}]"#).unwrap();
+        let new = parse_results_json(r#"[{"site":"s","title":"B","url":"u2"}]"#).unwrap();
+        let v = diff_json(&diff_results(&old, &new));
+        assert_eq!(v["summary"]["added"], 1);
+        assert_eq!(v["summary"]["removed"], 1);
+        assert_eq!(v["summary"]["changed"], 0);
+    }
+}
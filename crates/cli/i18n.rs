@@ -0,0 +1,178 @@
+//! Localized user-facing strings backed by Fluent.
+//!
+//! Messages live in `locales/<lang>.ftl` and are compiled into the binary. The
+//! language comes from `--lang`, then `WEBSITE_SEARCHER_LANG`, then the usual
+//! `LC_ALL`/`LC_MESSAGES`/`LANG` variables, falling back to English. Messages
+//! missing from a translation fall back to the English text.
+
+use std::sync::OnceLock;
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use unic_langid::LanguageIdentifier;
+
+/// Languages with a bundled translation
+pub const SUPPORTED_LANGS: &[&str] = &["en", "ru"];
+
+const EN_FTL: &str = include_str!("locales/en.ftl");
+const RU_FTL: &str = include_str!("locales/ru.ftl");
+
+struct Localizer {
+    primary: FluentBundle<FluentResource>,
+    fallback: Option<FluentBundle<FluentResource>>,
+}
+
+static LOCALIZER: OnceLock<Localizer> = OnceLock::new();
+
+fn bundle(lang: &str) -> FluentBundle<FluentResource> {
+    let source = match lang {
+        "ru" => RU_FTL,
+        _ => EN_FTL,
+    };
+    let langid: LanguageIdentifier = lang.parse().expect("bundled language id is valid");
+    let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+    // Terminal output: Unicode isolation marks would show up as stray characters
+    bundle.set_use_isolating(false);
+    let resource = FluentResource::try_new(source.to_string())
+        .unwrap_or_else(|(_, errs)| panic!("invalid {lang}.ftl: {errs:?}"));
+    bundle
+        .add_resource(resource)
+        .unwrap_or_else(|errs| panic!("duplicate messages in {lang}.ftl: {errs:?}"));
+    bundle
+}
+
+/// Map a locale string such as `ru_RU.UTF-8` or `en-GB` to a supported language
+fn supported_lang(value: &str) -> Option<&'static str> {
+    let code = value
+        .split(['_', '-', '.', '@'])
+        .next()?
+        .trim()
+        .to_ascii_lowercase();
+    SUPPORTED_LANGS.iter().copied().find(|l| *l == code)
+}
+
+/// Pick the UI language from an explicit choice or the environment
+pub fn resolve_lang(explicit: Option<&str>) -> &'static str {
+    let env = |k: &str| std::env::var(k).ok().filter(|v| !v.trim().is_empty());
+    explicit
+        .map(str::to_string)
+        .or_else(|| env("WEBSITE_SEARCHER_LANG"))
+        .or_else(|| env("LC_ALL"))
+        .or_else(|| env("LC_MESSAGES"))
+        .or_else(|| env("LANG"))
+        .and_then(|v| supported_lang(&v))
+        .unwrap_or("en")
+}
+
+/// Select the UI language for the rest of the process. Only the first call has effect.
+pub fn init(explicit: Option<&str>) {
+    LOCALIZER.get_or_init(|| localizer(resolve_lang(explicit)));
+}
+
+fn localizer(lang: &str) -> Localizer {
+    Localizer {
+        primary: bundle(lang),
+        fallback: (lang != "en").then(|| bundle("en")),
+    }
+}
+
+fn format(
+    bundle: &FluentBundle<FluentResource>,
+    id: &str,
+    args: Option<&FluentArgs>,
+) -> Option<String> {
+    let pattern = bundle.get_message(id)?.value()?;
+    let mut errors = Vec::new();
+    Some(
+        bundle
+            .format_pattern(pattern, args, &mut errors)
+            .into_owned(),
+    )
+}
+
+/// Look up message `id`; returns the id itself if no bundle defines it
+pub fn tr(id: &str, args: Option<&FluentArgs>) -> String {
+    let l = LOCALIZER.get_or_init(|| localizer("en"));
+    format(&l.primary, id, args)
+        .or_else(|| l.fallback.as_ref().and_then(|b| format(b, id, args)))
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Translate a message, optionally with named arguments: `t!("no-results")`,
+/// `t!("progress-searching", count = 3, sites = "a, b")`
+macro_rules! t {
+    ($id:literal) => {
+        $crate::i18n::tr($id, None)
+    };
+    ($id:literal, $($key:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($key), $value);)+
+        $crate::i18n::tr($id, Some(&args))
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(source: &str) -> Vec<String> {
+        source
+            .lines()
+            .filter(|l| !l.starts_with([' ', '#', '-']) && l.contains(" ="))
+            .map(|l| l.split(" =").next().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn supported_lang_parses_locale_strings() {
+        assert_eq!(supported_lang("ru_RU.UTF-8"), Some("ru"));
+        assert_eq!(supported_lang("en-GB"), Some("en"));
+        assert_eq!(supported_lang("EN"), Some("en"));
+        assert_eq!(supported_lang("de_DE"), None);
+        assert_eq!(supported_lang("C"), None);
+    }
+
+    #[test]
+    fn explicit_lang_wins() {
+        assert_eq!(resolve_lang(Some("ru")), "ru");
+        assert_eq!(resolve_lang(Some("ru-RU")), "ru");
+    }
+
+    #[test]
+    fn every_translation_covers_english_ids() {
+        let en = ids(EN_FTL);
+        let ru = ids(RU_FTL);
+        let missing: Vec<_> = en.iter().filter(|id| !ru.contains(id)).collect();
+        assert!(missing.is_empty(), "ru.ftl is missing {missing:?}");
+    }
+
+    #[test]
+    fn formats_arguments_and_plurals() {
+        let en = localizer("en");
+        let ru = localizer("ru");
+        let mut args = FluentArgs::new();
+        args.set("found", 1);
+        args.set("total", 2);
+        assert_eq!(
+            format(&en.primary, "wishlist-summary", Some(&args)).unwrap(),
+            "Found 1/2 titles"
+        );
+
+        let plural = |n: usize| {
+            let mut args = FluentArgs::new();
+            args.set("count", n);
+            format(&ru.primary, "tui-site-results", Some(&args)).unwrap()
+        };
+        assert_eq!(plural(1), "(1 результат)");
+        assert_eq!(plural(3), "(3 результата)");
+        assert_eq!(plural(5), "(5 результатов)");
+    }
+
+    #[test]
+    fn unknown_id_falls_back_to_id() {
+        assert_eq!(
+            tr("definitely-not-a-message", None),
+            "definitely-not-a-message"
+        );
+    }
+}
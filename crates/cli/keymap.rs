@@ -0,0 +1,367 @@
+//! Key bindings for the results browser.
+//!
+//! Every command has default keys; the `[keys]` table of `config.toml` replaces
+//! them per command, e.g. `quit = ["x"]` or `open = ["enter", "l"]`.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crossterm::event::KeyCode;
+
+/// Something a key press can do in the results browser
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Command {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    Collapse,
+    Expand,
+    Open,
+    YankUrl,
+    YankTitle,
+    Filter,
+    NewSearch,
+    NextMatch,
+    PrevMatch,
+    Mark,
+    MarkAll,
+    OpenMarked,
+    CopyUrls,
+    Export,
+    SendMagnets,
+    ToggleDetails,
+    FetchDetails,
+    CycleSort,
+    ToggleUrl,
+    ToggleSize,
+    ToggleVersion,
+    Help,
+    Quit,
+}
+
+impl Command {
+    /// All commands, in the order the help overlay lists them
+    pub const ALL: &[Command] = &[
+        Command::Up,
+        Command::Down,
+        Command::PageUp,
+        Command::PageDown,
+        Command::Top,
+        Command::Bottom,
+        Command::Collapse,
+        Command::Expand,
+        Command::Open,
+        Command::YankUrl,
+        Command::YankTitle,
+        Command::Filter,
+        Command::NewSearch,
+        Command::NextMatch,
+        Command::PrevMatch,
+        Command::Mark,
+        Command::MarkAll,
+        Command::OpenMarked,
+        Command::CopyUrls,
+        Command::Export,
+        Command::SendMagnets,
+        Command::ToggleDetails,
+        Command::FetchDetails,
+        Command::CycleSort,
+        Command::ToggleUrl,
+        Command::ToggleSize,
+        Command::ToggleVersion,
+        Command::Help,
+        Command::Quit,
+    ];
+
+    /// Name used in the `[keys]` table
+    pub fn name(self) -> &'static str {
+        match self {
+            Command::Up => "up",
+            Command::Down => "down",
+            Command::PageUp => "page_up",
+            Command::PageDown => "page_down",
+            Command::Top => "top",
+            Command::Bottom => "bottom",
+            Command::Collapse => "collapse",
+            Command::Expand => "expand",
+            Command::Open => "open",
+            Command::YankUrl => "yank_url",
+            Command::YankTitle => "yank_title",
+            Command::Filter => "filter",
+            Command::NewSearch => "new_search",
+            Command::NextMatch => "next_match",
+            Command::PrevMatch => "prev_match",
+            Command::Mark => "mark",
+            Command::MarkAll => "mark_all",
+            Command::OpenMarked => "open_marked",
+            Command::CopyUrls => "copy_urls",
+            Command::Export => "export",
+            Command::SendMagnets => "send_magnets",
+            Command::ToggleDetails => "toggle_details",
+            Command::FetchDetails => "fetch_details",
+            Command::CycleSort => "sort",
+            Command::ToggleUrl => "toggle_url",
+            Command::ToggleSize => "toggle_size",
+            Command::ToggleVersion => "toggle_version",
+            Command::Help => "help",
+            Command::Quit => "quit",
+        }
+    }
+
+    fn default_keys(self) -> &'static [KeyCode] {
+        use KeyCode::*;
+        match self {
+            Command::Up => &[Up, Char('k')],
+            Command::Down => &[Down, Char('j')],
+            Command::PageUp => &[PageUp],
+            Command::PageDown => &[PageDown],
+            Command::Top => &[Home],
+            Command::Bottom => &[End],
+            Command::Collapse => &[Left],
+            Command::Expand => &[Right],
+            Command::Open => &[Enter, Char('o')],
+            Command::YankUrl => &[Char('y')],
+            Command::YankTitle => &[Char('Y')],
+            Command::Filter => &[Char('/')],
+            Command::NewSearch => &[Char('s')],
+            Command::NextMatch => &[Char('n')],
+            Command::PrevMatch => &[Char('N')],
+            Command::Mark => &[Char(' ')],
+            Command::MarkAll => &[Char('a')],
+            Command::OpenMarked => &[Char('O')],
+            Command::CopyUrls => &[Char('c')],
+            Command::Export => &[Char('e')],
+            Command::SendMagnets => &[Char('m')],
+            Command::ToggleDetails => &[Char('i')],
+            Command::FetchDetails => &[Char('d')],
+            Command::CycleSort => &[Char('S')],
+            Command::ToggleUrl => &[Char('u')],
+            Command::ToggleSize => &[Char('z')],
+            Command::ToggleVersion => &[Char('v')],
+            Command::Help => &[Char('?')],
+            Command::Quit => &[Char('q'), Esc],
+        }
+    }
+
+    /// Localized one-line description for the help overlay
+    pub fn description(self) -> String {
+        match self {
+            Command::Up => t!("key-up"),
+            Command::Down => t!("key-down"),
+            Command::PageUp => t!("key-page-up"),
+            Command::PageDown => t!("key-page-down"),
+            Command::Top => t!("key-top"),
+            Command::Bottom => t!("key-bottom"),
+            Command::Collapse => t!("key-collapse"),
+            Command::Expand => t!("key-expand"),
+            Command::Open => t!("key-open"),
+            Command::YankUrl => t!("key-yank-url"),
+            Command::YankTitle => t!("key-yank-title"),
+            Command::Filter => t!("key-filter"),
+            Command::NewSearch => t!("key-new-search"),
+            Command::NextMatch => t!("key-next-match"),
+            Command::PrevMatch => t!("key-prev-match"),
+            Command::Mark => t!("key-mark"),
+            Command::MarkAll => t!("key-mark-all"),
+            Command::OpenMarked => t!("key-open-marked"),
+            Command::CopyUrls => t!("key-copy-urls"),
+            Command::Export => t!("key-export"),
+            Command::SendMagnets => t!("key-send-magnets"),
+            Command::ToggleDetails => t!("key-toggle-details"),
+            Command::FetchDetails => t!("key-fetch-details"),
+            Command::CycleSort => t!("key-sort"),
+            Command::ToggleUrl => t!("key-toggle-url"),
+            Command::ToggleSize => t!("key-toggle-size"),
+            Command::ToggleVersion => t!("key-toggle-version"),
+            Command::Help => t!("key-help"),
+            Command::Quit => t!("key-quit"),
+        }
+    }
+}
+
+/// Parse a key name such as `q`, `Q`, `space`, `enter`, `pgdn` or `f5`
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    let key = match name.to_ascii_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "enter" | "return" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "insert" | "ins" => KeyCode::Insert,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" | "pgup" => KeyCode::PageUp,
+        "pagedown" | "pgdn" => KeyCode::PageDown,
+        other => {
+            let n: u8 = other.strip_prefix('f')?.parse().ok()?;
+            return (1..=12).contains(&n).then_some(KeyCode::F(n));
+        }
+    };
+    Some(key)
+}
+
+/// Short label for a key in the help overlay
+pub fn key_label(key: KeyCode) -> String {
+    match key {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        KeyCode::PageUp => "PgUp".to_string(),
+        KeyCode::PageDown => "PgDn".to_string(),
+        KeyCode::F(n) => format!("F{n}"),
+        other => format!("{other:?}"),
+    }
+}
+
+/// Key → command lookup with user overrides applied
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<KeyCode, Command>,
+    keys: HashMap<Command, Vec<KeyCode>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&BTreeMap::new()).0
+    }
+}
+
+impl Keymap {
+    /// Build the keymap from `[keys]` overrides. Returns problems with the
+    /// overrides (unknown commands or keys) alongside it; those entries are ignored.
+    pub fn new(overrides: &BTreeMap<String, Vec<String>>) -> (Self, Vec<String>) {
+        let mut warnings = Vec::new();
+        for name in overrides.keys() {
+            if !Command::ALL.iter().any(|c| c.name() == name) {
+                warnings.push(t!("keys-unknown-command", command = name.as_str()));
+            }
+        }
+
+        let mut keys: HashMap<Command, Vec<KeyCode>> = HashMap::new();
+        for &command in Command::ALL {
+            let list = match overrides.get(command.name()) {
+                Some(names) => names
+                    .iter()
+                    .filter_map(|n| {
+                        let key = parse_key(n);
+                        if key.is_none() {
+                            warnings.push(t!("keys-unknown-key", key = n.as_str()));
+                        }
+                        key
+                    })
+                    .collect(),
+                None => command.default_keys().to_vec(),
+            };
+            keys.insert(command, list);
+        }
+
+        // Defaults first so overridden keys win any clash
+        let mut bindings = HashMap::new();
+        let (custom, default): (Vec<Command>, Vec<Command>) = Command::ALL
+            .iter()
+            .copied()
+            .partition(|c| overrides.contains_key(c.name()));
+        for command in default.into_iter().chain(custom) {
+            for &key in &keys[&command] {
+                bindings.insert(key, command);
+            }
+        }
+        // Drop keys taken over by another command so the help stays truthful
+        for (command, list) in keys.iter_mut() {
+            list.retain(|k| bindings.get(k) == Some(command));
+        }
+
+        (Self { bindings, keys }, warnings)
+    }
+
+    pub fn command(&self, key: KeyCode) -> Option<Command> {
+        self.bindings.get(&key).copied()
+    }
+
+    /// Keys bound to `command`, joined for display (e.g. "↑/k")
+    pub fn keys_for(&self, command: Command) -> String {
+        self.keys
+            .get(&command)
+            .map(|keys| keys.iter().map(|&k| key_label(k)).collect::<Vec<_>>())
+            .unwrap_or_default()
+            .join("/")
+    }
+
+    /// (keys, description) rows for the help overlay
+    pub fn help_lines(&self) -> Vec<(String, String)> {
+        Command::ALL
+            .iter()
+            .map(|&c| (self.keys_for(c), c.description()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overrides(pairs: &[(&str, &[&str])]) -> BTreeMap<String, Vec<String>> {
+        pairs
+            .iter()
+            .map(|(c, keys)| (c.to_string(), keys.iter().map(|k| k.to_string()).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_key_names() {
+        assert_eq!(parse_key("q"), Some(KeyCode::Char('q')));
+        assert_eq!(parse_key("Q"), Some(KeyCode::Char('Q')));
+        assert_eq!(parse_key("space"), Some(KeyCode::Char(' ')));
+        assert_eq!(parse_key("Enter"), Some(KeyCode::Enter));
+        assert_eq!(parse_key("pgdn"), Some(KeyCode::PageDown));
+        assert_eq!(parse_key("f5"), Some(KeyCode::F(5)));
+        assert_eq!(parse_key("f13"), None);
+        assert_eq!(parse_key("hyper"), None);
+    }
+
+    #[test]
+    fn defaults_cover_every_command() {
+        let map = Keymap::default();
+        assert_eq!(map.command(KeyCode::Char('q')), Some(Command::Quit));
+        assert_eq!(map.command(KeyCode::Char('?')), Some(Command::Help));
+        assert_eq!(map.keys_for(Command::Up), "↑/k");
+        for &c in Command::ALL {
+            assert!(!map.keys_for(c).is_empty(), "{} has no key", c.name());
+        }
+    }
+
+    #[test]
+    fn overrides_replace_defaults_and_win_clashes() {
+        let (map, warnings) = Keymap::new(&overrides(&[("quit", &["x"]), ("filter", &["s"])]));
+        assert!(warnings.is_empty());
+        assert_eq!(map.command(KeyCode::Char('x')), Some(Command::Quit));
+        assert_eq!(map.command(KeyCode::Char('q')), None);
+        assert_eq!(map.command(KeyCode::Char('s')), Some(Command::Filter));
+        assert_eq!(map.command(KeyCode::Char('/')), None);
+        // new_search lost its only key to the override
+        assert_eq!(map.keys_for(Command::NewSearch), "");
+    }
+
+    #[test]
+    fn unknown_commands_and_keys_are_reported() {
+        let (map, warnings) =
+            Keymap::new(&overrides(&[("fly", &["f"]), ("quit", &["hyper", "x"])]));
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(map.command(KeyCode::Char('x')), Some(Command::Quit));
+        assert_eq!(map.command(KeyCode::Char('f')), None);
+    }
+}
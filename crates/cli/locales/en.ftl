@@ -0,0 +1,263 @@
+## Cache
+
+cache-cleared = Cache cleared successfully.
+cache-empty = No cache to clear.
+debug-cleaned = Removed {$count} debug { $count ->
+        [one] file
+       *[other] files
+    } ({$kib} KiB) from {$dir}
+session-recorded = Recorded {$count} { $count ->
+        [one] response
+       *[other] responses
+    } to {$path}
+
+## Interactive prompts
+
+interactive-banner = Website Searcher (interactive)
+recent-searches = Recent searches:
+recent-search-entry = {$index}. {$query} ({$count} results)
+prompt-search-phrase = Search phrase:
+prompt-search-placeholder = e.g., elden ring
+error-empty-search = empty search phrase
+prompt-all-sites = Search all sites?
+prompt-all-sites-help = Choose 'No' to pick specific sites
+prompt-select-sites = Select sites (Space toggles, Enter confirms):
+prompt-select-sites-help = Use ↑/↓ to navigate, Space to toggle, Enter to confirm
+available-sites = Available sites:
+prompt-select-sites-plain = Select sites (names or numbers, space-separated). Press Enter for ALL:
+info-unknown-site-token = [info] ignoring unknown site token: {$token}
+info-no-valid-sites = [info] no valid sites selected; using ALL
+
+## Search progress (stderr)
+
+progress-searching = ⏳ Searching {$count} sites: {$sites}
+progress-site-done = {$done}/{$total} sites | {$site} {$count} results
+site-timed-out = [warn] {$site} timed out after {$secs}s
+site-fallbacks-exhausted = [warn] {$site}: no results from any strategy: {$attempts}
+site-likely-broken = [warn] {$site}: no results for {$count} different queries other sites matched; its parser is likely broken (see `stats`)
+site-ignores-query = [warn] {$site} answers every search with the same page; searching its listing page and filtering locally for a week (see `stats`)
+site-over-budget = [warn] {$site} skipped: its daily budget of {$limit} requests is spent; it resets at midnight UTC
+site-over-budget-short = daily budget of {$limit} requests spent
+sites-retrying = [info] searching {$sites} once more after network or server errors
+
+timings-header = [timings] {$total}s in total; per phase over every site:
+timings-phase = {$phase} {$secs}s
+timings-site = {$site}: {$phases}
+timings-advice-concurrency = [timings] sites spent a third of their time waiting for one of the 3 search slots; search fewer sites with --sites
+timings-advice-solver = [timings] FlareSolverr took half of the time; --no-cf or --fast skips it
+timings-advice-slow-site = [timings] {$site} took half of the time; leave it out with --sites or cap it with --timeout-per-site
+timings-cached = [timings] results came from the cache; search with --no-cache to time the sites
+
+## Result output
+
+no-results = No results.
+table-header-title = Title
+table-header-url = URL
+
+## Results browser TUI
+
+tui-results-title = Results ({$count}). ? keys, / filter, s new search, Enter open, q quit
+tui-results-filtered = Results ({$shown}/{$count}) matching "{$filter}". n/N next/prev match, Esc clear filter
+tui-filter-help = Enter apply, Esc clear
+tui-filter-no-matches = No results match "{$filter}"
+tui-new-search-prompt = New search:
+tui-new-search-help = Enter search, Esc cancel
+tui-marked-count = {$count} selected
+tui-opened = Opened {$count} { $count ->
+        [one] result
+       *[other] results
+    }
+tui-open-failed = Could not open link: {$error}
+tui-copied-urls = Copied {$count} { $count ->
+        [one] URL
+       *[other] URLs
+    } to the clipboard
+tui-copy-failed = Copy failed: {$error}
+tui-exported = Exported {$count} { $count ->
+        [one] result
+       *[other] results
+    } to {$path}
+tui-export-failed = Export failed: {$error}
+tui-magnets-sent = Sent {$count} magnet { $count ->
+        [one] link
+       *[other] links
+    } to the torrent client
+tui-no-magnets = No magnet links among the selected results
+tui-yanked-url = Copied URL
+tui-yanked-title = Copied title and URL
+tui-opened-with = Sent to {$name}
+tui-open-with-failed = Could not run {$name}: {$error}
+tui-open-with-empty = "{$name}" has no command set
+tui-open-with-unset = Nothing bound to {$key}; add [[open_with]] entries to config.toml
+tui-detail-title = Details
+tui-detail-site = Site
+tui-detail-url = URL
+tui-detail-size = Size
+tui-detail-version = Version
+tui-detail-build = Build
+tui-detail-date = Date
+tui-detail-links = Download links
+tui-detail-press-d = Press d to fetch them from the result page
+tui-detail-disabled = Run with --details to fetch download links
+tui-detail-loading = Fetching...
+tui-detail-no-links = None found on the page
+tui-detail-failed = Failed to fetch: {$error}
+tui-sort-site = site
+tui-sort-title = title
+tui-sort-relevance = relevance
+tui-sort-size = size
+tui-sort-date = date
+tui-sort-changed = Sorted by {$sort}
+tui-all-sites-sorted = All sites by {$sort}
+tui-settings-save-failed = Could not save view settings: {$error}
+tui-start-title = Recent searches ({$count})
+tui-start-help = Enter view cached results, r refresh, s new search, q quit
+tui-start-expired = expired
+tui-age-seconds = {$n}s ago
+tui-age-minutes = {$n}m ago
+tui-age-hours = {$n}h ago
+tui-age-days = {$n}d ago
+tui-help-title = Keys (any key to close)
+keys-unknown-command = Unknown command "{$command}" in [keys]
+keys-unknown-key = Unknown key "{$key}" in [keys]
+key-up = Move up
+key-down = Move down
+key-page-up = Scroll up 10 rows
+key-page-down = Scroll down 10 rows
+key-top = Jump to top
+key-bottom = Jump to bottom
+key-collapse = Fold the current site box
+key-expand = Unfold a folded site box
+key-open = Open the highlighted result
+key-yank-url = Copy URL
+key-yank-title = Copy title and URL
+key-filter = Filter results
+key-new-search = Start a new search
+key-next-match = Next match
+key-prev-match = Previous match
+key-mark = Select / deselect result
+key-mark-all = Select / deselect all shown
+key-open-marked = Open selected results
+key-copy-urls = Copy selected URLs
+key-export = Export selected to a JSON file
+key-send-magnets = Send selected magnet links
+key-toggle-details = Show / hide the detail pane
+key-fetch-details = Fetch download links
+key-sort = Cycle sort order
+key-toggle-url = Show / hide URLs
+key-toggle-size = Show / hide sizes
+key-toggle-version = Show / hide versions
+key-help = Show this help
+key-quit = Quit (Esc clears the filter first)
+key-open-with = Open with {$name}
+
+## Live search TUI
+
+tui-searching-title = Searching {$sites} sites | {$count} results found
+tui-sites-progress = {$done}/{$total} sites
+tui-site-progress = Site Progress
+tui-site-results = ({$count} results)
+tui-search-complete = Search complete! Press Enter to view results, q to quit
+tui-search-running = Searching... Press q to cancel
+site-timed-out-short = Timed out after {$secs}s
+status-queued = Queued
+status-fetching = Fetching
+status-solving = Solver
+status-parsing = Parsing
+status-completed = Done
+status-failed = Failed
+
+## Wishlist
+
+wishlist-empty = no titles found in {$file}
+wishlist-not-found = ✘ {$title} — not found
+wishlist-found = ✔ {$title} — {$sites} ({$count} results)
+wishlist-summary = Found {$found}/{$total} titles
+
+## Notes
+
+note-saved = Saved {$note} for {$url}
+note-cleared = Removed the note on {$url}
+note-not-found = No note on {$url}
+note-needs-change = give --status, --note or both
+notes-empty = No notes yet.
+
+## Ignore list
+
+ignore-added = Ignoring {$rule}
+ignore-exists = Already ignoring {$rule}
+ignore-removed = No longer ignoring {$rule}
+ignore-not-found = Not on the ignore list: {$rule}
+ignore-empty = The ignore list is empty.
+
+## Verify cache
+
+verify-not-cached = no cached search for "{$query}"; search it first
+verify-summary = {$alive} alive, {$dead} dead, {$blocked} blocked, {$unreachable} unreachable, {$skipped} not checked
+verify-pruned = Removed {$count} dead links from the cache
+
+## Grep
+
+grep-empty = No earlier search found a title matching "{$pattern}".
+grep-entry = [{$site}] {$title}  {$url}
+grep-found-by = found by: {$queries}
+
+## Learned patterns
+
+patterns-empty = No patterns learned yet.
+patterns-entry = {$site}: {$pattern} (found results on {$hits} pages)
+patterns-cleared-site = Forgot the pattern learned for {$site}
+patterns-not-found = No pattern learned for {$site}
+patterns-cleared = Forgot {$count} learned patterns
+
+## Secrets
+
+secrets-prompt = Value for {$name}:
+secrets-empty-value = no value given for secret "{$name}"
+secrets-stored = Stored {$name} in the {$backend}; refer to it as secret:{$name}
+secrets-empty = No secrets stored yet.
+secrets-entry = {$name} ({$backend})
+secrets-removed = Removed secret {$name}
+secrets-not-found = No secret named {$name}
+secrets-backend-keyring = OS keyring
+secrets-backend-file = encrypted file
+
+## Templates
+
+template-unknown = No template named "{$name}" in config.toml (see `template list`)
+template-invalid = template {$name}: {$error}
+template-bad-format = template {$name}: unknown format "{$format}" (json or table)
+templates-empty = No templates in config.toml.
+templates-entry = {$name}: {$query}  {$placeholders}
+
+## State archive
+
+state-exported = Exported {$count} files to {$file}:
+state-import-nothing = Nothing to import.
+state-change-added = added
+state-change-merged = merged
+state-change-replaced = replaced
+state-change-removed = removed
+
+## Stats
+
+stats-site = {$site}: {$p95} p95, empty for {$streak} queries other sites matched
+stats-likely-broken = likely broken parser, last empty for: {$queries}
+stats-ignores-query = search ignores the query; its listing page is filtered instead
+stats-budget = {$used} of {$limit} requests today
+
+## Sinks
+
+sink-failed = Could not send results to {$sink}: {$error}
+
+## Serve
+
+serve-listening = Answering searches on {$url} (Ctrl-C stops)
+serve-missing-query = missing the query: /search?q=...
+serve-no-sites = no configured site matches sites=
+
+## Diff
+
+diff-no-changes = No changes.
+diff-summary = {$added} added, {$removed} removed, {$changed} changed
+diff-no-cached = [info] no cached results for "{$query}"; all results count as added
@@ -0,0 +1,261 @@
+## Cache
+
+cache-cleared = Кэш успешно очищен.
+cache-empty = Кэш уже пуст.
+debug-cleaned = Удалено отладочных файлов: {$count} ({$kib} КиБ) из {$dir}
+session-recorded = Записано ответов: {$count} в {$path}
+
+## Interactive prompts
+
+interactive-banner = Website Searcher (интерактивный режим)
+recent-searches = Недавние поиски:
+recent-search-entry = {$index}. {$query} ({$count} { $count ->
+        [one] результат
+        [few] результата
+       *[many] результатов
+    })
+prompt-search-phrase = Поисковый запрос:
+prompt-search-placeholder = например, elden ring
+error-empty-search = пустой поисковый запрос
+prompt-all-sites = Искать на всех сайтах?
+prompt-all-sites-help = Выберите «Нет», чтобы отметить отдельные сайты
+prompt-select-sites = Выберите сайты (Пробел — отметить, Enter — подтвердить):
+prompt-select-sites-help = ↑/↓ — навигация, Пробел — отметить, Enter — подтвердить
+available-sites = Доступные сайты:
+prompt-select-sites-plain = Выберите сайты (названия или номера через пробел). Enter — ВСЕ:
+info-unknown-site-token = [info] неизвестный сайт пропущен: {$token}
+info-no-valid-sites = [info] не выбрано ни одного сайта; используются ВСЕ
+
+## Search progress (stderr)
+
+progress-searching = ⏳ Поиск на {$count} сайтах: {$sites}
+progress-site-done = {$done}/{$total} сайтов | {$site} {$count} { $count ->
+        [one] результат
+        [few] результата
+       *[many] результатов
+    }
+site-timed-out = [warn] {$site}: превышено время ожидания ({$secs} с)
+site-fallbacks-exhausted = [warn] {$site}: ни одна стратегия не дала результатов: {$attempts}
+site-likely-broken = [warn] {$site}: нет результатов по {$count} разным запросам, которые нашлись на других сайтах; вероятно, сломан парсер (см. `stats`)
+site-ignores-query = [warn] {$site} отвечает на любой поиск одной и той же страницей; неделю ищем по странице списка с локальной фильтрацией (см. `stats`)
+site-over-budget = [warn] {$site} пропущен: дневной лимит в {$limit} запросов исчерпан; он обнулится в полночь UTC
+site-over-budget-short = дневной лимит в {$limit} запросов исчерпан
+sites-retrying = [info] повторный поиск на {$sites} после сетевых ошибок или ошибок сервера
+
+timings-header = [timings] всего {$total} с; по этапам для всех сайтов:
+timings-phase = {$phase} {$secs} с
+timings-site = {$site}: {$phases}
+timings-advice-concurrency = [timings] треть времени сайты ждали одного из 3 слотов поиска; ищите на меньшем числе сайтов с --sites
+timings-advice-solver = [timings] половину времени занял FlareSolverr; --no-cf или --fast обходят его
+timings-advice-slow-site = [timings] половину времени занял {$site}; исключите его через --sites или ограничьте через --timeout-per-site
+timings-cached = [timings] результаты взяты из кэша; для замера сайтов ищите с --no-cache
+
+## Result output
+
+no-results = Ничего не найдено.
+table-header-title = Название
+table-header-url = Ссылка
+
+## Results browser TUI
+
+tui-results-title = Результаты ({$count}). ? — клавиши, / — фильтр, s — новый поиск, Enter — открыть, q — выход
+tui-results-filtered = Результаты ({$shown}/{$count}) по фильтру «{$filter}». n/N — след./пред. совпадение, Esc — сбросить фильтр
+tui-filter-help = Enter — применить, Esc — сбросить
+tui-filter-no-matches = Ничего не найдено по фильтру «{$filter}»
+tui-new-search-prompt = Новый поиск:
+tui-new-search-help = Enter — искать, Esc — отмена
+tui-marked-count = выбрано: {$count}
+tui-opened = Открыто: {$count}
+tui-open-failed = Не удалось открыть ссылку: {$error}
+tui-copied-urls = Скопировано ссылок в буфер обмена: {$count}
+tui-copy-failed = Не удалось скопировать: {$error}
+tui-exported = Сохранено результатов: {$count} в {$path}
+tui-export-failed = Не удалось сохранить: {$error}
+tui-magnets-sent = Magnet-ссылок отправлено в торрент-клиент: {$count}
+tui-no-magnets = Среди выбранных результатов нет magnet-ссылок
+tui-yanked-url = Ссылка скопирована
+tui-yanked-title = Название и ссылка скопированы
+tui-opened-with = Отправлено в {$name}
+tui-open-with-failed = Не удалось запустить {$name}: {$error}
+tui-open-with-empty = Для «{$name}» не задана команда
+tui-open-with-unset = На клавишу {$key} ничего не назначено; добавьте [[open_with]] в config.toml
+tui-detail-title = Подробности
+tui-detail-site = Сайт
+tui-detail-url = Ссылка
+tui-detail-size = Размер
+tui-detail-version = Версия
+tui-detail-build = Сборка
+tui-detail-date = Дата
+tui-detail-links = Ссылки на загрузку
+tui-detail-press-d = Нажмите d, чтобы получить их со страницы результата
+tui-detail-disabled = Запустите с --details, чтобы получать ссылки на загрузку
+tui-detail-loading = Загрузка...
+tui-detail-no-links = На странице ничего не найдено
+tui-detail-failed = Не удалось загрузить: {$error}
+tui-sort-site = сайту
+tui-sort-title = названию
+tui-sort-relevance = релевантности
+tui-sort-size = размеру
+tui-sort-date = дате
+tui-sort-changed = Сортировка по {$sort}
+tui-all-sites-sorted = Все сайты по {$sort}
+tui-settings-save-failed = Не удалось сохранить настройки вида: {$error}
+tui-start-title = Недавние поиски ({$count})
+tui-start-help = Enter — открыть из кэша, r — обновить, s — новый поиск, q — выход
+tui-start-expired = устарело
+tui-age-seconds = {$n} с назад
+tui-age-minutes = {$n} мин назад
+tui-age-hours = {$n} ч назад
+tui-age-days = {$n} дн. назад
+tui-help-title = Клавиши (любая клавиша — закрыть)
+keys-unknown-command = Неизвестная команда «{$command}» в [keys]
+keys-unknown-key = Неизвестная клавиша «{$key}» в [keys]
+key-up = Вверх
+key-down = Вниз
+key-page-up = Прокрутить на 10 строк вверх
+key-page-down = Прокрутить на 10 строк вниз
+key-top = В начало
+key-bottom = В конец
+key-collapse = Свернуть блок сайта
+key-expand = Развернуть блок сайта
+key-open = Открыть выбранный результат
+key-yank-url = Скопировать ссылку
+key-yank-title = Скопировать название и ссылку
+key-filter = Фильтр результатов
+key-new-search = Новый поиск
+key-next-match = Следующее совпадение
+key-prev-match = Предыдущее совпадение
+key-mark = Выбрать / снять выбор
+key-mark-all = Выбрать / снять все показанные
+key-open-marked = Открыть выбранные
+key-copy-urls = Скопировать ссылки выбранных
+key-export = Экспортировать выбранные в JSON
+key-send-magnets = Отправить magnet-ссылки выбранных
+key-toggle-details = Показать / скрыть подробности
+key-fetch-details = Загрузить ссылки на скачивание
+key-sort = Сменить сортировку
+key-toggle-url = Показать / скрыть ссылки
+key-toggle-size = Показать / скрыть размер
+key-toggle-version = Показать / скрыть версию
+key-help = Показать эту справку
+key-quit = Выход (Esc сначала сбрасывает фильтр)
+key-open-with = Открыть в {$name}
+
+## Live search TUI
+
+tui-searching-title = Поиск на {$sites} сайтах | найдено: {$count}
+tui-sites-progress = {$done}/{$total} сайтов
+tui-site-progress = Ход поиска
+tui-site-results = ({$count} { $count ->
+        [one] результат
+        [few] результата
+       *[many] результатов
+    })
+tui-search-complete = Поиск завершён! Enter — к результатам, q — выход
+tui-search-running = Идёт поиск... q — отмена
+site-timed-out-short = Время ожидания истекло ({$secs} с)
+status-queued = В очереди
+status-fetching = Загрузка
+status-solving = Обход защиты
+status-parsing = Разбор
+status-completed = Готово
+status-failed = Ошибка
+
+## Wishlist
+
+wishlist-empty = в {$file} не найдено ни одного названия
+wishlist-not-found = ✘ {$title} — не найдено
+wishlist-found = ✔ {$title} — {$sites} ({$count} { $count ->
+        [one] результат
+        [few] результата
+       *[many] результатов
+    })
+wishlist-summary = Найдено {$found} из {$total}
+
+## Notes
+
+note-saved = Сохранено {$note} для {$url}
+note-cleared = Заметка для {$url} удалена
+note-not-found = Для {$url} заметки нет
+note-needs-change = укажите --status, --note или оба
+notes-empty = Заметок пока нет.
+
+## Ignore list
+
+ignore-added = Игнорируется: {$rule}
+ignore-exists = Уже игнорируется: {$rule}
+ignore-removed = Больше не игнорируется: {$rule}
+ignore-not-found = Нет в списке игнорирования: {$rule}
+ignore-empty = Список игнорирования пуст.
+
+## Verify cache
+
+verify-not-cached = в кэше нет поиска «{$query}»; сначала выполните его
+verify-summary = доступно: {$alive}, удалено: {$dead}, заблокировано: {$blocked}, недоступно: {$unreachable}, не проверено: {$skipped}
+verify-pruned = Из кэша удалено мёртвых ссылок: {$count}
+
+## Grep
+
+grep-empty = Ни один прошлый поиск не нашёл названий по «{$pattern}».
+grep-entry = [{$site}] {$title}  {$url}
+grep-found-by = найдено по запросам: {$queries}
+
+## Learned patterns
+
+patterns-empty = Шаблоны ссылок ещё не изучены.
+patterns-entry = {$site}: {$pattern} (находил результаты на {$hits} стр.)
+patterns-cleared-site = Шаблон для {$site} забыт
+patterns-not-found = Для {$site} нет изученного шаблона
+patterns-cleared = Забыто шаблонов: {$count}
+
+## Secrets
+
+secrets-prompt = Значение для {$name}:
+secrets-empty-value = не задано значение секрета «{$name}»
+secrets-stored = {$name} сохранён ({$backend}); ссылайтесь на него как secret:{$name}
+secrets-empty = Секретов пока нет.
+secrets-entry = {$name} ({$backend})
+secrets-removed = Секрет {$name} удалён
+secrets-not-found = Секрета {$name} нет
+secrets-backend-keyring = связка ключей ОС
+secrets-backend-file = зашифрованный файл
+
+## Templates
+
+template-unknown = В config.toml нет шаблона «{$name}» (см. `template list`)
+template-invalid = шаблон {$name}: {$error}
+template-bad-format = шаблон {$name}: неизвестный формат «{$format}» (json или table)
+templates-empty = В config.toml нет шаблонов.
+templates-entry = {$name}: {$query}  {$placeholders}
+
+## State archive
+
+state-exported = Экспортировано файлов в {$file}: {$count}
+state-import-nothing = Импортировать нечего.
+state-change-added = добавлен
+state-change-merged = объединён
+state-change-replaced = заменён
+state-change-removed = удалён
+
+## Stats
+
+stats-site = {$site}: {$p95} p95, пусто по {$streak} запросам, найденным на других сайтах
+stats-likely-broken = вероятно, сломан парсер, последние пустые запросы: {$queries}
+stats-ignores-query = поиск игнорирует запрос; вместо него фильтруется страница списка
+stats-budget = запросов сегодня: {$used} из {$limit}
+
+## Sinks
+
+sink-failed = Не удалось отправить результаты в {$sink}: {$error}
+
+## Serve
+
+serve-listening = Поиск отвечает на {$url} (Ctrl-C — остановить)
+serve-missing-query = не указан запрос: /search?q=...
+serve-no-sites = ни один настроенный сайт не подходит под sites=
+
+## Diff
+
+diff-no-changes = Изменений нет.
+diff-summary = добавлено: {$added}, удалено: {$removed}, изменено: {$changed}
+diff-no-cached = [info] в кэше нет результатов для «{$query}»; все результаты считаются новыми
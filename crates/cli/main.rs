@@ -1,26 +1,50 @@
+#[macro_use]
+mod i18n;
+
+mod batch;
+mod config_show;
+mod diff;
+mod keymap;
+mod search;
+mod serve;
+mod sinks;
+mod tui;
+mod wishlist;
+
 use anyhow::Result;
-use clap::{Parser, ValueEnum};
-use futures::stream::{FuturesUnordered, StreamExt};
-use scraper::{Html, Selector};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::collections::HashSet;
 use std::sync::Arc;
-use tokio::sync::{Semaphore, mpsc};
 
-use website_searcher_core::analyzer::deduplicate_results;
+use website_searcher_core::auth::Accounts;
+use website_searcher_core::budget::{DailyBudget, RequestLog};
 use website_searcher_core::cache::{MIN_CACHE_SIZE, SearchCache};
+use website_searcher_core::capability::{self, QueryCapabilities};
+use website_searcher_core::debug_dump::DebugDir;
+use website_searcher_core::diff::{ResultDiff, diff_results};
+use website_searcher_core::fallback::Outcome;
+use website_searcher_core::ignore::{IgnoreKind, IgnoreList, IgnoreRule};
+use website_searcher_core::learned::LearnedPatterns;
+use website_searcher_core::limits::ResultCaps;
+use website_searcher_core::liveness::{self, LinkState};
 use website_searcher_core::models::SiteConfig;
-use website_searcher_core::monitoring;
-use website_searcher_core::query_parser::{
-    AdvancedQuery, MultiQuery, filter_results, operator_help,
+use website_searcher_core::monitoring::{
+    self, SearchPhase, SearchTimings, TimingAdvice, TimingBreakdown,
 };
+use website_searcher_core::notes::{NoteStatus, Notes};
+use website_searcher_core::profile::SearchProfile;
+use website_searcher_core::query::{build_search_url, with_query};
+use website_searcher_core::query_parser::{MultiQuery, operator_help};
 use website_searcher_core::rate_limiter::RateLimiter;
-use website_searcher_core::{cf, fetcher, output};
+use website_searcher_core::result_index::{self, ResultIndex};
+use website_searcher_core::route::Routes;
+use website_searcher_core::secrets::{self, Backend, SecretStore};
+use website_searcher_core::settings::{Settings, default_settings_path};
+use website_searcher_core::state_archive::{self, Change, ImportMode};
+use website_searcher_core::{cf, fetcher, output, paths, render_cache, session, template};
+
+use search::SearchOptions;
 
-use crossterm::event::KeyEventKind;
-use crossterm::{event, execute, terminal};
-use ratatui::{
-    prelude::*,
-    widgets::{Block, Borders, Gauge, List, ListItem, ListState, Paragraph, Wrap},
-};
 use reqwest::header::{
     ACCEPT, COOKIE, HeaderMap as ReqHeaderMap, HeaderName, HeaderValue, REFERER,
 };
@@ -30,69 +54,10 @@ use std::io::stdout;
 use std::process::Stdio;
 use tokio::io::AsyncReadExt;
 use tokio::process::Command;
-use website_searcher_core::cf::fetch_via_solver;
-use website_searcher_core::config::site_configs;
-use website_searcher_core::fetcher::{build_http_client, fetch_with_retry};
-use website_searcher_core::models::{SearchKind, SearchResult};
-use website_searcher_core::parser::parse_results;
-use website_searcher_core::query::{build_search_url, normalize_query};
-
-/// Events emitted during search for real-time progress updates
-#[derive(Debug, Clone)]
-pub enum SearchEvent {
-    /// Search is starting for a site
-    SiteStarted { site: String },
-    /// Site fetch is in progress
-    SiteFetching { site: String },
-    /// Site results are being parsed
-    SiteParsing { site: String },
-    /// Site search completed successfully
-    SiteCompleted { site: String, results_count: usize },
-    /// Site search failed
-    SiteFailed { site: String, error: String },
-    /// All searches are done
-    AllDone { total_results: usize },
-}
-
-/// Per-site progress status for TUI display
-#[derive(Debug, Clone)]
-pub struct SiteProgress {
-    pub site: String,
-    pub status: SiteStatus,
-    pub results_count: usize,
-    pub message: Option<String>,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum SiteStatus {
-    Pending,
-    Fetching,
-    Parsing,
-    Completed,
-    Failed,
-}
-
-impl SiteStatus {
-    fn emoji(&self) -> &'static str {
-        match self {
-            SiteStatus::Pending => "⏳",
-            SiteStatus::Fetching => "🔄",
-            SiteStatus::Parsing => "📄",
-            SiteStatus::Completed => "✅",
-            SiteStatus::Failed => "❌",
-        }
-    }
-
-    fn label(&self) -> &'static str {
-        match self {
-            SiteStatus::Pending => "Pending",
-            SiteStatus::Fetching => "Fetching",
-            SiteStatus::Parsing => "Parsing",
-            SiteStatus::Completed => "Done",
-            SiteStatus::Failed => "Failed",
-        }
-    }
-}
+use website_searcher_core::config::active_site_configs;
+use website_searcher_core::fetcher::build_http_client;
+use website_searcher_core::models::SearchResult;
+use website_searcher_core::parser::{QueryVariants, lowercase_into, parse_results};
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
 enum OutputFormat {
@@ -121,75 +86,78 @@ fn normalize_title(site: &str, title: &str) -> String {
                 }
             }
         }
-    } else if site.eq_ignore_ascii_case("csrin") {
-        // Drop forum boilerplate like "Main Forum •" and leading "Re:"
-        let mut c = cleaned.replace("Main Forum •", "");
-        let c_trim = c.trim_start();
-        if let Some(stripped) = c_trim.strip_prefix("Re: ") {
-            c = stripped.to_string();
-        } else if let Some(stripped) = c_trim.strip_prefix("Re:") {
-            c = stripped.to_string();
-        }
-        cleaned = c.trim().to_string();
     }
     cleaned
 }
 
 #[derive(Debug, Parser)]
-#[command(name = "websearcher", version, about = "Parallel game site searcher")]
+#[command(
+    name = "websearcher",
+    version,
+    about = "Parallel game site searcher",
+    disable_help_subcommand = true
+)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     /// Search phrase
     query: Option<String>,
 
-    /// Limit results per site
-    #[arg(long, default_value_t = 10)]
-    limit: usize,
+    /// Maximum results kept from each site
+    #[arg(long, visible_alias = "limit", default_value_t = 10, global = true)]
+    per_site_limit: usize,
 
-    /// Overall cutoff for total results across all sites
-    #[arg(long, default_value_t = 0)]
-    cutoff: usize,
+    /// Maximum results in total (0 = no limit); sites take turns filling it
+    #[arg(long, visible_alias = "cutoff", default_value_t = 0, global = true)]
+    total_limit: usize,
+
+    /// Maximum results per `|`-separated query segment (0 = no limit)
+    #[arg(long, default_value_t = 0, global = true)]
+    per_segment_limit: usize,
 
     /// Comma-separated site list to include (default: all)
-    #[arg(long)]
+    #[arg(long, global = true)]
     sites: Option<String>,
 
     /// Invert site selection (--sites a,b becomes "all except a,b")
-    #[arg(long, default_value_t = false)]
+    #[arg(long, default_value_t = false, global = true)]
     invert_sites: bool,
 
     /// Print per-site debug info (enables debug-level logs)
-    #[arg(long, default_value_t = false)]
+    #[arg(long, default_value_t = false, global = true)]
     debug: bool,
 
     /// Enable verbose output (shows info-level logs)
-    #[arg(short, long, default_value_t = false)]
+    #[arg(short, long, default_value_t = false, global = true)]
     verbose: bool,
 
     /// Output format: json or table
-    #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+    #[arg(long, value_enum, default_value_t = OutputFormat::Json, global = true)]
     format: OutputFormat,
 
     /// Disable FlareSolverr Cloudflare solver (enabled by default). Use this to opt out.
-    #[arg(long, default_value_t = false)]
+    #[arg(long, default_value_t = false, global = true)]
     no_cf: bool,
     /// FlareSolverr endpoint
-    #[arg(long, default_value = "http://localhost:8191/v1")]
+    #[arg(long, default_value = "http://localhost:8191/v1", global = true)]
     cf_url: String,
 
-    /// Cookie header to forward (e.g., from your browser) for protected sites
-    #[arg(long)]
+    /// Cookie header to forward (e.g., from your browser) for protected sites,
+    /// or `secret:<name>` for one stored with `secrets set`
+    #[arg(long, global = true)]
     cookie: Option<String>,
 
     /// Disable Playwright fallback for cs.rin.ru (forces non-PW backups only)
-    #[arg(long, default_value_t = false)]
+    #[arg(long, default_value_t = false, global = true)]
     no_playwright: bool,
 
     /// Maximum number of searches to cache (default: 3, max: 20)
-    #[arg(long, default_value_t = MIN_CACHE_SIZE)]
+    #[arg(long, default_value_t = MIN_CACHE_SIZE, global = true)]
     cache_size: usize,
 
     /// Disable search result caching
-    #[arg(long, default_value_t = false)]
+    #[arg(long, default_value_t = false, global = true)]
     no_cache: bool,
 
     /// Clear the search cache and exit
@@ -197,17 +165,359 @@ struct Cli {
     clear_cache: bool,
 
     /// Disable rate limiting between requests
-    #[arg(long, default_value_t = false)]
+    #[arg(long, default_value_t = false, global = true)]
     no_rate_limit: bool,
 
+    /// Abort a site after this many seconds (including all its fallbacks) and report it as timed out
+    #[arg(long, value_name = "SECS", global = true)]
+    timeout_per_site: Option<u64>,
+
+    /// Quick search: direct HTTP only (no FlareSolverr or Playwright), 5 seconds per site
+    /// unless --timeout-per-site says otherwise
+    #[arg(
+        long,
+        default_value_t = false,
+        global = true,
+        conflicts_with = "thorough"
+    )]
+    fast: bool,
+
+    /// Complete search: every fallback, up to 3 result pages per site and a check
+    /// that each result link still answers
+    #[arg(
+        long,
+        default_value_t = false,
+        global = true,
+        conflicts_with_all = ["no_cf", "no_playwright"]
+    )]
+    thorough: bool,
+
     /// Show help for advanced search operators and exit
     #[arg(long, default_value_t = false)]
     help_operators: bool,
+
+    /// Interface language (en, ru). Defaults to WEBSITE_SEARCHER_LANG, then LANG
+    #[arg(long, global = true, value_parser = i18n::SUPPORTED_LANGS.to_vec())]
+    lang: Option<String>,
+
+    /// Read one query per line from stdin and print one NDJSON line per query
+    #[arg(long, default_value_t = false, conflicts_with = "query")]
+    batch: bool,
+
+    /// Search fresh and print what changed versus the cached results for the same query
+    #[arg(long, default_value_t = false, conflicts_with = "no_cache")]
+    diff_cache: bool,
+
+    /// Let the results TUI fetch download links from result pages (press d)
+    #[arg(long, default_value_t = false, global = true)]
+    details: bool,
+
+    /// Only print the results; skip the `[[sinks]]` configured in config.toml
+    #[arg(long, default_value_t = false, global = true)]
+    no_sinks: bool,
+
+    /// Print plain URLs in table output instead of clickable terminal hyperlinks
+    #[arg(long, default_value_t = false, global = true)]
+    no_hyperlinks: bool,
+
+    /// Reproducible output: sort strictly by site, page rank and URL, omit fetch
+    /// times and send the same request headers every run
+    #[arg(long, default_value_t = false, global = true)]
+    deterministic: bool,
+
+    /// Leave out results marked with these statuses (see `annotate`), e.g. ignored
+    #[arg(long, value_name = "STATUS", value_delimiter = ',', global = true)]
+    hide_status: Vec<NoteStatus>,
+
+    /// Look up each result's Steam AppID (Steam's app list is downloaded once a week).
+    /// Also on when `steam_appids = true` in config.toml
+    #[arg(long, default_value_t = false, global = true)]
+    steam_appids: bool,
+
+    /// Search the sites that failed with a network or server error once more
+    /// after the others finish. Also on when `retry_failed = true` in config.toml
+    #[arg(long, default_value_t = false, global = true)]
+    retry_failed: bool,
+
+    /// After the results, print where the search's time went (queueing,
+    /// fetching, FlareSolverr, Playwright, parsing, post-processing) to stderr
+    #[arg(long, default_value_t = false, global = true)]
+    timings: bool,
+
+    /// Print each site's results as soon as it finishes instead of all at the
+    /// end: JSON lines (one result per line) or a table per site. Results are
+    /// still cached and sent to sinks once the search is over; ignored with
+    /// --diff-cache
+    #[arg(long, default_value_t = false, global = true)]
+    stream: bool,
+
+    /// Save every page, feed and solver response this run fetches to FILE (a tar archive)
+    #[arg(long, value_name = "FILE", global = true, conflicts_with = "replay")]
+    record: Option<std::path::PathBuf>,
+
+    /// Answer every fetch from a recording made with --record instead of the network
+    #[arg(long, value_name = "FILE", global = true)]
+    replay: Option<std::path::PathBuf>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Commands {
+    /// Bulk operations on a wishlist of titles
+    Wishlist {
+        #[command(subcommand)]
+        action: WishlistAction,
+    },
+    /// Compare two saved JSON result files and report added/removed/changed results
+    Diff {
+        /// Older results file (JSON output of a previous run)
+        old: std::path::PathBuf,
+        /// Newer results file
+        new: std::path::PathBuf,
+    },
+    /// Manage the pages and responses saved by --debug
+    Debug {
+        #[command(subcommand)]
+        action: DebugAction,
+    },
+    /// Inspect the configuration searches run with
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Mark result URLs as interested, downloaded or ignored and keep notes on them
+    Annotate {
+        #[command(subcommand)]
+        action: AnnotateAction,
+    },
+    /// Leave result URLs, whole domains or titles matching a pattern out of every search
+    Ignore {
+        #[command(subcommand)]
+        action: IgnoreAction,
+    },
+    /// Show or forget the link patterns learned for sites whose result selector stopped matching
+    Patterns {
+        #[command(subcommand)]
+        action: PatternsAction,
+    },
+    /// Keep passwords, cookies and tokens in the OS keyring; the config refers
+    /// to them as "secret:<name>"
+    Secrets {
+        #[command(subcommand)]
+        action: SecretsAction,
+    },
+    /// Move the config, cache, history, favorites, notes, ignore list and
+    /// watchlist to another machine through a zip
+    State {
+        #[command(subcommand)]
+        action: StateAction,
+    },
+    /// Run a search template from config.toml, filling its placeholders
+    Template {
+        #[command(subcommand)]
+        action: TemplateAction,
+    },
+    /// Show each site's recent search times and flag sites whose parser looks broken
+    Stats,
+    /// Find results of earlier searches by title words, without searching again
+    Grep {
+        /// Words the title must contain; each may be the start of a word
+        pattern: String,
+    },
+    /// Re-check every URL of a cached search and save whether each still answers
+    VerifyCache {
+        /// The query, as it was searched
+        query: String,
+        /// Also remove dead links from the cached results
+        #[arg(long, default_value_t = false)]
+        prune: bool,
+    },
+    /// Answer searches over HTTP: /search?q=..., /sites and /cache
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:7878")]
+        listen: std::net::SocketAddr,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum IgnoreAction {
+    /// Add a rule
+    Add {
+        /// url (one result), domain (a host and its subdomains) or title (a regex)
+        kind: IgnoreKind,
+        /// The URL, domain or title regex
+        value: String,
+    },
+    /// Remove a rule added with `ignore add`
+    Remove {
+        /// url, domain or title
+        kind: IgnoreKind,
+        /// The value it was added with
+        value: String,
+    },
+    /// List the rules
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+enum PatternsAction {
+    /// List each site's learned pattern
+    List,
+    /// Forget the pattern of SITE, or of every site
+    Clear {
+        /// Site name, as in --sites
+        site: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum SecretsAction {
+    /// Store a secret, read from a prompt or from stdin
+    Set {
+        /// Name the config refers to it by, as "secret:<name>"
+        name: String,
+    },
+    /// List the stored secrets and where each is kept (never their values)
+    List,
+    /// Delete a stored secret
+    Remove {
+        /// Name it was stored under
+        name: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum StateAction {
+    /// Write everything worth keeping into a zip (secrets excepted)
+    Export {
+        /// Zip file to create
+        file: std::path::PathBuf,
+    },
+    /// Restore a zip written by `state export`
+    Import {
+        /// Zip file to read
+        file: std::path::PathBuf,
+        /// merge (keep what is here and add what only the zip has) or
+        /// replace (make the files those of the zip)
+        #[arg(long, default_value = "merge")]
+        mode: ImportMode,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum TemplateAction {
+    /// Search with a template: `template run switch-roms --title zelda`
+    Run {
+        /// Template name, as in `[templates.<name>]`
+        name: String,
+        /// A value for each placeholder, as `--<placeholder> <value>`
+        #[arg(
+            trailing_var_arg = true,
+            allow_hyphen_values = true,
+            value_name = "--PLACEHOLDER VALUE"
+        )]
+        values: Vec<String>,
+    },
+    /// List the templates and their placeholders
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+enum AnnotateAction {
+    /// Set the status and/or note of a result URL; other searches show them
+    Set {
+        /// Result URL, as printed by a search
+        url: String,
+        /// interested, downloaded or ignored
+        #[arg(long)]
+        status: Option<NoteStatus>,
+        /// Free text; an empty string removes it
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// Remove the status and note of a result URL
+    Clear {
+        /// Result URL
+        url: String,
+    },
+    /// List annotated URLs
+    List {
+        /// Only URLs with this status
+        #[arg(long)]
+        status: Option<NoteStatus>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigAction {
+    /// Print the effective settings (defaults, config file, environment and
+    /// flags combined) and every field of the sites a search would query
+    Show,
+}
+
+#[derive(Debug, Subcommand)]
+enum DebugAction {
+    /// Delete every saved debug file
+    Clean,
+}
+
+#[derive(Debug, Subcommand)]
+enum WishlistAction {
+    /// Search every title in a wishlist file (plain text or Steam wishlist JSON)
+    Search {
+        /// Path to the wishlist file
+        file: std::path::PathBuf,
+    },
 }
 
 #[tokio::main(flavor = "multi_thread")]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+    i18n::init(cli.lang.as_deref());
+    if let Some(cookie) = &cli.cookie {
+        cli.cookie = Some(secrets::resolve(cookie)?);
+    }
+    if let Some(Commands::Template {
+        action: TemplateAction::Run { name, values },
+    }) = &cli.command
+    {
+        let (name, values) = (name.clone(), values.clone());
+        apply_template(&mut cli, &name, &values)?;
+        cli.command = None;
+    }
+    let session = start_session(&mut cli)?;
+    let outcome = run(cli).await;
+    // Keep what was fetched even if the run failed part way
+    if let Some(session) = session.filter(|s| s.mode() == session::Mode::Record) {
+        session.save()?;
+        eprintln!(
+            "{}",
+            t!(
+                "session-recorded",
+                count = session.len(),
+                path = session.path().display().to_string()
+            )
+        );
+    }
+    outcome
+}
+
+/// Activate `--record` or `--replay`. Both bypass the search cache, so every
+/// fetch really happens (or is really replayed).
+fn start_session(cli: &mut Cli) -> Result<Option<&'static session::Session>> {
+    let session = match (&cli.record, &cli.replay) {
+        (Some(path), _) => session::Session::record(path),
+        (None, Some(path)) => session::Session::replay(path)?,
+        (None, None) => return Ok(None),
+    };
+    cli.no_cache = true;
+    session::activate(session).map(Some)
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    if cli.deterministic {
+        fetcher::use_fixed_identity();
+    }
 
     // Initialize monitoring and tracing with appropriate log levels
     monitoring::init_monitoring_with_levels(
@@ -216,23 +526,30 @@ async fn main() -> Result<()> {
         cli.debug,
     )?;
 
-    // Cache file path - use platform-appropriate cache directory
-    let cache_path = dirs::cache_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("website-searcher")
-        .join("search_cache.json");
+    paths::migrate_legacy_files();
+    let cache_path = paths::search_cache_file();
 
-    // Handle --clear-cache flag
+    let render_cache = render_cache::RenderCache::new(paths::rendered_dir());
+
+    // Handle --clear-cache flag (rendered pages go too)
     if cli.clear_cache {
+        let rendered = render_cache.clear();
         if cache_path.exists() {
             std::fs::remove_file(&cache_path)?;
-            println!("Cache cleared successfully.");
+            println!("{}", t!("cache-cleared"));
+        } else if rendered > 0 {
+            println!("{}", t!("cache-cleared"));
         } else {
-            println!("No cache to clear.");
+            println!("{}", t!("cache-empty"));
         }
         return Ok(());
     }
 
+    // Pages rendered by the solver or Playwright are reused like cached searches
+    if !cli.no_cache {
+        render_cache::activate(render_cache);
+    }
+
     // Handle --help-operators flag
     if cli.help_operators {
         println!("{}", operator_help());
@@ -249,21 +566,188 @@ async fn main() -> Result<()> {
     // Update cache size if specified
     search_cache.set_max_size(cli.cache_size);
 
+    if let Some(Commands::Diff { old, new }) = &cli.command {
+        let old = diff::load_results_file(old)?;
+        let new = diff::load_results_file(new)?;
+        print_diff(cli.format, &diff_results(&old, &new))?;
+        return Ok(());
+    }
+
+    if let Some(Commands::Config {
+        action: ConfigAction::Show,
+    }) = &cli.command
+    {
+        let options = bulk_search_options(&cli);
+        let settings = Settings::load(&default_settings_path());
+        let sites = cli_selected_sites(&cli)?;
+        let effective = config_show::Effective {
+            options: &options,
+            settings: &settings,
+            sites: &sites,
+            lang: i18n::resolve_lang(cli.lang.as_deref()),
+            cache_path: &cache_path,
+            cache: !cli.no_cache,
+            cache_size: cli.cache_size,
+            rate_limit: !cli.no_rate_limit,
+        };
+        match cli.format {
+            OutputFormat::Json => println!(
+                "{}",
+                serde_json::to_string_pretty(&config_show::effective_json(&effective))?
+            ),
+            OutputFormat::Table => config_show::print_table(&effective),
+        }
+        return Ok(());
+    }
+
+    if let Some(Commands::Annotate { action }) = &cli.command {
+        return annotate(action, cli.format);
+    }
+
+    if let Some(Commands::Ignore { action }) = &cli.command {
+        return ignore(action, cli.format);
+    }
+
+    if let Some(Commands::Patterns { action }) = &cli.command {
+        return patterns(action, cli.format);
+    }
+
+    if let Some(Commands::Secrets { action }) = &cli.command {
+        return secrets(action, cli.format);
+    }
+
+    if let Some(Commands::State { action }) = &cli.command {
+        return state(action, cli.format);
+    }
+
+    if let Some(Commands::Template {
+        action: TemplateAction::List,
+    }) = &cli.command
+    {
+        return templates(cli.format);
+    }
+
+    if let Some(Commands::Stats) = &cli.command {
+        return stats(&cli_selected_sites(&cli)?, cli.format);
+    }
+
+    if let Some(Commands::Grep { pattern }) = &cli.command {
+        return grep(&search_cache, pattern, cli.format);
+    }
+
+    if let Some(Commands::VerifyCache { query, prune }) = &cli.command {
+        return verify_cache(&cli, &mut search_cache, &cache_path, query, *prune).await;
+    }
+
+    if let Some(Commands::Serve { listen }) = &cli.command {
+        let server = serve::Server {
+            sites: cli_selected_sites(&cli)?,
+            opts: bulk_search_options(&cli),
+            cache: (!cli.no_cache).then_some(search_cache),
+            cache_path,
+            rate_limiter: cli_rate_limiter(&cli),
+        };
+        return serve::run(*listen, server).await;
+    }
+
+    if let Some(Commands::Debug {
+        action: DebugAction::Clean,
+    }) = &cli.command
+    {
+        let dir = DebugDir::default();
+        let removed = dir.clean()?;
+        println!(
+            "{}",
+            t!(
+                "debug-cleaned",
+                count = removed.files,
+                kib = removed.bytes.div_ceil(1024),
+                dir = dir.path().display().to_string()
+            )
+        );
+        return Ok(());
+    }
+
+    // Batch mode: queries come from stdin, results go out as NDJSON
+    if cli.batch {
+        let stdin = std::io::stdin();
+        let mut out = stdout().lock();
+        let processed = batch::run_batch(
+            stdin.lock(),
+            &mut out,
+            &cli_selected_sites(&cli)?,
+            &bulk_search_options(&cli),
+            (!cli.no_cache).then_some(&mut search_cache),
+            cli_rate_limiter(&cli),
+        )
+        .await?;
+        if processed > 0 {
+            save_cache(&cli, &search_cache, &cache_path);
+        }
+        return Ok(());
+    }
+
+    if let Some(Commands::Wishlist {
+        action: WishlistAction::Search { file },
+    }) = &cli.command
+    {
+        let titles = wishlist::load_titles(file)?;
+        if titles.is_empty() {
+            anyhow::bail!(t!("wishlist-empty", file = file.display().to_string()));
+        }
+        let mut opts = bulk_search_options(&cli);
+        opts.show_progress = std::io::stderr().is_terminal() && !cli.debug;
+        let reports = wishlist::search_titles(
+            &titles,
+            &cli_selected_sites(&cli)?,
+            &opts,
+            (!cli.no_cache).then_some(&mut search_cache),
+            cli_rate_limiter(&cli),
+        )
+        .await;
+        save_cache(&cli, &search_cache, &cache_path);
+        match cli.format {
+            OutputFormat::Json => println!(
+                "{}",
+                serde_json::to_string_pretty(&wishlist::report_json(&reports))?
+            ),
+            OutputFormat::Table => wishlist::print_report_table(&reports),
+        }
+        return Ok(());
+    }
+
     // Interactive prompt when query omitted
     let query_value: String = match &cli.query {
         Some(q) => q.clone(),
+        // Full-screen start screen over the cached searches
+        None if std::io::stdin().is_terminal()
+            && std::io::stdout().is_terminal()
+            && !search_cache.is_empty() =>
+        {
+            match tui::run_start_screen(&search_cache)? {
+                tui::StartChoice::Open(q) | tui::StartChoice::Search(q) => q,
+                tui::StartChoice::Refresh(q) => {
+                    search_cache.remove(&q);
+                    q
+                }
+                tui::StartChoice::Quit => return Ok(()),
+            }
+        }
         None => {
-            println!("Website Searcher (interactive)\n");
+            println!("{}\n", t!("interactive-banner"));
 
             // Show recent searches if any
             if !search_cache.is_empty() {
-                println!("Recent searches:");
+                println!("{}", t!("recent-searches"));
                 for (i, entry) in search_cache.entries_newest_first().enumerate().take(5) {
                     println!(
-                        "  {}. {} ({} results)",
-                        i + 1,
-                        entry.query,
-                        entry.results.len()
+                        "  {}",
+                        t!(
+                            "recent-search-entry",
+                            index = i + 1,
+                            query = entry.query.as_str(),
+                            count = entry.results.len()
+                        )
                     );
                 }
                 println!();
@@ -271,22 +755,24 @@ async fn main() -> Result<()> {
 
             // Prefer TUI prompt when attached to a TTY; fall back to stdin prompt otherwise
             if std::io::stdin().is_terminal() && std::io::stdout().is_terminal() {
-                let ans = inquire::Text::new("Search phrase:")
-                    .with_placeholder("e.g., elden ring")
+                let prompt = t!("prompt-search-phrase");
+                let placeholder = t!("prompt-search-placeholder");
+                let ans = inquire::Text::new(&prompt)
+                    .with_placeholder(&placeholder)
                     .prompt();
                 match ans {
                     Ok(s) if !s.trim().is_empty() => s.trim().to_string(),
-                    _ => anyhow::bail!("empty search phrase"),
+                    _ => anyhow::bail!(t!("error-empty-search")),
                 }
             } else {
                 use std::io::{self, Write};
-                print!("Search phrase: ");
+                print!("{} ", t!("prompt-search-phrase"));
                 let _ = io::stdout().flush();
                 let mut line = String::new();
                 io::stdin().read_line(&mut line)?;
                 let s = line.trim().to_string();
                 if s.is_empty() {
-                    anyhow::bail!("empty search phrase");
+                    anyhow::bail!(t!("error-empty-search"));
                 }
                 s
             }
@@ -295,14 +781,11 @@ async fn main() -> Result<()> {
     // Parse for advanced operators (site:, -exclude, "phrase", regex:) and multi-query (|)
     let multi_query = MultiQuery::parse(&query_value);
     // For cache key and backward compat, use first segment's normalized terms
-    let normalized = if let Some(first) = multi_query.first() {
-        first.get_search_terms()
-    } else {
-        normalize_query(&query_value)
-    };
+    let normalized = search::cache_key(&query_value, &multi_query);
 
-    // Check cache first (unless disabled)
+    // Check cache first (unless disabled, or diffing against it)
     if !cli.no_cache
+        && !cli.diff_cache
         && let Some(cached) = search_cache.get(&normalized)
     {
         if cli.debug {
@@ -312,8 +795,12 @@ async fn main() -> Result<()> {
                 cached.results.len()
             );
         }
+        if cli.timings {
+            eprintln!("{}", t!("timings-cached"));
+        }
         // Use cached results
-        let combined = cached.results.clone();
+        let mut combined = cached.results.clone();
+        search::apply_ignore_and_notes(&mut combined, &cli.hide_status);
         let out_format = if cli.query.is_none() {
             OutputFormat::Table
         } else {
@@ -323,51 +810,54 @@ async fn main() -> Result<()> {
             && std::io::stdin().is_terminal()
             && std::io::stdout().is_terminal();
         if interactive_tui && matches!(out_format, OutputFormat::Table) {
-            run_live_tui(&combined)?;
+            sinks::deliver(None, !cli.no_sinks, &query_value, &combined).await;
+            let session = tui::SearchSession {
+                query: query_value.clone(),
+                sites: cli_selected_sites(&cli)?,
+                opts: bulk_search_options(&cli),
+                rate_limiter: cli_rate_limiter(&cli),
+                cache: (!cli.no_cache).then_some((&mut search_cache, cache_path.as_path())),
+                fetch_details: cli.details,
+                from_cache: true,
+            };
+            tui::run_live_tui(combined, session).await?;
+        } else if cli.stream {
+            stream_printer(&cli, out_format)(&combined);
+            sinks::deliver(None, !cli.no_sinks, &query_value, &combined).await;
         } else {
-            match out_format {
-                OutputFormat::Json => output::print_pretty_json(&combined),
-                OutputFormat::Table => output::print_table_grouped(&combined),
-            }
+            let terminal = terminal_sink(&cli, out_format);
+            sinks::deliver(Some(terminal), !cli.no_sinks, &query_value, &combined).await;
         }
         return Ok(());
     }
 
-    // Resolve CF URL: prefer CLI if non-default; otherwise allow CF_URL env override (for Docker)
-    let mut resolved_cf_url = cli.cf_url.clone();
-    if let (true, Some(env_cf)) = (
-        resolved_cf_url == "http://localhost:8191/v1",
-        std::env::var("CF_URL")
-            .ok()
-            .filter(|s| !s.trim().is_empty()),
-    ) {
-        resolved_cf_url = env_cf;
-    }
+    let resolved_cf_url = resolve_cf_url(&cli.cf_url);
 
     // All site configs loaded once
-    let all_sites = site_configs();
+    let all_sites = active_site_configs()?;
 
     // Interactive site selection only when no --sites provided and interactive mode
     let interactive_selection: Option<Vec<String>> = if cli.sites.is_none() && cli.query.is_none() {
         if std::io::stdin().is_terminal() && std::io::stdout().is_terminal() {
             // First ask if the user wants to search ALL sites (faster flow)
-            match inquire::Confirm::new("Search all sites?")
+            let confirm_prompt = t!("prompt-all-sites");
+            let confirm_help = t!("prompt-all-sites-help");
+            match inquire::Confirm::new(&confirm_prompt)
                 .with_default(true)
-                .with_help_message("Choose 'No' to pick specific sites")
+                .with_help_message(&confirm_help)
                 .prompt()
             {
                 Ok(true) => None,
                 Ok(false) => {
                     let site_names: Vec<&str> = all_sites.iter().map(|s| s.name.as_str()).collect();
                     // Multi-select with all preselected so you can quickly uncheck a few
-                    match inquire::MultiSelect::new(
-                        "Select sites (Space toggles, Enter confirms):",
-                        site_names.clone(),
-                    )
-                    .with_default(&[])
-                    .with_help_message("Use ↑/↓ to navigate, Space to toggle, Enter to confirm")
-                    .with_page_size(12)
-                    .prompt()
+                    let select_prompt = t!("prompt-select-sites");
+                    let select_help = t!("prompt-select-sites-help");
+                    match inquire::MultiSelect::new(&select_prompt, site_names.clone())
+                        .with_default(&[])
+                        .with_help_message(&select_help)
+                        .with_page_size(12)
+                        .prompt()
                     {
                         Ok(selected) => {
                             if selected.is_empty() {
@@ -383,11 +873,11 @@ async fn main() -> Result<()> {
             }
         } else {
             use std::io::{self, Write};
-            println!("\nAvailable sites:");
+            println!("\n{}", t!("available-sites"));
             for (i, s) in all_sites.iter().enumerate() {
                 println!("  {}. {}", i + 1, s.name);
             }
-            print!("\nSelect sites (names or numbers, space-separated). Press Enter for ALL: ");
+            print!("\n{} ", t!("prompt-select-sites-plain"));
             let _ = io::stdout().flush();
             let mut line = String::new();
             io::stdin().read_line(&mut line)?;
@@ -408,23 +898,7 @@ async fn main() -> Result<()> {
     };
 
     let selected_sites = if let Some(sites_csv) = cli.sites.as_deref() {
-        let wanted: Vec<String> = sites_csv
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect();
-        // Apply invert logic: if --invert-sites, select all EXCEPT the listed sites
-        if cli.invert_sites {
-            all_sites
-                .into_iter()
-                .filter(|s| !wanted.iter().any(|w| w.eq_ignore_ascii_case(&s.name)))
-                .collect()
-        } else {
-            all_sites
-                .into_iter()
-                .filter(|s| wanted.iter().any(|w| w.eq_ignore_ascii_case(&s.name)))
-                .collect()
-        }
+        select_sites(all_sites, sites_csv, cli.invert_sites)
     } else if let Some(tokens) = interactive_selection {
         // Map tokens to unique site names by name or 1-based index
         let mut chosen: Vec<&str> = Vec::new();
@@ -449,11 +923,11 @@ async fn main() -> Result<()> {
                     chosen.push(&s.name);
                 }
             } else {
-                eprintln!("[info] ignoring unknown site token: {}", t);
+                eprintln!("{}", t!("info-unknown-site-token", token = t.as_str()));
             }
         }
         if chosen.is_empty() {
-            eprintln!("[info] no valid sites selected; using ALL");
+            eprintln!("{}", t!("info-no-valid-sites"));
             all_sites
         } else {
             // Collect the chosen site names as strings to avoid borrow issues
@@ -469,18 +943,7 @@ async fn main() -> Result<()> {
     };
 
     // Build optional headers (Cookie) for forwarding
-    let cookie_headers: Option<ReqHeaderMap> = if let Some(ref c) = cli.cookie {
-        match HeaderValue::from_str(c) {
-            Ok(v) => {
-                let mut h = ReqHeaderMap::new();
-                h.insert(COOKIE, v);
-                Some(h)
-            }
-            Err(_) => None,
-        }
-    } else {
-        None
-    };
+    let cookie_headers = cookie_header_map(cli.cookie.as_deref());
 
     // Determine if we should use interactive live TUI for search progress
     let use_live_search_tui = cli.query.is_none()
@@ -489,1190 +952,940 @@ async fn main() -> Result<()> {
         && !cli.debug;
 
     // Run search - either with live TUI or standard progress output
-    let combined =
-        if use_live_search_tui {
-            // Interactive mode: use live search TUI with per-site progress
-            let rate_limiter = if !cli.no_rate_limit {
-                Some(Arc::new(tokio::sync::Mutex::new(RateLimiter::new())))
-            } else {
-                None
-            };
+    let rate_limiter = if !cli.no_rate_limit {
+        Some(Arc::new(tokio::sync::Mutex::new(RateLimiter::new())))
+    } else {
+        None
+    };
+    let opts = SearchOptions {
+        limit: cli.per_site_limit,
+        caps: result_caps(&cli),
+        debug: cli.debug,
+        use_cf: search_profile(&cli).use_solver(!cli.no_cf),
+        cf_url: resolved_cf_url.clone(),
+        cookie_headers: cookie_headers.clone(),
+        accounts: site_accounts(&cli),
+        no_playwright: !search_profile(&cli).use_playwright(!cli.no_playwright),
+        show_progress: std::io::stderr().is_terminal() && !cli.debug,
+        site_timeout: site_timeout(&cli),
+        deterministic: cli.deterministic,
+        routes: site_routes(),
+        steam_appids: steam_appids(&cli),
+        retry_failed: retry_failed(&cli),
+        hide_status: cli.hide_status.clone(),
+        profile: search_profile(&cli),
+        budget: daily_budget(&cli),
+        timings: cli.timings.then(|| Arc::new(SearchTimings::new())),
+        stream: None,
+    };
+    // Kept for follow-up searches started from the results browser
+    let session_sites = selected_sites.clone();
+    let out_format = if cli.query.is_none() {
+        OutputFormat::Table
+    } else {
+        cli.format
+    };
+    // Keep TUI only for interactive mode (no query provided). If user explicitly passes
+    // --format table with a query, print classic table output instead of TUI.
+    let interactive_tui =
+        cli.query.is_none() && std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+    // --stream prints sites as they finish, unless the results browser shows
+    // them or --diff-cache prints what changed instead
+    let streaming = cli.stream && !interactive_tui && !cli.diff_cache;
+    let combined = if use_live_search_tui {
+        // Interactive mode: use live search TUI with per-site progress
+        tui::run_live_search_tui(selected_sites, &multi_query, &opts, rate_limiter.clone()).await?
+    } else if streaming {
+        search::run_search_streaming(
+            &build_http_client(),
+            selected_sites,
+            &multi_query,
+            &normalized,
+            &opts,
+            rate_limiter.clone(),
+            stream_printer(&cli, out_format),
+        )
+        .await
+    } else {
+        // Non-interactive mode: use standard search with stderr progress
+        let client = build_http_client();
+        search::run_search(
+            &client,
+            selected_sites,
+            &multi_query,
+            &normalized,
+            &opts,
+            rate_limiter.clone(),
+        )
+        .await
+    };
 
-            run_live_search_tui(
-                selected_sites,
-                &multi_query,
-                cli.limit,
-                cli.debug,
-                cli.no_cf,
-                resolved_cf_url.clone(),
-                cookie_headers.clone(),
-                cli.no_playwright,
-                rate_limiter,
-            )
-            .await?
-        } else {
-            // Non-interactive mode: use standard search with stderr progress
-            let client = build_http_client();
-            let semaphore = Arc::new(Semaphore::new(3));
-            let rate_limiter = if !cli.no_rate_limit {
-                Some(Arc::new(tokio::sync::Mutex::new(RateLimiter::new())))
-            } else {
-                None
-            };
-            let mut tasks = FuturesUnordered::new();
-
-            // Show search progress indicator if interactive
-            let show_progress = std::io::stderr().is_terminal() && !cli.debug;
-            let site_names: Vec<String> = selected_sites.iter().map(|s| s.name.clone()).collect();
-            let total_sites = site_names.len();
-            if show_progress {
-                eprintln!(
-                    "⏳ Searching {} sites: {}",
-                    total_sites,
-                    site_names.join(", ")
-                );
+    let post_processing = std::time::Instant::now();
+    let mut combined = search::post_process(combined, &multi_query, &opts);
+    search::add_steam_appids(&build_http_client(), &opts, &mut combined).await;
+    search::verify_links(&build_http_client(), &opts, &mut combined).await;
+    opts.time("", SearchPhase::PostProcess, post_processing);
+
+    // Snapshot the previous run before the cache entry is replaced
+    let previous = cli.diff_cache.then(|| {
+        search_cache
+            .peek(&normalized)
+            .map(|e| e.results.clone())
+            .unwrap_or_else(|| {
+                eprintln!("{}", t!("diff-no-cached", query = normalized.as_str()));
+                Vec::new()
+            })
+    });
+
+    // Save to cache (unless disabled)
+    if !cli.no_cache && !combined.is_empty() {
+        search_cache.add(normalized.clone(), combined.clone());
+        if let Err(e) = search_cache.save_to_file_sync(&cache_path) {
+            if cli.debug {
+                eprintln!("[debug] Failed to save cache: {}", e);
             }
+        } else {
+            index_results(&search_cache, cli.debug);
+        }
+        if cli.debug {
+            eprintln!(
+                "[debug] Cached {} results for \"{}\"",
+                combined.len(),
+                normalized
+            );
+        }
+    }
 
-            for site in selected_sites {
-                let permit = semaphore.clone().acquire_owned().await.unwrap();
-                let client = client.clone();
-                // For multi-query: get site-specific search terms
-                let site_queries = multi_query.get_search_terms_for_site(&site.name);
-                let query = if site_queries.is_empty() {
-                    normalized.clone()
-                } else {
-                    site_queries.join(" ")
-                };
-                let debug = cli.debug;
-                let use_cf = !cli.no_cf;
-                let cf_url = resolved_cf_url.clone();
-                let cookie_headers = cookie_headers.clone();
-                let rate_limiter = rate_limiter.clone(); // This is now Option<Arc<Mutex<RateLimiter>>>
-
-                let no_playwright = cli.no_playwright;
-                let site_name = site.name.clone();
-                tasks.push(tokio::spawn(async move {
-            let _permit = permit; // hold until task end
-            let base_url = match site.search_kind {
-                SearchKind::ListingPage => site
-                    .listing_path
-                    .clone()
-                    .unwrap_or(site.base_url.clone())
-                    .to_string(),
-                SearchKind::PhpBBSearch => build_search_url(&site, &query), // Uses search.php URL
-                _ => build_search_url(&site, &query),
-            };
-            // Build page URLs: for most sites, just one URL. csrin uses PhpBBSearch URL directly.
-            let page_urls: Vec<String> = vec![base_url.clone()];
+    if let Some(previous) = previous {
+        print_diff(cli.format, &diff_results(&previous, &combined))?;
+        print_timings(&opts);
+        return Ok(());
+    }
+    let annotating = std::time::Instant::now();
+    search::apply_ignore_and_notes(&mut combined, &cli.hide_status);
+    opts.time("", SearchPhase::PostProcess, annotating);
+    let timings = opts.timings.as_ref().map(|t| t.breakdown());
 
-            let mut results: Vec<SearchResult> = Vec::new();
-            // If requested, try Playwright to load dynamic results (skip when solver is explicitly configured/local)
-            let cf_local = cf_url.contains("127.0.0.1") || cf_url.contains("localhost");
-            let non_default_cf = cf_url != "http://localhost:8191/v1";
-            let prefer_solver = use_cf && (cf_local || non_default_cf);
-            if site.name.eq_ignore_ascii_case("csrin") && !no_playwright && !prefer_solver {
-                let cookie_val = cookie_headers
-                    .as_ref()
-                    .and_then(|h| h.get(COOKIE))
-                    .and_then(|v| v.to_str().ok())
-                    .map(|s| s.to_string());
-                if let Some(html) = fetch_csrin_playwright_html(&query, cookie_val).await {
-                    if debug {
-                        eprintln!(
-                            "[debug] site={} via Playwright html_len={}",
-                            site.name,
-                            html.len()
-                        );
-                        let _ = tokio::fs::create_dir_all("debug").await;
-                        let _ = tokio::fs::write("debug/csrin_playwright.html", &html).await;
-                    }
-                    results = parse_results(&site, &html, &query);
-                }
-            }
-            if results.is_empty() {
-                for url in page_urls {
-                    // Solver gating:
-                    // - Default: use solver when the site requires Cloudflare
-                    // - csrin: allow solver when explicitly enabled via env, or when a non-default/local CF URL is provided (for tests)
-                    let allow_env = std::env::var("ALLOW_CSRIN_SOLVER")
-                        .ok()
-                        .map(|v| v == "1")
-                        .unwrap_or(false);
-                    let cf_local = cf_url.contains("127.0.0.1") || cf_url.contains("localhost");
-                    let non_default_cf = cf_url != "http://localhost:8191/v1";
-                    let csrin_solver_allowed = site.name.eq_ignore_ascii_case("csrin")
-                        && (allow_env || cf_local || non_default_cf);
-                    let use_solver_for_this =
-                        use_cf && (site.requires_cloudflare || csrin_solver_allowed);
-                    let html = if use_solver_for_this {
-                        if debug {
-                            eprintln!("[debug] site={} using FlareSolverr {}", site.name, cf_url);
-                        }
-                        (if cookie_headers.is_some() {
-                            cf::fetch_via_solver_with_headers(
-                                &client,
-                                &url,
-                                &cf_url,
-                                cookie_headers.clone(),
-                            )
-                            .await
-                        } else {
-                            fetch_via_solver(&client, &url, &cf_url).await
-                        })
-                        .unwrap_or_default()
-                    } else {
-                        let rate_limiter_ref = if let Some(ref rl) = rate_limiter {
-                            Some(&mut *rl.lock().await)
-                        } else {
-                            None
-                        };
+    if interactive_tui && matches!(out_format, OutputFormat::Table) {
+        sinks::deliver(None, !cli.no_sinks, &query_value, &combined).await;
+        let session = tui::SearchSession {
+            query: query_value.clone(),
+            sites: session_sites,
+            opts: SearchOptions {
+                show_progress: false,
+                timings: None,
+                stream: None,
+                ..opts
+            },
+            rate_limiter,
+            cache: (!cli.no_cache).then_some((&mut search_cache, cache_path.as_path())),
+            fetch_details: cli.details,
+            from_cache: false,
+        };
+        tui::run_live_tui(combined, session).await?;
+    } else if streaming {
+        // Already printed site by site
+        sinks::deliver(None, !cli.no_sinks, &query_value, &combined).await;
+    } else {
+        let terminal = terminal_sink(&cli, out_format);
+        sinks::deliver(Some(terminal), !cli.no_sinks, &query_value, &combined).await;
+    }
+    if let Some(timings) = timings {
+        print_breakdown(&timings);
+    }
+    Ok(())
+}
 
-                        (if cookie_headers.is_some() {
-                            fetcher::fetch_with_retry_headers(
-                                &client,
-                                &url,
-                                cookie_headers.clone(),
-                                rate_limiter_ref,
-                                Some(site.name.as_str()),
-                            )
-                            .await
-                        } else {
-                            fetch_with_retry(
-                                &client,
-                                &url,
-                                rate_limiter_ref,
-                                Some(site.name.as_str()),
-                            )
-                            .await
-                        })
-                        .unwrap_or_default()
-                    };
-                    if debug {
-                        eprintln!(
-                            "[debug] site={} url={} html_len={}",
-                            site.name,
-                            url,
-                            html.len()
-                        );
-                    }
-                    let mut page_results = parse_results(&site, &html, &query);
-                    // gog-games fallback: request AJAX JSON/fragment when DOM parse is empty
-                    if page_results.is_empty() && site.name.eq_ignore_ascii_case("gog-games") {
-                        let rate_limiter_ref = if let Some(ref rl) = rate_limiter {
-                            Some(&mut *rl.lock().await)
-                        } else {
-                            None
-                        };
+/// --timings: the search's timing breakdown so far, on stderr
+fn print_timings(opts: &SearchOptions) {
+    if let Some(timings) = &opts.timings {
+        print_breakdown(&timings.breakdown());
+    }
+}
 
-                        if let Some(r) = fetch_gog_games_ajax_json(
-                            &client,
-                            &site,
-                            &query,
-                            use_cf,
-                            &cf_url,
-                            cookie_headers.clone(),
-                            debug,
-                            rate_limiter_ref,
-                        )
-                        .await
-                            && !r.is_empty()
-                        {
-                            page_results = r;
-                        }
-                    }
-                    // csrin fallback: parse Atom feed when page body is minimal or selectors miss
-                    if page_results.is_empty() && site.name.eq_ignore_ascii_case("csrin") {
-                        let rate_limiter_ref = if let Some(ref rl) = rate_limiter {
-                            Some(&mut *rl.lock().await)
-                        } else {
-                            None
-                        };
+fn print_breakdown(timings: &TimingBreakdown) {
+    let secs = |ms: u64| format!("{:.2}", ms as f64 / 1000.0);
+    eprintln!("{}", t!("timings-header", total = secs(timings.total_ms)));
+    for phase in SearchPhase::ALL {
+        eprintln!(
+            "  {}",
+            t!(
+                "timings-phase",
+                phase = phase.as_str(),
+                secs = secs(timings.phase(phase))
+            )
+        );
+    }
+    for (site, phases) in &timings.sites {
+        let spent: Vec<String> = phases
+            .iter()
+            .filter(|(_, ms)| **ms > 0)
+            .map(|(phase, ms)| format!("{} {}s", phase.as_str(), secs(*ms)))
+            .collect();
+        eprintln!(
+            "  {}",
+            t!(
+                "timings-site",
+                site = site.as_str(),
+                phases = spent.join(", ")
+            )
+        );
+    }
+    for advice in timings.advice() {
+        let line = match advice {
+            TimingAdvice::Concurrency => t!("timings-advice-concurrency"),
+            TimingAdvice::Solver => t!("timings-advice-solver"),
+            TimingAdvice::SlowSite(site) => t!("timings-advice-slow-site", site = site.as_str()),
+        };
+        eprintln!("{line}");
+    }
+}
 
-                        if let Some(r) = fetch_csrin_feed(
-                            &client,
-                            &site,
-                            &query,
-                            use_cf,
-                            &cf_url,
-                            cookie_headers.clone(),
-                            debug,
-                            rate_limiter_ref,
-                        )
-                        .await
-                            && !r.is_empty()
-                        {
-                            page_results = r;
-                        }
-                    }
-                    // Extra filtering for gog-games to avoid unrelated pages/cards
-                    if site.name.eq_ignore_ascii_case("gog-games") {
-                        filter_results_by_query_strict(&mut page_results, &query);
-                    }
-                    results.extend(page_results);
-                    if results.len() >= 5000 {
-                        // safety cap
-                        break;
-                    }
-                }
+/// `annotate` subcommand: edit or list the notes in the data directory
+fn annotate(action: &AnnotateAction, format: OutputFormat) -> Result<()> {
+    let path = paths::notes_file();
+    let mut notes = Notes::load(&path);
+    match action {
+        AnnotateAction::Set { url, status, note } => {
+            if status.is_none() && note.is_none() {
+                anyhow::bail!(t!("note-needs-change"));
             }
-            // csrin: Automatic Playwright fallback if listing/feed produced nothing and user didn't explicitly request it
-            if site.name.eq_ignore_ascii_case("csrin") && results.is_empty() && !no_playwright {
-                let cookie_val = cookie_headers
-                    .as_ref()
-                    .and_then(|h| h.get(COOKIE))
-                    .and_then(|v| v.to_str().ok())
-                    .map(|s| s.to_string());
-                if let Some(html) = fetch_csrin_playwright_html(&query, cookie_val).await {
-                    if debug {
-                        eprintln!(
-                            "[debug] site={} via Playwright (auto) html_len={}",
-                            site.name,
-                            html.len()
-                        );
-                    }
-                    let rs = parse_results(&site, &html, &query);
-                    if !rs.is_empty() {
-                        results = rs;
-                    }
-                }
+            // Only what was given changes
+            let current = notes.get(url).cloned().unwrap_or_default();
+            let saved = notes.set(
+                url,
+                status.or(current.status),
+                note.clone().or(current.text),
+            );
+            notes.save(&path)?;
+            match saved {
+                Some(saved) => println!(
+                    "{}",
+                    t!("note-saved", note = saved.label(), url = url.as_str())
+                ),
+                None => println!("{}", t!("note-cleared", url = url.as_str())),
             }
-            if debug {
-                eprintln!(
-                    "[debug] site={} results={} (pre-truncate)",
-                    site.name,
-                    results.len()
-                );
-                if results.is_empty() {
-                    // compute debug stats in a tight scope so non-Send Html is dropped before awaits
-                    let (anchors_total, matched_samples, article_count, entry_title_count) = {
-                        let mut anchors_total = 0usize;
-                        let mut matched_samples: Vec<(String, String)> = Vec::new();
-                        let mut article_count = 0usize;
-                        let mut entry_title_count = 0usize;
-                        // For pagination, doc stats should be computed on the last page html if available
-                        let doc = Html::parse_document("");
-                        if let Ok(a_sel) = Selector::parse("a[href]") {
-                            anchors_total = doc.select(&a_sel).count();
-                            let ql = query.to_lowercase();
-                            for a in doc.select(&a_sel) {
-                                let text = a.text().collect::<String>();
-                                let href = a.value().attr("href").unwrap_or("");
-                                if text.to_lowercase().contains(&ql) {
-                                    matched_samples.push((text, href.to_string()));
-                                    if matched_samples.len() >= 5 {
-                                        break;
-                                    }
-                                }
-                            }
-                        }
-                        if let Ok(article_sel) = Selector::parse("article") {
-                            article_count = doc.select(&article_sel).count();
-                        }
-                        if let Ok(h2_sel) =
-                            Selector::parse("h2.entry-title, h1.entry-title, .entry-title")
-                        {
-                            entry_title_count = doc.select(&h2_sel).count();
-                        }
-                        (
-                            anchors_total,
-                            matched_samples,
-                            article_count,
-                            entry_title_count,
-                        )
-                    };
-
-                    eprintln!(
-                        "[debug] site={} anchors_total={} anchors_with_query_sample={}",
-                        site.name,
-                        anchors_total,
-                        matched_samples.len()
-                    );
-                    for (i, (t, h)) in matched_samples.into_iter().enumerate() {
-                        let t_short = t.trim().chars().take(80).collect::<String>();
-                        let h_short = h.chars().take(120).collect::<String>();
-                        eprintln!("[debug]  [{}] text='{}' href='{}'", i, t_short, h_short);
-                    }
-                    eprintln!("[debug] site={} article_count={}", site.name, article_count);
-                    eprintln!(
-                        "[debug] site={} entry_title_nodes={}",
-                        site.name, entry_title_count
-                    );
-
-                    // write html to debug file
-                    let _ = tokio::fs::create_dir_all("debug").await;
-                    let path = format!("debug/{}_sample.html", site.name);
-                    if let Err(e) = tokio::fs::write(&path, "").await {
-                        eprintln!("[debug] failed to write {}: {}", path, e);
-                    } else {
-                        eprintln!("[debug] wrote {}", path);
-                    }
-                }
+        }
+        AnnotateAction::Clear { url } => {
+            if notes.remove(url) {
+                notes.save(&path)?;
+                println!("{}", t!("note-cleared", url = url.as_str()));
+            } else {
+                println!("{}", t!("note-not-found", url = url.as_str()));
             }
-            if matches!(
-                site.search_kind,
-                SearchKind::FrontPage | SearchKind::ListingPage | SearchKind::PhpBBSearch
-            ) {
-                // csrin: keep only topic pages, and avoid URL-based query matches (phpBB adds
-                // hilit=<query> to every result link). Only keep titles that include the query.
-                let q_lower = query.to_lowercase();
-                if site.name.eq_ignore_ascii_case("csrin") {
-                    results.retain(|r| r.url.contains("viewtopic.php"));
-                    results.retain(|r| r.title.to_lowercase().contains(&q_lower));
-                } else {
-                    let q_dash = q_lower.replace(' ', "-");
-                    let q_plus = q_lower.replace(' ', "+");
-                    let q_enc = q_lower.replace(' ', "%20");
-                    let q_strip = q_lower.replace(' ', "");
-                    results.retain(|r| {
-                        let tl = r.title.to_lowercase();
-                        let ul = r.url.to_lowercase();
-                        tl.contains(&q_lower)
-                            || ul.contains(&q_lower)
-                            || ul.contains(&q_dash)
-                            || ul.contains(&q_plus)
-                            || ul.contains(&q_enc)
-                            || ul.contains(&q_strip)
-                    });
+        }
+        AnnotateAction::List { status } => {
+            let listed: Vec<_> = notes
+                .iter()
+                .filter(|(_, note)| status.is_none() || note.status == *status)
+                .collect();
+            match format {
+                OutputFormat::Json => {
+                    let entries: Vec<Value> = listed
+                        .iter()
+                        .map(|(url, note)| {
+                            let mut entry = serde_json::to_value(note).unwrap_or_default();
+                            entry["url"] = Value::from(*url);
+                            entry
+                        })
+                        .collect();
+                    println!("{}", serde_json::to_string_pretty(&entries)?);
+                }
+                OutputFormat::Table if listed.is_empty() => println!("{}", t!("notes-empty")),
+                OutputFormat::Table => {
+                    for (url, note) in listed {
+                        println!("{url} {}", note.label());
+                    }
                 }
             }
-            // Normalize titles for nicer output
-            for r in &mut results {
-                r.title = normalize_title(site.name.as_str(), &r.title);
+        }
+    }
+    Ok(())
+}
+
+/// `ignore` subcommand: edit or list the ignore list in the data directory
+fn ignore(action: &IgnoreAction, format: OutputFormat) -> Result<()> {
+    let path = paths::ignore_file();
+    let mut list = IgnoreList::load(&path);
+    match action {
+        IgnoreAction::Add { kind, value } => {
+            let rule = IgnoreRule::new(*kind, value)?;
+            if list.add(rule.clone()) {
+                list.save(&path)?;
+                println!("{}", t!("ignore-added", rule = rule.to_string()));
+            } else {
+                println!("{}", t!("ignore-exists", rule = rule.to_string()));
             }
-            if !results.is_empty() {
-                results.truncate(cli.limit);
+        }
+        IgnoreAction::Remove { kind, value } => {
+            // Normalized like `add`, so the same spelling finds the rule
+            let rule = IgnoreRule::new(*kind, value).unwrap_or_else(|_| IgnoreRule {
+                kind: *kind,
+                value: value.trim().to_string(),
+            });
+            if list.remove(&rule) {
+                list.save(&path)?;
+                println!("{}", t!("ignore-removed", rule = rule.to_string()));
+            } else {
+                println!("{}", t!("ignore-not-found", rule = rule.to_string()));
             }
-            // Return site name with results for progress tracking
-            (site_name, results)
-        }));
+        }
+        IgnoreAction::List => match format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(list.rules())?),
+            OutputFormat::Table if list.is_empty() => println!("{}", t!("ignore-empty")),
+            OutputFormat::Table => {
+                for rule in list.rules() {
+                    println!("{rule}");
+                }
             }
+        },
+    }
+    Ok(())
+}
 
-            let mut combined: Vec<SearchResult> = Vec::new();
-            let mut sites_completed = 0usize;
-            while let Some(joined) = tasks.next().await {
-                if let Ok((site_name, mut site_results)) = joined {
-                    sites_completed += 1;
-                    if show_progress {
-                        let emoji = if site_results.is_empty() {
-                            "⚪"
-                        } else {
-                            "✅"
-                        };
-                        eprint!(
-                            "\r{} {}/{} sites | {} {} results",
-                            emoji,
-                            sites_completed,
-                            total_sites,
-                            site_name,
-                            site_results.len()
-                        );
-                        // Pad with spaces to clear previous longer messages
-                        eprint!("                    ");
-                        use std::io::Write;
-                        let _ = std::io::stderr().flush();
-                    }
-                    combined.append(&mut site_results);
+/// `patterns` subcommand: list or forget the learned link patterns in the
+/// state directory
+fn patterns(action: &PatternsAction, format: OutputFormat) -> Result<()> {
+    let path = paths::learned_patterns_file();
+    let mut learned = LearnedPatterns::load(&path);
+    match action {
+        PatternsAction::List => match format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&learned)?),
+            OutputFormat::Table if learned.is_empty() => println!("{}", t!("patterns-empty")),
+            OutputFormat::Table => {
+                for (site, entry) in learned.iter() {
+                    println!(
+                        "{}",
+                        t!(
+                            "patterns-entry",
+                            site = site,
+                            pattern = entry.pattern.to_string(),
+                            hits = entry.hits
+                        )
+                    );
                 }
             }
-            if show_progress {
-                eprintln!(); // Final newline after progress
+        },
+        PatternsAction::Clear { site: Some(site) } => {
+            if learned.forget(site) {
+                learned.save(&path)?;
+                println!("{}", t!("patterns-cleared-site", site = site.as_str()));
+            } else {
+                println!("{}", t!("patterns-not-found", site = site.as_str()));
             }
-            combined
-        };
-
-    // Apply advanced query filtering (site:, -exclude, "phrase", regex: operators)
-    // For multi-query, filter per-site based on applicable segments
-    let mut combined = if multi_query.is_single() {
-        // Single query - use global filtering (backward compatible)
-        if let Some(first) = multi_query.first() {
-            filter_results(combined, first)
-        } else {
-            combined
         }
-    } else {
-        // Multi-query - filter per-site
-        let mut filtered = Vec::new();
-        let mut by_site: std::collections::HashMap<String, Vec<SearchResult>> =
-            std::collections::HashMap::new();
-        for r in combined {
-            by_site.entry(r.site.clone()).or_default().push(r);
-        }
-        for (site, results) in by_site {
-            let site_filtered = multi_query.filter_results_for_site(results, &site);
-            filtered.extend(site_filtered);
+        PatternsAction::Clear { site: None } => {
+            let count = learned.clear();
+            learned.save(&path)?;
+            println!("{}", t!("patterns-cleared", count = count));
         }
-        filtered
-    };
-
-    // First: remove exact URL duplicates within each site
-    combined.sort_by(|a, b| a.site.cmp(&b.site).then_with(|| a.url.cmp(&b.url)));
-    combined.dedup_by(|a, b| a.site == b.site && a.url == b.url);
-
-    // Then: smart cross-site deduplication using title similarity
-    let mut combined = deduplicate_results(combined);
+    }
+    Ok(())
+}
 
-    // Sort by site then title for final output
-    combined.sort_by(|a, b| a.site.cmp(&b.site).then_with(|| a.title.cmp(&b.title)));
+/// Turn `template run` into the search its template describes: the filled
+/// query, and the template's sites, limit and format in place of the flags'
+fn apply_template(cli: &mut Cli, name: &str, args: &[String]) -> Result<()> {
+    let settings = Settings::load(&default_settings_path());
+    let Some(template) = settings.templates.get(name) else {
+        anyhow::bail!(t!("template-unknown", name = name));
+    };
+    let query = template::parse_values(args).and_then(|values| template.fill(&values));
+    cli.query = Some(query.map_err(|e| {
+        anyhow::anyhow!(t!("template-invalid", name = name, error = e.to_string()))
+    })?);
+    if let Some(format) = &template.format {
+        cli.format = OutputFormat::from_str(format, true).map_err(|_| {
+            anyhow::anyhow!(t!(
+                "template-bad-format",
+                name = name,
+                format = format.as_str()
+            ))
+        })?;
+    }
+    if !template.sites.is_empty() {
+        cli.sites = Some(template.sites.join(","));
+    }
+    if let Some(limit) = template.limit {
+        cli.per_site_limit = limit;
+    }
+    for status in &template.hide_status {
+        if !cli.hide_status.contains(status) {
+            cli.hide_status.push(*status);
+        }
+    }
+    Ok(())
+}
 
-    // Apply overall cutoff if specified (0 means no cutoff)
-    if cli.cutoff > 0 && combined.len() > cli.cutoff {
-        combined.truncate(cli.cutoff);
+/// `template list` subcommand
+fn templates(format: OutputFormat) -> Result<()> {
+    let templates = Settings::load(&default_settings_path()).templates;
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&templates)?),
+        OutputFormat::Table if templates.is_empty() => println!("{}", t!("templates-empty")),
+        OutputFormat::Table => {
+            for (name, template) in &templates {
+                println!(
+                    "{}",
+                    t!(
+                        "templates-entry",
+                        name = name.as_str(),
+                        query = template.query.as_str(),
+                        placeholders = template
+                            .placeholders()
+                            .iter()
+                            .map(|p| format!("--{p}"))
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    )
+                );
+            }
+        }
     }
+    Ok(())
+}
 
-    // Save to cache (unless disabled)
-    if !cli.no_cache && !combined.is_empty() {
-        search_cache.add(normalized.clone(), combined.clone());
-        if let Err(e) = search_cache.save_to_file_sync(&cache_path) {
-            if cli.debug {
-                eprintln!("[debug] Failed to save cache: {}", e);
+/// `secrets` subcommand: store, list or delete the secrets config values refer
+/// to as `secret:<name>`
+fn secrets(action: &SecretsAction, format: OutputFormat) -> Result<()> {
+    let store = SecretStore::default();
+    match action {
+        SecretsAction::Set { name } => {
+            let value = if std::io::stdin().is_terminal() {
+                inquire::Password::new(&t!("secrets-prompt", name = name.as_str()))
+                    .without_confirmation()
+                    .prompt()?
+            } else {
+                let mut value = String::new();
+                std::io::Read::read_to_string(&mut std::io::stdin(), &mut value)?;
+                value.trim_end_matches(['\r', '\n']).to_string()
+            };
+            if value.is_empty() {
+                anyhow::bail!(t!("secrets-empty-value", name = name.as_str()));
             }
-        } else if cli.debug {
-            eprintln!(
-                "[debug] Cached {} results for \"{}\"",
-                combined.len(),
-                normalized
+            let backend = store.set(name, &value)?;
+            println!(
+                "{}",
+                t!(
+                    "secrets-stored",
+                    name = name.as_str(),
+                    backend = backend_label(backend)
+                )
             );
         }
+        SecretsAction::List => {
+            let stored = store.list()?;
+            match format {
+                OutputFormat::Json => {
+                    let rows: Vec<Value> = stored
+                        .iter()
+                        .map(
+                            |(name, backend)| serde_json::json!({"name": name, "backend": backend}),
+                        )
+                        .collect();
+                    println!("{}", serde_json::to_string_pretty(&rows)?);
+                }
+                OutputFormat::Table if stored.is_empty() => println!("{}", t!("secrets-empty")),
+                OutputFormat::Table => {
+                    for (name, backend) in &stored {
+                        println!(
+                            "{}",
+                            t!(
+                                "secrets-entry",
+                                name = name.as_str(),
+                                backend = backend_label(*backend)
+                            )
+                        );
+                    }
+                }
+            }
+        }
+        SecretsAction::Remove { name } => {
+            if store.remove(name)? {
+                println!("{}", t!("secrets-removed", name = name.as_str()));
+            } else {
+                println!("{}", t!("secrets-not-found", name = name.as_str()));
+            }
+        }
     }
+    Ok(())
+}
 
-    let out_format = if cli.query.is_none() {
-        OutputFormat::Table
-    } else {
-        cli.format
-    };
-    // Keep TUI only for interactive mode (no query provided). If user explicitly passes
-    // --format table with a query, print classic table output instead of TUI.
-    let interactive_tui =
-        cli.query.is_none() && std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
-    if interactive_tui && matches!(out_format, OutputFormat::Table) {
-        run_live_tui(&combined)?;
-    } else {
-        match out_format {
-            OutputFormat::Json => output::print_pretty_json(&combined),
-            OutputFormat::Table => output::print_table_grouped(&combined),
+fn backend_label(backend: Backend) -> String {
+    match backend {
+        Backend::Keyring => t!("secrets-backend-keyring"),
+        Backend::File => t!("secrets-backend-file"),
+    }
+}
+
+/// `state` subcommand: export the files worth moving to a zip, or import one
+fn state(action: &StateAction, format: OutputFormat) -> Result<()> {
+    match action {
+        StateAction::Export { file } => {
+            let files = state_archive::export(file)?;
+            match format {
+                OutputFormat::Json => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "file": file,
+                        "files": files,
+                    }))?
+                ),
+                OutputFormat::Table => {
+                    println!(
+                        "{}",
+                        t!(
+                            "state-exported",
+                            count = files.len(),
+                            file = file.display().to_string()
+                        )
+                    );
+                    for name in files {
+                        println!("  {name}");
+                    }
+                }
+            }
+        }
+        StateAction::Import { file, mode } => {
+            let imported = state_archive::import(file, *mode)?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&imported)?),
+                OutputFormat::Table if imported.is_empty() => {
+                    println!("{}", t!("state-import-nothing"))
+                }
+                OutputFormat::Table => {
+                    for done in imported {
+                        let change = match done.change {
+                            Change::Added => t!("state-change-added"),
+                            Change::Merged => t!("state-change-merged"),
+                            Change::Replaced => t!("state-change-replaced"),
+                            Change::Removed => t!("state-change-removed"),
+                        };
+                        println!("{}: {change}", done.file);
+                    }
+                }
+            }
         }
     }
     Ok(())
 }
 
-fn run_live_tui(results: &[SearchResult]) -> anyhow::Result<()> {
-    // Setup terminal
-    let mut stdout = stdout();
-    execute!(stdout, terminal::EnterAlternateScreen)?;
-    terminal::enable_raw_mode()?;
-    let backend = ratatui::backend::CrosstermBackend::new(stdout);
-    let mut terminal = ratatui::Terminal::new(backend)?;
-
-    // Prepare grouped entries and state (group by site, render boxed groups)
-    use std::collections::BTreeMap;
-    let mut by_site: BTreeMap<&str, Vec<&SearchResult>> = BTreeMap::new();
-    for r in results {
-        by_site.entry(&r.site).or_default().push(r);
-    }
-    // Meta for navigation and opening: one None for box top, Some(url) per item line, one None for box bottom
-    let mut entry_urls: Vec<Option<String>> = Vec::new();
-    // Keep ordered groups for rendering
-    let groups: Vec<(String, Vec<(String, String)>)> = by_site
-        .into_iter()
-        .map(|(site, items)| {
-            let list: Vec<(String, String)> = items
-                .into_iter()
-                .map(|r| (r.title.clone(), r.url.clone()))
+/// `stats` subcommand: each site's p95 search time, its streak of empty
+/// queries that other sites matched, whether its search ignores the query and
+/// its requests today, from the state directory
+fn stats(sites: &[SiteConfig], format: OutputFormat) -> Result<()> {
+    let timings = monitoring::SiteTimings::load(&paths::site_timings_file());
+    let health = monitoring::SiteHealth::load(&paths::site_health_file());
+    let broken = health.likely_broken();
+    let broken_site = |name: &str| broken.iter().find(|b| b.site.eq_ignore_ascii_case(name));
+    let capabilities = QueryCapabilities::load(&paths::query_capability_file());
+    let ignores_query =
+        |name: &str| capabilities.support(name) == Some(capability::QuerySupport::Ignored);
+    let budget = DailyBudget::new(
+        Settings::load(&default_settings_path()).search.daily_budget,
+        RequestLog::load(&paths::request_budget_file()),
+    );
+    match format {
+        OutputFormat::Json => {
+            let rows: Vec<Value> = sites
+                .iter()
+                .map(|site| {
+                    serde_json::json!({
+                        "site": site.name,
+                        "p95_ms": timings.p95(&site.name).map(|d| d.as_millis() as u64),
+                        "empty_streak": health.streak(&site.name),
+                        "likely_broken": broken_site(&site.name).is_some(),
+                        "ignores_query": ignores_query(&site.name),
+                        "requests_today": budget.used(&site.name),
+                        "daily_budget": budget.limit(&site.name),
+                    })
+                })
                 .collect();
-            (site.to_string(), list)
-        })
-        .collect();
-    for (_site, items) in &groups {
-        entry_urls.push(None); // top border
-        for (_t, u) in items {
-            entry_urls.push(Some(u.clone()));
+            println!("{}", serde_json::to_string_pretty(&rows)?);
         }
-        entry_urls.push(None); // bottom border
-    }
-    let mut state = ListState::default();
-    // Select first selectable row
-    let first_sel = entry_urls.iter().position(|u| u.is_some()).unwrap_or(0);
-    if !entry_urls.is_empty() {
-        state.select(Some(first_sel));
-    }
-
-    // Drain any pending keystrokes (e.g., the Enter used to run the command)
-    while event::poll(std::time::Duration::from_millis(0))? {
-        let _ = event::read()?;
-    }
-
-    let mut should_quit = false;
-    while !should_quit {
-        terminal.draw(|f| {
-            let area = f.area();
-            let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([Constraint::Min(1), Constraint::Length(1)])
-                .split(area);
-
-            // Build rendered lines based on available width to draw ASCII boxes per site
-            let width = chunks[0].width.max(2) as usize;
-            let inner = width.saturating_sub(2);
-            let mut rendered: Vec<String> = Vec::with_capacity(entry_urls.len());
-            for (site, items) in &groups {
-                // Top border with centered-ish site name
-                let title = format!(" {} ", site);
-                let mut top = String::new();
-                top.push('┌');
-                if title.len() + 2 <= inner {
-                    // pad with dashes before and after title
-                    let left = 1usize;
-                    let right = inner.saturating_sub(left + title.len());
-                    top.push_str(&"─".repeat(left));
-                    top.push_str(&title);
-                    top.push_str(&"─".repeat(right));
-                } else {
-                    top.push_str(&"─".repeat(inner));
+        OutputFormat::Table => {
+            for site in sites {
+                let p95 = timings
+                    .p95(&site.name)
+                    .map_or("–".to_string(), |d| format!("{:.1}s", d.as_secs_f64()));
+                println!(
+                    "{}",
+                    t!(
+                        "stats-site",
+                        site = site.name.as_str(),
+                        p95 = p95,
+                        streak = health.streak(&site.name)
+                    )
+                );
+                if let Some(b) = broken_site(&site.name) {
+                    println!(
+                        "  ⚠ {}",
+                        t!("stats-likely-broken", queries = b.queries.join(", "))
+                    );
                 }
-                top.push('┐');
-                rendered.push(top);
-
-                for (t, u) in items {
-                    let mut mid = String::new();
-                    mid.push('│');
-                    let content = format!(" - {} ({})", t, u);
-                    // ensure at least inner chars, pad or truncate
-                    if content.len() >= inner {
-                        mid.push_str(&content[..inner.min(content.len())]);
-                    } else {
-                        mid.push_str(&content);
-                        mid.push_str(&" ".repeat(inner - content.len()));
-                    }
-                    mid.push('│');
-                    rendered.push(mid);
+                if ignores_query(&site.name) {
+                    println!("  ⚠ {}", t!("stats-ignores-query"));
                 }
-
-                let mut bot = String::new();
-                bot.push('└');
-                bot.push_str(&"─".repeat(inner));
-                bot.push('┘');
-                rendered.push(bot);
-            }
-
-            let items: Vec<ListItem> = rendered
-                .iter()
-                .map(|text| ListItem::new(Line::from(text.as_str())))
-                .collect();
-
-            let title = format!(
-                "Results ({}). ↑/↓ move, PgUp/PgDn scroll, Enter/o open, q quit",
-                results.len()
-            );
-            let list = List::new(items)
-                .block(Block::default().title(title).borders(Borders::ALL))
-                .highlight_symbol("> ")
-                .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
-                .repeat_highlight_symbol(false);
-            f.render_stateful_widget(list, chunks[0], &mut state);
-
-            // Footer/help with selected URL
-            let sel = state
-                .selected()
-                .unwrap_or(0)
-                .min(entry_urls.len().saturating_sub(1));
-            let footer = if entry_urls.is_empty() {
-                String::new()
-            } else {
-                entry_urls[sel].clone().unwrap_or_default()
-            };
-            let foot = Paragraph::new(footer)
-                .block(Block::default().borders(Borders::TOP))
-                .wrap(Wrap { trim: false });
-            f.render_widget(foot, chunks[1]);
-        })?;
-
-        // Handle input & resize; non-blocking poll
-        if event::poll(std::time::Duration::from_millis(150))? {
-            match event::read()? {
-                event::Event::Key(k) => {
-                    if k.kind != KeyEventKind::Press {
-                        continue;
-                    }
-                    match k.code {
-                        event::KeyCode::Char('q') | event::KeyCode::Esc => should_quit = true,
-                        event::KeyCode::Up => {
-                            let mut i = state.selected().unwrap_or(0);
-                            i = i.saturating_sub(1);
-                            // skip non-selectable lines (borders)
-                            while i > 0 && entry_urls.get(i).and_then(|u| u.as_ref()).is_none() {
-                                i = i.saturating_sub(1);
-                            }
-                            state.select(Some(i));
-                        }
-                        event::KeyCode::Down => {
-                            let mut i = state.selected().unwrap_or(0);
-                            let max = entry_urls.len().saturating_sub(1);
-                            if i < max {
-                                i += 1;
-                            }
-                            while i < max && entry_urls.get(i).and_then(|u| u.as_ref()).is_none() {
-                                i += 1;
-                            }
-                            state.select(Some(i));
-                        }
-                        event::KeyCode::PageUp => {
-                            let i = state.selected().unwrap_or(0);
-                            let step = 10usize;
-                            let next = i.saturating_sub(step);
-                            state.select(Some(next));
-                        }
-                        event::KeyCode::PageDown => {
-                            let i = state.selected().unwrap_or(0);
-                            let step = 10usize;
-                            let max = entry_urls.len().saturating_sub(1);
-                            let next = (i + step).min(max);
-                            state.select(Some(next));
-                        }
-                        event::KeyCode::Home => {
-                            state.select(Some(0));
-                        }
-                        event::KeyCode::End => {
-                            let max = entry_urls.len().saturating_sub(1);
-                            state.select(Some(max));
-                        }
-                        event::KeyCode::Enter | event::KeyCode::Char('o') => {
-                            if let Some(i) = state.selected()
-                                && let Some(Some(url)) = entry_urls.get(i)
-                            {
-                                let _ = open_url(url);
-                            }
-                        }
-                        _ => {}
-                    }
+                if let Some(limit) = budget.limit(&site.name) {
+                    let used = budget.used(&site.name);
+                    let mark = if used >= limit { "⚠ " } else { "" };
+                    println!("  {mark}{}", t!("stats-budget", used = used, limit = limit));
                 }
-                event::Event::Resize(_, _) => {}
-                _ => {}
             }
         }
     }
-
-    // Restore terminal
-    terminal::disable_raw_mode()?;
-    crossterm::execute!(terminal.backend_mut(), terminal::LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
     Ok(())
 }
 
-fn open_url(url: &str) -> anyhow::Result<()> {
-    #[cfg(target_os = "windows")]
-    {
-        std::process::Command::new("cmd")
-            .args(["/C", "start", "", url])
-            .spawn()
-            .map(|_| ())?;
-        return Ok(());
+/// `grep` subcommand: results of earlier searches whose titles match
+/// `pattern`, with the queries that found them
+fn grep(search_cache: &SearchCache, pattern: &str, format: OutputFormat) -> Result<()> {
+    let path = paths::result_index_file();
+    let mut index = ResultIndex::load(&path);
+    // Searches saved before the index existed are still in the cache
+    index.absorb(search_cache);
+    let found = index.search(pattern);
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&found)?),
+        OutputFormat::Table if found.is_empty() => {
+            println!("{}", t!("grep-empty", pattern = pattern))
+        }
+        OutputFormat::Table => {
+            for result in found {
+                println!(
+                    "{}",
+                    t!(
+                        "grep-entry",
+                        site = result.site.as_str(),
+                        title = result.title.as_str(),
+                        url = result.url.as_str()
+                    )
+                );
+                println!(
+                    "  {}",
+                    t!("grep-found-by", queries = result.queries.join(", "))
+                );
+            }
+        }
     }
-    #[cfg(target_os = "macos")]
-    {
-        std::process::Command::new("open")
-            .arg(url)
-            .spawn()
-            .map(|_| ())?;
-        return Ok(());
+    Ok(())
+}
+
+/// `verify-cache` subcommand: check the URLs of a cached search and store the
+/// outcome in the cache entry, keeping its age so it expires as before
+async fn verify_cache(
+    cli: &Cli,
+    search_cache: &mut SearchCache,
+    cache_path: &std::path::Path,
+    query: &str,
+    prune: bool,
+) -> Result<()> {
+    let key = search::cache_key(query, &MultiQuery::parse(query));
+    let Some(entry) = search_cache.peek_mut(&key) else {
+        anyhow::bail!(t!("verify-not-cached", query = key.as_str()));
+    };
+    let summary = liveness::check_results(&build_http_client(), &mut entry.results).await;
+    let before = entry.results.len();
+    if prune {
+        entry.results.retain(|r| {
+            r.link_check
+                .as_ref()
+                .is_none_or(|c| c.state != LinkState::Dead)
+        });
     }
-    #[cfg(all(unix, not(target_os = "macos")))]
-    {
-        std::process::Command::new("xdg-open")
-            .arg(url)
-            .spawn()
-            .map(|_| ())?;
-        return Ok(());
+    let pruned = before - entry.results.len();
+    let results = entry.results.clone();
+    search_cache.save_to_file_sync(cache_path)?;
+
+    match cli.format {
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "query": key,
+                "summary": summary,
+                "pruned": pruned,
+                "results": results,
+            }))?
+        ),
+        OutputFormat::Table => {
+            output::print_table_grouped_with(&results, &table_labels(), hyperlinks_enabled(cli));
+            println!(
+                "{}",
+                t!(
+                    "verify-summary",
+                    alive = summary.alive,
+                    dead = summary.dead,
+                    blocked = summary.blocked,
+                    unreachable = summary.unreachable,
+                    skipped = summary.skipped
+                )
+            );
+            if prune {
+                println!("{}", t!("verify-pruned", count = pruned));
+            }
+        }
     }
-    #[allow(unreachable_code)]
     Ok(())
 }
 
-/// Live search TUI that shows per-site progress while search is running.
-/// Returns the combined results when search completes.
-async fn run_live_search_tui(
-    sites: Vec<SiteConfig>,
-    multi_query: &MultiQuery,
-    limit: usize,
-    _debug: bool, // prefixed with _ to avoid unused warning
-    no_cf: bool,
-    cf_url: String,
-    cookie_headers: Option<ReqHeaderMap>,
-    no_playwright: bool,
-    rate_limiter: Option<Arc<tokio::sync::Mutex<RateLimiter>>>,
-) -> anyhow::Result<Vec<SearchResult>> {
-    use std::collections::HashMap;
-    use std::time::Duration;
-
-    // Setup terminal
-    let mut stdout = stdout();
-    execute!(stdout, terminal::EnterAlternateScreen)?;
-    terminal::enable_raw_mode()?;
-    let backend = ratatui::backend::CrosstermBackend::new(stdout);
-    let mut terminal = ratatui::Terminal::new(backend)?;
-
-    // Initialize progress state for all sites
-    let mut progress_map: HashMap<String, SiteProgress> = HashMap::new();
-    for site in &sites {
-        progress_map.insert(
-            site.name.clone(),
-            SiteProgress {
-                site: site.name.clone(),
-                status: SiteStatus::Pending,
-                results_count: 0,
-                message: None,
-            },
-        );
+/// Table headers and empty-state text in the selected language
+fn table_labels() -> output::TableLabels {
+    output::TableLabels {
+        title: t!("table-header-title"),
+        url: t!("table-header-url"),
+        no_results: t!("no-results"),
     }
+}
 
-    // Create channel for search events
-    let (event_tx, mut event_rx) = mpsc::channel::<SearchEvent>(100);
-
-    // Create channel for results
-    let (result_tx, mut result_rx) = mpsc::channel::<(String, Vec<SearchResult>)>(100);
-
-    // Pre-compute site-specific queries for multi-query support
-    let site_queries: std::collections::HashMap<String, String> = sites
-        .iter()
-        .map(|s| {
-            let site_terms = multi_query.get_search_terms_for_site(&s.name);
-            let query = if site_terms.is_empty() {
-                multi_query
-                    .first()
-                    .map(|f| f.get_search_terms())
-                    .unwrap_or_default()
-            } else {
-                site_terms.join(" ")
-            };
-            (s.name.clone(), query)
-        })
-        .collect();
+/// Prints search results in `format` with the CLI's labels and hyperlink setting
+/// --stream: print each batch of results as it comes, as JSON lines or a
+/// table per site
+fn stream_printer(cli: &Cli, format: OutputFormat) -> impl FnMut(&[SearchResult]) {
+    let (labels, hyperlinks) = (table_labels(), hyperlinks_enabled(cli));
+    move |results| match format {
+        OutputFormat::Json => output::print_json_lines(results),
+        OutputFormat::Table => output::print_table_grouped_with(results, &labels, hyperlinks),
+    }
+}
 
-    // Spawn search task
-    let search_handle = {
-        let sites = sites.clone();
-        let site_queries = site_queries.clone();
-        let cf_url = cf_url.clone();
-        let cookie_headers = cookie_headers.clone();
-        let rate_limiter = rate_limiter.clone();
-        let event_tx = event_tx.clone();
-        let result_tx = result_tx.clone();
-
-        tokio::spawn(async move {
-            let client = build_http_client();
-            let semaphore = Arc::new(Semaphore::new(3));
-            let mut tasks = FuturesUnordered::new();
+fn terminal_sink(cli: &Cli, format: OutputFormat) -> sinks::TerminalSink {
+    sinks::TerminalSink {
+        format,
+        labels: table_labels(),
+        hyperlinks: hyperlinks_enabled(cli),
+    }
+}
 
-            for site in sites {
-                let permit = semaphore.clone().acquire_owned().await.unwrap();
-                let client = client.clone();
-                // Get site-specific query from pre-computed map
-                let query = site_queries.get(&site.name).cloned().unwrap_or_default();
-                let cf_url = cf_url.clone();
-                let cookie_headers = cookie_headers.clone();
-                let rate_limiter = rate_limiter.clone();
-                let event_tx = event_tx.clone();
-                let result_tx = result_tx.clone();
-                let use_cf = !no_cf;
-
-                tasks.push(tokio::spawn(async move {
-                    let _permit = permit;
-                    let site_name = site.name.clone();
-
-                    // Emit started event
-                    let _ = event_tx
-                        .send(SearchEvent::SiteStarted {
-                            site: site_name.clone(),
-                        })
-                        .await;
+/// Whether table output should use OSC 8 hyperlinks.
+///
+/// Off with --no-hyperlinks, when stdout is not a terminal, or on dumb terminals;
+/// `FORCE_HYPERLINK=1`/`0` overrides the detection.
+fn hyperlinks_enabled(cli: &Cli) -> bool {
+    if cli.no_hyperlinks {
+        return false;
+    }
+    match std::env::var("FORCE_HYPERLINK").ok().as_deref() {
+        Some("0") => return false,
+        Some(v) if !v.is_empty() => return true,
+        _ => {}
+    }
+    std::io::stdout().is_terminal() && std::env::var("TERM").ok().as_deref() != Some("dumb")
+}
 
-                    // Emit fetching event
-                    let _ = event_tx
-                        .send(SearchEvent::SiteFetching {
-                            site: site_name.clone(),
-                        })
-                        .await;
-
-                    let base_url = match site.search_kind {
-                        SearchKind::ListingPage => site
-                            .listing_path
-                            .clone()
-                            .unwrap_or(site.base_url.clone())
-                            .to_string(),
-                        SearchKind::PhpBBSearch => build_search_url(&site, &query),
-                        _ => build_search_url(&site, &query),
-                    };
-
-                    let mut results: Vec<SearchResult> = Vec::new();
-                    let cf_local = cf_url.contains("127.0.0.1") || cf_url.contains("localhost");
-                    let non_default_cf = cf_url != "http://localhost:8191/v1";
-                    let prefer_solver = use_cf && (cf_local || non_default_cf);
-
-                    // Try Playwright for csrin first
-                    if site.name.eq_ignore_ascii_case("csrin") && !no_playwright && !prefer_solver {
-                        let cookie_val = cookie_headers
-                            .as_ref()
-                            .and_then(|h| h.get(COOKIE))
-                            .and_then(|v| v.to_str().ok())
-                            .map(|s| s.to_string());
-                        if let Some(html) = fetch_csrin_playwright_html(&query, cookie_val).await {
-                            let _ = event_tx
-                                .send(SearchEvent::SiteParsing {
-                                    site: site_name.clone(),
-                                })
-                                .await;
-                            results = parse_results(&site, &html, &query);
-                        }
-                    }
+fn print_diff(format: OutputFormat, result_diff: &ResultDiff) -> Result<()> {
+    match format {
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&diff::diff_json(result_diff))?
+        ),
+        OutputFormat::Table => diff::print_diff_table(result_diff),
+    }
+    Ok(())
+}
 
-                    if results.is_empty() {
-                        let allow_env = std::env::var("ALLOW_CSRIN_SOLVER")
-                            .ok()
-                            .map(|v| v == "1")
-                            .unwrap_or(false);
-                        let csrin_solver_allowed = site.name.eq_ignore_ascii_case("csrin")
-                            && (allow_env || cf_local || non_default_cf);
-                        let use_solver_for_this =
-                            use_cf && (site.requires_cloudflare || csrin_solver_allowed);
-
-                        let html = if use_solver_for_this {
-                            (if cookie_headers.is_some() {
-                                cf::fetch_via_solver_with_headers(
-                                    &client,
-                                    &base_url,
-                                    &cf_url,
-                                    cookie_headers.clone(),
-                                )
-                                .await
-                            } else {
-                                fetch_via_solver(&client, &base_url, &cf_url).await
-                            })
-                            .unwrap_or_default()
-                        } else {
-                            let rate_limiter_ref = if let Some(ref rl) = rate_limiter {
-                                Some(&mut *rl.lock().await)
-                            } else {
-                                None
-                            };
-
-                            (if cookie_headers.is_some() {
-                                fetcher::fetch_with_retry_headers(
-                                    &client,
-                                    &base_url,
-                                    cookie_headers.clone(),
-                                    rate_limiter_ref,
-                                    Some(site.name.as_str()),
-                                )
-                                .await
-                            } else {
-                                fetch_with_retry(
-                                    &client,
-                                    &base_url,
-                                    rate_limiter_ref,
-                                    Some(site.name.as_str()),
-                                )
-                                .await
-                            })
-                            .unwrap_or_default()
-                        };
+/// Sites picked by --sites/--invert-sites, or all sites
+fn cli_selected_sites(cli: &Cli) -> Result<Vec<SiteConfig>> {
+    let sites = active_site_configs()?;
+    Ok(match cli.sites.as_deref() {
+        Some(csv) => select_sites(sites, csv, cli.invert_sites),
+        None => sites,
+    })
+}
 
-                        // Emit parsing event
-                        let _ = event_tx
-                            .send(SearchEvent::SiteParsing {
-                                site: site_name.clone(),
-                            })
-                            .await;
-                        results = parse_results(&site, &html, &query);
-                    }
+/// Total and per-segment caps from --total-limit and --per-segment-limit
+fn result_caps(cli: &Cli) -> ResultCaps {
+    ResultCaps {
+        total: cli.total_limit,
+        per_segment: cli.per_segment_limit,
+    }
+}
 
-                    // Apply per-site filtering
-                    if site.name.eq_ignore_ascii_case("csrin") {
-                        let q_lower = query.to_lowercase();
-                        results.retain(|r| r.url.contains("viewtopic.php"));
-                        results.retain(|r| r.title.to_lowercase().contains(&q_lower));
-                    } else if !site.name.eq_ignore_ascii_case("csrin") {
-                        let q_lower = query.to_lowercase();
-                        if site.name.eq_ignore_ascii_case("fitgirl")
-                            || site.name.eq_ignore_ascii_case("fitgirl-repacks")
-                        {
-                            results.retain(|r| r.title.to_lowercase().contains(&q_lower));
-                        } else {
-                            let q_dash = q_lower.replace(' ', "-");
-                            let q_plus = q_lower.replace(' ', "+");
-                            let q_enc = q_lower.replace(' ', "%20");
-                            let q_strip = q_lower.replace(' ', "");
-                            results.retain(|r| {
-                                let tl = r.title.to_lowercase();
-                                let ul = r.url.to_lowercase();
-                                tl.contains(&q_lower)
-                                    || ul.contains(&q_lower)
-                                    || ul.contains(&q_dash)
-                                    || ul.contains(&q_plus)
-                                    || ul.contains(&q_enc)
-                                    || ul.contains(&q_strip)
-                            });
-                        }
-                    }
+/// Search options for bulk modes (batch, wishlist) and searches started from the TUI
+fn bulk_search_options(cli: &Cli) -> SearchOptions {
+    SearchOptions {
+        limit: cli.per_site_limit,
+        caps: result_caps(cli),
+        debug: cli.debug,
+        use_cf: search_profile(cli).use_solver(!cli.no_cf),
+        cf_url: resolve_cf_url(&cli.cf_url),
+        cookie_headers: cookie_header_map(cli.cookie.as_deref()),
+        accounts: site_accounts(cli),
+        no_playwright: !search_profile(cli).use_playwright(!cli.no_playwright),
+        show_progress: false,
+        site_timeout: site_timeout(cli),
+        deterministic: cli.deterministic,
+        routes: site_routes(),
+        steam_appids: steam_appids(cli),
+        retry_failed: retry_failed(cli),
+        hide_status: cli.hide_status.clone(),
+        profile: search_profile(cli),
+        budget: daily_budget(cli),
+        timings: None,
+        stream: None,
+    }
+}
 
-                    // Normalize titles
-                    for r in &mut results {
-                        r.title = normalize_title(site.name.as_str(), &r.title);
-                    }
+/// Accounts from `[accounts]` in config.toml. Not with --cookie, which is
+/// sent to every site instead, or --replay, which never reaches the sites.
+fn site_accounts(cli: &Cli) -> Option<Arc<Accounts>> {
+    if cli.cookie.is_some() || cli.replay.is_some() {
+        return None;
+    }
+    let settings = Settings::load(&default_settings_path());
+    let accounts = Accounts::new(settings.accounts, SecretStore::default());
+    (!accounts.is_empty()).then(|| Arc::new(accounts))
+}
 
-                    if !results.is_empty() {
-                        results.truncate(limit);
-                    }
+/// Today's request counts and the budgets from `[search.daily_budget]` in
+/// config.toml; none with --replay, which sends no requests
+fn daily_budget(cli: &Cli) -> Option<Arc<DailyBudget>> {
+    if cli.replay.is_some() {
+        return None;
+    }
+    let limits = Settings::load(&default_settings_path()).search.daily_budget;
+    let log = RequestLog::load(&paths::request_budget_file());
+    Some(Arc::new(DailyBudget::new(limits, log)))
+}
 
-                    // Emit completed event
-                    let _ = event_tx
-                        .send(SearchEvent::SiteCompleted {
-                            site: site_name.clone(),
-                            results_count: results.len(),
-                        })
-                        .await;
+/// Per-site proxy routes from `[search.routes]` in config.toml
+fn site_routes() -> Routes {
+    Routes::new(&Settings::load(&default_settings_path()).search.routes)
+}
 
-                    // Send results
-                    let _ = result_tx.send((site_name, results)).await;
-                }));
-            }
+/// --steam-appids, or `steam_appids` in config.toml
+fn steam_appids(cli: &Cli) -> bool {
+    cli.steam_appids || Settings::load(&default_settings_path()).search.steam_appids
+}
 
-            // Wait for all tasks
-            while tasks.next().await.is_some() {}
+/// --retry-failed, or `retry_failed` in config.toml
+fn retry_failed(cli: &Cli) -> bool {
+    cli.retry_failed || Settings::load(&default_settings_path()).search.retry_failed
+}
 
-            // Signal completion - drop senders to close channels
-            drop(event_tx);
-            drop(result_tx);
-        })
-    };
+/// Per-site wall-clock budget from --timeout-per-site (0 disables it), or the
+/// --fast budget
+fn site_timeout(cli: &Cli) -> Option<std::time::Duration> {
+    match cli.timeout_per_site {
+        Some(0) => None,
+        secs => search_profile(cli).site_budget(secs.map(std::time::Duration::from_secs)),
+    }
+}
 
-    // Drop our copy of senders so channels close when search is done
-    drop(event_tx);
-    drop(result_tx);
-
-    let total_sites = sites.len();
-    let mut completed_sites = 0usize;
-    let mut collected_results: Vec<SearchResult> = Vec::new();
-    let mut search_done = false;
-    let mut should_quit = false;
-
-    // Drain any pending keystrokes
-    while event::poll(Duration::from_millis(0))? {
-        let _ = event::read()?;
-    }
-
-    while !should_quit {
-        // Non-blocking poll for search events
-        loop {
-            match event_rx.try_recv() {
-                Ok(search_event) => match search_event {
-                    SearchEvent::SiteStarted { site } => {
-                        if let Some(p) = progress_map.get_mut(&site) {
-                            p.status = SiteStatus::Pending;
-                            p.message = Some("Starting...".to_string());
-                        }
-                    }
-                    SearchEvent::SiteFetching { site } => {
-                        if let Some(p) = progress_map.get_mut(&site) {
-                            p.status = SiteStatus::Fetching;
-                            p.message = Some("Fetching...".to_string());
-                        }
-                    }
-                    SearchEvent::SiteParsing { site } => {
-                        if let Some(p) = progress_map.get_mut(&site) {
-                            p.status = SiteStatus::Parsing;
-                            p.message = Some("Parsing...".to_string());
-                        }
-                    }
-                    SearchEvent::SiteCompleted {
-                        site,
-                        results_count,
-                    } => {
-                        if let Some(p) = progress_map.get_mut(&site) {
-                            p.status = SiteStatus::Completed;
-                            p.results_count = results_count;
-                            p.message = None;
-                        }
-                        completed_sites += 1;
-                    }
-                    SearchEvent::SiteFailed { site, error } => {
-                        if let Some(p) = progress_map.get_mut(&site) {
-                            p.status = SiteStatus::Failed;
-                            p.message = Some(error);
-                        }
-                        completed_sites += 1;
-                    }
-                    SearchEvent::AllDone { .. } => {
-                        search_done = true;
-                    }
-                },
-                Err(mpsc::error::TryRecvError::Empty) => break,
-                Err(mpsc::error::TryRecvError::Disconnected) => {
-                    search_done = true;
-                    break;
-                }
-            }
-        }
+/// The profile chosen with --fast or --thorough
+fn search_profile(cli: &Cli) -> SearchProfile {
+    if cli.fast {
+        SearchProfile::Fast
+    } else if cli.thorough {
+        SearchProfile::Thorough
+    } else {
+        SearchProfile::Default
+    }
+}
 
-        // Non-blocking poll for results
-        loop {
-            match result_rx.try_recv() {
-                Ok((_, mut results)) => {
-                    collected_results.append(&mut results);
-                }
-                Err(mpsc::error::TryRecvError::Empty) => break,
-                Err(mpsc::error::TryRecvError::Disconnected) => break,
-            }
-        }
+/// Shared rate limiter unless --no-rate-limit was given
+fn cli_rate_limiter(cli: &Cli) -> Option<Arc<tokio::sync::Mutex<RateLimiter>>> {
+    (!cli.no_rate_limit).then(|| Arc::new(tokio::sync::Mutex::new(RateLimiter::new())))
+}
 
-        // Check if search is complete
-        if completed_sites >= total_sites {
-            search_done = true;
-        }
+/// Persist the cache unless --no-cache was given; failures only matter in debug output
+fn save_cache(cli: &Cli, cache: &SearchCache, path: &std::path::Path) {
+    if cli.no_cache || cache.is_empty() {
+        return;
+    }
+    match cache.save_to_file_sync(path) {
+        Ok(()) => index_results(cache, cli.debug),
+        Err(e) if cli.debug => eprintln!("[debug] Failed to save cache: {}", e),
+        Err(_) => {}
+    }
+}
 
-        // Draw UI
-        terminal.draw(|f| {
-            let area = f.area();
-            let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([
-                    Constraint::Length(3), // Title + progress bar
-                    Constraint::Min(1),    // Site list
-                    Constraint::Length(3), // Footer
-                ])
-                .split(area);
-
-            // Title and overall progress
-            let progress_ratio = if total_sites > 0 {
-                completed_sites as f64 / total_sites as f64
-            } else {
-                0.0
-            };
-            let title = format!(
-                " Searching {} sites | {} results found ",
-                total_sites,
-                collected_results.len()
-            );
-            let gauge = Gauge::default()
-                .block(Block::default().title(title).borders(Borders::ALL))
-                .gauge_style(Style::default().fg(Color::Cyan))
-                .ratio(progress_ratio)
-                .label(format!("{}/{} sites", completed_sites, total_sites));
-            f.render_widget(gauge, chunks[0]);
-
-            // Site progress list - sort by status before creating ListItems
-            let mut progress_vec: Vec<&SiteProgress> = progress_map.values().collect();
-            progress_vec.sort_by(|a, b| {
-                // Sort by status: Fetching/Parsing first, then Completed, then Pending/Failed
-                let get_order = |status: SiteStatus| match status {
-                    SiteStatus::Fetching | SiteStatus::Parsing => 0,
-                    SiteStatus::Completed => 1,
-                    SiteStatus::Pending => 2,
-                    SiteStatus::Failed => 3,
-                };
-                get_order(a.status).cmp(&get_order(b.status))
-            });
+/// Add the results in the saved cache to the index `grep` searches
+pub(crate) fn index_results(cache: &SearchCache, debug: bool) {
+    if let Err(e) = result_index::index_cache(&paths::result_index_file(), cache)
+        && debug
+    {
+        eprintln!("[debug] Failed to update the result index: {}", e);
+    }
+}
 
-            let progress_items: Vec<ListItem> = progress_vec
-                .iter()
-                .map(|p| {
-                    let emoji = p.status.emoji();
-                    let status_text = p.status.label();
-                    let count_text = if p.results_count > 0 {
-                        format!(" ({} results)", p.results_count)
-                    } else {
-                        String::new()
-                    };
-                    let msg = p.message.as_deref().unwrap_or("");
-                    let line = format!(
-                        "{} {} - {}{} {}",
-                        emoji, p.site, status_text, count_text, msg
-                    );
-                    ListItem::new(Line::from(line))
-                })
-                .collect();
+/// Resolve the FlareSolverr URL: prefer the CLI value if non-default; otherwise allow
+/// a CF_URL env override (for Docker)
+fn resolve_cf_url(cli_value: &str) -> String {
+    match std::env::var("CF_URL")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+    {
+        Some(env_cf) if cli_value == "http://localhost:8191/v1" => env_cf,
+        _ => cli_value.to_string(),
+    }
+}
 
-            let site_list = List::new(progress_items).block(
-                Block::default()
-                    .title(" Site Progress ")
-                    .borders(Borders::ALL),
-            );
-            f.render_widget(site_list, chunks[1]);
+/// Pick sites named in a comma-separated list, or all others when `invert` is set
+fn select_sites(all_sites: Vec<SiteConfig>, sites_csv: &str, invert: bool) -> Vec<SiteConfig> {
+    let wanted: Vec<String> = sites_csv
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    all_sites
+        .into_iter()
+        .filter(|s| wanted.iter().any(|w| w.eq_ignore_ascii_case(&s.name)) != invert)
+        .collect()
+}
 
-            // Footer
-            let footer_text = if search_done {
-                "Search complete! Press Enter to view results, q to quit"
-            } else {
-                "Searching... Press q to cancel"
-            };
-            let footer = Paragraph::new(footer_text)
-                .block(Block::default().borders(Borders::ALL))
-                .style(Style::default().fg(if search_done {
-                    Color::Green
-                } else {
-                    Color::Yellow
-                }));
-            f.render_widget(footer, chunks[2]);
-        })?;
+/// Build a Cookie header map for forwarding, ignoring values that aren't valid header text
+fn cookie_header_map(cookie: Option<&str>) -> Option<ReqHeaderMap> {
+    let v = HeaderValue::from_str(cookie?).ok()?;
+    let mut h = ReqHeaderMap::new();
+    h.insert(COOKIE, v);
+    Some(h)
+}
 
-        // Handle user input
-        if event::poll(Duration::from_millis(50))? {
-            match event::read()? {
-                event::Event::Key(k) => {
-                    if k.kind != KeyEventKind::Press {
-                        continue;
-                    }
-                    match k.code {
-                        event::KeyCode::Char('q') | event::KeyCode::Esc => {
-                            should_quit = true;
-                            // Cancel search if still running
-                            search_handle.abort();
-                        }
-                        event::KeyCode::Enter if search_done => {
-                            should_quit = true;
-                        }
-                        _ => {}
-                    }
-                }
-                event::Event::Resize(_, _) => {}
-                _ => {}
-            }
+/// Copy `text` to the system clipboard using the platform's clipboard command
+fn copy_to_clipboard(text: &str) -> anyhow::Result<()> {
+    use std::io::Write;
+    let candidates: &[(&str, &[&str])] = if cfg!(target_os = "windows") {
+        &[("clip", &[])]
+    } else if cfg!(target_os = "macos") {
+        &[("pbcopy", &[])]
+    } else {
+        &[
+            ("wl-copy", &[]),
+            ("xclip", &["-selection", "clipboard"]),
+            ("xsel", &["--clipboard", "--input"]),
+        ]
+    };
+    for (program, args) in candidates {
+        let Ok(mut child) = std::process::Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
         }
-
-        // If search is done and user hasn't pressed a key, auto-continue after brief pause
-        if search_done && !should_quit {
-            // Give user a moment to see the completion state
-            tokio::time::sleep(Duration::from_millis(500)).await;
-            should_quit = true;
+        if child.wait()?.success() {
+            return Ok(());
         }
     }
-
-    // Restore terminal
-    terminal::disable_raw_mode()?;
-    crossterm::execute!(terminal.backend_mut(), terminal::LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
-
-    // Wait for search task to finish (it may already be done or aborted)
-    let _ = search_handle.await;
-
-    Ok(collected_results)
+    let tried: Vec<&str> = candidates.iter().map(|(p, _)| *p).collect();
+    anyhow::bail!(
+        "no clipboard command available (tried {})",
+        tried.join(", ")
+    )
 }
 
 fn filter_results_by_query_strict(results: &mut Vec<SearchResult>, query: &str) {
-    let ql = query.to_lowercase();
-    let ql_dash = ql.replace(' ', "-");
-    let ql_plus = ql.replace(' ', "+");
-    let ql_encoded = ql.replace(' ', "%20");
-    let ql_stripped = ql.replace(' ', "");
+    let variants = QueryVariants::new(query);
+    let (mut tl, mut ul) = (String::new(), String::new());
     results.retain(|r| {
-        let tl = r.title.to_lowercase();
-        let ul = r.url.to_lowercase();
-        let matches = tl.contains(&ql)
-            || ul.contains(&ql)
-            || ul.contains(&ql_dash)
-            || ul.contains(&ql_plus)
-            || ul.contains(&ql_encoded)
-            || ul.contains(&ql_stripped);
-        let gog_path_ok = ul.contains("/game/") || ul.contains("/games/");
-        matches && gog_path_ok
+        lowercase_into(&mut tl, &r.title);
+        lowercase_into(&mut ul, &r.url);
+        variants.matches(&tl, &ul)
     });
 }
 
@@ -1686,15 +1899,17 @@ async fn fetch_gog_games_ajax_json(
     cookie_headers: Option<ReqHeaderMap>,
     debug: bool,
     mut rate_limiter: Option<&mut RateLimiter>,
-) -> Option<Vec<SearchResult>> {
-    let qenc = urlencoding::encode(query);
-    let urls = vec![
-        format!(
-            "https://gog-games.to/search?search={}&page=1&den_filter=none",
-            qenc
+) -> std::result::Result<Vec<SearchResult>, Outcome> {
+    // The search page itself, then the endpoints its script calls
+    let search_page = build_search_url(site, query);
+    let endpoint = format!("{}search", site.base_url);
+    let urls = [
+        with_query(
+            &endpoint,
+            &[("search", query), ("page", "1"), ("den_filter", "none")],
         ),
-        format!("https://gog-games.to/search?page=1&search={}", qenc),
-        format!("https://gog-games.to/?search={}", qenc),
+        with_query(&endpoint, &[("page", "1"), ("search", query)]),
+        search_page.clone(),
     ];
     // build headers
     let mut headers = ReqHeaderMap::new();
@@ -1708,7 +1923,7 @@ async fn fetch_gog_games_ajax_json(
     );
     headers.insert(
         REFERER,
-        HeaderValue::from_str(&format!("https://gog-games.to/?search={}", qenc))
+        HeaderValue::from_str(&search_page)
             .unwrap_or(HeaderValue::from_static("https://gog-games.to/")),
     );
     if let Some(ch) = &cookie_headers {
@@ -1717,27 +1932,47 @@ async fn fetch_gog_games_ajax_json(
         }
     }
 
+    // Why the last endpoint tried gave nothing
+    let mut outcome = Outcome::EmptyResponse;
+    // Endpoints often redirect to one another; each final page is read once
+    let mut seen: HashSet<String> = HashSet::new();
     for (i, u) in urls.into_iter().enumerate() {
-        let body: String = if use_cf {
-            (cf::fetch_via_solver_with_headers(client, &u, cf_url, Some(headers.clone())).await)
-                .unwrap_or_default()
+        let fetched = if use_cf {
+            cf::fetch_via_solver_with_headers(client, &u, cf_url, Some(headers.clone()))
+                .await
+                .map(|body| fetcher::Fetched {
+                    url: u.clone(),
+                    body,
+                })
         } else {
-            (fetcher::fetch_with_retry_headers(
+            fetcher::fetch_with_final_url(
                 client,
                 &u,
                 Some(headers.clone()),
                 rate_limiter.as_deref_mut(),
                 Some("gog-games"),
             )
-            .await)
-                .unwrap_or_default()
+            .await
+        };
+        let body = match fetched {
+            Ok(page) if !seen.insert(page.url.clone()) => {
+                if debug {
+                    eprintln!("[debug] gog-games endpoint {} led to {} again", u, page.url);
+                }
+                continue;
+            }
+            Ok(page) if !page.body.is_empty() => page.body,
+            Ok(_) => {
+                outcome = Outcome::EmptyResponse;
+                continue;
+            }
+            Err(e) => {
+                outcome = Outcome::fetch_error(use_cf, &e);
+                continue;
+            }
         };
-        if body.is_empty() {
-            continue;
-        }
         if debug {
-            let _ = tokio::fs::create_dir_all("debug").await;
-            let _ = tokio::fs::write(format!("debug/gog-games_ajax_{}.txt", i), &body).await;
+            let _ = DebugDir::default().write(&format!("gog-games_ajax_{}", i), "txt", &body);
         }
         let trimmed = body.trim_start();
         if trimmed.starts_with('<') {
@@ -1750,7 +1985,7 @@ async fn fetch_gog_games_ajax_json(
                         let mut results: Vec<SearchResult> = Vec::new();
                         collect_title_url_pairs(&v, &mut results);
                         if !results.is_empty() {
-                            return Some(results);
+                            return Ok(results);
                         }
                     }
                 }
@@ -1758,15 +1993,16 @@ async fn fetch_gog_games_ajax_json(
             // else treat as HTML fragment
             let rs = parse_results(site, &body, query);
             if !rs.is_empty() {
-                return Some(rs);
+                return Ok(rs);
             }
+            outcome = Outcome::of_page(site, &body, query, 0);
             continue;
         }
         if let Ok(v) = serde_json::from_str::<Value>(&body) {
             if let Some(html) = v.get("html").and_then(|x| x.as_str()) {
                 let rs = parse_results(site, html, query);
                 if !rs.is_empty() {
-                    return Some(rs);
+                    return Ok(rs);
                 }
             }
             if let Some(html) = v
@@ -1776,134 +2012,18 @@ async fn fetch_gog_games_ajax_json(
             {
                 let rs = parse_results(site, html, query);
                 if !rs.is_empty() {
-                    return Some(rs);
+                    return Ok(rs);
                 }
             }
             let mut results: Vec<SearchResult> = Vec::new();
             collect_title_url_pairs(&v, &mut results);
             if !results.is_empty() {
-                return Some(results);
-            }
-        }
-    }
-    None
-}
-
-#[allow(clippy::too_many_arguments)]
-async fn fetch_csrin_feed(
-    client: &reqwest::Client,
-    site: &website_searcher_core::models::SiteConfig,
-    query: &str,
-    _use_cf: bool,
-    cf_url: &str,
-    _cookie_headers: Option<ReqHeaderMap>,
-    debug: bool,
-    rate_limiter: Option<&mut RateLimiter>,
-) -> Option<Vec<SearchResult>> {
-    // Try forum feed which lists topics
-    let feed_url = "https://cs.rin.ru/forum/feed.php?f=10";
-    // Never route feeds via solver for csrin to avoid solver blacklisting/redirect noise
-    let body = if false {
-        cf::fetch_via_solver(client, feed_url, cf_url)
-            .await
-            .unwrap_or_default()
-    } else {
-        fetcher::fetch_with_retry(client, feed_url, rate_limiter, Some("csrin"))
-            .await
-            .unwrap_or_default()
-    };
-    if body.is_empty() {
-        return None;
-    }
-    // Some endpoints wrap Atom XML inside HTML <pre> with escaped entities; unwrap and decode
-    let mut xml = body.clone();
-    if let Some(pre_idx) = xml.find("<pre")
-        && let Some(tag_end) = xml[pre_idx..].find('>')
-    {
-        let content_start = pre_idx + tag_end + 1;
-        if let Some(close_rel) = xml[content_start..].find("</pre>") {
-            let content_end = content_start + close_rel;
-            let inner = &xml[content_start..content_end];
-            xml = inner
-                .replace("&lt;", "<")
-                .replace("&gt;", ">")
-                .replace("&amp;", "&")
-                .replace("&quot;", "\"")
-                .replace("&#39;", "'");
-        }
-    }
-    if debug {
-        let _ = tokio::fs::create_dir_all("debug").await;
-        let _ = tokio::fs::write("debug/csrin_feed.xml", &xml).await;
-    }
-    // Very light XML parse: find <entry><title> and <link href="...viewtopic.php?..."/>
-    let mut results: Vec<SearchResult> = Vec::new();
-    let ql = query.to_lowercase();
-    let mut i = 0usize;
-    while let Some(tidx) = xml[i..].find("<entry>") {
-        let start = i + tidx;
-        let end = xml[start..]
-            .find("</entry>")
-            .map(|e| start + e + 8)
-            .unwrap_or(xml.len());
-        let entry = &xml[start..end];
-        // Extract <title ...>...</title>, allowing attributes and CDATA
-        let mut title = "";
-        if let Some(t_open_rel) = entry.find("<title") {
-            let after_tag_rel = entry[t_open_rel..].find('>').map(|p| t_open_rel + p + 1);
-            if let Some(content_start) = after_tag_rel
-                && let Some(close_rel) = entry[content_start..].find("</title>")
-            {
-                let raw = &entry[content_start..content_start + close_rel];
-                let raw = raw.trim();
-                // Unwrap CDATA if present
-                if let Some(inner) = raw.strip_prefix("<![CDATA[") {
-                    if let Some(inner2) = inner.strip_suffix("]]>") {
-                        title = inner2.trim();
-                    } else {
-                        title = inner.trim();
-                    }
-                } else {
-                    title = raw;
-                }
-            }
-        }
-        if title.is_empty() {
-            title = entry
-                .split_once("<title>")
-                .and_then(|(_, rest)| rest.split_once("</title>").map(|(t, _)| t))
-                .unwrap_or("")
-                .trim();
-        }
-        let href = entry
-            .split_once("<link href=\"")
-            .and_then(|(_, rest)| rest.split_once('\"').map(|(u, _)| u))
-            .unwrap_or("");
-        if !title.is_empty() && href.contains("viewtopic.php") {
-            let tl = title.to_lowercase();
-            if tl.contains(&ql) || href.to_lowercase().contains(&ql.replace(' ', "+")) {
-                let url = if href.starts_with("http") {
-                    href.to_string()
-                } else {
-                    format!("https://cs.rin.ru/forum/{}", href.trim_start_matches('/'))
-                };
-                results.push(SearchResult {
-                    site: site.name.clone(),
-                    title: title.to_string(),
-                    url,
-                });
+                return Ok(results);
             }
+            outcome = Outcome::NoMatches(0);
         }
-        i = end;
-        if results.len() >= 50 {
-            break;
-        }
-    }
-    if results.is_empty() {
-        None
-    } else {
-        Some(results)
     }
+    Err(outcome)
 }
 
 /// Resolve the csrin_search.cjs script path with fallback search order:
@@ -1920,26 +2040,26 @@ fn resolve_csrin_script_path() -> Option<std::path::PathBuf> {
     }
 
     // 2. Executable's directory + scripts/csrin_search.cjs
-    if let Ok(exe_path) = std::env::current_exe() {
-        if let Some(exe_dir) = exe_path.parent() {
-            // Try scripts/ subdirectory (installed location)
-            let script_in_exe_scripts = exe_dir.join("scripts").join("csrin_search.cjs");
-            if script_in_exe_scripts.exists() {
-                return Some(script_in_exe_scripts);
-            }
-            // Try alongside executable
-            let script_beside_exe = exe_dir.join("csrin_search.cjs");
-            if script_beside_exe.exists() {
-                return Some(script_beside_exe);
-            }
-            // For development: go up to project root from target/debug/
-            if let Some(parent) = exe_dir.parent() {
-                if let Some(grandparent) = parent.parent() {
-                    let dev_script = grandparent.join("scripts").join("csrin_search.cjs");
-                    if dev_script.exists() {
-                        return Some(dev_script);
-                    }
-                }
+    if let Ok(exe_path) = std::env::current_exe()
+        && let Some(exe_dir) = exe_path.parent()
+    {
+        // Try scripts/ subdirectory (installed location)
+        let script_in_exe_scripts = exe_dir.join("scripts").join("csrin_search.cjs");
+        if script_in_exe_scripts.exists() {
+            return Some(script_in_exe_scripts);
+        }
+        // Try alongside executable
+        let script_beside_exe = exe_dir.join("csrin_search.cjs");
+        if script_beside_exe.exists() {
+            return Some(script_beside_exe);
+        }
+        // For development: go up to project root from target/debug/
+        if let Some(parent) = exe_dir.parent()
+            && let Some(grandparent) = parent.parent()
+        {
+            let dev_script = grandparent.join("scripts").join("csrin_search.cjs");
+            if dev_script.exists() {
+                return Some(dev_script);
             }
         }
     }
@@ -1962,16 +2082,31 @@ fn resolve_csrin_script_path() -> Option<std::path::PathBuf> {
 }
 
 // Spawn Node + Playwright helper to fetch rendered HTML for cs.rin search
-async fn fetch_csrin_playwright_html(query: &str, cookie: Option<String>) -> Option<String> {
+async fn fetch_csrin_playwright_html(
+    query: &str,
+    cookie: Option<String>,
+) -> std::result::Result<String, Outcome> {
     // Test/CI fast path: if CS_PLAYWRIGHT_HTML is provided, return it without spawning Node
     if let Ok(fake) = std::env::var("CS_PLAYWRIGHT_HTML")
         && !fake.trim().is_empty()
     {
-        return Some(fake);
+        return Ok(fake);
     }
+    // The rendered search depends on the page count, so it is part of the key
+    let pages = std::env::var("CSRIN_PAGES").unwrap_or_default();
+    let target = format!("csrin?pages={}&q={}", pages.trim(), query);
+    let key = render_cache::key("playwright", &target, cookie.as_deref());
+    render_cache::through(&key, run_csrin_playwright(query, cookie)).await
+}
 
+/// Render cs.rin.ru's search for `query` with the Node Playwright helper
+async fn run_csrin_playwright(
+    query: &str,
+    cookie: Option<String>,
+) -> std::result::Result<String, Outcome> {
     // Resolve script path with fallback order
-    let script_path = resolve_csrin_script_path()?;
+    let script_path = resolve_csrin_script_path()
+        .ok_or_else(|| Outcome::Unavailable("csrin_search.cjs not found".to_string()))?;
 
     let mut cmd = Command::new("node");
     cmd.arg(&script_path).arg(query);
@@ -1987,9 +2122,11 @@ async fn fetch_csrin_playwright_html(query: &str, cookie: Option<String>) -> Opt
     cmd.stdin(Stdio::null());
     cmd.stderr(Stdio::inherit());
     cmd.stdout(Stdio::piped());
+    // Don't leave a browser running if the site task is aborted (--timeout-per-site)
+    cmd.kill_on_drop(true);
     let mut child = match cmd.spawn() {
         Ok(c) => c,
-        Err(_) => return None,
+        Err(e) => return Err(Outcome::Unavailable(format!("failed to start node: {e}"))),
     };
     let mut out = String::new();
     if let Some(mut so) = child.stdout.take() {
@@ -1997,9 +2134,9 @@ async fn fetch_csrin_playwright_html(query: &str, cookie: Option<String>) -> Opt
     }
     let _ = child.wait().await;
     if out.trim().is_empty() {
-        None
+        Err(Outcome::EmptyResponse)
     } else {
-        Some(out)
+        Ok(out)
     }
 }
 
@@ -2042,9 +2179,10 @@ fn collect_title_url_pairs(v: &Value, out: &mut Vec<SearchResult>) {
                     u
                 };
                 out.push(SearchResult {
-                    site: "gog-games".to_string(),
+                    site: "gog-games".into(),
                     title: t.to_string(),
                     url: u_abs,
+                    ..Default::default()
                 });
             }
             for val in map.values() {
@@ -2104,22 +2242,12 @@ mod tests {
         assert!(urls.contains(&"https://gog-games.to/game/four"));
     }
 
-    #[test]
-    fn normalize_title_csrin_removes_forum_prefix() {
-        let s = "Main Forum • Elden Ring";
-        assert_eq!(normalize_title("csrin", s), "Elden Ring");
-    }
-
-    #[test]
-    fn normalize_title_csrin_removes_re_prefix() {
-        let s = "Re: Elden Ring Discussion";
-        assert_eq!(normalize_title("csrin", s), "Elden Ring Discussion");
-    }
-
-    #[test]
-    fn normalize_title_csrin_combined() {
-        let s = "Main Forum • Re: Some Game Title";
-        assert_eq!(normalize_title("csrin", s), "Some Game Title");
+    fn gog_rules() -> website_searcher_core::models::SiteRules {
+        website_searcher_core::config::site_configs()
+            .into_iter()
+            .find(|s| s.name == "gog-games")
+            .map(|s| s.rules)
+            .unwrap()
     }
 
     #[test]
@@ -2129,14 +2257,17 @@ mod tests {
                 site: "gog-games".into(),
                 title: "Elden Ring".into(),
                 url: "https://gog-games.to/game/elden-ring".into(),
+                ..Default::default()
             },
             SearchResult {
                 site: "gog-games".into(),
                 title: "Elden Ring".into(),
                 url: "https://gog-games.to/search?q=elden".into(),
+                ..Default::default()
             },
         ];
         filter_results_by_query_strict(&mut results, "elden ring");
+        gog_rules().apply(&mut results);
         assert_eq!(results.len(), 1);
         assert!(results[0].url.contains("/game/"));
     }
@@ -2147,6 +2278,7 @@ mod tests {
             site: "gog-games".into(),
             title: "Some Title".into(),
             url: "https://gog-games.to/games/elden%20ring-deluxe".into(),
+            ..Default::default()
         }];
         filter_results_by_query_strict(&mut results, "elden ring");
         assert_eq!(results.len(), 1);
@@ -2164,12 +2296,6 @@ mod tests {
         assert_eq!(normalize_title("ankergames", s), "Game Without Size");
     }
 
-    #[test]
-    fn normalize_title_csrin_re_without_space() {
-        let s = "Re:Some Topic";
-        assert_eq!(normalize_title("csrin", s), "Some Topic");
-    }
-
     #[test]
     fn collect_title_url_pairs_handles_href_field() {
         let v = serde_json::json!({
@@ -2234,6 +2360,7 @@ mod tests {
             site: "gog-games".into(),
             title: "Some Title".into(),
             url: "https://gog-games.to/game/eldenring".into(),
+            ..Default::default()
         }];
         filter_results_by_query_strict(&mut results, "elden ring");
         assert_eq!(results.len(), 1);
@@ -2245,6 +2372,7 @@ mod tests {
             site: "gog-games".into(),
             title: "Elden Ring".into(),
             url: "https://gog-games.to/games/elden-ring".into(),
+            ..Default::default()
         }];
         filter_results_by_query_strict(&mut results, "elden ring");
         assert_eq!(results.len(), 1);
@@ -2256,7 +2384,7 @@ mod tests {
         unsafe { std::env::set_var("CS_PLAYWRIGHT_HTML", "<html>mock</html>") };
         let result = fetch_csrin_playwright_html("test", None).await;
         unsafe { std::env::remove_var("CS_PLAYWRIGHT_HTML") };
-        assert!(result.is_some());
+        assert!(result.is_ok());
         assert!(result.unwrap().contains("mock"));
     }
 }
@@ -0,0 +1,1356 @@
+//! Search pipeline shared by single-query, batch and TUI modes.
+//!
+//! Interactive callers pass an event channel to follow each site's progress;
+//! [`run_search_streaming`] hands out each site's results as it finishes.
+
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::stream::{FuturesUnordered, StreamExt};
+use reqwest::Client;
+use reqwest::header::{COOKIE, HeaderMap as ReqHeaderMap};
+use tokio::sync::{Mutex, Semaphore, mpsc};
+
+use website_searcher_core::analyzer::deduplicate_results;
+use website_searcher_core::auth::Accounts;
+use website_searcher_core::budget::DailyBudget;
+use website_searcher_core::cache::SearchCache;
+use website_searcher_core::capability::{self, QueryCapabilities, QuerySupport};
+use website_searcher_core::cf::{self, fetch_via_solver};
+use website_searcher_core::crawl;
+use website_searcher_core::debug_dump::DebugDir;
+use website_searcher_core::fallback::{FallbackReport, Outcome};
+use website_searcher_core::fetcher::{self, Fetched};
+use website_searcher_core::ignore::IgnoreList;
+use website_searcher_core::learned::{AnchorPattern, LearnedPatterns};
+use website_searcher_core::limits::ResultCaps;
+use website_searcher_core::models::{Provenance, SearchKind, SearchResult, SiteConfig};
+use website_searcher_core::monitoring::{
+    self, SearchPhase, SearchTimings, SiteHealth, SiteTimings,
+};
+use website_searcher_core::notes::{NoteStatus, Notes};
+use website_searcher_core::page_store::PageStore;
+use website_searcher_core::pagination;
+use website_searcher_core::parser::{
+    QueryVariants, fetched_now, lowercase_into, parse_results_blocking,
+    parse_results_blocking_with, stamp_source,
+};
+use website_searcher_core::profile::SearchProfile;
+use website_searcher_core::query::{build_search_url, normalize_query};
+use website_searcher_core::query_parser::{MultiQuery, filter_results};
+use website_searcher_core::rate_limiter::RateLimiter;
+use website_searcher_core::route::{Route, Routes};
+use website_searcher_core::sanitize::sanitize_results;
+use website_searcher_core::stream::{SearchStream, StreamSender};
+use website_searcher_core::{canonical, feed, liveness, paths, session, sitemap, steam};
+
+use crate::{
+    cookie_header_map, fetch_csrin_playwright_html, fetch_gog_games_ajax_json,
+    filter_results_by_query_strict, normalize_title,
+};
+
+/// Pause before the sites that failed are searched once more
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Per-run settings shared by every site task
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    /// Maximum results kept per site
+    pub limit: usize,
+    /// Total and per-segment caps on the combined results
+    pub caps: ResultCaps,
+    /// Print per-site diagnostics and write debug HTML samples
+    pub debug: bool,
+    /// Route Cloudflare-protected sites through FlareSolverr
+    pub use_cf: bool,
+    /// FlareSolverr endpoint
+    pub cf_url: String,
+    /// Optional Cookie header forwarded to sites and the solver
+    pub cookie_headers: Option<ReqHeaderMap>,
+    /// Accounts from `[accounts]` in config.toml, signed in to when their
+    /// site is searched without --cookie
+    pub accounts: Option<Arc<Accounts>>,
+    /// Skip the Playwright path for cs.rin.ru
+    pub no_playwright: bool,
+    /// Print a one-line progress indicator to stderr
+    pub show_progress: bool,
+    /// Wall-clock budget for each site, covering every fallback it tries
+    pub site_timeout: Option<Duration>,
+    /// Reproducible output: strict (site, rank, url) order and no fetch times
+    pub deterministic: bool,
+    /// Proxy routes for sites, from `[search.routes]` in config.toml
+    pub routes: Routes,
+    /// Look up the Steam AppID of each result
+    pub steam_appids: bool,
+    /// Search the sites that failed with a network or server error once more
+    /// after the others finish
+    pub retry_failed: bool,
+    /// Drop results whose note has one of these statuses
+    pub hide_status: Vec<NoteStatus>,
+    /// --fast or --thorough; the fields above already reflect it, this adds
+    /// result pages past the first and link verification
+    pub profile: SearchProfile,
+    /// Today's requests per site and their budgets from `[search.daily_budget]`
+    /// (`None` with --replay, which never reaches the sites)
+    pub budget: Option<Arc<DailyBudget>>,
+    /// Where the search's time goes, with --timings
+    pub timings: Option<Arc<SearchTimings>>,
+    /// Where each site's raw results go as soon as it finishes, with --stream
+    pub stream: Option<StreamSender>,
+}
+
+impl SearchOptions {
+    /// Add the time since `since` to `site`'s `phase`, with --timings
+    pub fn time(&self, site: &str, phase: SearchPhase, since: Instant) {
+        if let Some(timings) = &self.timings {
+            timings.record_since(site, phase, since);
+        }
+    }
+}
+
+/// Per-site progress reported while a search runs
+#[derive(Debug, Clone)]
+pub enum SearchEvent {
+    /// The site got a concurrency slot and its search started
+    Started { site: String },
+    /// Fetching the search page directly (or through Playwright)
+    Fetching { site: String },
+    /// Fetching the search page through FlareSolverr
+    Solving { site: String },
+    /// Parsing the fetched HTML
+    Parsing { site: String },
+    /// The site finished; `results` are its raw results
+    Completed {
+        site: String,
+        results: Vec<SearchResult>,
+    },
+    /// The site was abandoned, e.g. after --timeout-per-site
+    Failed { site: String, error: String },
+}
+
+type EventSender = mpsc::UnboundedSender<SearchEvent>;
+
+fn emit(events: &Option<EventSender>, event: SearchEvent) {
+    if let Some(tx) = events {
+        // The receiver going away (e.g. TUI closed) must not fail the search
+        let _ = tx.send(event);
+    }
+}
+
+/// Search all `sites` concurrently and return the raw combined results.
+///
+/// Results are not yet filtered or deduplicated; see [`post_process`].
+pub async fn run_search(
+    client: &Client,
+    sites: Vec<SiteConfig>,
+    multi_query: &MultiQuery,
+    normalized: &str,
+    opts: &SearchOptions,
+    rate_limiter: Option<Arc<Mutex<RateLimiter>>>,
+) -> Vec<SearchResult> {
+    run_search_with_events(
+        client,
+        sites,
+        multi_query,
+        normalized,
+        opts,
+        rate_limiter,
+        None,
+    )
+    .await
+}
+
+/// [`run_search`] that also reports per-site [`SearchEvent`]s on `events`.
+///
+/// With an event channel, timeouts are reported as [`SearchEvent::Failed`]
+/// instead of being printed. Sites start slowest first by their recent
+/// [`SiteTimings`] (in the given order with `--deterministic`), so slow sites
+/// don't end up waiting for a slot behind quick ones.
+pub async fn run_search_with_events(
+    client: &Client,
+    mut sites: Vec<SiteConfig>,
+    multi_query: &MultiQuery,
+    normalized: &str,
+    opts: &SearchOptions,
+    rate_limiter: Option<Arc<Mutex<RateLimiter>>>,
+    events: Option<EventSender>,
+) -> Vec<SearchResult> {
+    let semaphore = Arc::new(Semaphore::new(3));
+    let mut tasks = FuturesUnordered::new();
+
+    // Sites that every segment's site: operators leave out aren't searched
+    if !multi_query.is_empty() {
+        sites.retain(|s| !multi_query.segments_for_site(&s.name).is_empty());
+    }
+    let show_progress = opts.show_progress;
+    let site_names: Vec<String> = sites.iter().map(|s| s.name.clone()).collect();
+    let total_sites = site_names.len();
+    if show_progress {
+        eprintln!(
+            "{}",
+            t!(
+                "progress-searching",
+                count = total_sites,
+                sites = site_names.join(", ")
+            )
+        );
+    }
+
+    let timings_path = paths::site_timings_file();
+    let mut timings = SiteTimings::load(&timings_path);
+    if !opts.deterministic {
+        timings.slowest_first(&mut sites, |s| s.name.as_str());
+    }
+    let patterns_path = paths::learned_patterns_file();
+    let learned = Arc::new(std::sync::Mutex::new(LearnedPatterns::load(&patterns_path)));
+    let capability_path = paths::query_capability_file();
+    let capabilities = QueryCapabilities::load(&capability_path);
+    let ignored_before: HashSet<String> = capabilities
+        .ignored()
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let capabilities = Arc::new(std::sync::Mutex::new(capabilities));
+    // Not with --deterministic, or --replay, whose pages don't answer new URLs
+    let probing = !opts.deterministic
+        && !session::active().is_some_and(|s| s.mode() == session::Mode::Replay);
+
+    let mut combined: Vec<SearchResult> = Vec::new();
+    let mut sites_completed = 0usize;
+    // Results per site that finished, for the site health streaks
+    let mut found: Vec<(String, usize)> = Vec::new();
+    // Sites that failed with a network or server error, searched once more
+    // after the rest with --retry-failed
+    let mut retry: Vec<SiteConfig> = Vec::new();
+    let mut retrying = false;
+    let mut round = sites;
+    loop {
+        let queued = Instant::now();
+        for mut site in round {
+            // A site whose daily budget is spent is left out until the day ends
+            if let Some(limit) = opts.budget.as_ref().and_then(|b| b.spent(&site.name)) {
+                emit(
+                    &events,
+                    SearchEvent::Failed {
+                        site: site.name.clone(),
+                        error: t!("site-over-budget-short", limit = limit),
+                    },
+                );
+                if events.is_none() {
+                    eprintln!(
+                        "{}",
+                        t!("site-over-budget", site = site.name.as_str(), limit = limit)
+                    );
+                }
+                continue;
+            }
+            let permit = semaphore.clone().acquire_owned().await.unwrap();
+            opts.time(&site.name, SearchPhase::Queue, queued);
+            let client = client.clone();
+            let queries = multi_query.site_searches(&site.name, normalized);
+            let opts = opts.clone();
+            let rate_limiter = rate_limiter.clone();
+            let events = events.clone();
+            let learned = learned.clone();
+            // Sites whose search ignores the query are searched as listing sites
+            // (not with --deterministic or --replay, which neither probe nor downgrade)
+            let capabilities = probing.then(|| capabilities.clone());
+            if let Some(capabilities) = &capabilities {
+                let downgraded = capabilities
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .downgrade(&mut site);
+                if downgraded && opts.debug {
+                    eprintln!(
+                        "[debug] site={} search ignores the query; filtering its listing page",
+                        site.name
+                    );
+                }
+            }
+            let retry_site = (opts.retry_failed && !retrying).then(|| site.clone());
+            tasks.push(tokio::spawn(async move {
+                let _permit = permit; // hold until task end
+                let started = Instant::now();
+                let site_name = site.name.clone();
+                let site_timeout = opts.site_timeout;
+                emit(
+                    &events,
+                    SearchEvent::Started {
+                        site: site_name.clone(),
+                    },
+                );
+                let memory = SiteMemory::new(&site, &opts, learned, capabilities);
+                let work = search_site_queries(
+                    client,
+                    site,
+                    queries,
+                    opts,
+                    rate_limiter,
+                    events.clone(),
+                    memory,
+                );
+                // Dropping the future on timeout aborts the site, fallbacks included
+                let searched = match site_timeout {
+                    Some(budget) => tokio::time::timeout(budget, work).await.ok(),
+                    None => Some(work.await),
+                };
+                // Keep the report only when every strategy failed, to explain the empty site
+                let (results, exhausted) = match searched {
+                    Some((results, report)) => {
+                        let exhausted =
+                            (results.is_empty() && report.exhausted()).then_some(report);
+                        (Some(results), exhausted)
+                    }
+                    None => (None, None),
+                };
+                match (&results, &exhausted) {
+                    (_, Some(report)) => emit(
+                        &events,
+                        SearchEvent::Failed {
+                            site: site_name.clone(),
+                            error: report.summary(),
+                        },
+                    ),
+                    (Some(results), None) => emit(
+                        &events,
+                        SearchEvent::Completed {
+                            site: site_name.clone(),
+                            results: results.clone(),
+                        },
+                    ),
+                    (None, None) => emit(
+                        &events,
+                        SearchEvent::Failed {
+                            site: site_name.clone(),
+                            error: t!(
+                                "site-timed-out-short",
+                                secs = site_timeout.map(|d| d.as_secs()).unwrap_or_default()
+                            ),
+                        },
+                    ),
+                }
+                // Return site name with results for progress tracking; None means timed out
+                (site_name, results, exhausted, started.elapsed(), retry_site)
+            }));
+        }
+
+        while let Some(joined) = tasks.next().await {
+            if let Ok((site_name, site_results, exhausted, elapsed, retry_site)) = joined {
+                // Reported once its second try is over
+                if let Some(site) =
+                    retry_site.filter(|_| exhausted.as_ref().is_some_and(|r| r.transient()))
+                {
+                    retry.push(site);
+                    continue;
+                }
+                sites_completed += 1;
+                timings.record(&site_name, elapsed);
+                if let Some(results) = &site_results {
+                    found.push((site_name.clone(), results.len()));
+                }
+                let timed_out = site_results.is_none();
+                let mut site_results = site_results.unwrap_or_default();
+                if timed_out && events.is_none() {
+                    let secs = opts.site_timeout.map(|d| d.as_secs()).unwrap_or_default();
+                    if show_progress {
+                        eprintln!();
+                    }
+                    eprintln!(
+                        "{}",
+                        t!("site-timed-out", site = site_name.as_str(), secs = secs)
+                    );
+                }
+                if let Some(report) = exhausted.filter(|_| events.is_none()) {
+                    if show_progress {
+                        eprintln!();
+                    }
+                    eprintln!(
+                        "{}",
+                        t!(
+                            "site-fallbacks-exhausted",
+                            site = site_name.as_str(),
+                            attempts = report.summary()
+                        )
+                    );
+                }
+                if show_progress {
+                    let emoji = if timed_out {
+                        "⏱"
+                    } else if site_results.is_empty() {
+                        "⚪"
+                    } else {
+                        "✅"
+                    };
+                    eprint!(
+                        "\r{} {}",
+                        emoji,
+                        t!(
+                            "progress-site-done",
+                            done = sites_completed,
+                            total = total_sites,
+                            site = site_name.as_str(),
+                            count = site_results.len()
+                        )
+                    );
+                    // Pad with spaces to clear previous longer messages
+                    eprint!("                    ");
+                    use std::io::Write;
+                    let _ = std::io::stderr().flush();
+                }
+                if let Some(stream) = &opts.stream {
+                    stream.send(&site_name, site_results.clone());
+                }
+                combined.append(&mut site_results);
+            }
+        }
+        if retry.is_empty() {
+            break;
+        }
+        retrying = true;
+        round = std::mem::take(&mut retry);
+        if events.is_none() {
+            if show_progress {
+                eprintln!();
+            }
+            let names: Vec<&str> = round.iter().map(|s| s.name.as_str()).collect();
+            eprintln!("{}", t!("sites-retrying", sites = names.join(", ")));
+        }
+        // A fresh start for the fetcher's backoff, after a pause for the
+        // network or the site to recover
+        tokio::time::sleep(RETRY_DELAY).await;
+    }
+    if show_progress {
+        eprintln!(); // Final newline after progress
+    }
+    if let Err(e) = timings.save(&timings_path)
+        && opts.debug
+    {
+        eprintln!("[debug] Failed to save site timings: {}", e);
+    }
+    let health_path = paths::site_health_file();
+    let mut health = SiteHealth::load(&health_path);
+    let found = found.iter().map(|(site, count)| (site.as_str(), *count));
+    for site in health.record_search(normalized, found) {
+        if events.is_none() {
+            eprintln!(
+                "{}",
+                t!(
+                    "site-likely-broken",
+                    site = site.as_str(),
+                    count = monitoring::BROKEN_AFTER
+                )
+            );
+        }
+    }
+    if let Err(e) = health.save(&health_path)
+        && opts.debug
+    {
+        eprintln!("[debug] Failed to save site health: {}", e);
+    }
+    let learned = learned.lock().unwrap_or_else(|e| e.into_inner());
+    if let Err(e) = learned.save(&patterns_path)
+        && opts.debug
+    {
+        eprintln!("[debug] Failed to save learned patterns: {}", e);
+    }
+    let capabilities = capabilities.lock().unwrap_or_else(|e| e.into_inner());
+    for site in capabilities.ignored() {
+        if !ignored_before.contains(site) && events.is_none() {
+            eprintln!("{}", t!("site-ignores-query", site = site));
+        }
+    }
+    if probing
+        && let Err(e) = capabilities.save(&capability_path)
+        && opts.debug
+    {
+        eprintln!("[debug] Failed to save query capabilities: {}", e);
+    }
+    if let Some(budget) = &opts.budget
+        && let Err(e) = budget.save(&paths::request_budget_file())
+        && opts.debug
+    {
+        eprintln!("[debug] Failed to save request counts: {}", e);
+    }
+    combined
+}
+
+/// Cache key for a query: the first segment's normalized search terms
+pub fn cache_key(query: &str, multi_query: &MultiQuery) -> String {
+    match multi_query.first() {
+        Some(first) => first.get_search_terms(),
+        None => normalize_query(query),
+    }
+}
+
+/// Run one query end to end: cache lookup, search, post-processing, cache insert.
+///
+/// Used by the bulk modes (`--batch`, `wishlist search`) that run queries back to back.
+pub async fn search_query(
+    client: &Client,
+    sites: &[SiteConfig],
+    query: &str,
+    opts: &SearchOptions,
+    cache: Option<&mut SearchCache>,
+    rate_limiter: Option<Arc<Mutex<RateLimiter>>>,
+) -> Vec<SearchResult> {
+    if let Some(results) = cache
+        .as_deref()
+        .and_then(|c| cached_results(c, query, opts))
+    {
+        return results;
+    }
+    let (key, mut results) = search_uncached(client, sites, query, opts, rate_limiter).await;
+    if let Some(c) = cache
+        && !results.is_empty()
+    {
+        c.add(key, results.clone());
+    }
+    apply_ignore_and_notes(&mut results, &opts.hide_status);
+    results
+}
+
+/// The cached results for `query`, with the ignore list and notes applied
+pub fn cached_results(
+    cache: &SearchCache,
+    query: &str,
+    opts: &SearchOptions,
+) -> Option<Vec<SearchResult>> {
+    let key = cache_key(query, &MultiQuery::parse(query));
+    let entry = cache.get(&key)?;
+    if opts.debug {
+        eprintln!("[debug] Cache hit for \"{}\"", key);
+    }
+    let mut results = entry.results.clone();
+    apply_ignore_and_notes(&mut results, &opts.hide_status);
+    Some(results)
+}
+
+/// Search for `query` without the cache: its cache key and the results to
+/// cache under it, before the ignore list and notes are applied
+pub async fn search_uncached(
+    client: &Client,
+    sites: &[SiteConfig],
+    query: &str,
+    opts: &SearchOptions,
+    rate_limiter: Option<Arc<Mutex<RateLimiter>>>,
+) -> (String, Vec<SearchResult>) {
+    let multi_query = MultiQuery::parse(query);
+    let normalized = cache_key(query, &multi_query);
+    let combined = run_search(
+        client,
+        sites.to_vec(),
+        &multi_query,
+        &normalized,
+        opts,
+        rate_limiter,
+    )
+    .await;
+    let mut results = post_process(combined, &multi_query, opts);
+    add_steam_appids(client, opts, &mut results).await;
+    verify_links(client, opts, &mut results).await;
+    (normalized, results)
+}
+
+/// Drop results on the ignore list, attach the saved notes and drop results
+/// whose status is in `hide`. Done after caching, so the cache never holds
+/// notes and edits to either list apply to cached results too.
+pub fn apply_ignore_and_notes(results: &mut Vec<SearchResult>, hide: &[NoteStatus]) {
+    IgnoreList::load(&paths::ignore_file()).apply(results);
+    Notes::load(&paths::notes_file()).apply(results, hide);
+}
+
+/// Set each result's Steam AppID when `opts.steam_appids` asks for it
+pub async fn add_steam_appids(client: &Client, opts: &SearchOptions, results: &mut [SearchResult]) {
+    if opts.steam_appids {
+        let matched = steam::annotate(client, results).await;
+        if opts.debug {
+            eprintln!(
+                "[debug] Steam AppIDs found for {matched} of {} results",
+                results.len()
+            );
+        }
+    }
+}
+
+/// Check that each result link still answers when the profile asks for it
+pub async fn verify_links(client: &Client, opts: &SearchOptions, results: &mut [SearchResult]) {
+    if opts.profile.verifies_links() {
+        let summary = liveness::check_results(client, results).await;
+        if opts.debug {
+            eprintln!(
+                "[debug] Link check: {} alive, {} dead, {} blocked, {} unreachable",
+                summary.alive, summary.dead, summary.blocked, summary.unreachable
+            );
+        }
+    }
+}
+
+/// Whether pages of `site` are fetched through FlareSolverr.
+///
+/// Sites that require Cloudflare use the solver; csrin also does when enabled
+/// via env, or when a non-default/local CF URL is provided (for tests).
+fn uses_solver(site: &SiteConfig, opts: &SearchOptions) -> bool {
+    let cf_url = &opts.cf_url;
+    let allow_env = std::env::var("ALLOW_CSRIN_SOLVER")
+        .ok()
+        .map(|v| v == "1")
+        .unwrap_or(false);
+    let cf_local = cf_url.contains("127.0.0.1") || cf_url.contains("localhost");
+    let non_default_cf = cf_url != "http://localhost:8191/v1";
+    let csrin_solver_allowed =
+        site.name.eq_ignore_ascii_case("csrin") && (allow_env || cf_local || non_default_cf);
+    // The solver only fetches pages; forms and GraphQL queries are posted directly
+    let posts = matches!(site.search_kind, SearchKind::PostForm | SearchKind::GraphQL);
+    opts.use_cf && !posts && (site.requires_cloudflare || csrin_solver_allowed)
+}
+
+/// Fetch one page of `site`, through FlareSolverr when the site needs it,
+/// unless this search already fetched it or a URL that redirected to it
+async fn fetch_page(
+    client: &Client,
+    site: &SiteConfig,
+    url: &str,
+    opts: &SearchOptions,
+    rate_limiter: &Option<Arc<Mutex<RateLimiter>>>,
+    events: &Option<EventSender>,
+    pages: &PageStore,
+) -> anyhow::Result<Fetched> {
+    let live = fetch_page_live(client, site, url, opts, rate_limiter, events);
+    pages.get_or_fetch(url, live).await
+}
+
+async fn fetch_page_live(
+    client: &Client,
+    site: &SiteConfig,
+    url: &str,
+    opts: &SearchOptions,
+    rate_limiter: &Option<Arc<Mutex<RateLimiter>>>,
+    events: &Option<EventSender>,
+) -> anyhow::Result<Fetched> {
+    let &SearchOptions {
+        debug,
+        ref cf_url,
+        ref cookie_headers,
+        ..
+    } = opts;
+    if let Some(budget) = &opts.budget {
+        budget.spend(&site.name)?;
+    }
+    if uses_solver(site, opts) {
+        emit(
+            events,
+            SearchEvent::Solving {
+                site: site.name.clone(),
+            },
+        );
+        if debug {
+            eprintln!("[debug] site={} using FlareSolverr {}", site.name, cf_url);
+        }
+        let solving = Instant::now();
+        let body = if cookie_headers.is_some() {
+            cf::fetch_via_solver_with_headers(client, url, cf_url, cookie_headers.clone()).await
+        } else {
+            fetch_via_solver(client, url, cf_url).await
+        };
+        opts.time(&site.name, SearchPhase::Solver, solving);
+        let body = body?;
+        // The solver follows redirects without reporting where they led
+        Ok(Fetched {
+            url: url.to_string(),
+            body,
+        })
+    } else {
+        emit(
+            events,
+            SearchEvent::Fetching {
+                site: site.name.clone(),
+            },
+        );
+        // Routed sites fetch directly through their proxy, not through the solver
+        let client = &opts.routes.client_for(&site.name, client)?;
+        let rate_limiter_ref = if let Some(rl) = rate_limiter {
+            Some(&mut *rl.lock().await)
+        } else {
+            None
+        };
+
+        let fetching = Instant::now();
+        let fetched =
+            fetcher::fetch_site_page(client, site, url, cookie_headers.clone(), rate_limiter_ref)
+                .await;
+        opts.time(&site.name, SearchPhase::Fetch, fetching);
+        fetched
+    }
+}
+
+/// What a site's queries share during one search: the pages fetched so far,
+/// the link pattern learned for the site and whether its search honors queries
+struct SiteMemory {
+    pages: PageStore,
+    /// The pattern learned on earlier searches, tried before scanning every
+    /// link (not with --deterministic)
+    hint: Option<AnchorPattern>,
+    /// Every site's patterns, saved when the search ends
+    learned: Arc<std::sync::Mutex<LearnedPatterns>>,
+    /// Every site's query support, saved when the search ends (`None` with
+    /// --deterministic or --replay, which do not probe)
+    capabilities: Option<Arc<std::sync::Mutex<QueryCapabilities>>>,
+}
+
+impl SiteMemory {
+    fn new(
+        site: &SiteConfig,
+        opts: &SearchOptions,
+        learned: Arc<std::sync::Mutex<LearnedPatterns>>,
+        capabilities: Option<Arc<std::sync::Mutex<QueryCapabilities>>>,
+    ) -> Self {
+        let hint = if opts.deterministic {
+            None
+        } else {
+            let patterns = learned.lock().unwrap_or_else(|e| e.into_inner());
+            patterns.get(&site.name).cloned()
+        };
+        Self {
+            pages: PageStore::default(),
+            hint,
+            learned,
+            capabilities,
+        }
+    }
+
+    /// Parse a page of `site`, remembering the link pattern its results came
+    /// from when the site's selector found nothing
+    async fn parse(&self, site: &SiteConfig, html: String, query: &str) -> Vec<SearchResult> {
+        let parsed = parse_results_blocking_with(site, html, query, self.hint.clone()).await;
+        if let Some(pattern) = parsed.learned {
+            let mut learned = self.learned.lock().unwrap_or_else(|e| e.into_inner());
+            learned.learn(&site.name, pattern);
+        }
+        parsed.results
+    }
+}
+
+/// Search `site` for each of `queries` in turn, within the site's one
+/// concurrency slot and timeout. Pages are fetched once however many queries
+/// need them. Each result is tagged with the segment of the query that found
+/// it, which `--per-segment-limit` counts by.
+async fn search_site_queries(
+    client: Client,
+    site: SiteConfig,
+    queries: Vec<(usize, String)>,
+    opts: SearchOptions,
+    rate_limiter: Option<Arc<Mutex<RateLimiter>>>,
+    events: Option<EventSender>,
+    memory: SiteMemory,
+) -> (Vec<SearchResult>, FallbackReport) {
+    let mut opts = opts;
+    if opts.cookie_headers.is_none()
+        && let Some(accounts) = &opts.accounts
+    {
+        // Signed in only now, so unsearched sites never log in
+        let cookie = accounts.cookie_for(&site).await;
+        if opts.debug && cookie.is_some() {
+            eprintln!("[debug] site={} signed in", site.name);
+        }
+        opts.cookie_headers = cookie_header_map(cookie.as_deref());
+    }
+    let mut results = Vec::new();
+    let mut report = FallbackReport::new(&site.name);
+    for (segment, query) in queries {
+        let (mut found, tried) = search_site(
+            client.clone(),
+            site.clone(),
+            query,
+            opts.clone(),
+            rate_limiter.clone(),
+            events.clone(),
+            &memory,
+        )
+        .await;
+        for r in &mut found {
+            r.segment = Some(segment);
+        }
+        results.append(&mut found);
+        report.attempts.extend(tried.attempts);
+    }
+    (results, report)
+}
+
+/// Fetch and parse a single site for `query`, applying site-specific fallbacks,
+/// then the site's rules and query filtering.
+///
+/// Also returns the report of every strategy tried and how it went.
+async fn search_site(
+    client: Client,
+    site: SiteConfig,
+    query: String,
+    opts: SearchOptions,
+    rate_limiter: Option<Arc<Mutex<RateLimiter>>>,
+    events: Option<EventSender>,
+    memory: &SiteMemory,
+) -> (Vec<SearchResult>, FallbackReport) {
+    let SearchOptions { limit, debug, .. } = opts;
+    let (mut results, report, last_html) = if site.name.eq_ignore_ascii_case("csrin") {
+        race_csrin_feed(
+            &client,
+            &site,
+            &query,
+            &opts,
+            &rate_limiter,
+            &events,
+            memory,
+        )
+        .await
+    } else {
+        fetch_site_results(
+            &client,
+            &site,
+            &query,
+            &opts,
+            &rate_limiter,
+            &events,
+            memory,
+        )
+        .await
+    };
+    if debug {
+        let mut via: BTreeMap<Provenance, usize> = BTreeMap::new();
+        for p in results.iter().filter_map(|r| r.provenance) {
+            *via.entry(p).or_default() += 1;
+        }
+        let via: Vec<String> = via.iter().map(|(p, n)| format!("{p}:{n}")).collect();
+        eprintln!(
+            "[debug] site={} results={} via={} (pre-truncate)",
+            site.name,
+            results.len(),
+            via.join(",")
+        );
+        eprintln!("[debug] site={} attempts: {}", site.name, report.summary());
+        if results.is_empty() {
+            // Keep the last page for inspection; old samples are pruned
+            match DebugDir::default().write(&format!("{}_sample", site.name), "html", &last_html) {
+                Ok(path) => eprintln!("[debug] wrote {}", path.display()),
+                Err(e) => eprintln!("[debug] failed to write {} sample: {}", site.name, e),
+            }
+        }
+    }
+    // The site's rules, for results that did not come through the parser
+    site.rules.apply(&mut results);
+    if matches!(
+        site.search_kind,
+        SearchKind::FrontPage | SearchKind::ListingPage | SearchKind::PhpBBSearch
+    ) {
+        // csrin: avoid URL-based query matches (phpBB adds hilit=<query> to every
+        // result link). Only keep titles that include the query.
+        let (mut tl, mut ul) = (String::new(), String::new());
+        if site.name.eq_ignore_ascii_case("csrin") {
+            let q_lower = query.to_lowercase();
+            results.retain(|r| {
+                lowercase_into(&mut tl, &r.title);
+                tl.contains(&q_lower)
+            });
+        } else {
+            let variants = QueryVariants::new(&query);
+            results.retain(|r| {
+                lowercase_into(&mut tl, &r.title);
+                lowercase_into(&mut ul, &r.url);
+                variants.matches(&tl, &ul)
+            });
+        }
+    }
+    // Normalize titles for nicer output, then drop markup and control characters
+    // a page could use to garble the terminal
+    for r in &mut results {
+        r.title = normalize_title(site.name.as_str(), &r.title);
+    }
+    sanitize_results(&mut results);
+    if !results.is_empty() {
+        results.truncate(limit);
+    }
+    (results, report)
+}
+
+/// csrin's search (Playwright, then search.php) raced against its Atom feed
+/// (see [`feed::race_feed`])
+async fn race_csrin_feed(
+    client: &Client,
+    site: &SiteConfig,
+    query: &str,
+    opts: &SearchOptions,
+    rate_limiter: &Option<Arc<Mutex<RateLimiter>>>,
+    events: &Option<EventSender>,
+    memory: &SiteMemory,
+) -> (Vec<SearchResult>, FallbackReport, String) {
+    let search = fetch_site_results(client, site, query, opts, rate_limiter, events, memory);
+    // A broken route is reported by the search
+    let Ok(routed) = opts.routes.client_for(&site.name, client) else {
+        return search.await;
+    };
+    // The last page the search fetched, once it finishes
+    let mut last_html = None;
+    let search = async {
+        let (results, report, html) = search.await;
+        last_html = Some(html);
+        (results, report)
+    };
+    let fed = async {
+        let fetching = Instant::now();
+        let fed =
+            feed::fetch_forum_feed(&routed, site, query, rate_limiter.as_deref(), opts.debug).await;
+        opts.time(&site.name, SearchPhase::Fetch, fetching);
+        fed
+    };
+    let (results, report) = feed::race_feed(site, query, search, fed).await;
+    if last_html.is_none() && opts.debug {
+        eprintln!("[debug] site={} feed answered before the search", site.name);
+    }
+    (results, report, last_html.unwrap_or_default())
+}
+
+/// Run `site`'s fetch strategies for `query` and return what they found, the
+/// report and the last page fetched (for the debug sample)
+async fn fetch_site_results(
+    client: &Client,
+    site: &SiteConfig,
+    query: &str,
+    opts: &SearchOptions,
+    rate_limiter: &Option<Arc<Mutex<RateLimiter>>>,
+    events: &Option<EventSender>,
+    memory: &SiteMemory,
+) -> (Vec<SearchResult>, FallbackReport, String) {
+    let site_name = site.name.clone();
+    let status = |make: fn(String) -> SearchEvent| emit(events, make(site_name.clone()));
+    let &SearchOptions {
+        limit,
+        debug,
+        use_cf,
+        ref cf_url,
+        ref cookie_headers,
+        no_playwright,
+        ..
+    } = opts;
+    let base_url = match site.search_kind {
+        SearchKind::ListingPage => site
+            .listing_path
+            .clone()
+            .unwrap_or(site.base_url.clone())
+            .to_string(),
+        SearchKind::PhpBBSearch => build_search_url(site, query), // Uses search.php URL
+        _ => build_search_url(site, query),
+    };
+    // Build page URLs: for most sites, just one URL. csrin uses PhpBBSearch URL directly.
+    // Listing sites with a crawl config queue their archive pages as they are fetched.
+    let mut page_urls: VecDeque<String> = VecDeque::from([base_url.clone()]);
+    let (mut page_number, mut crawl_depth) = (0, 0);
+
+    let mut results: Vec<SearchResult> = Vec::new();
+    let mut report = FallbackReport::new(&site.name);
+    // A site whose route is broken is skipped rather than fetched without its proxy
+    let routed = match opts.routes.client_for(&site.name, client) {
+        Ok(routed) => routed,
+        Err(e) => {
+            let provenance = Provenance::of_page(site.search_kind, uses_solver(site, opts));
+            report.record(provenance, Outcome::Unavailable(e.to_string()));
+            return (results, report, String::new());
+        }
+    };
+    if debug {
+        let route = opts.routes.route_for(&site.name);
+        let note = if route != Route::Direct && uses_solver(site, opts) {
+            " (FlareSolverr fetches are not routed)"
+        } else {
+            ""
+        };
+        eprintln!("[debug] site={} route={}{}", site.name, route, note);
+    }
+    // If requested, try Playwright to load dynamic results (skip when solver is explicitly configured/local)
+    let cf_local = cf_url.contains("127.0.0.1") || cf_url.contains("localhost");
+    let non_default_cf = cf_url != "http://localhost:8191/v1";
+    let prefer_solver = use_cf && (cf_local || non_default_cf);
+    if site.name.eq_ignore_ascii_case("csrin") && !no_playwright && !prefer_solver {
+        let cookie_val = cookie_headers
+            .as_ref()
+            .and_then(|h| h.get(COOKIE))
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string());
+        status(|site| SearchEvent::Fetching { site });
+        let rendering = Instant::now();
+        let rendered = fetch_csrin_playwright_html(query, cookie_val).await;
+        opts.time(&site.name, SearchPhase::Render, rendering);
+        match rendered {
+            Ok(html) => {
+                status(|site| SearchEvent::Parsing { site });
+                if debug {
+                    let _ = DebugDir::default().write("csrin_playwright", "html", &html);
+                }
+                let parsing = Instant::now();
+                results = parse_results_blocking(site, html.clone(), query).await;
+                opts.time(&site.name, SearchPhase::Parse, parsing);
+                stamp_source(
+                    &mut results,
+                    &base_url,
+                    fetched_now(),
+                    Provenance::Playwright,
+                );
+                let outcome = Outcome::of_page(site, &html, query, results.len());
+                report.record(Provenance::Playwright, outcome);
+            }
+            Err(outcome) => report.record(Provenance::Playwright, outcome),
+        }
+    }
+    if site.search_kind == SearchKind::Sitemap {
+        // Sitemap sites fetch the sitemap and matching pages instead of a search page
+        page_urls.clear();
+        let cache_dir = paths::sitemap_dir();
+        results = sitemap::search_sitemap(site, query, limit, Some(&cache_dir), |url| async move {
+            let page = fetch_page(
+                client,
+                site,
+                &url,
+                opts,
+                rate_limiter,
+                events,
+                &memory.pages,
+            )
+            .await;
+            page.ok()
+                .map(|page| page.body)
+                .filter(|html| !html.is_empty())
+        })
+        .await;
+    }
+    // Last page fetched, for the debug sample
+    let mut last_html = String::new();
+    // Final URLs already parsed for this query, so a page reached twice is read once
+    let mut parsed: HashSet<String> = HashSet::new();
+    if results.is_empty() {
+        while let Some(url) = page_urls.pop_front() {
+            page_number += 1;
+            let via_solver = uses_solver(site, opts);
+            let fetched = fetch_page(
+                client,
+                site,
+                &url,
+                opts,
+                rate_limiter,
+                events,
+                &memory.pages,
+            )
+            .await;
+            let (html, fetch_failure) = match fetched {
+                Ok(page) if !parsed.insert(page.url.clone()) => {
+                    if debug {
+                        eprintln!(
+                            "[debug] site={} url={} led to already parsed {}",
+                            site.name, url, page.url
+                        );
+                    }
+                    continue;
+                }
+                Ok(page) => (page.body, None),
+                Err(e) => (String::new(), Some(Outcome::fetch_error(via_solver, &e))),
+            };
+            if debug {
+                eprintln!(
+                    "[debug] site={} url={} html_len={}",
+                    site.name,
+                    url,
+                    html.len()
+                );
+            }
+            if let Some(next) =
+                crawl::next_archive_page(site, crawl_depth, &url, &html, fetched_now())
+            {
+                crawl_depth += 1;
+                page_urls.push_back(next);
+            }
+            status(|site| SearchEvent::Parsing { site });
+            let parsing = Instant::now();
+            let mut page_results = memory.parse(site, html.clone(), query).await;
+            opts.time(&site.name, SearchPhase::Parse, parsing);
+            if page_number == 1 && page_results.is_empty() && fetch_failure.is_none() {
+                probe_query_support(
+                    client,
+                    site,
+                    &html,
+                    via_solver,
+                    opts,
+                    rate_limiter,
+                    events,
+                    memory,
+                )
+                .await;
+            }
+            let mut provenance = Provenance::of_page(site.search_kind, via_solver);
+            report.record(
+                provenance,
+                fetch_failure
+                    .unwrap_or_else(|| Outcome::of_page(site, &html, query, page_results.len())),
+            );
+            // gog-games fallback: request AJAX JSON/fragment when DOM parse is empty
+            if page_results.is_empty() && site.name.eq_ignore_ascii_case("gog-games") {
+                let rate_limiter_ref = if let Some(rl) = rate_limiter {
+                    Some(&mut *rl.lock().await)
+                } else {
+                    None
+                };
+
+                let fetching = Instant::now();
+                let fetched = fetch_gog_games_ajax_json(
+                    if use_cf { client } else { &routed },
+                    site,
+                    query,
+                    use_cf,
+                    cf_url,
+                    cookie_headers.clone(),
+                    debug,
+                    rate_limiter_ref,
+                )
+                .await;
+                opts.time(&site.name, SearchPhase::Fetch, fetching);
+                match fetched {
+                    Ok(r) => {
+                        report.record(Provenance::Ajax, Outcome::Found(r.len()));
+                        page_results = r;
+                        provenance = Provenance::Ajax;
+                    }
+                    Err(outcome) => report.record(Provenance::Ajax, outcome),
+                }
+            }
+            stamp_source(&mut page_results, &url, fetched_now(), provenance);
+            // Extra filtering for gog-games to avoid unrelated pages/cards
+            if site.name.eq_ignore_ascii_case("gog-games") {
+                filter_results_by_query_strict(&mut page_results, query);
+            }
+            // The thorough profile reads further result pages while they find something
+            if !page_results.is_empty()
+                && page_number < opts.profile.pages()
+                && site.crawl.is_none()
+                && page_urls.is_empty()
+                && let Some(next) = pagination::next_page(site, query, page_number, &url, &html)
+            {
+                page_urls.push_back(next);
+            }
+            results.extend(page_results);
+            if debug {
+                last_html = html;
+            }
+            if results.len() >= 5000 {
+                // safety cap
+                break;
+            }
+        }
+    }
+    // csrin: Automatic Playwright fallback if the search page produced nothing and user didn't explicitly request it
+    if site.name.eq_ignore_ascii_case("csrin") && results.is_empty() && !no_playwright {
+        let cookie_val = cookie_headers
+            .as_ref()
+            .and_then(|h| h.get(COOKIE))
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string());
+        let rendering = Instant::now();
+        let rendered = fetch_csrin_playwright_html(query, cookie_val).await;
+        opts.time(&site.name, SearchPhase::Render, rendering);
+        match rendered {
+            Ok(html) => {
+                let parsing = Instant::now();
+                let mut rs = parse_results_blocking(site, html.clone(), query).await;
+                opts.time(&site.name, SearchPhase::Parse, parsing);
+                stamp_source(&mut rs, &base_url, fetched_now(), Provenance::Playwright);
+                let outcome = Outcome::of_page(site, &html, query, rs.len());
+                report.record(Provenance::Playwright, outcome);
+                if !rs.is_empty() {
+                    results = rs;
+                }
+            }
+            Err(outcome) => report.record(Provenance::Playwright, outcome),
+        }
+    }
+    (results, report, last_html)
+}
+
+/// Probe `site` after its search page had nothing for the query, unless it was
+/// probed recently: when the page still had result links, search for each of
+/// [`capability::PROBE_QUERIES`] and record whether the same links come back
+/// every time. Pages from a stand-in solver aren't probed.
+#[allow(clippy::too_many_arguments)]
+async fn probe_query_support(
+    client: &Client,
+    site: &SiteConfig,
+    html: &str,
+    via_solver: bool,
+    opts: &SearchOptions,
+    rate_limiter: &Option<Arc<Mutex<RateLimiter>>>,
+    events: &Option<EventSender>,
+    memory: &SiteMemory,
+) {
+    let Some(capabilities) = &memory.capabilities else {
+        return;
+    };
+    if via_solver && !capability::trusted_solver(&opts.cf_url) {
+        return;
+    }
+    let lock = || capabilities.lock().unwrap_or_else(|e| e.into_inner());
+    if !lock().needs_probe(site) {
+        return;
+    }
+    let searched = capability::result_links(site, html);
+    if searched.is_empty() {
+        return;
+    }
+    let mut support = QuerySupport::Ignored;
+    for url in capability::probe_urls(site) {
+        let fetched = fetch_page(
+            client,
+            site,
+            &url,
+            opts,
+            rate_limiter,
+            events,
+            &memory.pages,
+        )
+        .await;
+        // No verdict without every probe's page
+        let Ok(probe) = fetched else {
+            return;
+        };
+        support = capability::judge(&searched, &capability::result_links(site, &probe.body));
+        if opts.debug {
+            let verdict = match support {
+                QuerySupport::Honored => "honors",
+                QuerySupport::Ignored => "ignores",
+            };
+            eprintln!(
+                "[debug] site={} probed {}: search {} the query",
+                site.name, url, verdict
+            );
+        }
+        // One page with other links is enough to show the query counts
+        if support == QuerySupport::Honored {
+            break;
+        }
+    }
+    lock().record(&site.name, support);
+}
+
+/// Apply advanced query operators, dedupe, sort, and the result caps.
+///
+/// With `opts.deterministic` (--deterministic) results are sorted strictly by
+/// (site, rank, url) and fetch times are dropped, so the same pages always
+/// produce the same output. The caps take results from each site in turn.
+pub fn post_process(
+    combined: Vec<SearchResult>,
+    multi_query: &MultiQuery,
+    opts: &SearchOptions,
+) -> Vec<SearchResult> {
+    let mut combined = filter_by_query(combined, multi_query);
+
+    // First: remove copies of the same page within each site (URLs that
+    // differ only in tracking parameters or a trailing slash), keeping the
+    // higher-ranked copy
+    combined.sort_by(|a, b| {
+        a.site
+            .cmp(&b.site)
+            .then_with(|| a.rank.cmp(&b.rank))
+            .then_with(|| a.url.cmp(&b.url))
+    });
+    canonical::dedup_results(&mut combined);
+
+    // Then: smart cross-site deduplication using title similarity
+    let mut combined = deduplicate_results(combined);
+    sort_for_output(&mut combined, opts);
+
+    opts.caps.apply(combined, multi_query)
+}
+
+/// Apply advanced query operators (site:, -exclude, "phrase", regex:); with a
+/// multi-query, each site's results by the segments that apply to it
+fn filter_by_query(combined: Vec<SearchResult>, multi_query: &MultiQuery) -> Vec<SearchResult> {
+    if multi_query.is_single() {
+        // Single query - use global filtering (backward compatible)
+        if let Some(first) = multi_query.first() {
+            filter_results(combined, first)
+        } else {
+            combined
+        }
+    } else {
+        // Multi-query - filter per-site
+        let mut filtered = Vec::new();
+        let mut by_site: std::collections::HashMap<Arc<str>, Vec<SearchResult>> =
+            std::collections::HashMap::new();
+        for r in combined {
+            by_site.entry(r.site.clone()).or_default().push(r);
+        }
+        for (site, results) in by_site {
+            let site_filtered = multi_query.filter_results_for_site(results, &site);
+            filtered.extend(site_filtered);
+        }
+        filtered
+    }
+}
+
+/// Sort by site then title for output; with `opts.deterministic` strictly by
+/// (site, rank, url), without fetch times
+fn sort_for_output(results: &mut [SearchResult], opts: &SearchOptions) {
+    if opts.deterministic {
+        results.sort_by(SearchResult::page_order);
+        for r in results {
+            r.fetched_at = None;
+        }
+    } else {
+        results.sort_by(SearchResult::output_order);
+    }
+}
+
+/// [`run_search`] that also hands each site's results to `on_site` as soon as
+/// the site finishes, filtered by the query, ignore rules and notes and
+/// sorted as [`post_process`] would, and within `opts.caps.total` counted in
+/// the order they arrive. Results are not compared across sites until the
+/// returned combined results are post-processed.
+pub async fn run_search_streaming(
+    client: &Client,
+    sites: Vec<SiteConfig>,
+    multi_query: &MultiQuery,
+    normalized: &str,
+    opts: &SearchOptions,
+    rate_limiter: Option<Arc<Mutex<RateLimiter>>>,
+    mut on_site: impl FnMut(&[SearchResult]),
+) -> Vec<SearchResult> {
+    let (sender, mut stream) = SearchStream::channel();
+    let streaming = SearchOptions {
+        stream: Some(sender),
+        ..opts.clone()
+    };
+    // The search owns the only sender, so the stream ends with it
+    let search = async move {
+        run_search(
+            client,
+            sites,
+            multi_query,
+            normalized,
+            &streaming,
+            rate_limiter,
+        )
+        .await
+    };
+    let show = async {
+        let mut left = (opts.caps.total > 0).then_some(opts.caps.total);
+        while let Some(site) = stream.next().await {
+            let mut results = filter_by_query(site.results, multi_query);
+            results.sort_by(|a, b| a.rank.cmp(&b.rank).then_with(|| a.url.cmp(&b.url)));
+            canonical::dedup_results(&mut results);
+            apply_ignore_and_notes(&mut results, &opts.hide_status);
+            sort_for_output(&mut results, opts);
+            if let Some(left) = &mut left {
+                results.truncate(*left);
+                *left -= results.len();
+            }
+            if !results.is_empty() {
+                on_site(&results);
+            }
+        }
+    };
+    let (combined, ()) = tokio::join!(search, show);
+    combined
+}
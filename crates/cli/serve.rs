@@ -0,0 +1,191 @@
+//! `serve`: the search over HTTP, for dashboards and scripts on the local
+//! network.
+//!
+//! - `GET /search?q=elden+ring&sites=fitgirl,dodi&limit=5` answers the JSON a
+//!   search prints with `--format json`; `sites` and `limit` are optional
+//! - `GET /sites` lists the sites searched, as `config show` does
+//! - `GET /cache` lists the cached searches, newest first
+//!
+//! Searches share one HTTP client, rate limiter and cache, and run side by
+//! side. Only searches of every site with the configured limit use the cache:
+//! it is keyed by the query alone, so results for a `sites=` or `limit=`
+//! request would stand in for the full search, and the other way round.
+//!
+//! Each search saves the timings, site health, learned patterns, query support
+//! and request counts when it ends. The files are replaced whole (see
+//! [`website_searcher_core::paths::write_atomic`]), so searches ending together
+//! can't tear them; the last to finish wins.
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::sync::Mutex;
+
+use website_searcher_core::cache::SearchCache;
+use website_searcher_core::fetcher::build_http_client;
+use website_searcher_core::models::{SearchResult, SiteConfig};
+use website_searcher_core::rate_limiter::RateLimiter;
+
+use crate::search::{self, SearchOptions};
+
+/// What every request is answered with
+pub struct Server {
+    /// Sites searched, after --sites/--invert-sites; `sites=` picks among them
+    pub sites: Vec<SiteConfig>,
+    pub opts: SearchOptions,
+    /// `None` with --no-cache
+    pub cache: Option<SearchCache>,
+    pub cache_path: PathBuf,
+    pub rate_limiter: Option<Arc<Mutex<RateLimiter>>>,
+}
+
+struct AppState {
+    client: Client,
+    sites: Vec<SiteConfig>,
+    opts: SearchOptions,
+    cache: Option<Mutex<SearchCache>>,
+    cache_path: PathBuf,
+    rate_limiter: Option<Arc<Mutex<RateLimiter>>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchParams {
+    #[serde(default)]
+    q: String,
+    sites: Option<String>,
+    limit: Option<usize>,
+}
+
+/// The routes of `server`
+pub fn router(server: Server) -> Router {
+    let state = AppState {
+        client: build_http_client(),
+        sites: server.sites,
+        opts: server.opts,
+        cache: server.cache.map(Mutex::new),
+        cache_path: server.cache_path,
+        rate_limiter: server.rate_limiter,
+    };
+    Router::new()
+        .route("/search", get(search))
+        .route("/sites", get(sites))
+        .route("/cache", get(cache))
+        .with_state(Arc::new(state))
+}
+
+/// Answer on `addr` until Ctrl-C
+pub async fn run(addr: SocketAddr, server: Server) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("listen on {addr}"))?;
+    let addr = listener.local_addr()?;
+    eprintln!("{}", t!("serve-listening", url = format!("http://{addr}")));
+    axum::serve(listener, router(server))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
+
+fn error(status: StatusCode, message: String) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+async fn search(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SearchParams>,
+) -> Response {
+    let query = params.q.trim();
+    if query.is_empty() {
+        return error(StatusCode::BAD_REQUEST, t!("serve-missing-query"));
+    }
+    let sites = match params.sites.as_deref() {
+        Some(csv) => crate::select_sites(state.sites.clone(), csv, false),
+        None => state.sites.clone(),
+    };
+    if sites.is_empty() {
+        return error(StatusCode::BAD_REQUEST, t!("serve-no-sites"));
+    }
+    let mut opts = state.opts.clone();
+    if let Some(limit) = params.limit {
+        opts.limit = limit;
+    }
+    // The cache is only locked to look up and to insert, never during a search
+    let cache = state
+        .cache
+        .as_ref()
+        .filter(|_| params.sites.is_none() && params.limit.is_none());
+    if let Some(cache) = cache {
+        let cached = search::cached_results(&*cache.lock().await, query, &opts);
+        if let Some(results) = cached {
+            return found(results);
+        }
+    }
+    let rate_limiter = state.rate_limiter.clone();
+    let (key, mut results) =
+        search::search_uncached(&state.client, &sites, query, &opts, rate_limiter).await;
+    if let Some(cache) = cache
+        && !results.is_empty()
+    {
+        let mut cache = cache.lock().await;
+        cache.add(key, results.clone());
+        match cache.save_to_file_sync(&state.cache_path) {
+            Ok(()) => crate::index_results(&cache, opts.debug),
+            Err(e) if opts.debug => eprintln!("[debug] Failed to save cache: {}", e),
+            Err(_) => {}
+        }
+    }
+    search::apply_ignore_and_notes(&mut results, &opts.hide_status);
+    found(results)
+}
+
+/// The answer to a search, as `--format json` prints it
+fn found(results: Vec<SearchResult>) -> Response {
+    Json(json!({
+        "results": results,
+        "count": results.len(),
+    }))
+    .into_response()
+}
+
+async fn sites(State(state): State<Arc<AppState>>) -> Json<Vec<Value>> {
+    Json(
+        state
+            .sites
+            .iter()
+            .map(|site| crate::config_show::site_json(site, &state.opts))
+            .collect(),
+    )
+}
+
+async fn cache(State(state): State<Arc<AppState>>) -> Json<Vec<Value>> {
+    let Some(cache) = &state.cache else {
+        return Json(Vec::new());
+    };
+    let cache = cache.lock().await;
+    Json(
+        cache
+            .entries_newest_first()
+            .filter(|entry| !entry.is_expired())
+            .map(|entry| {
+                json!({
+                    "query": entry.query,
+                    "count": entry.results.len(),
+                    "age_secs": entry.age(),
+                    "expires_in_secs": entry.remaining_ttl(),
+                })
+            })
+            .collect(),
+    )
+}
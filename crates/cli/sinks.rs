@@ -0,0 +1,67 @@
+//! Delivering a search's results: the terminal plus the `[[sinks]]` in
+//! `config.toml`, all sent at once through the core [`Sink`] trait.
+
+use anyhow::Result;
+use futures::future::BoxFuture;
+
+use website_searcher_core::fetcher::build_http_client;
+use website_searcher_core::models::SearchResult;
+use website_searcher_core::output::{self, TableLabels};
+use website_searcher_core::settings::{Settings, default_settings_path};
+use website_searcher_core::sinks::{self, SearchOutput, Sink};
+
+use crate::OutputFormat;
+
+/// Prints the results as a JSON array or the grouped table
+pub struct TerminalSink {
+    pub format: OutputFormat,
+    pub labels: TableLabels,
+    pub hyperlinks: bool,
+}
+
+impl Sink for TerminalSink {
+    fn describe(&self) -> String {
+        "terminal".to_string()
+    }
+
+    fn send<'a>(&'a self, output: SearchOutput<'a>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            match self.format {
+                OutputFormat::Json => output::print_pretty_json(output.results),
+                OutputFormat::Table => {
+                    output::print_table_grouped_with(output.results, &self.labels, self.hyperlinks)
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Send `results` to `terminal` (`None` when the TUI shows them) and, unless
+/// `configured` is false (--no-sinks), to every sink in `config.toml`. A sink
+/// that fails is reported on stderr; the search still succeeds.
+pub async fn deliver(
+    terminal: Option<TerminalSink>,
+    configured: bool,
+    query: &str,
+    results: &[SearchResult],
+) {
+    let mut all: Vec<Box<dyn Sink>> = Vec::new();
+    if let Some(terminal) = terminal {
+        all.push(Box::new(terminal));
+    }
+    if configured {
+        let settings = Settings::load(&default_settings_path());
+        if !settings.sinks.is_empty() {
+            let client = build_http_client();
+            all.extend(settings.sinks.iter().map(|c| c.build(&client)));
+        }
+    }
+    let output = SearchOutput { query, results };
+    for (sink, e) in sinks::send_all(&all, output).await {
+        eprintln!(
+            "{}",
+            t!("sink-failed", sink = sink, error = format!("{e:#}"))
+        );
+    }
+}
@@ -0,0 +1,42 @@
+//! Sites with an `[accounts]` entry are signed in to on first use and the
+//! session is reused by later searches. The password comes from the secret
+//! store.
+
+mod mock_site;
+
+use assert_cmd::prelude::*;
+use mock_site::{MockSite, field, results};
+
+#[tokio::test]
+async fn csrin_login_is_sent_and_reused() {
+    let site = MockSite::start("accounts").await;
+    site.set_secret("csrin-password", "hunter2");
+    site.write_config(
+        "[accounts.csrin]\nusername = \"alice\"\npassword = \"secret:csrin-password\"\n",
+    );
+    site.page("/csrin/forum/ucp.php", "csrin_login.html", 1)
+        .await;
+    site.login(
+        "/csrin/forum/ucp.php",
+        "password=hunter2",
+        &["phpbb3_t_u=42; path=/", "phpbb3_t_k=key; path=/"],
+        1,
+    )
+    .await;
+    site.signed_in_page("/csrin/forum/search.php", "phpbb3_t_u=42", "search.html", 2)
+        .await;
+
+    for _ in 0..2 {
+        let assert = site
+            .search("elden ring", "csrin")
+            .arg("--no-cf")
+            .assert()
+            .success();
+        let found = results(&assert.get_output().stdout);
+        assert!(field(&found, "title").contains(&"Elden Ring Deluxe Edition"));
+    }
+    // The password and the session cookies are only stored encrypted
+    let stored = std::fs::read(site.file("data/secrets.bin")).unwrap();
+    let stored = String::from_utf8_lossy(&stored);
+    assert!(!stored.contains("hunter2") && !stored.contains("phpbb3_t_k"));
+}
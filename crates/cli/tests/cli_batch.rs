@@ -0,0 +1,54 @@
+mod isolated;
+
+use assert_cmd::Command;
+use mockito::Server;
+
+#[tokio::test]
+async fn batch_reads_stdin_and_emits_ndjson_per_query() {
+    let mut server = Server::new_async().await;
+    let _m = server
+        .mock("POST", "/")
+        .with_status(200)
+        .with_body(r#"{"solution":{"response":"<html><h2 class=\"entry-title\"><a href=\"https://fitgirl-repacks.site/elden-ring/\">Elden Ring Deluxe</a></h2></html>"},"status":"ok"}"#)
+        .expect_at_least(2)
+        .create_async()
+        .await;
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
+    cmd.args([
+        "--batch",
+        "--sites",
+        "fitgirl",
+        "--cf-url",
+        &server.url(),
+        "--no-cache",
+        "--no-rate-limit",
+    ]);
+    cmd.env("NO_COLOR", "1");
+    cmd.write_stdin("elden ring\n\n# comment\nminecraft\n");
+
+    let assert = cmd.assert().success();
+    let out = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    let lines: Vec<serde_json::Value> = out
+        .lines()
+        .map(|l| serde_json::from_str(l).expect("each line is JSON"))
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["query"], "elden ring");
+    assert_eq!(lines[0]["count"], 1);
+    assert_eq!(
+        lines[0]["results"][0]["url"],
+        "https://fitgirl-repacks.site/elden-ring/"
+    );
+    assert_eq!(lines[1]["query"], "minecraft");
+    assert_eq!(lines[1]["count"], 0);
+}
+
+#[test]
+fn batch_conflicts_with_positional_query() {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
+    cmd.args(["elden ring", "--batch"]);
+    cmd.assert().failure();
+}
@@ -0,0 +1,35 @@
+//! `[search.daily_budget]`: a site is skipped once it got its requests for the day.
+
+mod mock_site;
+
+use assert_cmd::prelude::*;
+use mock_site::{MockSite, results};
+
+#[tokio::test]
+async fn site_is_skipped_once_its_daily_budget_is_spent() {
+    let site = MockSite::start("budget").await;
+    site.write_config("[search.daily_budget]\nfrontpage = 2\n");
+    // Fetched by the first search only
+    site.page("/front/", "front.html", 1).await;
+    site.page("/front/page/2/", "front_page2.html", 1).await;
+
+    let assert = site.search("elden ring", "frontpage").assert().success();
+    assert_eq!(results(&assert.get_output().stdout).len(), 2);
+
+    let assert = site.search("elden ring", "frontpage").assert().success();
+    assert!(results(&assert.get_output().stdout).is_empty());
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(
+        stderr.contains("frontpage skipped: its daily budget of 2 requests is spent"),
+        "{stderr}"
+    );
+
+    let stats = site
+        .cli()
+        .args(["stats", "--sites", "frontpage", "--format", "json"])
+        .assert()
+        .success();
+    let rows: serde_json::Value = serde_json::from_slice(&stats.get_output().stdout).unwrap();
+    assert_eq!(rows[0]["requests_today"], 2);
+    assert_eq!(rows[0]["daily_budget"], 2);
+}
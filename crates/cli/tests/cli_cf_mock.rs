@@ -1,3 +1,5 @@
+mod isolated;
+
 use assert_cmd::prelude::*;
 use mockito::Server;
 use std::process::Command;
@@ -13,7 +15,8 @@ async fn cli_with_cf_mock_produces_results() {
         .create_async()
         .await;
 
-    let mut cmd = Command::cargo_bin("website-searcher").expect("binary built");
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
     cmd.args([
         "elden ring",
         "--limit",
@@ -44,7 +47,8 @@ async fn cli_table_format_groups_by_site() {
         .create_async()
         .await;
 
-    let mut cmd = Command::cargo_bin("website-searcher").expect("binary built");
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
     cmd.args([
         "elden ring",
         "--limit",
@@ -78,7 +82,8 @@ async fn cli_csrin_listing_via_solver() {
         .create_async()
         .await;
 
-    let mut cmd = Command::cargo_bin("website-searcher").expect("binary built");
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
     cmd.args([
         "elden ring",
         "--sites",
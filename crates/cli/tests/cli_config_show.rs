@@ -0,0 +1,56 @@
+mod isolated;
+
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+fn config_show(name: &str, config: &str) -> Command {
+    let dir = std::env::temp_dir().join(format!("ws-config-show-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("config.toml"), config).unwrap();
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.args(["config", "show"]);
+    cmd.envs(isolated::env_in(&dir));
+    cmd.env_remove("CF_URL");
+    cmd.env_remove("WEBSITE_SEARCHER_SITES");
+    cmd.env("WEBSITE_SEARCHER_LANG", "en");
+    cmd
+}
+
+#[test]
+fn json_merges_config_file_environment_and_flags() {
+    let mut cmd = config_show(
+        "json",
+        "[search]\nlimit = 25\n\n[search.cookies]\ngog-games = \"session=abc\"\n\n[search.routes]\ncsrin = \"tor\"\n",
+    );
+    cmd.args(["--sites", "csrin,fitgirl", "--limit", "4", "--no-cache"]);
+    cmd.env("CF_URL", "http://solver:8191/v1");
+    let assert = cmd.assert().success();
+    let out: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).expect("json");
+
+    assert_eq!(out["search"]["per_site_limit"], 4);
+    assert_eq!(out["search"]["cloudflare_solver"], "http://solver:8191/v1");
+    assert_eq!(out["search"]["cache"], false);
+    assert_eq!(out["settings"]["search"]["limit"], 25);
+    assert_eq!(out["settings"]["search"]["cookies"]["gog-games"], "***");
+    let sites = out["sites"].as_array().expect("sites");
+    let names: Vec<&str> = sites.iter().map(|s| s["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["fitgirl", "csrin"]);
+    assert_eq!(sites[1]["route"], "tor");
+    assert_eq!(sites[0]["query_param"], "s");
+}
+
+#[test]
+fn table_lists_one_line_per_setting_and_site() {
+    let mut cmd = config_show("table", "");
+    cmd.args(["--format", "table", "--sites", "fitgirl", "--no-cf"]);
+    let assert = cmd.assert().success();
+    let out = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    assert!(out.contains("search.cloudflare_solver = -\n"), "{out}");
+    assert!(out.contains("settings.tui.show_url = true\n"), "{out}");
+    assert!(
+        out.contains(
+            "sites.fitgirl = QueryParam https://fitgirl-repacks.site/ route=direct (cloudflare)\n"
+        ),
+        "{out}"
+    );
+}
@@ -1,3 +1,5 @@
+mod isolated;
+
 use assert_cmd::prelude::*;
 use mockito::{Matcher, Server};
 use std::process::Command;
@@ -13,7 +15,8 @@ async fn cli_cookie_forwarded_to_solver_payload() {
         .create_async()
         .await;
 
-    let mut cmd = Command::cargo_bin("website-searcher").expect("binary built");
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
     cmd.args([
         "elden ring",
         "--sites",
@@ -0,0 +1,81 @@
+//! csrin's Atom feed races its search page: whichever finds results first is
+//! used and the other is not waited for.
+
+mod mock_site;
+
+use std::time::{Duration, Instant};
+
+use assert_cmd::prelude::*;
+use mock_site::{MockSite, field, results};
+
+/// Longer than any test should take, so waiting for it would show
+const SLOW: Duration = Duration::from_secs(20);
+
+#[tokio::test]
+async fn feed_wins_over_a_slow_search_page() {
+    let site = MockSite::start("csrin-feed-wins").await;
+    site.slow_page("/csrin/forum/search.php", "search.html", SLOW)
+        .await;
+    site.page("/csrin/forum/feed.php", "feed.xml", 1).await;
+
+    let started = Instant::now();
+    let assert = site
+        .search("elden ring", "csrin")
+        .arg("--no-cf")
+        .assert()
+        .success();
+    assert!(started.elapsed() < SLOW / 2, "waited for the search page");
+    let found = results(&assert.get_output().stdout);
+    assert_eq!(field(&found, "title"), ["Elden Ring Shadow of the Erdtree"]);
+    assert_eq!(field(&found, "provenance"), ["feed"]);
+}
+
+#[tokio::test]
+async fn search_page_wins_over_a_slow_feed() {
+    let site = MockSite::start("csrin-search-wins").await;
+    site.page("/csrin/forum/search.php", "search.html", 1).await;
+    site.slow_page("/csrin/forum/feed.php", "feed.xml", SLOW)
+        .await;
+
+    let started = Instant::now();
+    let assert = site
+        .search("elden ring", "csrin")
+        .arg("--no-cf")
+        .assert()
+        .success();
+    assert!(started.elapsed() < SLOW / 2, "waited for the feed");
+    let found = results(&assert.get_output().stdout);
+    assert!(!found.is_empty());
+    assert!(field(&found, "provenance").iter().all(|p| *p == "html"));
+    assert!(field(&found, "title").contains(&"Elden Ring Deluxe Edition"));
+}
+
+/// With the rate limiter on, the feed takes its turn without holding the
+/// limiter for its request, so a slow search page can't stall it
+#[tokio::test]
+async fn feed_wins_with_rate_limiting_on() {
+    let site = MockSite::start("csrin-feed-rate-limited").await;
+    site.slow_page("/csrin/forum/search.php", "search.html", SLOW)
+        .await;
+    site.page("/csrin/forum/feed.php", "feed.xml", 1).await;
+
+    let started = Instant::now();
+    let assert = site
+        .cli()
+        .args([
+            "elden ring",
+            "--sites",
+            "csrin",
+            "--format",
+            "json",
+            "--no-cache",
+            "--no-playwright",
+            "--no-cf",
+        ])
+        .assert()
+        .success();
+    assert!(started.elapsed() < SLOW / 2, "waited for the search page");
+    let found = results(&assert.get_output().stdout);
+    assert_eq!(field(&found, "title"), ["Elden Ring Shadow of the Erdtree"]);
+    assert_eq!(field(&found, "provenance"), ["feed"]);
+}
@@ -1,3 +1,5 @@
+mod isolated;
+
 use assert_cmd::prelude::*;
 use mockito::Server;
 use std::process::Command;
@@ -15,7 +17,8 @@ async fn cli_dedup_and_limit_json() {
         .await;
 
     // Limit 1 => only 1 result
-    let mut cmd = Command::cargo_bin("website-searcher").expect("binary built");
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
     cmd.args([
         "elden ring",
         "--limit",
@@ -43,7 +46,8 @@ async fn cli_dedup_and_limit_json() {
     assert_eq!(v["results"][0]["site"].as_str().unwrap_or(""), "fitgirl");
 
     // With higher limit, duplicates should be removed and both unique URLs remain
-    let mut cmd2 = Command::cargo_bin("website-searcher").expect("binary built");
+    let mut cmd2 = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd2.envs(isolated::env());
     cmd2.args([
         "elden ring",
         "--limit",
@@ -70,3 +74,42 @@ async fn cli_dedup_and_limit_json() {
     assert!(urls.contains(&"https://fitgirl-repacks.site/game1".to_string()));
     assert!(urls.contains(&"https://fitgirl-repacks.site/game2".to_string()));
 }
+
+#[tokio::test]
+async fn cli_total_limit_caps_combined_results() {
+    let mut server = Server::new_async().await;
+    let body = r#"{"solution":{"response":"<html><h2 class=\"entry-title\"><a href=\"https://fitgirl-repacks.site/game1\">Elden Ring One</a></h2><h2 class=\"entry-title\"><a href=\"https://fitgirl-repacks.site/game2\">Elden Ring Two</a></h2></html>"},"status":"ok"}"#;
+    let _m = server
+        .mock("POST", "/")
+        .with_status(200)
+        .with_body(body)
+        .create_async()
+        .await;
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
+    cmd.args([
+        "elden ring",
+        "--per-site-limit",
+        "5",
+        "--total-limit",
+        "1",
+        "--sites",
+        "fitgirl",
+        "--cf-url",
+        &server.url(),
+        "--format",
+        "json",
+        "--no-cache",
+    ]);
+    cmd.env("NO_COLOR", "1");
+    let assert = cmd.assert().success();
+    let out = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    let v: serde_json::Value = serde_json::from_str(&out).expect("valid json");
+    assert_eq!(v["count"].as_u64().unwrap_or(0), 1);
+    // The site's best-ranked result is the one kept
+    assert_eq!(
+        v["results"][0]["url"].as_str().unwrap_or(""),
+        "https://fitgirl-repacks.site/game1"
+    );
+}
@@ -0,0 +1,75 @@
+mod isolated;
+
+use assert_cmd::prelude::*;
+use mockito::Server;
+use std::process::Command;
+
+fn search(server_url: &str, extra: &[&str]) -> String {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
+    cmd.args([
+        "elden ring",
+        "--sites",
+        "fitgirl",
+        "--cf-url",
+        server_url,
+        "--format",
+        "json",
+        "--no-cache",
+        "--no-rate-limit",
+    ]);
+    cmd.args(extra);
+    cmd.env("NO_COLOR", "1");
+    let assert = cmd.assert().success();
+    String::from_utf8(assert.get_output().stdout.clone()).expect("utf8")
+}
+
+fn urls(out: &str) -> Vec<String> {
+    let v: serde_json::Value = serde_json::from_str(out).expect("valid json");
+    v["results"]
+        .as_array()
+        .expect("results array")
+        .iter()
+        .map(|r| r["url"].as_str().unwrap_or_default().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn deterministic_output_follows_page_rank_and_is_stable() {
+    let mut server = Server::new_async().await;
+    // The page lists "Zeta" before "Alpha"
+    let body = r#"{"solution":{"response":"<html><h2 class=\"entry-title\"><a href=\"https://fitgirl-repacks.site/elden-ring-zeta/\">Elden Ring Zeta</a></h2><h2 class=\"entry-title\"><a href=\"https://fitgirl-repacks.site/elden-ring-alpha/\">Elden Ring Alpha</a></h2></html>"},"status":"ok"}"#;
+    let _m = server
+        .mock("POST", "/")
+        .with_status(200)
+        .with_body(body)
+        .expect_at_least(3)
+        .create_async()
+        .await;
+
+    let first = search(&server.url(), &["--deterministic"]);
+    let second = search(&server.url(), &["--deterministic"]);
+    assert_eq!(first, second, "deterministic runs print identical output");
+    assert_eq!(
+        urls(&first),
+        [
+            "https://fitgirl-repacks.site/elden-ring-zeta/",
+            "https://fitgirl-repacks.site/elden-ring-alpha/",
+        ]
+    );
+    let v: serde_json::Value = serde_json::from_str(&first).unwrap();
+    assert_eq!(v["results"][0]["rank"], 1);
+    assert!(v["results"][0].get("fetched_at").is_none());
+
+    // Default output is sorted by title and keeps fetch times
+    let default = search(&server.url(), &[]);
+    assert_eq!(
+        urls(&default),
+        [
+            "https://fitgirl-repacks.site/elden-ring-alpha/",
+            "https://fitgirl-repacks.site/elden-ring-zeta/",
+        ]
+    );
+    let v: serde_json::Value = serde_json::from_str(&default).unwrap();
+    assert!(v["results"][0]["fetched_at"].is_u64());
+}
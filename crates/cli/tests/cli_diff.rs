@@ -0,0 +1,117 @@
+mod isolated;
+
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("ws-diff-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn diff_subcommand_reports_added_removed_changed() {
+    let dir = temp_dir("files");
+    let old = dir.join("old.json");
+    let new = dir.join("new.json");
+    std::fs::write(
+        &old,
+        r#"{"results":[
+            {"site":"fitgirl","title":"Elden Ring v1.0","url":"https://fitgirl-repacks.site/elden-ring/"},
+            {"site":"dodi","title":"Elden Ring","url":"https://dodi-repacks.site/elden-ring/"}
+        ],"count":2}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        &new,
+        r#"[
+            {"site":"fitgirl","title":"Elden Ring v1.1","url":"https://fitgirl-repacks.site/elden-ring/"},
+            {"site":"steamrip","title":"Elden Ring","url":"https://steamrip.com/elden-ring/"}
+        ]"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
+    cmd.arg("diff").arg(&old).arg(&new);
+    let assert = cmd.assert().success();
+    let out = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    let v: serde_json::Value = serde_json::from_str(&out).expect("json");
+    assert_eq!(v["summary"]["added"], 1);
+    assert_eq!(v["summary"]["removed"], 1);
+    assert_eq!(v["summary"]["changed"], 1);
+    assert_eq!(v["added"][0]["site"], "steamrip");
+    assert_eq!(v["changed"][0]["new"]["title"], "Elden Ring v1.1");
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
+    cmd.arg("diff")
+        .arg(&old)
+        .arg(&new)
+        .args(["--format", "table"]);
+    let assert = cmd.assert().success();
+    let out = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    assert!(out.contains("+ [steamrip] Elden Ring (https://steamrip.com/elden-ring/)"));
+    assert!(out.contains("- [dodi] Elden Ring"));
+    assert!(out.contains("~ [fitgirl] Elden Ring v1.0 -> Elden Ring v1.1"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn diff_subcommand_rejects_non_results_file() {
+    let dir = temp_dir("bad");
+    let bad = dir.join("bad.json");
+    std::fs::write(&bad, r#"{"nope":true}"#).unwrap();
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
+    cmd.arg("diff").arg(&bad).arg(&bad);
+    cmd.assert().failure();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn diff_cache_compares_against_previous_run() {
+    let mut server = mockito::Server::new_async().await;
+    let _m = server
+        .mock("POST", "/")
+        .with_status(200)
+        .with_body(r#"{"solution":{"response":"<html><h2 class=\"entry-title\"><a href=\"https://fitgirl-repacks.site/elden-ring/\">Elden Ring v1.1</a></h2></html>"},"status":"ok"}"#)
+        .create_async()
+        .await;
+
+    let dir = temp_dir("cache");
+    let cache_dir = dir.join("cache");
+    std::fs::create_dir_all(&cache_dir).unwrap();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    std::fs::write(
+        cache_dir.join("search_cache.json"),
+        format!(
+            r#"{{"entries":[{{"query":"elden ring","results":[
+                {{"site":"fitgirl","title":"Elden Ring v1.0","url":"https://fitgirl-repacks.site/elden-ring/"}}
+            ],"timestamp":{now},"ttl":43200}}],"max_size":3}}"#
+        ),
+    )
+    .unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.args([
+        "elden ring",
+        "--sites",
+        "fitgirl",
+        "--cf-url",
+        &server.url(),
+        "--diff-cache",
+    ]);
+    cmd.envs(isolated::env_in(&dir));
+    let assert = cmd.assert().success();
+    let out = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    let v: serde_json::Value = serde_json::from_str(&out).expect("json");
+    assert_eq!(v["summary"]["changed"], 1);
+    assert_eq!(v["changed"][0]["old"]["title"], "Elden Ring v1.0");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,42 @@
+mod isolated;
+
+use assert_cmd::prelude::*;
+use mockito::{Matcher, Server};
+use std::process::Command;
+
+#[tokio::test]
+async fn failed_site_gets_one_consolidated_warning() {
+    let mut server = Server::new_async().await;
+    let _fit = server
+        .mock("POST", "/")
+        .match_body(Matcher::Regex("fitgirl-repacks.site".into()))
+        .with_status(500)
+        .create_async()
+        .await;
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
+    cmd.args([
+        "elden ring",
+        "--sites",
+        "fitgirl",
+        "--format",
+        "json",
+        "--cf-url",
+        &server.url(),
+        "--no-cache",
+    ]);
+    cmd.env("NO_COLOR", "1");
+    cmd.env("WEBSITE_SEARCHER_LANG", "en");
+
+    let assert = cmd.assert().success();
+    let err = String::from_utf8(assert.get_output().stderr.clone()).expect("utf8");
+    let warnings: Vec<&str> = err.lines().filter(|l| l.contains("[warn]")).collect();
+    assert_eq!(warnings.len(), 1, "stderr: {err}");
+    assert!(
+        warnings[0].starts_with(
+            "[warn] fitgirl: no results from any strategy: solver (solver error: flaresolverr http status 500"
+        ),
+        "stderr: {err}"
+    );
+}
@@ -0,0 +1,56 @@
+//! `grep`: finding the results of earlier searches after the cache dropped them.
+
+mod mock_site;
+
+use assert_cmd::prelude::*;
+use mock_site::MockSite;
+
+#[tokio::test]
+async fn titles_found_by_earlier_searches_are_listed_with_their_queries() {
+    let site = MockSite::start("grep").await;
+    site.page("/front/", "front.html", 1).await;
+    site.page("/front/page/2/", "front_page2.html", 1).await;
+    site.cli()
+        .args([
+            "elden ring",
+            "--sites",
+            "frontpage",
+            "--format",
+            "json",
+            "--no-rate-limit",
+            "--no-playwright",
+            "--cf-url",
+            &format!("{}/v1", site.base()),
+        ])
+        .assert()
+        .success();
+    site.cli().arg("--clear-cache").assert().success();
+
+    let out = site.cli().args(["grep", "nightr"]).assert().success();
+    let found: serde_json::Value = serde_json::from_slice(&out.get_output().stdout).unwrap();
+    let found = found.as_array().unwrap();
+    assert_eq!(found.len(), 1, "{found:?}");
+    assert_eq!(found[0]["title"], "Elden Ring Nightreign");
+    assert_eq!(found[0]["site"], "frontpage");
+    assert_eq!(found[0]["queries"], serde_json::json!(["elden ring"]));
+
+    let out = site
+        .cli()
+        .args(["grep", "NIGHTREIGN", "--format", "table"])
+        .assert()
+        .success();
+    let table = String::from_utf8(out.get_output().stdout.clone()).unwrap();
+    assert!(
+        table.contains("[frontpage] Elden Ring Nightreign"),
+        "{table}"
+    );
+    assert!(table.contains("found by: elden ring"), "{table}");
+
+    let out = site
+        .cli()
+        .args(["grep", "hades", "--format", "table"])
+        .assert()
+        .success();
+    let table = String::from_utf8(out.get_output().stdout.clone()).unwrap();
+    assert!(table.contains("No earlier search found"), "{table}");
+}
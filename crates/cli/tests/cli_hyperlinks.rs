@@ -0,0 +1,61 @@
+mod isolated;
+
+use assert_cmd::prelude::*;
+use mockito::Server;
+use std::process::Command;
+
+const BODY: &str = r#"{"solution":{"response":"<html><h2 class=\"entry-title\"><a href=\"https://fitgirl-repacks.site/elden-ring/\">Elden Ring</a></h2></html>"},"status":"ok"}"#;
+
+async fn run_table(extra: &[&str], force: &str) -> String {
+    let mut server = Server::new_async().await;
+    let _m = server
+        .mock("POST", "/")
+        .with_status(200)
+        .with_body(BODY)
+        .create_async()
+        .await;
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
+    cmd.args([
+        "elden ring",
+        "--sites",
+        "fitgirl",
+        "--cf-url",
+        &server.url(),
+        "--format",
+        "table",
+        "--no-cache",
+    ]);
+    cmd.args(extra);
+    cmd.env("NO_COLOR", "1");
+    cmd.env("FORCE_HYPERLINK", force);
+
+    let assert = cmd.assert().success();
+    String::from_utf8(assert.get_output().stdout.clone()).expect("utf8")
+}
+
+#[tokio::test]
+async fn cli_table_emits_osc8_links_when_forced() {
+    let out = run_table(&[], "1").await;
+    assert!(
+        out.contains(
+            "\x1b]8;;https://fitgirl-repacks.site/elden-ring/\x1b\\Elden Ring\x1b]8;;\x1b\\"
+        ),
+        "missing hyperlink in: {out:?}"
+    );
+}
+
+#[tokio::test]
+async fn cli_no_hyperlinks_prints_plain_table() {
+    let out = run_table(&["--no-hyperlinks"], "1").await;
+    assert!(out.contains("Elden Ring"));
+    assert!(!out.contains('\x1b'));
+}
+
+#[tokio::test]
+async fn cli_piped_output_has_no_hyperlinks_by_default() {
+    let out = run_table(&[], "").await;
+    assert!(out.contains("https://fitgirl-repacks.site/elden-ring/"));
+    assert!(!out.contains('\x1b'));
+}
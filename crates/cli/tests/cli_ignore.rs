@@ -0,0 +1,71 @@
+//! The ignore list: rules added with `ignore` drop results from every search.
+
+mod mock_site;
+
+use assert_cmd::prelude::*;
+use mock_site::{MockSite, field, results};
+
+#[tokio::test]
+async fn ignored_titles_and_urls_are_left_out() {
+    let site = MockSite::start("ignore").await;
+    site.page("/front/", "front.html", 2).await;
+    site.page("/front/page/2/", "front_page2.html", 2).await;
+    let base = site.base();
+
+    site.cli()
+        .args(["ignore", "add", "title", "night\\w+"])
+        .assert()
+        .success();
+    let assert = site.search("elden ring", "frontpage").assert().success();
+    let found = results(&assert.get_output().stdout);
+    assert_eq!(field(&found, "url"), [format!("{base}/front/elden-ring/")]);
+
+    let url = format!("{base}/front/elden-ring/");
+    site.cli()
+        .args(["ignore", "add", "url", &url])
+        .assert()
+        .success();
+    let assert = site.search("elden ring", "frontpage").assert().success();
+    assert!(results(&assert.get_output().stdout).is_empty());
+
+    let list = site
+        .cli()
+        .args(["ignore", "list", "--format", "json"])
+        .assert()
+        .success();
+    let rules: serde_json::Value = serde_json::from_slice(&list.get_output().stdout).unwrap();
+    assert_eq!(rules[0]["kind"], "title");
+    assert_eq!(rules[1]["value"], url.as_str());
+
+    site.cli()
+        .args(["ignore", "remove", "title", "night\\w+"])
+        .assert()
+        .success();
+    let list = site
+        .cli()
+        .args(["ignore", "list", "--format", "json"])
+        .assert()
+        .success();
+    let rules: serde_json::Value = serde_json::from_slice(&list.get_output().stdout).unwrap();
+    assert_eq!(rules.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn invalid_ignore_rules_are_rejected() {
+    let site = MockSite::start("ignore-invalid").await;
+    site.cli()
+        .args(["ignore", "add", "title", "(unclosed"])
+        .assert()
+        .failure();
+    site.cli()
+        .args(["ignore", "add", "site", "example.com"])
+        .assert()
+        .failure();
+    let list = site
+        .cli()
+        .args(["ignore", "list", "--format", "table"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(list.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("The ignore list is empty."), "{stdout}");
+}
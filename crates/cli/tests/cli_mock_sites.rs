@@ -0,0 +1,251 @@
+//! End-to-end searches against the local mock sites in `mock_site`.
+
+mod mock_site;
+
+use assert_cmd::prelude::*;
+use mock_site::{MockSite, field, results};
+
+#[tokio::test]
+async fn search_page_results_go_through_the_whole_engine() {
+    let site = MockSite::start("search").await;
+    site.search_page("/", "s", "elden ring", "search.html")
+        .await;
+
+    let assert = site.search("elden ring", "repacks").assert().success();
+    let found = results(&assert.get_output().stdout);
+    let base = site.base();
+    // Relative links are made absolute, the rules drop the pagination link and
+    // titles without the query are filtered out
+    assert_eq!(
+        field(&found, "url"),
+        [
+            format!("{base}/elden-ring-deluxe/"),
+            format!("{base}/elden-ring-nightreign/"),
+        ]
+    );
+    assert_eq!(field(&found, "provenance"), ["html", "html"]);
+    assert_eq!(
+        found[0]["source_url"],
+        format!("{base}/?s=elden+ring").as_str()
+    );
+}
+
+#[tokio::test]
+async fn challenged_site_is_fetched_through_the_solver() {
+    let site = MockSite::start("solver").await;
+    site.challenge("/shielded/", 0).await;
+    let page = format!("{}/shielded/?s=elden+ring", site.base());
+    site.solver(&page, "search.html").await;
+
+    let assert = site.search("elden ring", "shielded").assert().success();
+    let found = results(&assert.get_output().stdout);
+    assert!(!found.is_empty());
+    assert!(field(&found, "provenance").iter().all(|p| *p == "solver"));
+    assert!(field(&found, "url").contains(&format!("{}/elden-ring-deluxe/", site.base()).as_str()));
+}
+
+#[tokio::test]
+async fn solved_page_is_reused_by_the_next_search() {
+    let site = MockSite::start("rendered").await;
+    site.challenge("/shielded/", 0).await;
+    let page = format!("{}/shielded/?s=elden+ring", site.base());
+    // Asked once: the second search reads the page from the render cache
+    site.solver(&page, "search.html").await;
+
+    for _ in 0..2 {
+        let mut cmd = site.cli();
+        cmd.args([
+            "elden ring",
+            "--sites",
+            "shielded",
+            "--no-rate-limit",
+            "--no-playwright",
+            "--cf-url",
+            &format!("{}/v1", site.base()),
+        ]);
+        let assert = cmd.assert().success();
+        assert!(!results(&assert.get_output().stdout).is_empty());
+        // Forget the search itself so the page is fetched again
+        std::fs::remove_file(site.file("cache").join("search_cache.json")).unwrap();
+    }
+    assert_eq!(
+        std::fs::read_dir(site.file("cache").join("rendered"))
+            .unwrap()
+            .count(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn challenge_without_the_solver_is_reported() {
+    let site = MockSite::start("challenge").await;
+    site.challenge("/shielded/", 1).await;
+
+    let mut cmd = site.search("elden ring", "shielded");
+    cmd.arg("--no-cf");
+    let assert = cmd.assert().success();
+    assert!(results(&assert.get_output().stdout).is_empty());
+    let err = String::from_utf8(assert.get_output().stderr.clone()).expect("utf8");
+    assert!(
+        err.contains("[warn] shielded: no results from any strategy: html (empty response)"),
+        "stderr: {err}"
+    );
+}
+
+#[tokio::test]
+async fn front_page_crawl_reaches_older_posts() {
+    let site = MockSite::start("crawl").await;
+    site.page("/front/", "front.html", 1).await;
+    site.page("/front/page/2/", "front_page2.html", 1).await;
+
+    let assert = site.search("elden ring", "frontpage").assert().success();
+    let found = results(&assert.get_output().stdout);
+    let mut urls = field(&found, "url");
+    urls.sort();
+    let base = site.base();
+    assert_eq!(
+        urls,
+        [
+            format!("{base}/front/elden-ring-nightreign/"),
+            format!("{base}/front/elden-ring/"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn feed_entries_are_filtered_locally() {
+    let site = MockSite::start("feed").await;
+    site.page("/blog/feed/", "feed.xml", 1).await;
+
+    let assert = site.search("elden ring", "feedsite").assert().success();
+    let found = results(&assert.get_output().stdout);
+    assert_eq!(field(&found, "title"), ["Elden Ring Shadow of the Erdtree"]);
+    assert_eq!(field(&found, "provenance"), ["feed"]);
+}
+
+#[tokio::test]
+async fn empty_search_page_falls_back_to_the_json_endpoint() {
+    let site = MockSite::start("ajax").await;
+    site.search_page("/", "search", "elden ring", "gog_search.html")
+        .await;
+    site.page("/search", "gog_search.json", 1).await;
+
+    // Without --no-cf the JSON endpoint would be asked for through the solver
+    let mut cmd = site.search("elden ring", "gog-games");
+    cmd.arg("--no-cf");
+    let assert = cmd.assert().success();
+    let found = results(&assert.get_output().stdout);
+    // The guide is not a game page, so the site's rules drop it
+    assert_eq!(
+        field(&found, "url"),
+        [format!("{}/game/elden-ring", site.base())]
+    );
+    assert_eq!(field(&found, "provenance"), ["ajax"]);
+}
+
+#[tokio::test]
+async fn multi_query_fetches_each_page_once() {
+    let site = MockSite::start("multi").await;
+    // Both segments read the same front and archive pages
+    site.page("/front/", "front.html", 1).await;
+    site.page("/front/page/2/", "front_page2.html", 1).await;
+
+    let assert = site
+        .search("elden ring | hollow knight", "frontpage")
+        .assert()
+        .success();
+    let found = results(&assert.get_output().stdout);
+    let mut urls = field(&found, "url");
+    urls.sort();
+    let base = site.base();
+    assert_eq!(
+        urls,
+        [
+            format!("{base}/front/elden-ring-nightreign/"),
+            format!("{base}/front/elden-ring/"),
+            format!("{base}/front/hollow-knight-silksong/"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn page_reached_twice_through_a_redirect_is_parsed_once() {
+    let site = MockSite::start("redirect").await;
+    // The archive page is gone and sends readers back to the front page,
+    // which is fetched again by following the redirect
+    site.page("/front/", "front.html", 2).await;
+    site.redirect("/front/page/2/", "/front/", 1).await;
+
+    let mut cmd = site.search("elden ring", "frontpage");
+    cmd.arg("--debug");
+    let assert = cmd.assert().success();
+    let found = results(&assert.get_output().stdout);
+    let base = site.base();
+    assert_eq!(
+        field(&found, "url"),
+        [format!("{base}/front/elden-ring-nightreign/")]
+    );
+    let err = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(
+        err.contains(&format!(
+            "[debug] site=frontpage url={base}/front/page/2/ led to already parsed {base}/front/"
+        )),
+        "stderr: {err}"
+    );
+}
+
+#[tokio::test]
+async fn multi_query_searches_each_segment_separately() {
+    let site = MockSite::start("segments").await;
+    site.search_page("/", "s", "elden ring", "search.html")
+        .await;
+    site.search_page("/", "s", "hades", "search.html").await;
+
+    // The repeated segment is searched once (each search page expects one hit)
+    let assert = site
+        .search("elden ring | hades | Elden Ring", "repacks")
+        .assert()
+        .success();
+    let found = results(&assert.get_output().stdout);
+    let titles = field(&found, "title");
+    assert!(titles.contains(&"Hades II"), "{titles:?}");
+    assert!(titles.contains(&"Elden Ring Deluxe Edition"), "{titles:?}");
+}
+
+#[tokio::test]
+async fn site_operators_leave_the_other_sites_unsearched() {
+    let site = MockSite::start("site-operator").await;
+    site.search_page("/", "s", "elden ring", "search.html")
+        .await;
+    // The front page site isn't named by site:, so it is never fetched
+    site.page("/front/", "front.html", 0).await;
+
+    let assert = site
+        .search("elden ring -nightreign site:repacks", "repacks,frontpage")
+        .assert()
+        .success();
+    let found = results(&assert.get_output().stdout);
+    assert_eq!(field(&found, "title"), ["Elden Ring Deluxe Edition"]);
+    assert_eq!(field(&found, "site"), ["repacks"]);
+}
+
+#[tokio::test]
+async fn steam_appids_come_from_the_cached_app_list() {
+    let site = MockSite::start("steam").await;
+    site.page("/blog/feed/", "feed.xml", 2).await;
+    // Downloaded by the first search only; the second reads the cached copy
+    site.page("/steam/apps.json", "steam_apps.json", 1).await;
+
+    for _ in 0..2 {
+        let mut cmd = site.search("elden ring", "feedsite");
+        cmd.arg("--steam-appids");
+        cmd.env(
+            "WEBSITE_SEARCHER_STEAM_APPS_URL",
+            format!("{}/steam/apps.json", site.base()),
+        );
+        let assert = cmd.assert().success();
+        let found = results(&assert.get_output().stdout);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0]["steam_appid"], 2778580);
+    }
+}
@@ -1,3 +1,5 @@
+mod isolated;
+
 use assert_cmd::prelude::*;
 use mockito::Server;
 use std::process::Command;
@@ -13,7 +15,8 @@ async fn cli_no_results_prints_table_message() {
         .create_async()
         .await;
 
-    let mut cmd = Command::cargo_bin("website-searcher").expect("binary built");
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
     cmd.args([
         "elden ring",
         "--sites",
@@ -30,3 +33,34 @@ async fn cli_no_results_prints_table_message() {
     let out = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
     assert!(out.contains("No results."));
 }
+
+#[tokio::test]
+async fn cli_no_results_message_is_localized() {
+    let mut server = Server::new_async().await;
+    let _m = server
+        .mock("POST", "/")
+        .with_status(200)
+        .with_body(r#"{"solution":{"response":"<html></html>"},"status":"ok"}"#)
+        .create_async()
+        .await;
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
+    cmd.args([
+        "elden ring",
+        "--sites",
+        "fitgirl",
+        "--cf-url",
+        &server.url(),
+        "--format",
+        "table",
+        "--no-cache",
+        "--lang",
+        "ru",
+    ]);
+    cmd.env("NO_COLOR", "1");
+
+    let assert = cmd.assert().success();
+    let out = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    assert!(out.contains("Ничего не найдено."));
+}
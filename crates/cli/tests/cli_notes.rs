@@ -0,0 +1,99 @@
+//! Notes on result URLs: set with `annotate`, shown and filtered by searches.
+
+mod mock_site;
+
+use assert_cmd::prelude::*;
+use mock_site::{MockSite, field, results};
+
+#[tokio::test]
+async fn notes_show_up_in_searches_and_hide_statuses() {
+    let site = MockSite::start("notes").await;
+    site.page("/front/", "front.html", 2).await;
+    site.page("/front/page/2/", "front_page2.html", 2).await;
+    let base = site.base();
+    let ignored = format!("{base}/front/elden-ring-nightreign/");
+    let downloaded = format!("{base}/front/elden-ring/");
+
+    site.cli()
+        .args(["annotate", "set", &ignored, "--status", "ignored"])
+        .assert()
+        .success();
+    site.cli()
+        .args(["annotate", "set", &downloaded, "--note", "1.16 works"])
+        .assert()
+        .success();
+    // Setting the status keeps the note
+    let out = site
+        .cli()
+        .args(["annotate", "set", &downloaded, "--status", "downloaded"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(out.get_output().stdout.clone()).unwrap();
+    assert!(
+        stdout.contains("[downloaded: 1.16 works]"),
+        "stdout: {stdout}"
+    );
+
+    let assert = site.search("elden ring", "frontpage").assert().success();
+    let found = results(&assert.get_output().stdout);
+    assert_eq!(found.len(), 2);
+    let note = |url: &str| {
+        found
+            .iter()
+            .find(|r| r["url"] == url)
+            .map(|r| r["note"].clone())
+            .unwrap()
+    };
+    assert_eq!(note(&ignored)["status"], "ignored");
+    assert_eq!(note(&downloaded)["status"], "downloaded");
+    assert_eq!(note(&downloaded)["text"], "1.16 works");
+
+    let mut cmd = site.search("elden ring", "frontpage");
+    cmd.args(["--hide-status", "ignored"]);
+    let assert = cmd.assert().success();
+    let found = results(&assert.get_output().stdout);
+    assert_eq!(field(&found, "url"), [downloaded.as_str()]);
+
+    let list = site
+        .cli()
+        .args([
+            "annotate",
+            "list",
+            "--status",
+            "downloaded",
+            "--format",
+            "json",
+        ])
+        .assert()
+        .success();
+    let listed: serde_json::Value = serde_json::from_slice(&list.get_output().stdout).unwrap();
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+    assert_eq!(listed[0]["url"], downloaded.as_str());
+
+    site.cli()
+        .args(["annotate", "clear", &ignored])
+        .assert()
+        .success();
+    let list = site.cli().args(["annotate", "list"]).assert().success();
+    let listed: serde_json::Value = serde_json::from_slice(&list.get_output().stdout).unwrap();
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn annotate_set_needs_a_status_or_note() {
+    let site = MockSite::start("notes-empty").await;
+    site.cli()
+        .args(["annotate", "set", "https://a.example/1"])
+        .assert()
+        .failure();
+    site.cli()
+        .args([
+            "annotate",
+            "set",
+            "https://a.example/1",
+            "--status",
+            "maybe",
+        ])
+        .assert()
+        .failure();
+}
@@ -0,0 +1,38 @@
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+#[test]
+fn state_files_move_out_of_the_cache_dir() {
+    let root = std::env::temp_dir().join(format!("ws-paths-{}", std::process::id()));
+    let (cache, state) = (root.join("cache"), root.join("state"));
+    std::fs::create_dir_all(&cache).unwrap();
+    std::fs::write(cache.join("search_history.json"), "{\"entries\":[]}").unwrap();
+    std::fs::write(cache.join("tui_session.json"), "{}").unwrap();
+    std::fs::write(cache.join("search_cache.json"), "{}").unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.args(["config", "show"]);
+    cmd.env("WEBSITE_SEARCHER_CONFIG_DIR", root.join("config"));
+    cmd.env("WEBSITE_SEARCHER_CACHE_DIR", &cache);
+    cmd.env("WEBSITE_SEARCHER_STATE_DIR", &state);
+    cmd.env_remove("WEBSITE_SEARCHER_DEBUG_DIR");
+    let assert = cmd.assert().success();
+    let out: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).expect("json");
+
+    assert!(state.join("search_history.json").is_file());
+    assert!(state.join("tui_session.json").is_file());
+    assert!(!cache.join("search_history.json").exists());
+    // The search cache stays where it is
+    assert!(cache.join("search_cache.json").is_file());
+    assert_eq!(
+        out["paths"]["cache"],
+        cache.join("search_cache.json").to_str().unwrap()
+    );
+    assert_eq!(out["paths"]["debug"], cache.join("debug").to_str().unwrap());
+    assert_eq!(out["paths"]["state"], state.to_str().unwrap());
+    assert_eq!(
+        out["paths"]["settings"],
+        root.join("config").join("config.toml").to_str().unwrap()
+    );
+    let _ = std::fs::remove_dir_all(&root);
+}
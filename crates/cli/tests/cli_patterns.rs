@@ -0,0 +1,70 @@
+//! Learned link patterns: a site whose result selector stopped matching is
+//! searched through the pattern its links shared last time.
+
+mod mock_site;
+
+use assert_cmd::prelude::*;
+use mock_site::{MockSite, field, results};
+
+#[tokio::test]
+async fn link_pattern_is_learned_then_tried_first() {
+    let site = MockSite::start("patterns").await;
+    // The redesigned page no longer has the site's `h2.entry-title a` results
+    site.page("/", "search_redesign.html", 2).await;
+    let base = site.base();
+
+    // The scan of every link keeps the tag link in the menu too
+    let assert = site.search("elden ring", "repacks").assert().success();
+    let found = results(&assert.get_output().stdout);
+    let mut urls = field(&found, "url");
+    urls.sort();
+    assert_eq!(
+        urls,
+        [
+            format!("{base}/games/elden-ring-deluxe/"),
+            format!("{base}/games/elden-ring-nightreign/"),
+            format!("{base}/tag/elden-ring/"),
+        ]
+    );
+
+    let list = site
+        .cli()
+        .args(["patterns", "list", "--format", "json"])
+        .assert()
+        .success();
+    let learned: serde_json::Value = serde_json::from_slice(&list.get_output().stdout).unwrap();
+    let repacks = &learned["sites"]["repacks"];
+    assert_eq!(repacks["href_prefix"], format!("{base}/games/"));
+    assert_eq!(repacks["parent_class"], "post-card");
+
+    // Next time only the links fitting the pattern are read
+    let assert = site.search("elden ring", "repacks").assert().success();
+    let found = results(&assert.get_output().stdout);
+    let mut urls = field(&found, "url");
+    urls.sort();
+    assert_eq!(
+        urls,
+        [
+            format!("{base}/games/elden-ring-deluxe/"),
+            format!("{base}/games/elden-ring-nightreign/"),
+        ]
+    );
+
+    let cleared = site
+        .cli()
+        .args(["patterns", "clear", "repacks"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(cleared.get_output().stdout.clone()).unwrap();
+    assert!(
+        stdout.contains("Forgot the pattern learned for repacks"),
+        "{stdout}"
+    );
+    let list = site
+        .cli()
+        .args(["patterns", "list", "--format", "table"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(list.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("No patterns learned yet."), "{stdout}");
+}
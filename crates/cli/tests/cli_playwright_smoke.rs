@@ -1,3 +1,5 @@
+mod isolated;
+
 use assert_cmd::prelude::*;
 use std::process::Command;
 
@@ -6,7 +8,8 @@ use std::process::Command;
 // We only assert that the binary runs successfully and prints a site header line.
 #[test]
 fn csrin_playwright_runs_and_prints_header() {
-    let mut cmd = Command::cargo_bin("website-searcher").expect("binary built");
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
     cmd.args([
         "elden ring",
         "--sites",
@@ -0,0 +1,79 @@
+//! Search profiles: `--fast` fetches directly within a short budget,
+//! `--thorough` reads further result pages and checks every result link.
+
+mod mock_site;
+
+use std::time::Duration;
+
+use assert_cmd::prelude::*;
+use mock_site::{MockSite, field, results};
+
+#[tokio::test]
+async fn fast_profile_skips_the_solver_and_keeps_an_explicit_budget() {
+    let site = MockSite::start("profile-fast").await;
+    // Fetched directly despite requiring Cloudflare: no solver under --fast
+    site.challenge("/shielded/", 1).await;
+    site.slow_page("/", "search.html", Duration::from_secs(3))
+        .await;
+
+    let assert = site
+        .search("elden ring", "shielded,repacks")
+        .args(["--fast", "--timeout-per-site", "1"])
+        .assert()
+        .success();
+    assert!(results(&assert.get_output().stdout).is_empty());
+    let err = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(err.contains("repacks timed out after 1s"), "{err}");
+}
+
+#[tokio::test]
+async fn thorough_profile_reads_more_pages_and_checks_links() {
+    let site = MockSite::start("profile-thorough").await;
+    site.search_page("/", "s", "elden ring", "search.html")
+        .await;
+    site.search_page("/page/2/", "s", "elden ring", "search_page2.html")
+        .await;
+    // Page 3 is missing (404), which ends the pages; so are two of the results
+    site.page("/elden-ring-deluxe/", "front.html", 1).await;
+    site.page("/elden-ring-shadow-of-the-erdtree/", "front.html", 1)
+        .await;
+
+    let assert = site
+        .cli()
+        .args([
+            "elden ring",
+            "--sites",
+            "repacks",
+            "--format",
+            "json",
+            "--no-cache",
+            "--no-rate-limit",
+            "--thorough",
+        ])
+        .assert()
+        .success();
+    let results = results(&assert.get_output().stdout);
+    assert_eq!(
+        field(&results, "title"),
+        [
+            "Elden Ring Deluxe Edition",
+            "Elden Ring Nightreign",
+            "Elden Ring Shadow of the Erdtree",
+        ]
+    );
+    let states: Vec<&str> = results
+        .iter()
+        .map(|r| r["link_check"]["state"].as_str().unwrap())
+        .collect();
+    assert_eq!(states, ["alive", "dead", "alive"]);
+}
+
+#[test]
+fn thorough_profile_cannot_turn_fallbacks_off() {
+    let mut cmd = std::process::Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.args(["elden ring", "--thorough", "--no-cf"]);
+    cmd.assert().failure();
+    let mut cmd = std::process::Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.args(["elden ring", "--thorough", "--fast"]);
+    cmd.assert().failure();
+}
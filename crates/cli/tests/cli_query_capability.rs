@@ -0,0 +1,59 @@
+//! A site whose search answers every query with the same page is probed, then
+//! searched as a listing site filtered locally. Pages from a stand-in solver
+//! are never probed.
+
+mod mock_site;
+
+use assert_cmd::prelude::*;
+use mock_site::{MockSite, field, results};
+
+#[tokio::test]
+async fn site_ignoring_the_query_becomes_a_listing_site() {
+    let site = MockSite::start("capability").await;
+    // Every search, the three probes included, gets the front page
+    site.page("/", "front.html", 5).await;
+
+    let assert = site.search("hades", "repacks").assert().success();
+    assert!(results(&assert.get_output().stdout).is_empty());
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(
+        stderr.contains("repacks answers every search with the same page"),
+        "{stderr}"
+    );
+
+    // The next search reads the front page as a listing and is not probed again
+    let assert = site
+        .search("elden ring", "repacks")
+        .arg("--debug")
+        .assert()
+        .success();
+    let found = results(&assert.get_output().stdout);
+    assert_eq!(field(&found, "title"), ["Elden Ring Nightreign"]);
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(stderr.contains("site=repacks search ignores the query"));
+    assert!(!stderr.contains("probed"));
+
+    let stats = site
+        .cli()
+        .args(["stats", "--sites", "repacks", "--format", "json"])
+        .assert()
+        .success();
+    let rows: serde_json::Value = serde_json::from_slice(&stats.get_output().stdout).unwrap();
+    assert_eq!(rows[0]["ignores_query"], true);
+}
+
+#[tokio::test]
+async fn pages_from_a_stand_in_solver_are_not_probed() {
+    let site = MockSite::start("capability-solver").await;
+    site.challenge("/shielded/", 0).await;
+    // Answers anything with the same page, the probes included if sent
+    site.any_solver("front.html", 1).await;
+
+    let assert = site.search("hades", "shielded").assert().success();
+    assert!(results(&assert.get_output().stdout).is_empty());
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(!stderr.contains("answers every search"), "{stderr}");
+    let verdicts = std::fs::read_to_string(site.file("state").join("query_capability.json"))
+        .unwrap_or_default();
+    assert!(!verdicts.contains("shielded"), "{verdicts}");
+}
@@ -0,0 +1,56 @@
+//! `--record` against the mock sites, then `--replay` with the server gone.
+
+mod mock_site;
+
+use assert_cmd::prelude::*;
+use mock_site::{MockSite, field, results};
+
+#[tokio::test]
+async fn replayed_run_matches_the_recorded_one_without_the_network() {
+    let site = MockSite::start("record").await;
+    site.search_page("/", "s", "elden ring", "search.html")
+        .await;
+    site.page("/blog/feed/", "feed.xml", 1).await;
+    site.challenge("/shielded/", 0).await;
+    let solved = format!("{}/shielded/?s=elden+ring", site.base());
+    site.solver(&solved, "search.html").await;
+    let recording = std::env::temp_dir().join(format!("ws-record-{}.tar", std::process::id()));
+
+    let mut cmd = site.search("elden ring", "repacks,feedsite,shielded");
+    cmd.arg("--record").arg(&recording);
+    let assert = cmd.assert().success();
+    let recorded = results(&assert.get_output().stdout);
+    assert!(field(&recorded, "provenance").contains(&"solver"));
+    let err = String::from_utf8(assert.get_output().stderr.clone()).expect("utf8");
+    assert!(
+        err.contains(&format!("Recorded 3 responses to {}", recording.display())),
+        "stderr: {err}"
+    );
+
+    // Every fetch is answered from the archive once the server is gone
+    let mut cmd = site.search("elden ring", "repacks,feedsite,shielded");
+    drop(site);
+    cmd.arg("--replay").arg(&recording);
+    let assert = cmd.assert().success();
+    let replayed = results(&assert.get_output().stdout);
+    assert_eq!(field(&replayed, "url"), field(&recorded, "url"));
+    assert_eq!(field(&replayed, "title"), field(&recorded, "title"));
+    assert_eq!(
+        field(&replayed, "provenance"),
+        field(&recorded, "provenance")
+    );
+    std::fs::remove_file(&recording).ok();
+}
+
+#[test]
+fn missing_recording_is_an_error() {
+    let mut cmd = std::process::Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.args(["elden ring", "--replay", "/nonexistent/session.tar"]);
+    cmd.env("WEBSITE_SEARCHER_LANG", "en");
+    let assert = cmd.assert().failure();
+    let err = String::from_utf8(assert.get_output().stderr.clone()).expect("utf8");
+    assert!(
+        err.contains("failed to open recording /nonexistent/session.tar"),
+        "stderr: {err}"
+    );
+}
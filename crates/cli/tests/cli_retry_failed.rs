@@ -0,0 +1,31 @@
+//! `--retry-failed`: a site that failed with a server error is searched once
+//! more after the others finish.
+
+mod mock_site;
+
+use assert_cmd::prelude::*;
+use mock_site::{MockSite, field, results};
+
+#[tokio::test]
+async fn site_failing_with_server_errors_is_searched_again_at_the_end() {
+    let site = MockSite::start("retry-failed").await;
+    // Every try of the first search fails; the second search gets the page
+    site.failing_page("/", 500, 3).await;
+    site.search_page("/", "s", "elden ring", "search.html")
+        .await;
+
+    let mut cmd = site.search("elden ring", "repacks");
+    cmd.arg("--retry-failed");
+    let assert = cmd.assert().success();
+    let found = results(&assert.get_output().stdout);
+    assert_eq!(
+        field(&found, "title"),
+        ["Elden Ring Deluxe Edition", "Elden Ring Nightreign"]
+    );
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(
+        stderr.contains("[info] searching repacks once more after network or server errors"),
+        "{stderr}"
+    );
+    assert!(!stderr.contains("no results from any strategy"), "{stderr}");
+}
@@ -0,0 +1,45 @@
+//! Secrets stored with `secrets set` stand in for values as `secret:<name>`.
+
+mod mock_site;
+
+use assert_cmd::prelude::*;
+use mock_site::{MockSite, field, results};
+
+#[tokio::test]
+async fn stored_cookie_is_forwarded_by_reference() {
+    let site = MockSite::start("secrets").await;
+    site.set_secret("repacks-cookie", "sid=abc123");
+
+    let list = site
+        .cli()
+        .args(["secrets", "list", "--format", "json"])
+        .assert()
+        .success();
+    let stored: serde_json::Value = serde_json::from_slice(&list.get_output().stdout).unwrap();
+    assert_eq!(
+        stored,
+        serde_json::json!([{"name": "repacks-cookie", "backend": "file"}])
+    );
+
+    site.signed_in_page("/", "sid=abc123", "search.html", 1)
+        .await;
+    let assert = site
+        .search("elden ring", "repacks")
+        .args(["--cookie", "secret:repacks-cookie"])
+        .assert()
+        .success();
+    let found = results(&assert.get_output().stdout);
+    assert_eq!(field(&found, "title").len(), 2);
+
+    site.cli()
+        .args(["secrets", "remove", "repacks-cookie"])
+        .assert()
+        .success();
+    site.search("elden ring", "repacks")
+        .args(["--cookie", "secret:repacks-cookie"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "secret \"repacks-cookie\" is not stored",
+        ));
+}
@@ -0,0 +1,79 @@
+//! `serve`: searches answered over HTTP with the JSON a search prints, plus
+//! the sites searched and the cached searches.
+
+mod mock_site;
+
+use std::process::Stdio;
+
+use mock_site::{MockSite, field, results};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn searches_sites_and_cache_are_answered_as_json() {
+    let site = MockSite::start("serve").await;
+    site.search_page("/", "s", "elden ring", "search.html")
+        .await;
+    // Fetched once: the second search comes from the cache
+    site.page("/front/", "front.html", 1).await;
+
+    let mut cmd = site.cli();
+    cmd.args([
+        "serve",
+        "--listen",
+        "127.0.0.1:0",
+        "--sites",
+        "repacks,frontpage",
+        "--no-rate-limit",
+        "--no-playwright",
+    ]);
+    let mut server = Command::from(cmd)
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .unwrap();
+    let mut stderr = BufReader::new(server.stderr.take().unwrap()).lines();
+    let line = stderr.next_line().await.unwrap().expect("listening line");
+    let base = line
+        .split_whitespace()
+        .find(|word| word.starts_with("http://"))
+        .expect("the address listened on")
+        .to_string();
+
+    let client = reqwest::Client::new();
+    let get = |path: &str| client.get(format!("{base}{path}")).send();
+
+    let answer = get("/search?q=elden+ring&sites=repacks").await.unwrap();
+    assert_eq!(answer.status(), 200);
+    let body = answer.bytes().await.unwrap();
+    let found = results(&body);
+    assert_eq!(
+        field(&found, "title"),
+        ["Elden Ring Deluxe Edition", "Elden Ring Nightreign"]
+    );
+
+    let answer = get("/search?sites=repacks").await.unwrap();
+    assert_eq!(answer.status(), 400);
+    let answer = get("/search?q=elden+ring&sites=fitgirl").await.unwrap();
+    assert_eq!(answer.status(), 400);
+
+    let sites: Vec<serde_json::Value> = get("/sites").await.unwrap().json().await.unwrap();
+    let mut names = field(&sites, "name");
+    names.sort_unstable();
+    assert_eq!(names, ["frontpage", "repacks"]);
+
+    // Searches of some sites aren't cached, or they would answer for all
+    let cached: Vec<serde_json::Value> = get("/cache").await.unwrap().json().await.unwrap();
+    assert!(cached.is_empty(), "{cached:?}");
+
+    for _ in 0..2 {
+        let answer = get("/search?q=nightreign").await.unwrap();
+        let found = results(&answer.bytes().await.unwrap());
+        assert_eq!(field(&found, "title"), ["Elden Ring Nightreign"]);
+    }
+    let cached: Vec<serde_json::Value> = get("/cache").await.unwrap().json().await.unwrap();
+    assert_eq!(field(&cached, "query"), ["nightreign"]);
+    assert_eq!(cached[0]["count"], 1);
+
+    server.kill().await.unwrap();
+}
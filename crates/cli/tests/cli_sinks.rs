@@ -0,0 +1,67 @@
+//! Output sinks: a search sends its results to every `[[sinks]]` table in
+//! `config.toml` as well as printing them.
+
+mod mock_site;
+
+use assert_cmd::prelude::*;
+use mock_site::{MockSite, results};
+use serde_json::json;
+
+const SINKS: &str = r#"
+[[sinks]]
+kind = "file"
+path = "{dir}/latest.csv"
+format = "csv"
+
+[[sinks]]
+kind = "webhook"
+url = "{base}/hook"
+"#;
+
+#[tokio::test]
+async fn results_go_to_the_terminal_a_file_and_a_webhook() {
+    let site = MockSite::start("sinks").await;
+    site.page("/front/", "front.html", 1).await;
+    site.page("/front/page/2/", "front_page2.html", 1).await;
+    site.webhook("/hook", json!({ "query": "elden ring", "count": 2 }), 1)
+        .await;
+    let dir = site.file("");
+    site.write_config(&SINKS.replace("{dir}", dir.to_str().unwrap()));
+
+    let assert = site.search("elden ring", "frontpage").assert().success();
+    assert_eq!(results(&assert.get_output().stdout).len(), 2);
+    let csv = std::fs::read_to_string(site.file("latest.csv")).unwrap();
+    assert!(csv.starts_with("site,title,url"), "{csv}");
+    assert_eq!(csv.lines().count(), 3, "{csv}");
+}
+
+#[tokio::test]
+async fn failing_sinks_warn_and_no_sinks_skips_them() {
+    let site = MockSite::start("sinks-failing").await;
+    site.page("/front/", "front.html", 2).await;
+    site.page("/front/page/2/", "front_page2.html", 2).await;
+    site.write_config(
+        r#"
+[[sinks]]
+kind = "mqtt"
+broker = "http://localhost:1883"
+topic = "home/websearcher"
+"#,
+    );
+
+    let assert = site.search("elden ring", "frontpage").assert().success();
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr).to_string();
+    assert!(
+        stderr.contains("Could not send results to mqtt"),
+        "{stderr}"
+    );
+    assert_eq!(results(&assert.get_output().stdout).len(), 2);
+
+    let assert = site
+        .search("elden ring", "frontpage")
+        .arg("--no-sinks")
+        .assert()
+        .success();
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr).to_string();
+    assert!(!stderr.contains("Could not send"), "{stderr}");
+}
@@ -0,0 +1,59 @@
+//! Site health: a site that keeps finding nothing for queries other sites
+//! match is flagged as likely broken, after the search and in `stats`.
+
+mod mock_site;
+
+use assert_cmd::prelude::*;
+use mock_site::{MockSite, results};
+
+#[tokio::test]
+async fn site_empty_for_queries_others_match_is_flagged() {
+    let site = MockSite::start("health").await;
+    // repacks now renders its results by script, so its pages have none
+    site.page("/", "gog_search.html", 3).await;
+    site.page("/front/", "front.html", 3).await;
+    site.page("/front/page/2/", "front_page2.html", 3).await;
+
+    for (query, warned) in [
+        ("elden ring", false),
+        ("sekiro", false),
+        ("hollow knight", true),
+    ] {
+        let assert = site.search(query, "repacks,frontpage").assert().success();
+        assert!(!results(&assert.get_output().stdout).is_empty());
+        let err = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+        let warning = "[warn] repacks: no results for 3 different queries other sites matched";
+        assert_eq!(err.contains(warning), warned, "{query}: {err}");
+    }
+
+    let stats = site
+        .cli()
+        .args(["stats", "--sites", "repacks,frontpage", "--format", "json"])
+        .assert()
+        .success();
+    let rows: serde_json::Value = serde_json::from_slice(&stats.get_output().stdout).unwrap();
+    let row = |name: &str| {
+        rows.as_array()
+            .unwrap()
+            .iter()
+            .find(|r| r["site"] == name)
+            .unwrap()
+            .clone()
+    };
+    assert_eq!(row("repacks")["empty_streak"], 3);
+    assert_eq!(row("repacks")["likely_broken"], true);
+    assert_eq!(row("frontpage")["empty_streak"], 0);
+    assert_eq!(row("frontpage")["likely_broken"], false);
+    assert!(row("frontpage")["p95_ms"].is_u64());
+
+    let stats = site
+        .cli()
+        .args(["stats", "--sites", "repacks", "--format", "table"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(stats.get_output().stdout.clone()).unwrap();
+    assert!(
+        stdout.contains("likely broken parser, last empty for: elden ring, sekiro, hollow knight"),
+        "{stdout}"
+    );
+}
@@ -0,0 +1,74 @@
+mod isolated;
+
+use assert_cmd::prelude::*;
+use mockito::{Matcher, Server};
+use std::process::Command;
+
+fn config_dir(name: &str, routes: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("ws-routes-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("config.toml"),
+        format!("[search.routes]\n{routes}\n"),
+    )
+    .unwrap();
+    dir
+}
+
+fn search_fitgirl(config_dir: &std::path::Path) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.args([
+        "elden ring",
+        "--sites",
+        "fitgirl",
+        "--format",
+        "json",
+        "--no-cf",
+        "--no-cache",
+        "--no-rate-limit",
+    ]);
+    cmd.envs(isolated::env_in(config_dir));
+    cmd.env("WEBSITE_SEARCHER_DEBUG_DIR", config_dir.join("debug"));
+    cmd.env("NO_COLOR", "1");
+    cmd.env("WEBSITE_SEARCHER_LANG", "en");
+    cmd
+}
+
+#[tokio::test]
+async fn routed_site_is_fetched_through_its_proxy() {
+    // The mock server plays an HTTP proxy and refuses the tunnel to fitgirl
+    let mut proxy = Server::new_async().await;
+    let tunnel = proxy
+        .mock("CONNECT", Matcher::Any)
+        .match_header("host", "fitgirl-repacks.site:443")
+        .with_status(403)
+        .expect_at_least(1)
+        .create_async()
+        .await;
+    let dir = config_dir("proxy", &format!("fitgirl = \"{}\"", proxy.url()));
+
+    let mut cmd = search_fitgirl(&dir);
+    cmd.arg("--debug");
+    let assert = cmd.assert().success();
+    tunnel.assert_async().await;
+
+    let err = String::from_utf8(assert.get_output().stderr.clone()).expect("utf8");
+    assert!(
+        err.contains(&format!("[debug] site=fitgirl route={}", proxy.url())),
+        "stderr: {err}"
+    );
+}
+
+#[test]
+fn invalid_route_skips_the_site_instead_of_going_direct() {
+    let dir = config_dir("invalid", "fitgirl = \"gopher://127.0.0.1:70\"");
+
+    let assert = search_fitgirl(&dir).assert().success();
+    let err = String::from_utf8(assert.get_output().stderr.clone()).expect("utf8");
+    assert!(
+        err.contains(
+            "[warn] fitgirl: no results from any strategy: html (unavailable: invalid route for fitgirl: unsupported proxy scheme"
+        ),
+        "stderr: {err}"
+    );
+}
@@ -0,0 +1,80 @@
+//! `state export` and `state import`: moving notes, rules and settings between
+//! machines, each mock site's directory standing in for one.
+
+mod mock_site;
+
+use assert_cmd::prelude::*;
+use mock_site::MockSite;
+
+fn annotate(site: &MockSite, url: &str, note: &str) {
+    site.cli()
+        .args(["annotate", "set", url, "--note", note])
+        .assert()
+        .success();
+}
+
+fn notes(site: &MockSite) -> serde_json::Value {
+    let out = site.cli().args(["annotate", "list"]).assert().success();
+    serde_json::from_slice(&out.get_output().stdout).unwrap()
+}
+
+#[tokio::test]
+async fn imports_merge_into_or_replace_the_local_state() {
+    let old = MockSite::start("state-old").await;
+    annotate(&old, "https://a.example/elden", "from the old machine");
+    annotate(&old, "https://a.example/hades", "played");
+    old.cli()
+        .args(["ignore", "add", "domain", "spam.example"])
+        .assert()
+        .success();
+    old.write_config("language = \"en\"\n");
+    let archive = old.file("state.zip");
+    let out = old
+        .cli()
+        .args(["state", "export"])
+        .arg(&archive)
+        .assert()
+        .success();
+    let exported: serde_json::Value = serde_json::from_slice(&out.get_output().stdout).unwrap();
+    let files = exported["files"].as_array().unwrap();
+    assert!(files.contains(&"data/notes.json".into()), "{files:?}");
+    assert!(files.contains(&"config/config.toml".into()), "{files:?}");
+
+    let new = MockSite::start("state-new").await;
+    annotate(&new, "https://a.example/elden", "from the new machine");
+    let out = new
+        .cli()
+        .args(["state", "import"])
+        .arg(&archive)
+        .args(["--format", "table"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(out.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("data/notes.json: merged"), "{stdout}");
+    assert!(stdout.contains("data/ignore.json: added"), "{stdout}");
+    // What was here wins; what only the archive had is added
+    let merged = notes(&new);
+    assert_eq!(merged.as_array().unwrap().len(), 2, "{merged}");
+    assert!(merged.to_string().contains("from the new machine"));
+    assert!(merged.to_string().contains("played"));
+    let rules = new.cli().args(["ignore", "list"]).assert().success();
+    assert!(String::from_utf8_lossy(&rules.get_output().stdout).contains("spam.example"));
+
+    new.cli()
+        .args(["state", "import", "--mode", "replace"])
+        .arg(&archive)
+        .assert()
+        .success();
+    assert_eq!(notes(&new), notes(&old));
+
+    let err = new
+        .cli()
+        .args(["state", "import"])
+        .arg(new.file("sites.toml"))
+        .assert()
+        .failure();
+    assert!(
+        String::from_utf8_lossy(&err.get_output().stderr).contains("not a state archive"),
+        "{err:?}"
+    );
+}
@@ -0,0 +1,37 @@
+//! `--stream`: each site's results are printed as soon as it finishes, one
+//! JSON object per line, instead of all together at the end.
+
+mod mock_site;
+
+use std::time::Duration;
+
+use assert_cmd::prelude::*;
+use mock_site::{MockSite, field};
+
+#[tokio::test]
+async fn sites_are_printed_in_the_order_they_finish() {
+    let site = MockSite::start("stream").await;
+    site.search_page("/", "s", "elden ring", "search.html")
+        .await;
+    site.slow_page("/front/", "front.html", Duration::from_millis(500))
+        .await;
+    site.page("/front/page/2/", "front_page2.html", 1).await;
+
+    let mut cmd = site.search("elden ring", "frontpage,repacks");
+    cmd.arg("--stream");
+    let assert = cmd.assert().success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    let lines: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("one result per line"))
+        .collect();
+    // The slow site comes last although it sorts first
+    assert_eq!(
+        field(&lines, "site"),
+        ["repacks", "repacks", "frontpage", "frontpage"]
+    );
+    assert_eq!(
+        field(&lines[..2], "title"),
+        ["Elden Ring Deluxe Edition", "Elden Ring Nightreign"]
+    );
+}
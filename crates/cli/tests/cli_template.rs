@@ -0,0 +1,51 @@
+//! `template run`: a `[templates.<name>]` query skeleton filled from flags.
+
+mod mock_site;
+
+use assert_cmd::prelude::*;
+use mock_site::{MockSite, field, results};
+
+#[tokio::test]
+async fn template_fills_its_query_and_picks_sites_and_format() {
+    let site = MockSite::start("template").await;
+    site.write_config(
+        "[templates.clean]\nquery = \"{title} ring -deluxe\"\nsites = [\"repacks\"]\nformat = \"json\"\n",
+    );
+    site.search_page("/", "s", "elden ring", "search.html")
+        .await;
+
+    let assert = site
+        .cli()
+        .args([
+            "--no-cache",
+            "--no-rate-limit",
+            "--no-playwright",
+            "--format",
+            "table",
+        ])
+        .args(["template", "run", "clean", "--title", "elden"])
+        .assert()
+        .success();
+    // The exclusion filters the results; the template's format wins
+    let found = results(&assert.get_output().stdout);
+    assert_eq!(field(&found, "title"), ["Elden Ring Nightreign"]);
+
+    let assert = site
+        .cli()
+        .args(["template", "run", "clean"])
+        .assert()
+        .failure();
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(
+        stderr.contains("template clean: missing --title"),
+        "{stderr}"
+    );
+
+    let assert = site
+        .cli()
+        .args(["template", "list", "--format", "table"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert_eq!(stdout.trim(), "clean: {title} ring -deluxe  --title");
+}
@@ -0,0 +1,44 @@
+mod isolated;
+
+use assert_cmd::prelude::*;
+use mockito::Server;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn slow_site_is_aborted_and_reported() {
+    let mut server = Server::new_async().await;
+    let _m = server
+        .mock("POST", "/")
+        .with_status(200)
+        .with_body_from_request(|_| {
+            std::thread::sleep(Duration::from_secs(4));
+            br#"{"solution":{"response":"<html></html>"},"status":"ok"}"#.to_vec()
+        })
+        .create_async()
+        .await;
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
+    cmd.args([
+        "elden ring",
+        "--sites",
+        "fitgirl",
+        "--cf-url",
+        &server.url(),
+        "--no-cache",
+        "--timeout-per-site",
+        "1",
+    ]);
+    cmd.env("NO_COLOR", "1");
+    cmd.env("WEBSITE_SEARCHER_LANG", "en");
+
+    let started = Instant::now();
+    let assert = cmd.assert().success();
+    assert!(started.elapsed() < Duration::from_secs(4));
+
+    let out = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    let err = String::from_utf8(assert.get_output().stderr.clone()).expect("utf8");
+    assert!(out.contains("\"count\": 0"));
+    assert!(err.contains("fitgirl timed out after 1s"));
+}
@@ -0,0 +1,41 @@
+//! `--timings`: where a search's time went is printed to stderr after the
+//! results, which stay as they are on stdout.
+
+mod mock_site;
+
+use assert_cmd::prelude::*;
+use mock_site::{MockSite, field, results};
+
+#[tokio::test]
+async fn search_time_is_broken_down_by_phase_and_site() {
+    let site = MockSite::start("timings").await;
+    site.search_page("/", "s", "elden ring", "search.html")
+        .await;
+
+    let mut cmd = site.search("elden ring", "repacks");
+    cmd.arg("--timings");
+    let assert = cmd.assert().success();
+    let found = results(&assert.get_output().stdout);
+    assert_eq!(
+        field(&found, "title"),
+        ["Elden Ring Deluxe Edition", "Elden Ring Nightreign"]
+    );
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(stderr.contains("[timings] "), "{stderr}");
+    let lines: Vec<&str> = stderr.lines().map(str::trim_start).collect();
+    for phase in [
+        "queue",
+        "fetch",
+        "solver",
+        "render",
+        "parse",
+        "post_process",
+    ] {
+        assert!(
+            lines.iter().any(|l| l.starts_with(&format!("{phase} "))),
+            "{stderr}"
+        );
+    }
+    let site = lines.iter().find(|l| l.starts_with("repacks: "));
+    assert!(site.is_some_and(|l| l.contains("parse ")), "{stderr}");
+}
@@ -0,0 +1,49 @@
+//! Sites in the user's `sites.toml` are searched along with the built-in ones.
+
+mod mock_site;
+
+use assert_cmd::prelude::*;
+use mock_site::{MockSite, field, results};
+
+#[tokio::test]
+async fn user_sites_join_the_built_in_ones() {
+    let site = MockSite::start("user-sites").await;
+    site.search_page("/", "s", "elden ring", "search.html")
+        .await;
+
+    // The mock sites are the user's `sites.toml` in the config directory
+    let mut cmd = site.search("elden ring", "repacks");
+    cmd.env_remove("WEBSITE_SEARCHER_SITES");
+    let assert = cmd.assert().success();
+    let found = results(&assert.get_output().stdout);
+    assert_eq!(
+        field(&found, "title"),
+        ["Elden Ring Deluxe Edition", "Elden Ring Nightreign"]
+    );
+
+    let mut cmd = site.cli();
+    cmd.env_remove("WEBSITE_SEARCHER_SITES");
+    cmd.args(["config", "show", "--format", "json"]);
+    let assert = cmd.assert().success();
+    let shown: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let names: Vec<&str> = shown["sites"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap())
+        .collect();
+    assert!(
+        names.contains(&"fitgirl") && names.contains(&"repacks"),
+        "{names:?}"
+    );
+    let sites_file = shown["paths"]["sites"].as_str().unwrap();
+    assert!(sites_file.starts_with("built-in + "), "{sites_file}");
+
+    // A broken file is reported instead of quietly ignored
+    std::fs::write(site.file("sites.toml"), "[sites.broken]\nname = 1\n").unwrap();
+    let mut cmd = site.search("elden ring", "repacks");
+    cmd.env_remove("WEBSITE_SEARCHER_SITES");
+    let assert = cmd.assert().failure();
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(stderr.contains("failed to load sites from"), "{stderr}");
+}
@@ -0,0 +1,66 @@
+//! `verify-cache`: re-checking the URLs of a cached search without searching again.
+
+mod mock_site;
+
+use assert_cmd::prelude::*;
+use mock_site::{MockSite, field, results};
+use std::process::Command;
+
+/// The CLI searching the mock front page with the search cache on
+fn cached_search(site: &MockSite) -> Command {
+    let mut cmd = site.cli();
+    cmd.args([
+        "elden ring",
+        "--sites",
+        "frontpage",
+        "--format",
+        "json",
+        "--no-rate-limit",
+        "--no-playwright",
+        "--cf-url",
+        &format!("{}/v1", site.base()),
+    ]);
+    cmd
+}
+
+#[tokio::test]
+async fn dead_links_are_marked_and_pruned_from_the_cache() {
+    let site = MockSite::start("verify").await;
+    // Fetched by the first search only; the second is answered from the cache
+    site.page("/front/", "front.html", 1).await;
+    site.page("/front/page/2/", "front_page2.html", 1).await;
+    // The Nightreign post is gone (404), the Elden Ring one still answers
+    site.page("/front/elden-ring/", "front_page2.html", 1).await;
+    cached_search(&site).assert().success();
+
+    let out = site
+        .cli()
+        .args(["verify-cache", "Elden Ring", "--prune", "--format", "json"])
+        .assert()
+        .success();
+    let report: serde_json::Value = serde_json::from_slice(&out.get_output().stdout).unwrap();
+    assert_eq!(report["summary"]["alive"], 1);
+    assert_eq!(report["summary"]["dead"], 1);
+    assert_eq!(report["pruned"], 1);
+
+    let assert = cached_search(&site).assert().success();
+    let found = results(&assert.get_output().stdout);
+    assert_eq!(
+        field(&found, "url"),
+        [format!("{}/front/elden-ring/", site.base())]
+    );
+    assert_eq!(found[0]["link_check"]["state"], "alive");
+    assert_eq!(found[0]["link_check"]["status"], 200);
+}
+
+#[tokio::test]
+async fn verifying_an_uncached_search_fails() {
+    let site = MockSite::start("verify-missing").await;
+    let out = site
+        .cli()
+        .args(["verify-cache", "hades"])
+        .assert()
+        .failure();
+    let err = String::from_utf8(out.get_output().stderr.clone()).unwrap();
+    assert!(err.contains("no cached search for \"hades\""), "{err}");
+}
@@ -0,0 +1,53 @@
+mod isolated;
+
+use assert_cmd::prelude::*;
+use mockito::Server;
+use std::process::Command;
+
+#[tokio::test]
+async fn wishlist_search_reports_found_and_missing_titles() {
+    let mut server = Server::new_async().await;
+    let _m = server
+        .mock("POST", "/")
+        .with_status(200)
+        .with_body(r#"{"solution":{"response":"<html><h2 class=\"entry-title\"><a href=\"https://fitgirl-repacks.site/elden-ring/\">Elden Ring Deluxe</a></h2></html>"},"status":"ok"}"#)
+        .create_async()
+        .await;
+
+    let dir = tempfile_dir();
+    let wishlist = dir.join("wishlist.json");
+    std::fs::write(
+        &wishlist,
+        r#"{"1245620":{"name":"Elden Ring","priority":1},"367520":{"name":"Hollow Knight","priority":2}}"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
+    cmd.arg("wishlist").arg("search").arg(&wishlist).args([
+        "--sites",
+        "fitgirl",
+        "--cf-url",
+        &server.url(),
+        "--no-cache",
+        "--no-rate-limit",
+    ]);
+    cmd.env("NO_COLOR", "1");
+
+    let assert = cmd.assert().success();
+    let out = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    let v: serde_json::Value = serde_json::from_str(&out).expect("json report");
+    assert_eq!(v["summary"]["total"], 2);
+    assert_eq!(v["summary"]["found"], 1);
+    assert_eq!(v["summary"]["missing"][0], "Hollow Knight");
+    assert_eq!(v["titles"][0]["title"], "Elden Ring");
+    assert_eq!(v["titles"][0]["found_on"][0], "fitgirl");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+fn tempfile_dir() -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("ws-wishlist-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -1,3 +1,5 @@
+mod isolated;
+
 use assert_cmd::prelude::*;
 use mockito::{Matcher, Server};
 use std::process::Command;
@@ -7,7 +9,8 @@ fn interactive_empty_input_errors() {
     use std::io::Write;
     use std::process::Stdio;
     // Use assert_cmd to locate the test-built binary reliably
-    let mut cmd = Command::cargo_bin("website-searcher").expect("binary built");
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
     cmd.stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
@@ -38,7 +41,8 @@ async fn multi_site_table_grouping() {
         .create_async()
         .await;
 
-    let mut cmd = Command::cargo_bin("website-searcher").unwrap();
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
     cmd.args([
         "elden ring",
         "--sites",
@@ -81,7 +85,8 @@ async fn sites_filter_only_selected_sites_queried() {
         .create_async()
         .await;
 
-    let mut cmd = Command::cargo_bin("website-searcher").unwrap();
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
     cmd.args([
         "elden ring",
         "--sites",
@@ -99,7 +104,8 @@ async fn sites_filter_only_selected_sites_queried() {
 
 #[test]
 fn unknown_sites_graceful_json_empty() {
-    let mut cmd = Command::cargo_bin("website-searcher").unwrap();
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
     cmd.args([
         "elden ring",
         "--sites",
@@ -127,10 +133,11 @@ async fn debug_file_written_on_empty_results() {
         .create_async()
         .await;
 
-    let path = "debug/fitgirl_sample.html";
-    let _ = std::fs::remove_file(path);
+    let dir = std::env::temp_dir().join(format!("ws-debug-sample-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
 
-    let mut cmd = Command::cargo_bin("website-searcher").unwrap();
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
     cmd.args([
         "elden ring",
         "--sites",
@@ -143,13 +150,31 @@ async fn debug_file_written_on_empty_results() {
         "--no-cache",
     ]);
     cmd.env("NO_COLOR", "1");
+    cmd.env("WEBSITE_SEARCHER_DEBUG_DIR", &dir);
 
     let assert = cmd.assert().success();
     let out = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
     let err = String::from_utf8(assert.get_output().stderr.clone()).unwrap_or_default();
     assert!(out.contains("No results."));
-    assert!(err.contains("wrote debug/fitgirl_sample.html"));
-    assert!(std::path::Path::new(path).exists());
+    let samples: Vec<_> = std::fs::read_dir(&dir)
+        .expect("debug dir")
+        .map(|e| e.unwrap().path())
+        .collect();
+    assert_eq!(samples.len(), 1, "{samples:?}");
+    let name = samples[0].file_name().unwrap().to_string_lossy();
+    assert!(name.starts_with("fitgirl_sample-") && name.ends_with(".html"));
+    assert!(err.contains(&format!("[debug] wrote {}", samples[0].display())));
+
+    // `debug clean` removes it again
+    let mut clean = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    clean.envs(isolated::env());
+    clean.args(["debug", "clean"]);
+    clean.env("WEBSITE_SEARCHER_DEBUG_DIR", &dir);
+    clean.env("WEBSITE_SEARCHER_LANG", "en");
+    let assert = clean.assert().success();
+    let out = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    assert!(out.starts_with("Removed 1 debug file ("), "{out}");
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
 }
 
 #[tokio::test]
@@ -170,7 +195,8 @@ async fn per_site_limit_across_multiple_sites_json() {
         .create_async()
         .await;
 
-    let mut cmd = Command::cargo_bin("website-searcher").unwrap();
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+    cmd.envs(isolated::env());
     cmd.args([
         "elden ring",
         "--limit",
@@ -0,0 +1,43 @@
+//! Keeps the CLI's files out of the directories of the user running the tests.
+//!
+//! Every search saves site timings, site health, learned link patterns, query
+//! support, the result index and request counts, and reads `config.toml`, the
+//! ignore list and notes. Tests that don't run against a `MockSite` (whose
+//! `cli` does the same) give each command [`env`] so none of that touches the
+//! real config, data, state or cache directories.
+
+// Each test file uses only some of these helpers
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+/// A new empty directory for one command
+pub fn fresh_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "ws-isolated-{}-{}",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Variables pointing the CLI at a fresh directory of its own
+pub fn env() -> [(&'static str, PathBuf); 4] {
+    env_in(&fresh_dir())
+}
+
+/// Variables pointing the CLI at `dir`: `config.toml` in it, and the data,
+/// state and cache directories under it
+pub fn env_in(dir: &Path) -> [(&'static str, PathBuf); 4] {
+    [
+        ("WEBSITE_SEARCHER_CONFIG_DIR", dir.to_path_buf()),
+        ("WEBSITE_SEARCHER_DATA_DIR", dir.join("data")),
+        ("WEBSITE_SEARCHER_STATE_DIR", dir.join("state")),
+        ("WEBSITE_SEARCHER_CACHE_DIR", dir.join("cache")),
+    ]
+}
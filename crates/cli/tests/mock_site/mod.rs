@@ -0,0 +1,269 @@
+//! A local stand-in for the scraped sites, for end-to-end tests.
+//!
+//! [`MockSite`] serves the pages in `tests/fixtures/mock_site` from a wiremock
+//! server: search and listing pages, feeds, JSON endpoints, Cloudflare
+//! challenges and a FlareSolverr endpoint. The sites in its `sites.toml` point
+//! at the server (`{base}` in a fixture is replaced with its URL), and
+//! [`MockSite::search`] runs the CLI against them through
+//! `WEBSITE_SEARCHER_SITES`, so whole fallback chains run without the network.
+
+// Each test file compiles this module and uses only some of its helpers
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::Duration;
+
+use wiremock::matchers::{body_partial_json, body_string_contains, method, path, query_param};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+pub struct MockSite {
+    server: MockServer,
+    dir: PathBuf,
+}
+
+impl MockSite {
+    /// Start a server for the test `name` (which keeps its files apart)
+    pub async fn start(name: &str) -> Self {
+        let server = MockServer::start().await;
+        let dir = std::env::temp_dir().join(format!("ws-mock-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let site = Self { server, dir };
+        std::fs::write(site.dir.join("sites.toml"), site.fixture("sites.toml")).unwrap();
+        site
+    }
+
+    /// The server's URL, without a trailing slash
+    pub fn base(&self) -> String {
+        self.server.uri()
+    }
+
+    /// A file in this test's directory (where `config.toml` is read from)
+    pub fn file(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    /// Write the CLI's `config.toml`, with `{base}` replaced as in fixtures
+    pub fn write_config(&self, toml: &str) {
+        std::fs::write(
+            self.file("config.toml"),
+            toml.replace("{base}", &self.base()),
+        )
+        .unwrap();
+    }
+
+    /// Accept `hits` webhook POSTs at `url_path` whose JSON includes `body`
+    pub async fn webhook(&self, url_path: &str, body: serde_json::Value, hits: u64) {
+        Mock::given(method("POST"))
+            .and(path(url_path))
+            .and(body_partial_json(body))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(hits)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// A fixture file with `{base}` filled in
+    pub fn fixture(&self, name: &str) -> String {
+        let file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/mock_site")
+            .join(name);
+        std::fs::read_to_string(&file)
+            .unwrap_or_else(|e| panic!("read {}: {e}", file.display()))
+            .replace("{base}", &self.base())
+    }
+
+    /// Serve `fixture` at `url_path`, expecting `hits` requests
+    pub async fn page(&self, url_path: &str, fixture: &str, hits: u64) {
+        Mock::given(method("GET"))
+            .and(path(url_path))
+            .respond_with(self.response(200, fixture))
+            .expect(hits)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Serve `fixture` at `url_path` only to requests sending `cookie`,
+    /// expecting `hits` of them
+    pub async fn signed_in_page(&self, url_path: &str, cookie: &str, fixture: &str, hits: u64) {
+        let cookie = cookie.to_string();
+        Mock::given(method("GET"))
+            .and(path(url_path))
+            .and(move |request: &Request| {
+                let sent = request.headers.get("cookie").and_then(|v| v.to_str().ok());
+                sent.is_some_and(|sent| sent.contains(&cookie))
+            })
+            .respond_with(self.response(200, fixture))
+            .expect(hits)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Accept `hits` login form posts to `url_path` whose body includes
+    /// `field`, answering with a redirect that sets `cookies`
+    pub async fn login(&self, url_path: &str, field: &str, cookies: &[&str], hits: u64) {
+        let mut response = ResponseTemplate::new(302).insert_header("location", "/");
+        for cookie in cookies {
+            response = response.append_header("set-cookie", *cookie);
+        }
+        Mock::given(method("POST"))
+            .and(path(url_path))
+            .and(body_string_contains(field))
+            .respond_with(response)
+            .expect(hits)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Redirect `url_path` to `to`, expecting `hits` requests
+    pub async fn redirect(&self, url_path: &str, to: &str, hits: u64) {
+        Mock::given(method("GET"))
+            .and(path(url_path))
+            .respond_with(ResponseTemplate::new(302).insert_header("location", to))
+            .expect(hits)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Answer the first `times` requests for `url_path` with `status`, as a
+    /// site having a bad moment does; mounted before a page, the page follows
+    pub async fn failing_page(&self, url_path: &str, status: u16, times: u64) {
+        Mock::given(method("GET"))
+            .and(path(url_path))
+            .respond_with(ResponseTemplate::new(status))
+            .up_to_n_times(times)
+            .expect(times)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Serve `fixture` at `url_path` only after `delay`, as a slow site does
+    pub async fn slow_page(&self, url_path: &str, fixture: &str, delay: Duration) {
+        Mock::given(method("GET"))
+            .and(path(url_path))
+            .respond_with(self.response(200, fixture).set_delay(delay))
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Serve `fixture` for searches of `query` in the `param` query parameter
+    pub async fn search_page(&self, url_path: &str, param: &str, query: &str, fixture: &str) {
+        Mock::given(method("GET"))
+            .and(path(url_path))
+            .and(query_param(param, query))
+            .respond_with(self.response(200, fixture))
+            .expect(1)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Answer every direct request for `url_path` with Cloudflare's
+    /// "Just a moment..." page and a 403, as a challenged site does
+    pub async fn challenge(&self, url_path: &str, hits: u64) {
+        Mock::given(method("GET"))
+            .and(path(url_path))
+            .respond_with(self.response(403, "challenge.html"))
+            .expect(hits)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Play FlareSolverr at `/v1`: asked for `url`, it returns `fixture` as
+    /// the solved page
+    pub async fn solver(&self, url: &str, fixture: &str) {
+        let solution = serde_json::json!({
+            "status": "ok",
+            "solution": { "url": url, "status": 200, "response": self.fixture(fixture) },
+        });
+        Mock::given(method("POST"))
+            .and(path("/v1"))
+            .and(body_partial_json(serde_json::json!({ "url": url })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(solution))
+            .expect(1)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Play a stand-in FlareSolverr at `/v1` that returns `fixture` for every
+    /// URL, as simple test solvers do, expecting `hits` requests
+    pub async fn any_solver(&self, fixture: &str, hits: u64) {
+        let solution = serde_json::json!({
+            "status": "ok",
+            "solution": { "status": 200, "response": self.fixture(fixture) },
+        });
+        Mock::given(method("POST"))
+            .and(path("/v1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(solution))
+            .expect(hits)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// The CLI searching `sites` for `query`, with JSON output and no
+    /// cache, rate limit or user configuration
+    pub fn search(&self, query: &str, sites: &str) -> Command {
+        let mut cmd = self.cli();
+        cmd.args([
+            query,
+            "--sites",
+            sites,
+            "--format",
+            "json",
+            "--no-cache",
+            "--no-rate-limit",
+            "--no-playwright",
+            "--cf-url",
+            &format!("{}/v1", self.base()),
+        ]);
+        cmd
+    }
+
+    /// The CLI with its files kept in this test's directory
+    pub fn cli(&self) -> Command {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_website-searcher"));
+        cmd.env("WEBSITE_SEARCHER_SITES", self.dir.join("sites.toml"));
+        cmd.env("WEBSITE_SEARCHER_CONFIG_DIR", &self.dir);
+        cmd.env("WEBSITE_SEARCHER_CACHE_DIR", self.dir.join("cache"));
+        cmd.env("WEBSITE_SEARCHER_STATE_DIR", self.dir.join("state"));
+        cmd.env("WEBSITE_SEARCHER_DATA_DIR", self.dir.join("data"));
+        cmd.env("WEBSITE_SEARCHER_DEBUG_DIR", self.dir.join("debug"));
+        cmd.env("NO_COLOR", "1");
+        cmd.env("WEBSITE_SEARCHER_LANG", "en");
+        // Secrets go to the encrypted file in the data directory, never the
+        // keyring of the machine running the tests
+        cmd.env("WEBSITE_SEARCHER_NO_KEYRING", "1");
+        cmd
+    }
+
+    /// Store `value` as the secret `name` with `secrets set`, piped through stdin
+    pub fn set_secret(&self, name: &str, value: &str) {
+        assert_cmd::Command::from_std(self.cli())
+            .args(["secrets", "set", name])
+            .write_stdin(format!("{value}\n"))
+            .assert()
+            .success();
+    }
+
+    fn response(&self, status: u16, fixture: &str) -> ResponseTemplate {
+        let mime = match fixture.rsplit('.').next() {
+            Some("json") => "application/json",
+            Some("xml") => "application/atom+xml",
+            _ => "text/html; charset=utf-8",
+        };
+        ResponseTemplate::new(status).set_body_raw(self.fixture(fixture), mime)
+    }
+}
+
+/// The `results` of the CLI's JSON output
+pub fn results(stdout: &[u8]) -> Vec<serde_json::Value> {
+    let out: serde_json::Value = serde_json::from_slice(stdout).expect("json output");
+    out["results"].as_array().expect("results array").clone()
+}
+
+/// One field of every result
+pub fn field<'a>(results: &'a [serde_json::Value], name: &str) -> Vec<&'a str> {
+    results
+        .iter()
+        .map(|r| r[name].as_str().unwrap_or_default())
+        .collect()
+}
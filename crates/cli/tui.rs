@@ -0,0 +1,2612 @@
+//! Interactive results browser.
+//!
+//! Results are drawn as one box per site. [`Browser`] owns the navigation and
+//! filter state so key handling can be tested without a terminal. Pressing `s`
+//! runs a new search in place, so the browser doubles as a search console.
+//! A side pane shows details for the highlighted result. The sort order and
+//! visible columns are remembered in the settings file. The mouse works too:
+//! click to select, double-click to open, wheel to scroll.
+//!
+//! Interactive runs without a query start on a list of cached searches.
+//! Reopening a cached search restores where the browser was left.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{Stdout, stdout};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, KeyCode, KeyEventKind, MouseButton, MouseEventKind};
+use crossterm::{execute, terminal};
+use ratatui::{
+    backend::CrosstermBackend,
+    prelude::*,
+    widgets::{Block, Borders, Clear, Gauge, List, ListItem, ListState, Paragraph, Wrap},
+};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, mpsc};
+use unicode_width::UnicodeWidthStr;
+
+use website_searcher_core::analyzer::{
+    ResultMetadata, calculate_similarity, date_sort_key, extract_metadata, size_in_bytes,
+};
+use website_searcher_core::browser::open_url;
+use website_searcher_core::cache::{CacheEntry, SearchCache};
+use website_searcher_core::details::extract_download_links;
+use website_searcher_core::fetcher::build_http_client;
+use website_searcher_core::models::{SearchResult, SiteConfig};
+use website_searcher_core::query_parser::MultiQuery;
+use website_searcher_core::rate_limiter::RateLimiter;
+use website_searcher_core::settings::{self, OpenWith, Settings, SortKey, TuiView};
+use website_searcher_core::{cf, fetcher, paths};
+
+use crate::copy_to_clipboard;
+use crate::keymap::{Command, Keymap};
+use crate::search::{self, SearchEvent, SearchOptions};
+
+/// What the browser needs to run follow-up searches from the `s` prompt
+pub struct SearchSession<'a> {
+    /// Query behind the current results, used for relevance sorting
+    pub query: String,
+    pub sites: Vec<SiteConfig>,
+    pub opts: SearchOptions,
+    pub rate_limiter: Option<Arc<Mutex<RateLimiter>>>,
+    /// Cache to consult and update, and where to save it
+    pub cache: Option<(&'a mut SearchCache, &'a Path)>,
+    /// Allow `d` to fetch download links from result pages (`--details`)
+    pub fetch_details: bool,
+    /// Whether the initial results came from the cache, so a saved view applies
+    pub from_cache: bool,
+}
+
+/// Where the browser was left for one query
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+struct SavedView {
+    sort: SortKey,
+    /// URL of the highlighted result
+    selected: Option<String>,
+    /// First visible row
+    offset: usize,
+    /// Labels of folded groups
+    collapsed: Vec<String>,
+}
+
+/// Browser state kept between runs in `tui_session.json` in the state directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+struct SessionState {
+    last_query: Option<String>,
+    /// Saved views by cache key
+    views: BTreeMap<String, SavedView>,
+}
+
+impl SessionState {
+    /// Views are keyed like the cache: normalized terms, case-insensitive
+    fn key(query: &str) -> String {
+        search::cache_key(query, &MultiQuery::parse(query)).to_lowercase()
+    }
+
+    fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default()
+    }
+
+    /// Record the browser's view for `query` and write the file, dropping
+    /// views for queries that are no longer cached
+    fn remember(&mut self, query: &str, browser: &Browser, cache: &SearchCache, path: &Path) {
+        self.last_query = Some(query.to_string());
+        self.views.insert(Self::key(query), browser.saved_view());
+        self.views.retain(|k, _| cache.peek(k).is_some());
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+
+    fn view_for(&self, query: &str) -> Option<&SavedView> {
+        self.views.get(&Self::key(query))
+    }
+}
+
+/// Download links for a result page, fetched on demand with `d`
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DetailLinks {
+    Loading,
+    Found(Vec<String>),
+    Failed(String),
+}
+
+/// Results from one site, or every result when sorted by something other than site
+#[derive(Debug, Clone)]
+struct Group {
+    label: String,
+    items: Vec<SearchResult>,
+}
+
+/// One rendered line of the list: a box border or a result inside group `.0`.
+/// The top border of a collapsed group doubles as its header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    Top(usize),
+    Item(usize, usize),
+    Bottom(usize),
+}
+
+impl Row {
+    fn group(self) -> usize {
+        match self {
+            Row::Top(g) | Row::Item(g, _) | Row::Bottom(g) => g,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Normal,
+    /// Typing into the `/` filter prompt
+    Filter,
+    /// Typing a new search query after `s`
+    Query,
+}
+
+/// Actions applied to every marked result (or the highlighted one if none are marked)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BulkAction {
+    Open,
+    CopyUrls,
+    Export,
+    /// Hand `magnet:` links to the system torrent client
+    SendMagnets,
+}
+
+/// What the event loop should do after a key press
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Action {
+    None,
+    Quit,
+    /// Run a new search for this query and replace the results
+    Search(String),
+    Bulk(BulkAction, Vec<SearchResult>),
+    /// Fetch download links for this result in the background
+    FetchDetails(SearchResult),
+    /// Copy text to the clipboard; `true` when it includes the title
+    Yank(String, bool),
+    /// Hand the result to the n-th configured "open with" program
+    OpenWith(usize, SearchResult),
+}
+
+struct Browser {
+    /// Results in the order the search returned them
+    results: Vec<SearchResult>,
+    query: String,
+    view: TuiView,
+    /// Metadata parsed from each title, by title
+    meta: HashMap<String, ResultMetadata>,
+    groups: Vec<Group>,
+    total: usize,
+    filter: String,
+    query_input: String,
+    mode: Mode,
+    rows: Vec<Row>,
+    state: ListState,
+    /// Results marked with Space, as (group, item) indices
+    marked: BTreeSet<(usize, usize)>,
+    /// Groups folded down to their header
+    collapsed: BTreeSet<usize>,
+    /// Results matching the filter, per group
+    match_counts: Vec<usize>,
+    /// One-shot message shown in the footer until the next key press
+    status: Option<String>,
+    show_details: bool,
+    details_enabled: bool,
+    /// Download links by result URL
+    details: HashMap<String, DetailLinks>,
+    /// Row and time of the last left click, to detect double-clicks
+    last_click: Option<(usize, Instant)>,
+    keys: Keymap,
+    /// Showing the `?` key overlay
+    show_help: bool,
+    /// Names of the "open with" programs on keys 1-9
+    open_with: Vec<String>,
+}
+
+/// Two clicks on the same row within this window count as a double-click
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+impl Browser {
+    fn new(results: &[SearchResult]) -> Self {
+        let meta = results
+            .iter()
+            .map(|r| (r.title.clone(), extract_metadata(&r.title)))
+            .collect();
+        let mut browser = Self {
+            results: results.to_vec(),
+            query: String::new(),
+            view: TuiView::default(),
+            meta,
+            groups: Vec::new(),
+            total: results.len(),
+            filter: String::new(),
+            query_input: String::new(),
+            mode: Mode::Normal,
+            rows: Vec::new(),
+            state: ListState::default(),
+            marked: BTreeSet::new(),
+            collapsed: BTreeSet::new(),
+            match_counts: Vec::new(),
+            status: None,
+            show_details: true,
+            details_enabled: false,
+            details: HashMap::new(),
+            last_click: None,
+            keys: Keymap::default(),
+            show_help: false,
+            open_with: Vec::new(),
+        };
+        browser.regroup();
+        browser
+    }
+
+    fn metadata(&self, r: &SearchResult) -> Option<&ResultMetadata> {
+        self.meta.get(&r.title)
+    }
+
+    /// Results ordered by `key`; ties keep the search order
+    fn sorted_results(&self, key: SortKey) -> Vec<SearchResult> {
+        let mut sorted = self.results.clone();
+        match key {
+            SortKey::Site => {}
+            SortKey::Title => sorted.sort_by_cached_key(|r| r.title.to_lowercase()),
+            SortKey::Relevance => {
+                let query = self.query.to_lowercase();
+                // Titles containing the whole query rank above fuzzy matches
+                let score = |r: &SearchResult| {
+                    let contains = !query.is_empty() && r.title.to_lowercase().contains(&query);
+                    calculate_similarity(&query, &r.title) + if contains { 1.0 } else { 0.0 }
+                };
+                let mut scored: Vec<(f32, SearchResult)> =
+                    sorted.into_iter().map(|r| (score(&r), r)).collect();
+                scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+                sorted = scored.into_iter().map(|(_, r)| r).collect();
+            }
+            SortKey::Size => sorted.sort_by_cached_key(|r| {
+                Reverse(
+                    self.metadata(r)
+                        .and_then(|m| m.file_size.as_deref())
+                        .and_then(size_in_bytes),
+                )
+            }),
+            SortKey::Date => sorted.sort_by_cached_key(|r| {
+                Reverse(
+                    self.metadata(r)
+                        .and_then(|m| m.release_date.as_deref())
+                        .and_then(date_sort_key),
+                )
+            }),
+        }
+        sorted
+    }
+
+    /// Rebuild the groups for the current sort order. Marks and the highlighted
+    /// result carry over; folding is reset.
+    fn regroup(&mut self) {
+        let marked: Vec<SearchResult> = self
+            .marked
+            .iter()
+            .map(|&(g, i)| self.groups[g].items[i].clone())
+            .collect();
+        let selected = self.selected_result().cloned();
+
+        self.groups = if self.view.sort == SortKey::Site {
+            let mut by_site: BTreeMap<&str, Vec<SearchResult>> = BTreeMap::new();
+            for r in &self.results {
+                by_site.entry(&r.site).or_default().push(r.clone());
+            }
+            by_site
+                .into_iter()
+                .map(|(site, items)| Group {
+                    label: site.to_string(),
+                    items,
+                })
+                .collect()
+        } else {
+            vec![Group {
+                label: t!("tui-all-sites-sorted", sort = sort_label(self.view.sort)),
+                items: self.sorted_results(self.view.sort),
+            }]
+        };
+
+        let position = |r: &SearchResult| {
+            self.groups
+                .iter()
+                .enumerate()
+                .find_map(|(g, group)| group.items.iter().position(|x| x == r).map(|i| (g, i)))
+        };
+        self.marked = marked.iter().filter_map(position).collect();
+        let selected = selected.and_then(|r| position(&r));
+        self.collapsed.clear();
+        self.state.select(None);
+        self.rebuild();
+        if let Some(row) =
+            selected.and_then(|(g, i)| self.rows.iter().position(|r| *r == Row::Item(g, i)))
+        {
+            self.state.select(Some(row));
+        }
+    }
+
+    fn saved_view(&self) -> SavedView {
+        SavedView {
+            sort: self.view.sort,
+            selected: self.selected_result().map(|r| r.url.clone()),
+            offset: self.state.offset(),
+            collapsed: self
+                .collapsed
+                .iter()
+                .map(|&g| self.groups[g].label.clone())
+                .collect(),
+        }
+    }
+
+    /// Put back a view saved by an earlier run; unknown groups or results are skipped
+    fn restore_view(&mut self, saved: &SavedView) {
+        self.view.sort = saved.sort;
+        self.regroup();
+        self.collapsed = self
+            .groups
+            .iter()
+            .enumerate()
+            .filter(|(_, g)| saved.collapsed.contains(&g.label))
+            .map(|(i, _)| i)
+            .collect();
+        self.state.select(None);
+        self.rebuild();
+        let selected = saved.selected.as_ref().and_then(|url| {
+            self.rows.iter().position(
+                |row| matches!(*row, Row::Item(g, i) if self.groups[g].items[i].url == *url),
+            )
+        });
+        if selected.is_some() {
+            self.state.select(selected);
+        }
+        *self.state.offset_mut() = saved.offset.min(self.rows.len().saturating_sub(1));
+    }
+
+    /// Switch to the next sort order
+    fn cycle_sort(&mut self) {
+        self.view.sort = self.view.sort.next();
+        self.regroup();
+        self.status = Some(t!("tui-sort-changed", sort = sort_label(self.view.sort)));
+    }
+
+    /// Whether a result matches every whitespace-separated filter term
+    /// (case-insensitive, against title, site or URL)
+    fn matches(&self, r: &SearchResult) -> bool {
+        let haystack = format!("{}\n{}\n{}", r.title, r.site, r.url).to_lowercase();
+        self.filter
+            .to_lowercase()
+            .split_whitespace()
+            .all(|term| haystack.contains(term))
+    }
+
+    /// Recompute visible rows after the filter or folding changed, keeping the
+    /// selected row highlighted if it is still visible
+    fn rebuild(&mut self) {
+        let previous = self.selected_row();
+        let mut rows = Vec::new();
+        let mut match_counts = Vec::with_capacity(self.groups.len());
+        for (g, group) in self.groups.iter().enumerate() {
+            let items: Vec<usize> = (0..group.items.len())
+                .filter(|&i| self.matches(&group.items[i]))
+                .collect();
+            match_counts.push(items.len());
+            if items.is_empty() {
+                continue;
+            }
+            rows.push(Row::Top(g));
+            if !self.collapsed.contains(&g) {
+                rows.extend(items.into_iter().map(|i| Row::Item(g, i)));
+            }
+            rows.push(Row::Bottom(g));
+        }
+        self.rows = rows;
+        self.match_counts = match_counts;
+        let keep = previous.and_then(|p| self.rows.iter().position(|r| *r == p));
+        match keep {
+            Some(i) => self.state.select(Some(i)),
+            None => self.select_first(),
+        }
+    }
+
+    fn item_positions(&self) -> impl Iterator<Item = usize> + '_ {
+        self.rows
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| matches!(r, Row::Item(..)))
+            .map(|(i, _)| i)
+    }
+
+    /// Rows the cursor can rest on: results and collapsed group headers
+    fn selectable_positions(&self) -> impl Iterator<Item = usize> + '_ {
+        self.rows
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| match r {
+                Row::Item(..) => true,
+                Row::Top(g) => self.collapsed.contains(g),
+                Row::Bottom(_) => false,
+            })
+            .map(|(i, _)| i)
+    }
+
+    fn select_first(&mut self) {
+        let first = self.selectable_positions().next();
+        self.state.select(first);
+    }
+
+    fn select_last(&mut self) {
+        let last = self.selectable_positions().last();
+        self.state.select(last);
+    }
+
+    /// Group under the cursor, whether on a result or a header
+    fn selected_group(&self) -> Option<usize> {
+        self.selected_row().map(Row::group)
+    }
+
+    /// Fold the group under the cursor and park the cursor on its header
+    fn collapse_selected(&mut self) {
+        let Some(g) = self.selected_group() else {
+            return;
+        };
+        self.collapsed.insert(g);
+        self.rebuild();
+        let header = self.rows.iter().position(|r| *r == Row::Top(g));
+        self.state.select(header);
+    }
+
+    /// Unfold the group under the cursor and move onto its first result
+    fn expand_selected(&mut self) {
+        let Some(g) = self.selected_group() else {
+            return;
+        };
+        if self.collapsed.remove(&g) {
+            self.rebuild();
+            let first = self
+                .rows
+                .iter()
+                .position(|r| matches!(r, Row::Item(x, _) if *x == g));
+            self.state.select(first);
+        }
+    }
+
+    fn selected_row(&self) -> Option<Row> {
+        self.state
+            .selected()
+            .and_then(|i| self.rows.get(i).copied())
+    }
+
+    fn selected_result(&self) -> Option<&SearchResult> {
+        match self.selected_row()? {
+            Row::Item(g, i) => Some(&self.groups[g].items[i]),
+            _ => None,
+        }
+    }
+
+    /// Mark or unmark the highlighted result
+    fn toggle_mark(&mut self) {
+        if let Some(Row::Item(g, i)) = self.selected_row()
+            && !self.marked.remove(&(g, i))
+        {
+            self.marked.insert((g, i));
+        }
+    }
+
+    /// Mark every visible result, or clear all marks if they are already marked
+    fn toggle_mark_all(&mut self) {
+        let visible: Vec<(usize, usize)> = self
+            .rows
+            .iter()
+            .filter_map(|r| match *r {
+                Row::Item(g, i) => Some((g, i)),
+                _ => None,
+            })
+            .collect();
+        if visible.iter().all(|k| self.marked.contains(k)) {
+            self.marked.clear();
+        } else {
+            self.marked.extend(visible);
+        }
+    }
+
+    /// Results a bulk action applies to: the marked ones, else the highlighted one
+    fn targets(&self) -> Vec<SearchResult> {
+        if self.marked.is_empty() {
+            return self.selected_result().cloned().into_iter().collect();
+        }
+        self.marked
+            .iter()
+            .map(|&(g, i)| self.groups[g].items[i].clone())
+            .collect()
+    }
+
+    fn bulk(&self, action: BulkAction) -> Action {
+        let targets = self.targets();
+        if targets.is_empty() {
+            Action::None
+        } else {
+            Action::Bulk(action, targets)
+        }
+    }
+
+    /// Number of results matching the filter, including those in folded groups
+    fn visible_count(&self) -> usize {
+        self.match_counts.iter().sum()
+    }
+
+    /// Move the selection by `delta` rows, landing on the nearest result in that direction
+    fn move_by(&mut self, delta: isize) {
+        let Some(cur) = self.state.selected() else {
+            return self.select_first();
+        };
+        let target = cur
+            .saturating_add_signed(delta)
+            .min(self.rows.len().saturating_sub(1));
+        let landed = if delta < 0 {
+            self.selectable_positions().filter(|&i| i <= target).last()
+        } else {
+            self.selectable_positions().find(|&i| i >= target)
+        };
+        if let Some(i) = landed {
+            self.state.select(Some(i));
+        }
+    }
+
+    /// Jump to the next (or previous) match, wrapping around the list
+    fn next_match(&mut self, forward: bool) {
+        let cur = self.state.selected().unwrap_or(0);
+        let items: Vec<usize> = self.item_positions().collect();
+        let next = if forward {
+            items.iter().find(|&&i| i > cur).or(items.first())
+        } else {
+            items.iter().rev().find(|&&i| i < cur).or(items.last())
+        };
+        if let Some(&i) = next {
+            self.state.select(Some(i));
+        }
+    }
+
+    /// Row index under screen position (`x`, `y`) when the list is drawn in `area`
+    fn row_at(&self, area: Rect, x: u16, y: u16) -> Option<usize> {
+        let inner = area.inner(Margin::new(1, 1));
+        if !inner.contains(Position::new(x, y)) {
+            return None;
+        }
+        let row = self.state.offset() + usize::from(y - inner.y);
+        (row < self.rows.len()).then_some(row)
+    }
+
+    /// Handle a left click on `row`. Clicking a site header folds or unfolds it;
+    /// returns the result to open on a double-click.
+    fn click(&mut self, row: usize, now: Instant) -> Option<SearchResult> {
+        let double = self
+            .last_click
+            .is_some_and(|(r, at)| r == row && now.duration_since(at) <= DOUBLE_CLICK);
+        self.last_click = Some((row, now));
+        match *self.rows.get(row)? {
+            Row::Item(..) => {
+                self.state.select(Some(row));
+                if double {
+                    self.last_click = None;
+                    return self.selected_result().cloned();
+                }
+            }
+            Row::Top(g) => {
+                self.state.select(Some(row));
+                if self.collapsed.contains(&g) {
+                    self.expand_selected();
+                } else {
+                    self.collapse_selected();
+                }
+            }
+            Row::Bottom(_) => {}
+        }
+        None
+    }
+
+    fn set_filter(&mut self, filter: String) {
+        self.filter = filter;
+        self.rebuild();
+    }
+
+    /// Handle a key press
+    fn handle_key(&mut self, code: KeyCode) -> Action {
+        self.status = None;
+        if self.mode == Mode::Query {
+            match code {
+                KeyCode::Char(c) => self.query_input.push(c),
+                KeyCode::Backspace => {
+                    self.query_input.pop();
+                }
+                KeyCode::Enter => {
+                    let query = self.query_input.trim().to_string();
+                    if !query.is_empty() {
+                        self.mode = Mode::Normal;
+                        self.query_input.clear();
+                        return Action::Search(query);
+                    }
+                }
+                KeyCode::Esc => {
+                    self.mode = Mode::Normal;
+                    self.query_input.clear();
+                }
+                _ => {}
+            }
+            return Action::None;
+        }
+
+        if self.mode == Mode::Filter {
+            match code {
+                KeyCode::Char(c) => {
+                    let mut f = self.filter.clone();
+                    f.push(c);
+                    self.set_filter(f);
+                }
+                KeyCode::Backspace => {
+                    let mut f = self.filter.clone();
+                    f.pop();
+                    self.set_filter(f);
+                }
+                KeyCode::Enter => self.mode = Mode::Normal,
+                KeyCode::Esc => {
+                    self.mode = Mode::Normal;
+                    self.set_filter(String::new());
+                }
+                KeyCode::Up => self.move_by(-1),
+                KeyCode::Down => self.move_by(1),
+                _ => {}
+            }
+            return Action::None;
+        }
+
+        // Any key closes the help overlay
+        if self.show_help {
+            self.show_help = false;
+            return Action::None;
+        }
+
+        let Some(command) = self.keys.command(code) else {
+            return match code {
+                KeyCode::Char(c @ '1'..='9') => self.open_with_slot(c as usize - '1' as usize),
+                _ => Action::None,
+            };
+        };
+        match command {
+            // Esc first drops an active filter, then quits
+            Command::Quit if code == KeyCode::Esc && !self.filter.is_empty() => {
+                self.set_filter(String::new())
+            }
+            Command::Quit => return Action::Quit,
+            Command::Help => self.show_help = true,
+            Command::Filter => self.mode = Mode::Filter,
+            Command::NewSearch => self.mode = Mode::Query,
+            Command::NextMatch => self.next_match(true),
+            Command::PrevMatch => self.next_match(false),
+            Command::Up => self.move_by(-1),
+            Command::Down => self.move_by(1),
+            Command::PageUp => self.move_by(-10),
+            Command::PageDown => self.move_by(10),
+            Command::Mark => {
+                self.toggle_mark();
+                self.move_by(1);
+            }
+            Command::MarkAll => self.toggle_mark_all(),
+            Command::OpenMarked => return self.bulk(BulkAction::Open),
+            Command::CopyUrls => return self.bulk(BulkAction::CopyUrls),
+            Command::Export => return self.bulk(BulkAction::Export),
+            Command::SendMagnets => return self.bulk(BulkAction::SendMagnets),
+            Command::ToggleDetails => self.show_details = !self.show_details,
+            Command::CycleSort => self.cycle_sort(),
+            Command::ToggleUrl => self.view.show_url = !self.view.show_url,
+            Command::ToggleSize => self.view.show_size = !self.view.show_size,
+            Command::ToggleVersion => self.view.show_version = !self.view.show_version,
+            Command::FetchDetails => return self.request_details(),
+            Command::Top => self.select_first(),
+            Command::Bottom => self.select_last(),
+            Command::Collapse => self.collapse_selected(),
+            Command::Expand => self.expand_selected(),
+            Command::Open if matches!(self.selected_row(), Some(Row::Top(_))) => {
+                self.expand_selected()
+            }
+            Command::Open => {
+                if let Some(r) = self.selected_result()
+                    && let Err(e) = open_url(&r.url)
+                {
+                    self.status = Some(t!("tui-open-failed", error = format!("{e:#}")));
+                }
+            }
+            Command::YankUrl => {
+                if let Some(r) = self.selected_result() {
+                    return Action::Yank(r.url.clone(), false);
+                }
+            }
+            Command::YankTitle => {
+                if let Some(r) = self.selected_result() {
+                    return Action::Yank(format!("{} - {}", r.title, r.url), true);
+                }
+            }
+        }
+        Action::None
+    }
+
+    /// Open the highlighted result with the program on key `slot + 1`
+    fn open_with_slot(&mut self, slot: usize) -> Action {
+        if slot >= self.open_with.len() {
+            self.status = Some(t!("tui-open-with-unset", key = slot + 1));
+            return Action::None;
+        }
+        match self.selected_result() {
+            Some(r) => Action::OpenWith(slot, r.clone()),
+            None => Action::None,
+        }
+    }
+
+    /// Aligned "keys  description" lines for the `?` overlay
+    fn help_lines(&self) -> Vec<String> {
+        let mut rows = self.keys.help_lines();
+        rows.extend(self.open_with.iter().take(9).enumerate().map(|(i, name)| {
+            (
+                (i + 1).to_string(),
+                t!("key-open-with", name = name.as_str()),
+            )
+        }));
+        let width = rows
+            .iter()
+            .map(|(keys, _)| text_width(keys))
+            .max()
+            .unwrap_or(0);
+        rows.into_iter()
+            .map(|(keys, desc)| format!("{}  {desc}", pad(&keys, width)))
+            .collect()
+    }
+
+    /// Scroll so the selected row is among the `height` rows drawn, the way
+    /// ratatui's `List` does
+    fn scroll_to_selection(&mut self, height: usize) {
+        let offset = self.state.offset().min(self.rows.len().saturating_sub(1));
+        let offset = match self.state.selected() {
+            Some(sel) if sel < offset => sel,
+            Some(sel) if height > 0 && sel >= offset + height => sel + 1 - height,
+            _ => offset,
+        };
+        *self.state.offset_mut() = offset;
+    }
+
+    /// Draw the rows scrolled into `area`: each site's results in a box titled
+    /// with the site, cut where the box is scrolled off. Folded groups show a
+    /// count; ratatui clips titles too long for the box.
+    fn render_list(&mut self, area: Rect, buf: &mut Buffer) {
+        self.scroll_to_selection(usize::from(area.height));
+        let highlight = Style::default().add_modifier(Modifier::REVERSED);
+        let selected = self.state.selected();
+        let end = self
+            .rows
+            .len()
+            .min(self.state.offset() + usize::from(area.height));
+        let mut first = self.state.offset();
+        let mut y = area.y;
+        while first < end {
+            let g = self.rows[first].group();
+            let last = (first..end)
+                .take_while(|&row| self.rows[row].group() == g)
+                .last()
+                .unwrap_or(first);
+            let shown = &self.rows[first..=last];
+
+            // Only the edges of the box that are on screen
+            let mut borders = Borders::LEFT | Borders::RIGHT;
+            if shown[shown.len() - 1] == Row::Bottom(g) {
+                borders |= Borders::BOTTOM;
+            }
+            let mut block = Block::bordered();
+            if shown[0] == Row::Top(g) {
+                borders |= Borders::TOP;
+                let label = if self.collapsed.contains(&g) {
+                    format!(" + {} ({}) ", self.groups[g].label, self.match_counts[g])
+                } else {
+                    format!(" {} ", self.groups[g].label)
+                };
+                let style = if selected == Some(first) {
+                    highlight
+                } else {
+                    Style::default()
+                };
+                block = block.title(Line::styled(label, style));
+            }
+            let block = block.borders(borders);
+
+            let mut items = Vec::new();
+            let mut state = ListState::default();
+            for (row, shown_row) in (first..=last).zip(shown) {
+                let Row::Item(g, i) = *shown_row else {
+                    continue;
+                };
+                if selected == Some(row) {
+                    state.select(Some(items.len()));
+                }
+                let mark = if self.marked.contains(&(g, i)) {
+                    '*'
+                } else {
+                    '-'
+                };
+                let text = format!(" {} {}", mark, self.columns(&self.groups[g].items[i]));
+                items.push(ListItem::new(text));
+            }
+            let rect = Rect::new(area.x, y, area.width, shown.len() as u16);
+            let list = List::new(items).block(block).highlight_style(highlight);
+            StatefulWidget::render(list, rect, buf, &mut state);
+
+            y += rect.height;
+            first = last + 1;
+        }
+    }
+
+    /// Text of a result row with the enabled columns
+    fn columns(&self, r: &SearchResult) -> String {
+        let mut text = if self.view.sort == SortKey::Site {
+            r.title.clone()
+        } else {
+            format!("[{}] {}", r.site, r.title)
+        };
+        let meta = self.metadata(r);
+        let mut push = |enabled: bool, value: Option<&String>| {
+            if enabled && let Some(v) = value {
+                text.push_str(&format!(" [{v}]"));
+            }
+        };
+        push(self.view.show_size, meta.and_then(|m| m.file_size.as_ref()));
+        push(
+            self.view.show_version,
+            meta.and_then(|m| m.version.as_ref()),
+        );
+        if self.view.show_url {
+            text.push_str(&format!(" ({})", r.url));
+        }
+        text
+    }
+
+    /// Start fetching download links for the highlighted result, unless already known
+    fn request_details(&mut self) -> Action {
+        if !self.details_enabled {
+            self.status = Some(t!("tui-detail-disabled"));
+            return Action::None;
+        }
+        let Some(r) = self.selected_result().cloned() else {
+            return Action::None;
+        };
+        match self.details.get(&r.url) {
+            Some(DetailLinks::Loading | DetailLinks::Found(_)) => Action::None,
+            _ => {
+                self.show_details = true;
+                self.details.insert(r.url.clone(), DetailLinks::Loading);
+                Action::FetchDetails(r)
+            }
+        }
+    }
+
+    /// Text of the detail pane for the highlighted result
+    fn detail_lines(&self) -> Vec<String> {
+        let Some(r) = self.selected_result() else {
+            return Vec::new();
+        };
+        let mut lines = vec![
+            r.title.clone(),
+            String::new(),
+            format!("{}: {}", t!("tui-detail-site"), r.site),
+            format!("{}: {}", t!("tui-detail-url"), r.url),
+        ];
+        let meta = self.metadata(r).cloned().unwrap_or_default();
+        let fields = [
+            (t!("tui-detail-size"), meta.file_size),
+            (t!("tui-detail-version"), meta.version),
+            (t!("tui-detail-build"), meta.build),
+            (t!("tui-detail-date"), meta.release_date),
+        ];
+        for (label, value) in fields {
+            if let Some(v) = value {
+                lines.push(format!("{label}: {v}"));
+            }
+        }
+        lines.push(String::new());
+        lines.push(format!("{}:", t!("tui-detail-links")));
+        match self.details.get(&r.url) {
+            None if self.details_enabled => lines.push(t!("tui-detail-press-d")),
+            None => lines.push(t!("tui-detail-disabled")),
+            Some(DetailLinks::Loading) => lines.push(t!("tui-detail-loading")),
+            Some(DetailLinks::Found(links)) if links.is_empty() => {
+                lines.push(t!("tui-detail-no-links"))
+            }
+            Some(DetailLinks::Found(links)) => lines.extend(links.iter().map(|l| format!("  {l}"))),
+            Some(DetailLinks::Failed(e)) => lines.push(t!("tui-detail-failed", error = e.as_str())),
+        }
+        lines
+    }
+
+    fn title(&self) -> String {
+        if self.filter.is_empty() {
+            t!("tui-results-title", count = self.total)
+        } else {
+            t!(
+                "tui-results-filtered",
+                shown = self.visible_count(),
+                count = self.total,
+                filter = self.filter.as_str()
+            )
+        }
+    }
+
+    fn footer(&self) -> String {
+        match self.mode {
+            Mode::Filter => format!("/{}▏ {}", self.filter, t!("tui-filter-help")),
+            Mode::Query => format!(
+                "{} {}▏ {}",
+                t!("tui-new-search-prompt"),
+                self.query_input,
+                t!("tui-new-search-help")
+            ),
+            Mode::Normal if self.status.is_some() => self.status.clone().unwrap_or_default(),
+            Mode::Normal if self.rows.is_empty() && !self.filter.is_empty() => {
+                t!("tui-filter-no-matches", filter = self.filter.as_str())
+            }
+            Mode::Normal => {
+                let url = self
+                    .selected_result()
+                    .map(|r| r.url.clone())
+                    .unwrap_or_default();
+                if self.marked.is_empty() {
+                    url
+                } else {
+                    format!(
+                        "[{}] {}",
+                        t!("tui-marked-count", count = self.marked.len()),
+                        url
+                    )
+                }
+            }
+        }
+    }
+}
+
+/// Start a configured program for `result` without waiting for it
+fn run_open_with(entry: &OpenWith, result: &SearchResult) -> String {
+    let name = entry.name.as_str();
+    let Some((program, args)) = entry.command_for(result) else {
+        return t!("tui-open-with-empty", name = name);
+    };
+    // Detached from the terminal so its output cannot draw over the TUI
+    let spawned = std::process::Command::new(&program)
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    match spawned {
+        Ok(_) => t!("tui-opened-with", name = name),
+        Err(e) => t!("tui-open-with-failed", name = name, error = e.to_string()),
+    }
+}
+
+/// Display width of `s` in terminal columns
+fn text_width(s: &str) -> usize {
+    UnicodeWidthStr::width(s)
+}
+
+/// `s` padded with spaces to `width` columns, for lining up columns
+fn pad(s: &str, width: usize) -> String {
+    let fill = width.saturating_sub(text_width(s));
+    format!("{s}{}", " ".repeat(fill))
+}
+
+/// A `width` x `height` rectangle centered in `area`, clamped to fit
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+fn sort_label(key: SortKey) -> String {
+    match key {
+        SortKey::Site => t!("tui-sort-site"),
+        SortKey::Title => t!("tui-sort-title"),
+        SortKey::Relevance => t!("tui-sort-relevance"),
+        SortKey::Size => t!("tui-sort-size"),
+        SortKey::Date => t!("tui-sort-date"),
+    }
+}
+
+type Term = Terminal<CrosstermBackend<Stdout>>;
+
+fn enter_terminal() -> anyhow::Result<Term> {
+    let mut stdout = stdout();
+    execute!(
+        stdout,
+        terminal::EnterAlternateScreen,
+        event::EnableMouseCapture
+    )?;
+    terminal::enable_raw_mode()?;
+    Ok(Terminal::new(CrosstermBackend::new(stdout))?)
+}
+
+fn leave_terminal(terminal: &mut Term) -> anyhow::Result<()> {
+    terminal::disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        event::DisableMouseCapture,
+        terminal::LeaveAlternateScreen
+    )?;
+    terminal.show_cursor()?;
+    Ok(())
+}
+
+/// Drop keystrokes typed before the view appeared (e.g. the Enter used to run the command)
+fn drain_pending_keys() -> anyhow::Result<()> {
+    while event::poll(std::time::Duration::from_millis(0))? {
+        let _ = event::read()?;
+    }
+    Ok(())
+}
+
+/// Write results to a JSON file in the current directory, in the same shape as
+/// `--format json` output so it can be fed back into `diff`
+fn export_results(results: &[SearchResult]) -> anyhow::Result<PathBuf> {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let path = std::env::current_dir()?.join(format!("website-searcher-selection-{secs}.json"));
+    let value = serde_json::json!({ "results": results, "count": results.len() });
+    std::fs::write(&path, serde_json::to_string_pretty(&value)?)?;
+    Ok(path)
+}
+
+/// Perform a bulk action and describe the outcome for the status line
+fn run_bulk(action: BulkAction, targets: &[SearchResult]) -> String {
+    match action {
+        BulkAction::Open => open_all(targets.iter().map(|r| r.url.as_str()), |n| {
+            t!("tui-opened", count = n)
+        }),
+        BulkAction::CopyUrls => {
+            let urls: Vec<&str> = targets.iter().map(|r| r.url.as_str()).collect();
+            match copy_to_clipboard(&urls.join("\n")) {
+                Ok(()) => t!("tui-copied-urls", count = urls.len()),
+                Err(e) => t!("tui-copy-failed", error = e.to_string()),
+            }
+        }
+        BulkAction::Export => match export_results(targets) {
+            Ok(path) => t!(
+                "tui-exported",
+                count = targets.len(),
+                path = path.display().to_string()
+            ),
+            Err(e) => t!("tui-export-failed", error = e.to_string()),
+        },
+        BulkAction::SendMagnets => {
+            let magnets: Vec<&str> = targets
+                .iter()
+                .map(|r| r.url.as_str())
+                .filter(|u| u.starts_with("magnet:"))
+                .collect();
+            if magnets.is_empty() {
+                return t!("tui-no-magnets");
+            }
+            open_all(magnets.into_iter(), |n| t!("tui-magnets-sent", count = n))
+        }
+    }
+}
+
+/// Open each of `urls`; report how many opened with `done`, or the error when
+/// none did
+fn open_all<'a>(urls: impl Iterator<Item = &'a str>, done: fn(usize) -> String) -> String {
+    let (mut opened, mut failure) = (0, None);
+    for url in urls {
+        match open_url(url) {
+            Ok(()) => opened += 1,
+            Err(e) => failure = Some(e),
+        }
+    }
+    match failure {
+        Some(e) if opened == 0 => t!("tui-open-failed", error = format!("{e:#}")),
+        _ => done(opened),
+    }
+}
+
+/// Per-site state shown by the live search dashboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SiteStatus {
+    Queued,
+    Fetching,
+    Solving,
+    Parsing,
+    Completed,
+    Failed,
+}
+
+impl SiteStatus {
+    fn emoji(&self) -> &'static str {
+        match self {
+            SiteStatus::Queued => "⏳",
+            SiteStatus::Fetching => "🔄",
+            SiteStatus::Solving => "🛡",
+            SiteStatus::Parsing => "📄",
+            SiteStatus::Completed => "✅",
+            SiteStatus::Failed => "❌",
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            SiteStatus::Queued => t!("status-queued"),
+            SiteStatus::Fetching => t!("status-fetching"),
+            SiteStatus::Solving => t!("status-solving"),
+            SiteStatus::Parsing => t!("status-parsing"),
+            SiteStatus::Completed => t!("status-completed"),
+            SiteStatus::Failed => t!("status-failed"),
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        matches!(self, SiteStatus::Completed | SiteStatus::Failed)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SiteProgress {
+    site: String,
+    status: SiteStatus,
+    results_count: usize,
+    error: Option<String>,
+    started: Option<Instant>,
+    /// Frozen once the site finishes
+    elapsed: Option<Duration>,
+}
+
+impl SiteProgress {
+    fn new(site: &str) -> Self {
+        Self {
+            site: site.to_string(),
+            status: SiteStatus::Queued,
+            results_count: 0,
+            error: None,
+            started: None,
+            elapsed: None,
+        }
+    }
+
+    fn set_status(&mut self, status: SiteStatus, now: Instant) {
+        let started = *self.started.get_or_insert(now);
+        if status.is_finished() {
+            self.elapsed = Some(now.saturating_duration_since(started));
+        }
+        self.status = status;
+    }
+
+    fn elapsed(&self, now: Instant) -> Option<Duration> {
+        self.elapsed
+            .or_else(|| self.started.map(|s| now.saturating_duration_since(s)))
+    }
+}
+
+/// Live search state: one row per site, fed by [`SearchEvent`]s
+struct Dashboard {
+    sites: Vec<SiteProgress>,
+    /// Raw results from the sites that finished so far
+    results: Vec<SearchResult>,
+}
+
+impl Dashboard {
+    fn new(sites: &[SiteConfig]) -> Self {
+        Self {
+            sites: sites.iter().map(|s| SiteProgress::new(&s.name)).collect(),
+            results: Vec::new(),
+        }
+    }
+
+    fn apply(&mut self, event: SearchEvent, now: Instant) {
+        let (site, status) = match &event {
+            // Sites wait in the queue until they get a slot; the fetch follows immediately
+            SearchEvent::Started { site } => (site, SiteStatus::Fetching),
+            SearchEvent::Fetching { site } => (site, SiteStatus::Fetching),
+            SearchEvent::Solving { site } => (site, SiteStatus::Solving),
+            SearchEvent::Parsing { site } => (site, SiteStatus::Parsing),
+            SearchEvent::Completed { site, .. } => (site, SiteStatus::Completed),
+            SearchEvent::Failed { site, .. } => (site, SiteStatus::Failed),
+        };
+        let Some(p) = self.sites.iter_mut().find(|p| &p.site == site) else {
+            return;
+        };
+        p.set_status(status, now);
+        match event {
+            SearchEvent::Completed { results, .. } => {
+                p.results_count = results.len();
+                self.results.extend(results);
+            }
+            SearchEvent::Failed { error, .. } => p.error = Some(error),
+            // A site that failed is started again by --retry-failed
+            SearchEvent::Started { .. } => p.error = None,
+            _ => {}
+        }
+    }
+
+    fn finished(&self) -> usize {
+        self.sites.iter().filter(|p| p.status.is_finished()).count()
+    }
+
+    /// One aligned row per site: status, elapsed time, then result count or error
+    fn lines(&self, now: Instant) -> Vec<String> {
+        let name_width = self
+            .sites
+            .iter()
+            .map(|p| text_width(&p.site))
+            .max()
+            .unwrap_or(0);
+        let label_width = self
+            .sites
+            .iter()
+            .map(|p| text_width(&p.status.label()))
+            .max()
+            .unwrap_or(0);
+        self.sites
+            .iter()
+            .map(|p| {
+                let elapsed = p
+                    .elapsed(now)
+                    .map(|d| format!("{:.1}s", d.as_secs_f64()))
+                    .unwrap_or_default();
+                let detail = match (&p.error, p.status) {
+                    (Some(e), _) => e.clone(),
+                    (None, SiteStatus::Completed) => {
+                        t!("tui-site-results", count = p.results_count)
+                    }
+                    _ => String::new(),
+                };
+                format!(
+                    "{} {}  {}  {:>6}  {}",
+                    p.status.emoji(),
+                    pad(&p.site, name_width),
+                    pad(&p.status.label(), label_width),
+                    elapsed,
+                    detail
+                )
+                .trim_end()
+                .to_string()
+            })
+            .collect()
+    }
+}
+
+/// What to do after the start screen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StartChoice {
+    /// Show the cached results for this query
+    Open(String),
+    /// Search this query again, replacing its cached results
+    Refresh(String),
+    /// Search a newly typed query
+    Search(String),
+    Quit,
+}
+
+/// Start screen listing cached searches, newest first
+struct StartScreen {
+    entries: Vec<CacheEntry>,
+    state: ListState,
+    /// Typing a new query after `s`
+    typing: bool,
+    query_input: String,
+}
+
+/// Short localized age such as "5m ago"
+fn format_age(secs: u64) -> String {
+    match secs {
+        0..60 => t!("tui-age-seconds", n = secs),
+        60..3600 => t!("tui-age-minutes", n = secs / 60),
+        3600..86400 => t!("tui-age-hours", n = secs / 3600),
+        _ => t!("tui-age-days", n = secs / 86400),
+    }
+}
+
+impl StartScreen {
+    fn new(cache: &SearchCache) -> Self {
+        let entries: Vec<CacheEntry> = cache.entries_newest_first().cloned().collect();
+        let mut state = ListState::default();
+        state.select((!entries.is_empty()).then_some(0));
+        Self {
+            entries,
+            state,
+            typing: false,
+            query_input: String::new(),
+        }
+    }
+
+    /// Highlight the entry for `query` (matched by cache key), if listed
+    fn select_query(&mut self, query: &str) {
+        let key = SessionState::key(query);
+        if let Some(i) = self
+            .entries
+            .iter()
+            .position(|e| e.query.to_lowercase() == key)
+        {
+            self.state.select(Some(i));
+        }
+    }
+
+    fn selected_query(&self) -> Option<String> {
+        self.state
+            .selected()
+            .and_then(|i| self.entries.get(i))
+            .map(|e| e.query.clone())
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let cur = self.state.selected().unwrap_or(0);
+        let next = cur.saturating_add_signed(delta).min(self.entries.len() - 1);
+        self.state.select(Some(next));
+    }
+
+    fn handle_key(&mut self, code: KeyCode) -> Option<StartChoice> {
+        if self.typing {
+            match code {
+                KeyCode::Char(c) => self.query_input.push(c),
+                KeyCode::Backspace => {
+                    self.query_input.pop();
+                }
+                KeyCode::Enter => {
+                    let query = self.query_input.trim().to_string();
+                    if !query.is_empty() {
+                        return Some(StartChoice::Search(query));
+                    }
+                }
+                KeyCode::Esc => {
+                    self.typing = false;
+                    self.query_input.clear();
+                }
+                _ => {}
+            }
+            return None;
+        }
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return Some(StartChoice::Quit),
+            KeyCode::Char('s') | KeyCode::Char('/') => self.typing = true,
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::PageUp => self.move_by(-10),
+            KeyCode::PageDown => self.move_by(10),
+            KeyCode::Home => self.move_by(isize::MIN),
+            KeyCode::End => self.move_by(isize::MAX),
+            KeyCode::Enter => return self.selected_query().map(StartChoice::Open),
+            KeyCode::Char('r') => return self.selected_query().map(StartChoice::Refresh),
+            _ => {}
+        }
+        None
+    }
+
+    /// One aligned line per entry: query, result count, age
+    fn lines(&self) -> Vec<String> {
+        let width = self
+            .entries
+            .iter()
+            .map(|e| text_width(&e.query))
+            .max()
+            .unwrap_or(0);
+        let counts: Vec<String> = self
+            .entries
+            .iter()
+            .map(|e| t!("tui-site-results", count = e.results.len()))
+            .collect();
+        let count_width = counts.iter().map(|c| text_width(c)).max().unwrap_or(0);
+        self.entries
+            .iter()
+            .zip(counts)
+            .map(|(e, count)| {
+                let mut line = format!(
+                    "{}  {}  {}",
+                    pad(&e.query, width),
+                    pad(&count, count_width),
+                    format_age(e.age())
+                );
+                if e.is_expired() {
+                    line.push_str(&format!(" ({})", t!("tui-start-expired")));
+                }
+                line
+            })
+            .collect()
+    }
+
+    fn footer(&self) -> String {
+        if self.typing {
+            format!(
+                "{} {}▏ {}",
+                t!("tui-new-search-prompt"),
+                self.query_input,
+                t!("tui-new-search-help")
+            )
+        } else {
+            t!("tui-start-help")
+        }
+    }
+}
+
+/// Let the user reopen, refresh or replace a cached search. The search
+/// browsed last time is highlighted.
+pub fn run_start_screen(cache: &SearchCache) -> anyhow::Result<StartChoice> {
+    let mut terminal = enter_terminal()?;
+    let mut screen = StartScreen::new(cache);
+    if let Some(last) = SessionState::load(&paths::tui_session_file()).last_query {
+        screen.select_query(&last);
+    }
+    drain_pending_keys()?;
+    let choice = loop {
+        terminal.draw(|f| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Length(2)])
+                .split(f.area());
+            let items: Vec<ListItem> = screen.lines().into_iter().map(ListItem::new).collect();
+            let list = List::new(items)
+                .block(
+                    Block::default()
+                        .title(t!("tui-start-title", count = screen.entries.len()))
+                        .borders(Borders::ALL),
+                )
+                .highlight_symbol("> ")
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+            f.render_stateful_widget(list, chunks[0], &mut screen.state);
+            let foot = Paragraph::new(screen.footer())
+                .block(Block::default().borders(Borders::TOP))
+                .wrap(Wrap { trim: false });
+            f.render_widget(foot, chunks[1]);
+        })?;
+
+        match event::read()? {
+            event::Event::Key(k) if k.kind == KeyEventKind::Press => {
+                if let Some(choice) = screen.handle_key(k.code) {
+                    break choice;
+                }
+            }
+            event::Event::Mouse(m) => match m.kind {
+                MouseEventKind::ScrollUp => screen.move_by(-1),
+                MouseEventKind::ScrollDown => screen.move_by(1),
+                _ => {}
+            },
+            _ => {}
+        }
+    };
+    leave_terminal(&mut terminal)?;
+    Ok(choice)
+}
+
+/// Live search view with a per-site progress dashboard.
+/// Returns the raw combined results when the search completes or is cancelled.
+pub async fn run_live_search_tui(
+    sites: Vec<SiteConfig>,
+    multi_query: &MultiQuery,
+    opts: &SearchOptions,
+    rate_limiter: Option<Arc<Mutex<RateLimiter>>>,
+) -> anyhow::Result<Vec<SearchResult>> {
+    let mut terminal = enter_terminal()?;
+    let total_sites = sites.len();
+    let mut dashboard = Dashboard::new(&sites);
+
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel::<SearchEvent>();
+    let search_handle = {
+        let multi_query = multi_query.clone();
+        let normalized = multi_query
+            .first()
+            .map(|f| f.get_search_terms())
+            .unwrap_or_default();
+        // Progress goes to the dashboard; nothing may print over the TUI
+        let opts = SearchOptions {
+            show_progress: false,
+            debug: false,
+            ..opts.clone()
+        };
+        tokio::spawn(async move {
+            let client = build_http_client();
+            search::run_search_with_events(
+                &client,
+                sites,
+                &multi_query,
+                &normalized,
+                &opts,
+                rate_limiter,
+                Some(event_tx),
+            )
+            .await
+        })
+    };
+
+    drain_pending_keys()?;
+
+    let mut should_quit = false;
+    while !should_quit {
+        let now = Instant::now();
+        while let Ok(event) = event_rx.try_recv() {
+            dashboard.apply(event, now);
+        }
+        let completed_sites = dashboard.finished();
+        let search_done = completed_sites >= total_sites || search_handle.is_finished();
+
+        terminal.draw(|f| {
+            let area = f.area();
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(3), // Title + progress bar
+                    Constraint::Min(1),    // Site list
+                    Constraint::Length(3), // Footer
+                ])
+                .split(area);
+
+            let progress_ratio = if total_sites > 0 {
+                completed_sites as f64 / total_sites as f64
+            } else {
+                0.0
+            };
+            let title = format!(
+                " {} ",
+                t!(
+                    "tui-searching-title",
+                    sites = total_sites,
+                    count = dashboard.results.len()
+                )
+            );
+            let gauge = Gauge::default()
+                .block(Block::default().title(title).borders(Borders::ALL))
+                .gauge_style(Style::default().fg(Color::Cyan))
+                .ratio(progress_ratio)
+                .label(t!(
+                    "tui-sites-progress",
+                    done = completed_sites,
+                    total = total_sites
+                ));
+            f.render_widget(gauge, chunks[0]);
+
+            let items: Vec<ListItem> = dashboard
+                .lines(now)
+                .into_iter()
+                .map(|l| ListItem::new(Line::from(l)))
+                .collect();
+            let site_list = List::new(items).block(
+                Block::default()
+                    .title(format!(" {} ", t!("tui-site-progress")))
+                    .borders(Borders::ALL),
+            );
+            f.render_widget(site_list, chunks[1]);
+
+            let footer_text = if search_done {
+                t!("tui-search-complete")
+            } else {
+                t!("tui-search-running")
+            };
+            let footer = Paragraph::new(footer_text)
+                .block(Block::default().borders(Borders::ALL))
+                .style(Style::default().fg(if search_done {
+                    Color::Green
+                } else {
+                    Color::Yellow
+                }));
+            f.render_widget(footer, chunks[2]);
+        })?;
+
+        if event::poll(Duration::from_millis(50))?
+            && let event::Event::Key(k) = event::read()?
+            && k.kind == KeyEventKind::Press
+        {
+            match k.code {
+                KeyCode::Char('q') | KeyCode::Esc => {
+                    should_quit = true;
+                    // Cancel search if still running
+                    search_handle.abort();
+                }
+                KeyCode::Enter if search_done => should_quit = true,
+                _ => {}
+            }
+        }
+
+        // If search is done and user hasn't pressed a key, auto-continue after brief pause
+        if search_done && !should_quit {
+            // Give user a moment to see the completion state
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            should_quit = true;
+        }
+    }
+
+    leave_terminal(&mut terminal)?;
+
+    // Wait for search task to finish (it may already be done or aborted), then
+    // collect results reported after the last redraw
+    let _ = search_handle.await;
+    while let Ok(event) = event_rx.try_recv() {
+        dashboard.apply(event, Instant::now());
+    }
+    Ok(dashboard.results)
+}
+
+/// Fetch a result page (through the solver when the site needs it) and pull
+/// download links out of it
+async fn fetch_detail_links(
+    client: &Client,
+    result: &SearchResult,
+    site: Option<&SiteConfig>,
+    opts: &SearchOptions,
+) -> anyhow::Result<Vec<String>> {
+    let needs_solver = opts.use_cf && site.is_some_and(|s| s.requires_cloudflare);
+    let html = if needs_solver {
+        cf::fetch_via_solver_with_headers(
+            client,
+            &result.url,
+            &opts.cf_url,
+            opts.cookie_headers.clone(),
+        )
+        .await?
+    } else {
+        fetcher::fetch_with_retry_headers(
+            &opts.routes.client_for(&result.site, client)?,
+            &result.url,
+            opts.cookie_headers.clone(),
+            None,
+            Some(&result.site),
+        )
+        .await?
+    };
+    Ok(extract_download_links(&html, &result.url))
+}
+
+/// Search `query` from inside the browser, showing the live per-site progress view.
+///
+/// Cached results are reused like in the one-shot flow, and fresh ones are cached.
+async fn search_from_browser(
+    query: &str,
+    session: &mut SearchSession<'_>,
+) -> anyhow::Result<Vec<SearchResult>> {
+    let multi_query = MultiQuery::parse(query);
+    let normalized = search::cache_key(query, &multi_query);
+    if let Some((cache, _)) = &session.cache
+        && let Some(entry) = cache.get(&normalized)
+    {
+        session.from_cache = true;
+        let mut results = entry.results.clone();
+        search::apply_ignore_and_notes(&mut results, &session.opts.hide_status);
+        return Ok(results);
+    }
+    session.from_cache = false;
+
+    let combined = run_live_search_tui(
+        session.sites.clone(),
+        &multi_query,
+        &session.opts,
+        session.rate_limiter.clone(),
+    )
+    .await?;
+    let mut results = search::post_process(combined, &multi_query, &session.opts);
+    search::add_steam_appids(&build_http_client(), &session.opts, &mut results).await;
+    search::verify_links(&build_http_client(), &session.opts, &mut results).await;
+    if let Some((cache, path)) = session.cache.as_mut()
+        && !results.is_empty()
+    {
+        cache.add(normalized, results.clone());
+        match cache.save_to_file_sync(path) {
+            Ok(()) => crate::index_results(cache, session.opts.debug),
+            Err(e) if session.opts.debug => eprintln!("[debug] Failed to save cache: {}", e),
+            Err(_) => {}
+        }
+    }
+    search::apply_ignore_and_notes(&mut results, &session.opts.hide_status);
+    Ok(results)
+}
+
+pub async fn run_live_tui(
+    results: Vec<SearchResult>,
+    mut session: SearchSession<'_>,
+) -> anyhow::Result<()> {
+    let settings_path = settings::default_settings_path();
+    let mut settings = Settings::load(&settings_path);
+
+    let mut terminal = enter_terminal()?;
+    let mut browser = Browser::new(&results);
+    browser.query = session.query.clone();
+    browser.details_enabled = session.fetch_details;
+    browser.view = settings.tui;
+    browser.regroup();
+    let (keys, key_warnings) = Keymap::new(&settings.keys);
+    browser.keys = keys;
+    browser.open_with = settings.open_with.iter().map(|o| o.name.clone()).collect();
+    browser.status = key_warnings.into_iter().next();
+    let session_file = paths::tui_session_file();
+    let mut saved = session
+        .cache
+        .as_ref()
+        .map(|_| SessionState::load(&session_file))
+        .unwrap_or_default();
+    if session.from_cache
+        && let Some(view) = saved.view_for(&session.query)
+    {
+        browser.restore_view(view);
+    }
+    drain_pending_keys()?;
+
+    let client = build_http_client();
+    let (detail_tx, mut detail_rx) = mpsc::unbounded_channel::<(String, DetailLinks)>();
+
+    let mut should_quit = false;
+    // Where the list was last drawn, to map mouse clicks to rows
+    let mut list_rect = Rect::default();
+    while !should_quit {
+        while let Ok((url, links)) = detail_rx.try_recv() {
+            browser.details.insert(url, links);
+        }
+
+        terminal.draw(|f| {
+            let area = f.area();
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Length(2)])
+                .split(area);
+
+            // Detail pane to the right on wide terminals, below the list otherwise
+            let (list_area, detail_area) = if !browser.show_details {
+                (chunks[0], None)
+            } else if chunks[0].width >= 100 {
+                let cols = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+                    .split(chunks[0]);
+                (cols[0], Some(cols[1]))
+            } else {
+                let rows = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(5), Constraint::Length(10)])
+                    .split(chunks[0]);
+                (rows[0], Some(rows[1]))
+            };
+
+            let list = Block::bordered().title(browser.title());
+            let boxes = list.inner(list_area);
+            f.render_widget(list, list_area);
+            browser.render_list(boxes, f.buffer_mut());
+            list_rect = list_area;
+
+            if let Some(detail_area) = detail_area {
+                let text: Vec<Line> = browser.detail_lines().into_iter().map(Line::from).collect();
+                let pane = Paragraph::new(text)
+                    .block(
+                        Block::default()
+                            .title(format!(" {} ", t!("tui-detail-title")))
+                            .borders(Borders::ALL),
+                    )
+                    .wrap(Wrap { trim: false });
+                f.render_widget(pane, detail_area);
+            }
+
+            // Footer: filter/search prompt or the selected URL
+            let foot = Paragraph::new(browser.footer())
+                .block(Block::default().borders(Borders::TOP))
+                .wrap(Wrap { trim: false });
+            f.render_widget(foot, chunks[1]);
+
+            if browser.show_help {
+                let lines = browser.help_lines();
+                let width = lines.iter().map(|l| text_width(l)).max().unwrap_or(0) + 4;
+                let overlay = centered(area, width as u16, lines.len() as u16 + 2);
+                let text: Vec<Line> = lines.into_iter().map(Line::from).collect();
+                let help = Paragraph::new(text).block(
+                    Block::default()
+                        .title(format!(" {} ", t!("tui-help-title")))
+                        .borders(Borders::ALL),
+                );
+                f.render_widget(Clear, overlay);
+                f.render_widget(help, overlay);
+            }
+        })?;
+
+        // Handle input & resize; non-blocking poll
+        if event::poll(std::time::Duration::from_millis(150))? {
+            match event::read()? {
+                event::Event::Key(k) => {
+                    if k.kind != KeyEventKind::Press {
+                        continue;
+                    }
+                    match browser.handle_key(k.code) {
+                        Action::None => {}
+                        Action::Quit => should_quit = true,
+                        Action::Search(query) => {
+                            if let Some((cache, _)) = &session.cache {
+                                saved.remember(&session.query, &browser, cache, &session_file);
+                            }
+                            // The progress view manages the terminal itself
+                            leave_terminal(&mut terminal)?;
+                            let results = search_from_browser(&query, &mut session).await?;
+                            session.query = query;
+                            terminal = enter_terminal()?;
+                            terminal.clear()?;
+                            let previous = browser;
+                            browser = Browser::new(&results);
+                            browser.query = session.query.clone();
+                            browser.details_enabled = session.fetch_details;
+                            browser.show_details = previous.show_details;
+                            browser.view = previous.view;
+                            browser.keys = previous.keys;
+                            browser.open_with = previous.open_with;
+                            browser.regroup();
+                            if session.from_cache
+                                && let Some(view) = saved.view_for(&session.query)
+                            {
+                                browser.restore_view(view);
+                            }
+                            drain_pending_keys()?;
+                        }
+                        Action::FetchDetails(result) => {
+                            let client = client.clone();
+                            let site = session
+                                .sites
+                                .iter()
+                                .find(|s| s.name.eq_ignore_ascii_case(&result.site))
+                                .cloned();
+                            let opts = session.opts.clone();
+                            let tx = detail_tx.clone();
+                            tokio::spawn(async move {
+                                let links = match fetch_detail_links(
+                                    &client,
+                                    &result,
+                                    site.as_ref(),
+                                    &opts,
+                                )
+                                .await
+                                {
+                                    Ok(links) => DetailLinks::Found(links),
+                                    Err(e) => DetailLinks::Failed(e.to_string()),
+                                };
+                                let _ = tx.send((result.url, links));
+                            });
+                        }
+                        Action::Bulk(action, targets) => {
+                            browser.status = Some(run_bulk(action, &targets));
+                        }
+                        Action::OpenWith(slot, result) => {
+                            browser.status =
+                                Some(run_open_with(&settings.open_with[slot], &result));
+                        }
+                        Action::Yank(text, with_title) => {
+                            browser.status = Some(match copy_to_clipboard(&text) {
+                                Ok(()) if with_title => t!("tui-yanked-title"),
+                                Ok(()) => t!("tui-yanked-url"),
+                                Err(e) => t!("tui-copy-failed", error = e.to_string()),
+                            });
+                        }
+                    }
+                    if browser.view != settings.tui {
+                        settings.tui = browser.view;
+                        if let Err(e) = settings.save(&settings_path) {
+                            browser.status =
+                                Some(t!("tui-settings-save-failed", error = e.to_string()));
+                        }
+                    }
+                }
+                event::Event::Mouse(m) => match m.kind {
+                    MouseEventKind::Down(MouseButton::Left) => {
+                        if let Some(row) = browser.row_at(list_rect, m.column, m.row)
+                            && let Some(r) = browser.click(row, Instant::now())
+                            && let Err(e) = open_url(&r.url)
+                        {
+                            browser.status = Some(t!("tui-open-failed", error = format!("{e:#}")));
+                        }
+                    }
+                    MouseEventKind::ScrollUp => browser.move_by(-3),
+                    MouseEventKind::ScrollDown => browser.move_by(3),
+                    _ => {}
+                },
+                event::Event::Resize(_, _) => {}
+                _ => {}
+            }
+        }
+    }
+
+    if let Some((cache, _)) = &session.cache {
+        saved.remember(&session.query, &browser, cache, &session_file);
+    }
+    leave_terminal(&mut terminal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r(site: &str, title: &str) -> SearchResult {
+        SearchResult {
+            site: site.into(),
+            title: title.into(),
+            url: format!(
+                "https://{site}.example/{}",
+                title.to_lowercase().replace(' ', "-")
+            ),
+            ..Default::default()
+        }
+    }
+
+    fn sample() -> Browser {
+        Browser::new(&[
+            r("fitgirl", "Elden Ring"),
+            r("fitgirl", "Hollow Knight"),
+            r("dodi", "Elden Ring Deluxe"),
+            r("steamrip", "Celeste"),
+        ])
+    }
+
+    fn selected_title(b: &Browser) -> &str {
+        b.selected_result().map(|r| r.title.as_str()).unwrap_or("")
+    }
+
+    /// The list drawn `width` columns wide and tall enough for every row, as text
+    fn screen(b: &mut Browser, width: u16) -> Vec<String> {
+        let area = Rect::new(0, 0, width, b.rows.len() as u16);
+        let mut buf = Buffer::empty(area);
+        b.render_list(area, &mut buf);
+        (0..area.height)
+            .map(|y| {
+                // A wide character's cell is followed by one it covers
+                let mut line = String::new();
+                let mut covered = 0;
+                for x in 0..width {
+                    let symbol = buf[(x, y)].symbol();
+                    if covered > 0 {
+                        covered -= 1;
+                        continue;
+                    }
+                    covered = text_width(symbol).saturating_sub(1);
+                    line.push_str(symbol);
+                }
+                line
+            })
+            .collect()
+    }
+
+    #[test]
+    fn groups_by_site_and_selects_first_result() {
+        let b = sample();
+        // dodi, fitgirl, steamrip boxes: 3 tops + 4 items + 3 bottoms
+        assert_eq!(b.rows.len(), 10);
+        assert_eq!(selected_title(&b), "Elden Ring Deluxe");
+    }
+
+    #[test]
+    fn navigation_skips_borders() {
+        let mut b = sample();
+        b.handle_key(KeyCode::Down);
+        assert_eq!(selected_title(&b), "Elden Ring");
+        b.handle_key(KeyCode::Up);
+        assert_eq!(selected_title(&b), "Elden Ring Deluxe");
+        b.handle_key(KeyCode::Up);
+        assert_eq!(selected_title(&b), "Elden Ring Deluxe");
+        b.handle_key(KeyCode::End);
+        assert_eq!(selected_title(&b), "Celeste");
+    }
+
+    #[test]
+    fn typing_a_filter_narrows_rows() {
+        let mut b = sample();
+        b.handle_key(KeyCode::Char('/'));
+        for c in "elden".chars() {
+            b.handle_key(KeyCode::Char(c));
+        }
+        assert_eq!(b.visible_count(), 2);
+        // Matches on site and URL too
+        b.set_filter("steamrip".into());
+        assert_eq!(b.visible_count(), 1);
+        assert_eq!(selected_title(&b), "Celeste");
+        b.set_filter("hollow-knight".into());
+        assert_eq!(selected_title(&b), "Hollow Knight");
+    }
+
+    #[test]
+    fn filter_keeps_selection_when_still_visible() {
+        let mut b = sample();
+        b.handle_key(KeyCode::Down);
+        assert_eq!(selected_title(&b), "Elden Ring");
+        b.set_filter("ring".into());
+        assert_eq!(selected_title(&b), "Elden Ring");
+    }
+
+    #[test]
+    fn n_and_shift_n_wrap_between_matches() {
+        let mut b = sample();
+        b.handle_key(KeyCode::Char('/'));
+        for c in "ring".chars() {
+            b.handle_key(KeyCode::Char(c));
+        }
+        b.handle_key(KeyCode::Enter);
+        assert_eq!(b.mode, Mode::Normal);
+        assert_eq!(selected_title(&b), "Elden Ring Deluxe");
+        b.handle_key(KeyCode::Char('n'));
+        assert_eq!(selected_title(&b), "Elden Ring");
+        b.handle_key(KeyCode::Char('n'));
+        assert_eq!(selected_title(&b), "Elden Ring Deluxe");
+        b.handle_key(KeyCode::Char('N'));
+        assert_eq!(selected_title(&b), "Elden Ring");
+    }
+
+    #[test]
+    fn esc_clears_filter_before_quitting() {
+        let mut b = sample();
+        b.set_filter("celeste".into());
+        assert_eq!(b.handle_key(KeyCode::Esc), Action::None);
+        assert_eq!(b.visible_count(), 4);
+        assert_eq!(b.handle_key(KeyCode::Esc), Action::Quit);
+    }
+
+    #[test]
+    fn s_prompts_for_a_new_search() {
+        let mut b = sample();
+        b.handle_key(KeyCode::Char('s'));
+        assert_eq!(b.mode, Mode::Query);
+        // Keys go to the prompt, not navigation or quit
+        for c in "hades q".chars() {
+            assert_eq!(b.handle_key(KeyCode::Char(c)), Action::None);
+        }
+        b.handle_key(KeyCode::Backspace);
+        assert!(b.footer().contains("hades"));
+        assert_eq!(b.handle_key(KeyCode::Enter), Action::Search("hades".into()));
+        assert_eq!(b.mode, Mode::Normal);
+        assert!(b.query_input.is_empty());
+    }
+
+    #[test]
+    fn empty_or_cancelled_search_prompt_does_nothing() {
+        let mut b = sample();
+        b.handle_key(KeyCode::Char('s'));
+        assert_eq!(b.handle_key(KeyCode::Enter), Action::None);
+        assert_eq!(b.mode, Mode::Query);
+        b.handle_key(KeyCode::Char('x'));
+        assert_eq!(b.handle_key(KeyCode::Esc), Action::None);
+        assert_eq!(b.mode, Mode::Normal);
+        assert_eq!(b.visible_count(), 4);
+    }
+
+    #[test]
+    fn space_marks_and_advances() {
+        let mut b = sample();
+        b.handle_key(KeyCode::Char(' '));
+        assert_eq!(selected_title(&b), "Elden Ring");
+        b.handle_key(KeyCode::Char(' '));
+        let Action::Bulk(BulkAction::CopyUrls, targets) = b.handle_key(KeyCode::Char('c')) else {
+            panic!("expected bulk copy");
+        };
+        let titles: Vec<&str> = targets.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(titles, vec!["Elden Ring Deluxe", "Elden Ring"]);
+        assert!(
+            screen(&mut b, 60)
+                .iter()
+                .any(|l| l.starts_with("│ * Elden Ring Deluxe"))
+        );
+    }
+
+    #[test]
+    fn bulk_action_without_marks_uses_highlighted_result() {
+        let mut b = sample();
+        b.handle_key(KeyCode::End);
+        assert_eq!(
+            b.handle_key(KeyCode::Char('O')),
+            Action::Bulk(BulkAction::Open, vec![r("steamrip", "Celeste")])
+        );
+        b.set_filter("zzz".into());
+        assert_eq!(b.handle_key(KeyCode::Char('e')), Action::None);
+    }
+
+    #[test]
+    fn mark_all_toggles_visible_results() {
+        let mut b = sample();
+        b.set_filter("ring".into());
+        b.handle_key(KeyCode::Char('a'));
+        assert_eq!(b.marked.len(), 2);
+        b.handle_key(KeyCode::Char('a'));
+        assert!(b.marked.is_empty());
+    }
+
+    #[test]
+    fn magnets_are_filtered_from_targets() {
+        let targets = vec![r("fitgirl", "Elden Ring")];
+        assert_eq!(
+            run_bulk(BulkAction::SendMagnets, &targets),
+            t!("tui-no-magnets")
+        );
+    }
+
+    #[test]
+    fn left_collapses_group_and_right_expands_it() {
+        let mut b = sample();
+        b.handle_key(KeyCode::Down);
+        assert_eq!(selected_title(&b), "Elden Ring");
+        b.handle_key(KeyCode::Left);
+        assert_eq!(b.selected_row(), Some(Row::Top(1)));
+        // fitgirl's two results are hidden but still counted
+        assert_eq!(b.rows.len(), 8);
+        assert_eq!(b.visible_count(), 4);
+        assert!(screen(&mut b, 40)[3].contains(" + fitgirl (2) "));
+
+        b.handle_key(KeyCode::Right);
+        assert_eq!(selected_title(&b), "Elden Ring");
+        assert_eq!(b.rows.len(), 10);
+    }
+
+    #[test]
+    fn cursor_stops_on_collapsed_headers_and_enter_expands() {
+        let mut b = sample();
+        b.handle_key(KeyCode::Left);
+        assert_eq!(b.selected_row(), Some(Row::Top(0)));
+        b.handle_key(KeyCode::Down);
+        assert_eq!(selected_title(&b), "Elden Ring");
+        b.handle_key(KeyCode::Up);
+        assert_eq!(b.selected_row(), Some(Row::Top(0)));
+        assert_eq!(b.handle_key(KeyCode::Enter), Action::None);
+        assert_eq!(selected_title(&b), "Elden Ring Deluxe");
+    }
+
+    #[test]
+    fn detail_pane_shows_title_metadata() {
+        let b = Browser::new(&[r("fitgirl", "Elden Ring v1.2.3 [45.2 GB]")]);
+        let lines = b.detail_lines();
+        assert_eq!(lines[0], "Elden Ring v1.2.3 [45.2 GB]");
+        assert!(lines.iter().any(|l| l.ends_with(": v1.2.3")));
+        assert!(lines.iter().any(|l| l.ends_with(": 45.2GB")));
+        assert!(lines.contains(&t!("tui-detail-disabled")));
+    }
+
+    #[test]
+    fn shift_s_cycles_sort_keeping_marks_and_selection() {
+        let mut b = sample();
+        b.query = "elden ring".into();
+        b.handle_key(KeyCode::Down);
+        b.handle_key(KeyCode::Char(' '));
+        assert_eq!(selected_title(&b), "Hollow Knight");
+
+        b.handle_key(KeyCode::Char('S'));
+        assert_eq!(b.view.sort, SortKey::Title);
+        assert_eq!(b.groups.len(), 1);
+        let titles: Vec<&str> = b.groups[0].items.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            [
+                "Celeste",
+                "Elden Ring",
+                "Elden Ring Deluxe",
+                "Hollow Knight"
+            ]
+        );
+        assert_eq!(selected_title(&b), "Hollow Knight");
+        assert_eq!(b.targets()[0].title, "Elden Ring");
+
+        b.handle_key(KeyCode::Char('S'));
+        assert_eq!(b.view.sort, SortKey::Relevance);
+        assert_eq!(b.groups[0].items[0].title, "Elden Ring");
+        assert_eq!(b.groups[0].items[3].title, "Celeste");
+
+        for _ in 0..3 {
+            b.handle_key(KeyCode::Char('S'));
+        }
+        assert_eq!(b.view.sort, SortKey::Site);
+        assert_eq!(b.groups.len(), 3);
+    }
+
+    #[test]
+    fn size_and_date_sorts_put_unknown_values_last() {
+        let mut b = Browser::new(&[
+            r("a", "Small [700 MB] 2023-05-01"),
+            r("a", "No metadata"),
+            r("b", "Big [45.2 GB] 2022-01-01"),
+            r("b", "Newest 15.06.2024"),
+        ]);
+        let titles = |b: &Browser| -> Vec<String> {
+            b.groups[0].items.iter().map(|r| r.title.clone()).collect()
+        };
+        b.view.sort = SortKey::Size;
+        b.regroup();
+        assert_eq!(
+            titles(&b)[..2],
+            ["Big [45.2 GB] 2022-01-01", "Small [700 MB] 2023-05-01"]
+        );
+        b.view.sort = SortKey::Date;
+        b.regroup();
+        assert_eq!(
+            titles(&b)[..3],
+            [
+                "Newest 15.06.2024",
+                "Small [700 MB] 2023-05-01",
+                "Big [45.2 GB] 2022-01-01"
+            ]
+        );
+    }
+
+    #[test]
+    fn column_toggles_change_row_text() {
+        let mut b = Browser::new(&[r("fitgirl", "Elden Ring v1.2 [45 GB]")]);
+        let row = |b: &mut Browser| screen(b, 120)[1].clone();
+        assert!(row(&mut b).contains("(https://fitgirl.example/"));
+        b.handle_key(KeyCode::Char('u'));
+        b.handle_key(KeyCode::Char('z'));
+        b.handle_key(KeyCode::Char('v'));
+        let text = row(&mut b);
+        assert!(!text.contains("https://"));
+        assert!(text.contains("[45GB] [v1.2]"));
+        assert!(b.view.show_size && b.view.show_version && !b.view.show_url);
+    }
+
+    #[test]
+    fn clicks_select_rows_and_double_click_opens() {
+        let mut b = sample();
+        let area = Rect::new(0, 0, 80, 20);
+        // Row 0 is drawn inside the border, one line down
+        assert_eq!(b.row_at(area, 5, 0), None);
+        assert_eq!(b.row_at(area, 5, 1), Some(0));
+        assert_eq!(b.row_at(area, 5, 15), None);
+
+        let t0 = Instant::now();
+        assert_eq!(b.click(5, t0), None);
+        assert_eq!(selected_title(&b), "Hollow Knight");
+        let opened = b.click(5, t0 + Duration::from_millis(200));
+        assert_eq!(opened.map(|r| r.title), Some("Hollow Knight".to_string()));
+        // Too slow for a double-click
+        b.click(1, t0);
+        assert_eq!(b.click(1, t0 + Duration::from_secs(1)), None);
+    }
+
+    #[test]
+    fn clicking_a_site_header_folds_and_unfolds_it() {
+        let mut b = sample();
+        b.click(0, Instant::now());
+        assert!(b.collapsed.contains(&0));
+        b.click(0, Instant::now() + Duration::from_secs(1));
+        assert!(b.collapsed.is_empty());
+        assert_eq!(selected_title(&b), "Elden Ring Deluxe");
+    }
+
+    fn cache_with(queries: &[&str]) -> SearchCache {
+        let mut cache = SearchCache::with_default_size();
+        for q in queries {
+            cache.add(q.to_string(), vec![r("fitgirl", q)]);
+        }
+        cache
+    }
+
+    #[test]
+    fn start_screen_lists_newest_first_and_opens_or_refreshes() {
+        let mut screen = StartScreen::new(&cache_with(&["elden ring", "hades"]));
+        let lines = screen.lines();
+        assert!(lines[0].starts_with("hades       "));
+        assert!(lines[1].contains(&t!("tui-site-results", count = 1)));
+        assert!(lines[1].ends_with(&format_age(0)));
+
+        assert_eq!(
+            screen.handle_key(KeyCode::Enter),
+            Some(StartChoice::Open("hades".into()))
+        );
+        screen.handle_key(KeyCode::Down);
+        assert_eq!(
+            screen.handle_key(KeyCode::Char('r')),
+            Some(StartChoice::Refresh("elden ring".into()))
+        );
+        assert_eq!(
+            screen.handle_key(KeyCode::Char('q')),
+            Some(StartChoice::Quit)
+        );
+    }
+
+    #[test]
+    fn start_screen_s_types_a_new_query() {
+        let mut screen = StartScreen::new(&cache_with(&["hades"]));
+        screen.handle_key(KeyCode::Char('s'));
+        for c in "celeste".chars() {
+            assert_eq!(screen.handle_key(KeyCode::Char(c)), None);
+        }
+        assert!(screen.footer().contains("celeste"));
+        assert_eq!(
+            screen.handle_key(KeyCode::Enter),
+            Some(StartChoice::Search("celeste".into()))
+        );
+    }
+
+    #[test]
+    fn ages_use_the_largest_whole_unit() {
+        assert_eq!(format_age(59), t!("tui-age-seconds", n = 59));
+        assert_eq!(format_age(150), t!("tui-age-minutes", n = 2));
+        assert_eq!(format_age(7200), t!("tui-age-hours", n = 2));
+        assert_eq!(format_age(3 * 86400), t!("tui-age-days", n = 3));
+    }
+
+    #[test]
+    fn question_mark_shows_help_and_next_key_only_closes_it() {
+        let mut b = sample();
+        b.handle_key(KeyCode::Char('?'));
+        assert!(b.show_help);
+        assert!(b.help_lines().iter().any(|l| l.starts_with("↑/k ")));
+        assert_eq!(b.handle_key(KeyCode::Char('q')), Action::None);
+        assert!(!b.show_help);
+        assert_eq!(b.handle_key(KeyCode::Char('q')), Action::Quit);
+    }
+
+    #[test]
+    fn custom_keys_drive_the_browser() {
+        let mut b = sample();
+        let overrides = [
+            ("down".to_string(), vec!["x".to_string()]),
+            ("quit".to_string(), vec!["Q".to_string()]),
+        ]
+        .into_iter()
+        .collect();
+        b.keys = Keymap::new(&overrides).0;
+        b.handle_key(KeyCode::Char('x'));
+        assert_eq!(selected_title(&b), "Elden Ring");
+        assert_eq!(b.handle_key(KeyCode::Char('q')), Action::None);
+        assert_eq!(b.handle_key(KeyCode::Char('Q')), Action::Quit);
+        assert!(b.help_lines().iter().any(|l| l.starts_with("x ")));
+    }
+
+    #[test]
+    fn y_yanks_url_and_shift_y_title_and_url() {
+        let mut b = sample();
+        assert_eq!(
+            b.handle_key(KeyCode::Char('y')),
+            Action::Yank("https://dodi.example/elden-ring-deluxe".into(), false)
+        );
+        assert_eq!(
+            b.handle_key(KeyCode::Char('Y')),
+            Action::Yank(
+                "Elden Ring Deluxe - https://dodi.example/elden-ring-deluxe".into(),
+                true
+            )
+        );
+        b.handle_key(KeyCode::Left);
+        assert_eq!(b.handle_key(KeyCode::Char('y')), Action::None);
+    }
+
+    #[test]
+    fn padding_respects_display_width() {
+        assert_eq!(pad("ab", 4), "ab  ");
+        // Wide CJK characters take two columns each
+        assert_eq!(text_width(&pad("原神", 6)), 6);
+        assert_eq!(pad("Elden Ring", 4), "Elden Ring");
+    }
+
+    #[test]
+    fn boxes_keep_their_width_with_non_ascii_titles() {
+        let mut b = Browser::new(&[
+            r("csrin", "Ведьмак 3: Дикая Охота"),
+            r("csrin", "東方Project 紅魔郷 — полная версия"),
+            r("f95zone", "Ñandú 🦤 Adventures"),
+        ]);
+        for width in [8, 20, 41, 80] {
+            for line in screen(&mut b, width) {
+                assert_eq!(text_width(&line), usize::from(width), "{line:?}");
+            }
+        }
+        // Long titles are clipped at the box's right edge
+        let lines = screen(&mut b, 30);
+        assert!(lines[1].starts_with("│ - Ведьмак"));
+        assert!(lines[1].ends_with('│'));
+    }
+
+    #[test]
+    fn selection_scrolls_into_view_and_cut_boxes_lose_their_edges() {
+        let mut b = sample();
+        b.handle_key(KeyCode::End);
+        let area = Rect::new(0, 0, 30, 4);
+        let mut buf = Buffer::empty(area);
+        b.render_list(area, &mut buf);
+        let text = |y: u16| (0..30).map(|x| buf[(x, y)].symbol()).collect::<String>();
+        // fitgirl's last result and bottom border, then steamrip's box down
+        // to Celeste, whose bottom border is off screen
+        assert_eq!(b.state.offset(), 5);
+        assert!(text(0).starts_with("│ - "));
+        assert!(text(1).starts_with('└'));
+        assert!(text(2).starts_with("┌ steamrip "));
+        assert!(text(3).starts_with("│ - Celeste"));
+        assert!(text(3).ends_with('│'));
+    }
+
+    #[test]
+    fn digits_pick_open_with_programs() {
+        let mut b = sample();
+        b.open_with = vec!["aria2".into(), "mpv".into()];
+        assert_eq!(
+            b.handle_key(KeyCode::Char('2')),
+            Action::OpenWith(1, r("dodi", "Elden Ring Deluxe"))
+        );
+        assert_eq!(b.handle_key(KeyCode::Char('3')), Action::None);
+        assert_eq!(b.status, Some(t!("tui-open-with-unset", key = 3)));
+        assert!(
+            b.help_lines()
+                .iter()
+                .any(|l| l.starts_with("1 ") && l.contains("aria2"))
+        );
+    }
+
+    #[test]
+    fn saved_view_restores_sort_folds_selection_and_scroll() {
+        let mut b = sample();
+        b.handle_key(KeyCode::Char('S'));
+        b.handle_key(KeyCode::End);
+        *b.state.offset_mut() = 2;
+        let saved = b.saved_view();
+        assert_eq!(saved.sort, SortKey::Title);
+        assert_eq!(
+            saved.selected.as_deref(),
+            Some("https://fitgirl.example/hollow-knight")
+        );
+
+        let mut fresh = sample();
+        fresh.restore_view(&saved);
+        assert_eq!(fresh.view.sort, SortKey::Title);
+        assert_eq!(selected_title(&fresh), "Hollow Knight");
+        assert_eq!(fresh.state.offset(), 2);
+
+        let mut folded = sample();
+        folded.handle_key(KeyCode::Down);
+        folded.handle_key(KeyCode::Left);
+        let saved = folded.saved_view();
+        assert_eq!(saved.collapsed, ["fitgirl"]);
+        let mut fresh = sample();
+        fresh.restore_view(&saved);
+        assert!(fresh.collapsed.contains(&1));
+        assert_eq!(selected_title(&fresh), "Elden Ring Deluxe");
+    }
+
+    #[test]
+    fn session_state_is_saved_and_pruned() {
+        let dir = std::env::temp_dir().join(format!("ws-tui-session-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let session_file = dir.join("tui_session.json");
+        let cache = cache_with(&["elden ring"]);
+
+        let mut state = SessionState::default();
+        state.views.insert("gone".into(), SavedView::default());
+        let mut b = sample();
+        b.handle_key(KeyCode::Down);
+        state.remember("Elden Ring", &b, &cache, &session_file);
+
+        let loaded = SessionState::load(&session_file);
+        assert_eq!(loaded.last_query.as_deref(), Some("Elden Ring"));
+        assert!(!loaded.views.contains_key("gone"));
+        assert_eq!(
+            loaded
+                .view_for("elden ring")
+                .and_then(|v| v.selected.clone()),
+            b.selected_result().map(|r| r.url.clone())
+        );
+
+        let mut screen = StartScreen::new(&cache_with(&["elden ring", "hades"]));
+        screen.select_query("Elden Ring");
+        assert_eq!(screen.selected_query().as_deref(), Some("elden ring"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn d_fetches_details_once_when_enabled() {
+        let mut b = sample();
+        assert_eq!(b.handle_key(KeyCode::Char('d')), Action::None);
+        assert_eq!(b.footer(), t!("tui-detail-disabled"));
+
+        b.details_enabled = true;
+        let deluxe = r("dodi", "Elden Ring Deluxe");
+        assert_eq!(
+            b.handle_key(KeyCode::Char('d')),
+            Action::FetchDetails(deluxe.clone())
+        );
+        assert!(b.detail_lines().contains(&t!("tui-detail-loading")));
+        // Already loading: no second request
+        assert_eq!(b.handle_key(KeyCode::Char('d')), Action::None);
+
+        b.details.insert(
+            deluxe.url.clone(),
+            DetailLinks::Found(vec!["magnet:?xt=urn:btih:abc".into()]),
+        );
+        assert!(
+            b.detail_lines()
+                .contains(&"  magnet:?xt=urn:btih:abc".to_string())
+        );
+    }
+
+    #[test]
+    fn i_toggles_detail_pane() {
+        let mut b = sample();
+        assert!(b.show_details);
+        b.handle_key(KeyCode::Char('i'));
+        assert!(!b.show_details);
+    }
+
+    fn site(name: &str) -> SiteConfig {
+        website_searcher_core::config::site_configs()
+            .into_iter()
+            .find(|s| s.name == name)
+            .expect("known site")
+    }
+
+    #[test]
+    fn dashboard_tracks_status_results_and_elapsed_time() {
+        let mut d = Dashboard::new(&[site("fitgirl"), site("dodi")]);
+        let t0 = Instant::now();
+        d.apply(
+            SearchEvent::Started {
+                site: "fitgirl".into(),
+            },
+            t0,
+        );
+        d.apply(
+            SearchEvent::Solving {
+                site: "fitgirl".into(),
+            },
+            t0,
+        );
+        assert_eq!(d.sites[0].status, SiteStatus::Solving);
+        assert_eq!(d.sites[1].status, SiteStatus::Queued);
+        assert_eq!(d.sites[1].elapsed(t0), None);
+
+        let t1 = t0 + Duration::from_millis(1500);
+        d.apply(
+            SearchEvent::Completed {
+                site: "fitgirl".into(),
+                results: vec![r("fitgirl", "Elden Ring")],
+            },
+            t1,
+        );
+        d.apply(
+            SearchEvent::Failed {
+                site: "dodi".into(),
+                error: "timed out".into(),
+            },
+            t1,
+        );
+        assert_eq!(d.finished(), 2);
+        assert_eq!(d.results.len(), 1);
+        // Elapsed time stops counting once a site finishes
+        let later = t1 + Duration::from_secs(10);
+        assert_eq!(d.sites[0].elapsed(later), Some(Duration::from_millis(1500)));
+
+        let lines = d.lines(later);
+        assert!(lines[0].contains("fitgirl") && lines[0].contains("1.5s"));
+        assert!(lines[1].ends_with("timed out"));
+    }
+
+    #[test]
+    fn events_for_unknown_sites_are_ignored() {
+        let mut d = Dashboard::new(&[site("fitgirl")]);
+        d.apply(
+            SearchEvent::Completed {
+                site: "nope".into(),
+                results: vec![r("nope", "X")],
+            },
+            Instant::now(),
+        );
+        assert_eq!(d.finished(), 0);
+        assert!(d.results.is_empty());
+    }
+
+    #[test]
+    fn filter_with_no_matches_shows_message() {
+        let mut b = sample();
+        b.set_filter("zzz".into());
+        assert!(b.rows.is_empty());
+        assert!(b.selected_result().is_none());
+        assert!(b.footer().contains("zzz"));
+    }
+}
@@ -0,0 +1,262 @@
+//! `wishlist search` subcommand: bulk search titles from a wishlist file.
+//!
+//! Accepts a plain text file (one title per line) or a Steam wishlist JSON export.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use tokio::sync::Mutex;
+
+use website_searcher_core::cache::SearchCache;
+use website_searcher_core::fetcher::build_http_client;
+use website_searcher_core::models::{SearchResult, SiteConfig};
+use website_searcher_core::rate_limiter::RateLimiter;
+
+use crate::search::{self, SearchOptions};
+
+/// Results for one wishlist title
+#[derive(Debug, Clone)]
+pub struct TitleReport {
+    pub title: String,
+    pub results: Vec<SearchResult>,
+}
+
+impl TitleReport {
+    /// Sites that returned at least one result, sorted and unique
+    pub fn found_on(&self) -> Vec<String> {
+        self.results
+            .iter()
+            .map(|r| r.site.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|site| site.to_string())
+            .collect()
+    }
+}
+
+/// Read titles from `path`, detecting Steam JSON exports by content.
+pub fn load_titles(path: &Path) -> Result<Vec<String>> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read wishlist {}", path.display()))?;
+    parse_titles(&raw)
+}
+
+/// Parse wishlist contents into a de-duplicated list of titles (order preserved).
+///
+/// JSON input may be an object keyed by AppID with `name` fields (Steam's
+/// `wishlistdata` export), an array of objects with `name`/`title`, or an array
+/// of strings. Anything else is treated as one title per line.
+pub fn parse_titles(raw: &str) -> Result<Vec<String>> {
+    let trimmed = raw.trim_start();
+    let titles: Vec<String> = if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        let v: Value = serde_json::from_str(trimmed).context("invalid wishlist JSON")?;
+        titles_from_json(&v)
+    } else {
+        raw.lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(str::to_string)
+            .collect()
+    };
+
+    let mut seen = BTreeSet::new();
+    Ok(titles
+        .into_iter()
+        .filter(|t| seen.insert(t.to_lowercase()))
+        .collect())
+}
+
+fn titles_from_json(v: &Value) -> Vec<String> {
+    fn name_of(item: &Value) -> Option<String> {
+        match item {
+            Value::String(s) => Some(s.clone()),
+            Value::Object(o) => o
+                .get("name")
+                .or_else(|| o.get("title"))
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            _ => None,
+        }
+    }
+
+    let mut entries: Vec<(i64, String)> = match v {
+        Value::Array(items) => items
+            .iter()
+            .filter_map(name_of)
+            .enumerate()
+            .map(|(i, n)| (i as i64 + 1, n))
+            .collect(),
+        // Steam export: { "<appid>": { "name": "...", "priority": N, ... }, ... }
+        Value::Object(map) => map
+            .values()
+            .filter_map(|item| {
+                let name = name_of(item)?;
+                let priority = item.get("priority").and_then(Value::as_i64);
+                Some((priority.unwrap_or(i64::MAX), name))
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    // Steam priority 0 means "unranked"; keep ranked ones first
+    entries.sort_by_key(|(p, _)| if *p == 0 { i64::MAX } else { *p });
+    entries
+        .into_iter()
+        .map(|(_, n)| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .collect()
+}
+
+/// Search every title in turn with a shared client and rate limiter.
+pub async fn search_titles(
+    titles: &[String],
+    sites: &[SiteConfig],
+    opts: &SearchOptions,
+    mut cache: Option<&mut SearchCache>,
+    rate_limiter: Option<Arc<Mutex<RateLimiter>>>,
+) -> Vec<TitleReport> {
+    let client = build_http_client();
+    let mut reports = Vec::with_capacity(titles.len());
+    for (i, title) in titles.iter().enumerate() {
+        if opts.show_progress {
+            eprintln!("🔎 [{}/{}] {}", i + 1, titles.len(), title);
+        }
+        let results = search::search_query(
+            &client,
+            sites,
+            title,
+            opts,
+            cache.as_deref_mut(),
+            rate_limiter.clone(),
+        )
+        .await;
+        reports.push(TitleReport {
+            title: title.clone(),
+            results,
+        });
+    }
+    reports
+}
+
+/// JSON report: per-title results plus a found/missing summary
+pub fn report_json(reports: &[TitleReport]) -> Value {
+    let titles: Vec<Value> = reports
+        .iter()
+        .map(|r| {
+            json!({
+                "title": r.title,
+                "found_on": r.found_on(),
+                "count": r.results.len(),
+                "results": r.results,
+            })
+        })
+        .collect();
+    let missing: Vec<&str> = reports
+        .iter()
+        .filter(|r| r.results.is_empty())
+        .map(|r| r.title.as_str())
+        .collect();
+    json!({
+        "titles": titles,
+        "summary": {
+            "total": reports.len(),
+            "found": reports.len() - missing.len(),
+            "missing": missing,
+        },
+    })
+}
+
+/// Plain-text report grouped per title
+pub fn print_report_table(reports: &[TitleReport]) {
+    for r in reports {
+        if r.results.is_empty() {
+            println!("{}", t!("wishlist-not-found", title = r.title.as_str()));
+            continue;
+        }
+        println!(
+            "{}",
+            t!(
+                "wishlist-found",
+                title = r.title.as_str(),
+                sites = r.found_on().join(", "),
+                count = r.results.len()
+            )
+        );
+        for res in &r.results {
+            println!("    [{}] {} ({})", res.site, res.title, res.url);
+        }
+    }
+    let found = reports.iter().filter(|r| !r.results.is_empty()).count();
+    println!();
+    println!(
+        "{}",
+        t!("wishlist-summary", found = found, total = reports.len())
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_text_skipping_comments_and_duplicates() {
+        let titles = parse_titles("Elden Ring\n\n# later\nHollow Knight\nelden ring\n").unwrap();
+        assert_eq!(titles, vec!["Elden Ring", "Hollow Knight"]);
+    }
+
+    #[test]
+    fn parses_steam_wishlistdata_export_by_priority() {
+        let raw = r#"{
+            "1245620": {"name": "ELDEN RING", "priority": 2},
+            "367520": {"name": "Hollow Knight", "priority": 1},
+            "413150": {"name": "Stardew Valley", "priority": 0}
+        }"#;
+        let titles = parse_titles(raw).unwrap();
+        assert_eq!(
+            titles,
+            vec!["Hollow Knight", "ELDEN RING", "Stardew Valley"]
+        );
+    }
+
+    #[test]
+    fn parses_json_arrays_of_strings_or_objects() {
+        assert_eq!(
+            parse_titles(r#"["Celeste", {"title": "Hades"}, {"name": "Tunic"}, 5]"#).unwrap(),
+            vec!["Celeste", "Hades", "Tunic"]
+        );
+    }
+
+    #[test]
+    fn report_summarises_found_and_missing() {
+        let reports = vec![
+            TitleReport {
+                title: "Elden Ring".into(),
+                results: vec![
+                    SearchResult {
+                        site: "steamrip".into(),
+                        title: "Elden Ring".into(),
+                        url: "https://steamrip.com/elden-ring/".into(),
+                        ..Default::default()
+                    },
+                    SearchResult {
+                        site: "fitgirl".into(),
+                        title: "Elden Ring".into(),
+                        url: "https://fitgirl-repacks.site/elden-ring/".into(),
+                        ..Default::default()
+                    },
+                ],
+            },
+            TitleReport {
+                title: "Hollow Knight".into(),
+                results: vec![],
+            },
+        ];
+        let v = report_json(&reports);
+        assert_eq!(v["summary"]["total"], 2);
+        assert_eq!(v["summary"]["found"], 1);
+        assert_eq!(v["summary"]["missing"][0], "Hollow Knight");
+        assert_eq!(v["titles"][0]["found_on"], json!(["fitgirl", "steamrip"]));
+    }
+}
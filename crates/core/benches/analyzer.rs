@@ -0,0 +1,64 @@
+//! Title similarity and cross-site de-duplication on large result sets.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+use website_searcher_core::analyzer::{
+    calculate_similarity, deduplicate_results, group_duplicates,
+};
+use website_searcher_core::models::SearchResult;
+
+const SITES: &[&str] = &["fitgirl", "dodi", "steamrip", "gog-games", "elamigos"];
+
+/// `n` results spread over several sites, with each game listed on a few of them
+fn results(n: usize) -> Vec<SearchResult> {
+    (0..n)
+        .map(|i| {
+            let site = SITES[i % SITES.len()];
+            let game = i / 3;
+            SearchResult {
+                site: site.into(),
+                title: format!(
+                    "Game Number {game}: Definitive Edition (v1.{} + {} DLCs)",
+                    i % 7,
+                    i % 4
+                ),
+                url: format!("https://{site}.example/game-{game}-{i}/"),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+fn bench_similarity(c: &mut Criterion) {
+    c.bench_function("calculate_similarity", |b| {
+        b.iter(|| {
+            calculate_similarity(
+                black_box("Elden Ring: Shadow of the Erdtree Deluxe Edition (v1.12.3 + DLC)"),
+                black_box("ELDEN RING Shadow of the Erdtree - Deluxe Edition [FitGirl Repack]"),
+            )
+        })
+    });
+}
+
+fn bench_dedup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dedup");
+    group.sample_size(10);
+    for n in [500, 1000] {
+        let input = results(n);
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(
+            BenchmarkId::new("deduplicate_results", n),
+            &input,
+            |b, input| b.iter(|| deduplicate_results(black_box(input.clone()))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("group_duplicates", n),
+            &input,
+            |b, input| b.iter(|| group_duplicates(black_box(input))),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_similarity, bench_dedup);
+criterion_main!(benches);
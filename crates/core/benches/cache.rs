@@ -0,0 +1,54 @@
+//! Saving and loading a full search cache file.
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+use website_searcher_core::cache::SearchCache;
+use website_searcher_core::models::{Provenance, SearchResult};
+
+/// A cache with `entries` queries of `per_query` results each
+fn filled_cache(entries: usize, per_query: usize) -> SearchCache {
+    let mut cache = SearchCache::new(entries);
+    for q in 0..entries {
+        let results = (0..per_query)
+            .map(|i| SearchResult {
+                site: "fitgirl".into(),
+                title: format!("Query {q} Result {i} (v1.{i})"),
+                url: format!("https://fitgirl-repacks.site/query-{q}-result-{i}/"),
+                source_url: Some(format!("https://fitgirl-repacks.site/?s=query+{q}")),
+                rank: Some(i + 1),
+                image: None,
+                fetched_at: Some(1_700_000_000),
+                provenance: Some(Provenance::Html),
+                steam_appid: None,
+                note: None,
+                link_check: None,
+                segment: None,
+            })
+            .collect();
+        cache.add(format!("query {q}"), results);
+    }
+    cache
+}
+
+fn bench_cache_file(c: &mut Criterion) {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let path = dir.path().join("cache.json");
+    let cache = filled_cache(50, 100);
+
+    let mut group = c.benchmark_group("cache_file");
+    group.bench_function("save", |b| {
+        b.iter(|| {
+            cache
+                .save_to_file_sync(black_box(&path))
+                .expect("save cache")
+        })
+    });
+    cache.save_to_file_sync(&path).expect("save cache");
+    group.bench_function("load", |b| {
+        b.iter(|| SearchCache::load_from_file_sync(black_box(&path)).expect("load cache"))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_cache_file);
+criterion_main!(benches);
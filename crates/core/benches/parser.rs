@@ -0,0 +1,87 @@
+//! `parse_results` on large generated search pages.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+use website_searcher_core::config::site_configs;
+use website_searcher_core::models::{SearchKind, SiteConfig};
+use website_searcher_core::parser::parse_results;
+
+fn wordpress_site() -> SiteConfig {
+    SiteConfig {
+        name: "example".to_string(),
+        base_url: "https://example.com/".to_string(),
+        search_kind: SearchKind::QueryParam,
+        query_param: Some("s".to_string()),
+        search_params: Vec::new(),
+        listing_path: None,
+        result_selector: "h2.entry-title a".into(),
+        title_attr: "text".to_string(),
+        url_attr: "href".to_string(),
+        requires_js: false,
+        requires_cloudflare: false,
+        timeout_seconds: 30,
+        retry_attempts: 3,
+        rate_limit_delay_ms: 1000,
+        crawl: None,
+        form: None,
+        graphql: None,
+        rules: Default::default(),
+    }
+}
+
+/// A WordPress search page with `n` articles, every other one matching "elden ring"
+fn wordpress_page(n: usize) -> String {
+    let mut html = String::from("<html><head><title>Search</title></head><body><main>");
+    for i in 0..n {
+        let title = if i % 2 == 0 {
+            format!("Elden Ring Edition {i} (v1.{i})")
+        } else {
+            format!("Unrelated Game {i}")
+        };
+        html.push_str(&format!(
+            r#"<article class="post"><header><h2 class="entry-title"><a href="/game-{i}/">{title}</a></h2></header>
+            <div class="entry-summary"><p>Repack of {title}, size 12.{i} GB</p><a href="/tag/{i}/">tag</a></div></article>"#
+        ));
+    }
+    html.push_str("</main></body></html>");
+    html
+}
+
+/// A phpBB search page with `n` topics
+fn phpbb_page(n: usize) -> String {
+    let mut html =
+        String::from(r#"<html><body><form action="./search.php"></form><ul class="topics">"#);
+    for i in 0..n {
+        html.push_str(&format!(
+            r#"<li class="row"><a class="topictitle" href="./viewtopic.php?t={i}&amp;hilit=elden">Elden Ring build {i}</a>
+            <span class="author">by user{i}</span></li>"#
+        ));
+    }
+    html.push_str("</ul></body></html>");
+    html
+}
+
+fn bench_parse_results(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_results");
+    let wordpress = wordpress_site();
+    let csrin = site_configs()
+        .into_iter()
+        .find(|s| s.name == "csrin")
+        .expect("csrin is configured");
+    for n in [100, 1000] {
+        let page = wordpress_page(n);
+        group.throughput(Throughput::Bytes(page.len() as u64));
+        group.bench_with_input(BenchmarkId::new("wordpress", n), &page, |b, page| {
+            b.iter(|| parse_results(&wordpress, black_box(page), "elden ring"))
+        });
+        let page = phpbb_page(n);
+        group.throughput(Throughput::Bytes(page.len() as u64));
+        group.bench_with_input(BenchmarkId::new("phpbb", n), &page, |b, page| {
+            b.iter(|| parse_results(&csrin, black_box(page), "elden ring"))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse_results);
+criterion_main!(benches);
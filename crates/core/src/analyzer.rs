@@ -7,6 +7,7 @@
 
 use crate::models::SearchResult;
 use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
 
 /// Extracted metadata from a search result title
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
@@ -82,6 +83,11 @@ impl ContentAnalyzer {
     pub fn deduplicate_results(&self, results: Vec<SearchResult>) -> Vec<SearchResult> {
         deduplicate_results_with_threshold(results, self.duplicate_threshold)
     }
+
+    /// Group cross-site duplicates instead of dropping them
+    pub fn group_duplicates(&self, results: &[SearchResult]) -> Vec<DuplicateGroup> {
+        group_duplicates_with_threshold(results, self.duplicate_threshold)
+    }
 }
 
 /// Extract metadata from a title string
@@ -149,6 +155,64 @@ pub fn extract_metadata(title: &str) -> ResultMetadata {
     metadata
 }
 
+/// Convert an extracted file size such as `"45.2GB"` or `"700MIB"` to bytes
+pub fn size_in_bytes(size: &str) -> Option<u64> {
+    let size = size.trim().to_uppercase();
+    let split = size.find(|c: char| c.is_ascii_alphabetic())?;
+    let (number, unit) = size.split_at(split);
+    let number: f64 = number.trim().parse().ok()?;
+    let multiplier: u64 = match unit.trim().trim_end_matches("IB").trim_end_matches('B') {
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => return None,
+    };
+    Some((number * multiplier as f64) as u64)
+}
+
+/// Turn an extracted release date into a sortable `(year, month, day)` key.
+///
+/// Day-first forms (`15.01.2024`, `15-01-2024`) are assumed unless the first
+/// number can only be a day in month-first order (`01/15/2024`).
+pub fn date_sort_key(date: &str) -> Option<(u32, u32, u32)> {
+    let parts: Vec<u32> = date
+        .split(['-', '/', '.'])
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+    let [a, b, c] = parts[..] else {
+        return None;
+    };
+    let key = if a > 999 {
+        (a, b, c)
+    } else if b > 12 {
+        (c, a, b)
+    } else {
+        (c, b, a)
+    };
+    (1..=12).contains(&key.1).then_some(key)
+}
+
+/// Whether a title is tagged as adult content (`NSFW`, `18+`, `Hentai`, ...)
+pub fn looks_nsfw(title: &str) -> bool {
+    const TAGS: &[&str] = &[
+        "nsfw",
+        "18+",
+        "hentai",
+        "adult only",
+        "adults only",
+        "uncensored",
+    ];
+    let title = title.to_lowercase();
+    TAGS.iter().any(|tag| {
+        title.match_indices(tag).any(|(i, _)| {
+            let before = title[..i].chars().next_back();
+            let after = title[i + tag.len()..].chars().next();
+            !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+        })
+    })
+}
+
 /// Calculate Levenshtein similarity between two strings (0.0 to 1.0)
 pub fn calculate_similarity(a: &str, b: &str) -> f32 {
     let a_normalized = normalize_for_comparison(a);
@@ -168,24 +232,30 @@ pub fn calculate_similarity(a: &str, b: &str) -> f32 {
     1.0 - (distance as f32 / max_len as f32)
 }
 
+/// Noise removed from titles before comparing them, compiled once
+static NOISE_PATTERNS: OnceLock<Vec<regex::Regex>> = OnceLock::new();
+
 /// Normalize a title for comparison
 fn normalize_for_comparison(title: &str) -> String {
     let mut normalized = title.to_lowercase();
 
     // Remove common noise patterns
-    let noise_patterns = [
-        r"\s*[\[(][^\])]*(?:gb|mb|tb|gib|mib|tib)[\])]", // Size markers in brackets
-        r"\s*[\[(]v?\d+(?:\.\d+)+[\])]",                 // Version markers in brackets
-        r"\s*v\d+(?:\.\d+)+",        // Standalone version markers (e.g., v1.2.3)
-        r"\s*[\[(]build\s*\d+[\])]", // Build markers
-        r"(?:repack|rip|proper|update|fix)", // Release tags
-        r"[-_]+",                    // Separators
-    ];
+    let noise_patterns = NOISE_PATTERNS.get_or_init(|| {
+        [
+            r"\s*[\[(][^\])]*(?:gb|mb|tb|gib|mib|tib)[\])]", // Size markers in brackets
+            r"\s*[\[(]v?\d+(?:\.\d+)+[\])]",                 // Version markers in brackets
+            r"\s*v\d+(?:\.\d+)+", // Standalone version markers (e.g., v1.2.3)
+            r"\s*[\[(]build\s*\d+[\])]", // Build markers
+            r"(?:repack|rip|proper|update|fix)", // Release tags
+            r"[-_]+",             // Separators
+        ]
+        .iter()
+        .filter_map(|pattern| regex::Regex::new(&format!("(?i){}", pattern)).ok())
+        .collect()
+    });
 
-    for pattern in noise_patterns {
-        if let Ok(re) = regex::Regex::new(&format!("(?i){}", pattern)) {
-            normalized = re.replace_all(&normalized, " ").to_string();
-        }
+    for re in noise_patterns {
+        normalized = re.replace_all(&normalized, " ").to_string();
     }
 
     // Collapse whitespace
@@ -285,15 +355,109 @@ pub fn deduplicate_results(results: Vec<SearchResult>) -> Vec<SearchResult> {
     deduplicate_results_with_threshold(results, 0.95)
 }
 
+/// One result of a [`DuplicateGroup`], with the metadata found in its title
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct GroupMember {
+    #[serde(flatten)]
+    pub result: SearchResult,
+    pub metadata: ResultMetadata,
+}
+
+/// Results from different sites that look like the same release
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    /// Title without size, version and release tags
+    pub title: String,
+    /// Sites of the members, in order of first appearance
+    pub sites: Vec<String>,
+    pub members: Vec<GroupMember>,
+}
+
+/// Group cross-site duplicates; every result ends up in exactly one group.
+///
+/// Groups are ordered by their first member's position in `results`.
+pub fn group_duplicates_with_threshold(
+    results: &[SearchResult],
+    threshold: f32,
+) -> Vec<DuplicateGroup> {
+    // Union-find over the duplicate pairs
+    let mut parent: Vec<usize> = (0..results.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    for (i, j) in find_duplicates_with_threshold(results, threshold) {
+        let (ri, rj) = (root(&mut parent, i), root(&mut parent, j));
+        if ri != rj {
+            parent[ri.max(rj)] = ri.min(rj);
+        }
+    }
+
+    let mut groups: Vec<DuplicateGroup> = Vec::new();
+    let mut group_of_root: std::collections::HashMap<usize, usize> =
+        std::collections::HashMap::new();
+    for (i, result) in results.iter().enumerate() {
+        let r = root(&mut parent, i);
+        let index = *group_of_root.entry(r).or_insert_with(|| {
+            groups.push(DuplicateGroup {
+                title: String::new(),
+                sites: Vec::new(),
+                members: Vec::new(),
+            });
+            groups.len() - 1
+        });
+        let group = &mut groups[index];
+        if !group.sites.iter().any(|s| **s == *result.site) {
+            group.sites.push(result.site.to_string());
+        }
+        group.members.push(GroupMember {
+            result: result.clone(),
+            metadata: extract_metadata(&result.title),
+        });
+    }
+    for group in &mut groups {
+        group.title = canonical_title(group.members.iter().map(|m| m.result.title.as_str()));
+    }
+    groups
+}
+
+/// Group duplicates using the [`ContentAnalyzer`] default threshold (0.85)
+pub fn group_duplicates(results: &[SearchResult]) -> Vec<DuplicateGroup> {
+    group_duplicates_with_threshold(results, ContentAnalyzer::default().duplicate_threshold)
+}
+
+/// Shortest member title once bracketed tags, versions and trailing separators are removed
+fn canonical_title<'a>(titles: impl Iterator<Item = &'a str> + Clone) -> String {
+    let tags = regex::Regex::new(r"\s*[\[(][^\])]*[\])]|\s+[vV]\d+(?:\.\d+)+\S*").ok();
+    let cleaned = titles.clone().filter_map(|title| {
+        let stripped = match &tags {
+            Some(re) => re.replace_all(title, "").to_string(),
+            None => title.to_string(),
+        };
+        let stripped = stripped
+            .trim_end_matches(|c: char| c.is_whitespace() || "-–—:,+".contains(c))
+            .trim();
+        (!stripped.is_empty()).then(|| stripped.split_whitespace().collect::<Vec<_>>().join(" "))
+    });
+    cleaned
+        .min_by_key(|t| t.chars().count())
+        .or_else(|| titles.into_iter().next().map(str::to_string))
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     fn make_result(site: &str, title: &str) -> SearchResult {
         SearchResult {
-            site: site.to_string(),
+            site: site.into(),
             title: title.to_string(),
             url: format!("https://{}.com/test", site),
+            ..Default::default()
         }
     }
 
@@ -413,8 +577,8 @@ mod tests {
 
         let deduped = deduplicate_results(results);
         assert_eq!(deduped.len(), 2);
-        assert_eq!(deduped[0].site, "fitgirl");
-        assert_eq!(deduped[1].site, "steamrip");
+        assert_eq!(&*deduped[0].site, "fitgirl");
+        assert_eq!(&*deduped[1].site, "steamrip");
     }
 
     #[test]
@@ -491,4 +655,58 @@ mod tests {
         let analyzer2 = ContentAnalyzer::with_threshold(-0.5);
         assert!((analyzer2.duplicate_threshold - 0.0).abs() < 0.01);
     }
+
+    #[test]
+    fn test_size_in_bytes() {
+        assert_eq!(size_in_bytes("1GB"), Some(1 << 30));
+        assert_eq!(size_in_bytes("1.5MB"), Some(3 << 19));
+        assert_eq!(size_in_bytes("2 TiB"), Some(2 << 40));
+        assert!(size_in_bytes("45.2GB") > size_in_bytes("900MB"));
+        assert_eq!(size_in_bytes("lots"), None);
+    }
+
+    #[test]
+    fn test_date_sort_key() {
+        assert_eq!(date_sort_key("2024-01-15"), Some((2024, 1, 15)));
+        assert_eq!(date_sort_key("15.01.2024"), Some((2024, 1, 15)));
+        assert_eq!(date_sort_key("01/15/2024"), Some((2024, 1, 15)));
+        assert_eq!(date_sort_key("03-02-2024"), Some((2024, 2, 3)));
+        assert_eq!(date_sort_key("2024-13-01"), None);
+        assert_eq!(date_sort_key("soon"), None);
+    }
+
+    #[test]
+    fn test_looks_nsfw() {
+        assert!(looks_nsfw("Some Game [18+] v1.2"));
+        assert!(looks_nsfw("Another Game (Uncensored)"));
+        assert!(looks_nsfw("NSFW Patch"));
+        assert!(!looks_nsfw("Elden Ring"));
+        assert!(!looks_nsfw("Unnsfwable"));
+    }
+
+    #[test]
+    fn test_group_duplicates() {
+        let results = vec![
+            make_result("fitgirl", "Elden Ring [45.2 GB] v1.10"),
+            make_result("dodi", "Hades"),
+            make_result("dodi", "Elden Ring - Repack"),
+            make_result("gog-games", "Elden Ring v1.10"),
+        ];
+        let groups = group_duplicates(&results);
+        assert_eq!(groups.len(), 2);
+
+        let elden = &groups[0];
+        assert_eq!(elden.title, "Elden Ring");
+        assert_eq!(elden.sites, ["fitgirl", "dodi", "gog-games"]);
+        assert_eq!(elden.members.len(), 3);
+        assert_eq!(
+            elden.members[0].metadata.file_size.as_deref(),
+            Some("45.2GB")
+        );
+        assert_eq!(elden.members[2].metadata.version.as_deref(), Some("v1.10"));
+
+        assert_eq!(groups[1].title, "Hades");
+        assert_eq!(groups[1].members[0].result, results[1]);
+        assert!(group_duplicates(&[]).is_empty());
+    }
 }
@@ -0,0 +1,715 @@
+//! Signing in to sites that need an account.
+//!
+//! Each such site has an [`Authenticator`]: it turns a [`LoginFlow`] (a
+//! username and password, or cookies pasted from a browser) into an
+//! [`AuthSession`], refreshes a saved session and tells whether one is still
+//! signed in. csrin is a phpBB forum and f95zone a XenForo one.
+//!
+//! Accounts are `[accounts.<site>]` tables in `config.toml`, whose password
+//! or cookie is usually a `secret:<name>` reference (see [`crate::secrets`]).
+//! [`Accounts`] signs in lazily, the first time a search reaches a site, and
+//! keeps each session as the secret `session.<site>`, so later searches reuse
+//! it until it needs a refresh or stops being valid.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, bail};
+use futures::future::BoxFuture;
+use reqwest::header::{COOKIE, HeaderMap, SET_COOKIE};
+use reqwest::{Client, redirect};
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::cookies;
+use crate::fetcher::get_anti_detection_config;
+use crate::models::SiteConfig;
+use crate::secrets::SecretStore;
+
+/// Age past which a saved session is refreshed before it is used
+pub const REFRESH_AFTER: Duration = Duration::from_secs(12 * 60 * 60);
+
+/// Longest wait for a login page or form post
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// How to sign in to a site
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoginFlow {
+    /// Post the site's login form
+    Password(Credentials),
+    /// Use cookies of a browser that is signed in: a `Cookie` header value or
+    /// a cookies.txt export
+    Cookie(String),
+}
+
+impl LoginFlow {
+    /// The flow with `secret:<name>` references replaced by their values
+    pub fn resolve(self, secrets: &SecretStore) -> Result<Self> {
+        Ok(match self {
+            Self::Password(creds) => Self::Password(Credentials {
+                password: secrets.resolve(&creds.password)?,
+                ..creds
+            }),
+            Self::Cookie(cookie) => Self::Cookie(secrets.resolve(&cookie)?),
+        })
+    }
+
+    /// Which account the flow signs in to, so a saved session of another one
+    /// is not reused. Pasted cookies are only stored as a hash.
+    pub fn account(&self) -> String {
+        match self {
+            Self::Password(creds) => creds.username.clone(),
+            Self::Cookie(cookie) => {
+                let mut hasher = DefaultHasher::new();
+                cookie.hash(&mut hasher);
+                format!("cookie:{:016x}", hasher.finish())
+            }
+        }
+    }
+}
+
+/// One `[accounts.<site>]` table in `config.toml`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct AccountConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// Cookies of a signed-in browser, used when there is no password
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cookie: Option<String>,
+}
+
+impl AccountConfig {
+    /// The password login if both halves are set, else the cookie
+    pub fn flow(&self) -> Option<LoginFlow> {
+        match (&self.username, &self.password, &self.cookie) {
+            (Some(username), Some(password), _) => Some(LoginFlow::Password(Credentials {
+                username: username.clone(),
+                password: password.clone(),
+            })),
+            (_, _, Some(cookie)) if !cookie.trim().is_empty() => {
+                Some(LoginFlow::Cookie(cookie.clone()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Cookies that keep a site signed in
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthSession {
+    /// [`LoginFlow::account`] of the login that made it
+    pub account: String,
+    pub cookies: BTreeMap<String, String>,
+    /// Unix timestamp (seconds) of the login or the last refresh
+    pub refreshed_at: u64,
+}
+
+impl AuthSession {
+    /// A session holding the pasted `cookie` (either format)
+    pub fn from_cookie(cookie: &str) -> Result<Self> {
+        let parsed = cookies::parse(cookie)?;
+        Ok(Self {
+            cookies: parsed
+                .cookies
+                .into_iter()
+                .map(|c| (c.name, c.value))
+                .collect(),
+            refreshed_at: now_secs(),
+            ..Default::default()
+        })
+    }
+
+    /// `Cookie` header value sending the session
+    pub fn header(&self) -> String {
+        self.cookies
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    /// Apply the `Set-Cookie` headers of a response: new values replace old
+    /// ones and deleted cookies are dropped
+    pub fn absorb(&mut self, headers: &HeaderMap) {
+        for set in headers.get_all(SET_COOKIE).iter() {
+            let Ok(set) = set.to_str() else { continue };
+            let mut parts = set.split(';').map(str::trim);
+            let Some((name, value)) = parts.next().and_then(|p| p.split_once('=')) else {
+                continue;
+            };
+            let expired = parts.any(|a| a.eq_ignore_ascii_case("max-age=0"));
+            if expired || value.is_empty() || value == "deleted" {
+                self.cookies.remove(name);
+            } else {
+                self.cookies.insert(name.to_string(), value.to_string());
+            }
+        }
+    }
+
+    /// Whether the session is old enough to be refreshed before use
+    pub fn is_stale(&self) -> bool {
+        now_secs().saturating_sub(self.refreshed_at) >= REFRESH_AFTER.as_secs()
+    }
+}
+
+/// Signing in to one site
+pub trait Authenticator: Send + Sync {
+    /// Name of the site, as in `sites.toml`
+    fn site(&self) -> &str;
+
+    /// Sign in with `flow`. `client` must not follow redirects, so the
+    /// cookies set by a redirecting login are seen.
+    fn login<'a>(
+        &'a self,
+        client: &'a Client,
+        flow: &'a LoginFlow,
+    ) -> BoxFuture<'a, Result<AuthSession>>;
+
+    /// Visit the site with `session` and keep the cookies it renews
+    fn refresh<'a>(
+        &'a self,
+        client: &'a Client,
+        session: &'a AuthSession,
+    ) -> BoxFuture<'a, Result<AuthSession>>;
+
+    /// Whether `session` holds the cookies of a signed-in user
+    fn is_valid(&self, session: &AuthSession) -> bool;
+}
+
+/// The authenticator of `site`, if it is one that has accounts
+pub fn authenticator_for(site: &SiteConfig) -> Option<Box<dyn Authenticator>> {
+    let base = site.base_url.trim_end_matches('/').to_string();
+    match site.name.to_lowercase().as_str() {
+        "csrin" => Some(Box::new(PhpbbAuthenticator {
+            site: site.name.clone(),
+            forum_url: base,
+        })),
+        "f95zone" => Some(Box::new(XenForoAuthenticator {
+            site: site.name.clone(),
+            base_url: base,
+        })),
+        _ => None,
+    }
+}
+
+/// Hidden fields of the first form on `html` that has a password input
+fn login_form_fields(html: &str) -> Vec<(String, String)> {
+    let doc = Html::parse_document(html);
+    let form = Selector::parse("form").unwrap();
+    let password = Selector::parse("input[type='password']").unwrap();
+    let hidden = Selector::parse("input[type='hidden'][name]").unwrap();
+    doc.select(&form)
+        .find(|f| f.select(&password).next().is_some())
+        .map(|f| {
+            f.select(&hidden)
+                .filter_map(|i| {
+                    let v = i.value();
+                    Some((v.attr("name")?.to_string(), v.attr("value")?.to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// GET `url` with the session's cookies, keeping the ones the response sets.
+/// Returns the body.
+async fn visit(client: &Client, url: &str, session: &mut AuthSession) -> Result<String> {
+    let mut request = client.get(url);
+    if !session.cookies.is_empty() {
+        request = request.header(COOKIE, session.header());
+    }
+    let response = request.send().await?;
+    session.absorb(response.headers());
+    Ok(response.text().await?)
+}
+
+/// POST `form` to `url` with the session's cookies, keeping the ones the
+/// response sets
+async fn post_form(
+    client: &Client,
+    url: &str,
+    form: &[(String, String)],
+    session: &mut AuthSession,
+) -> Result<()> {
+    let response = client
+        .post(url)
+        .header(COOKIE, session.header())
+        .form(form)
+        .send()
+        .await?;
+    let status = response.status();
+    session.absorb(response.headers());
+    if status.is_client_error() || status.is_server_error() {
+        bail!("login form answered {}", status);
+    }
+    Ok(())
+}
+
+/// A session made of pasted cookies
+fn cookie_session(cookie: &str) -> BoxFuture<'static, Result<AuthSession>> {
+    let session = AuthSession::from_cookie(cookie);
+    Box::pin(async move { session })
+}
+
+/// A phpBB forum: the login form at `ucp.php?mode=login`. phpBB sends a
+/// `<prefix>_u` cookie with the user id, `1` for guests.
+pub struct PhpbbAuthenticator {
+    site: String,
+    /// The forum's root, e.g. `https://cs.rin.ru/forum`
+    forum_url: String,
+}
+
+impl PhpbbAuthenticator {
+    async fn password_login(&self, client: &Client, creds: &Credentials) -> Result<AuthSession> {
+        let url = format!("{}/ucp.php?mode=login", self.forum_url);
+        let mut session = AuthSession::default();
+        let page = visit(client, &url, &mut session).await?;
+        let mut form = login_form_fields(&page);
+        form.extend([
+            ("username".to_string(), creds.username.clone()),
+            ("password".to_string(), creds.password.clone()),
+            ("autologin".to_string(), "on".to_string()),
+            ("login".to_string(), "Login".to_string()),
+        ]);
+        post_form(client, &url, &form, &mut session).await?;
+        session.refreshed_at = now_secs();
+        Ok(session)
+    }
+}
+
+impl Authenticator for PhpbbAuthenticator {
+    fn site(&self) -> &str {
+        &self.site
+    }
+
+    fn login<'a>(
+        &'a self,
+        client: &'a Client,
+        flow: &'a LoginFlow,
+    ) -> BoxFuture<'a, Result<AuthSession>> {
+        match flow {
+            LoginFlow::Password(creds) => Box::pin(self.password_login(client, creds)),
+            LoginFlow::Cookie(cookie) => cookie_session(cookie),
+        }
+    }
+
+    fn refresh<'a>(
+        &'a self,
+        client: &'a Client,
+        session: &'a AuthSession,
+    ) -> BoxFuture<'a, Result<AuthSession>> {
+        Box::pin(async move {
+            let mut session = session.clone();
+            visit(
+                client,
+                &format!("{}/index.php", self.forum_url),
+                &mut session,
+            )
+            .await?;
+            session.refreshed_at = now_secs();
+            Ok(session)
+        })
+    }
+
+    fn is_valid(&self, session: &AuthSession) -> bool {
+        session
+            .cookies
+            .iter()
+            .any(|(name, value)| name.ends_with("_u") && !value.is_empty() && value != "1")
+    }
+}
+
+/// A XenForo forum: the login form at `login/`, posted with its `_xfToken`.
+/// XenForo sends `xf_user` to users who are signed in and chose to stay so.
+pub struct XenForoAuthenticator {
+    site: String,
+    /// The forum's root, e.g. `https://f95zone.to`
+    base_url: String,
+}
+
+impl XenForoAuthenticator {
+    async fn password_login(&self, client: &Client, creds: &Credentials) -> Result<AuthSession> {
+        let mut session = AuthSession::default();
+        let page = visit(client, &format!("{}/login/", self.base_url), &mut session).await?;
+        let mut form = login_form_fields(&page);
+        form.extend([
+            ("login".to_string(), creds.username.clone()),
+            ("password".to_string(), creds.password.clone()),
+            ("remember".to_string(), "1".to_string()),
+        ]);
+        let url = format!("{}/login/login", self.base_url);
+        post_form(client, &url, &form, &mut session).await?;
+        session.refreshed_at = now_secs();
+        Ok(session)
+    }
+}
+
+impl Authenticator for XenForoAuthenticator {
+    fn site(&self) -> &str {
+        &self.site
+    }
+
+    fn login<'a>(
+        &'a self,
+        client: &'a Client,
+        flow: &'a LoginFlow,
+    ) -> BoxFuture<'a, Result<AuthSession>> {
+        match flow {
+            LoginFlow::Password(creds) => Box::pin(self.password_login(client, creds)),
+            LoginFlow::Cookie(cookie) => cookie_session(cookie),
+        }
+    }
+
+    fn refresh<'a>(
+        &'a self,
+        client: &'a Client,
+        session: &'a AuthSession,
+    ) -> BoxFuture<'a, Result<AuthSession>> {
+        Box::pin(async move {
+            let mut session = session.clone();
+            visit(client, &format!("{}/", self.base_url), &mut session).await?;
+            session.refreshed_at = now_secs();
+            Ok(session)
+        })
+    }
+
+    fn is_valid(&self, session: &AuthSession) -> bool {
+        session
+            .cookies
+            .get("xf_user")
+            .is_some_and(|v| !v.is_empty())
+    }
+}
+
+/// Secret holding the saved session of `site`
+fn session_secret(site: &str) -> String {
+    format!("session.{site}")
+}
+
+struct AccountsState {
+    /// Cookie headers of the sites signed in to by this instance
+    signed_in: HashMap<String, String>,
+    /// Sites whose login failed, not tried again
+    failed: HashSet<String>,
+}
+
+/// The configured accounts and their sessions, signing in on first use
+pub struct Accounts {
+    configs: BTreeMap<String, AccountConfig>,
+    secrets: SecretStore,
+    client: Client,
+    state: Mutex<AccountsState>,
+}
+
+impl fmt::Debug for Accounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Accounts")
+            .field("sites", &self.configs.keys().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Accounts {
+    /// Accounts from `[accounts.<site>]` tables, with passwords and sessions
+    /// in `secrets`
+    pub fn new(configs: BTreeMap<String, AccountConfig>, secrets: SecretStore) -> Self {
+        let client = Client::builder()
+            .user_agent(get_anti_detection_config().get_user_agent())
+            .redirect(redirect::Policy::none())
+            .timeout(LOGIN_TIMEOUT)
+            .build()
+            .expect("failed to build login client");
+        Self {
+            configs: configs
+                .into_iter()
+                .map(|(site, config)| (site.to_lowercase(), config))
+                .collect(),
+            secrets,
+            client,
+            state: Mutex::new(AccountsState {
+                signed_in: HashMap::new(),
+                failed: HashSet::new(),
+            }),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.configs.is_empty()
+    }
+
+    /// `Cookie` header value signing in to `site`: the saved session, else a
+    /// refreshed one, else a new login. `None` if the site has no account or
+    /// signing in failed (which is logged and not retried by this instance).
+    pub async fn cookie_for(&self, site: &SiteConfig) -> Option<String> {
+        let name = site.name.to_lowercase();
+        let config = self.configs.get(&name)?;
+        let auth = authenticator_for(site)?;
+        let mut state = self.state.lock().await;
+        if let Some(header) = state.signed_in.get(&name) {
+            return Some(header.clone());
+        }
+        if state.failed.contains(&name) {
+            return None;
+        }
+        match self.sign_in(auth.as_ref(), config, &name).await {
+            Ok(header) => {
+                state.signed_in.insert(name, header.clone());
+                Some(header)
+            }
+            Err(e) => {
+                tracing::warn!("Signing in to {} failed: {:#}", site.name, e);
+                state.failed.insert(name);
+                None
+            }
+        }
+    }
+
+    /// Sign in with the saved or a new session and save it if it changed
+    async fn sign_in(
+        &self,
+        auth: &dyn Authenticator,
+        config: &AccountConfig,
+        name: &str,
+    ) -> Result<String> {
+        let flow = config
+            .flow()
+            .context("the account has no password or cookie")?
+            .resolve(&self.secrets)?;
+        let secret = session_secret(name);
+        let saved: Option<AuthSession> = match self.secrets.get(&secret) {
+            Ok(saved) => saved.and_then(|json| serde_json::from_str(&json).ok()),
+            Err(e) => {
+                tracing::warn!("Ignoring the saved session of {}: {}", name, e);
+                None
+            }
+        };
+        let session = self.session(auth, &flow, saved.as_ref()).await?;
+        if saved.as_ref() != Some(&session)
+            && let Err(e) = self.secrets.set(&secret, &serde_json::to_string(&session)?)
+        {
+            tracing::warn!("Failed to save the session of {}: {}", name, e);
+        }
+        Ok(session.header())
+    }
+
+    async fn session(
+        &self,
+        auth: &dyn Authenticator,
+        flow: &LoginFlow,
+        saved: Option<&AuthSession>,
+    ) -> Result<AuthSession> {
+        let account = flow.account();
+        if let Some(saved) = saved.filter(|s| s.account == account && auth.is_valid(s)) {
+            if !saved.is_stale() {
+                return Ok(saved.clone());
+            }
+            match auth.refresh(&self.client, saved).await {
+                Ok(refreshed) if auth.is_valid(&refreshed) => return Ok(refreshed),
+                Ok(_) => tracing::debug!("Session of {} expired", auth.site()),
+                Err(e) => tracing::debug!("Refreshing {} failed: {}", auth.site(), e),
+            }
+        }
+        let mut session = auth
+            .login(&self.client, flow)
+            .await
+            .with_context(|| format!("login to {}", auth.site()))?;
+        if !auth.is_valid(&session) {
+            bail!("{} did not accept the login", auth.site());
+        }
+        session.account = account;
+        Ok(session)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use tempfile::tempdir;
+
+    fn store(dir: &std::path::Path) -> SecretStore {
+        SecretStore::file_only(dir.join("secrets.bin"), dir.join("secrets.key"))
+    }
+
+    fn site(name: &str, base_url: &str) -> SiteConfig {
+        SiteConfig {
+            name: name.into(),
+            base_url: base_url.into(),
+            search_kind: crate::models::SearchKind::ListingPage,
+            result_selector: "a".into(),
+            timeout_seconds: 30,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn set_cookie_headers_update_and_delete_cookies() {
+        let mut session = AuthSession::from_cookie("phpbb3_u=1; phpbb3_sid=guest").unwrap();
+        let mut headers = HeaderMap::new();
+        for set in [
+            "phpbb3_u=42; path=/; HttpOnly",
+            "phpbb3_k=key; expires=Fri, 01 Jan 2100 00:00:00 GMT",
+            "phpbb3_sid=deleted; Max-Age=0",
+        ] {
+            headers.append(SET_COOKIE, HeaderValue::from_static(set));
+        }
+        session.absorb(&headers);
+        assert_eq!(session.header(), "phpbb3_k=key; phpbb3_u=42");
+
+        let csrin = authenticator_for(&site("csrin", "https://a.example/forum/")).unwrap();
+        assert!(csrin.is_valid(&session));
+        assert!(!csrin.is_valid(&AuthSession::from_cookie("phpbb3_u=1").unwrap()));
+        let f95 = authenticator_for(&site("f95zone", "https://b.example/")).unwrap();
+        assert!(!f95.is_valid(&session));
+        assert!(authenticator_for(&site("fitgirl", "https://c.example/")).is_none());
+    }
+
+    #[test]
+    fn password_wins_over_cookie_and_accounts_differ() {
+        let both = AccountConfig {
+            username: Some("alice".into()),
+            password: Some("secret".into()),
+            cookie: Some("xf_user=1".into()),
+        };
+        assert_eq!(both.flow().unwrap().account(), "alice");
+        let cookie = AccountConfig {
+            username: Some("alice".into()),
+            cookie: Some("xf_user=1".into()),
+            ..Default::default()
+        };
+        let account = cookie.flow().unwrap().account();
+        assert!(account.starts_with("cookie:") && !account.contains("xf_user"));
+        assert_eq!(AccountConfig::default().flow(), None);
+    }
+
+    #[test]
+    fn login_form_hidden_fields_are_kept() {
+        let html = r#"<form id="search"><input type="hidden" name="q" value="x"></form>
+            <form id="login"><input type="hidden" name="form_token" value="tok">
+            <input type="hidden" name="sid" value="abc"><input type="password" name="password"></form>"#;
+        assert_eq!(
+            login_form_fields(html),
+            [
+                ("form_token".to_string(), "tok".to_string()),
+                ("sid".to_string(), "abc".to_string())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn xenforo_posts_its_token_and_keeps_the_redirect_cookies() {
+        let mut server = mockito::Server::new_async().await;
+        let page = server
+            .mock("GET", "/login/")
+            .with_header("set-cookie", "xf_csrf=c1; path=/")
+            .with_body(
+                r#"<form action="/login/login" method="post">
+                <input type="text" name="login"><input type="password" name="password">
+                <input type="hidden" name="_xfToken" value="tok"></form>"#,
+            )
+            .create_async()
+            .await;
+        let post = server
+            .mock("POST", "/login/login")
+            .match_header("cookie", "xf_csrf=c1")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("_xfToken".into(), "tok".into()),
+                mockito::Matcher::UrlEncoded("login".into(), "alice".into()),
+                mockito::Matcher::UrlEncoded("password".into(), "secret".into()),
+            ]))
+            .with_status(303)
+            .with_header("location", "/")
+            .with_header("set-cookie", "xf_user=7%2Cabc; path=/")
+            .with_header("set-cookie", "xf_session=s2; path=/")
+            .create_async()
+            .await;
+
+        let dir = tempdir().unwrap();
+        let configs = BTreeMap::from([(
+            "f95zone".to_string(),
+            AccountConfig {
+                username: Some("alice".into()),
+                password: Some("secret".into()),
+                cookie: None,
+            },
+        )]);
+        let accounts = Accounts::new(configs, store(dir.path()));
+        let f95 = site("f95zone", &format!("{}/", server.url()));
+        let cookie = accounts.cookie_for(&f95).await.unwrap();
+        assert_eq!(cookie, "xf_csrf=c1; xf_session=s2; xf_user=7%2Cabc");
+        // The second search reuses the session
+        assert_eq!(accounts.cookie_for(&f95).await, Some(cookie));
+        page.assert_async().await;
+        post.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn cookie_logins_are_saved_and_reused() {
+        let dir = tempdir().unwrap();
+        let secrets = store(dir.path());
+        secrets.set("f95", "xf_user=7%2Cabc; xf_session=s").unwrap();
+        let f95 = site("f95zone", "https://f95.example/");
+        let configs = BTreeMap::from([(
+            "F95zone".to_string(),
+            AccountConfig {
+                cookie: Some("secret:f95".into()),
+                ..Default::default()
+            },
+        )]);
+        let accounts = Accounts::new(configs, secrets.clone());
+        assert_eq!(
+            accounts.cookie_for(&f95).await.as_deref(),
+            Some("xf_session=s; xf_user=7%2Cabc")
+        );
+        assert_eq!(
+            accounts.cookie_for(&site("csrin", "https://a/")).await,
+            None
+        );
+        let saved = secrets.get("session.f95zone").unwrap().unwrap();
+        assert!(saved.contains("xf_user"));
+
+        // A cookie that does not sign in fails once and is not retried
+        let guest = BTreeMap::from([(
+            "f95zone".to_string(),
+            AccountConfig {
+                cookie: Some("xf_session=guest".into()),
+                ..Default::default()
+            },
+        )]);
+        let accounts = Accounts::new(guest, secrets.clone());
+        assert_eq!(accounts.cookie_for(&f95).await, None);
+        assert!(accounts.state.lock().await.failed.contains("f95zone"));
+        assert_eq!(secrets.get("session.f95zone").unwrap(), Some(saved));
+
+        // So does a reference to a secret that is not stored
+        let missing = BTreeMap::from([(
+            "f95zone".to_string(),
+            AccountConfig {
+                cookie: Some("secret:gone".into()),
+                ..Default::default()
+            },
+        )]);
+        let accounts = Accounts::new(missing, secrets);
+        assert_eq!(accounts.cookie_for(&f95).await, None);
+    }
+}
@@ -0,0 +1,45 @@
+//! Opening result links in the system's default browser or torrent client.
+//!
+//! Shared by the CLI's results browser and the GUI backend. Links come from
+//! scraped pages, so only web and magnet links are opened, and they are handed
+//! to the platform launcher without going through a shell.
+
+use anyhow::Context;
+use reqwest::Url;
+
+/// Parse `url`, allowing only web and magnet links to be opened
+pub fn checked_url(url: &str) -> anyhow::Result<Url> {
+    let parsed = Url::parse(url.trim()).context("invalid URL")?;
+    match parsed.scheme() {
+        "http" | "https" | "magnet" => Ok(parsed),
+        other => anyhow::bail!("refusing to open {other}: URL"),
+    }
+}
+
+/// Open `url` with the default handler for its scheme: `open` on macOS,
+/// `ShellExecute` on Windows and `xdg-open` (or a desktop equivalent) elsewhere.
+///
+/// Returns once the launcher has started; fails if the URL is not a web or
+/// magnet link or no launcher could be started.
+pub fn open_url(url: &str) -> anyhow::Result<()> {
+    let url = checked_url(url)?;
+    open::that_detached(url.as_str()).with_context(|| format!("failed to open {url}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_web_and_magnet_links_are_opened() {
+        assert!(checked_url(" https://fitgirl-repacks.site/elden-ring/?a=1&b=2 ").is_ok());
+        assert!(checked_url("magnet:?xt=urn:btih:abc&dn=Elden%20Ring").is_ok());
+        for url in ["file:///etc/passwd", "javascript:alert(1)", "not a url"] {
+            assert!(open_url(url).is_err(), "{url} was opened");
+        }
+        assert_eq!(
+            checked_url("smb://host/share").unwrap_err().to_string(),
+            "refusing to open smb: URL"
+        );
+    }
+}
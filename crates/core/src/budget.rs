@@ -0,0 +1,171 @@
+//! Daily request budgets per site.
+//!
+//! Watchlists, batch runs and wishlists can send a site far more requests than
+//! a person searching by hand would. Every page fetched from a site is counted
+//! in `request_budget.json` in the state directory, per UTC day, and a site can
+//! be given a daily budget in `config.toml`:
+//!
+//! ```toml
+//! [search.daily_budget]
+//! csrin = 50
+//! "*" = 300   # every site without its own budget
+//! ```
+//!
+//! Once a site's budget is spent, searches skip it until midnight UTC.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::paths;
+
+/// Budget key covering every site without its own
+pub const ANY_SITE: &str = "*";
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / DAY_SECS)
+        .unwrap_or(0)
+}
+
+/// Requests sent to each site (by lowercased name) on one UTC day
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestLog {
+    /// Days since the Unix epoch
+    day: u64,
+    sites: BTreeMap<String, u32>,
+}
+
+impl RequestLog {
+    /// Load the counts, starting empty if the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid request counts {:?}: {}", path, e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        paths::write_atomic(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Requests sent to `site` today
+    pub fn used(&self, site: &str) -> u32 {
+        if self.day != today() {
+            return 0;
+        }
+        self.sites.get(&site.to_lowercase()).copied().unwrap_or(0)
+    }
+
+    pub fn record(&mut self, site: &str) {
+        let day = today();
+        if self.day != day {
+            self.day = day;
+            self.sites.clear();
+        }
+        *self.sites.entry(site.to_lowercase()).or_default() += 1;
+    }
+}
+
+/// The configured budgets and today's counts, shared by every fetch of a run
+#[derive(Debug, Default)]
+pub struct DailyBudget {
+    /// Requests a day by site name, [`ANY_SITE`] for the rest
+    limits: BTreeMap<String, u32>,
+    log: Mutex<RequestLog>,
+}
+
+impl DailyBudget {
+    pub fn new(limits: BTreeMap<String, u32>, log: RequestLog) -> Self {
+        Self {
+            limits,
+            log: Mutex::new(log),
+        }
+    }
+
+    /// Today's budget of `site`, if it has one
+    pub fn limit(&self, site: &str) -> Option<u32> {
+        self.limits
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(site))
+            .or_else(|| self.limits.get_key_value(ANY_SITE))
+            .map(|(_, limit)| *limit)
+    }
+
+    /// Requests sent to `site` today
+    pub fn used(&self, site: &str) -> u32 {
+        self.log
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .used(site)
+    }
+
+    /// The budget of `site` if today's is spent
+    pub fn spent(&self, site: &str) -> Option<u32> {
+        self.limit(site).filter(|limit| self.used(site) >= *limit)
+    }
+
+    /// Count a request to `site`, or refuse it once the day's budget is spent
+    pub fn spend(&self, site: &str) -> anyhow::Result<()> {
+        let mut log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(limit) = self.limit(site).filter(|limit| log.used(site) >= *limit) {
+            anyhow::bail!("daily budget of {limit} requests spent");
+        }
+        log.record(site);
+        Ok(())
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        self.log
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .save(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn sites_stop_at_their_budget_until_the_next_day() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("request_budget.json");
+        let limits = BTreeMap::from([("csrin".to_string(), 2), (ANY_SITE.to_string(), 3)]);
+        let budget = DailyBudget::new(limits.clone(), RequestLog::load(&path));
+        assert_eq!(budget.limit("CSRIN"), Some(2));
+        assert_eq!(budget.limit("fitgirl"), Some(3));
+
+        budget.spend("csrin").unwrap();
+        budget.spend("csrin").unwrap();
+        assert_eq!(budget.spent("csrin"), Some(2));
+        let refused = budget.spend("csrin").unwrap_err();
+        assert_eq!(refused.to_string(), "daily budget of 2 requests spent");
+        assert_eq!(budget.used("csrin"), 2);
+        budget.spend("fitgirl").unwrap();
+        assert_eq!(budget.spent("fitgirl"), None);
+        budget.save(&path).unwrap();
+
+        let reloaded = DailyBudget::new(limits.clone(), RequestLog::load(&path));
+        assert_eq!(reloaded.spent("csrin"), Some(2));
+        // Counts from an earlier day no longer count
+        let mut log = RequestLog::load(&path);
+        log.day -= 1;
+        let tomorrow = DailyBudget::new(limits, log);
+        assert_eq!(tomorrow.used("csrin"), 0);
+        tomorrow.spend("csrin").unwrap();
+        assert_eq!(tomorrow.used("fitgirl"), 0);
+        // No budget, no limit
+        assert_eq!(DailyBudget::default().spent("csrin"), None);
+    }
+}
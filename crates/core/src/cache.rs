@@ -1,3 +1,4 @@
+use crate::canonical;
 use crate::models::SearchResult;
 use crate::monitoring::get_metrics;
 use serde::{Deserialize, Serialize};
@@ -148,6 +149,23 @@ impl SearchCache {
         }
     }
 
+    /// Look up an entry for a query (case-insensitive), including expired ones.
+    /// Does not record cache hit/miss metrics.
+    pub fn peek(&self, query: &str) -> Option<&CacheEntry> {
+        let query_lower = query.to_lowercase();
+        self.entries
+            .iter()
+            .find(|e| e.query.to_lowercase() == query_lower)
+    }
+
+    /// [`Self::peek`] for changing an entry in place; its timestamp and TTL stay
+    pub fn peek_mut(&mut self, query: &str) -> Option<&mut CacheEntry> {
+        let query_lower = query.to_lowercase();
+        self.entries
+            .iter_mut()
+            .find(|e| e.query.to_lowercase() == query_lower)
+    }
+
     /// Add a search to the cache
     /// If the query already exists, it's updated and moved to the end (most recent)
     pub fn add(&mut self, query: String, results: Vec<SearchResult>) {
@@ -224,6 +242,18 @@ impl SearchCache {
         self.entries.retain(|e| !e.is_expired());
     }
 
+    /// Strip tracking parameters from the result URLs of every entry and drop
+    /// the copies that leaves (see [`crate::canonical`]), for entries saved
+    /// before URLs were normalized
+    pub fn normalize_urls(&mut self) {
+        for entry in &mut self.entries {
+            for r in &mut entry.results {
+                r.url = canonical::normalize_url(&r.url);
+            }
+            canonical::dedup_results(&mut entry.results);
+        }
+    }
+
     /// Get the number of expired entries (without removing them)
     pub fn expired_count(&self) -> usize {
         self.entries.iter().filter(|e| e.is_expired()).count()
@@ -235,6 +265,7 @@ impl SearchCache {
         let mut cache: SearchCache = serde_json::from_str(&content)?;
         // Clean up expired entries on load
         cache.cleanup_expired();
+        cache.normalize_urls();
         Ok(cache)
     }
 
@@ -255,6 +286,7 @@ impl SearchCache {
         let mut cache: SearchCache = serde_json::from_str(&content)?;
         // Clean up expired entries on load
         cache.cleanup_expired();
+        cache.normalize_urls();
         Ok(cache)
     }
 
@@ -275,9 +307,10 @@ mod tests {
 
     fn make_result(site: &str, title: &str) -> SearchResult {
         SearchResult {
-            site: site.to_string(),
+            site: site.into(),
             title: title.to_string(),
             url: format!("https://example.com/{}", title.replace(' ', "-")),
+            ..Default::default()
         }
     }
 
@@ -453,6 +486,26 @@ mod tests {
         assert!(cache.get("test").is_none());
     }
 
+    #[test]
+    fn cache_peek_returns_expired_entries() {
+        let mut cache = SearchCache::with_default_size();
+        cache.add_with_ttl(
+            "Elden Ring".to_string(),
+            vec![make_result("fitgirl", "Elden Ring")],
+            Duration::from_secs(1),
+        );
+        if let Some(entry) = cache.entries_mut().last_mut() {
+            entry.timestamp = 0;
+        }
+
+        assert!(cache.get("elden ring").is_none());
+        let peeked = cache
+            .peek("elden ring")
+            .expect("expired entry still visible");
+        assert_eq!(peeked.results.len(), 1);
+        assert!(cache.peek("minecraft").is_none());
+    }
+
     #[test]
     fn cache_cleanup_expired() {
         let mut cache = SearchCache::new(5);
@@ -533,4 +586,40 @@ mod tests {
         assert!(loaded.get("valid").is_some());
         assert!(loaded.get("expired").is_none());
     }
+
+    #[test]
+    fn entries_saved_with_tracking_urls_load_without_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("search_cache.json");
+        let result = |url: &str| SearchResult {
+            site: "csrin".into(),
+            title: "Elden Ring".into(),
+            url: url.into(),
+            ..Default::default()
+        };
+        let mut cache = SearchCache::new(3);
+        cache.add(
+            "elden ring".to_string(),
+            vec![
+                result("https://cs.rin.ru/forum/viewtopic.php?t=1&hilit=elden&sid=a"),
+                result("https://cs.rin.ru/forum/viewtopic.php?t=1&sid=b"),
+                result("https://cs.rin.ru/forum/viewtopic.php?t=2"),
+            ],
+        );
+        cache.save_to_file_sync(&path).unwrap();
+
+        let loaded = SearchCache::load_from_file_sync(&path).unwrap();
+        let urls: Vec<&str> = loaded.entries()[0]
+            .results
+            .iter()
+            .map(|r| r.url.as_str())
+            .collect();
+        assert_eq!(
+            urls,
+            [
+                "https://cs.rin.ru/forum/viewtopic.php?t=1",
+                "https://cs.rin.ru/forum/viewtopic.php?t=2"
+            ]
+        );
+    }
 }
@@ -0,0 +1,175 @@
+//! One form for each result URL.
+//!
+//! The same page reaches the results in several spellings: csrin's search adds
+//! `hilit=` and a phpBB session `sid=` to every topic link, shared links carry
+//! `utm_*` parameters, and a fallback path may find `/game` where the search
+//! page linked `/game/`. [`normalize_url`] drops the parameters that only
+//! track the visitor and doubled slashes; [`url_key`] also ignores a trailing
+//! slash, so [`dedup_results`] keeps one copy of each page per site. `hilit`
+//! and `sid` are only dropped from phpBB pages: elsewhere `sid` is often what
+//! tells two pages apart.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use reqwest::Url;
+
+use crate::models::SearchResult;
+
+/// Query parameters that only track the visitor, on any site
+const TRACKING_PARAMS: [&str; 3] = ["fbclid", "gclid", "msclkid"];
+
+/// Prefix of campaign parameters
+const TRACKING_PREFIX: &str = "utm_";
+
+/// phpBB's search highlight and session parameters
+const PHPBB_PARAMS: [&str; 2] = ["hilit", "sid"];
+
+/// phpBB scripts whose links carry [`PHPBB_PARAMS`]
+const PHPBB_PAGES: [&str; 3] = ["viewtopic.php", "viewforum.php", "search.php"];
+
+/// Whether `url` is a phpBB page, like csrin's topics
+fn is_phpbb(url: &Url) -> bool {
+    let page = url.path().rsplit('/').next().unwrap_or_default();
+    PHPBB_PAGES.iter().any(|p| p.eq_ignore_ascii_case(page))
+}
+
+fn is_tracking(name: &str, phpbb: bool) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.starts_with(TRACKING_PREFIX)
+        || TRACKING_PARAMS.contains(&name.as_str())
+        || (phpbb && PHPBB_PARAMS.contains(&name.as_str()))
+}
+
+fn param_name(pair: &str) -> &str {
+    pair.split('=').next().unwrap_or_default()
+}
+
+/// `url` without tracking parameters and doubled slashes in its path; other
+/// parameters keep their order and encoding. A URL with neither, or that
+/// isn't absolute, is returned as it is.
+pub fn normalize_url(url: &str) -> String {
+    let Ok(mut parsed) = Url::parse(url) else {
+        return url.to_string();
+    };
+    let phpbb = is_phpbb(&parsed);
+    let tracked = parsed.query().is_some_and(|q| {
+        q.split('&')
+            .any(|pair| is_tracking(param_name(pair), phpbb))
+    });
+    if parsed.cannot_be_a_base() || !(tracked || parsed.path().contains("//")) {
+        return url.to_string();
+    }
+    if parsed.path().contains("//") {
+        let path = parsed
+            .path()
+            .split('/')
+            .enumerate()
+            .filter(|(i, segment)| *i == 0 || !segment.is_empty())
+            .map(|(_, segment)| segment)
+            .collect::<Vec<_>>()
+            .join("/");
+        let trailing = parsed.path().ends_with('/') && !path.ends_with('/');
+        parsed.set_path(&if trailing { format!("{path}/") } else { path });
+    }
+    if let Some(query) = parsed.query() {
+        let kept: Vec<&str> = query
+            .split('&')
+            .filter(|pair| !param_name(pair).is_empty() && !is_tracking(param_name(pair), phpbb))
+            .collect();
+        let kept = kept.join("&");
+        parsed.set_query((!kept.is_empty()).then_some(kept.as_str()));
+    }
+    parsed.into()
+}
+
+/// What two URLs of the same page have in common: the normalized URL without
+/// a trailing slash on its path
+pub fn url_key(url: &str) -> String {
+    let normalized = normalize_url(url);
+    match Url::parse(&normalized) {
+        Ok(mut parsed) if parsed.path().len() > 1 && parsed.path().ends_with('/') => {
+            let path = parsed.path().trim_end_matches('/').to_string();
+            parsed.set_path(&path);
+            parsed.into()
+        }
+        _ => normalized,
+    }
+}
+
+/// Drop every result whose site and [`url_key`] an earlier one has
+pub fn dedup_results(results: &mut Vec<SearchResult>) {
+    let mut seen: HashSet<(Arc<str>, String)> = HashSet::with_capacity(results.len());
+    results.retain(|r| seen.insert((r.site.clone(), url_key(&r.url))));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracking_parameters_and_doubled_slashes_are_dropped() {
+        assert_eq!(
+            normalize_url("https://cs.rin.ru/forum/viewtopic.php?f=10&t=123&hilit=elden&sid=0a1b"),
+            "https://cs.rin.ru/forum/viewtopic.php?f=10&t=123"
+        );
+        assert_eq!(
+            normalize_url("https://a.example//games//elden-ring/?utm_source=x&UTM_Medium=y"),
+            "https://a.example/games/elden-ring/"
+        );
+        // Other parameters keep their order and encoding, fragments stay
+        assert_eq!(
+            normalize_url("https://a.example/?s=elden%20ring&sidebar=1&fbclid=z#top"),
+            "https://a.example/?s=elden%20ring&sidebar=1#top"
+        );
+        assert_eq!(
+            normalize_url("magnet:?xt=urn:btih:abc&sid=1"),
+            "magnet:?xt=urn:btih:abc&sid=1"
+        );
+        // Off phpBB pages `sid` and `hilit` may be what names the page
+        assert_eq!(
+            normalize_url("https://a.example/game.php?sid=42&hilit=x&gclid=1"),
+            "https://a.example/game.php?sid=42&hilit=x"
+        );
+        assert_eq!(
+            normalize_url("https://forum.example/phpBB3/ViewForum.php?f=2&sid=0a1b"),
+            "https://forum.example/phpBB3/ViewForum.php?f=2"
+        );
+        assert_eq!(normalize_url("/relative/"), "/relative/");
+    }
+
+    #[test]
+    fn one_copy_of_each_page_per_site_is_kept() {
+        assert_eq!(
+            url_key("https://a.example/elden-ring/"),
+            url_key("https://a.example/elden-ring?utm_source=feed")
+        );
+        assert_eq!(url_key("https://a.example/"), "https://a.example/");
+
+        let result = |site: &str, url: &str| SearchResult {
+            site: site.into(),
+            title: "Elden Ring".into(),
+            url: url.into(),
+            ..Default::default()
+        };
+        let mut results = vec![
+            result("fitgirl", "https://a.example/elden-ring/"),
+            result("fitgirl", "https://a.example/elden-ring"),
+            result("dodi", "https://a.example/elden-ring"),
+            result("fitgirl", "https://a.example/elden-ring/?fbclid=1"),
+            result("fitgirl", "https://a.example/elden-ring/?sid=1"),
+            result("fitgirl", "https://a.example/elden-ring/?sid=2"),
+        ];
+        dedup_results(&mut results);
+        let kept: Vec<(&str, &str)> = results.iter().map(|r| (&*r.site, r.url.as_str())).collect();
+        assert_eq!(
+            kept,
+            [
+                ("fitgirl", "https://a.example/elden-ring/"),
+                ("dodi", "https://a.example/elden-ring"),
+                ("fitgirl", "https://a.example/elden-ring/?sid=1"),
+                ("fitgirl", "https://a.example/elden-ring/?sid=2")
+            ]
+        );
+    }
+}
@@ -0,0 +1,283 @@
+//! Whether each site's search honors the query.
+//!
+//! Some sites answer every search with their front page: their "search" only
+//! finds what happens to be on it, and every miss looks like a broken parser.
+//! When a search page has result links but none for the query, the site is
+//! probed with each of [`PROBE_QUERIES`], which nothing matches. A site that
+//! honors queries answers them with other links (or none); one that ignores
+//! them sends the same page again, and only when every probe agrees is it
+//! taken to ignore queries. Pages from a stand-in solver are never probed (see
+//! [`trusted_solver`]). The verdict is saved per site in
+//! `query_capability.json` in the state directory and trusted for
+//! [`RECHECK_AFTER`]. Until then a site that ignores queries is searched as a
+//! listing site: its listing page (or front page) is fetched and filtered
+//! locally, like the sites configured that way.
+
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::models::{SearchKind, SiteConfig};
+use crate::paths;
+use crate::query::build_search_url;
+
+/// Queries sent to probe a site, made up so no title contains them
+pub const PROBE_QUERIES: [&str; 3] = ["qzxjvkw", "vbxqjzp", "jwfkzqx"];
+
+/// Port FlareSolverr listens on by default
+const SOLVER_PORT: u16 = 8191;
+
+/// How long a verdict is trusted before the site is probed again
+pub const RECHECK_AFTER: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Share of links two pages must have in common to count as the same page
+const SAME_PAGE: f64 = 0.8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuerySupport {
+    /// The search page depends on the query
+    Honored,
+    /// Every query gets the same page
+    Ignored,
+}
+
+/// What the last probe of a site found
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Verdict {
+    pub support: QuerySupport,
+    /// Unix timestamp (seconds) of the probe
+    pub checked_at: u64,
+}
+
+/// Verdicts by lowercased site name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryCapabilities {
+    sites: BTreeMap<String, Verdict>,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl QueryCapabilities {
+    /// Load the verdicts, starting empty if the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid query capabilities {:?}: {}", path, e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        paths::write_atomic(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The verdict on `site`, unless it is older than [`RECHECK_AFTER`]
+    pub fn support(&self, site: &str) -> Option<QuerySupport> {
+        let verdict = self.sites.get(&site.to_lowercase())?;
+        (now_secs().saturating_sub(verdict.checked_at) < RECHECK_AFTER.as_secs())
+            .then_some(verdict.support)
+    }
+
+    /// Whether a page of `site` with result links but none for the query
+    /// calls for a probe: the site searches by query and has no recent verdict
+    pub fn needs_probe(&self, site: &SiteConfig) -> bool {
+        matches!(
+            site.search_kind,
+            SearchKind::QueryParam | SearchKind::PathEncoded
+        ) && self.support(&site.name).is_none()
+    }
+
+    pub fn record(&mut self, site: &str, support: QuerySupport) {
+        self.sites.insert(
+            site.to_lowercase(),
+            Verdict {
+                support,
+                checked_at: now_secs(),
+            },
+        );
+    }
+
+    /// Turn `site` into a listing site if its search ignores the query;
+    /// returns whether it did
+    pub fn downgrade(&self, site: &mut SiteConfig) -> bool {
+        if self.support(&site.name) != Some(QuerySupport::Ignored) {
+            return false;
+        }
+        site.search_kind = SearchKind::ListingPage;
+        true
+    }
+
+    /// Sites whose search ignores the query, by lowercased name
+    pub fn ignored(&self) -> Vec<&str> {
+        self.sites
+            .keys()
+            .filter(|site| self.support(site) == Some(QuerySupport::Ignored))
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// URLs of the probe searches on `site`, in the order they are sent
+pub fn probe_urls(site: &SiteConfig) -> Vec<String> {
+    PROBE_QUERIES
+        .iter()
+        .map(|query| build_search_url(site, query))
+        .collect()
+}
+
+/// Whether pages fetched through the solver at `cf_url` show what the site
+/// sends. A server on a loopback address but not FlareSolverr's port is a
+/// stand-in, like the mock solvers of tests, which answer every query with the
+/// same page; probing through it would condemn the site.
+pub fn trusted_solver(cf_url: &str) -> bool {
+    let Ok(url) = reqwest::Url::parse(cf_url) else {
+        return true;
+    };
+    let host = url.host_str().unwrap_or_default();
+    let loopback = host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_matches(['[', ']'])
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback());
+    !loopback || url.port_or_known_default() == Some(SOLVER_PORT)
+}
+
+/// Links of the elements the first of `site`'s result selectors that finds
+/// any matches on `html`, before any filtering by the query
+pub fn result_links(site: &SiteConfig, html: &str) -> HashSet<String> {
+    let document = Html::parse_document(html);
+    site.result_selector
+        .iter()
+        .filter_map(|selector| Selector::parse(selector).ok())
+        .map(|selector| {
+            document
+                .select(&selector)
+                .filter_map(|el| {
+                    // Cards often wrap the anchor, as in the parser
+                    el.value().attr("href").or_else(|| {
+                        el.parent()
+                            .and_then(|p| p.value().as_element())
+                            .and_then(|p| p.attr("href"))
+                    })
+                })
+                .filter(|href| !href.is_empty())
+                .map(str::to_string)
+                .collect::<HashSet<_>>()
+        })
+        .find(|links| !links.is_empty())
+        .unwrap_or_default()
+}
+
+/// Verdict from the result links of the search page for a query and of the
+/// probe's page
+pub fn judge(searched: &HashSet<String>, probed: &HashSet<String>) -> QuerySupport {
+    if searched.is_empty() || probed.is_empty() {
+        return QuerySupport::Honored;
+    }
+    let larger = searched.len().max(probed.len());
+    let shared = searched.intersection(probed).count();
+    if shared as f64 >= larger as f64 * SAME_PAGE {
+        QuerySupport::Ignored
+    } else {
+        QuerySupport::Honored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn site(kind: SearchKind) -> SiteConfig {
+        SiteConfig {
+            name: "Repacks".to_string(),
+            base_url: "https://a.example/".to_string(),
+            search_kind: kind,
+            query_param: Some("s".to_string()),
+            result_selector: "h2.entry-title a".into(),
+            ..Default::default()
+        }
+    }
+
+    fn page(slugs: &[&str]) -> String {
+        slugs
+            .iter()
+            .map(|s| format!("<h2 class=\"entry-title\"><a href=\"/{s}/\">{s}</a></h2>"))
+            .chain(["<a href=\"/about/\">About</a>".to_string()])
+            .collect()
+    }
+
+    #[test]
+    fn the_same_page_for_any_query_means_it_is_ignored() {
+        let repacks = site(SearchKind::QueryParam);
+        assert_eq!(
+            probe_urls(&repacks),
+            [
+                "https://a.example/?s=qzxjvkw",
+                "https://a.example/?s=vbxqjzp",
+                "https://a.example/?s=jwfkzqx"
+            ]
+        );
+        let front = result_links(&repacks, &page(&["hades", "celeste", "tunic"]));
+        assert_eq!(front.len(), 3);
+        assert!(!front.contains("/about/"));
+
+        assert_eq!(judge(&front, &front), QuerySupport::Ignored);
+        let shuffled = result_links(&repacks, &page(&["tunic", "hades", "celeste"]));
+        assert_eq!(judge(&front, &shuffled), QuerySupport::Ignored);
+        // A fuzzy search answers the probe with other links, or none
+        let fuzzy = result_links(&repacks, &page(&["hades", "hollow-knight", "inside"]));
+        assert_eq!(judge(&front, &fuzzy), QuerySupport::Honored);
+        assert_eq!(judge(&front, &HashSet::new()), QuerySupport::Honored);
+    }
+
+    #[test]
+    fn loopback_servers_off_the_solver_port_are_stand_ins() {
+        assert!(trusted_solver("http://localhost:8191/v1"));
+        assert!(trusted_solver("http://127.0.0.1:8191/v1"));
+        assert!(trusted_solver("https://solver.example/v1"));
+        assert!(trusted_solver("http://192.168.1.5:9000/v1"));
+        assert!(!trusted_solver("http://127.0.0.1:41234"));
+        assert!(!trusted_solver("http://[::1]:41234/v1"));
+        assert!(!trusted_solver("http://localhost:8080/v1"));
+    }
+
+    #[test]
+    fn ignoring_sites_become_listing_sites_until_rechecked() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("query_capability.json");
+        let mut capabilities = QueryCapabilities::default();
+        let mut repacks = site(SearchKind::QueryParam);
+        assert!(capabilities.needs_probe(&repacks));
+        assert!(!capabilities.needs_probe(&site(SearchKind::FrontPage)));
+
+        capabilities.record("repacks", QuerySupport::Ignored);
+        capabilities.record("fitgirl", QuerySupport::Honored);
+        capabilities.save(&path).unwrap();
+        let loaded = QueryCapabilities::load(&path);
+        assert_eq!(loaded, capabilities);
+        assert!(!loaded.needs_probe(&repacks));
+        assert_eq!(loaded.ignored(), ["repacks"]);
+        assert!(loaded.downgrade(&mut repacks));
+        assert_eq!(repacks.search_kind, SearchKind::ListingPage);
+
+        // A verdict past its recheck time no longer counts
+        let mut stale = loaded.clone();
+        stale.sites.get_mut("repacks").unwrap().checked_at -= RECHECK_AFTER.as_secs();
+        let mut repacks = site(SearchKind::QueryParam);
+        assert!(!stale.downgrade(&mut repacks));
+        assert!(stale.needs_probe(&repacks));
+        assert!(stale.ignored().is_empty());
+    }
+}
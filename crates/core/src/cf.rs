@@ -1,6 +1,7 @@
+use crate::{render_cache, session};
 use anyhow::{Context, Result};
 use reqwest::{Client, header::HeaderMap};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
 #[derive(Debug, Deserialize)]
 struct FlareResponseSolution {
@@ -14,7 +15,22 @@ struct FlareResponse {
     status: String,
 }
 
+/// `url` as FlareSolverr at `solver_url` sees it after any challenge. Goes
+/// through the active recording or replay, if any, and reuses a page the
+/// solver rendered a few minutes ago (see [`render_cache`]).
 pub async fn fetch_via_solver(client: &Client, url: &str, solver_url: &str) -> Result<String> {
+    let session_key = session_key(url);
+    let live = session::fetch_through(&session_key, solve(client, url, solver_url));
+    render_cache::through(&render_cache::key("solver", url, None), live).await
+}
+
+/// Recording key for a solved page, apart from a direct fetch of the same URL
+/// (which usually got the challenge)
+fn session_key(url: &str) -> String {
+    format!("solver:{url}")
+}
+
+async fn solve(client: &Client, url: &str, solver_url: &str) -> Result<String> {
     // POST {cmd: request.get, url}
     let payload = serde_json::json!({
         "cmd": "request.get",
@@ -39,11 +55,29 @@ pub async fn fetch_via_solver(client: &Client, url: &str, solver_url: &str) -> R
     Ok(fr.solution.response)
 }
 
+/// [`fetch_via_solver`] with headers for the solver to send, e.g. a Cookie
 pub async fn fetch_via_solver_with_headers(
     client: &Client,
     url: &str,
     solver_url: &str,
     headers: Option<HeaderMap>,
+) -> Result<String> {
+    let cookie = headers
+        .as_ref()
+        .and_then(|h| h.get(reqwest::header::COOKIE))
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let live = solve_with_headers(client, url, solver_url, headers);
+    let session_key = session_key(url);
+    let live = session::fetch_through(&session_key, live);
+    render_cache::through(&render_cache::key("solver", url, cookie.as_deref()), live).await
+}
+
+async fn solve_with_headers(
+    client: &Client,
+    url: &str,
+    solver_url: &str,
+    headers: Option<HeaderMap>,
 ) -> Result<String> {
     let mut payload = serde_json::json!({
         "cmd": "request.get",
@@ -77,6 +111,68 @@ pub async fn fetch_via_solver_with_headers(
     Ok(fr.solution.response)
 }
 
+/// Open solver sessions, as reported by `sessions.list`
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SolverSessions {
+    #[serde(default)]
+    pub sessions: Vec<String>,
+    /// FlareSolverr version, when the solver reports one
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+async fn solver_command<T: DeserializeOwned>(
+    client: &Client,
+    solver_url: &str,
+    payload: serde_json::Value,
+) -> Result<T> {
+    let resp = client
+        .post(solver_url)
+        .header("content-type", "application/json")
+        .json(&payload)
+        .send()
+        .await
+        .context("send flaresolverr request")?;
+
+    let status = resp.status();
+    if !status.is_success() {
+        anyhow::bail!("flaresolverr http status {}", status);
+    }
+    resp.json().await.context("decode flaresolverr json")
+}
+
+pub async fn list_sessions(client: &Client, solver_url: &str) -> Result<SolverSessions> {
+    solver_command(
+        client,
+        solver_url,
+        serde_json::json!({ "cmd": "sessions.list" }),
+    )
+    .await
+}
+
+pub async fn destroy_session(client: &Client, solver_url: &str, session: &str) -> Result<()> {
+    let _: serde_json::Value = solver_command(
+        client,
+        solver_url,
+        serde_json::json!({ "cmd": "sessions.destroy", "session": session }),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Destroy every open session, e.g. after one got stuck on a challenge.
+///
+/// Returns how many sessions were destroyed.
+pub async fn reset_sessions(client: &Client, solver_url: &str) -> Result<usize> {
+    let open = list_sessions(client, solver_url).await?;
+    for session in &open.sessions {
+        destroy_session(client, solver_url, session)
+            .await
+            .with_context(|| format!("destroy session {session}"))?;
+    }
+    Ok(open.sessions.len())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -139,4 +235,31 @@ mod tests {
                 .unwrap();
         assert!(body.contains("<html>ok</html>"));
     }
+
+    #[tokio::test]
+    async fn reset_destroys_every_listed_session() {
+        let mut server = Server::new_async().await;
+        let _list = server
+            .mock("POST", "/")
+            .match_body(Matcher::Regex("sessions.list".into()))
+            .with_status(200)
+            .with_body(r#"{"status":"ok","sessions":["a","b"],"version":"3.3.21"}"#)
+            .create_async()
+            .await;
+        let destroy = server
+            .mock("POST", "/")
+            .match_body(Matcher::Regex("sessions.destroy".into()))
+            .with_status(200)
+            .with_body(r#"{"status":"ok","message":"The session has been removed."}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let client = Client::new();
+        let open = list_sessions(&client, &server.url()).await.unwrap();
+        assert_eq!(open.sessions, ["a", "b"]);
+        assert_eq!(open.version.as_deref(), Some("3.3.21"));
+
+        assert_eq!(reset_sessions(&client, &server.url()).await.unwrap(), 2);
+        destroy.assert_async().await;
+    }
 }
@@ -0,0 +1,92 @@
+//! Cloudflare's email obfuscation, undone before a page is parsed.
+//!
+//! Sites behind Cloudflare can have anything on their pages that looks like an
+//! email address replaced with `[email protected]` in an element carrying the
+//! address XOR-encoded in `data-cfemail`, and `mailto:` links pointed at
+//! `/cdn-cgi/l/email-protection#<hex>`. A title such as `Hades v1.0@Repack`
+//! then reads `Hades v[email protected]`. [`unprotect`] puts the addresses
+//! back: each such element becomes the address as plain text, and each
+//! protected link a `mailto:` link, which the parser skips.
+
+use regex::Regex;
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+static ELEMENT: OnceLock<Regex> = OnceLock::new();
+static LINK: OnceLock<Regex> = OnceLock::new();
+
+/// The address encoded in `hex`: its first byte is the key every other byte
+/// is XORed with
+pub fn decode(hex: &str) -> Option<String> {
+    if hex.len() < 2 || hex.len() % 2 != 0 {
+        return None;
+    }
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    let (key, encoded) = bytes.split_first()?;
+    String::from_utf8(encoded.iter().map(|b| b ^ key).collect()).ok()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// `html` with Cloudflare's protected addresses decoded; borrowed as it is
+/// when the page has none
+pub fn unprotect(html: &str) -> Cow<'_, str> {
+    if !html.contains("data-cfemail") && !html.contains("/cdn-cgi/l/email-protection#") {
+        return Cow::Borrowed(html);
+    }
+    let element = ELEMENT.get_or_init(|| {
+        Regex::new(
+            r#"(?is)<(?:a|span)\b[^>]*?\bdata-cfemail\s*=\s*["']?([0-9a-f]+)["']?[^>]*>.*?</(?:a|span)\s*>"#,
+        )
+        .expect("valid cfemail regex")
+    });
+    let link = LINK.get_or_init(|| {
+        Regex::new(r#"(?i)href\s*=\s*["'][^"'#]*/cdn-cgi/l/email-protection#([0-9a-f]+)["']"#)
+            .expect("valid email-protection regex")
+    });
+    let decoded = element.replace_all(html, |caps: &regex::Captures| match decode(&caps[1]) {
+        Some(address) => escape(&address),
+        None => caps[0].to_string(),
+    });
+    let decoded = link.replace_all(&decoded, |caps: &regex::Captures| match decode(&caps[1]) {
+        Some(address) => format!("href=\"mailto:{}\"", escape(&address)),
+        None => caps[0].to_string(),
+    });
+    Cow::Owned(decoded.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protected_addresses_are_decoded_in_place() {
+        // "user@example.com" with key 0x42
+        let hex = "423731273002273a232f322e276c212d2f";
+        assert_eq!(decode(hex).as_deref(), Some("user@example.com"));
+        assert_eq!(decode("42zz"), None);
+        assert_eq!(decode("423"), None);
+
+        let html = format!(
+            r#"<a href="/hades/">Hades v1.0 <span class="__cf_email__" data-cfemail="{hex}">[email&#160;protected]</span></a>
+            <a href="/cdn-cgi/l/email-protection" class="__cf_email__" data-cfemail="{hex}">[email&#160;protected]</a>
+            <a href="https://a.example/cdn-cgi/l/email-protection#{hex}">Contact</a>"#
+        );
+        assert_eq!(
+            unprotect(&html),
+            r#"<a href="/hades/">Hades v1.0 user@example.com</a>
+            user@example.com
+            <a href="mailto:user@example.com">Contact</a>"#
+        );
+        let plain = "<a href=\"/hades/\">Hades</a>";
+        assert!(matches!(unprotect(plain), Cow::Borrowed(_)));
+    }
+}
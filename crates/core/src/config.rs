@@ -1,5 +1,7 @@
-use crate::models::{SiteConfig, SitesConfig};
-use std::path::PathBuf;
+use crate::extract::Extract;
+use crate::models::{SearchKind, SiteConfig, SiteRules, SitesConfig};
+use anyhow::Context;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -28,29 +30,7 @@ impl ConfigManager {
             tracing::warn!("Configuration file not found at {:?}, using defaults", path);
             return Ok(hardcoded_site_configs());
         }
-
-        let sites_config = SitesConfig::load_from_file(path)?;
-        let mut sites = sites_config.get_site_configs();
-
-        // Apply global defaults where needed
-        if let Some(global) = sites_config.global {
-            for site in &mut sites {
-                if site.timeout_seconds == 0 {
-                    site.timeout_seconds = global.default_timeout_seconds;
-                }
-                if site.retry_attempts == 0 {
-                    site.retry_attempts = global.default_retry_attempts;
-                }
-                if site.rate_limit_delay_ms == 0 {
-                    site.rate_limit_delay_ms = global.default_rate_limit_delay_ms;
-                }
-            }
-        }
-
-        // Validate configurations
-        validate_sites(&sites)?;
-
-        Ok(sites)
+        load_sites_file(path)
     }
 
     /// Get all site configurations
@@ -85,9 +65,28 @@ fn validate_sites(sites: &[SiteConfig]) -> anyhow::Result<()> {
         if site.base_url.is_empty() {
             anyhow::bail!("Base URL for site '{}' cannot be empty", site.name);
         }
-        if site.result_selector.is_empty() {
+        if site.search_kind == SearchKind::GraphQL {
+            let Some(graphql) = &site.graphql else {
+                anyhow::bail!("GraphQL site '{}' needs a [graphql] table", site.name);
+            };
+            if graphql.query.is_empty() && graphql.persisted_hash.is_none() {
+                anyhow::bail!(
+                    "GraphQL site '{}' needs a query or a persisted_hash",
+                    site.name
+                );
+            }
+            if graphql.results.is_empty() {
+                anyhow::bail!("GraphQL site '{}' needs a results pointer", site.name);
+            }
+        } else if site.result_selector.is_empty() {
             anyhow::bail!("Result selector for site '{}' cannot be empty", site.name);
         }
+        if let Err(e) = Extract::title(&site.title_attr) {
+            anyhow::bail!("title_attr for site '{}': {e}", site.name);
+        }
+        if let Err(e) = Extract::url(&site.url_attr) {
+            anyhow::bail!("url_attr for site '{}': {e}", site.name);
+        }
         if site.timeout_seconds == 0 {
             anyhow::bail!("Timeout for site '{}' must be greater than 0", site.name);
         }
@@ -104,8 +103,9 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             base_url: "https://steamgg.net/".to_string(),
             search_kind: crate::models::SearchKind::QueryParam,
             query_param: Some("s".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "h2.entry-title a, h3.entry-title a, .post-title a".to_string(),
+            result_selector: "h2.entry-title a, h3.entry-title a, .post-title a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -113,6 +113,10 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: SiteRules::default(),
         },
         // 2. gog-games.to
         SiteConfig {
@@ -120,8 +124,9 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             base_url: "https://gog-games.to/".to_string(),
             search_kind: crate::models::SearchKind::QueryParam,
             query_param: Some("search".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "a.card, .games-list a, article a".to_string(),
+            result_selector: "a.card, .games-list a, article a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -129,6 +134,14 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            // Search and listing cards also link to non-game pages
+            rules: SiteRules {
+                url_must_contain: ["/game/", "/games/"].map(String::from).to_vec(),
+                ..SiteRules::default()
+            },
         },
         // 3. atopgames.com
         SiteConfig {
@@ -136,8 +149,9 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             base_url: "https://atopgames.com/".to_string(),
             search_kind: crate::models::SearchKind::QueryParam,
             query_param: Some("s".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "h2.entry-title a, h3.entry-title a, .post-box-title a".to_string(),
+            result_selector: "h2.entry-title a, h3.entry-title a, .post-box-title a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -145,6 +159,10 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: SiteRules::default(),
         },
         // 4. elamigos.site
         SiteConfig {
@@ -152,8 +170,9 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             base_url: "https://elamigos.site/".to_string(),
             search_kind: crate::models::SearchKind::FrontPage,
             query_param: None,
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "h2.entry-title a, .card-title a, .entry-title a".to_string(),
+            result_selector: "h2.entry-title a, .card-title a, .entry-title a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -161,6 +180,13 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: Some(crate::models::CrawlConfig {
+                max_depth: 2,
+                max_age_days: Some(365),
+            }),
+            form: None,
+            graphql: None,
+            rules: SiteRules::default(),
         },
         // 5. fitgirl-repacks.site
         SiteConfig {
@@ -168,8 +194,9 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             base_url: "https://fitgirl-repacks.site/".to_string(),
             search_kind: crate::models::SearchKind::QueryParam,
             query_param: Some("s".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "h2.entry-title a, h1.post-title a, .post-title a".to_string(),
+            result_selector: "h2.entry-title a, h1.post-title a, .post-title a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -177,6 +204,27 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            // Skip archive, comment and inquiry links, and "Continue reading"
+            // teasers that duplicate the post link
+            rules: SiteRules {
+                drop_if_url_contains: [
+                    "/page/",
+                    "#respond",
+                    "?s=",
+                    "/tag/",
+                    "/category/",
+                    "/categories/",
+                    "/inquiry",
+                    "/inquery",
+                ]
+                .map(String::from)
+                .to_vec(),
+                drop_if_title_starts_with: vec!["Continue reading".to_string()],
+                ..SiteRules::default()
+            },
         },
         // 6. dodi-repacks.download
         SiteConfig {
@@ -184,8 +232,9 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             base_url: "https://dodi-repacks.download/".to_string(),
             search_kind: crate::models::SearchKind::QueryParam,
             query_param: Some("s".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "h2.entry-title a, .entry-title a".to_string(),
+            result_selector: "h2.entry-title a, .entry-title a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -193,6 +242,10 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: SiteRules::default(),
         },
         // 7. skidrowrepacks.com
         SiteConfig {
@@ -200,10 +253,11 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             base_url: "https://skidrowrepacks.com/".to_string(),
             search_kind: crate::models::SearchKind::QueryParam,
             query_param: Some("s".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
             result_selector:
                 "h2.entry-title a, h1.entry-title a, .entry-title a, .entry-title > a, article h2 a"
-                    .to_string(),
+                    .into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -211,6 +265,10 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: SiteRules::default(),
         },
         // 8. steamrip.com
         SiteConfig {
@@ -218,9 +276,10 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             base_url: "https://steamrip.com/".to_string(),
             search_kind: crate::models::SearchKind::QueryParam,
             query_param: Some("s".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
             result_selector: "h2.entry-title a, h3.entry-title a, .post-title a, article h2 a"
-                .to_string(),
+                .into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -228,6 +287,14 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            // Skip pagination and search navigation
+            rules: SiteRules {
+                drop_if_url_contains: ["/page/", "?s="].map(String::from).to_vec(),
+                ..SiteRules::default()
+            },
         },
         // 9. reloadedsteam.com
         SiteConfig {
@@ -235,8 +302,9 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             base_url: "https://reloadedsteam.com/".to_string(),
             search_kind: crate::models::SearchKind::QueryParam,
             query_param: Some("s".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "h2.entry-title a, .post-title a, article h2 a".to_string(),
+            result_selector: "h2.entry-title a, .post-title a, article h2 a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -244,6 +312,10 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: SiteRules::default(),
         },
         // 10. ankergames.net
         SiteConfig {
@@ -251,8 +323,9 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             base_url: "https://ankergames.net/search/".to_string(),
             search_kind: crate::models::SearchKind::PathEncoded,
             query_param: None,
+            search_params: Vec::new(),
             listing_path: Some("https://ankergames.net/games-list".to_string()),
-            result_selector: "div a[href^='/game/'], a.game-card, h2 a, h3 a".to_string(),
+            result_selector: "div a[href^='/game/'], a.game-card, h2 a, h3 a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -260,6 +333,10 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: SiteRules::default(),
         },
         // 11. cs.rin.ru forum
         SiteConfig {
@@ -267,8 +344,12 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             base_url: "https://cs.rin.ru/forum/".to_string(),
             search_kind: crate::models::SearchKind::PhpBBSearch,
             query_param: Some("keywords".to_string()),
+            // Main forum only, one hit per topic, matching first posts
+            search_params: [("fid[]", "10"), ("sr", "topics"), ("sf", "firstpost")]
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .to_vec(),
             listing_path: Some("https://cs.rin.ru/forum/viewforum.php?f=10".to_string()),
-            result_selector: "a.topictitle, a[href^='viewtopic.php']".to_string(),
+            result_selector: "a.topictitle, a[href^='viewtopic.php']".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -276,6 +357,15 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            // Topic links only, titled without the forum name or reply prefix
+            rules: SiteRules {
+                url_must_contain: vec!["viewtopic.php".to_string()],
+                title_strip_prefixes: ["Main Forum •", "Re:"].map(String::from).to_vec(),
+                ..SiteRules::default()
+            },
         },
         // 12. nswpedia.com
         SiteConfig {
@@ -283,8 +373,9 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             base_url: "https://nswpedia.com/".to_string(),
             search_kind: crate::models::SearchKind::QueryParam,
             query_param: Some("s".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "h2 a, article h2 a, .post-title a".to_string(),
+            result_selector: "h2 a, article h2 a, .post-title a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -292,6 +383,10 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: SiteRules::default(),
         },
         // 13. f95zone.to
         SiteConfig {
@@ -299,8 +394,9 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             base_url: "https://f95zone.to/".to_string(),
             search_kind: crate::models::SearchKind::ListingPage,
             query_param: None,
+            search_params: Vec::new(),
             listing_path: Some("https://f95zone.to/forums/games.2/".to_string()),
-            result_selector: "a[href*='/threads/']".to_string(),
+            result_selector: "a[href*='/threads/']".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -308,20 +404,81 @@ fn hardcoded_site_configs() -> Vec<SiteConfig> {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: SiteRules::default(),
         },
     ]
 }
 
-/// Get the default configuration file path
-pub fn default_config_path() -> PathBuf {
-    if let Ok(config_dir) = std::env::var("WEBSITE_SEARCHER_CONFIG_DIR") {
-        PathBuf::from(config_dir).join("sites.toml")
+/// Sites defined in a `sites.toml` file, sorted by name, with `[global]`
+/// defaults (or the built-in ones) filled in
+pub fn load_sites_file(path: &Path) -> anyhow::Result<Vec<SiteConfig>> {
+    let sites_config = SitesConfig::load_from_file(&path.to_path_buf())?;
+    let mut sites = sites_config.get_site_configs();
+    sites.sort_by(|a, b| a.name.cmp(&b.name));
+
+    // Apply global defaults where needed
+    let global = sites_config.global.unwrap_or_default();
+    for site in &mut sites {
+        if site.timeout_seconds == 0 {
+            site.timeout_seconds = global.default_timeout_seconds;
+        }
+        if site.retry_attempts == 0 {
+            site.retry_attempts = global.default_retry_attempts;
+        }
+        if site.rate_limit_delay_ms == 0 {
+            site.rate_limit_delay_ms = global.default_rate_limit_delay_ms;
+        }
+    }
+
+    // Validate configurations
+    validate_sites(&sites)?;
+
+    Ok(sites)
+}
+
+/// The sites to search: those in the `sites.toml` named by
+/// `$WEBSITE_SEARCHER_SITES` when it is set (e.g. a test's local mock sites),
+/// else the built-in ones with those of the user's `sites.toml` in the config
+/// directory (see [`merge_sites`])
+pub fn active_site_configs() -> anyhow::Result<Vec<SiteConfig>> {
+    let (path, only) = match std::env::var_os("WEBSITE_SEARCHER_SITES") {
+        Some(path) => (PathBuf::from(path), true),
+        None => (default_config_path(), false),
+    };
+    if !only && !path.exists() {
+        return Ok(site_configs());
+    }
+    let sites = load_sites_file(&path)
+        .with_context(|| format!("failed to load sites from {}", path.display()))?;
+    Ok(if only {
+        sites
     } else {
-        dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("website-searcher")
-            .join("sites.toml")
+        merge_sites(site_configs(), sites)
+    })
+}
+
+/// The `builtin` sites with the `user` ones added; a user site named like a
+/// built-in one (ignoring case) takes its place
+pub fn merge_sites(builtin: Vec<SiteConfig>, user: Vec<SiteConfig>) -> Vec<SiteConfig> {
+    let mut sites = builtin;
+    for site in user {
+        match sites
+            .iter_mut()
+            .find(|s| s.name.eq_ignore_ascii_case(&site.name))
+        {
+            Some(existing) => *existing = site,
+            None => sites.push(site),
+        }
     }
+    sites
+}
+
+/// Get the default configuration file path
+pub fn default_config_path() -> PathBuf {
+    crate::paths::config_dir().join("sites.toml")
 }
 
 /// Get the local configuration file path (for development)
@@ -382,4 +539,89 @@ rate_limit_delay_ms = 2000
         assert!(!sites.is_empty());
         assert!(sites.iter().any(|s| s.name == "fitgirl"));
     }
+
+    #[test]
+    fn user_sites_are_added_to_the_built_in_ones() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("sites.toml");
+        std::fs::write(
+            &path,
+            r#"
+[sites.my-site]
+base_url = "https://my-site.example/"
+search_kind = "QueryParam"
+query_param = "q"
+result_selector = "h3.title a"
+
+[sites.FitGirl]
+name = "fitgirl"
+base_url = "https://fitgirl-mirror.example/"
+search_kind = "QueryParam"
+query_param = "s"
+result_selector = "h1.entry-title a"
+requires_cloudflare = true
+"#,
+        )
+        .unwrap();
+        let user = load_sites_file(&path).unwrap();
+        // Unset fields take their defaults
+        assert_eq!(user[1].name, "my-site");
+        assert_eq!(user[1].timeout_seconds, 30);
+        assert_eq!(user[1].rate_limit_delay_ms, 1000);
+        assert!(user[1].title_attr.is_empty() && !user[1].requires_js);
+
+        let builtin = site_configs();
+        let sites = merge_sites(builtin.clone(), user);
+        assert_eq!(sites.len(), builtin.len() + 1);
+        let fitgirl = sites.iter().find(|s| s.name == "fitgirl").unwrap();
+        assert_eq!(fitgirl.base_url, "https://fitgirl-mirror.example/");
+        assert_eq!(sites.last().unwrap().name, "my-site");
+    }
+
+    #[test]
+    fn result_selector_takes_one_selector_or_a_list() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("sites.toml");
+        let site = |name: &str, selector: &str| {
+            format!(
+                r#"
+[sites.{name}]
+name = "{name}"
+base_url = "https://{name}.example/"
+search_kind = "QueryParam"
+query_param = "s"
+result_selector = {selector}
+title_attr = "text"
+url_attr = "href"
+requires_js = false
+requires_cloudflare = false
+timeout_seconds = 30
+retry_attempts = 3
+rate_limit_delay_ms = 1000
+"#
+            )
+        };
+        let toml = site("new-theme", r#"["article.card a", "h2.entry-title a"]"#)
+            + &site("old-theme", r#""h2.entry-title a""#);
+        std::fs::write(&path, toml).unwrap();
+        let sites = load_sites_file(&path).unwrap();
+        let selectors: Vec<Vec<&str>> = sites
+            .iter()
+            .map(|s| s.result_selector.iter().collect())
+            .collect();
+        assert_eq!(
+            selectors,
+            [
+                vec!["article.card a", "h2.entry-title a"],
+                vec!["h2.entry-title a"]
+            ]
+        );
+        // Written back the way it was given
+        let json = serde_json::to_value(&sites).unwrap();
+        assert_eq!(json[1]["result_selector"], "h2.entry-title a");
+        assert_eq!(json[0]["result_selector"][1], "h2.entry-title a");
+
+        std::fs::write(&path, site("empty", "[]")).unwrap();
+        assert!(load_sites_file(&path).is_err());
+    }
 }
@@ -0,0 +1,176 @@
+//! Reading cookies pasted from a browser: either a `Cookie` header value
+//! (`name=value; other=value`) or a Netscape `cookies.txt` export.
+
+use anyhow::{Result, bail};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    /// Domain from a cookies.txt line, without the leading dot; `None` for a
+    /// pasted header
+    pub domain: Option<String>,
+}
+
+/// Cookies read from one paste or file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedCookies {
+    pub cookies: Vec<Cookie>,
+    /// cookies.txt lines skipped because they had expired
+    pub expired: usize,
+}
+
+/// Whether `input` looks like a cookies.txt file rather than a header value
+pub fn is_netscape(input: &str) -> bool {
+    input.lines().any(|line| {
+        let line = line.trim();
+        line.starts_with("# Netscape HTTP Cookie File")
+            || line.starts_with("#HttpOnly_")
+            || (!line.starts_with('#') && line.split('\t').count() == 7)
+    })
+}
+
+/// Parse a `Cookie` header value; a leading `Cookie:` is ignored
+pub fn parse_header(input: &str) -> Result<Vec<Cookie>> {
+    let input = input.trim();
+    let input = input
+        .get(..7)
+        .filter(|p| p.eq_ignore_ascii_case("cookie:"))
+        .map_or(input, |_| &input[7..]);
+    let mut cookies = Vec::new();
+    for pair in input.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+        let Some((name, value)) = pair.split_once('=') else {
+            bail!("\"{}\" is not a name=value pair", pair);
+        };
+        let name = name.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            bail!("invalid cookie name \"{}\"", name);
+        }
+        cookies.push(Cookie {
+            name: name.to_string(),
+            value: value.trim().to_string(),
+            domain: None,
+        });
+    }
+    if cookies.is_empty() {
+        bail!("no cookies found");
+    }
+    Ok(cookies)
+}
+
+/// Parse a cookies.txt export, dropping cookies that expired before `now`
+/// (Unix seconds). Session cookies (expiry 0) are kept.
+pub fn parse_netscape(input: &str, now: u64) -> Result<ParsedCookies> {
+    let mut parsed = ParsedCookies::default();
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [domain, _, _, _, expires, name, value] = fields[..] else {
+            bail!("line {}: expected 7 tab-separated fields", i + 1);
+        };
+        let expires: u64 = expires
+            .trim()
+            .parse()
+            .map_err(|_| anyhow::anyhow!("line {}: invalid expiry \"{}\"", i + 1, expires))?;
+        if expires != 0 && expires < now {
+            parsed.expired += 1;
+            continue;
+        }
+        parsed.cookies.push(Cookie {
+            name: name.to_string(),
+            value: value.to_string(),
+            domain: Some(domain.trim_start_matches('.').to_lowercase()),
+        });
+    }
+    if parsed.cookies.is_empty() && parsed.expired == 0 {
+        bail!("no cookies found");
+    }
+    Ok(parsed)
+}
+
+/// Parse either format, using the current time for cookies.txt expiry
+pub fn parse(input: &str) -> Result<ParsedCookies> {
+    if is_netscape(input) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        parse_netscape(input, now)
+    } else {
+        Ok(ParsedCookies {
+            cookies: parse_header(input)?,
+            expired: 0,
+        })
+    }
+}
+
+/// Whether a cookie set for `domain` is sent to `host` (same host or a subdomain)
+pub fn domain_matches(domain: &str, host: &str) -> bool {
+    let host = host.to_lowercase();
+    host == domain || host.ends_with(&format!(".{domain}"))
+}
+
+/// `Cookie` header value for `cookies`
+pub fn to_header(cookies: &[Cookie]) -> String {
+    cookies
+        .iter()
+        .map(|c| format!("{}={}", c.name, c.value))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COOKIES_TXT: &str = "# Netscape HTTP Cookie File\n\
+        .cs.rin.ru\tTRUE\t/\tTRUE\t2000000000\tphpbb3_sid\tabc\n\
+        #HttpOnly_cs.rin.ru\tFALSE\t/\tTRUE\t0\tcf_clearance\txyz\n\
+        .example.com\tTRUE\t/\tFALSE\t100\told\tgone\n";
+
+    #[test]
+    fn header_values_are_split_into_pairs() {
+        let cookies = parse_header("Cookie: sid=1; cf_clearance=a=b ;").unwrap();
+        assert_eq!(to_header(&cookies), "sid=1; cf_clearance=a=b");
+        assert!(cookies.iter().all(|c| c.domain.is_none()));
+
+        assert!(parse_header("").is_err());
+        assert!(parse_header("just text").is_err());
+        assert!(parse_header("bad name=1").is_err());
+    }
+
+    #[test]
+    fn cookies_txt_keeps_domains_and_drops_expired() {
+        assert!(is_netscape(COOKIES_TXT));
+        assert!(!is_netscape("sid=1; a=b"));
+
+        let parsed = parse_netscape(COOKIES_TXT, 1_000).unwrap();
+        assert_eq!(parsed.expired, 1);
+        assert_eq!(
+            to_header(&parsed.cookies),
+            "phpbb3_sid=abc; cf_clearance=xyz"
+        );
+        assert!(
+            parsed
+                .cookies
+                .iter()
+                .all(|c| c.domain.as_deref() == Some("cs.rin.ru"))
+        );
+
+        assert!(parse_netscape("a\tb\tc\n", 0).is_err());
+        assert!(parse_netscape("# only comments\n", 0).is_err());
+    }
+
+    #[test]
+    fn domains_match_hosts_and_subdomains() {
+        assert!(domain_matches("cs.rin.ru", "cs.rin.ru"));
+        assert!(domain_matches("rin.ru", "CS.RIN.RU"));
+        assert!(!domain_matches("rin.ru", "notrin.ru"));
+        assert!(!domain_matches("cs.rin.ru", "rin.ru"));
+    }
+}
@@ -0,0 +1,174 @@
+//! Following a listing site's archive pages past its front page.
+//!
+//! Sites without a search (e.g. elamigos) only show their newest posts on the
+//! front page. With a [`CrawlConfig`](crate::models::CrawlConfig) the "older posts" pages are fetched too,
+//! up to a depth and, when the pages carry post dates, a date horizon.
+
+use crate::analyzer::date_sort_key;
+use crate::models::{SearchKind, SiteConfig};
+use crate::pagination::next_page_link;
+use scraper::{Html, Selector};
+
+const SECS_PER_DAY: u64 = 86_400;
+
+/// Elements carrying a post's publication date
+const DATE_SELECTORS: &[(&str, &str)] = &[
+    ("time[datetime]", "datetime"),
+    ("meta[property='article:published_time']", "content"),
+    ("abbr.published[title]", "title"),
+];
+
+/// The archive page to fetch after `page_url`, or `None` when the crawl stops.
+///
+/// `depth` counts the archive pages already followed (0 on the front page).
+/// The crawl stops at the site's `max_depth`, and once `html` holds a post
+/// older than `max_age_days` before `now` (Unix seconds): later pages are older still.
+pub fn next_archive_page(
+    site: &SiteConfig,
+    depth: usize,
+    page_url: &str,
+    html: &str,
+    now: u64,
+) -> Option<String> {
+    let crawl = site.crawl.as_ref()?;
+    if depth >= crawl.max_depth
+        || !matches!(
+            site.search_kind,
+            SearchKind::FrontPage | SearchKind::ListingPage
+        )
+    {
+        return None;
+    }
+    if let Some(max_age) = crawl.max_age_days
+        && oldest_post_day(html).is_some_and(|day| day + max_age < now / SECS_PER_DAY)
+    {
+        return None;
+    }
+    next_page_link(html, page_url).or_else(|| {
+        // WordPress front pages page as /page/N/ even without a "next" link
+        (site.search_kind == SearchKind::FrontPage).then(|| {
+            format!(
+                "{}/page/{}/",
+                site.base_url.trim_end_matches('/'),
+                depth + 2
+            )
+        })
+    })
+}
+
+/// Day (since the Unix epoch) of the oldest dated post in `html`
+fn oldest_post_day(html: &str) -> Option<u64> {
+    let doc = Html::parse_document(html);
+    DATE_SELECTORS
+        .iter()
+        .filter_map(|(sel, attr)| Selector::parse(sel).ok().map(|s| (s, *attr)))
+        .flat_map(|(sel, attr)| {
+            doc.select(&sel)
+                .filter_map(|el| el.value().attr(attr))
+                .filter_map(|d| date_sort_key(d.get(..10)?))
+                .collect::<Vec<_>>()
+        })
+        .filter_map(|(y, m, d)| days_from_civil(y, m, d))
+        .min()
+}
+
+/// Days from 1970-01-01 to the given date (proleptic Gregorian calendar)
+fn days_from_civil(y: u32, m: u32, d: u32) -> Option<u64> {
+    let y = i64::from(y) - i64::from(m <= 2);
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (i64::from(m) + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + i64::from(d) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    u64::try_from(era * 146_097 + doe - 719_468).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::site_configs;
+    use crate::models::CrawlConfig;
+
+    fn elamigos() -> SiteConfig {
+        site_configs()
+            .into_iter()
+            .find(|s| s.name == "elamigos")
+            .expect("elamigos is configured")
+    }
+
+    fn page(dates: &[&str], next: Option<&str>) -> String {
+        let mut html = String::from("<html><body>");
+        for d in dates {
+            html.push_str(&format!(
+                r#"<article><h2 class="entry-title"><a href="/g/">Game</a></h2><time datetime="{d}T10:00:00+00:00">{d}</time></article>"#
+            ));
+        }
+        if let Some(href) = next {
+            html.push_str(&format!(
+                r#"<div class="nav-previous"><a href="{href}">Older posts</a></div>"#
+            ));
+        }
+        html.push_str("</body></html>");
+        html
+    }
+
+    // 2024-06-01T00:00:00Z
+    const NOW: u64 = 1_717_200_000;
+
+    #[test]
+    fn days_from_civil_matches_known_dates() {
+        assert_eq!(days_from_civil(1970, 1, 1), Some(0));
+        assert_eq!(days_from_civil(2000, 3, 1), Some(11_017));
+        assert_eq!(days_from_civil(2024, 6, 1), Some(NOW / SECS_PER_DAY));
+    }
+
+    #[test]
+    fn crawl_follows_older_posts_up_to_the_depth() {
+        let mut site = elamigos();
+        site.crawl = Some(CrawlConfig {
+            max_depth: 2,
+            max_age_days: None,
+        });
+        let html = page(&["2024-05-30"], Some("https://elamigos.site/archive/2"));
+        assert_eq!(
+            next_archive_page(&site, 0, "https://elamigos.site/", &html, NOW).as_deref(),
+            Some("https://elamigos.site/archive/2")
+        );
+        // Without a link, WordPress paging is assumed
+        assert_eq!(
+            next_archive_page(&site, 1, "https://elamigos.site/archive/2", "", NOW).as_deref(),
+            Some("https://elamigos.site/page/3/")
+        );
+        assert_eq!(
+            next_archive_page(&site, 2, "https://elamigos.site/page/3/", &html, NOW),
+            None
+        );
+    }
+
+    #[test]
+    fn crawl_stops_past_the_date_horizon() {
+        let mut site = elamigos();
+        site.crawl = Some(CrawlConfig {
+            max_depth: 5,
+            max_age_days: Some(30),
+        });
+        let recent = page(&["2024-05-30", "2024-05-10"], Some("/page/2/"));
+        assert!(next_archive_page(&site, 0, "https://elamigos.site/", &recent, NOW).is_some());
+        let old = page(&["2024-05-30", "2024-03-01"], Some("/page/2/"));
+        assert_eq!(
+            next_archive_page(&site, 0, "https://elamigos.site/", &old, NOW),
+            None
+        );
+    }
+
+    #[test]
+    fn sites_without_crawl_config_are_not_crawled() {
+        let mut site = elamigos();
+        site.crawl = None;
+        let html = page(&[], Some("/page/2/"));
+        assert_eq!(
+            next_archive_page(&site, 0, "https://elamigos.site/", &html, NOW),
+            None
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! Pages and responses saved by `--debug`.
+//!
+//! Samples go to a `debug` directory under the cache directory instead of the
+//! working directory, with a timestamp in each file name so runs don't
+//! overwrite each other. After every write the oldest files are pruned to stay
+//! within [`Retention`].
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How many debug files are kept, and how much space they may take
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retention {
+    pub max_files: usize,
+    pub max_bytes: u64,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            max_files: 50,
+            max_bytes: 20 * 1024 * 1024,
+        }
+    }
+}
+
+/// Files removed by [`DebugDir::prune`] or [`DebugDir::clean`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Removed {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// The directory debug samples are written to
+#[derive(Debug, Clone)]
+pub struct DebugDir {
+    dir: PathBuf,
+    retention: Retention,
+}
+
+impl Default for DebugDir {
+    fn default() -> Self {
+        Self::new(crate::paths::debug_dir())
+    }
+}
+
+impl DebugDir {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            retention: Retention::default(),
+        }
+    }
+
+    pub fn with_retention(mut self, retention: Retention) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Save `contents` as `<name>-<unix millis>.<ext>` and prune old files.
+    /// Returns the path written.
+    pub fn write(&self, name: &str, ext: &str, contents: impl AsRef<[u8]>) -> io::Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)?;
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let path = self.dir.join(format!("{name}-{millis}.{ext}"));
+        std::fs::write(&path, contents)?;
+        self.prune()?;
+        Ok(path)
+    }
+
+    /// Remove the oldest files until the directory is within its retention
+    /// limits. The newest file is always kept, however large.
+    pub fn prune(&self) -> io::Result<Removed> {
+        let mut files = self.files()?;
+        // Newest first: keep files until a limit is reached, remove the rest
+        files.sort_by(|a, b| {
+            b.modified
+                .cmp(&a.modified)
+                .then_with(|| b.path.cmp(&a.path))
+        });
+        let (mut kept, mut kept_bytes) = (0, 0);
+        let mut removed = Removed::default();
+        for file in files {
+            let fits = kept < self.retention.max_files
+                && kept_bytes + file.len <= self.retention.max_bytes;
+            if kept == 0 || fits {
+                kept += 1;
+                kept_bytes += file.len;
+            } else {
+                std::fs::remove_file(&file.path)?;
+                removed.files += 1;
+                removed.bytes += file.len;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Remove every debug file
+    pub fn clean(&self) -> io::Result<Removed> {
+        let mut removed = Removed::default();
+        for file in self.files()? {
+            std::fs::remove_file(&file.path)?;
+            removed.files += 1;
+            removed.bytes += file.len;
+        }
+        Ok(removed)
+    }
+
+    fn files(&self) -> io::Result<Vec<DebugFile>> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut files = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let meta = entry.metadata()?;
+            if meta.is_file() {
+                files.push(DebugFile {
+                    path: entry.path(),
+                    len: meta.len(),
+                    modified: meta.modified().unwrap_or(UNIX_EPOCH),
+                });
+            }
+        }
+        Ok(files)
+    }
+}
+
+struct DebugFile {
+    path: PathBuf,
+    len: u64,
+    modified: SystemTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn names(dir: &DebugDir) -> Vec<String> {
+        let mut names: Vec<String> = dir
+            .files()
+            .unwrap()
+            .iter()
+            .map(|f| f.path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn samples_are_timestamped_and_cleaned() {
+        let tmp = tempdir().unwrap();
+        let dir = DebugDir::new(tmp.path().join("debug"));
+        assert_eq!(dir.clean().unwrap(), Removed::default());
+
+        let path = dir
+            .write("fitgirl_sample", "html", "<html></html>")
+            .unwrap();
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.starts_with("fitgirl_sample-") && name.ends_with(".html"));
+        assert_eq!(
+            dir.clean().unwrap(),
+            Removed {
+                files: 1,
+                bytes: 13
+            }
+        );
+        assert!(names(&dir).is_empty());
+    }
+
+    #[test]
+    fn oldest_files_are_pruned_past_the_limits() {
+        let tmp = tempdir().unwrap();
+        let dir = DebugDir::new(tmp.path().to_path_buf()).with_retention(Retention {
+            max_files: 2,
+            max_bytes: 1024,
+        });
+        for (i, name) in ["a", "b", "c"].iter().enumerate() {
+            let path = tmp.path().join(format!("{name}.txt"));
+            std::fs::write(&path, "x").unwrap();
+            let modified = UNIX_EPOCH + std::time::Duration::from_secs(1_000 + i as u64);
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+        assert_eq!(dir.prune().unwrap().files, 1);
+        assert_eq!(names(&dir), ["b.txt", "c.txt"]);
+
+        // A new sample over the size budget is kept, at the expense of older ones
+        let small = DebugDir::new(tmp.path().to_path_buf()).with_retention(Retention {
+            max_files: 10,
+            max_bytes: 4,
+        });
+        let path = small.write("huge", "html", "<html></html>").unwrap();
+        assert_eq!(names(&small), [path.file_name().unwrap().to_string_lossy()]);
+    }
+}
@@ -0,0 +1,334 @@
+//! Download link extraction from a result's detail page.
+
+use crate::analyzer::{ResultMetadata, extract_metadata};
+use crate::sanitize::sanitize_text;
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+use serde::Serialize;
+
+/// Longest description snippet kept, in characters
+const DESCRIPTION_CHARS: usize = 300;
+
+/// Where the release itself is on a detail page, most specific first:
+/// WordPress posts, phpBB posts (every post of a cs.rin.ru thread), then
+/// generic containers
+const CONTENT_SELECTORS: &[&str] = &[".entry-content", ".postbody", "article", "main", "body"];
+
+/// File hosts commonly used by repack and forum posts
+const FILE_HOSTS: &[&str] = &[
+    "1fichier.com",
+    "buzzheavier.com",
+    "datanodes.to",
+    "gofile.io",
+    "mediafire.com",
+    "mega.nz",
+    "megaup.net",
+    "pixeldrain.com",
+    "qiwi.gg",
+    "rapidgator.net",
+    "uploadhaven.com",
+];
+
+fn is_download_link(url: &Url) -> bool {
+    if url.scheme() == "magnet" {
+        return true;
+    }
+    if url.path().to_ascii_lowercase().ends_with(".torrent") {
+        return true;
+    }
+    url.host_str().is_some_and(|host| {
+        FILE_HOSTS
+            .iter()
+            .any(|h| host == *h || host.ends_with(&format!(".{h}")))
+    })
+}
+
+/// Collect magnet, `.torrent` and file-host links from a detail page.
+///
+/// Relative links are resolved against `page_url`; duplicates are dropped and
+/// page order is kept.
+pub fn extract_download_links(html: &str, page_url: &str) -> Vec<String> {
+    let Ok(base) = Url::parse(page_url) else {
+        return Vec::new();
+    };
+    let document = Html::parse_document(html);
+    let Ok(sel) = Selector::parse("a[href]") else {
+        return Vec::new();
+    };
+    let mut links: Vec<String> = Vec::new();
+    for a in document.select(&sel) {
+        let href = a.value().attr("href").unwrap_or("").trim();
+        let Ok(url) = base.join(href) else {
+            continue;
+        };
+        let url_str = url.to_string();
+        if is_download_link(&url) && !links.contains(&url_str) {
+            links.push(url_str);
+        }
+    }
+    links
+}
+
+/// What a result's detail page says about the release
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct ResultDetails {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Page description, cut to a short snippet
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Size, version and date found in the title, description or body
+    pub metadata: ResultMetadata,
+    pub magnets: Vec<String>,
+    /// `.torrent` file links
+    pub torrents: Vec<String>,
+    /// File-host mirror links
+    pub mirrors: Vec<String>,
+}
+
+fn meta_content(document: &Html, selector: &str) -> Option<String> {
+    let sel = Selector::parse(selector).ok()?;
+    document
+        .select(&sel)
+        .filter_map(|m| m.value().attr("content"))
+        .map(sanitize_text)
+        .find(|c| !c.is_empty())
+}
+
+fn element_text(el: ElementRef<'_>) -> String {
+    sanitize_text(&el.text().collect::<String>())
+}
+
+fn snippet(text: String) -> String {
+    if text.chars().count() <= DESCRIPTION_CHARS {
+        return text;
+    }
+    let cut: String = text.chars().take(DESCRIPTION_CHARS).collect();
+    // Don't end mid-word
+    let cut = cut.rsplit_once(' ').map_or(cut.as_str(), |(head, _)| head);
+    format!("{}…", cut.trim_end())
+}
+
+/// Pull title, description, metadata and download links out of a detail page
+pub fn extract_details(html: &str, page_url: &str) -> ResultDetails {
+    let document = Html::parse_document(html);
+    let title = meta_content(&document, "meta[property='og:title']").or_else(|| {
+        let sel = Selector::parse("title").ok()?;
+        document
+            .select(&sel)
+            .map(element_text)
+            .find(|t| !t.is_empty())
+    });
+    let paragraphs: Vec<String> = Selector::parse("p")
+        .map(|sel| document.select(&sel).map(element_text).collect())
+        .unwrap_or_default();
+    let description = meta_content(&document, "meta[name='description']")
+        .or_else(|| meta_content(&document, "meta[property='og:description']"))
+        .or_else(|| paragraphs.iter().find(|p| p.len() >= 40).cloned())
+        .map(snippet);
+
+    // Title first, then description, then body paragraphs fill any gaps
+    let mut metadata = ResultMetadata::default();
+    let sources = title.iter().chain(description.iter()).chain(&paragraphs);
+    for text in sources {
+        let found = extract_metadata(text);
+        metadata.file_size = metadata.file_size.or(found.file_size);
+        metadata.release_date = metadata.release_date.or(found.release_date);
+        metadata.version = metadata.version.or(found.version);
+        metadata.build = metadata.build.or(found.build);
+    }
+
+    let mut details = ResultDetails {
+        title,
+        description,
+        metadata,
+        ..ResultDetails::default()
+    };
+    for link in extract_download_links(html, page_url) {
+        if link.starts_with("magnet:") {
+            details.magnets.push(link);
+        } else if Url::parse(&link)
+            .is_ok_and(|u| u.path().to_ascii_lowercase().ends_with(".torrent"))
+        {
+            details.torrents.push(link);
+        } else {
+            details.mirrors.push(link);
+        }
+    }
+    details
+}
+
+/// Fingerprint of the part of a detail page that changes when the release does.
+///
+/// Covers the text and link targets of the first [`CONTENT_SELECTORS`] region
+/// found, without scripts, styles or whitespace differences, so sidebars, ads
+/// and page chrome don't count as changes. FNV-1a, so the value is the same
+/// across runs and builds.
+pub fn content_hash(html: &str) -> String {
+    let document = Html::parse_document(html);
+    let mut content = String::new();
+    for selector in CONTENT_SELECTORS {
+        let Ok(sel) = Selector::parse(selector) else {
+            continue;
+        };
+        for el in document.select(&sel) {
+            push_content(el, &mut content);
+        }
+        if !content.trim().is_empty() {
+            break;
+        }
+    }
+    let hash = sanitize_text(&content)
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, b| {
+            (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{hash:016x}")
+}
+
+fn push_content(el: ElementRef<'_>, out: &mut String) {
+    for node in el.descendants() {
+        if let Some(text) = node.value().as_text() {
+            let hidden = node.ancestors().any(|a| {
+                a.value()
+                    .as_element()
+                    .is_some_and(|e| matches!(e.name(), "script" | "style" | "noscript"))
+            });
+            if !hidden {
+                out.push_str(text);
+                out.push(' ');
+            }
+        } else if let Some(href) = node
+            .value()
+            .as_element()
+            .filter(|e| e.name() == "a")
+            .and_then(|e| e.attr("href"))
+        {
+            out.push_str(href);
+            out.push(' ');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_magnets_torrents_and_file_hosts() {
+        let html = r#"
+            <a href="magnet:?xt=urn:btih:abc&dn=Game">magnet</a>
+            <a href="/files/game.torrent">torrent</a>
+            <a href="https://gofile.io/d/xyz">gofile</a>
+            <a href="https://www.mediafire.com/file/abc">mediafire</a>
+            <a href="https://example.com/about">about</a>
+            <a href="https://gofile.io/d/xyz">dupe</a>
+        "#;
+        let links = extract_download_links(html, "https://repacks.example/game/");
+        assert_eq!(
+            links,
+            vec![
+                "magnet:?xt=urn:btih:abc&dn=Game",
+                "https://repacks.example/files/game.torrent",
+                "https://gofile.io/d/xyz",
+                "https://www.mediafire.com/file/abc",
+            ]
+        );
+    }
+
+    #[test]
+    fn lookalike_hosts_are_ignored() {
+        let html = r#"<a href="https://notgofile.io/d/x">x</a>"#;
+        assert!(extract_download_links(html, "https://a.example/").is_empty());
+    }
+
+    #[test]
+    fn invalid_page_url_yields_nothing() {
+        assert!(extract_download_links("<a href='magnet:?x'>m</a>", "not a url").is_empty());
+    }
+
+    #[test]
+    fn extract_details_collects_description_metadata_and_grouped_links() {
+        let html = r#"
+            <html><head>
+              <title>Elden Ring v1.10 - Repacks</title>
+              <meta name="description" content="  Action RPG
+                 in the Lands Between. ">
+            </head><body>
+              <p>Repack size: 47.5 GB</p>
+              <a href="magnet:?xt=urn:btih:abc">magnet</a>
+              <a href="/dl/elden.torrent">torrent</a>
+              <a href="https://gofile.io/d/xyz">gofile</a>
+            </body></html>
+        "#;
+        let details = extract_details(html, "https://repacks.example/elden-ring/");
+        assert_eq!(details.title.as_deref(), Some("Elden Ring v1.10 - Repacks"));
+        assert_eq!(
+            details.description.as_deref(),
+            Some("Action RPG in the Lands Between.")
+        );
+        assert_eq!(details.metadata.version.as_deref(), Some("v1.10"));
+        assert!(details.metadata.file_size.is_some());
+        assert_eq!(details.magnets, ["magnet:?xt=urn:btih:abc"]);
+        assert_eq!(
+            details.torrents,
+            ["https://repacks.example/dl/elden.torrent"]
+        );
+        assert_eq!(details.mirrors, ["https://gofile.io/d/xyz"]);
+    }
+
+    #[test]
+    fn long_paragraph_descriptions_are_cut_at_a_word() {
+        let body = "word ".repeat(200);
+        let html = format!("<p>short</p><p>{body}</p>");
+        let description = extract_details(&html, "https://a.example/")
+            .description
+            .unwrap();
+        assert!(description.ends_with("word…"));
+        assert!(description.chars().count() <= DESCRIPTION_CHARS + 1);
+    }
+
+    #[test]
+    fn content_hash_follows_the_post_and_ignores_page_chrome() {
+        let page = |post: &str, sidebar: &str| {
+            format!(
+                "<html><body><aside>{sidebar}</aside><article><div class='entry-content'>\
+                 {post}<script>var views = {};</script></div></article></body></html>",
+                sidebar.len()
+            )
+        };
+        let post = "<p>Elden Ring v1.16</p><a href='magnet:?xt=urn:btih:aaa'>magnet</a>";
+        let hash = content_hash(&page(post, "Popular: Hades"));
+        assert_eq!(hash.len(), 16);
+        assert_eq!(
+            hash,
+            content_hash(&page(post, "Popular: Hollow Knight Silksong"))
+        );
+        assert_eq!(
+            hash,
+            content_hash(&page(&post.replace("</p>", "  </p>\n"), "Popular: Hades"))
+        );
+
+        let new_link = post.replace("aaa", "bbb");
+        assert_ne!(hash, content_hash(&page(&new_link, "Popular: Hades")));
+        let new_build = post.replace("1.16", "1.16.1");
+        assert_ne!(hash, content_hash(&page(&new_build, "Popular: Hades")));
+    }
+
+    #[test]
+    fn content_hash_covers_every_forum_post() {
+        let thread = |posts: &[&str]| {
+            let posts: String = posts
+                .iter()
+                .map(|p| format!("<div class='post'><div class='postbody'>{p}</div></div>"))
+                .collect();
+            format!("<html><body><div id='nav'>Index</div>{posts}</body></html>")
+        };
+        let first = content_hash(&thread(&["Build 123"]));
+        assert_ne!(
+            first,
+            content_hash(&thread(&["Build 123", "Build 124 is up"]))
+        );
+        assert_eq!(first, content_hash(&thread(&["Build 123"])));
+    }
+}
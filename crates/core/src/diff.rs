@@ -0,0 +1,113 @@
+use crate::models::SearchResult;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A result present in both runs whose title changed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ChangedResult {
+    pub old: SearchResult,
+    pub new: SearchResult,
+}
+
+/// Difference between two sets of search results.
+///
+/// Results are matched by site and URL; a matching pair whose title differs is
+/// reported as changed (e.g. a repack bumped to a new version on the same page).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ResultDiff {
+    pub added: Vec<SearchResult>,
+    pub removed: Vec<SearchResult>,
+    pub changed: Vec<ChangedResult>,
+}
+
+impl ResultDiff {
+    /// True when both runs returned the same results
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+fn key(r: &SearchResult) -> (String, String) {
+    (
+        r.site.to_lowercase(),
+        r.url.trim_end_matches('/').to_lowercase(),
+    )
+}
+
+/// Compare an older result set against a newer one.
+///
+/// Output order follows the input order of `new` (added, changed) and `old` (removed).
+pub fn diff_results(old: &[SearchResult], new: &[SearchResult]) -> ResultDiff {
+    let old_by_key: HashMap<_, &SearchResult> = old.iter().map(|r| (key(r), r)).collect();
+    let new_by_key: HashMap<_, &SearchResult> = new.iter().map(|r| (key(r), r)).collect();
+
+    let mut diff = ResultDiff::default();
+    for r in new {
+        match old_by_key.get(&key(r)) {
+            None => diff.added.push(r.clone()),
+            Some(prev) if prev.title.trim() != r.title.trim() => diff.changed.push(ChangedResult {
+                old: (*prev).clone(),
+                new: r.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    diff.removed = old
+        .iter()
+        .filter(|r| !new_by_key.contains_key(&key(r)))
+        .cloned()
+        .collect();
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn r(site: &str, title: &str, url: &str) -> SearchResult {
+        SearchResult {
+            site: site.into(),
+            title: title.into(),
+            url: url.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn identical_sets_produce_empty_diff() {
+        let a = vec![r("fitgirl", "Elden Ring", "https://f.site/elden-ring/")];
+        assert!(diff_results(&a, &a).is_empty());
+    }
+
+    #[test]
+    fn reports_added_removed_and_changed() {
+        let old = vec![
+            r("fitgirl", "Elden Ring v1.0", "https://f.site/elden-ring/"),
+            r("dodi", "Elden Ring", "https://d.site/elden-ring/"),
+        ];
+        let new = vec![
+            r("fitgirl", "Elden Ring v1.1", "https://f.site/elden-ring"),
+            r("steamrip", "Elden Ring", "https://s.site/elden-ring/"),
+        ];
+        let diff = diff_results(&old, &new);
+        assert_eq!(diff.added, vec![new[1].clone()]);
+        assert_eq!(diff.removed, vec![old[1].clone()]);
+        assert_eq!(
+            diff.changed,
+            vec![ChangedResult {
+                old: old[0].clone(),
+                new: new[0].clone(),
+            }]
+        );
+    }
+
+    #[test]
+    fn same_url_on_different_sites_is_distinct() {
+        let old = vec![r("fitgirl", "X", "https://mirror/x")];
+        let new = vec![r("dodi", "X", "https://mirror/x")];
+        let diff = diff_results(&old, &new);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.removed.len(), 1);
+    }
+}
@@ -0,0 +1,149 @@
+//! Errors returned to the GUI, with a category the frontend can act on
+//! ("start FlareSolverr", "fix the query") instead of a bare message.
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::resilience::{ErrorCategory, categorize_error, is_retryable};
+
+/// What went wrong, as far as the user can do something about it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiErrorCategory {
+    /// Bad input from the user: empty query, unknown site, malformed URL or cookie
+    InvalidInput,
+    /// The thing asked for no longer exists (finished job, deleted entry)
+    NotFound,
+    /// FlareSolverr could not be reached or answered with an error status
+    SolverUnavailable,
+    Network,
+    RateLimit,
+    /// HTTP 401/403 from a site, usually a missing or stale cookie
+    Auth,
+    ServerError,
+    Parse,
+    CircuitOpen,
+    /// Reading or writing settings, cache or export files failed
+    Storage,
+    Internal,
+}
+
+impl ApiErrorCategory {
+    /// Whether the same request may succeed later without any change
+    pub fn is_retryable(self) -> bool {
+        match self {
+            ApiErrorCategory::SolverUnavailable | ApiErrorCategory::CircuitOpen => true,
+            ApiErrorCategory::Network => is_retryable(ErrorCategory::Network),
+            ApiErrorCategory::RateLimit => is_retryable(ErrorCategory::RateLimit),
+            ApiErrorCategory::ServerError => is_retryable(ErrorCategory::ServerError),
+            _ => false,
+        }
+    }
+}
+
+impl From<ErrorCategory> for ApiErrorCategory {
+    fn from(category: ErrorCategory) -> Self {
+        match category {
+            ErrorCategory::Network => ApiErrorCategory::Network,
+            ErrorCategory::RateLimit => ApiErrorCategory::RateLimit,
+            ErrorCategory::Auth => ApiErrorCategory::Auth,
+            ErrorCategory::ServerError => ApiErrorCategory::ServerError,
+            ErrorCategory::Parse => ApiErrorCategory::Parse,
+            ErrorCategory::CircuitOpen => ApiErrorCategory::CircuitOpen,
+            ErrorCategory::Unknown => ApiErrorCategory::Internal,
+        }
+    }
+}
+
+/// Error of a GUI command, serialized as
+/// `{ category, message, site, retryable }`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Error)]
+#[error("{message}")]
+pub struct ApiError {
+    pub category: ApiErrorCategory,
+    pub message: String,
+    /// The site the error concerns, if any
+    pub site: Option<String>,
+    pub retryable: bool,
+}
+
+impl ApiError {
+    pub fn new(category: ApiErrorCategory, message: impl Into<String>) -> Self {
+        Self {
+            category,
+            message: message.into(),
+            site: None,
+            retryable: category.is_retryable(),
+        }
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(ApiErrorCategory::InvalidInput, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ApiErrorCategory::NotFound, message)
+    }
+
+    pub fn storage(message: impl Into<String>) -> Self {
+        Self::new(ApiErrorCategory::Storage, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ApiErrorCategory::Internal, message)
+    }
+
+    /// Categorize a failed fetch; errors from talking to FlareSolverr itself
+    /// (not the site behind it) are `SolverUnavailable`
+    pub fn from_fetch(err: &anyhow::Error) -> Self {
+        let message = format!("{err:#}");
+        let solver_failed = err.chain().any(|cause| {
+            let cause = cause.to_string();
+            cause.starts_with("send flaresolverr request")
+                || cause.starts_with("flaresolverr http status")
+        });
+        let category = if solver_failed {
+            ApiErrorCategory::SolverUnavailable
+        } else {
+            categorize_error(err).into()
+        };
+        Self::new(category, message)
+    }
+
+    pub fn for_site(mut self, site: impl Into<String>) -> Self {
+        self.site = Some(site.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn fetch_errors_are_categorized() {
+        let refused: anyhow::Result<()> = Err(anyhow::anyhow!("connection refused"));
+        let solver =
+            ApiError::from_fetch(&refused.context("send flaresolverr request").unwrap_err());
+        assert_eq!(solver.category, ApiErrorCategory::SolverUnavailable);
+        assert!(solver.retryable);
+        assert!(solver.message.contains("connection refused"));
+
+        let auth = ApiError::from_fetch(&anyhow::anyhow!("HTTP 403 Forbidden")).for_site("csrin");
+        assert_eq!(auth.category, ApiErrorCategory::Auth);
+        assert!(!auth.retryable);
+        assert_eq!(auth.site.as_deref(), Some("csrin"));
+
+        let json = serde_json::to_value(ApiError::invalid_input("empty search phrase")).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "category": "invalid_input",
+                "message": "empty search phrase",
+                "site": null,
+                "retryable": false,
+            })
+        );
+    }
+}
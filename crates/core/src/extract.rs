@@ -0,0 +1,149 @@
+//! Where a result's title and URL are read from, set per site by
+//! `title_attr` and `url_attr` in `sites.toml`.
+//!
+//! ```toml
+//! title_attr = "text"                       # the element's text
+//! url_attr = "data-href"                    # any attribute, data-* included
+//! title_attr = "json:data-props:/game/name" # a value of JSON in an attribute
+//! ```
+//!
+//! The JSON form names the attribute and a JSON pointer into its value
+//! (RFC 6901: `/` between keys, array positions as numbers); an empty pointer
+//! takes the whole value. Strings and numbers are read, anything else is
+//! treated as missing.
+
+use scraper::ElementRef;
+
+/// One way of reading a value from a result element
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Extract {
+    /// The element's text
+    Text,
+    /// An attribute of the element
+    Attr(String),
+    /// A value inside JSON held by an attribute
+    Json { attr: String, pointer: String },
+}
+
+impl Extract {
+    /// How `title_attr` reads titles; empty means the text
+    pub fn title(spec: &str) -> anyhow::Result<Self> {
+        Self::parse(spec, Extract::Text)
+    }
+
+    /// How `url_attr` reads URLs; empty means `href`
+    pub fn url(spec: &str) -> anyhow::Result<Self> {
+        Self::parse(spec, Extract::Attr("href".into()))
+    }
+
+    fn parse(spec: &str, default: Extract) -> anyhow::Result<Self> {
+        let spec = spec.trim();
+        if spec.is_empty() {
+            return Ok(default);
+        }
+        if spec.eq_ignore_ascii_case("text") {
+            return Ok(Extract::Text);
+        }
+        if let Some(rest) = spec.strip_prefix("json:") {
+            let (attr, pointer) = rest.split_once(':').unwrap_or((rest, ""));
+            if !pointer.is_empty() && !pointer.starts_with('/') {
+                anyhow::bail!("{spec:?}: the JSON pointer must start with /");
+            }
+            return Ok(Extract::Json {
+                attr: attribute(attr, spec)?,
+                pointer: pointer.to_string(),
+            });
+        }
+        Ok(Extract::Attr(attribute(spec, spec)?))
+    }
+
+    /// The value of `el`, trimmed; `None` if it is missing or empty
+    pub fn read(&self, el: ElementRef) -> Option<String> {
+        let value = match self {
+            Extract::Text => el.text().collect::<String>(),
+            Extract::Attr(attr) => el.value().attr(attr)?.to_string(),
+            Extract::Json { attr, pointer } => {
+                let json: serde_json::Value = serde_json::from_str(el.value().attr(attr)?).ok()?;
+                match json.pointer(pointer)? {
+                    serde_json::Value::String(s) => s.clone(),
+                    serde_json::Value::Number(n) => n.to_string(),
+                    _ => return None,
+                }
+            }
+        };
+        let value = value.trim();
+        (!value.is_empty()).then(|| value.to_string())
+    }
+
+    /// The value of `el` or, when it is read from an attribute `el` lacks,
+    /// of the first element inside it that has one; so a selector can match
+    /// a whole card whose link and title sit on an anchor or image inside it
+    pub fn find(&self, el: ElementRef) -> Option<String> {
+        self.read(el).or_else(|| match self {
+            Extract::Text => None,
+            _ => el
+                .descendants()
+                .skip(1)
+                .filter_map(ElementRef::wrap)
+                .find_map(|inner| self.read(inner)),
+        })
+    }
+}
+
+/// An attribute name as the HTML parser stores it: lowercased
+fn attribute(name: &str, spec: &str) -> anyhow::Result<String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'));
+    if !valid {
+        anyhow::bail!("{spec:?} is not text, an attribute name or json:<attribute>:<pointer>");
+    }
+    Ok(name.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scraper::{Html, Selector};
+
+    #[test]
+    fn values_come_from_text_attributes_and_json() {
+        let html = Html::parse_fragment(
+            r#"<div class="card" data-URL="/game/1" data-props='{"game":{"name":"Elden Ring","ids":[1245620]},"tags":[]}'> Card </div>"#,
+        );
+        let card = html
+            .select(&Selector::parse("div.card").unwrap())
+            .next()
+            .unwrap();
+        let read = |spec: &str| Extract::title(spec).unwrap().read(card);
+        assert_eq!(read(""), Some("Card".into()));
+        assert_eq!(read("data-url"), Some("/game/1".into()));
+        assert_eq!(
+            read("json:data-props:/game/name"),
+            Some("Elden Ring".into())
+        );
+        assert_eq!(read("json:data-props:/game/ids/0"), Some("1245620".into()));
+        assert_eq!(read("json:data-props:/tags"), None);
+        assert_eq!(read("json:data-props:/missing"), None);
+        assert_eq!(read("title"), None);
+        let html = Html::parse_fragment(
+            r#"<div class="card"><span>New</span><a href="/game/1"><img alt="Elden Ring"></a></div>"#,
+        );
+        let card = html
+            .select(&Selector::parse("div.card").unwrap())
+            .next()
+            .unwrap();
+        assert_eq!(Extract::url("").unwrap().find(card), Some("/game/1".into()));
+        let alt = Extract::title("alt").unwrap();
+        assert_eq!(
+            (alt.read(card), alt.find(card)),
+            (None, Some("Elden Ring".into()))
+        );
+        assert_eq!(Extract::url("").unwrap(), Extract::Attr("href".into()));
+
+        assert!(Extract::url("json:data-props:game").is_err());
+        assert!(Extract::url("a b").is_err());
+        assert!(Extract::url("json::/url").is_err());
+    }
+}
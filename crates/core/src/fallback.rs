@@ -0,0 +1,252 @@
+//! One consolidated report of the strategies tried for a site.
+//!
+//! Sites like csrin fall back from the search page to a feed and then to
+//! Playwright. Each strategy records its outcome in a [`FallbackReport`], so a
+//! site that ends up empty can say why in a single message.
+
+use crate::models::{Provenance, SiteConfig};
+use crate::parser::diagnose;
+use crate::resilience::{ErrorCategory, categorize_message};
+use std::fmt;
+
+/// What one strategy produced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// This many results
+    Found(usize),
+    /// A page came back but yielded nothing
+    NoResults {
+        /// Elements the site's result selector matched (`None` if it is invalid)
+        selector_matches: Option<usize>,
+        /// The page looked like a Cloudflare challenge
+        challenge: bool,
+    },
+    /// A feed or JSON response had this many entries, none for the query
+    NoMatches(usize),
+    /// The response body was empty (blocked, missing or redirected)
+    EmptyResponse,
+    /// The HTTP fetch failed: status or network error
+    Http(String),
+    /// FlareSolverr failed
+    Solver(String),
+    /// The strategy could not run, e.g. the Playwright script is missing
+    Unavailable(String),
+}
+
+impl Outcome {
+    /// Outcome of parsing a fetched page of `site` into `found` results
+    pub fn of_page(site: &SiteConfig, html: &str, query: &str, found: usize) -> Self {
+        if found > 0 {
+            return Outcome::Found(found);
+        }
+        if html.trim().is_empty() {
+            return Outcome::EmptyResponse;
+        }
+        let diagnostics = diagnose(site, html, query);
+        Outcome::NoResults {
+            selector_matches: diagnostics.selector_matches,
+            challenge: diagnostics.looks_like_challenge,
+        }
+    }
+
+    /// Outcome of a failed fetch, through the solver or not
+    pub fn fetch_error(via_solver: bool, err: &anyhow::Error) -> Self {
+        if via_solver {
+            Outcome::Solver(err.to_string())
+        } else {
+            Outcome::Http(err.to_string())
+        }
+    }
+
+    /// Kind of failure of a fetch or solver error
+    pub fn error_category(&self) -> Option<ErrorCategory> {
+        match self {
+            Outcome::Http(e) | Outcome::Solver(e) => Some(categorize_message(e)),
+            _ => None,
+        }
+    }
+
+    fn is_error(&self) -> bool {
+        matches!(
+            self,
+            Outcome::EmptyResponse
+                | Outcome::Http(_)
+                | Outcome::Solver(_)
+                | Outcome::Unavailable(_)
+        )
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Found(n) => write!(f, "{n} results"),
+            Outcome::NoResults {
+                selector_matches,
+                challenge,
+            } => {
+                match selector_matches {
+                    Some(0) => write!(f, "result selector matched nothing")?,
+                    Some(n) => write!(f, "result selector matched {n}, none for the query")?,
+                    None => write!(f, "invalid result selector")?,
+                }
+                if *challenge {
+                    write!(f, ", page looks like a Cloudflare challenge")?;
+                }
+                Ok(())
+            }
+            Outcome::NoMatches(n) => write!(f, "{n} entries, none for the query"),
+            Outcome::EmptyResponse => write!(f, "empty response"),
+            Outcome::Http(e) => write!(f, "HTTP error: {e}"),
+            Outcome::Solver(e) => write!(f, "solver error: {e}"),
+            Outcome::Unavailable(why) => write!(f, "unavailable: {why}"),
+        }
+    }
+}
+
+/// One strategy tried for a site
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attempt {
+    pub via: Provenance,
+    pub outcome: Outcome,
+}
+
+/// Every strategy tried for one site, in order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FallbackReport {
+    pub site: String,
+    pub attempts: Vec<Attempt>,
+}
+
+impl FallbackReport {
+    pub fn new(site: &str) -> Self {
+        Self {
+            site: site.to_string(),
+            attempts: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, via: Provenance, outcome: Outcome) {
+        self.attempts.push(Attempt { via, outcome });
+    }
+
+    /// Whether every strategy came up empty and it is worth telling the user why:
+    /// a fallback ran or something failed, as opposed to a plain "no matches"
+    pub fn exhausted(&self) -> bool {
+        !self.attempts.is_empty()
+            && !self
+                .attempts
+                .iter()
+                .any(|a| matches!(a.outcome, Outcome::Found(_)))
+            && (self.attempts.len() > 1 || self.attempts.iter().any(|a| a.outcome.is_error()))
+    }
+
+    /// Whether the site came up empty because a fetch hit a network or server
+    /// error, which may well pass by the time it is tried again
+    pub fn transient(&self) -> bool {
+        self.exhausted()
+            && self.attempts.iter().any(|a| {
+                matches!(
+                    a.outcome.error_category(),
+                    Some(ErrorCategory::Network | ErrorCategory::ServerError)
+                )
+            })
+    }
+
+    /// The attempts as one line, e.g. `solver (solver error: timeout); feed (empty response)`
+    pub fn summary(&self) -> String {
+        self.attempts
+            .iter()
+            .map(|a| format!("{} ({})", a.via, a.outcome))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+impl fmt::Display for FallbackReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.site, self.summary())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::site_configs;
+
+    fn csrin() -> SiteConfig {
+        site_configs()
+            .into_iter()
+            .find(|s| s.name == "csrin")
+            .expect("csrin is configured")
+    }
+
+    #[test]
+    fn report_lists_each_strategy_and_why_it_failed() {
+        let site = csrin();
+        let mut report = FallbackReport::new(&site.name);
+        report.record(
+            Provenance::Solver,
+            Outcome::fetch_error(true, &anyhow::anyhow!("flaresolverr http status 500")),
+        );
+        report.record(Provenance::Feed, Outcome::NoMatches(12));
+        report.record(
+            Provenance::Playwright,
+            Outcome::Unavailable("csrin_search.cjs not found".to_string()),
+        );
+        assert!(report.exhausted());
+        assert_eq!(
+            report.to_string(),
+            "csrin: solver (solver error: flaresolverr http status 500); \
+             feed (12 entries, none for the query); \
+             playwright (unavailable: csrin_search.cjs not found)"
+        );
+    }
+
+    #[test]
+    fn challenge_pages_are_called_out() {
+        let html = "<html><head><title>Just a moment...</title></head><body></body></html>";
+        let outcome = Outcome::of_page(&csrin(), html, "elden", 0);
+        assert_eq!(
+            outcome.to_string(),
+            "result selector matched nothing, page looks like a Cloudflare challenge"
+        );
+        assert_eq!(
+            Outcome::of_page(&csrin(), "", "elden", 0),
+            Outcome::EmptyResponse
+        );
+    }
+
+    #[test]
+    fn plain_misses_and_successes_are_not_reported() {
+        let site = csrin();
+        let mut report = FallbackReport::new(&site.name);
+        assert!(!report.exhausted());
+        report.record(
+            Provenance::Html,
+            Outcome::of_page(&site, "<html><body></body></html>", "elden", 0),
+        );
+        // One page that parsed fine but had no matches is just "no results"
+        assert!(!report.exhausted());
+        report.record(Provenance::Feed, Outcome::Found(2));
+        assert!(!report.exhausted());
+    }
+
+    #[test]
+    fn network_and_server_errors_are_worth_another_try() {
+        let failed = |outcome: Outcome| {
+            let mut report = FallbackReport::new("repacks");
+            report.record(Provenance::Html, outcome);
+            report
+        };
+        let dns =
+            anyhow::anyhow!("Request failed: error sending request for url (https://a.example/)");
+        assert!(failed(Outcome::fetch_error(false, &dns)).transient());
+        let status = anyhow::anyhow!("Unexpected status: 500 Internal Server Error");
+        assert!(failed(Outcome::fetch_error(false, &status)).transient());
+        // A missing page or a challenge stays the same when tried again
+        assert!(!failed(Outcome::EmptyResponse).transient());
+        let denied = anyhow::anyhow!("403 Forbidden");
+        assert!(!failed(Outcome::fetch_error(true, &denied)).transient());
+    }
+}
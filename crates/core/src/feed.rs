@@ -0,0 +1,365 @@
+//! Atom and RSS feeds as a search source.
+//!
+//! Used by [`SearchKind::Feed`](crate::models::SearchKind::Feed) sites and as a
+//! fallback for sites whose search pages come back empty (cs.rin.ru).
+
+use crate::debug_dump::DebugDir;
+use crate::fallback::{FallbackReport, Outcome};
+use crate::fetcher;
+use crate::models::{Provenance, SearchResult, SiteConfig};
+use crate::parser::{QueryVariants, fetched_now, lowercase_into, stamp_source};
+use crate::query::build_search_url;
+use crate::rate_limiter::RateLimiter;
+use quick_xml::Reader;
+use quick_xml::events::Event;
+use reqwest::{Client, Url};
+use scraper::{Html, Selector};
+use std::borrow::Cow;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// One `<entry>` (Atom) or `<item>` (RSS) of a feed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeedEntry {
+    pub title: String,
+    pub link: String,
+}
+
+#[derive(Clone, Copy)]
+enum Field {
+    Title,
+    Link,
+}
+
+/// Append the text carried by a text, CDATA or entity event to `buf`
+pub(crate) fn push_text(buf: &mut String, event: &Event<'_>) {
+    match event {
+        Event::Text(t) => {
+            if let Ok(text) = t.xml_content() {
+                buf.push_str(&text);
+            }
+        }
+        Event::CData(t) => {
+            if let Ok(text) = t.decode() {
+                buf.push_str(&text);
+            }
+        }
+        Event::GeneralRef(r) => {
+            if let Ok(name) = r.decode()
+                && let Ok(text) = quick_xml::escape::unescape(&format!("&{name};"))
+            {
+                buf.push_str(&text);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The feed XML in `body`; some servers wrap it in an HTML `<pre>` with
+/// escaped markup
+fn unwrap_feed(body: &str) -> Cow<'_, str> {
+    let start = body.trim_start();
+    if ["<?xml", "<feed", "<rss", "<rdf"]
+        .iter()
+        .any(|p| start.starts_with(p))
+    {
+        return Cow::Borrowed(body);
+    }
+    let doc = Html::parse_document(body);
+    Selector::parse("pre")
+        .ok()
+        .and_then(|sel| doc.select(&sel).next().map(|pre| pre.text().collect()))
+        .map_or(Cow::Borrowed(body), Cow::Owned)
+}
+
+/// Entries of an Atom, RSS 2.0 or RSS 1.0 feed, in feed order.
+///
+/// Entries without a title or link are skipped; malformed XML ends the feed
+/// at the last complete entry.
+pub fn parse_feed(body: &str) -> Vec<FeedEntry> {
+    let xml = unwrap_feed(body);
+    let mut reader = Reader::from_str(&xml);
+    let mut entries = Vec::new();
+    let mut current: Option<FeedEntry> = None;
+    let mut field: Option<Field> = None;
+    while let Ok(event) = reader.read_event() {
+        match &event {
+            Event::Start(e) | Event::Empty(e) => {
+                let is_start = matches!(event, Event::Start(_));
+                match e.local_name().as_ref() {
+                    b"entry" | b"item" if is_start => current = Some(FeedEntry::default()),
+                    b"title" if is_start && current.is_some() => field = Some(Field::Title),
+                    b"link" if current.is_some() => {
+                        // Atom: <link rel="alternate" href="..."/>; RSS: <link>url</link>
+                        let attr = |name: &[u8]| {
+                            e.try_get_attribute(name)
+                                .ok()
+                                .flatten()
+                                .and_then(|a| a.unescape_value().ok().map(Cow::into_owned))
+                        };
+                        let rel = attr(b"rel");
+                        if let Some(href) = attr(b"href") {
+                            if rel.as_deref().is_none_or(|r| r == "alternate")
+                                && let Some(entry) = current.as_mut()
+                                && entry.link.is_empty()
+                            {
+                                entry.link = href;
+                            }
+                        } else if is_start {
+                            field = Some(Field::Link);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::End(e) => match e.local_name().as_ref() {
+                b"entry" | b"item" => {
+                    if let Some(mut entry) = current.take() {
+                        entry.title = entry.title.trim().to_string();
+                        entry.link = entry.link.trim().to_string();
+                        if !entry.title.is_empty() && !entry.link.is_empty() {
+                            entries.push(entry);
+                        }
+                    }
+                    field = None;
+                }
+                b"title" | b"link" => field = None,
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {
+                if let (Some(entry), Some(field)) = (current.as_mut(), field) {
+                    let buf = match field {
+                        Field::Title => &mut entry.title,
+                        Field::Link => &mut entry.link,
+                    };
+                    push_text(buf, &event);
+                }
+            }
+        }
+    }
+    entries
+}
+
+/// Results for `query` from the feed in `body`, fetched from `feed_url`.
+///
+/// Relative links are resolved against `feed_url`; an entry is kept when its
+/// title or link contains the query.
+pub fn feed_results(
+    site: &SiteConfig,
+    body: &str,
+    query: &str,
+    feed_url: &str,
+) -> Vec<SearchResult> {
+    let base = Url::parse(feed_url).ok();
+    let site_name: Arc<str> = Arc::from(site.name.as_str());
+    let variants = QueryVariants::new(query);
+    let (mut tl, mut ul) = (String::new(), String::new());
+    parse_feed(body)
+        .into_iter()
+        .filter_map(|entry| {
+            let url = match &base {
+                Some(base) => base.join(&entry.link).ok()?.to_string(),
+                None => entry.link,
+            };
+            lowercase_into(&mut tl, &entry.title);
+            lowercase_into(&mut ul, &url);
+            variants.matches(&tl, &ul).then(|| SearchResult {
+                site: site_name.clone(),
+                title: entry.title,
+                url,
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// Topics of a phpBB forum's feed (`feed.php?f=10`, cs.rin.ru's games forum)
+/// matching `query`, after the site's rules; at most 50.
+///
+/// The request waits for the site's turn in `rate_limiter` but doesn't hold
+/// the limiter while it runs, so the feed and other fetches don't queue
+/// behind each other. With `debug` the feed is saved as a debug sample.
+pub async fn fetch_forum_feed(
+    client: &Client,
+    site: &SiteConfig,
+    query: &str,
+    rate_limiter: Option<&Mutex<RateLimiter>>,
+    debug: bool,
+) -> Result<Vec<SearchResult>, Outcome> {
+    let feed_url = Url::parse(&site.base_url)
+        .and_then(|base| base.join("feed.php?f=10"))
+        .map_err(|e| Outcome::Unavailable(e.to_string()))?;
+    let feed_url = feed_url.as_str();
+    if let Some(limiter) = rate_limiter {
+        limiter
+            .lock()
+            .await
+            .wait_for_site(&site.name)
+            .await
+            .map_err(|e| Outcome::Unavailable(format!("rate limit: {e}")))?;
+    }
+    // Never through the solver, which gets csrin's feed blacklisted
+    let body = fetcher::fetch_with_retry(client, feed_url, None, Some(&site.name))
+        .await
+        .map_err(|e| Outcome::fetch_error(false, &e))?;
+    if body.is_empty() {
+        return Err(Outcome::EmptyResponse);
+    }
+    if debug {
+        let _ = DebugDir::default().write("csrin_feed", "xml", &body);
+    }
+    let mut results = feed_results(site, &body, query, feed_url);
+    site.rules.apply(&mut results);
+    results.truncate(50);
+    if results.is_empty() {
+        return Err(Outcome::NoMatches(parse_feed(&body).len()));
+    }
+    Ok(results)
+}
+
+/// `search` raced against `feed` ([`fetch_forum_feed`]), which is cheap but
+/// only lists recent topics. The first to find anything wins and the other is
+/// dropped (a running Playwright browser with it); if one comes up empty the
+/// other is awaited.
+///
+/// The feed is polled first, so it takes its turn at the rate limiter before
+/// the search holds it for a fetch.
+pub async fn race_feed<S, F>(
+    site: &SiteConfig,
+    query: &str,
+    search: S,
+    feed: F,
+) -> (Vec<SearchResult>, FallbackReport)
+where
+    S: Future<Output = (Vec<SearchResult>, FallbackReport)>,
+    F: Future<Output = Result<Vec<SearchResult>, Outcome>>,
+{
+    tokio::pin!(search, feed);
+    let stamp = |mut results: Vec<SearchResult>| {
+        let url = build_search_url(site, query);
+        stamp_source(&mut results, &url, fetched_now(), Provenance::Feed);
+        results
+    };
+    tokio::select! {
+        biased;
+        fed = &mut feed => match fed {
+            Ok(found) => {
+                let mut report = FallbackReport::new(&site.name);
+                report.record(Provenance::Feed, Outcome::Found(found.len()));
+                (stamp(found), report)
+            }
+            Err(outcome) => {
+                let (results, mut report) = search.await;
+                report.record(Provenance::Feed, outcome);
+                (results, report)
+            }
+        },
+        searched = &mut search => {
+            if !searched.0.is_empty() {
+                return searched;
+            }
+            let (results, mut report) = searched;
+            match feed.await {
+                Ok(found) => {
+                    report.record(Provenance::Feed, Outcome::Found(found.len()));
+                    (stamp(found), report)
+                }
+                Err(outcome) => {
+                    report.record(Provenance::Feed, outcome);
+                    (results, report)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::site_configs;
+
+    const ATOM: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>CS.RIN.RU</title>
+  <link href="https://cs.rin.ru/forum/feed.php" rel="self"/>
+  <entry>
+    <title type="html"><![CDATA[Main Forum • Elden Ring: Shadow of the Erdtree]]></title>
+    <link rel="alternate" href="./viewtopic.php?f=10&amp;t=1"/>
+  </entry>
+  <entry>
+    <title>Hollow Knight &amp; Silksong</title>
+    <link href="https://cs.rin.ru/forum/viewtopic.php?f=10&amp;t=2"/>
+  </entry>
+  <entry><title></title><link href="./viewtopic.php?t=3"/></entry>
+</feed>"#;
+
+    const RSS: &str = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel>
+  <title>Repacks</title>
+  <link>https://example.com/</link>
+  <item><title>Elden Ring Deluxe</title><link>https://example.com/elden-ring/</link></item>
+  <item><title>Cyberpunk 2077</title><link>https://example.com/cyberpunk/</link></item>
+</channel></rss>"#;
+
+    fn csrin() -> SiteConfig {
+        site_configs()
+            .into_iter()
+            .find(|s| s.name == "csrin")
+            .expect("csrin is configured")
+    }
+
+    #[test]
+    fn parses_atom_entries() {
+        let entries = parse_feed(ATOM);
+        assert_eq!(
+            entries,
+            [
+                FeedEntry {
+                    title: "Main Forum • Elden Ring: Shadow of the Erdtree".to_string(),
+                    link: "./viewtopic.php?f=10&t=1".to_string(),
+                },
+                FeedEntry {
+                    title: "Hollow Knight & Silksong".to_string(),
+                    link: "https://cs.rin.ru/forum/viewtopic.php?f=10&t=2".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_rss_items_but_not_the_channel() {
+        let entries = parse_feed(RSS);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].title, "Elden Ring Deluxe");
+        assert_eq!(entries[0].link, "https://example.com/elden-ring/");
+    }
+
+    #[test]
+    fn unwraps_feeds_served_inside_pre() {
+        let escaped = ATOM
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        let body = format!("<html><body><pre>{escaped}</pre></body></html>");
+        assert_eq!(parse_feed(&body), parse_feed(ATOM));
+    }
+
+    #[test]
+    fn results_resolve_links_and_match_the_query() {
+        let results = feed_results(
+            &csrin(),
+            ATOM,
+            "elden ring",
+            "https://cs.rin.ru/forum/feed.php?f=10",
+        );
+        assert_eq!(results.len(), 1);
+        assert_eq!(&*results[0].site, "csrin");
+        assert_eq!(
+            results[0].url,
+            "https://cs.rin.ru/forum/viewtopic.php?f=10&t=1"
+        );
+        assert!(parse_feed("<feed><entry><title>unclosed").is_empty());
+    }
+}
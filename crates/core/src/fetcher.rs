@@ -1,8 +1,12 @@
 use std::time::Duration;
 
 use crate::anti_detection::AntiDetectionConfig;
+use crate::form;
+use crate::graphql;
+use crate::models::{SearchKind, SiteConfig};
 use crate::monitoring::get_metrics;
 use crate::rate_limiter::RateLimiter;
+use crate::session;
 use anyhow::{Context, Result};
 use reqwest::{Client, StatusCode, header::HeaderMap};
 use tokio::time::sleep;
@@ -20,8 +24,31 @@ pub fn get_anti_detection_config() -> &'static AntiDetectionConfig {
     })
 }
 
+/// Send every request with the same user agent and headers instead of rotating
+/// them, so repeated runs ask sites for the same thing. Only has an effect
+/// before the first client is built.
+pub fn use_fixed_identity() {
+    let _ = ANTI_DETECTION.set(AntiDetectionConfig::new());
+}
+
 /// Build HTTP client with rotating user agent from anti-detection module
 pub fn build_http_client() -> Client {
+    client_builder()
+        .build()
+        .expect("failed to build reqwest client")
+}
+
+/// [`build_http_client`] that sends every request through `proxy`, e.g.
+/// `socks5h://127.0.0.1:9050` for Tor
+pub fn build_proxied_client(proxy: &str) -> Result<Client> {
+    let proxy = reqwest::Proxy::all(proxy).context("invalid proxy URL")?;
+    client_builder()
+        .proxy(proxy)
+        .build()
+        .context("failed to build proxied client")
+}
+
+fn client_builder() -> reqwest::ClientBuilder {
     let ua = get_anti_detection_config().get_user_agent();
     Client::builder()
         .user_agent(ua)
@@ -32,17 +59,92 @@ pub fn build_http_client() -> Client {
         .pool_idle_timeout(Duration::from_secs(30))
         .pool_max_idle_per_host(2)
         .timeout(Duration::from_secs(15))
-        .build()
-        .expect("failed to build reqwest client")
 }
 
-#[instrument(skip(client, rate_limiter))]
+/// GET `url`, retrying server errors and rate limits. 403, 404 and redirects
+/// give an empty body. Goes through the active recording or replay, if any.
 pub async fn fetch_with_retry(
     client: &Client,
     url: &str,
-    mut rate_limiter: Option<&mut RateLimiter>,
+    rate_limiter: Option<&mut RateLimiter>,
     site_name: Option<&str>,
 ) -> Result<String> {
+    let live = async {
+        let fetched = fetch_live(client, url, rate_limiter, site_name).await?;
+        Ok(fetched.body)
+    };
+    session::fetch_through(url, live).await
+}
+
+/// A fetched page and the URL it was fetched from after redirects
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fetched {
+    pub url: String,
+    pub body: String,
+}
+
+impl Fetched {
+    /// No body, as for a 403, 404 or unfollowed redirect of `url`
+    pub fn empty(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            body: String::new(),
+        }
+    }
+}
+
+/// [`fetch_with_retry`] (or [`fetch_with_retry_headers`] with `headers`) that
+/// also returns the URL the page came from after redirects. Replayed pages
+/// keep the URL they were asked for.
+pub async fn fetch_with_final_url(
+    client: &Client,
+    url: &str,
+    headers: Option<HeaderMap>,
+    rate_limiter: Option<&mut RateLimiter>,
+    site_name: Option<&str>,
+) -> Result<Fetched> {
+    let mut final_url = url.to_string();
+    let live = async {
+        let fetched = match headers {
+            Some(headers) => {
+                fetch_live_with_headers(client, url, Some(headers), rate_limiter, site_name).await?
+            }
+            None => fetch_live(client, url, rate_limiter, site_name).await?,
+        };
+        final_url = fetched.url;
+        Ok(fetched.body)
+    };
+    let body = session::fetch_through(url, live).await?;
+    Ok(Fetched {
+        url: final_url,
+        body,
+    })
+}
+
+/// [`fetch_with_final_url`] for a page of `site`, except that the search
+/// forms of [`SearchKind::PostForm`] sites are posted (see [`crate::form`]),
+/// as are the queries of [`SearchKind::GraphQL`] ones (see [`crate::graphql`])
+pub async fn fetch_site_page(
+    client: &Client,
+    site: &SiteConfig,
+    url: &str,
+    headers: Option<HeaderMap>,
+    rate_limiter: Option<&mut RateLimiter>,
+) -> Result<Fetched> {
+    match site.search_kind {
+        SearchKind::PostForm => form::post_search(client, site, url, headers, rate_limiter).await,
+        SearchKind::GraphQL => graphql::post_search(client, site, url, headers, rate_limiter).await,
+        _ => fetch_with_final_url(client, url, headers, rate_limiter, Some(&site.name)).await,
+    }
+}
+
+#[instrument(skip(client, rate_limiter))]
+async fn fetch_live(
+    client: &Client,
+    url: &str,
+    mut rate_limiter: Option<&mut RateLimiter>,
+    site_name: Option<&str>,
+) -> Result<Fetched> {
     let site = site_name.unwrap_or("unknown");
     let mut attempt: u32 = 0;
     let max_attempts: u32 = 3;
@@ -80,13 +182,17 @@ pub async fn fetch_with_retry(
 
                 match status {
                     StatusCode::OK => {
+                        let final_url = r.url().to_string();
                         let body = r.text().await.context("Failed to read response body")?;
                         debug!(
                             site = site,
                             body_length = body.len(),
                             "Successfully fetched body"
                         );
-                        return Ok(body);
+                        return Ok(Fetched {
+                            url: final_url,
+                            body,
+                        });
                     }
                     StatusCode::TOO_MANY_REQUESTS => {
                         warn!(site = site, "Rate limited (429), backing off");
@@ -98,12 +204,12 @@ pub async fn fetch_with_retry(
                     StatusCode::FORBIDDEN | StatusCode::UNAUTHORIZED => {
                         warn!(site = site, status = status.as_u16(), "Access denied");
                         // Return empty string for access denied errors
-                        return Ok(String::new());
+                        return Ok(Fetched::empty(url));
                     }
                     StatusCode::NOT_FOUND => {
                         debug!(site = site, "Resource not found (404)");
                         // Return empty string for not found errors
-                        return Ok(String::new());
+                        return Ok(Fetched::empty(url));
                     }
                     StatusCode::SERVICE_UNAVAILABLE
                     | StatusCode::BAD_GATEWAY
@@ -126,7 +232,7 @@ pub async fn fetch_with_retry(
                                 status = status.as_u16(),
                                 "Redirection received"
                             );
-                            return Ok(String::new());
+                            return Ok(Fetched::empty(url));
                         }
                         warn!(site = site, status = status.as_u16(), "Unexpected status");
                         last_err = Some(anyhow::anyhow!("Unexpected status: {}", status));
@@ -157,13 +263,29 @@ pub async fn fetch_with_retry(
     Err(last_err.unwrap_or_else(|| anyhow::anyhow!("unknown error fetching {}", url)))
 }
 
+/// [`fetch_with_retry`] with extra request headers, e.g. a forwarded Cookie
 pub async fn fetch_with_retry_headers(
     client: &Client,
     url: &str,
     headers: Option<HeaderMap>,
-    mut rate_limiter: Option<&mut RateLimiter>,
+    rate_limiter: Option<&mut RateLimiter>,
     site_name: Option<&str>,
 ) -> Result<String> {
+    let live = async {
+        let fetched =
+            fetch_live_with_headers(client, url, headers, rate_limiter, site_name).await?;
+        Ok(fetched.body)
+    };
+    session::fetch_through(url, live).await
+}
+
+async fn fetch_live_with_headers(
+    client: &Client,
+    url: &str,
+    headers: Option<HeaderMap>,
+    mut rate_limiter: Option<&mut RateLimiter>,
+    site_name: Option<&str>,
+) -> Result<Fetched> {
     let site = site_name.unwrap_or("unknown");
     let mut attempt: u32 = 0;
     let max_attempts: u32 = 3;
@@ -197,13 +319,17 @@ pub async fn fetch_with_retry_headers(
 
                 match status {
                     StatusCode::OK => {
+                        let final_url = r.url().to_string();
                         let body = r.text().await.context("Failed to read response body")?;
                         debug!(
                             site = site,
                             body_length = body.len(),
                             "Successfully fetched body"
                         );
-                        return Ok(body);
+                        return Ok(Fetched {
+                            url: final_url,
+                            body,
+                        });
                     }
                     StatusCode::TOO_MANY_REQUESTS => {
                         warn!(site = site, "Rate limited (429), backing off");
@@ -215,12 +341,12 @@ pub async fn fetch_with_retry_headers(
                     StatusCode::FORBIDDEN | StatusCode::UNAUTHORIZED => {
                         warn!(site = site, status = status.as_u16(), "Access denied");
                         // Return empty string for access denied errors
-                        return Ok(String::new());
+                        return Ok(Fetched::empty(url));
                     }
                     StatusCode::NOT_FOUND => {
                         debug!(site = site, "Resource not found (404)");
                         // Return empty string for not found errors
-                        return Ok(String::new());
+                        return Ok(Fetched::empty(url));
                     }
                     StatusCode::SERVICE_UNAVAILABLE
                     | StatusCode::BAD_GATEWAY
@@ -243,7 +369,7 @@ pub async fn fetch_with_retry_headers(
                                 status = status.as_u16(),
                                 "Redirection received"
                             );
-                            return Ok(String::new());
+                            return Ok(Fetched::empty(url));
                         }
                         warn!(site = site, status = status.as_u16(), "Unexpected status");
                         last_err = Some(anyhow::anyhow!("Unexpected status: {}", status));
@@ -313,6 +439,36 @@ mod tests {
         assert_eq!(body, "");
     }
 
+    #[tokio::test]
+    async fn fetch_with_final_url_reports_where_redirects_led() {
+        let mut server = Server::new_async().await;
+        let _moved = server
+            .mock("GET", "/search")
+            .with_status(301)
+            .with_header("location", "/?s=elden")
+            .create_async()
+            .await;
+        let _page = server
+            .mock("GET", "/")
+            .match_query(mockito::Matcher::UrlEncoded("s".into(), "elden".into()))
+            .with_status(200)
+            .with_body("results")
+            .create_async()
+            .await;
+        let client = build_http_client();
+        let fetched = fetch_with_final_url(
+            &client,
+            &format!("{}/search", server.url()),
+            None,
+            None,
+            Some("test"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(fetched.url, format!("{}/?s=elden", server.url()));
+        assert_eq!(fetched.body, "results");
+    }
+
     #[tokio::test]
     async fn fetch_forbidden_returns_empty() {
         let mut server = Server::new_async().await;
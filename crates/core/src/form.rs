@@ -0,0 +1,251 @@
+//! Sites whose search is a form posted with POST
+//! ([`SearchKind::PostForm`](crate::models::SearchKind::PostForm)), such as
+//! phpBB's advanced search or some XenForo forums.
+//!
+//! ```toml
+//! [sites.forum]
+//! base_url = "https://forum.example/"
+//! search_kind = "PostForm"
+//! query_param = "keywords"                          # the query's field
+//! search_params = [["sr", "topics"], ["fid[]", "10"]] # the other fields
+//! result_selector = "a.topictitle"
+//!
+//! [sites.forum.form]
+//! action = "search.php"
+//! token_page = "search.php"
+//! ```
+//!
+//! The site's search URL ([`build_search_url`](crate::query::build_search_url))
+//! is `action` with the fields as its query string: it names the search in the
+//! page cache, recordings and each result's `source_url`, and [`post_search`]
+//! posts its fields as the form's body. A form guarded by a CSRF token gets
+//! `token_page` fetched first: the hidden fields of the form on it that holds
+//! the query field (phpBB's `form_token` and `creation_time`, XenForo's
+//! `_xfToken`) are posted along, with the cookies that page set.
+
+use anyhow::{Context, Result, bail};
+use reqwest::header::{COOKIE, HeaderMap, HeaderValue};
+use reqwest::{Client, StatusCode, Url};
+use scraper::{Html, Selector};
+
+use crate::auth::AuthSession;
+use crate::fetcher::Fetched;
+use crate::models::SiteConfig;
+use crate::monitoring::get_metrics;
+use crate::rate_limiter::RateLimiter;
+use crate::session;
+
+/// `path` of a site at `base`: `base` itself when empty, as it is when absolute
+pub fn resolve(base: &str, path: &str) -> String {
+    if path.is_empty() {
+        return base.to_string();
+    }
+    match Url::parse(base).and_then(|base| base.join(path)) {
+        Ok(url) => url.into(),
+        Err(_) => format!(
+            "{}/{}",
+            base.trim_end_matches('/'),
+            path.trim_start_matches('/')
+        ),
+    }
+}
+
+/// The URL a search URL posts to and the fields it posts, in order; a query
+/// string `action` has is posted too
+fn split(url: &str) -> Result<(String, Vec<(String, String)>)> {
+    let mut action = Url::parse(url).with_context(|| format!("form URL {url:?}"))?;
+    let fields = action.query_pairs().into_owned().collect();
+    action.set_query(None);
+    Ok((action.into(), fields))
+}
+
+/// Hidden fields of the form on `html` that holds `field`, else of its first
+/// form posting with POST
+pub fn hidden_fields(html: &str, field: &str) -> Vec<(String, String)> {
+    let doc = Html::parse_document(html);
+    let (Ok(forms), Ok(named), Ok(hidden)) = (
+        Selector::parse("form"),
+        Selector::parse("[name]"),
+        Selector::parse("input[type='hidden'][name]"),
+    ) else {
+        return Vec::new();
+    };
+    let holds_field = |f: &scraper::ElementRef| {
+        f.select(&named)
+            .any(|input| input.value().attr("name") == Some(field))
+    };
+    let posts = |f: &scraper::ElementRef| {
+        f.value()
+            .attr("method")
+            .is_some_and(|m| m.eq_ignore_ascii_case("post"))
+    };
+    let form = doc
+        .select(&forms)
+        .find(holds_field)
+        .or_else(|| doc.select(&forms).find(posts));
+    form.map(|f| {
+        f.select(&hidden)
+            .filter_map(|input| {
+                let name = input.value().attr("name")?;
+                let value = input.value().attr("value").unwrap_or_default();
+                Some((name.to_string(), value.to_string()))
+            })
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Post the search form named by `url` (a [`build_search_url`] of `site`),
+/// fetching its token page first if the site has one. `headers` go with both
+/// requests; the returned URL is the results page the post led to.
+///
+/// [`build_search_url`]: crate::query::build_search_url
+pub async fn post_search(
+    client: &Client,
+    site: &SiteConfig,
+    url: &str,
+    headers: Option<HeaderMap>,
+    rate_limiter: Option<&mut RateLimiter>,
+) -> Result<Fetched> {
+    let mut final_url = url.to_string();
+    let live = async {
+        let fetched =
+            post_live(client, site, url, headers.unwrap_or_default(), rate_limiter).await?;
+        final_url = fetched.url;
+        Ok(fetched.body)
+    };
+    // Tokens differ on every visit, so recordings are keyed by the fields asked for
+    let body = session::fetch_through(&format!("POST {url}"), live).await?;
+    Ok(Fetched {
+        url: final_url,
+        body,
+    })
+}
+
+async fn post_live(
+    client: &Client,
+    site: &SiteConfig,
+    url: &str,
+    mut headers: HeaderMap,
+    mut rate_limiter: Option<&mut RateLimiter>,
+) -> Result<Fetched> {
+    let (action, mut fields) = split(url)?;
+    let cookie = headers.remove(COOKIE);
+    let mut jar = cookie
+        .and_then(|c| AuthSession::from_cookie(c.to_str().ok()?).ok())
+        .unwrap_or_default();
+    let with_cookies = |headers: &HeaderMap, jar: &AuthSession| {
+        let mut headers = headers.clone();
+        if let Ok(value) = HeaderValue::from_str(&jar.header())
+            && !jar.cookies.is_empty()
+        {
+            headers.insert(COOKIE, value);
+        }
+        headers
+    };
+
+    if let Some(page) = site.form.as_ref().and_then(|f| f.token_page.as_deref()) {
+        let page = resolve(&site.base_url, page);
+        if let Some(limiter) = rate_limiter.as_mut() {
+            limiter.wait_for_site(&site.name).await?;
+        }
+        let response = client
+            .get(&page)
+            .headers(with_cookies(&headers, &jar))
+            .send()
+            .await
+            .with_context(|| format!("fetch the search form at {page}"))?;
+        jar.absorb(response.headers());
+        let html = response.text().await?;
+        let query_field = site.query_param.as_deref().unwrap_or("keywords");
+        let ours: Vec<String> = fields.iter().map(|(name, _)| name.clone()).collect();
+        let tokens = hidden_fields(&html, query_field);
+        // The site's own fields win over the form's defaults
+        let tokens = tokens.into_iter().filter(|(name, _)| !ours.contains(name));
+        fields = tokens.chain(fields).collect();
+    }
+
+    if let Some(limiter) = rate_limiter.as_mut() {
+        limiter.wait_for_site(&site.name).await?;
+    }
+    let started = std::time::Instant::now();
+    let response = client
+        .post(&action)
+        .headers(with_cookies(&headers, &jar))
+        .form(&fields)
+        .send()
+        .await;
+    get_metrics()
+        .record_request(&site.name, started.elapsed(), response.is_ok())
+        .await;
+    let response = response.context("post the search form")?;
+    match response.status() {
+        StatusCode::OK => Ok(Fetched {
+            url: response.url().to_string(),
+            body: response.text().await?,
+        }),
+        // As for fetched pages: access denied and missing pages have no results
+        StatusCode::FORBIDDEN | StatusCode::UNAUTHORIZED | StatusCode::NOT_FOUND => {
+            Ok(Fetched::empty(url))
+        }
+        status => bail!("search form answered {status}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{FormConfig, SearchKind};
+    use crate::query::build_search_url;
+
+    #[tokio::test]
+    async fn the_form_is_posted_with_its_token_and_cookies() {
+        let mut server = mockito::Server::new_async().await;
+        let page = server
+            .mock("GET", "/search.php")
+            .with_header("set-cookie", "phpbb_sid=s1; path=/")
+            .with_body(
+                r#"<form id="login" method="post"><input type="hidden" name="sid" value="x"></form>
+                <form action="./search.php?mode=results" method="post">
+                <input type="text" name="keywords">
+                <input type="hidden" name="form_token" value="tok">
+                <input type="hidden" name="sr" value="posts"></form>"#,
+            )
+            .create_async()
+            .await;
+        let post = server
+            .mock("POST", "/search.php")
+            .match_query(mockito::Matcher::Missing)
+            .match_header("cookie", "phpbb_sid=s1; theme=dark")
+            .match_body("form_token=tok&keywords=elden+ring&sr=topics")
+            .with_body(r#"<a class="topictitle" href="./viewtopic.php?t=1">Elden Ring</a>"#)
+            .create_async()
+            .await;
+
+        let forum = SiteConfig {
+            name: "forum".into(),
+            base_url: format!("{}/", server.url()),
+            search_kind: SearchKind::PostForm,
+            search_params: vec![("sr".into(), "topics".into())],
+            result_selector: "a.topictitle".into(),
+            form: Some(FormConfig {
+                action: "search.php".into(),
+                token_page: Some("search.php".into()),
+            }),
+            ..Default::default()
+        };
+        let url = build_search_url(&forum, "elden ring");
+        assert_eq!(
+            url,
+            format!("{}/search.php?keywords=elden+ring&sr=topics", server.url())
+        );
+        let mut headers = HeaderMap::new();
+        headers.insert(COOKIE, HeaderValue::from_static("theme=dark"));
+        let fetched = post_search(&Client::new(), &forum, &url, Some(headers), None)
+            .await
+            .unwrap();
+        assert!(fetched.body.contains("Elden Ring"));
+        page.assert_async().await;
+        post.assert_async().await;
+    }
+}
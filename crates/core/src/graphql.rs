@@ -0,0 +1,339 @@
+//! Sites searched through a GraphQL API
+//! ([`SearchKind::GraphQL`](crate::models::SearchKind::GraphQL)), as some game
+//! indexes built as single-page apps only offer.
+//!
+//! ```toml
+//! [sites.index]
+//! base_url = "https://index.example/"
+//! search_kind = "GraphQL"
+//!
+//! [sites.index.graphql]
+//! endpoint = "api/graphql"
+//! query = "query Search($q: String!) { search(term: $q) { items { name slug cover } } }"
+//! operation_name = "Search"
+//! variables = { q = "{query}", first = 20 }
+//! persisted_hash = "9c0f…"           # Apollo's persisted query, if the API wants one
+//! results = "/data/search/items"     # JSON pointers (RFC 6901)
+//! title = "/name"
+//! url = "/slug"
+//! image = "/cover"
+//! ```
+//!
+//! The site's search URL ([`build_search_url`](crate::query::build_search_url))
+//! is the endpoint, with `search_params` as its query string, and the search as
+//! its fragment; it names the search in the page cache, recordings and each
+//! result's `source_url`. [`post_search`] posts the query with `{query}` in the
+//! variables' strings replaced by the search. A site with `persisted_hash`
+//! sends only the hash at first, and the query document too once the server
+//! answers that it doesn't know the hash. [`results`] reads the answer.
+
+use anyhow::{Context, Result, bail};
+use reqwest::header::HeaderMap;
+use reqwest::{Client, StatusCode, Url};
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+use crate::fetcher::Fetched;
+use crate::form;
+use crate::models::{GraphQLConfig, SearchResult, SiteConfig};
+use crate::monitoring::get_metrics;
+use crate::query::with_query;
+use crate::rate_limiter::RateLimiter;
+use crate::session;
+
+/// The search URL of `site` for `query` (see the module docs)
+pub fn search_url(site: &SiteConfig, query: &str) -> String {
+    let endpoint = site
+        .graphql
+        .as_ref()
+        .map(|g| g.endpoint.as_str())
+        .filter(|e| !e.is_empty())
+        .unwrap_or("graphql");
+    let params: Vec<(&str, &str)> = site
+        .search_params
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    let endpoint = with_query(&form::resolve(&site.base_url, endpoint), &params);
+    format!("{endpoint}#{}", urlencoding::encode(query))
+}
+
+/// The endpoint a search URL posts to and the search it names
+fn split(url: &str) -> Result<(String, String)> {
+    let mut endpoint = Url::parse(url).with_context(|| format!("GraphQL URL {url:?}"))?;
+    let search = endpoint.fragment().unwrap_or_default();
+    let search = urlencoding::decode(search)?.into_owned();
+    endpoint.set_fragment(None);
+    Ok((endpoint.into(), search))
+}
+
+/// `variables` with `{query}` replaced by `search` in every string
+fn substitute(variables: &Value, search: &str) -> Value {
+    match variables {
+        Value::String(s) => Value::String(s.replace("{query}", search)),
+        Value::Array(items) => Value::Array(items.iter().map(|v| substitute(v, search)).collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(k, v)| (k.clone(), substitute(v, search)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Whether the server answered that it doesn't know a persisted query's hash
+fn not_persisted(answer: &Value) -> bool {
+    let errors = answer["errors"].as_array().map(Vec::as_slice);
+    errors.unwrap_or_default().iter().any(|e| {
+        e["message"] == "PersistedQueryNotFound"
+            || e["extensions"]["code"] == "PERSISTED_QUERY_NOT_FOUND"
+    })
+}
+
+/// Post the search named by `url` (a [`build_search_url`] of `site`) to its
+/// endpoint; the returned body is the JSON answer.
+///
+/// [`build_search_url`]: crate::query::build_search_url
+pub async fn post_search(
+    client: &Client,
+    site: &SiteConfig,
+    url: &str,
+    headers: Option<HeaderMap>,
+    rate_limiter: Option<&mut RateLimiter>,
+) -> Result<Fetched> {
+    let live = post_live(client, site, url, headers.unwrap_or_default(), rate_limiter);
+    let body = session::fetch_through(&format!("POST {url}"), live).await?;
+    Ok(Fetched {
+        url: url.to_string(),
+        body,
+    })
+}
+
+async fn post_live(
+    client: &Client,
+    site: &SiteConfig,
+    url: &str,
+    headers: HeaderMap,
+    mut rate_limiter: Option<&mut RateLimiter>,
+) -> Result<String> {
+    let Some(config) = site.graphql.as_ref() else {
+        bail!("site '{}' has no [graphql] table", site.name);
+    };
+    let (endpoint, search) = split(url)?;
+    let variables = match &config.variables {
+        Value::Null => json!({ "query": search }),
+        variables => substitute(variables, &search),
+    };
+    let mut request = json!({ "variables": variables });
+    if let Some(name) = &config.operation_name {
+        request["operationName"] = json!(name);
+    }
+    match &config.persisted_hash {
+        Some(hash) => {
+            request["extensions"] =
+                json!({ "persistedQuery": { "version": 1, "sha256Hash": hash } });
+        }
+        None => request["query"] = json!(config.query),
+    }
+
+    let limiter = rate_limiter.as_deref_mut();
+    let mut answer = send(client, site, &endpoint, &headers, &request, limiter).await?;
+    if let Some((_, json)) = &answer
+        && not_persisted(json)
+        && !config.query.is_empty()
+    {
+        // Sending the document along has the server persist it under the hash
+        request["query"] = json!(config.query);
+        answer = send(client, site, &endpoint, &headers, &request, rate_limiter).await?;
+    }
+    let Some((body, json)) = answer else {
+        return Ok(String::new());
+    };
+    if json["data"].is_null()
+        && let Some(error) = json["errors"].get(0)
+    {
+        let message = error["message"].as_str().unwrap_or("unknown error");
+        bail!("GraphQL search failed: {message}");
+    }
+    Ok(body)
+}
+
+/// The answer to one request, as text and as JSON; `None` when access was
+/// denied or the endpoint is missing, which as for fetched pages means no
+/// results
+async fn send(
+    client: &Client,
+    site: &SiteConfig,
+    endpoint: &str,
+    headers: &HeaderMap,
+    request: &Value,
+    rate_limiter: Option<&mut RateLimiter>,
+) -> Result<Option<(String, Value)>> {
+    if let Some(limiter) = rate_limiter {
+        limiter.wait_for_site(&site.name).await?;
+    }
+    let started = std::time::Instant::now();
+    let response = client
+        .post(endpoint)
+        .headers(headers.clone())
+        .json(request)
+        .send()
+        .await;
+    get_metrics()
+        .record_request(&site.name, started.elapsed(), response.is_ok())
+        .await;
+    let response = response.context("post the GraphQL search")?;
+    match response.status() {
+        // GraphQL servers answer errors with 400 and a JSON body too
+        StatusCode::OK | StatusCode::BAD_REQUEST => {}
+        StatusCode::FORBIDDEN | StatusCode::UNAUTHORIZED | StatusCode::NOT_FOUND => {
+            return Ok(None);
+        }
+        status => bail!("GraphQL endpoint answered {status}"),
+    }
+    let body = response.text().await?;
+    let json = serde_json::from_str(&body).context("GraphQL answer is not JSON")?;
+    Ok(Some((body, json)))
+}
+
+/// A string or number at `pointer` in `value`, trimmed; `None` if missing or empty
+fn text(value: &Value, pointer: &str) -> Option<String> {
+    let text = match value.pointer(pointer)? {
+        Value::String(s) => s.trim().to_string(),
+        Value::Number(n) => n.to_string(),
+        _ => return None,
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+/// Results in the JSON answer `body` of a [`post_search`] of `site`
+pub fn results(site: &SiteConfig, body: &str) -> Vec<SearchResult> {
+    let default = GraphQLConfig::default();
+    let config = site.graphql.as_ref().unwrap_or(&default);
+    let Ok(json) = serde_json::from_str::<Value>(body) else {
+        return Vec::new();
+    };
+    let Some(items) = json.pointer(&config.results).and_then(Value::as_array) else {
+        return Vec::new();
+    };
+    let or = |pointer: &'static str, set: &str| {
+        if set.is_empty() {
+            pointer.to_string()
+        } else {
+            set.to_string()
+        }
+    };
+    let (title, url) = (or("/title", &config.title), or("/url", &config.url));
+    let base = Url::parse(&site.base_url).ok();
+    let absolute = |link: String| match &base {
+        Some(base) => base.join(&link).ok().map(String::from),
+        None => Some(link),
+    };
+    let site_name: Arc<str> = Arc::from(site.name.as_str());
+    let mut out: Vec<SearchResult> = items
+        .iter()
+        .filter_map(|item| {
+            Some(SearchResult {
+                site: site_name.clone(),
+                title: text(item, &title)?,
+                url: absolute(text(item, &url)?)?,
+                image: config
+                    .image
+                    .as_deref()
+                    .and_then(|p| text(item, p))
+                    .and_then(absolute),
+                ..Default::default()
+            })
+        })
+        .collect();
+    site.rules.apply(&mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SearchKind;
+    use crate::query::build_search_url;
+    use mockito::Matcher;
+
+    #[tokio::test]
+    async fn persisted_queries_are_sent_by_hash_then_with_their_document() {
+        let mut server = mockito::Server::new_async().await;
+        let document = "query Search($q: String!) { search(term: $q) { items { name slug } } }";
+        let by_hash = server
+            .mock("POST", "/api/graphql")
+            .match_query(Matcher::UrlEncoded("lang".into(), "en".into()))
+            .match_body(Matcher::PartialJson(json!({
+                "operationName": "Search",
+                "variables": { "q": "elden ring", "first": 20, "tags": ["game elden ring"] },
+                "extensions": { "persistedQuery": { "version": 1, "sha256Hash": "abc123" } },
+            })))
+            .with_body(r#"{"errors":[{"message":"PersistedQueryNotFound"}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let with_document = server
+            .mock("POST", "/api/graphql")
+            .match_query(Matcher::UrlEncoded("lang".into(), "en".into()))
+            .match_body(Matcher::PartialJson(json!({ "query": document })))
+            .with_body(
+                r#"{"data":{"search":{"items":[
+                    {"name":"Elden Ring","slug":"/games/elden-ring"},
+                    {"name":"","slug":"/games/untitled"},
+                    {"name":"Elden Ring Nightreign","slug":"https://cdn.example/nightreign"}
+                ]}}}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let index = SiteConfig {
+            name: "index".into(),
+            base_url: format!("{}/", server.url()),
+            search_kind: SearchKind::GraphQL,
+            search_params: vec![("lang".into(), "en".into())],
+            graphql: Some(GraphQLConfig {
+                endpoint: "api/graphql".into(),
+                query: document.into(),
+                operation_name: Some("Search".into()),
+                variables: json!({ "q": "{query}", "first": 20, "tags": ["game {query}"] }),
+                persisted_hash: Some("abc123".into()),
+                results: "/data/search/items".into(),
+                title: "/name".into(),
+                url: "/slug".into(),
+                image: None,
+            }),
+            ..Default::default()
+        };
+        let url = build_search_url(&index, "elden ring");
+        assert_eq!(
+            url,
+            format!("{}/api/graphql?lang=en#elden%20ring", server.url())
+        );
+        let fetched = post_search(&Client::new(), &index, &url, None, None)
+            .await
+            .unwrap();
+        by_hash.assert_async().await;
+        with_document.assert_async().await;
+
+        let found: Vec<(String, String)> = results(&index, &fetched.body)
+            .into_iter()
+            .map(|r| (r.title, r.url))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    "Elden Ring".to_string(),
+                    format!("{}/games/elden-ring", server.url())
+                ),
+                (
+                    "Elden Ring Nightreign".to_string(),
+                    "https://cdn.example/nightreign".to_string()
+                ),
+            ]
+        );
+    }
+}
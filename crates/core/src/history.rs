@@ -0,0 +1,210 @@
+//! Search history: one entry per search run, kept apart from the result cache.
+//!
+//! The cache holds a handful of recent result sets for instant reloads; the
+//! history only records what was searched, when, where and how much was found.
+
+use crate::models::SearchResult;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Oldest entries are dropped beyond this many
+pub const MAX_HISTORY: usize = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub id: u64,
+    pub query: String,
+    /// Unix timestamp when the search was run
+    pub timestamp: u64,
+    /// Sites that were searched; empty means all sites
+    pub sites: Vec<String>,
+    pub result_count: usize,
+    /// Result count per site that returned anything
+    pub site_counts: BTreeMap<String, usize>,
+}
+
+/// One page of history, newest first
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct HistoryPage {
+    pub entries: Vec<HistoryEntry>,
+    /// Number of entries across all pages
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchHistory {
+    entries: Vec<HistoryEntry>,
+    next_id: u64,
+}
+
+impl SearchHistory {
+    /// Load history, starting empty if the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid history file {:?}: {}", path, e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Record a finished search and return its id
+    pub fn record(&mut self, query: &str, sites: Vec<String>, results: &[SearchResult]) -> u64 {
+        let mut site_counts = BTreeMap::new();
+        for r in results {
+            *site_counts.entry(r.site.to_string()).or_insert(0) += 1;
+        }
+        self.next_id += 1;
+        self.entries.push(HistoryEntry {
+            id: self.next_id,
+            query: query.to_string(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            sites,
+            result_count: results.len(),
+            site_counts,
+        });
+        if self.entries.len() > MAX_HISTORY {
+            let excess = self.entries.len() - MAX_HISTORY;
+            self.entries.drain(..excess);
+        }
+        self.next_id
+    }
+
+    /// Entries of the zero-based `page`, newest first
+    pub fn page(&self, page: usize, page_size: usize) -> HistoryPage {
+        let page_size = page_size.max(1);
+        HistoryPage {
+            entries: self
+                .entries
+                .iter()
+                .rev()
+                .skip(page.saturating_mul(page_size))
+                .take(page_size)
+                .cloned()
+                .collect(),
+            total: self.entries.len(),
+            page,
+            page_size,
+        }
+    }
+
+    /// Remove one entry; returns false if there was none with `id`
+    pub fn remove(&mut self, id: u64) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.id != id);
+        self.entries.len() != before
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn result(site: &str) -> SearchResult {
+        SearchResult {
+            site: site.into(),
+            title: "Elden Ring".into(),
+            url: format!("https://{site}.example/elden-ring"),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn record_counts_results_per_site() {
+        let mut history = SearchHistory::default();
+        let id = history.record(
+            "elden ring",
+            vec!["fitgirl".into(), "dodi".into()],
+            &[result("fitgirl"), result("fitgirl"), result("dodi")],
+        );
+        let entry = &history.page(0, 10).entries[0];
+        assert_eq!(entry.id, id);
+        assert_eq!(entry.result_count, 3);
+        assert_eq!(entry.site_counts["fitgirl"], 2);
+        assert_eq!(entry.site_counts["dodi"], 1);
+    }
+
+    #[test]
+    fn pages_are_newest_first() {
+        let mut history = SearchHistory::default();
+        for q in ["a", "b", "c", "d", "e"] {
+            history.record(q, vec![], &[]);
+        }
+        let first = history.page(0, 2);
+        assert_eq!(first.total, 5);
+        let queries: Vec<&str> = first.entries.iter().map(|e| e.query.as_str()).collect();
+        assert_eq!(queries, ["e", "d"]);
+        assert_eq!(history.page(2, 2).entries[0].query, "a");
+        assert!(history.page(3, 2).entries.is_empty());
+    }
+
+    #[test]
+    fn remove_and_clear_keep_ids_unique() {
+        let mut history = SearchHistory::default();
+        let first = history.record("a", vec![], &[]);
+        let second = history.record("b", vec![], &[]);
+        assert!(history.remove(first));
+        assert!(!history.remove(first));
+        assert_eq!(history.len(), 1);
+
+        history.clear();
+        assert!(history.is_empty());
+        assert!(history.record("c", vec![], &[]) > second);
+    }
+
+    #[test]
+    fn oldest_entries_are_dropped_past_the_limit() {
+        let mut history = SearchHistory::default();
+        for i in 0..MAX_HISTORY + 3 {
+            history.record(&i.to_string(), vec![], &[]);
+        }
+        assert_eq!(history.len(), MAX_HISTORY);
+        let oldest = history.page(MAX_HISTORY - 1, 1).entries[0].query.clone();
+        assert_eq!(oldest, "3");
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("cache").join("search_history.json");
+        assert!(SearchHistory::load(&path).is_empty());
+
+        let mut history = SearchHistory::default();
+        history.record("hades", vec!["fitgirl".into()], &[result("fitgirl")]);
+        history.save(&path).unwrap();
+
+        let loaded = SearchHistory::load(&path);
+        assert_eq!(loaded.page(0, 10), history.page(0, 10));
+        std::fs::write(&path, "not json").unwrap();
+        assert!(SearchHistory::load(&path).is_empty());
+    }
+}
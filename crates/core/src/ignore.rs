@@ -0,0 +1,266 @@
+//! Ignore list: results the user never wants to see again.
+//!
+//! Kept in `ignore.json` in the data directory and shared by the CLI and the
+//! GUI. A rule matches an exact result URL, every result hosted on a domain
+//! (subdomains included) or titles matching a case-insensitive regex. The list
+//! is applied after every search, cached results included.
+
+use crate::cookies::domain_matches;
+use crate::models::SearchResult;
+use anyhow::{Result, bail};
+use regex::{Regex, RegexBuilder};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IgnoreKind {
+    /// One result URL, compared exactly
+    Url,
+    /// Every URL on a host or its subdomains
+    Domain,
+    /// Titles matching a regex, ignoring case
+    Title,
+}
+
+impl IgnoreKind {
+    pub const ALL: [IgnoreKind; 3] = [Self::Url, Self::Domain, Self::Title];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Url => "url",
+            Self::Domain => "domain",
+            Self::Title => "title",
+        }
+    }
+}
+
+impl fmt::Display for IgnoreKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for IgnoreKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!("unknown ignore rule {s:?} (expected url, domain or title)"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IgnoreRule {
+    pub kind: IgnoreKind,
+    pub value: String,
+}
+
+impl IgnoreRule {
+    /// Validate and normalize a rule: domains are lowercased (a URL may be
+    /// given for its host) and title regexes must compile
+    pub fn new(kind: IgnoreKind, value: &str) -> Result<Self> {
+        let value = value.trim();
+        if value.is_empty() {
+            bail!("empty {kind} rule");
+        }
+        let value = match kind {
+            IgnoreKind::Url => value.to_string(),
+            IgnoreKind::Domain => normalize_domain(value),
+            IgnoreKind::Title => {
+                title_regex(value)?;
+                value.to_string()
+            }
+        };
+        Ok(Self { kind, value })
+    }
+}
+
+impl fmt::Display for IgnoreRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.kind, self.value)
+    }
+}
+
+fn normalize_domain(value: &str) -> String {
+    let host = Url::parse(value)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_else(|| value.to_string());
+    host.trim_end_matches('.').to_lowercase()
+}
+
+fn title_regex(pattern: &str) -> Result<Regex> {
+    Ok(RegexBuilder::new(pattern).case_insensitive(true).build()?)
+}
+
+/// Ignore rules in the order they were added
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IgnoreList {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreList {
+    /// Load the list, starting empty if the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid ignore list {:?}: {}", path, e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Add a rule; returns false if the same rule is already listed
+    pub fn add(&mut self, rule: IgnoreRule) -> bool {
+        if self.rules.contains(&rule) {
+            return false;
+        }
+        self.rules.push(rule);
+        true
+    }
+
+    /// Remove a rule; returns false if it was not listed
+    pub fn remove(&mut self, rule: &IgnoreRule) -> bool {
+        let before = self.rules.len();
+        self.rules.retain(|r| r != rule);
+        self.rules.len() != before
+    }
+
+    pub fn rules(&self) -> &[IgnoreRule] {
+        &self.rules
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Drop every result a rule matches; returns how many were dropped.
+    /// Title rules that no longer compile (edited by hand) are skipped.
+    pub fn apply(&self, results: &mut Vec<SearchResult>) -> usize {
+        if self.rules.is_empty() {
+            return 0;
+        }
+        let titles: Vec<Regex> = self
+            .rules
+            .iter()
+            .filter(|r| r.kind == IgnoreKind::Title)
+            .filter_map(|r| match title_regex(&r.value) {
+                Ok(re) => Some(re),
+                Err(e) => {
+                    tracing::warn!("Skipping ignore rule {:?}: {}", r.value, e);
+                    None
+                }
+            })
+            .collect();
+        let before = results.len();
+        results.retain(|result| {
+            let host = Url::parse(&result.url)
+                .ok()
+                .and_then(|u| u.host_str().map(str::to_string));
+            let ignored = self.rules.iter().any(|rule| match rule.kind {
+                IgnoreKind::Url => result.url.trim() == rule.value,
+                IgnoreKind::Domain => host
+                    .as_deref()
+                    .is_some_and(|host| domain_matches(&rule.value, host)),
+                IgnoreKind::Title => false,
+            }) || titles.iter().any(|re| re.is_match(&result.title));
+            !ignored
+        });
+        before - results.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn result(title: &str, url: &str) -> SearchResult {
+        SearchResult {
+            site: "fitgirl".into(),
+            title: title.into(),
+            url: url.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rules_are_validated_and_normalized() {
+        let domain = IgnoreRule::new(IgnoreKind::Domain, " https://Spam.Example/x ").unwrap();
+        assert_eq!(domain.value, "spam.example");
+        assert_eq!(
+            IgnoreRule::new(IgnoreKind::Domain, "Spam.Example.")
+                .unwrap()
+                .value,
+            "spam.example"
+        );
+        assert!(IgnoreRule::new(IgnoreKind::Title, "(unclosed").is_err());
+        assert!(IgnoreRule::new(IgnoreKind::Url, "  ").is_err());
+        assert_eq!("Domain".parse::<IgnoreKind>(), Ok(IgnoreKind::Domain));
+        assert!("site".parse::<IgnoreKind>().is_err());
+    }
+
+    #[test]
+    fn list_round_trips_without_duplicates() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("ignore.json");
+        let mut list = IgnoreList::default();
+        let rule = IgnoreRule::new(IgnoreKind::Title, "soundtrack").unwrap();
+        assert!(list.add(rule.clone()));
+        assert!(!list.add(rule.clone()));
+        list.save(&path).unwrap();
+
+        let mut loaded = IgnoreList::load(&path);
+        assert_eq!(loaded.rules(), std::slice::from_ref(&rule));
+        assert!(loaded.remove(&rule));
+        assert!(!loaded.remove(&rule));
+        assert!(IgnoreList::load(&dir.path().join("missing.json")).is_empty());
+    }
+
+    #[test]
+    fn apply_drops_matching_urls_domains_and_titles() {
+        let mut list = IgnoreList::default();
+        for (kind, value) in [
+            (IgnoreKind::Url, "https://a.example/elden-ring-fake/"),
+            (IgnoreKind::Domain, "spam.example"),
+            (IgnoreKind::Title, r"\bOST\b"),
+        ] {
+            list.add(IgnoreRule::new(kind, value).unwrap());
+        }
+        let mut results = vec![
+            result("Elden Ring", "https://a.example/elden-ring/"),
+            result("Elden Ring", "https://a.example/elden-ring-fake/"),
+            result("Elden Ring", "https://mirror.spam.example/elden-ring/"),
+            result("Elden Ring Ost", "https://a.example/elden-ring-ost/"),
+            result("Hollow Knight", "https://notspam.example/hk/"),
+        ];
+        assert_eq!(list.apply(&mut results), 3);
+        let urls: Vec<_> = results.iter().map(|r| r.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://a.example/elden-ring/",
+                "https://notspam.example/hk/"
+            ]
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! Link patterns learned for sites whose result selector stopped matching.
+//!
+//! When a site's selector finds nothing, the parser falls back to scanning
+//! every link on the page for the query. The links that scan keeps usually
+//! share a URL prefix and sit in elements of the same class, so that pattern
+//! is saved per site in `learned_patterns.json` in the state directory and
+//! tried before the full scan on the next search. It keeps out the menu and
+//! tag links a full scan lets through. `patterns list` and `patterns clear`
+//! show and forget them.
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::paths;
+
+/// Where a result link points and what it sits in
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AnchorPattern {
+    /// Scheme, host and first path segment, e.g. `https://a.example/game/`
+    pub href_prefix: String,
+    /// First class of the link's parent element, if it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_class: Option<String>,
+}
+
+impl AnchorPattern {
+    /// The pattern of a link to the absolute `url` inside an element of
+    /// `parent_class` (its whole class attribute). A URL with a single path
+    /// segment (the post slug) only keeps its origin.
+    pub fn of(url: &str, parent_class: Option<&str>) -> Option<Self> {
+        let parsed = Url::parse(url).ok()?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return None;
+        }
+        let segments: Vec<&str> = parsed
+            .path_segments()
+            .map(|s| s.filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+        let origin = parsed.origin().ascii_serialization();
+        let href_prefix = match segments.as_slice() {
+            [first, _, ..] => format!("{origin}/{first}/"),
+            _ => format!("{origin}/"),
+        };
+        Some(Self {
+            href_prefix,
+            parent_class: parent_class
+                .and_then(|c| c.split_whitespace().next())
+                .map(str::to_string),
+        })
+    }
+
+    /// Whether a link to `url` inside an element of `parent_class` fits
+    pub fn matches(&self, url: &str, parent_class: Option<&str>) -> bool {
+        url.starts_with(&self.href_prefix)
+            && self.parent_class.as_deref().is_none_or(|class| {
+                parent_class.is_some_and(|c| c.split_whitespace().any(|c| c == class))
+            })
+    }
+}
+
+impl fmt::Display for AnchorPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}*", self.href_prefix)?;
+        if let Some(class) = &self.parent_class {
+            write!(f, " in .{class}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A site's learned pattern and how often it found results
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LearnedPattern {
+    #[serde(flatten)]
+    pub pattern: AnchorPattern,
+    /// Pages it found results on
+    pub hits: u32,
+    /// Unix timestamp (seconds) of the last of them
+    pub last_used: u64,
+}
+
+/// Learned patterns by lowercased site name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LearnedPatterns {
+    sites: BTreeMap<String, LearnedPattern>,
+}
+
+impl LearnedPatterns {
+    /// Load the patterns, starting empty if the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid learned patterns {:?}: {}", path, e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        paths::write_atomic(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn get(&self, site: &str) -> Option<&AnchorPattern> {
+        self.sites.get(&site.to_lowercase()).map(|l| &l.pattern)
+    }
+
+    /// Record that `pattern` found results on `site`, replacing a different
+    /// pattern learned before
+    pub fn learn(&mut self, site: &str, pattern: AnchorPattern) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let learned = self
+            .sites
+            .entry(site.to_lowercase())
+            .or_insert_with(|| LearnedPattern {
+                pattern: pattern.clone(),
+                hits: 0,
+                last_used: now,
+            });
+        if learned.pattern != pattern {
+            learned.pattern = pattern;
+            learned.hits = 0;
+        }
+        learned.hits += 1;
+        learned.last_used = now;
+    }
+
+    /// Forget the pattern of `site`; returns false if it had none
+    pub fn forget(&mut self, site: &str) -> bool {
+        self.sites.remove(&site.to_lowercase()).is_some()
+    }
+
+    /// Forget every pattern; returns how many there were
+    pub fn clear(&mut self) -> usize {
+        let count = self.sites.len();
+        self.sites.clear();
+        count
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &LearnedPattern)> {
+        self.sites.iter().map(|(site, l)| (site.as_str(), l))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sites.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn patterns_keep_the_first_path_segment_and_class() {
+        let pattern =
+            AnchorPattern::of("https://a.example/game/elden-ring", Some("card big")).unwrap();
+        assert_eq!(pattern.href_prefix, "https://a.example/game/");
+        assert_eq!(pattern.parent_class.as_deref(), Some("card"));
+        assert_eq!(pattern.to_string(), "https://a.example/game/* in .card");
+        assert!(pattern.matches("https://a.example/game/hades", Some("wide card")));
+        assert!(!pattern.matches("https://a.example/tag/elden-ring", Some("card")));
+        assert!(!pattern.matches("https://a.example/game/hades", None));
+
+        let slug = AnchorPattern::of("https://a.example/elden-ring-free/", None).unwrap();
+        assert_eq!(slug.href_prefix, "https://a.example/");
+        assert!(slug.matches("https://a.example/hades/", Some("menu")));
+        assert!(AnchorPattern::of("magnet:?xt=urn:btih:abc", None).is_none());
+    }
+
+    #[test]
+    fn learning_counts_hits_and_round_trips() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("learned_patterns.json");
+        let game = AnchorPattern::of("https://a.example/game/x", Some("card")).unwrap();
+        let post = AnchorPattern::of("https://a.example/post/x", None).unwrap();
+
+        let mut learned = LearnedPatterns::default();
+        learned.learn("GOG-Games", game.clone());
+        learned.learn("gog-games", game.clone());
+        assert_eq!(learned.iter().next().unwrap().1.hits, 2);
+        learned.learn("gog-games", post.clone());
+        let (site, entry) = learned.iter().next().unwrap();
+        assert_eq!((site, entry.hits), ("gog-games", 1));
+        learned.learn("fitgirl", game);
+        learned.save(&path).unwrap();
+
+        let mut loaded = LearnedPatterns::load(&path);
+        assert_eq!(loaded, learned);
+        assert_eq!(loaded.get("Gog-Games"), Some(&post));
+        assert!(loaded.forget("fitgirl"));
+        assert!(!loaded.forget("fitgirl"));
+        assert_eq!(loaded.clear(), 1);
+        assert!(loaded.is_empty());
+        assert!(LearnedPatterns::load(&dir.path().join("missing.json")).is_empty());
+    }
+}
@@ -1,14 +1,56 @@
 pub mod analyzer;
 pub mod anti_detection;
+pub mod auth;
+pub mod browser;
+pub mod budget;
 pub mod cache;
+pub mod canonical;
+pub mod capability;
 pub mod cf;
+pub mod cfemail;
 pub mod config;
+pub mod cookies;
+pub mod crawl;
+pub mod debug_dump;
+pub mod details;
+pub mod diff;
+pub mod error;
+pub mod extract;
+pub mod fallback;
+pub mod feed;
 pub mod fetcher;
+pub mod form;
+pub mod graphql;
+pub mod history;
+pub mod ignore;
+pub mod learned;
+pub mod limits;
+pub mod liveness;
 pub mod models;
 pub mod monitoring;
+pub mod notes;
 pub mod output;
+pub mod page_store;
+pub mod pagination;
 pub mod parser;
+pub mod paths;
+pub mod profile;
 pub mod query;
 pub mod query_parser;
 pub mod rate_limiter;
+pub mod render_cache;
 pub mod resilience;
+pub mod result_index;
+pub mod route;
+pub mod rules;
+pub mod sanitize;
+pub mod secrets;
+pub mod session;
+pub mod settings;
+pub mod sinks;
+pub mod sitemap;
+pub mod state_archive;
+pub mod steam;
+pub mod stream;
+pub mod template;
+pub mod thumbnails;
@@ -0,0 +1,218 @@
+//! Capping combined results so no single site monopolizes the output.
+//!
+//! The per-site limit is applied while each site is searched; the caps here
+//! run afterwards on the combined, sorted results.
+
+use crate::models::SearchResult;
+use crate::query_parser::MultiQuery;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Caps on the combined results of one search (0 = no cap)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResultCaps {
+    /// Maximum results across all sites
+    pub total: usize,
+    /// Maximum results per `|`-separated query segment
+    pub per_segment: usize,
+}
+
+impl ResultCaps {
+    /// Apply the per-segment cap, then the total cap
+    pub fn apply(&self, results: Vec<SearchResult>, multi_query: &MultiQuery) -> Vec<SearchResult> {
+        let results = cap_per_segment(results, multi_query, self.per_segment);
+        round_robin_truncate(results, self.total)
+    }
+}
+
+/// Keep at most `total` results (0 = all), taking one from each site in turn.
+///
+/// Each site gives up its best-ranked results first. The kept results stay in
+/// their input order, so a sorted list stays sorted.
+pub fn round_robin_truncate(results: Vec<SearchResult>, total: usize) -> Vec<SearchResult> {
+    if total == 0 || results.len() <= total {
+        return results;
+    }
+    let keep = round_robin_pick(&results, (0..results.len()).collect(), total);
+    retain_indices(results, &keep)
+}
+
+/// Keep at most `per_segment` results (0 = all) for each segment of a multi-query.
+///
+/// A result counts toward the segment whose search found it (see
+/// [`SearchResult::segment`]), whether or not its title has that segment's
+/// terms; within a segment sites take turns as in [`round_robin_truncate`].
+/// Results without a segment are kept.
+pub fn cap_per_segment(
+    results: Vec<SearchResult>,
+    multi_query: &MultiQuery,
+    per_segment: usize,
+) -> Vec<SearchResult> {
+    if per_segment == 0 || multi_query.is_single() {
+        return results;
+    }
+    let mut by_segment: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut keep = vec![false; results.len()];
+    for (i, r) in results.iter().enumerate() {
+        match r.segment {
+            Some(seg) => by_segment.entry(seg).or_default().push(i),
+            None => keep[i] = true,
+        }
+    }
+    for indices in by_segment.into_values() {
+        let picked = round_robin_pick(&results, indices, per_segment);
+        for (k, p) in keep.iter_mut().zip(picked) {
+            *k |= p;
+        }
+    }
+    retain_indices(results, &keep)
+}
+
+/// Pick `count` of the `candidates` (indices into `results`) one site at a time,
+/// in order of each site's first appearance. Returns a keep-mask over `results`.
+fn round_robin_pick(results: &[SearchResult], candidates: Vec<usize>, count: usize) -> Vec<bool> {
+    let mut sites: Vec<Arc<str>> = Vec::new();
+    let mut queues: HashMap<Arc<str>, Vec<usize>> = HashMap::new();
+    for i in candidates {
+        let site = &results[i].site;
+        if !queues.contains_key(site) {
+            sites.push(site.clone());
+        }
+        queues.entry(site.clone()).or_default().push(i);
+    }
+    // Best-ranked first; unranked results keep their input order at the end
+    for queue in queues.values_mut() {
+        queue.sort_by_key(|&i| (results[i].rank.is_none(), results[i].rank, i));
+        queue.reverse();
+    }
+
+    let mut keep = vec![false; results.len()];
+    let mut taken = 0;
+    while taken < count {
+        let mut progressed = false;
+        for site in &sites {
+            if taken == count {
+                break;
+            }
+            if let Some(i) = queues.get_mut(site).and_then(Vec::pop) {
+                keep[i] = true;
+                taken += 1;
+                progressed = true;
+            }
+        }
+        if !progressed {
+            break;
+        }
+    }
+    keep
+}
+
+fn retain_indices(results: Vec<SearchResult>, keep: &[bool]) -> Vec<SearchResult> {
+    results
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(r, &k)| k.then_some(r))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(site: &str, title: &str, rank: usize) -> SearchResult {
+        SearchResult {
+            site: site.into(),
+            title: title.to_string(),
+            url: format!("https://{site}.example/{}", title.replace(' ', "-")),
+            rank: Some(rank),
+            ..Default::default()
+        }
+    }
+
+    fn found_by(segment: usize, mut result: SearchResult) -> SearchResult {
+        result.segment = Some(segment);
+        result
+    }
+
+    fn titles(results: &[SearchResult]) -> Vec<&str> {
+        results.iter().map(|r| r.title.as_str()).collect()
+    }
+
+    #[test]
+    fn total_cap_takes_turns_between_sites() {
+        // Sorted by site then title: the first site alone would fill the cap
+        let results = vec![
+            result("dodi", "Elden Ring A", 3),
+            result("dodi", "Elden Ring B", 1),
+            result("dodi", "Elden Ring C", 2),
+            result("dodi", "Elden Ring D", 4),
+            result("fitgirl", "Elden Ring E", 1),
+            result("steamrip", "Elden Ring F", 2),
+            result("steamrip", "Elden Ring G", 1),
+        ];
+        let capped = round_robin_truncate(results, 5);
+        // Two rounds: dodi B, fitgirl E, steamrip G, then dodi C, steamrip F
+        assert_eq!(
+            titles(&capped),
+            [
+                "Elden Ring B",
+                "Elden Ring C",
+                "Elden Ring E",
+                "Elden Ring F",
+                "Elden Ring G"
+            ]
+        );
+    }
+
+    #[test]
+    fn zero_or_large_total_keeps_everything() {
+        let results = vec![result("dodi", "A", 1), result("fitgirl", "B", 1)];
+        assert_eq!(round_robin_truncate(results.clone(), 0).len(), 2);
+        assert_eq!(round_robin_truncate(results, 5).len(), 2);
+    }
+
+    #[test]
+    fn segment_cap_applies_to_each_segment_separately() {
+        let mq = MultiQuery::parse("elden ring | minecraft");
+        let results = vec![
+            found_by(0, result("dodi", "Elden Ring 1", 1)),
+            found_by(0, result("dodi", "Elden Ring 2", 2)),
+            found_by(1, result("dodi", "Minecraft 1", 3)),
+            found_by(0, result("fitgirl", "Elden Ring 3", 1)),
+            found_by(1, result("fitgirl", "Minecraft 2", 2)),
+            found_by(1, result("fitgirl", "Minecraft 3", 3)),
+        ];
+        let capped = ResultCaps {
+            total: 0,
+            per_segment: 2,
+        }
+        .apply(results, &mq);
+        assert_eq!(
+            titles(&capped),
+            ["Elden Ring 1", "Minecraft 1", "Elden Ring 3", "Minecraft 2"]
+        );
+    }
+
+    #[test]
+    fn segment_cap_counts_results_without_the_segment_terms() {
+        // Sites answer "gta 5" with titles that share none of its words
+        let mq = MultiQuery::parse("gta 5 | minecraft");
+        let results = vec![
+            found_by(0, result("dodi", "Grand Theft Auto V", 1)),
+            found_by(0, result("dodi", "Grand Theft Auto V Premium", 2)),
+            found_by(0, result("fitgirl", "Grand Theft Auto V Enhanced", 1)),
+            found_by(1, result("fitgirl", "Minecraft", 2)),
+            result("steamrip", "Untagged", 1),
+        ];
+        let capped = cap_per_segment(results, &mq, 2);
+        assert_eq!(
+            titles(&capped),
+            [
+                "Grand Theft Auto V",
+                "Grand Theft Auto V Enhanced",
+                "Minecraft",
+                "Untagged"
+            ]
+        );
+    }
+}
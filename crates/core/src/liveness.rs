@@ -0,0 +1,244 @@
+//! Liveness checks for result URLs, used to prune dead links from old searches.
+//!
+//! Each URL gets one GET request; only the status and where redirects ended
+//! matter, so the body is never read. Sites often take a removed post down by
+//! redirecting it to the home page, which counts as dead too.
+
+use futures::stream::{self, StreamExt};
+use reqwest::{Client, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::models::SearchResult;
+
+/// URLs checked at once
+const CONCURRENCY: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkState {
+    /// The page answered
+    Alive,
+    /// 404/410, or redirected to the site's home page
+    Dead,
+    /// Refused by the site (403, 429, a Cloudflare challenge), so it may still exist
+    Blocked,
+    /// No answer, or a server error
+    Unreachable,
+}
+
+impl LinkState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Alive => "alive",
+            Self::Dead => "dead",
+            Self::Blocked => "blocked",
+            Self::Unreachable => "unreachable",
+        }
+    }
+}
+
+impl fmt::Display for LinkState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Outcome of checking one result URL
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkCheck {
+    pub state: LinkState,
+    /// HTTP status of the final response, if there was one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Unix timestamp (seconds) of the check
+    pub checked_at: u64,
+}
+
+/// How many results of each state a check found
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct LinkSummary {
+    pub alive: usize,
+    pub dead: usize,
+    pub blocked: usize,
+    pub unreachable: usize,
+    /// Magnet links and other URLs that have no page to check
+    pub skipped: usize,
+}
+
+impl LinkSummary {
+    pub fn of(results: &[SearchResult]) -> Self {
+        let mut summary = Self::default();
+        for r in results {
+            match r.link_check.as_ref().map(|c| c.state) {
+                Some(LinkState::Alive) => summary.alive += 1,
+                Some(LinkState::Dead) => summary.dead += 1,
+                Some(LinkState::Blocked) => summary.blocked += 1,
+                Some(LinkState::Unreachable) => summary.unreachable += 1,
+                None => summary.skipped += 1,
+            }
+        }
+        summary
+    }
+}
+
+/// State of `requested` given the final response status and URL
+fn classify(requested: &Url, status: StatusCode, final_url: &Url) -> LinkState {
+    match status {
+        StatusCode::NOT_FOUND | StatusCode::GONE => LinkState::Dead,
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS => {
+            LinkState::Blocked
+        }
+        s if s.is_success() => {
+            let sent_home = final_url.path() == "/"
+                && requested.path() != "/"
+                && final_url.host_str() == requested.host_str();
+            if sent_home {
+                LinkState::Dead
+            } else {
+                LinkState::Alive
+            }
+        }
+        // Cloudflare answers challenges with 503
+        StatusCode::SERVICE_UNAVAILABLE => LinkState::Blocked,
+        _ => LinkState::Unreachable,
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Check one URL; `None` for URLs without a web page (magnets, invalid URLs)
+pub async fn check_url(client: &Client, url: &str) -> Option<LinkCheck> {
+    let requested = Url::parse(url)
+        .ok()
+        .filter(|u| matches!(u.scheme(), "http" | "https"))?;
+    let (state, status) = match client.get(requested.clone()).send().await {
+        Ok(resp) => (
+            classify(&requested, resp.status(), resp.url()),
+            Some(resp.status().as_u16()),
+        ),
+        Err(e) => {
+            tracing::debug!("Link check of {} failed: {}", url, e);
+            (LinkState::Unreachable, None)
+        }
+    };
+    Some(LinkCheck {
+        state,
+        status,
+        checked_at: now_secs(),
+    })
+}
+
+/// Check every result's URL and set its [`SearchResult::link_check`]
+pub async fn check_results(client: &Client, results: &mut [SearchResult]) -> LinkSummary {
+    // Owned URLs keep the stream's future `Send` for spawned searches
+    let urls: Vec<String> = results.iter().map(|r| r.url.clone()).collect();
+    let checks: Vec<(usize, Option<LinkCheck>)> = stream::iter(urls.into_iter().enumerate())
+        .map(|(i, url)| async move { (i, check_url(client, &url).await) })
+        .buffer_unordered(CONCURRENCY)
+        .collect()
+        .await;
+    for (i, check) in checks {
+        results[i].link_check = check;
+    }
+    LinkSummary::of(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(s: &str) -> Url {
+        Url::parse(s).unwrap()
+    }
+
+    #[test]
+    fn statuses_and_redirects_are_classified() {
+        let post = url("https://a.example/elden-ring/");
+        let state = |status: u16, final_url: &str| {
+            classify(
+                &post,
+                StatusCode::from_u16(status).unwrap(),
+                &url(final_url),
+            )
+        };
+        assert_eq!(
+            state(200, "https://a.example/elden-ring/"),
+            LinkState::Alive
+        );
+        assert_eq!(
+            state(200, "https://a.example/elden-ring-v2/"),
+            LinkState::Alive
+        );
+        assert_eq!(state(200, "https://a.example/"), LinkState::Dead);
+        // A moved site's home page is not the post being removed
+        assert_eq!(state(200, "https://b.example/"), LinkState::Alive);
+        assert_eq!(state(404, "https://a.example/elden-ring/"), LinkState::Dead);
+        assert_eq!(state(410, "https://a.example/elden-ring/"), LinkState::Dead);
+        assert_eq!(
+            state(403, "https://a.example/elden-ring/"),
+            LinkState::Blocked
+        );
+        assert_eq!(
+            state(503, "https://a.example/elden-ring/"),
+            LinkState::Blocked
+        );
+        assert_eq!(
+            state(500, "https://a.example/elden-ring/"),
+            LinkState::Unreachable
+        );
+
+        let home = url("https://a.example/");
+        assert_eq!(
+            classify(&home, StatusCode::OK, &home),
+            LinkState::Alive,
+            "a result that is the home page is alive"
+        );
+    }
+
+    #[tokio::test]
+    async fn results_get_checked_and_counted() {
+        let mut server = mockito::Server::new_async().await;
+        let _alive = server
+            .mock("GET", "/alive/")
+            .with_status(200)
+            .create_async()
+            .await;
+        let _gone = server
+            .mock("GET", "/gone/")
+            .with_status(404)
+            .create_async()
+            .await;
+        let result = |url: String| SearchResult {
+            site: "fitgirl".into(),
+            title: "Elden Ring".into(),
+            url,
+            ..Default::default()
+        };
+        let mut results = vec![
+            result(format!("{}/alive/", server.url())),
+            result(format!("{}/gone/", server.url())),
+            result("magnet:?xt=urn:btih:abc".into()),
+        ];
+
+        let summary = check_results(&Client::new(), &mut results).await;
+        assert_eq!(
+            summary,
+            LinkSummary {
+                alive: 1,
+                dead: 1,
+                skipped: 1,
+                ..Default::default()
+            }
+        );
+        let check = results[1].link_check.as_ref().unwrap();
+        assert_eq!((check.state, check.status), (LinkState::Dead, Some(404)));
+        assert!(results[2].link_check.is_none());
+    }
+}
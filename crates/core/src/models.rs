@@ -1,21 +1,134 @@
+use crate::liveness::LinkCheck;
+use crate::notes::Note;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::path::PathBuf;
+use std::sync::Arc;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SearchResult {
-    pub site: String,
+    pub site: Arc<str>,
     pub title: String,
     pub url: String,
+    /// Search or listing page the result was parsed from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    /// 1-based position on `source_url`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rank: Option<usize>,
+    /// Image on the result's card, shown by the GUI through
+    /// [`crate::thumbnails`] rather than loaded from the site
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// Unix timestamp (seconds) of the fetch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetched_at: Option<u64>,
+    /// Which fetch path or fallback produced the result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// Steam AppID of the game the title names, when looked up (`--steam-appids`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub steam_appid: Option<u32>,
+    /// The user's status and note for this URL (see [`crate::notes`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<Note>,
+    /// Whether the URL still answered when last checked (`verify-cache`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link_check: Option<LinkCheck>,
+    /// Index of the `|`-separated query segment whose search found the
+    /// result, for the per-segment cap; not saved
+    #[serde(skip)]
+    pub segment: Option<usize>,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+/// How a result was obtained, so odd results can be traced to the fallback
+/// that produced them
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Provenance {
+    /// Search page fetched directly
+    Html,
+    /// Search page fetched through the Cloudflare solver
+    Solver,
+    /// Page rendered by the Playwright script
+    Playwright,
+    /// Atom or RSS feed
+    Feed,
+    /// AJAX/JSON endpoint (gog-games) or GraphQL API
+    Ajax,
+    /// Sitemap slug match, confirmed on the page
+    Sitemap,
+}
+
+impl Provenance {
+    /// Provenance of a fetched search page of `kind`
+    pub fn of_page(kind: SearchKind, via_solver: bool) -> Self {
+        match kind {
+            SearchKind::Feed => Provenance::Feed,
+            SearchKind::Sitemap => Provenance::Sitemap,
+            SearchKind::GraphQL => Provenance::Ajax,
+            _ if via_solver => Provenance::Solver,
+            _ => Provenance::Html,
+        }
+    }
+
+    /// Name as serialized (`snake_case`)
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Provenance::Html => "html",
+            Provenance::Solver => "solver",
+            Provenance::Playwright => "playwright",
+            Provenance::Feed => "feed",
+            Provenance::Ajax => "ajax",
+            Provenance::Sitemap => "sitemap",
+        }
+    }
+}
+
+impl std::fmt::Display for Provenance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl SearchResult {
+    /// Order of final output: by site, then title; results with equal titles
+    /// keep their order on the site's page, so repeated searches print the same
+    pub fn output_order(a: &Self, b: &Self) -> Ordering {
+        a.site
+            .cmp(&b.site)
+            .then_with(|| a.title.cmp(&b.title))
+            .then_with(|| a.rank.cmp(&b.rank))
+            .then_with(|| a.url.cmp(&b.url))
+    }
+
+    /// Strict order for reproducible output: by site, then position on the
+    /// site's page, then URL; unlike [`Self::output_order`] titles play no part
+    pub fn page_order(a: &Self, b: &Self) -> Ordering {
+        a.site
+            .cmp(&b.site)
+            .then_with(|| a.rank.cmp(&b.rank))
+            .then_with(|| a.url.cmp(&b.url))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub enum SearchKind {
+    #[default]
     QueryParam,
     FrontPage,
     PathEncoded,
     ListingPage,
     /// phpBB forum search with keywords, fid[], sr params (e.g., cs.rin.ru)
     PhpBBSearch,
+    /// Slug matching against the site's sitemap.xml (`listing_path` overrides its URL)
+    Sitemap,
+    /// Atom or RSS feed at `listing_path` (default `<base_url>/feed/`), filtered locally
+    Feed,
+    /// Search form posted with POST, e.g. phpBB's advanced search (see [`crate::form`])
+    PostForm,
+    /// GraphQL API queried with the search (see [`crate::graphql`])
+    GraphQL,
 }
 
 impl From<&str> for SearchKind {
@@ -26,26 +139,202 @@ impl From<&str> for SearchKind {
             "PathEncoded" => SearchKind::PathEncoded,
             "ListingPage" => SearchKind::ListingPage,
             "PhpBBSearch" => SearchKind::PhpBBSearch,
+            "Sitemap" => SearchKind::Sitemap,
+            "Feed" => SearchKind::Feed,
+            "PostForm" => SearchKind::PostForm,
+            "GraphQL" => SearchKind::GraphQL,
             _ => SearchKind::QueryParam, // Default fallback
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// A site from `sites.toml`. [`Default`] is an empty [`SearchKind::QueryParam`]
+/// site, for filling in the rest of a hand-built one
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SiteConfig {
+    /// Defaults to the site's key in `sites.toml`
+    #[serde(default)]
     pub name: String,
     pub base_url: String,
     pub search_kind: SearchKind,
     pub query_param: Option<String>,
+    /// Fixed parameters added to every search URL, in order, e.g. a forum id
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub search_params: Vec<(String, String)>,
     pub listing_path: Option<String>,
-    pub result_selector: String,
+    /// Left out only by [`SearchKind::GraphQL`] sites
+    #[serde(default)]
+    pub result_selector: ResultSelector,
+    /// Where titles are read from (see [`crate::extract`]); the text if unset
+    #[serde(default)]
     pub title_attr: String,
+    /// Where URLs are read from; `href` if unset
+    #[serde(default)]
     pub url_attr: String,
+    #[serde(default)]
     pub requires_js: bool,
+    #[serde(default)]
     pub requires_cloudflare: bool,
+    /// 0 (or unset) takes the `[global]` default, as do the next two
+    #[serde(default)]
     pub timeout_seconds: u64,
+    #[serde(default)]
     pub retry_attempts: u32,
+    #[serde(default)]
     pub rate_limit_delay_ms: u64,
+    /// Archive pages to follow past the front page of listing sites
+    #[serde(default)]
+    pub crawl: Option<CrawlConfig>,
+    /// Where the search form of [`SearchKind::PostForm`] sites is posted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub form: Option<FormConfig>,
+    /// The query and result mapping of [`SearchKind::GraphQL`] sites
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graphql: Option<GraphQLConfig>,
+    /// Which results to keep and how to tidy their titles
+    #[serde(default, skip_serializing_if = "SiteRules::is_empty")]
+    pub rules: SiteRules,
+}
+
+/// CSS selectors for a site's results, tried in order until one finds any,
+/// so a site's old and new theme can both be listed. `sites.toml` takes one
+/// string or a list:
+///
+/// ```toml
+/// result_selector = ["article.game-card a", "h2.entry-title a"]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(from = "OneOrMany", into = "OneOrMany")]
+pub struct ResultSelector(Vec<String>);
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl From<OneOrMany> for ResultSelector {
+    fn from(value: OneOrMany) -> Self {
+        match value {
+            OneOrMany::One(selector) => Self(vec![selector]),
+            OneOrMany::Many(selectors) => Self(selectors),
+        }
+    }
+}
+
+impl From<ResultSelector> for OneOrMany {
+    fn from(mut value: ResultSelector) -> Self {
+        match value.0.len() {
+            1 => OneOrMany::One(value.0.remove(0)),
+            _ => OneOrMany::Many(value.0),
+        }
+    }
+}
+
+impl From<&str> for ResultSelector {
+    fn from(selector: &str) -> Self {
+        Self(vec![selector.to_string()])
+    }
+}
+
+impl From<String> for ResultSelector {
+    fn from(selector: String) -> Self {
+        Self(vec![selector])
+    }
+}
+
+impl<const N: usize> From<[&str; N]> for ResultSelector {
+    fn from(selectors: [&str; N]) -> Self {
+        Self(selectors.iter().map(|s| s.to_string()).collect())
+    }
+}
+
+impl ResultSelector {
+    /// The selectors in the order they are tried
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+
+    /// Whether there is no selector to try
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|s| s.trim().is_empty())
+    }
+}
+
+impl std::fmt::Display for ResultSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0.join(" | "))
+    }
+}
+
+/// How far to crawl a listing site's archive pages (see [`crate::crawl`])
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CrawlConfig {
+    /// Archive pages to follow after the front page
+    pub max_depth: usize,
+    /// Stop once a page holds posts older than this many days
+    #[serde(default)]
+    pub max_age_days: Option<u64>,
+}
+
+/// The search form of a [`SearchKind::PostForm`] site (see [`crate::form`])
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct FormConfig {
+    /// URL the form is posted to, relative to `base_url`; `base_url` if empty
+    pub action: String,
+    /// Page holding the form, relative to `base_url`, fetched first for its
+    /// hidden fields when the form is guarded by a CSRF token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_page: Option<String>,
+}
+
+/// The GraphQL search of a [`SearchKind::GraphQL`] site (see [`crate::graphql`])
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct GraphQLConfig {
+    /// Endpoint, relative to `base_url`; `graphql` if empty
+    pub endpoint: String,
+    /// The query document; may be left out with `persisted_hash`
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub query: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_name: Option<String>,
+    /// Variables; `{query}` in their strings is replaced by the search, and
+    /// none send `{"query": <search>}`
+    #[serde(skip_serializing_if = "serde_json::Value::is_null")]
+    pub variables: serde_json::Value,
+    /// SHA-256 hash of the query the server has persisted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persisted_hash: Option<String>,
+    /// JSON pointer to the list of results in the response
+    pub results: String,
+    /// JSON pointers into each result to its title (`/title` if empty), URL
+    /// (`/url` if empty, and may be relative to `base_url`) and image
+    pub title: String,
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+}
+
+/// Post-processing rules for a site's results (see [`crate::rules`]).
+/// Matching is case-insensitive.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct SiteRules {
+    /// Keep only results whose URL contains one of these
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub url_must_contain: Vec<String>,
+    /// Drop results whose URL contains any of these, e.g. pagination links
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub drop_if_url_contains: Vec<String>,
+    /// Drop results whose title starts with any of these, e.g. teaser links
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub drop_if_title_starts_with: Vec<String>,
+    /// Boilerplate removed from the start of titles, e.g. a forum name
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub title_strip_prefixes: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,7 +367,17 @@ impl SitesConfig {
         Ok(config)
     }
 
+    /// The sites, each named after its key unless it has a `name`
     pub fn get_site_configs(&self) -> Vec<SiteConfig> {
-        self.sites.values().cloned().collect()
+        self.sites
+            .iter()
+            .map(|(key, site)| {
+                let mut site = site.clone();
+                if site.name.trim().is_empty() {
+                    site.name = key.clone();
+                }
+                site
+            })
+            .collect()
     }
 }
@@ -1,10 +1,16 @@
 use metrics::{counter, gauge};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::path::Path;
 use std::sync::Arc;
 use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::{Level, debug, info, span};
 
+use crate::paths;
+
 /// Global metrics collector
 pub static METRICS: OnceLock<Arc<SearchMetrics>> = OnceLock::new();
 
@@ -123,8 +129,8 @@ pub struct SearchMetrics {
     pub total_requests: u64,
     pub successful_requests: u64,
     pub failed_requests: u64,
-    pub cache_hits: u64,
-    pub cache_misses: u64,
+    pub cache_hits: AtomicU64,
+    pub cache_misses: AtomicU64,
     pub total_duration: Duration,
     pub site_metrics: RwLock<std::collections::HashMap<String, SiteMetrics>>,
     pub start_time: Instant,
@@ -138,6 +144,41 @@ pub struct SiteMetrics {
     pub avg_response_time: Duration,
 }
 
+/// Per-site request counts, as reported by [`SearchMetrics::snapshot`]
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SiteStats {
+    pub site: String,
+    pub requests: u64,
+    pub successes: u64,
+    pub failures: u64,
+    /// Share of requests that succeeded, from 0 to 1
+    pub success_rate: f64,
+    pub avg_response_ms: u64,
+}
+
+/// Point-in-time copy of the collected metrics, for dashboards
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MetricsSnapshot {
+    pub uptime_seconds: u64,
+    pub requests: u64,
+    pub successes: u64,
+    pub failures: u64,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    /// Share of cache lookups that hit, or `None` before the first lookup
+    pub cache_hit_rate: Option<f64>,
+    /// Sites sorted by name
+    pub sites: Vec<SiteStats>,
+}
+
+fn rate(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
 impl Default for SearchMetrics {
     fn default() -> Self {
         Self::new()
@@ -154,8 +195,8 @@ impl SearchMetrics {
             total_requests: 0,
             successful_requests: 0,
             failed_requests: 0,
-            cache_hits: 0,
-            cache_misses: 0,
+            cache_hits: AtomicU64::new(0),
+            cache_misses: AtomicU64::new(0),
             total_duration: Duration::ZERO,
             site_metrics: RwLock::new(std::collections::HashMap::new()),
             start_time: Instant::now(),
@@ -197,11 +238,13 @@ impl SearchMetrics {
 
     pub fn record_cache_hit(&self) {
         counter!("website_searcher_cache_hits_total");
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
         debug!("Cache hit recorded");
     }
 
     pub fn record_cache_miss(&self) {
         counter!("website_searcher_cache_misses_total");
+        self.cache_misses.fetch_add(1, Ordering::Relaxed);
         debug!("Cache miss recorded");
     }
 
@@ -217,6 +260,38 @@ impl SearchMetrics {
         self.start_time.elapsed()
     }
 
+    pub async fn snapshot(&self) -> MetricsSnapshot {
+        let mut sites: Vec<SiteStats> = self
+            .site_metrics
+            .read()
+            .await
+            .iter()
+            .map(|(site, m)| SiteStats {
+                site: site.clone(),
+                requests: m.requests,
+                successes: m.successes,
+                failures: m.failures,
+                success_rate: rate(m.successes, m.requests),
+                avg_response_ms: m.avg_response_time.as_millis() as u64,
+            })
+            .collect();
+        sites.sort_by(|a, b| a.site.cmp(&b.site));
+
+        let cache_hits = self.cache_hits.load(Ordering::Relaxed);
+        let cache_misses = self.cache_misses.load(Ordering::Relaxed);
+        let lookups = cache_hits + cache_misses;
+        MetricsSnapshot {
+            uptime_seconds: self.uptime().as_secs(),
+            requests: sites.iter().map(|s| s.requests).sum(),
+            successes: sites.iter().map(|s| s.successes).sum(),
+            failures: sites.iter().map(|s| s.failures).sum(),
+            cache_hits,
+            cache_misses,
+            cache_hit_rate: (lookups > 0).then(|| rate(cache_hits, lookups)),
+            sites,
+        }
+    }
+
     pub async fn log_summary(&self) {
         let site_metrics = self.site_metrics.read().await;
         let total_requests: u64 = site_metrics.values().map(|m| m.requests).sum();
@@ -258,6 +333,339 @@ impl SearchMetrics {
     }
 }
 
+/// Site search durations kept per site
+const TIMING_SAMPLES: usize = 20;
+
+/// How long each site's searches took in recent runs, kept in
+/// `site_timings.json` in the state directory so the next search can start
+/// the slowest sites first (see [`SiteTimings::slowest_first`])
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SiteTimings {
+    /// Milliseconds of the last [`TIMING_SAMPLES`] searches, oldest first
+    sites: BTreeMap<String, VecDeque<u64>>,
+}
+
+impl SiteTimings {
+    /// Load the timings, starting empty if the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid site timings {:?}: {}", path, e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        paths::write_atomic(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Add how long a search of `site` took, dropping its oldest sample
+    pub fn record(&mut self, site: &str, duration: Duration) {
+        let samples = self.sites.entry(site.to_lowercase()).or_default();
+        samples.push_back(duration.as_millis() as u64);
+        while samples.len() > TIMING_SAMPLES {
+            samples.pop_front();
+        }
+    }
+
+    /// 95th percentile of the site's recent durations, if it has any
+    pub fn p95(&self, site: &str) -> Option<Duration> {
+        let mut samples: Vec<u64> = self
+            .sites
+            .get(&site.to_lowercase())?
+            .iter()
+            .copied()
+            .collect();
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort_unstable();
+        let rank = (samples.len() * 95).div_ceil(100).max(1);
+        Some(Duration::from_millis(samples[rank - 1]))
+    }
+
+    /// Start order for `items`: sites with no timings yet first (they may well
+    /// be slow), then by p95, slowest first. Ties keep their order.
+    pub fn slowest_first<T>(&self, items: &mut [T], name: impl Fn(&T) -> &str) {
+        items.sort_by_key(|item| std::cmp::Reverse(self.p95(name(item)).unwrap_or(Duration::MAX)));
+    }
+}
+
+/// Distinct queries a site must come up empty on, while other sites matched
+/// them, before it is flagged as likely broken
+pub const BROKEN_AFTER: usize = 3;
+
+/// Queries kept per site streak
+const STREAK_QUERIES: usize = 10;
+
+/// Sites that keep finding nothing for queries other sites match, kept in
+/// `site_health.json` in the state directory. A site's streak grows by one
+/// for each distinct such query and ends as soon as it finds anything; after
+/// [`BROKEN_AFTER`] queries its parser is probably broken (the site changed
+/// its pages), not the searches.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SiteHealth {
+    sites: BTreeMap<String, ZeroStreak>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+struct ZeroStreak {
+    /// Distinct queries (lowercased) of the streak, oldest first; only the
+    /// last [`STREAK_QUERIES`] are kept
+    queries: VecDeque<String>,
+    /// How many distinct queries the streak has lasted
+    length: usize,
+}
+
+/// A site flagged by [`SiteHealth::likely_broken`]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct BrokenSite {
+    pub site: String,
+    /// Distinct queries in a row the site found nothing for
+    pub streak: usize,
+    /// The most recent of them, oldest first
+    pub queries: Vec<String>,
+}
+
+impl SiteHealth {
+    /// Load the streaks, starting empty if the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid site health {:?}: {}", path, e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        paths::write_atomic(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Record how many results each searched site found for `query`. Returns
+    /// the sites this search flagged as likely broken.
+    ///
+    /// Nothing changes when no site found anything: the query just has no
+    /// matches.
+    pub fn record_search<'a>(
+        &mut self,
+        query: &str,
+        found: impl IntoIterator<Item = (&'a str, usize)>,
+    ) -> Vec<String> {
+        let found: Vec<(&str, usize)> = found.into_iter().collect();
+        if found.iter().all(|&(_, count)| count == 0) {
+            return Vec::new();
+        }
+        let query = query.trim().to_lowercase();
+        let mut flagged = Vec::new();
+        for (site, count) in found {
+            let site = site.to_lowercase();
+            if count > 0 {
+                self.sites.remove(&site);
+                continue;
+            }
+            let streak = self.sites.entry(site.clone()).or_default();
+            if streak.queries.contains(&query) {
+                continue;
+            }
+            streak.queries.push_back(query.clone());
+            while streak.queries.len() > STREAK_QUERIES {
+                streak.queries.pop_front();
+            }
+            streak.length += 1;
+            if streak.length == BROKEN_AFTER {
+                flagged.push(site);
+            }
+        }
+        flagged
+    }
+
+    /// Distinct queries in a row `site` found nothing for that other sites matched
+    pub fn streak(&self, site: &str) -> usize {
+        self.sites.get(&site.to_lowercase()).map_or(0, |s| s.length)
+    }
+
+    /// Sites whose streak reached [`BROKEN_AFTER`], by name
+    pub fn likely_broken(&self) -> Vec<BrokenSite> {
+        self.sites
+            .iter()
+            .filter(|(_, streak)| streak.length >= BROKEN_AFTER)
+            .map(|(site, streak)| BrokenSite {
+                site: site.clone(),
+                streak: streak.length,
+                queries: streak.queries.iter().cloned().collect(),
+            })
+            .collect()
+    }
+}
+
+/// Part of a search its time goes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchPhase {
+    /// Waiting for a concurrency slot
+    Queue,
+    /// Fetching pages, feeds and JSON directly
+    Fetch,
+    /// Fetching pages through FlareSolverr
+    Solver,
+    /// Loading pages in Playwright
+    Render,
+    /// Parsing pages into results
+    Parse,
+    /// Filtering, deduplicating, sorting and annotating the combined results
+    PostProcess,
+}
+
+impl SearchPhase {
+    pub const ALL: [SearchPhase; 6] = [
+        SearchPhase::Queue,
+        SearchPhase::Fetch,
+        SearchPhase::Solver,
+        SearchPhase::Render,
+        SearchPhase::Parse,
+        SearchPhase::PostProcess,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SearchPhase::Queue => "queue",
+            SearchPhase::Fetch => "fetch",
+            SearchPhase::Solver => "solver",
+            SearchPhase::Render => "render",
+            SearchPhase::Parse => "parse",
+            SearchPhase::PostProcess => "post_process",
+        }
+    }
+}
+
+/// Searches shorter than this get no [`TimingAdvice`]; there is nothing to win
+const ADVICE_AFTER: Duration = Duration::from_secs(1);
+
+/// Where the time of one search went, filled in by its site tasks as they run
+#[derive(Debug)]
+pub struct SearchTimings {
+    started: Instant,
+    /// Time by site (empty for the search as a whole) and phase
+    spent: std::sync::Mutex<BTreeMap<(String, SearchPhase), Duration>>,
+}
+
+impl Default for SearchTimings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SearchTimings {
+    /// Timings of a search starting now
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            spent: Default::default(),
+        }
+    }
+
+    /// Add `duration` to `site`'s time in `phase`; an empty `site` is the
+    /// search as a whole, e.g. its post-processing
+    pub fn record(&self, site: &str, phase: SearchPhase, duration: Duration) {
+        let mut spent = self.spent.lock().unwrap_or_else(|e| e.into_inner());
+        *spent.entry((site.to_string(), phase)).or_default() += duration;
+    }
+
+    /// [`record`](Self::record) the time since `since`
+    pub fn record_since(&self, site: &str, phase: SearchPhase, since: Instant) {
+        self.record(site, phase, since.elapsed());
+    }
+
+    /// The time so far, per phase and per site
+    pub fn breakdown(&self) -> TimingBreakdown {
+        let spent = self.spent.lock().unwrap_or_else(|e| e.into_inner());
+        let mut breakdown = TimingBreakdown {
+            total_ms: self.started.elapsed().as_millis() as u64,
+            ..Default::default()
+        };
+        for ((site, phase), duration) in spent.iter() {
+            let ms = duration.as_millis() as u64;
+            *breakdown.phases.entry(*phase).or_default() += ms;
+            if !site.is_empty() {
+                *breakdown
+                    .sites
+                    .entry(site.clone())
+                    .or_default()
+                    .entry(*phase)
+                    .or_default() += ms;
+            }
+        }
+        breakdown
+    }
+}
+
+/// Time spent in each [`SearchPhase`] of one search, in milliseconds
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimingBreakdown {
+    /// Wall-clock time of the whole search
+    pub total_ms: u64,
+    /// Time per phase over every site; sites run at once, so these can add
+    /// up to more than `total_ms`
+    pub phases: BTreeMap<SearchPhase, u64>,
+    /// Time per phase of each site
+    pub sites: BTreeMap<String, BTreeMap<SearchPhase, u64>>,
+}
+
+/// What a [`TimingBreakdown`] suggests changing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimingAdvice {
+    /// Sites spent a third of their time waiting for a slot
+    Concurrency,
+    /// FlareSolverr took half of the sites' time
+    Solver,
+    /// One of several sites took half of the sites' time
+    SlowSite(String),
+}
+
+impl TimingBreakdown {
+    /// Time of `phase` over every site
+    pub fn phase(&self, phase: SearchPhase) -> u64 {
+        self.phases.get(&phase).copied().unwrap_or(0)
+    }
+
+    /// Time of a site over its phases
+    fn site_total(phases: &BTreeMap<SearchPhase, u64>) -> u64 {
+        phases.values().sum()
+    }
+
+    /// What would make a search like this one faster, if it took long enough
+    /// to matter
+    pub fn advice(&self) -> Vec<TimingAdvice> {
+        let mut advice = Vec::new();
+        let sites: u64 = self.sites.values().map(Self::site_total).sum();
+        if self.total_ms < ADVICE_AFTER.as_millis() as u64 || sites == 0 {
+            return advice;
+        }
+        if self.phase(SearchPhase::Queue) * 3 >= sites {
+            advice.push(TimingAdvice::Concurrency);
+        }
+        if self.phase(SearchPhase::Solver) * 2 >= sites {
+            advice.push(TimingAdvice::Solver);
+        }
+        if self.sites.len() > 1
+            && let Some((site, phases)) = self
+                .sites
+                .iter()
+                .max_by_key(|(_, phases)| Self::site_total(phases))
+            && Self::site_total(phases) * 2 >= sites
+        {
+            advice.push(TimingAdvice::SlowSite(site.clone()));
+        }
+        advice
+    }
+}
+
 /// A timer for measuring operation duration
 pub struct Timer {
     start: Instant,
@@ -330,6 +738,139 @@ mod tests {
         assert_eq!(site_metrics.failures, 1);
     }
 
+    #[tokio::test]
+    async fn snapshot_sums_sites_and_cache_lookups() {
+        let metrics = SearchMetrics::new();
+        assert_eq!(metrics.snapshot().await.cache_hit_rate, None);
+
+        metrics
+            .record_request("fitgirl", Duration::from_millis(100), true)
+            .await;
+        metrics
+            .record_request("fitgirl", Duration::from_millis(300), false)
+            .await;
+        metrics
+            .record_request("dodi", Duration::from_millis(50), true)
+            .await;
+        metrics.record_cache_hit();
+        metrics.record_cache_miss();
+        metrics.record_cache_miss();
+        metrics.record_cache_miss();
+
+        let snapshot = metrics.snapshot().await;
+        assert_eq!(snapshot.requests, 3);
+        assert_eq!(snapshot.failures, 1);
+        assert_eq!(snapshot.cache_hit_rate, Some(0.25));
+        let sites: Vec<&str> = snapshot.sites.iter().map(|s| s.site.as_str()).collect();
+        assert_eq!(sites, ["dodi", "fitgirl"]);
+        assert_eq!(snapshot.sites[1].success_rate, 0.5);
+        assert_eq!(snapshot.sites[1].avg_response_ms, 200);
+    }
+
+    #[test]
+    fn empty_streaks_flag_sites_that_others_outmatch() {
+        let mut health = SiteHealth::default();
+        assert!(
+            health
+                .record_search("hades", [("fitgirl", 0), ("dodi", 0)])
+                .is_empty(),
+            "no site matched, so nobody is to blame"
+        );
+        assert_eq!(health.streak("fitgirl"), 0);
+
+        health.record_search("elden ring", [("fitgirl", 0), ("dodi", 4)]);
+        // The same query again does not lengthen the streak
+        health.record_search("Elden Ring", [("fitgirl", 0), ("dodi", 4)]);
+        health.record_search("sekiro", [("FitGirl", 0), ("dodi", 2)]);
+        assert_eq!(health.streak("fitgirl"), 2);
+        assert!(health.likely_broken().is_empty());
+
+        let flagged = health.record_search("hollow knight", [("fitgirl", 0), ("dodi", 1)]);
+        assert_eq!(flagged, ["fitgirl"]);
+        let broken = health.likely_broken();
+        assert_eq!(broken[0].site, "fitgirl");
+        assert_eq!(broken[0].queries, ["elden ring", "sekiro", "hollow knight"]);
+        // Flagged once, when the streak reaches the threshold
+        assert!(
+            health
+                .record_search("celeste", [("fitgirl", 0), ("dodi", 1)])
+                .is_empty()
+        );
+        assert_eq!(health.streak("fitgirl"), 4);
+
+        health.record_search("hades", [("fitgirl", 3)]);
+        assert_eq!(health.streak("fitgirl"), 0);
+        assert!(health.likely_broken().is_empty());
+    }
+
+    #[test]
+    fn p95_orders_sites_slowest_first() {
+        let mut timings = SiteTimings::default();
+        for ms in 1..=20 {
+            timings.record("csrin", Duration::from_secs(ms));
+        }
+        // One slow outlier in twenty is below the 95th percentile
+        for _ in 0..19 {
+            timings.record("fitgirl", Duration::from_millis(800));
+        }
+        timings.record("fitgirl", Duration::from_secs(60));
+        timings.record("dodi", Duration::from_secs(2));
+        assert_eq!(timings.p95("CSRIN"), Some(Duration::from_secs(19)));
+        assert_eq!(timings.p95("fitgirl"), Some(Duration::from_millis(800)));
+        assert_eq!(timings.p95("new-site"), None);
+
+        let mut sites = ["fitgirl", "dodi", "new-site", "csrin"];
+        timings.slowest_first(&mut sites, |s| s);
+        assert_eq!(sites, ["new-site", "csrin", "dodi", "fitgirl"]);
+    }
+
+    #[test]
+    fn timings_keep_recent_samples_and_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("site_timings.json");
+        let mut timings = SiteTimings::default();
+        for ms in 0..30 {
+            timings.record("dodi", Duration::from_millis(ms));
+        }
+        assert_eq!(timings.sites["dodi"].len(), TIMING_SAMPLES);
+        assert_eq!(timings.sites["dodi"].front(), Some(&10));
+        timings.save(&path).unwrap();
+        assert_eq!(SiteTimings::load(&path), timings);
+        assert_eq!(
+            SiteTimings::load(&dir.path().join("missing.json")),
+            SiteTimings::default()
+        );
+    }
+
+    #[test]
+    fn timings_add_up_per_phase_and_site() {
+        let timings = SearchTimings::new();
+        let ms = Duration::from_millis;
+        timings.record("csrin", SearchPhase::Solver, ms(1500));
+        timings.record("csrin", SearchPhase::Parse, ms(20));
+        timings.record("csrin", SearchPhase::Parse, ms(30));
+        timings.record("dodi", SearchPhase::Queue, ms(200));
+        timings.record("dodi", SearchPhase::Fetch, ms(250));
+        timings.record("", SearchPhase::PostProcess, ms(5));
+        let mut breakdown = timings.breakdown();
+        assert_eq!(breakdown.phase(SearchPhase::Parse), 50);
+        assert_eq!(breakdown.phase(SearchPhase::PostProcess), 5);
+        assert_eq!(breakdown.sites.len(), 2);
+        assert_eq!(breakdown.sites["dodi"][&SearchPhase::Fetch], 250);
+        let json = serde_json::to_value(&breakdown).unwrap();
+        assert_eq!(json["phases"]["post_process"], 5);
+
+        // Too quick to give advice about
+        assert!(breakdown.advice().is_empty());
+        breakdown.total_ms = 2000;
+        assert_eq!(
+            breakdown.advice(),
+            [TimingAdvice::Solver, TimingAdvice::SlowSite("csrin".into())]
+        );
+        breakdown.phases.insert(SearchPhase::Queue, 1000);
+        assert_eq!(breakdown.advice()[0], TimingAdvice::Concurrency);
+    }
+
     #[tokio::test]
     async fn test_timer() {
         let timer = Timer::start("test");
@@ -0,0 +1,247 @@
+//! Notes on results: a status and free text attached to a result URL.
+//!
+//! Kept in `notes.json` in the data directory and shared by the CLI and the
+//! GUI. Every search attaches the note saved for a result's URL, so a release
+//! marked downloaded or ignored is recognized wherever it turns up again, and
+//! results with a status the user asked to hide are dropped.
+
+use crate::models::SearchResult;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoteStatus {
+    Interested,
+    Downloaded,
+    Ignored,
+}
+
+impl NoteStatus {
+    pub const ALL: [NoteStatus; 3] = [Self::Interested, Self::Downloaded, Self::Ignored];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Interested => "interested",
+            Self::Downloaded => "downloaded",
+            Self::Ignored => "ignored",
+        }
+    }
+}
+
+impl fmt::Display for NoteStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for NoteStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|status| status.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                format!("unknown status {s:?} (expected interested, downloaded or ignored)")
+            })
+    }
+}
+
+/// What the user noted about one result URL
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Note {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<NoteStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Unix timestamp (seconds) of the last change
+    #[serde(default)]
+    pub updated_at: u64,
+}
+
+impl Note {
+    fn is_empty(&self) -> bool {
+        self.status.is_none() && self.text.is_none()
+    }
+
+    /// `[status: text]`, or whichever of the two is set, for table output
+    pub fn label(&self) -> String {
+        match (self.status, &self.text) {
+            (Some(status), Some(text)) => format!("[{status}: {text}]"),
+            (Some(status), None) => format!("[{status}]"),
+            (None, Some(text)) => format!("[{text}]"),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+/// Notes by result URL
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Notes {
+    notes: BTreeMap<String, Note>,
+}
+
+impl Notes {
+    /// Load notes, starting empty if the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid notes file {:?}: {}", path, e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn get(&self, url: &str) -> Option<&Note> {
+        self.notes.get(url.trim())
+    }
+
+    /// Replace the note on `url`. Blank text counts as none, and a note with
+    /// neither status nor text is removed. Returns the saved note.
+    pub fn set(
+        &mut self,
+        url: &str,
+        status: Option<NoteStatus>,
+        text: Option<String>,
+    ) -> Option<Note> {
+        let note = Note {
+            status,
+            text: text.map(|t| t.trim().to_string()).filter(|t| !t.is_empty()),
+            updated_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        };
+        let url = url.trim().to_string();
+        if note.is_empty() {
+            self.notes.remove(&url);
+            return None;
+        }
+        self.notes.insert(url, note.clone());
+        Some(note)
+    }
+
+    /// Remove the note on `url`; returns false if there was none
+    pub fn remove(&mut self, url: &str) -> bool {
+        self.notes.remove(url.trim()).is_some()
+    }
+
+    /// Every note, by URL
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Note)> {
+        self.notes.iter().map(|(url, note)| (url.as_str(), note))
+    }
+
+    pub fn len(&self) -> usize {
+        self.notes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    /// Attach each result's note and drop results whose status is in `hide`
+    pub fn apply(&self, results: &mut Vec<SearchResult>, hide: &[NoteStatus]) {
+        for r in results.iter_mut() {
+            r.note = self.get(&r.url).cloned();
+        }
+        results.retain(|r| {
+            !r.note
+                .as_ref()
+                .and_then(|n| n.status)
+                .is_some_and(|s| hide.contains(&s))
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn result(url: &str) -> SearchResult {
+        SearchResult {
+            site: "fitgirl".into(),
+            title: url.into(),
+            url: url.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn notes_round_trip_and_empty_notes_are_removed() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("notes.json");
+        let mut notes = Notes::default();
+        let saved = notes
+            .set(
+                " https://a.example/elden-ring/ ",
+                Some(NoteStatus::Downloaded),
+                Some("  1.16, works  ".into()),
+            )
+            .unwrap();
+        assert_eq!(saved.text.as_deref(), Some("1.16, works"));
+        notes.set(
+            "https://a.example/hades/",
+            None,
+            Some("wait for 1.0".into()),
+        );
+        notes.save(&path).unwrap();
+
+        let mut loaded = Notes::load(&path);
+        assert_eq!(loaded.len(), 2);
+        let note = loaded.get("https://a.example/elden-ring/").unwrap();
+        assert_eq!(note.status, Some(NoteStatus::Downloaded));
+
+        assert_eq!(
+            loaded.set("https://a.example/hades/", None, Some(" ".into())),
+            None
+        );
+        assert!(loaded.get("https://a.example/hades/").is_none());
+        assert!(loaded.remove("https://a.example/elden-ring/"));
+        assert!(!loaded.remove("https://a.example/elden-ring/"));
+        assert!(Notes::load(&dir.path().join("missing.json")).is_empty());
+    }
+
+    #[test]
+    fn apply_attaches_notes_and_hides_statuses() {
+        let mut notes = Notes::default();
+        notes.set("https://a.example/1", Some(NoteStatus::Ignored), None);
+        notes.set("https://a.example/2", Some(NoteStatus::Interested), None);
+        let mut results = vec![
+            result("https://a.example/1"),
+            result("https://a.example/2"),
+            result("https://a.example/3"),
+        ];
+        results[2].note = Some(Note::default());
+
+        notes.apply(&mut results, &[NoteStatus::Ignored]);
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[0].note.as_ref().and_then(|n| n.status),
+            Some(NoteStatus::Interested)
+        );
+        // Notes removed since the result was cached are dropped too
+        assert_eq!(results[1].note, None);
+    }
+
+    #[test]
+    fn status_parses_case_insensitively() {
+        assert_eq!("Ignored".parse::<NoteStatus>(), Ok(NoteStatus::Ignored));
+        assert!("skipped".parse::<NoteStatus>().is_err());
+        assert_eq!(NoteStatus::Downloaded.to_string(), "downloaded");
+    }
+}
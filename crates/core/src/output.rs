@@ -1,9 +1,11 @@
 use colored_json::ToColoredJson;
 use serde_json::json;
 
+use crate::liveness::LinkState;
 use crate::models::SearchResult;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
-use tabled::{Table, Tabled, settings::Style};
+use tabled::{builder::Builder, settings::Style};
 use terminal_size::{Width as TWidth, terminal_size};
 use textwrap::fill as tw_fill;
 
@@ -30,9 +32,47 @@ pub fn print_pretty_json(results: &[SearchResult]) {
     }
 }
 
+/// One compact JSON object per result and line, for output read as it comes
+pub fn print_json_lines(results: &[SearchResult]) {
+    for r in results {
+        match serde_json::to_string(r) {
+            Ok(s) => println!("{s}"),
+            Err(e) => eprintln!("failed to serialize result: {e}"),
+        }
+    }
+}
+
+/// Text used by [`print_table_grouped_with`], so frontends can localize it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableLabels {
+    pub title: String,
+    pub url: String,
+    pub no_results: String,
+}
+
+impl Default for TableLabels {
+    fn default() -> Self {
+        Self {
+            title: "Title".to_string(),
+            url: "URL".to_string(),
+            no_results: "No results.".to_string(),
+        }
+    }
+}
+
 pub fn print_table_grouped(results: &[SearchResult]) {
+    print_table_grouped_with(results, &TableLabels::default(), false);
+}
+
+/// Wrap `text` in an OSC 8 escape so supporting terminals render it as a link to `url`
+pub fn hyperlink(text: &str, url: &str) -> String {
+    format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+/// Grouped table output. With `hyperlinks`, titles and URLs are emitted as OSC 8 links.
+pub fn print_table_grouped_with(results: &[SearchResult], labels: &TableLabels, hyperlinks: bool) {
     if results.is_empty() {
-        println!("No results.");
+        println!("{}", labels.no_results);
         return;
     }
     // Deterministically group rows by site (alphabetical) so no site is dropped
@@ -60,40 +100,201 @@ pub fn print_table_grouped(results: &[SearchResult]) {
                 r.title = tw_fill(&r.title, title_wrap);
             }
         }
-        let mut table = Table::new(rows.clone());
+        let mut builder = Builder::default();
+        builder.push_record([labels.title.as_str(), labels.url.as_str()]);
+        for r in rows.iter() {
+            builder.push_record([r.title.as_str(), r.url.as_str()]);
+        }
+        let mut table = builder.build();
         table.with(Style::rounded());
         println!("{site}:");
         if std::env::var("NO_TABLE").ok().as_deref() == Some("1") {
             for r in rows.iter() {
-                println!("  - {} ({})", r.title, r.url);
+                if hyperlinks {
+                    println!("  - {} ({})", hyperlink(&r.title, &r.url), r.url);
+                } else {
+                    println!("  - {} ({})", r.title, r.url);
+                }
             }
             println!();
+        } else if hyperlinks {
+            println!("{}\n", linkify_table(&table.to_string(), rows));
         } else {
             println!("{table}\n");
         }
     }
 }
 
-#[derive(Clone, Tabled)]
+/// Turn the title and URL cells of a rendered table into hyperlinks.
+///
+/// Links are added after rendering so the escape sequences don't skew column
+/// widths. Cells are located by scanning forward in row order, past the header.
+fn linkify_table(rendered: &str, rows: &[DisplayRow]) -> String {
+    let body_start = rendered
+        .match_indices('\n')
+        .nth(2)
+        .map(|(i, _)| i + 1)
+        .unwrap_or(rendered.len());
+    let mut out = String::with_capacity(rendered.len() * 2);
+    out.push_str(&rendered[..body_start]);
+    let mut rest = &rendered[body_start..];
+    let mut link_next = |out: &mut String, needle: &str, url: &str| {
+        if needle.is_empty() {
+            return;
+        }
+        if let Some(pos) = rest.find(needle) {
+            out.push_str(&rest[..pos]);
+            out.push_str(&hyperlink(needle, url));
+            rest = &rest[pos + needle.len()..];
+        }
+    };
+    for r in rows {
+        // Cells appear as: first title line, URL, then any wrapped title lines
+        let mut lines = r.title.lines();
+        link_next(&mut out, lines.next().unwrap_or_default(), &r.url);
+        link_next(&mut out, &r.url, &r.url);
+        for line in lines {
+            link_next(&mut out, line.trim(), &r.url);
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[derive(Clone)]
 struct DisplayRow {
-    #[tabled(rename = "Title")]
     title: String,
-    #[tabled(rename = "URL")]
     url: String,
 }
 
 impl From<&SearchResult> for DisplayRow {
     fn from(r: &SearchResult) -> Self {
+        let mut title = match &r.note {
+            Some(note) => format!("{} {}", r.title, note.label()),
+            None => r.title.clone(),
+        };
+        // Only links that stopped answering are worth a mark
+        if let Some(check) = r
+            .link_check
+            .as_ref()
+            .filter(|c| c.state != LinkState::Alive)
+        {
+            title.push_str(&format!(" [{}]", check.state));
+        }
         Self {
-            title: r.title.clone(),
+            title,
             url: r.url.replace("/./", "/"),
         }
     }
 }
 
+/// File formats results can be exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    /// Usual file extension, without the dot
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+/// Render `results` as the complete contents of an export file
+pub fn render_results(results: &[SearchResult], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Json => {
+            let value = json!({
+                "results": results,
+                "count": results.len(),
+            });
+            serde_json::to_string_pretty(&value).unwrap_or_default() + "\n"
+        }
+        ExportFormat::Csv => {
+            let mut out = String::from("site,title,url\r\n");
+            for r in results {
+                out.push_str(&format!(
+                    "{},{},{}\r\n",
+                    csv_field(&r.site),
+                    csv_field(&r.title),
+                    csv_field(&r.url)
+                ));
+            }
+            out
+        }
+        ExportFormat::Markdown => {
+            let mut out = String::new();
+            for (site, rows) in group_by_site(results) {
+                out.push_str(&format!("## {site}\n\n| Title | URL |\n| --- | --- |\n"));
+                for r in rows {
+                    let title = r.title.replace('|', "\\|").replace('\n', " ");
+                    out.push_str(&format!("| [{title}](<{}>) | <{}> |\n", r.url, r.url));
+                }
+                out.push('\n');
+            }
+            out
+        }
+        ExportFormat::Html => {
+            let mut out = String::from(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Search results</title>\n</head>\n<body>\n",
+            );
+            for (site, rows) in group_by_site(results) {
+                out.push_str(&format!("<h2>{}</h2>\n<ul>\n", html_escape(site)));
+                for r in rows {
+                    out.push_str(&format!(
+                        "<li><a href=\"{}\">{}</a></li>\n",
+                        html_escape(&r.url),
+                        html_escape(&r.title)
+                    ));
+                }
+                out.push_str("</ul>\n");
+            }
+            out.push_str("</body>\n</html>\n");
+            out
+        }
+    }
+}
+
+fn group_by_site(results: &[SearchResult]) -> BTreeMap<&str, Vec<&SearchResult>> {
+    let mut grouped: BTreeMap<&str, Vec<&SearchResult>> = BTreeMap::new();
+    for r in results {
+        grouped.entry(&r.site).or_default().push(r);
+    }
+    grouped
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::liveness::LinkCheck;
+    use crate::notes::{Note, NoteStatus};
 
     #[test]
     fn calc_title_wrap_columns_returns_reasonable_default() {
@@ -108,22 +309,100 @@ mod tests {
             site: "test".into(),
             title: "Test".into(),
             url: "http://example.com/./path".into(),
+            ..Default::default()
         };
         let row = DisplayRow::from(&r);
         assert_eq!(row.url, "http://example.com/path");
     }
 
+    #[test]
+    fn display_row_shows_the_note() {
+        let r = SearchResult {
+            site: "test".into(),
+            title: "Elden Ring".into(),
+            url: "http://example.com/elden-ring".into(),
+            note: Some(Note {
+                status: Some(NoteStatus::Downloaded),
+                text: Some("1.16".into()),
+                updated_at: 0,
+            }),
+            ..Default::default()
+        };
+        assert_eq!(DisplayRow::from(&r).title, "Elden Ring [downloaded: 1.16]");
+    }
+
+    #[test]
+    fn display_row_marks_links_that_stopped_answering() {
+        let mut r = SearchResult {
+            site: "test".into(),
+            title: "Elden Ring".into(),
+            url: "http://example.com/elden-ring".into(),
+            link_check: Some(LinkCheck {
+                state: LinkState::Alive,
+                status: Some(200),
+                checked_at: 0,
+            }),
+            ..Default::default()
+        };
+        assert_eq!(DisplayRow::from(&r).title, "Elden Ring");
+        r.link_check.as_mut().unwrap().state = LinkState::Dead;
+        assert_eq!(DisplayRow::from(&r).title, "Elden Ring [dead]");
+    }
+
     #[test]
     fn display_row_preserves_normal_url() {
         let r = SearchResult {
             site: "test".into(),
             title: "Test".into(),
             url: "http://example.com/normal/path".into(),
+            ..Default::default()
         };
         let row = DisplayRow::from(&r);
         assert_eq!(row.url, "http://example.com/normal/path");
     }
 
+    #[test]
+    fn hyperlink_wraps_text_in_osc8() {
+        assert_eq!(
+            hyperlink("Game", "https://x/g"),
+            "\x1b]8;;https://x/g\x1b\\Game\x1b]8;;\x1b\\"
+        );
+    }
+
+    #[test]
+    fn linkify_table_links_cells_without_touching_header() {
+        let rows = vec![
+            DisplayRow {
+                title: "Title".into(),
+                url: "https://x/title".into(),
+            },
+            DisplayRow {
+                title: "Long\nwrapped".into(),
+                url: "https://x/long".into(),
+            },
+        ];
+        let mut builder = Builder::default();
+        builder.push_record(["Title", "URL"]);
+        for r in &rows {
+            builder.push_record([r.title.as_str(), r.url.as_str()]);
+        }
+        let mut table = builder.build();
+        table.with(Style::rounded());
+        let rendered = table.to_string();
+        let linked = linkify_table(&rendered, &rows);
+
+        let header = linked.lines().nth(1).unwrap();
+        assert!(!header.contains('\x1b'));
+        assert!(linked.contains(&hyperlink("Title", "https://x/title")));
+        assert!(linked.contains(&hyperlink("https://x/title", "https://x/title")));
+        assert!(linked.contains(&hyperlink("wrapped", "https://x/long")));
+        // Stripping the escapes gives back the original table
+        let stripped = regex::Regex::new("\x1b]8;;[^\x1b]*\x1b\\\\")
+            .unwrap()
+            .replace_all(&linked, "");
+        assert_eq!(stripped, rendered);
+    }
+
     #[test]
     fn print_table_grouped_handles_empty() {
         // Should print "No results." without panic
@@ -144,8 +423,65 @@ mod tests {
             site: "test".into(),
             title: "Game Title".into(),
             url: "http://example.com".into(),
+            ..Default::default()
         }];
         // Just verify it doesn't crash
         print_pretty_json(&results);
     }
+
+    fn export_sample() -> Vec<SearchResult> {
+        vec![
+            SearchResult {
+                site: "fitgirl".into(),
+                title: "Elden Ring, \"Deluxe\" | v1.10".into(),
+                url: "https://fitgirl-repacks.site/elden-ring/".into(),
+                ..Default::default()
+            },
+            SearchResult {
+                site: "dodi".into(),
+                title: "Hades <GOTY>".into(),
+                url: "https://dodi-repacks.site/hades?a=1&b=2".into(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn render_results_json_and_csv() {
+        let results = export_sample();
+        let json: serde_json::Value =
+            serde_json::from_str(&render_results(&results, ExportFormat::Json)).unwrap();
+        assert_eq!(json["count"], 2);
+        assert_eq!(json["results"][1]["site"], "dodi");
+
+        let csv = render_results(&results, ExportFormat::Csv);
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(lines[0], "site,title,url");
+        assert_eq!(
+            lines[1],
+            "fitgirl,\"Elden Ring, \"\"Deluxe\"\" | v1.10\",https://fitgirl-repacks.site/elden-ring/"
+        );
+        assert_eq!(
+            lines[2],
+            "dodi,Hades <GOTY>,https://dodi-repacks.site/hades?a=1&b=2"
+        );
+    }
+
+    #[test]
+    fn render_results_markdown_and_html_group_by_site_and_escape() {
+        let results = export_sample();
+        let md = render_results(&results, ExportFormat::Markdown);
+        assert!(md.find("## dodi").unwrap() < md.find("## fitgirl").unwrap());
+        assert!(md.contains(
+            "| [Elden Ring, \"Deluxe\" \\| v1.10](<https://fitgirl-repacks.site/elden-ring/>) |"
+        ));
+
+        let html = render_results(&results, ExportFormat::Html);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<h2>dodi</h2>"));
+        assert!(html.contains(
+            "<a href=\"https://dodi-repacks.site/hades?a=1&amp;b=2\">Hades &lt;GOTY&gt;</a>"
+        ));
+        assert_eq!(ExportFormat::Markdown.extension(), "md");
+    }
 }
@@ -0,0 +1,126 @@
+//! Pages fetched during one search, keyed by the URL they ended up at.
+//!
+//! Fallback chains often ask for the same page under different URLs: an
+//! endpoint that redirects to the search page, or a crawl link back to a page
+//! already read. Each requested URL remembers where its redirects led, so
+//! asking again, or asking for that final URL directly, is answered from
+//! memory. Failures are kept too, as their message, so a broken page is not
+//! retried within the same search.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+
+use anyhow::{Result, anyhow};
+
+use crate::fetcher::Fetched;
+
+#[derive(Debug, Default)]
+struct Pages {
+    /// Requested URL to the URL its redirects ended at
+    redirects: HashMap<String, String>,
+    /// Bodies by final URL; failed fetches by requested URL
+    bodies: HashMap<String, Result<String, String>>,
+}
+
+#[derive(Debug, Default)]
+pub struct PageStore(Mutex<Pages>);
+
+impl PageStore {
+    /// The page `url` already led to, if it (or a URL redirecting to it) was fetched
+    pub fn get(&self, url: &str) -> Option<Result<Fetched>> {
+        let pages = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let final_url = pages.redirects.get(url).map_or(url, String::as_str);
+        let page = pages.bodies.get(final_url)?;
+        Some(match page {
+            Ok(body) => Ok(Fetched {
+                url: final_url.to_string(),
+                body: body.clone(),
+            }),
+            Err(e) => Err(anyhow!(e.clone())),
+        })
+    }
+
+    /// Remember what fetching `requested` gave
+    pub fn insert(&self, requested: &str, page: &Result<Fetched>) {
+        let mut pages = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        match page {
+            Ok(fetched) => {
+                if fetched.url != requested {
+                    pages
+                        .redirects
+                        .insert(requested.to_string(), fetched.url.clone());
+                }
+                pages
+                    .bodies
+                    .insert(fetched.url.clone(), Ok(fetched.body.clone()));
+            }
+            Err(e) => {
+                pages
+                    .bodies
+                    .insert(requested.to_string(), Err(format!("{e:#}")));
+            }
+        }
+    }
+
+    /// The stored page for `url`, or the result of `fetch` (then stored)
+    pub async fn get_or_fetch<F>(&self, url: &str, fetch: F) -> Result<Fetched>
+    where
+        F: Future<Output = Result<Fetched>>,
+    {
+        if let Some(page) = self.get(url) {
+            return page;
+        }
+        let fetched = fetch.await;
+        self.insert(url, &fetched);
+        fetched
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(url: &str, body: &str) -> Result<Fetched> {
+        Ok(Fetched {
+            url: url.into(),
+            body: body.into(),
+        })
+    }
+
+    #[tokio::test]
+    async fn redirected_urls_share_the_final_page() {
+        let store = PageStore::default();
+        let fetched = store
+            .get_or_fetch("https://a.example/search?page=1&s=elden", async {
+                page("https://a.example/?s=elden", "<html>results</html>")
+            })
+            .await
+            .unwrap();
+        assert_eq!(fetched.url, "https://a.example/?s=elden");
+
+        // Both the redirecting URL and the page it led to are now known
+        for url in [
+            "https://a.example/search?page=1&s=elden",
+            "https://a.example/?s=elden",
+        ] {
+            let again = store
+                .get_or_fetch(url, async { panic!("{url} fetched twice") })
+                .await
+                .unwrap();
+            assert_eq!(again, fetched);
+        }
+        assert!(store.get("https://a.example/?s=other").is_none());
+    }
+
+    #[test]
+    fn failures_are_kept_by_requested_url() {
+        let store = PageStore::default();
+        store.insert(
+            "https://a.example/broken",
+            &Err(anyhow!("HTTP 500 from a.example")),
+        );
+        let err = store.get("https://a.example/broken").unwrap().unwrap_err();
+        assert_eq!(err.to_string(), "HTTP 500 from a.example");
+    }
+}
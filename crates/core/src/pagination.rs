@@ -0,0 +1,150 @@
+//! Finding the next page of a site's search results.
+//!
+//! A page's own "next" link is preferred; when there is none, the URL is built
+//! the way the site's search kind pages (WordPress `/page/N/` for query-param
+//! searches, `start=` offsets for phpBB).
+
+use crate::models::{SearchKind, SiteConfig};
+use crate::query::{build_search_url, with_query};
+use reqwest::Url;
+use scraper::{Html, Selector};
+
+/// Results per page of a phpBB search
+const PHPBB_PAGE_SIZE: usize = 25;
+
+/// Links to the following page, most specific first
+const NEXT_SELECTORS: &[&str] = &[
+    "link[rel=next]",
+    "a[rel~=next]",
+    "a.next.page-numbers",
+    // WordPress themes label older (later) results "previous"
+    ".nav-previous a",
+    ".pagination a.next",
+    "a.nextpostslink",
+    "li.next a",
+];
+
+/// The page `html` links to as the next one, resolved against `page_url`
+pub fn next_page_link(html: &str, page_url: &str) -> Option<String> {
+    let mut base = Url::parse(page_url).ok()?;
+    base.set_fragment(None);
+    let doc = Html::parse_document(html);
+    NEXT_SELECTORS
+        .iter()
+        .filter_map(|s| Selector::parse(s).ok())
+        .flat_map(|sel| {
+            doc.select(&sel)
+                .filter_map(|el| el.value().attr("href"))
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .filter_map(|href| base.join(href.trim()).ok())
+        .map(|mut url| {
+            url.set_fragment(None);
+            url
+        })
+        .find(|url| matches!(url.scheme(), "http" | "https") && *url != base)
+        .map(String::from)
+}
+
+/// URL of search results page `page` (1-based) for sites whose search kind
+/// pages predictably; `None` for kinds without pages
+pub fn page_url(site: &SiteConfig, query: &str, page: usize) -> Option<String> {
+    let first = build_search_url(site, query);
+    if page <= 1 {
+        return Some(first);
+    }
+    match site.search_kind {
+        SearchKind::QueryParam => {
+            let (_, qs) = first.split_once('?')?;
+            let base = site.base_url.trim_end_matches('/');
+            Some(format!("{base}/page/{page}/?{qs}"))
+        }
+        SearchKind::PhpBBSearch => {
+            let start = ((page - 1) * PHPBB_PAGE_SIZE).to_string();
+            Some(with_query(&first, &[("start", &start)]))
+        }
+        SearchKind::FrontPage
+        | SearchKind::ListingPage
+        | SearchKind::PathEncoded
+        | SearchKind::Sitemap
+        | SearchKind::Feed
+        | SearchKind::PostForm
+        | SearchKind::GraphQL => None,
+    }
+}
+
+/// The page after `page` (fetched from `current_url` with body `html`)
+pub fn next_page(
+    site: &SiteConfig,
+    query: &str,
+    page: usize,
+    current_url: &str,
+    html: &str,
+) -> Option<String> {
+    next_page_link(html, current_url).or_else(|| page_url(site, query, page + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn site(kind: SearchKind) -> SiteConfig {
+        SiteConfig {
+            name: "example".to_string(),
+            base_url: "https://example.com/".to_string(),
+            search_kind: kind,
+            query_param: Some("s".to_string()),
+            result_selector: "h2 a".into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn next_links_are_found_and_resolved() {
+        let html = r#"<nav><a class="prev page-numbers" href="/">1</a>
+            <a class="next page-numbers" href="/page/3/?s=hades">Next</a></nav>"#;
+        assert_eq!(
+            next_page_link(html, "https://example.com/page/2/?s=hades").as_deref(),
+            Some("https://example.com/page/3/?s=hades")
+        );
+        let head = r#"<head><link rel="next" href="?s=hades&amp;paged=2"></head>"#;
+        assert_eq!(
+            next_page_link(head, "https://example.com/?s=hades").as_deref(),
+            Some("https://example.com/?s=hades&paged=2")
+        );
+        // Links back to the same page or to scripts don't count
+        let same = r##"<a rel="next" href="#">Next</a><a rel="next" href="javascript:void(0)">"##;
+        assert_eq!(next_page_link(same, "https://example.com/?s=hades"), None);
+        assert_eq!(
+            next_page_link("<p>no pages</p>", "https://example.com/"),
+            None
+        );
+    }
+
+    #[test]
+    fn page_urls_follow_the_search_kind() {
+        let wp = site(SearchKind::QueryParam);
+        assert_eq!(
+            page_url(&wp, "elden ring", 1).as_deref(),
+            Some("https://example.com/?s=elden+ring")
+        );
+        assert_eq!(
+            page_url(&wp, "elden ring", 3).as_deref(),
+            Some("https://example.com/page/3/?s=elden+ring")
+        );
+        let forum = site(SearchKind::PhpBBSearch);
+        assert!(page_url(&forum, "hades", 2).unwrap().ends_with("&start=25"));
+        assert_eq!(page_url(&site(SearchKind::FrontPage), "hades", 2), None);
+
+        let html = r#"<a rel="next" href="/search/hades/2">Next</a>"#;
+        assert_eq!(
+            next_page(&wp, "hades", 1, "https://example.com/?s=hades", html).as_deref(),
+            Some("https://example.com/search/hades/2")
+        );
+        assert_eq!(
+            next_page(&wp, "hades", 1, "https://example.com/?s=hades", "").as_deref(),
+            Some("https://example.com/page/2/?s=hades")
+        );
+    }
+}
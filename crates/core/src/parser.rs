@@ -1,12 +1,66 @@
-use scraper::{Html, Selector};
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use urlencoding::decode;
 
-use crate::models::{SearchResult, SiteConfig};
+use crate::cfemail;
+use crate::extract::Extract;
+use crate::feed;
+use crate::graphql;
+use crate::learned::AnchorPattern;
+use crate::models::{Provenance, ResultSelector, SearchKind, SearchResult, SiteConfig};
+use crate::query::build_search_url;
 
 pub fn parse_results(site: &SiteConfig, html: &str, query: &str) -> Vec<SearchResult> {
+    parse_results_with(site, html, query, None).results
+}
+
+/// Results of parsing a page, and the link pattern they came from when the
+/// site's selectors found nothing and every link on the page was scanned
+#[derive(Debug, Default)]
+pub struct Parsed {
+    pub results: Vec<SearchResult>,
+    pub learned: Option<AnchorPattern>,
+    /// Position in the site's `result_selector` of the selector the results
+    /// came from
+    pub selector: Option<usize>,
+    /// Whether the results came from the page's `<noscript>` HTML
+    pub noscript: bool,
+}
+
+impl From<Vec<SearchResult>> for Parsed {
+    fn from(results: Vec<SearchResult>) -> Self {
+        Self {
+            results,
+            ..Default::default()
+        }
+    }
+}
+
+/// [`parse_results`] that, when the site's selector finds nothing, first tries
+/// the links matching `hint` (the pattern learned for the site) before
+/// scanning every link, and reports the pattern the results came from
+pub fn parse_results_with(
+    site: &SiteConfig,
+    html: &str,
+    query: &str,
+    hint: Option<&AnchorPattern>,
+) -> Parsed {
     if html.is_empty() {
-        return Vec::new();
+        return Parsed::default();
     }
+    let unprotected = cfemail::unprotect(html);
+    let html = unprotected.as_ref();
+    if site.search_kind == SearchKind::Feed {
+        return feed::feed_results(site, html, query, &build_search_url(site, query)).into();
+    }
+    if site.search_kind == SearchKind::GraphQL {
+        return graphql::results(site, html).into();
+    }
+    // Every result shares one allocation of the site name
+    let site_name: Arc<str> = Arc::from(site.name.as_str());
 
     // csrin phpBB search page: topics are anchors with class topictitle
     if site.name.eq_ignore_ascii_case("csrin") && html.contains("search.php") {
@@ -38,47 +92,119 @@ pub fn parse_results(site: &SiteConfig, html: &str, query: &str) -> Vec<SearchRe
                 }
                 if !title.is_empty() {
                     out.push(SearchResult {
-                        site: site.name.to_string(),
+                        site: site_name.clone(),
                         title,
                         url,
+                        ..Default::default()
                     });
                 }
             }
+            site.rules.apply(&mut out);
             if !out.is_empty() {
-                return out;
+                return out.into();
             }
         }
     }
 
     // Site-specific parser for elamigos: titles are in the heading text, link text is "DOWNLOAD"
     if site.name.eq_ignore_ascii_case("elamigos") {
-        return parse_elamigos(site, html, query);
+        return parse_elamigos(site, html, query).into();
     }
 
     // Site-specific parser for f95zone: parse forum thread listings
     if site.name.eq_ignore_ascii_case("f95zone") {
-        return parse_f95zone(site, html, query);
+        return parse_f95zone(site, html, query).into();
     }
 
     // Site-specific parser for nswpedia: filter WordPress search results
     if site.name.eq_ignore_ascii_case("nswpedia") {
-        return parse_nswpedia(site, html, query);
+        return parse_nswpedia(site, html, query).into();
     }
     let document = Html::parse_document(html);
 
-    // Primary: use provided selector
-    if let Ok(sel) = Selector::parse(&site.result_selector) {
+    // Primary: the provided selectors, in order until one finds results
+    if let Some((position, results)) = select_results(site, &site_name, &document, query) {
+        return Parsed {
+            results,
+            selector: Some(position),
+            ..Default::default()
+        };
+    }
+    // Sites that load their results with JavaScript often render them into
+    // <noscript> too; those are read before the page counts as empty and the
+    // site is escalated to a browser
+    if let Some(fallback) = noscript_document(&document)
+        && let Some((position, results)) = select_results(site, &site_name, &fallback, query)
+    {
+        tracing::debug!("{}: results from the page's <noscript> HTML", site.name);
+        return Parsed {
+            results,
+            selector: Some(position),
+            noscript: true,
+            ..Default::default()
+        };
+    }
+
+    // Fallback: scan all anchors and filter by query presence, trying the
+    // links that fit the site's learned pattern first
+    if let Some(hint) = hint {
+        let results: Vec<SearchResult> =
+            scan_anchors(site, &site_name, &document, query, Some(hint))
+                .into_iter()
+                .map(|(result, _)| result)
+                .collect();
+        if !results.is_empty() {
+            return Parsed {
+                results,
+                learned: Some(hint.clone()),
+                ..Default::default()
+            };
+        }
+    }
+    let scanned = scan_anchors(site, &site_name, &document, query, None);
+    let learned = most_common(scanned.iter().filter_map(|(_, pattern)| pattern.as_ref()));
+    Parsed {
+        results: scanned.into_iter().map(|(result, _)| result).collect(),
+        learned,
+        ..Default::default()
+    }
+}
+
+/// Results of the first of the site's selectors that finds any for `query`
+/// in `document`, with its position in `result_selector`
+fn select_results(
+    site: &SiteConfig,
+    site_name: &Arc<str>,
+    document: &Html,
+    query: &str,
+) -> Option<(usize, Vec<SearchResult>)> {
+    let base = Url::parse(&site.base_url).ok();
+    // Specs that don't parse were reported when the sites were loaded
+    let title_from = Extract::title(&site.title_attr).unwrap_or(Extract::Text);
+    let url_from = Extract::url(&site.url_attr).unwrap_or_else(|_| Extract::Attr("href".into()));
+    for (position, selector) in site.result_selector.iter().enumerate() {
+        let Ok(sel) = Selector::parse(selector) else {
+            continue;
+        };
         let mut primary: Vec<SearchResult> = Vec::new();
         for el in document.select(&sel) {
-            let mut title = el.text().collect::<String>().trim().to_string();
-            // Extract href; if empty, try parent element (some cards wrap anchors)
-            let href_attr = el.value().attr("href").or_else(|| {
-                el.parent()
-                    .and_then(|p| p.value().as_element())
-                    .and_then(|pel| pel.attr("href"))
-            });
-            let href = href_attr.unwrap_or("");
-            let mut url = href.to_string();
+            let mut title = title_from.find(el).unwrap_or_default();
+            // Read the URL from the element or inside it; if missing, try the
+            // parent element (some cards wrap anchors)
+            let mut url = url_from
+                .find(el)
+                .or_else(|| match url_from {
+                    Extract::Text => None,
+                    _ => el
+                        .parent()
+                        .and_then(ElementRef::wrap)
+                        .and_then(|p| url_from.read(p)),
+                })
+                .unwrap_or_default();
+            // Mail links, e.g. decoded email protection, are not results
+            if url.starts_with("mailto:") {
+                continue;
+            }
             // Build absolute URL if relative
             if !url.is_empty() {
                 let is_http = url.starts_with("http://")
@@ -101,14 +227,18 @@ pub fn parse_results(site: &SiteConfig, html: &str, query: &str) -> Vec<SearchRe
             if title.is_empty() {
                 title = derive_title_from_href(&url).unwrap_or(title);
             }
+            match site.rules.apply_to(&url, &title) {
+                Some(clean) => title = clean,
+                None => continue,
+            }
             if site.name.eq_ignore_ascii_case("fitgirl") {
-                if let Some(clean) = filter_and_normalize_fitgirl(&url, &title) {
+                if let Some(clean) = filter_and_normalize_fitgirl(&title) {
                     title = clean;
                 } else {
                     continue;
                 }
             } else if site.name.eq_ignore_ascii_case("steamrip") {
-                if let Some(clean) = filter_and_normalize_steamrip(&url, &title) {
+                if let Some(clean) = filter_and_normalize_steamrip(&title) {
                     title = clean;
                 } else {
                     continue;
@@ -116,67 +246,126 @@ pub fn parse_results(site: &SiteConfig, html: &str, query: &str) -> Vec<SearchRe
             }
             if !title.is_empty() {
                 primary.push(SearchResult {
-                    site: site.name.to_string(),
+                    site: site_name.clone(),
                     title,
                     url,
+                    image: base.as_ref().and_then(|base| card_image(el, &sel, base)),
+                    ..Default::default()
                 });
             }
         }
         if !primary.is_empty() {
             // Filter by query presence in title or URL to drop unrelated items
-            let ql = query.to_lowercase();
-            let ql_dash = ql.replace(' ', "-");
-            let ql_plus = ql.replace(' ', "+");
-            let ql_encoded = ql.replace(' ', "%20");
-            let ql_stripped = ql.replace(' ', "");
+            let variants = QueryVariants::new(query);
+            let (mut tl, mut ul) = (String::new(), String::new());
             primary.retain(|r| {
-                let tl = r.title.to_lowercase();
-                let ul = r.url.to_lowercase();
-                let basic = tl.contains(&ql)
-                    || ul.contains(&ql)
-                    || ul.contains(&ql_dash)
-                    || ul.contains(&ql_plus)
-                    || ul.contains(&ql_encoded)
-                    || ul.contains(&ql_stripped);
-                if site.name.eq_ignore_ascii_case("gog-games") {
-                    // Tighten for gog-games: require a game-like path
-                    basic && (ul.contains("/game/") || ul.contains("/games/"))
-                } else {
-                    basic
-                }
+                lowercase_into(&mut tl, &r.title);
+                lowercase_into(&mut ul, &r.url);
+                variants.matches(&tl, &ul)
             });
             if !primary.is_empty() {
-                return primary;
+                if position > 0 {
+                    tracing::debug!(
+                        "{}: results from result selector {} ({selector:?})",
+                        site.name,
+                        position + 1
+                    );
+                }
+                return Some((position, primary));
             }
         }
     }
+    None
+}
+
+/// The HTML inside the page's `<noscript>` blocks as a document of its own;
+/// the page's parser reads it as plain text. `None` if there is no markup in
+/// them.
+fn noscript_document(document: &Html) -> Option<Html> {
+    let sel = Selector::parse("noscript").ok()?;
+    let inner: String = document
+        .select(&sel)
+        .flat_map(|el| el.text())
+        .collect::<Vec<_>>()
+        .join("\n");
+    inner.contains('<').then(|| Html::parse_document(&inner))
+}
+
+/// Attributes holding an image's URL, lazy-loading ones first since `src` is
+/// then a placeholder
+const IMAGE_ATTRS: [&str; 4] = ["data-src", "data-lazy-src", "data-original", "src"];
+
+/// Ancestors of a result searched for its image
+const CARD_DEPTH: usize = 3;
+
+/// Absolute URL of the image on the card of the result `el` matched: the
+/// first image in the element or its nearest ancestor holding one, short of an
+/// ancestor that holds other results too
+fn card_image(el: ElementRef, sel: &Selector, base: &Url) -> Option<String> {
+    let img = Selector::parse("img").ok()?;
+    let cards = std::iter::once(el).chain(
+        el.ancestors()
+            .filter_map(ElementRef::wrap)
+            .take(CARD_DEPTH)
+            .take_while(|card| card.select(sel).nth(1).is_none()),
+    );
+    cards
+        .flat_map(|card| card.select(&img))
+        .find_map(|image| {
+            IMAGE_ATTRS
+                .iter()
+                .filter_map(|attr| image.value().attr(attr))
+                .map(str::trim)
+                .find(|src| !src.is_empty() && !src.starts_with("data:"))
+        })
+        .and_then(|src| base.join(src).ok())
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+        .map(String::from)
+}
+
+/// The pattern shared by the most links; the first one seen on a tie
+fn most_common<'a>(patterns: impl Iterator<Item = &'a AnchorPattern>) -> Option<AnchorPattern> {
+    let mut counts: Vec<(&AnchorPattern, usize)> = Vec::new();
+    for pattern in patterns {
+        match counts.iter_mut().find(|(p, _)| *p == pattern) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((pattern, 1)),
+        }
+    }
+    let best = counts.iter().map(|(_, count)| *count).max()?;
+    counts
+        .into_iter()
+        .find(|(_, count)| *count == best)
+        .map(|(pattern, _)| pattern.clone())
+}
 
-    // Fallback: scan all anchors and filter by query presence
+/// Links on the page whose text or URL has the query, each with its
+/// [`AnchorPattern`]; only those fitting `only` when given
+fn scan_anchors(
+    site: &SiteConfig,
+    site_name: &Arc<str>,
+    document: &Html,
+    query: &str,
+    only: Option<&AnchorPattern>,
+) -> Vec<(SearchResult, Option<AnchorPattern>)> {
     let Ok(a_sel) = Selector::parse("a[href]") else {
         return Vec::new();
     };
-    let ql = query.to_lowercase();
-    let ql_dash = ql.replace(' ', "-");
-    let ql_plus = ql.replace(' ', "+");
-    let ql_encoded = ql.replace(' ', "%20");
-    let ql_stripped = ql.replace(' ', "");
+    let variants = QueryVariants::new(query);
+    // Most anchors don't match; their text is read into reused buffers
+    let (mut text, mut text_l, mut href_l) = (String::new(), String::new(), String::new());
     document
         .select(&a_sel)
         .filter_map(|el| {
-            let text = el.text().collect::<String>();
             let href = el.value().attr("href").unwrap_or("");
-            if href.is_empty() {
+            if href.is_empty() || href.starts_with("mailto:") {
                 return None;
             }
-            let text_l = text.to_lowercase();
-            let href_l = href.to_lowercase();
-            let matches_query = text_l.contains(&ql)
-                || href_l.contains(&ql)
-                || href_l.contains(&ql_dash)
-                || href_l.contains(&ql_plus)
-                || href_l.contains(&ql_encoded)
-                || href_l.contains(&ql_stripped);
-            if !matches_query {
+            text.clear();
+            text.extend(el.text());
+            lowercase_into(&mut text_l, &text);
+            lowercase_into(&mut href_l, href);
+            if !variants.matches(&text_l, &href_l) {
                 return None;
             }
             // treat non-slashed hrefs like "post-slug/" as relative too
@@ -206,29 +395,135 @@ pub fn parse_results(site: &SiteConfig, html: &str, query: &str) -> Vec<SearchRe
                     return None;
                 }
             }
+            title = site.rules.apply_to(&url, &title)?;
             if site.name.eq_ignore_ascii_case("fitgirl") {
-                if let Some(clean) = filter_and_normalize_fitgirl(&url, &title) {
+                if let Some(clean) = filter_and_normalize_fitgirl(&title) {
                     title = clean;
                 } else {
                     return None;
                 }
             } else if site.name.eq_ignore_ascii_case("steamrip") {
-                if let Some(clean) = filter_and_normalize_steamrip(&url, &title) {
+                if let Some(clean) = filter_and_normalize_steamrip(&title) {
                     title = clean;
                 } else {
                     return None;
                 }
             }
 
-            Some(SearchResult {
-                site: site.name.to_string(),
+            let url = url.replace("/./", "/");
+            let parent_class = el
+                .parent()
+                .and_then(|p| p.value().as_element())
+                .and_then(|p| p.attr("class"));
+            if only.is_some_and(|pattern| !pattern.matches(&url, parent_class)) {
+                return None;
+            }
+            let pattern = AnchorPattern::of(&url, parent_class);
+            let result = SearchResult {
+                site: site_name.clone(),
                 title,
-                url: url.replace("/./", "/"),
-            })
+                url,
+                ..Default::default()
+            };
+            Some((result, pattern))
         })
         .collect()
 }
 
+/// The lowercased query and the forms it takes in URL slugs, built once per
+/// page rather than once per result
+pub struct QueryVariants {
+    lower: String,
+    dash: String,
+    plus: String,
+    encoded: String,
+    stripped: String,
+}
+
+impl QueryVariants {
+    pub fn new(query: &str) -> Self {
+        let lower = query.to_lowercase();
+        Self {
+            dash: lower.replace(' ', "-"),
+            plus: lower.replace(' ', "+"),
+            encoded: lower.replace(' ', "%20"),
+            stripped: lower.replace(' ', ""),
+            lower,
+        }
+    }
+
+    /// Whether an already lowercased title or URL contains the query in any form
+    pub fn matches(&self, title_lower: &str, url_lower: &str) -> bool {
+        title_lower.contains(&self.lower)
+            || url_lower.contains(&self.lower)
+            || url_lower.contains(&self.dash)
+            || url_lower.contains(&self.plus)
+            || url_lower.contains(&self.encoded)
+            || url_lower.contains(&self.stripped)
+    }
+}
+
+/// Lowercase `s` into `buf`, reusing its allocation
+pub fn lowercase_into(buf: &mut String, s: &str) {
+    buf.clear();
+    buf.extend(s.chars().flat_map(char::to_lowercase));
+}
+
+/// [`parse_results`] on tokio's blocking pool: building the DOM of a large page
+/// is CPU-heavy and would otherwise stall the async workers fetching other sites
+pub async fn parse_results_blocking(
+    site: &SiteConfig,
+    html: String,
+    query: &str,
+) -> Vec<SearchResult> {
+    parse_results_blocking_with(site, html, query, None)
+        .await
+        .results
+}
+
+/// [`parse_results_with`] on tokio's blocking pool, like [`parse_results_blocking`]
+pub async fn parse_results_blocking_with(
+    site: &SiteConfig,
+    html: String,
+    query: &str,
+    hint: Option<AnchorPattern>,
+) -> Parsed {
+    let site = site.clone();
+    let query = query.to_string();
+    let parse = move || parse_results_with(&site, &html, &query, hint.as_ref());
+    match tokio::task::spawn_blocking(parse).await {
+        Ok(parsed) => parsed,
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        // Only when the runtime is shutting down
+        Err(_) => Parsed::default(),
+    }
+}
+
+/// Record where freshly parsed `results` came from: the page URL, each
+/// result's 1-based position on it, the fetch time (Unix seconds) and the
+/// fetch path. Call before any filtering so ranks match the page.
+pub fn stamp_source(
+    results: &mut [SearchResult],
+    source_url: &str,
+    fetched_at: u64,
+    provenance: Provenance,
+) {
+    for (i, r) in results.iter_mut().enumerate() {
+        r.source_url = Some(source_url.to_string());
+        r.rank = Some(i + 1);
+        r.fetched_at = Some(fetched_at);
+        r.provenance = Some(provenance);
+    }
+}
+
+/// Current time as stored in [`SearchResult::fetched_at`]
+pub fn fetched_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
 fn derive_title_from_href(href: &str) -> Option<String> {
     // Try last path segment
     let mut segment = href;
@@ -269,19 +564,9 @@ fn looks_like_date_ddmmyyyy(s: &str) -> bool {
     t.chars().all(|c| c.is_ascii_digit() || c == '/')
 }
 
-fn filter_and_normalize_fitgirl(url: &str, title: &str) -> Option<String> {
-    // Drop pagination and comment anchors
-    if url.contains("/page/") || url.contains("#respond") || url.contains("?s=") {
-        return None;
-    }
-    // Drop tag/category/archive and inquiry pages
-    let url_l = url.to_lowercase();
-    if url_l.contains("/tag/") || url_l.contains("/category/") || url_l.contains("/categories/") {
-        return None;
-    }
-    if url_l.contains("/inquiry") || url_l.contains("/inquery") {
-        return None;
-    }
+/// Drops the fitgirl titles its rules can't describe: post numbers, dates and
+/// comment counts (URL and teaser filtering is in its `rules`)
+fn filter_and_normalize_fitgirl(title: &str) -> Option<String> {
     let t = title.trim();
     if t.is_empty() {
         return None;
@@ -295,19 +580,12 @@ fn filter_and_normalize_fitgirl(url: &str, title: &str) -> Option<String> {
     if looks_like_date_ddmmyyyy(t) {
         return None;
     }
-
-    // Drop "Continue reading ..." teaser links (we keep the main post link instead)
-    if t.to_lowercase().starts_with("continue reading") {
-        return None;
-    }
     Some(t.to_string())
 }
 
-fn filter_and_normalize_steamrip(url: &str, title: &str) -> Option<String> {
-    // Drop obvious pagination and search navigational links
-    if url.contains("/page/") || url.contains("?s=") {
-        return None;
-    }
+/// Drops steamrip's next/previous and page number links (pagination URLs are
+/// dropped by its `rules`)
+fn filter_and_normalize_steamrip(title: &str) -> Option<String> {
     let t = title.trim();
     if t.is_empty() {
         return None;
@@ -324,6 +602,7 @@ fn filter_and_normalize_steamrip(url: &str, title: &str) -> Option<String> {
 
 fn parse_elamigos(site: &SiteConfig, html: &str, query: &str) -> Vec<SearchResult> {
     let document = Html::parse_document(html);
+    let site_name: Arc<str> = Arc::from(site.name.as_str());
     let Ok(sel) = Selector::parse("h3, h5") else {
         return Vec::new();
     };
@@ -365,9 +644,10 @@ fn parse_elamigos(site: &SiteConfig, html: &str, query: &str) -> Vec<SearchResul
             // Title: remove trailing DOWNLOAD and trim
             let title = text_norm.replace("DOWNLOAD", "").trim().to_string();
             results.push(SearchResult {
-                site: site.name.to_string(),
+                site: site_name.clone(),
                 title,
                 url,
+                ..Default::default()
             });
         }
     }
@@ -379,6 +659,7 @@ fn parse_elamigos(site: &SiteConfig, html: &str, query: &str) -> Vec<SearchResul
 /// Extracts game titles from thread links like [Game Name [vX.X] [Developer]]
 fn parse_f95zone(site: &SiteConfig, html: &str, query: &str) -> Vec<SearchResult> {
     let document = Html::parse_document(html);
+    let site_name: Arc<str> = Arc::from(site.name.as_str());
     let Ok(sel) = Selector::parse("a[href*='/threads/']") else {
         return Vec::new();
     };
@@ -437,9 +718,10 @@ fn parse_f95zone(site: &SiteConfig, html: &str, query: &str) -> Vec<SearchResult
 
         seen_urls.insert(url.clone());
         results.push(SearchResult {
-            site: site.name.to_string(),
+            site: site_name.clone(),
             title,
             url,
+            ..Default::default()
         });
 
         if results.len() >= 50 {
@@ -454,6 +736,7 @@ fn parse_f95zone(site: &SiteConfig, html: &str, query: &str) -> Vec<SearchResult
 /// Filters navigation links and extracts game titles
 fn parse_nswpedia(site: &SiteConfig, html: &str, query: &str) -> Vec<SearchResult> {
     let document = Html::parse_document(html);
+    let site_name: Arc<str> = Arc::from(site.name.as_str());
     // Match h2 elements that contain links (search result cards)
     let Ok(sel) = Selector::parse("h2 a, article a, .post-title a") else {
         return Vec::new();
@@ -515,9 +798,10 @@ fn parse_nswpedia(site: &SiteConfig, html: &str, query: &str) -> Vec<SearchResul
 
         seen_urls.insert(url.clone());
         results.push(SearchResult {
-            site: site.name.to_string(),
+            site: site_name.clone(),
             title,
             url,
+            ..Default::default()
         });
 
         if results.len() >= 50 {
@@ -528,6 +812,133 @@ fn parse_nswpedia(site: &SiteConfig, html: &str, query: &str) -> Vec<SearchResul
     results
 }
 
+/// A link on the page whose text contains the query
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct AnchorSample {
+    pub text: String,
+    pub href: String,
+}
+
+/// How a fetched page looked to the parser, for reports when a site stops parsing
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ParserDiagnostics {
+    pub site: String,
+    pub query: String,
+    pub html_bytes: usize,
+    /// Contents of the page's `<title>`
+    pub page_title: Option<String>,
+    /// Whether the page looks like a Cloudflare challenge instead of results
+    pub looks_like_challenge: bool,
+    pub result_selector: ResultSelector,
+    /// Position in `result_selector` of the selector the results came from
+    pub matched_selector: Option<usize>,
+    /// Whether they came from the page's `<noscript>` HTML, which the
+    /// selector counts below are then taken from
+    pub noscript: bool,
+    /// Elements matching that selector, else the first one that parses;
+    /// `None` if none does
+    pub selector_matches: Option<usize>,
+    pub title_attr: String,
+    pub url_attr: String,
+    /// Matching elements a URL was found in with `url_attr`; fewer than
+    /// `selector_matches` means the attribute is elsewhere on those cards
+    pub selector_urls: Option<usize>,
+    /// Results `parse_results` returned for the page
+    pub results: usize,
+    pub anchors: usize,
+    /// Up to five links whose text contains the query
+    pub anchors_matching_query: Vec<AnchorSample>,
+    pub articles: usize,
+    pub entry_titles: usize,
+}
+
+/// Parse `html` as `site` would and describe what was found
+pub fn diagnose(site: &SiteConfig, html: &str, query: &str) -> ParserDiagnostics {
+    let parsed = parse_results_with(site, html, query, None);
+    let results = parsed.results.len();
+    let doc = Html::parse_document(html);
+    let noscript = parsed.noscript.then(|| noscript_document(&doc)).flatten();
+    let selected = noscript.as_ref().unwrap_or(&doc);
+    let count = |selector: &str| {
+        Selector::parse(selector)
+            .ok()
+            .map(|sel| doc.select(&sel).count())
+    };
+    let selector = match parsed.selector {
+        Some(position) => site.result_selector.iter().nth(position),
+        None => site
+            .result_selector
+            .iter()
+            .find(|s| Selector::parse(s).is_ok()),
+    };
+
+    let page_title = Selector::parse("title").ok().and_then(|sel| {
+        doc.select(&sel)
+            .next()
+            .map(|t| t.text().collect::<String>().trim().to_string())
+    });
+    let looks_like_challenge = page_title
+        .as_deref()
+        .is_some_and(|t| t.contains("Just a moment") || t.contains("Attention Required"))
+        || html.contains("cf-challenge")
+        || html.contains("challenge-platform");
+
+    let selector_urls = selector.and_then(|s| Selector::parse(s).ok()).map(|sel| {
+        let url_from =
+            Extract::url(&site.url_attr).unwrap_or_else(|_| Extract::Attr("href".into()));
+        selected
+            .select(&sel)
+            .filter(|el| url_from.find(*el).is_some())
+            .count()
+    });
+
+    let ql = query.to_lowercase();
+    let mut anchors = 0;
+    let mut anchors_matching_query = Vec::new();
+    if let Ok(sel) = Selector::parse("a[href]") {
+        for a in doc.select(&sel) {
+            anchors += 1;
+            let text = a.text().collect::<String>();
+            if anchors_matching_query.len() < 5 && text.to_lowercase().contains(&ql) {
+                anchors_matching_query.push(AnchorSample {
+                    text: text.trim().chars().take(80).collect(),
+                    href: a
+                        .value()
+                        .attr("href")
+                        .unwrap_or("")
+                        .chars()
+                        .take(120)
+                        .collect(),
+                });
+            }
+        }
+    }
+
+    ParserDiagnostics {
+        site: site.name.clone(),
+        query: query.to_string(),
+        html_bytes: html.len(),
+        page_title,
+        looks_like_challenge,
+        result_selector: site.result_selector.clone(),
+        matched_selector: parsed.selector,
+        noscript: parsed.noscript,
+        selector_matches: selector.and_then(|s| {
+            Selector::parse(s)
+                .ok()
+                .map(|sel| selected.select(&sel).count())
+        }),
+        title_attr: site.title_attr.clone(),
+        url_attr: site.url_attr.clone(),
+        selector_urls,
+        results,
+        anchors,
+        anchors_matching_query,
+        articles: count("article").unwrap_or(0),
+        entry_titles: count("h2.entry-title, h1.entry-title, .entry-title").unwrap_or(0),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -538,8 +949,9 @@ mod tests {
             base_url: "https://example.com/".to_string(),
             search_kind: crate::models::SearchKind::QueryParam,
             query_param: Some("s".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "h2.entry-title a".to_string(),
+            result_selector: "h2.entry-title a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -547,17 +959,34 @@ mod tests {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: Default::default(),
         }
     }
 
+    /// The test config under `name`, with that site's post-processing rules
+    fn cfg_named(name: &str) -> SiteConfig {
+        let mut cfg = cfg();
+        cfg.name = name.to_string();
+        cfg.rules = crate::config::site_configs()
+            .into_iter()
+            .find(|s| s.name == name)
+            .map(|s| s.rules)
+            .unwrap_or_default();
+        cfg
+    }
+
     fn cfg_with_selector(selector: &str) -> SiteConfig {
         SiteConfig {
             name: "example".to_string(),
             base_url: "https://example.com/".to_string(),
             search_kind: crate::models::SearchKind::QueryParam,
             query_param: Some("s".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: selector.to_string(),
+            result_selector: selector.into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -565,9 +994,99 @@ mod tests {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: Default::default(),
         }
     }
 
+    #[test]
+    fn diagnose_reports_selector_matches_and_challenges() {
+        let html = r#"<html><head><title>Search: elden</title></head><body>
+            <article><h2 class="entry-title"><a href="https://example.com/elden-ring/">Elden Ring</a></h2></article>
+            <a href="/about">About</a></body></html>"#;
+        let report = diagnose(&cfg(), html, "elden");
+        assert_eq!(report.page_title.as_deref(), Some("Search: elden"));
+        assert!(!report.looks_like_challenge);
+        assert_eq!(report.selector_matches, Some(1));
+        assert_eq!(report.selector_urls, Some(1));
+        assert_eq!(report.results, 1);
+        assert_eq!(report.anchors, 2);
+        assert_eq!(report.anchors_matching_query[0].text, "Elden Ring");
+        assert_eq!((report.articles, report.entry_titles), (1, 1));
+
+        let challenge = "<html><head><title>Just a moment...</title></head></html>";
+        let report = diagnose(&cfg_with_selector("a[["), challenge, "elden");
+        assert!(report.looks_like_challenge);
+        assert_eq!(report.selector_matches, None);
+        assert_eq!(report.results, 0);
+    }
+
+    #[test]
+    fn stamped_results_keep_their_page_and_position() {
+        let html = r#"<h2 class="entry-title"><a href="/elden-ring/">Elden Ring</a></h2>
+            <h2 class="entry-title"><a href="/elden-ring-dlc/">Elden Ring DLC</a></h2>"#;
+        let mut results = parse_results(&cfg(), html, "elden");
+        stamp_source(
+            &mut results,
+            "https://example.com/?s=elden",
+            1_700_000_000,
+            Provenance::Solver,
+        );
+        let ranks: Vec<_> = results.iter().map(|r| r.rank).collect();
+        assert_eq!(ranks, [Some(1), Some(2)]);
+        assert!(results.iter().all(|r| {
+            r.source_url.as_deref() == Some("https://example.com/?s=elden")
+                && r.fetched_at == Some(1_700_000_000)
+                && r.provenance == Some(Provenance::Solver)
+        }));
+        let json = serde_json::to_value(&results[0]).unwrap();
+        assert_eq!(json["provenance"], "solver");
+
+        // Same title on one site: page order decides, whatever order they arrived in
+        let mut dupes = vec![results[0].clone(), results[0].clone()];
+        dupes[0].rank = Some(3);
+        dupes.sort_by(SearchResult::output_order);
+        assert_eq!(dupes[0].rank, Some(1));
+
+        // Results saved before these fields existed still load
+        let old: SearchResult =
+            serde_json::from_str(r#"{"site":"example","title":"Elden Ring","url":"/x"}"#).unwrap();
+        assert_eq!(
+            (old.source_url, old.rank, old.fetched_at, old.provenance),
+            (None, None, None, None)
+        );
+    }
+
+    #[tokio::test]
+    async fn blocking_parse_matches_inline_parse() {
+        let html = r#"<h2 class="entry-title"><a href="/elden-ring/">Elden Ring</a></h2>"#;
+        let inline = parse_results(&cfg(), html, "elden");
+        let blocking = parse_results_blocking(&cfg(), html.to_string(), "elden").await;
+        assert_eq!(blocking, inline);
+        assert_eq!(blocking.len(), 1);
+    }
+
+    #[test]
+    fn results_share_the_site_name_and_query_variants_match_slugs() {
+        let html = r#"<h2 class="entry-title"><a href="/elden-ring/">Elden Ring</a></h2>
+            <h2 class="entry-title"><a href="/elden-ring-dlc/">Elden Ring DLC</a></h2>"#;
+        let results = parse_results(&cfg(), html, "elden ring");
+        assert_eq!(results.len(), 2);
+        assert!(Arc::ptr_eq(&results[0].site, &results[1].site));
+
+        let variants = QueryVariants::new("Elden Ring");
+        assert!(variants.matches("", "https://example.com/elden-ring/"));
+        assert!(variants.matches("", "https://example.com/?s=elden+ring"));
+        assert!(variants.matches("", "https://example.com/eldenring"));
+        assert!(!variants.matches("dark souls", "https://example.com/dark-souls/"));
+
+        let mut buf = String::from("previous contents");
+        lowercase_into(&mut buf, "ELDEN Ring");
+        assert_eq!(buf, "elden ring");
+    }
+
     #[test]
     fn primary_selector_is_filtered_by_query() {
         let cfg = cfg_with_selector("a");
@@ -592,6 +1111,159 @@ mod tests {
         assert_eq!(results[0].url, "https://example.com/viewtopic.php?t=12345");
     }
 
+    #[test]
+    fn titles_and_urls_come_from_the_configured_attributes() {
+        let mut cfg = cfg_with_selector("div.card");
+        cfg.title_attr = "data-title".into();
+        cfg.url_attr = "data-href".into();
+        let html = r#"<html><body>
+            <div class="card" data-title="Elden Ring" data-href="/game/elden-ring">Download</div>
+            <div class="card" data-href="/game/elden-ring-nightreign">Download</div>
+        </body></html>"#;
+        let results = parse_results(&cfg, html, "elden ring");
+        let found: Vec<(&str, &str)> = results
+            .iter()
+            .map(|r| (r.title.as_str(), r.url.as_str()))
+            .collect();
+        // Without the attribute the title comes from the URL, not the text
+        assert_eq!(
+            found,
+            [
+                ("Elden Ring", "https://example.com/game/elden-ring"),
+                (
+                    "Elden Ring Nightreign",
+                    "https://example.com/game/elden-ring-nightreign"
+                )
+            ]
+        );
+
+        cfg.title_attr = "json:data-props:/name".into();
+        cfg.url_attr = "json:data-props:/links/0".into();
+        let html = r#"<html><body>
+            <div class="card" data-props='{"name":"Elden Ring","links":["https://cdn.example/er"]}'></div>
+        </body></html>"#;
+        let results = parse_results(&cfg, html, "elden ring");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "Elden Ring");
+        assert_eq!(results[0].url, "https://cdn.example/er");
+    }
+
+    #[test]
+    fn cloudflare_protected_addresses_are_decoded_in_titles() {
+        // "v1.0@FitGirl" behind Cloudflare's email protection, key 0x1f
+        let html = r#"<html><body>
+            <h2 class="entry-title"><a href="/hades/">Hades <span class="__cf_email__" data-cfemail="1f692e312f5f59766b58766d73">[email&#160;protected]</span></a></h2>
+            <h2 class="entry-title"><a href="/cdn-cgi/l/email-protection#1f777e7b7a6c5f7e317a677e726f737a317c7072">Hades fan mail</a></h2>
+        </body></html>"#;
+        let results = parse_results(&cfg(), html, "hades");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "Hades v1.0@FitGirl");
+        assert_eq!(results[0].url, "https://example.com/hades/");
+    }
+
+    #[test]
+    fn result_selectors_are_tried_in_order_until_one_finds_results() {
+        let mut cfg = cfg();
+        cfg.result_selector = ["[[", "article.game-card a", "h2.entry-title a"].into();
+        let old_theme = r#"<html><body>
+            <h2 class="entry-title"><a href="/elden-ring/">Elden Ring</a></h2>
+        </body></html>"#;
+        let parsed = parse_results_with(&cfg, old_theme, "elden ring", None);
+        assert_eq!(parsed.results.len(), 1);
+        assert_eq!(parsed.selector, Some(2));
+        let report = diagnose(&cfg, old_theme, "elden ring");
+        assert_eq!(
+            (report.matched_selector, report.selector_matches),
+            (Some(2), Some(1))
+        );
+
+        // A match that isn't a result for the query moves on to the next
+        let new_theme = r#"<html><body>
+            <article class="game-card"><a href="/hades/">Hades</a></article>
+            <article class="game-card"><a href="/elden-ring/">Elden Ring</a></article>
+            <h2 class="entry-title"><a href="/news/">News</a></h2>
+        </body></html>"#;
+        let parsed = parse_results_with(&cfg, new_theme, "elden ring", None);
+        assert_eq!(parsed.selector, Some(1));
+        let parsed = parse_results_with(&cfg, new_theme, "hades", None);
+        assert_eq!(parsed.results[0].title, "Hades");
+        assert_eq!(parsed.selector, Some(1));
+    }
+
+    #[test]
+    fn noscript_results_are_read_when_the_page_has_none() {
+        let cfg = cfg_with_selector("article.game a");
+        let html = r#"<html><body>
+            <nav><a href="/">Home</a></nav>
+            <div id="results" data-lazy="/api/results?s=elden+ring"></div>
+            <noscript>
+                <article class="game"><a href="/elden-ring/">Elden Ring &amp; DLC</a></article>
+                <article class="game"><a href="/hades/">Hades</a></article>
+            </noscript>
+        </body></html>"#;
+        let parsed = parse_results_with(&cfg, html, "elden ring", None);
+        assert!(parsed.noscript);
+        assert_eq!(parsed.results.len(), 1);
+        assert_eq!(parsed.results[0].title, "Elden Ring & DLC");
+        assert_eq!(parsed.results[0].url, "https://example.com/elden-ring/");
+        let report = diagnose(&cfg, html, "elden ring");
+        assert_eq!((report.noscript, report.selector_matches), (true, Some(2)));
+
+        // Results on the page itself win over the <noscript> copy
+        let html = r#"<html><body>
+            <article class="game"><a href="/elden-ring-live/">Elden Ring</a></article>
+            <noscript><article class="game"><a href="/elden-ring/">Elden Ring</a></article></noscript>
+        </body></html>"#;
+        let parsed = parse_results_with(&cfg, html, "elden ring", None);
+        assert!(!parsed.noscript);
+        assert_eq!(
+            parsed.results[0].url,
+            "https://example.com/elden-ring-live/"
+        );
+    }
+
+    #[test]
+    fn card_layouts_are_read_from_the_elements_inside_the_card() {
+        let mut cfg = cfg_with_selector("div.card");
+        cfg.title_attr = "title".into();
+        let html = r#"<html><body>
+            <div class="card"><span class="badge">New</span>
+                <a href="/game/elden-ring" title="Elden Ring">Download</a></div>
+            <div class="card"><span class="badge">Sale</span></div>
+        </body></html>"#;
+        let results = parse_results(&cfg, html, "elden ring");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "Elden Ring");
+        assert_eq!(results[0].url, "https://example.com/game/elden-ring");
+
+        let report = diagnose(&cfg, html, "elden ring");
+        assert_eq!(report.selector_matches, Some(2));
+        assert_eq!(report.selector_urls, Some(1));
+    }
+
+    #[test]
+    fn results_take_the_image_of_their_own_card() {
+        let cfg = cfg();
+        let html = r#"<html><body><main>
+            <img src="/logo.png">
+            <article>
+                <img src="data:image/gif;base64,R0lGOD" data-lazy-src="/covers/elden-ring.jpg">
+                <h2 class="entry-title"><a href="/elden-ring/">Elden Ring</a></h2>
+            </article>
+            <article>
+                <h2 class="entry-title"><a href="/elden-ring-nightreign/">Elden Ring Nightreign</a></h2>
+            </article>
+        </main></body></html>"#;
+        let results = parse_results(&cfg, html, "elden ring");
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[0].image.as_deref(),
+            Some("https://example.com/covers/elden-ring.jpg")
+        );
+        // A card without an image doesn't borrow the page's
+        assert_eq!(results[1].image, None);
+    }
+
     #[test]
     fn fallback_finds_query_text() {
         let html = r#"<html><body>
@@ -612,6 +1284,46 @@ mod tests {
         assert!(urls.contains(&"https://other.com/x".to_string()));
     }
 
+    #[test]
+    fn fallback_learns_the_common_link_pattern_and_tries_it_first() {
+        let html = r#"<html><body>
+            <nav><a href="/tag/elden-ring/">Elden Ring</a></nav>
+            <div class="card"><a href="/game/elden-ring/">Elden Ring</a></div>
+            <div class="card"><a href="/game/elden-ring-nightreign/">Elden Ring Nightreign</a></div>
+        </body></html>"#;
+        let parsed = parse_results_with(&cfg(), html, "elden ring", None);
+        assert_eq!(parsed.results.len(), 3);
+        let learned = parsed.learned.unwrap();
+        assert_eq!(learned.href_prefix, "https://example.com/game/");
+        assert_eq!(learned.parent_class.as_deref(), Some("card"));
+
+        // Next time only the links fitting the pattern are kept
+        let parsed = parse_results_with(&cfg(), html, "elden ring", Some(&learned));
+        let urls: Vec<&str> = parsed.results.iter().map(|r| r.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://example.com/game/elden-ring/",
+                "https://example.com/game/elden-ring-nightreign/"
+            ]
+        );
+        assert_eq!(parsed.learned, Some(learned.clone()));
+
+        // A pattern that no longer fits falls back to the full scan
+        let stale = AnchorPattern::of("https://example.com/post/x", None).unwrap();
+        let parsed = parse_results_with(&cfg(), html, "elden ring", Some(&stale));
+        assert_eq!(parsed.results.len(), 3);
+        assert_eq!(parsed.learned, Some(learned));
+
+        // Nothing is learned when the site's selector works
+        let html = r#"<h2 class="entry-title"><a href="/elden-ring/">Elden Ring</a></h2>"#;
+        assert!(
+            parse_results_with(&cfg(), html, "elden ring", None)
+                .learned
+                .is_none()
+        );
+    }
+
     #[test]
     fn derives_title_from_empty_anchor_text() {
         let html = r#"<html><body>
@@ -635,8 +1347,7 @@ mod tests {
 
     #[test]
     fn fitgirl_filters_and_normalizes() {
-        let mut cfg = cfg();
-        cfg.name = "fitgirl".to_string();
+        let cfg = cfg_named("fitgirl");
         let html = r#"<html><body>
             <a href="/page/2">Elden Ring Page</a>
             <a href="/post/1">12345</a>
@@ -658,8 +1369,9 @@ mod tests {
             base_url: "https://elamigos.site/".to_string(),
             search_kind: crate::models::SearchKind::FrontPage,
             query_param: None,
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "ignored".to_string(),
+            result_selector: "ignored".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -667,6 +1379,10 @@ mod tests {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: Default::default(),
         };
         let html = r#"<html><body>
             <h3><a href="/post/elden-ring">ELDEN RING DOWNLOAD</a></h3>
@@ -685,8 +1401,9 @@ mod tests {
             base_url: "https://f95zone.to".to_string(),
             search_kind: crate::models::SearchKind::QueryParam,
             query_param: Some("q".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "a".to_string(),
+            result_selector: "a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -694,6 +1411,10 @@ mod tests {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: Default::default(),
         };
         let html = r#"<html><body>
             <a href="/threads/elden-ring-nightreign.12345/">Elden Ring Nightreign [v1.0] [FromSoft]</a>
@@ -713,8 +1434,9 @@ mod tests {
             base_url: "https://f95zone.to".to_string(),
             search_kind: crate::models::SearchKind::QueryParam,
             query_param: Some("q".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "a".to_string(),
+            result_selector: "a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -722,6 +1444,10 @@ mod tests {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: Default::default(),
         };
         let html = r#"<html><body>
             <a href="/threads/elden-ring.12345/">Elden Ring</a>
@@ -738,8 +1464,9 @@ mod tests {
             base_url: "https://nswpedia.com".to_string(),
             search_kind: crate::models::SearchKind::QueryParam,
             query_param: Some("s".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "h2 a".to_string(),
+            result_selector: "h2 a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -747,6 +1474,10 @@ mod tests {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: Default::default(),
         };
         let html = r#"<html><body>
             <h2><a href="https://nswpedia.com/zelda-tears-kingdom/">Zelda Tears of the Kingdom</a></h2>
@@ -766,8 +1497,9 @@ mod tests {
             base_url: "https://nswpedia.com".to_string(),
             search_kind: crate::models::SearchKind::QueryParam,
             query_param: Some("s".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "h2 a".to_string(),
+            result_selector: "h2 a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -775,6 +1507,10 @@ mod tests {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: Default::default(),
         };
         let html = r#"<html><body>
             <h2><a href="https://nswpedia.com/about">About</a></h2>
@@ -791,8 +1527,9 @@ mod tests {
             base_url: "https://cs.rin.ru/forum".to_string(),
             search_kind: crate::models::SearchKind::PhpBBSearch,
             query_param: Some("keywords".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "a.topictitle".to_string(),
+            result_selector: "a.topictitle".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -800,6 +1537,10 @@ mod tests {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: Default::default(),
         };
         // Simulate search.php results page
         let html = r#"<html><body>search.php
@@ -830,8 +1571,7 @@ mod tests {
 
     #[test]
     fn steamrip_filter_drops_nav_links() {
-        let mut cfg = cfg();
-        cfg.name = "steamrip".to_string();
+        let cfg = cfg_named("steamrip");
         let html = r#"<html><body>
             <a href="/page/2">Next</a>
             <a href="/game?s=test">Previous</a>
@@ -844,8 +1584,7 @@ mod tests {
 
     #[test]
     fn steamrip_filter_drops_numeric_titles() {
-        let mut cfg = cfg();
-        cfg.name = "steamrip".to_string();
+        let cfg = cfg_named("steamrip");
         let html = r#"<html><body>
             <a href="/elden-ring">12345</a>
             <a href="/elden-ring-deluxe">Elden Ring Deluxe</a>
@@ -871,8 +1610,7 @@ mod tests {
 
     #[test]
     fn gog_games_filtering_requires_game_path() {
-        let mut cfg = cfg();
-        cfg.name = "gog-games".to_string();
+        let cfg = cfg_named("gog-games");
         let html = r#"<html><body>
             <a href="/game/elden-ring">Elden Ring</a>
             <a href="/search?q=elden">Search Results</a>
@@ -884,8 +1622,7 @@ mod tests {
 
     #[test]
     fn fitgirl_filters_category_and_tag_urls() {
-        let mut cfg = cfg();
-        cfg.name = "fitgirl".to_string();
+        let cfg = cfg_named("fitgirl");
         let html = r#"<html><body>
             <a href="/category/games">Elden Ring Category</a>
             <a href="/tag/rpg">Elden Ring RPG Tag</a>
@@ -898,8 +1635,7 @@ mod tests {
 
     #[test]
     fn fitgirl_filters_inquiry_pages() {
-        let mut cfg = cfg();
-        cfg.name = "fitgirl".to_string();
+        let cfg = cfg_named("fitgirl");
         let html = r#"<html><body>
             <a href="/inquiry/elden-ring">Elden Ring Inquiry</a>
             <a href="/inquery/elden">Elden Inquery</a>
@@ -917,8 +1653,9 @@ mod tests {
             base_url: "https://elamigos.site/".to_string(),
             search_kind: crate::models::SearchKind::FrontPage,
             query_param: None,
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "h3 a".to_string(),
+            result_selector: "h3 a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -926,6 +1663,10 @@ mod tests {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: Default::default(),
         };
         let html = r#"<html><body>
             <h3><a href="/game/other">Other Game DOWNLOAD</a></h3>
@@ -941,8 +1682,9 @@ mod tests {
             base_url: "https://f95zone.to".to_string(),
             search_kind: crate::models::SearchKind::QueryParam,
             query_param: Some("q".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "a".to_string(),
+            result_selector: "a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -950,6 +1692,10 @@ mod tests {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: Default::default(),
         };
         let html = r#"<html><body>
             <a href="/threads/elden-ring.12345/page-2">Page 2</a>
@@ -982,8 +1728,9 @@ mod tests {
             base_url: "https://example.com/".to_string(),
             search_kind: crate::models::SearchKind::QueryParam,
             query_param: Some("s".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "a".to_string(),
+            result_selector: "a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -991,6 +1738,10 @@ mod tests {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: Default::default(),
         };
         let html = r#"<html><body>
             <a href="/elden-ring"><span class="title">Elden Ring</span></a>
@@ -1006,8 +1757,9 @@ mod tests {
             base_url: "https://cs.rin.ru/forum".to_string(),
             search_kind: crate::models::SearchKind::PhpBBSearch,
             query_param: Some("keywords".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "a.topictitle".to_string(),
+            result_selector: "a.topictitle".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -1015,6 +1767,10 @@ mod tests {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: Default::default(),
         };
         let html = r#"<html><body>search.php
             <a class="topictitle" href="viewtopic.php?t=99">Elden Ring</a>
@@ -1026,13 +1782,13 @@ mod tests {
 
     #[test]
     fn steamrip_filter_empty_title_returns_none() {
-        let result = filter_and_normalize_steamrip("/game", "   ");
+        let result = filter_and_normalize_steamrip("   ");
         assert!(result.is_none());
     }
 
     #[test]
     fn fitgirl_filter_empty_title_returns_none() {
-        let result = filter_and_normalize_fitgirl("/game", "   ");
+        let result = filter_and_normalize_fitgirl("   ");
         assert!(result.is_none());
     }
 
@@ -1043,8 +1799,9 @@ mod tests {
             base_url: "https://nswpedia.com".to_string(),
             search_kind: crate::models::SearchKind::QueryParam,
             query_param: Some("s".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "h2 a".to_string(),
+            result_selector: "h2 a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -1052,6 +1809,10 @@ mod tests {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: Default::default(),
         };
         let html = r#"<html><body>
             <h2><a href="https://other-site.com/zelda">Zelda on Other</a></h2>
@@ -0,0 +1,301 @@
+//! Where the app keeps its files on each platform.
+//!
+//! Files are split by how much they matter:
+//!
+//! | Kind   | Holds                                                                                                                                         | Linux              | macOS                           | Windows          |
+//! | ------ | --------------------------------------------------------------------------------------------------------------------------------------------- | ------------------ | ------------------------------- | ---------------- |
+//! | config | `sites.toml`, `config.toml`                                                                                                                   | `$XDG_CONFIG_HOME` | `~/Library/Application Support` | `%APPDATA%`      |
+//! | data   | the GUI watchlist and favorites, notes on results, the ignore list, encrypted secrets                                                         | `$XDG_DATA_HOME`   | `~/Library/Application Support` | `%APPDATA%`      |
+//! | state  | search history, the results browser's views, timings, site health, patterns, query support, the result index, request counts, the secrets key | `$XDG_STATE_HOME`  | `~/Library/Application Support` | `%LOCALAPPDATA%` |
+//! | cache  | search cache, sitemaps, rendered pages, result images, Steam app list, debug samples                                                          | `$XDG_CACHE_HOME`  | `~/Library/Caches`              | `%LOCALAPPDATA%` |
+//!
+//! each under a `website-searcher` directory. `WEBSITE_SEARCHER_CONFIG_DIR`,
+//! `_DATA_DIR`, `_STATE_DIR`, `_CACHE_DIR` and `_DEBUG_DIR` override them.
+//!
+//! Older versions kept history and the browser's views in the cache directory;
+//! [`migrate_legacy_files`] moves them on startup.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Directory name under each platform directory
+pub const APP_DIR: &str = "website-searcher";
+
+/// `$var` if set, else `base` (or the working directory) joined with [`APP_DIR`]
+fn resolve(var: &str, base: Option<PathBuf>) -> PathBuf {
+    match std::env::var_os(var).filter(|v| !v.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => base.unwrap_or_else(|| PathBuf::from(".")).join(APP_DIR),
+    }
+}
+
+/// User configuration: `sites.toml` and `config.toml`
+pub fn config_dir() -> PathBuf {
+    resolve("WEBSITE_SEARCHER_CONFIG_DIR", dirs::config_dir())
+}
+
+/// Data the user created and would miss, like the watchlist
+pub fn data_dir() -> PathBuf {
+    resolve("WEBSITE_SEARCHER_DATA_DIR", dirs::data_dir())
+}
+
+/// History and UI state worth keeping between runs, but not backing up.
+/// Only Linux has a state directory; elsewhere it is the local data directory.
+pub fn state_dir() -> PathBuf {
+    resolve(
+        "WEBSITE_SEARCHER_STATE_DIR",
+        dirs::state_dir().or_else(dirs::data_local_dir),
+    )
+}
+
+/// Anything that can be fetched again
+pub fn cache_dir() -> PathBuf {
+    resolve("WEBSITE_SEARCHER_CACHE_DIR", dirs::cache_dir())
+}
+
+pub fn search_cache_file() -> PathBuf {
+    cache_dir().join("search_cache.json")
+}
+
+pub fn sitemap_dir() -> PathBuf {
+    cache_dir().join("sitemaps")
+}
+
+/// Pages rendered by FlareSolverr or Playwright, reused for a few minutes
+pub fn rendered_dir() -> PathBuf {
+    cache_dir().join("rendered")
+}
+
+/// Result images the GUI shows on its cards
+pub fn thumbnails_dir() -> PathBuf {
+    cache_dir().join("thumbnails")
+}
+
+/// Steam's app list, for AppIDs of results
+pub fn steam_apps_file() -> PathBuf {
+    cache_dir().join("steam_apps.json")
+}
+
+/// Pages saved by `--debug`: `$WEBSITE_SEARCHER_DEBUG_DIR`, else `debug` in
+/// the cache directory
+pub fn debug_dir() -> PathBuf {
+    match std::env::var_os("WEBSITE_SEARCHER_DEBUG_DIR").filter(|v| !v.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => cache_dir().join("debug"),
+    }
+}
+
+pub fn history_file() -> PathBuf {
+    state_dir().join("search_history.json")
+}
+
+/// Where the results browser left each cached search
+pub fn tui_session_file() -> PathBuf {
+    state_dir().join("tui_session.json")
+}
+
+/// Recent search durations per site, for the start order of the next search
+pub fn site_timings_file() -> PathBuf {
+    state_dir().join("site_timings.json")
+}
+
+/// Per-site streaks of queries that found nothing while other sites matched
+pub fn site_health_file() -> PathBuf {
+    state_dir().join("site_health.json")
+}
+
+/// Link patterns learned for sites whose result selector stopped matching
+pub fn learned_patterns_file() -> PathBuf {
+    state_dir().join("learned_patterns.json")
+}
+
+/// Whether each site's search honors the query (see [`crate::capability`])
+pub fn query_capability_file() -> PathBuf {
+    state_dir().join("query_capability.json")
+}
+
+/// Requests sent to each site today (see [`crate::budget`])
+pub fn request_budget_file() -> PathBuf {
+    state_dir().join("request_budget.json")
+}
+
+/// Every result earlier searches found (see [`crate::result_index`])
+pub fn result_index_file() -> PathBuf {
+    state_dir().join("result_index.json")
+}
+
+/// Key of the encrypted secrets file, kept apart from it (see [`crate::secrets`])
+pub fn secrets_key_file() -> PathBuf {
+    state_dir().join("secrets.key")
+}
+
+/// Results saved in the GUI
+pub fn favorites_file() -> PathBuf {
+    data_dir().join("favorites.json")
+}
+
+pub fn watchlist_file() -> PathBuf {
+    data_dir().join("watchlist.json")
+}
+
+/// Statuses and notes the user attached to result URLs
+pub fn notes_file() -> PathBuf {
+    data_dir().join("notes.json")
+}
+
+/// URLs, domains and title patterns left out of every search
+pub fn ignore_file() -> PathBuf {
+    data_dir().join("ignore.json")
+}
+
+/// Secrets stored when the OS keyring is unavailable, encrypted
+pub fn secrets_file() -> PathBuf {
+    data_dir().join("secrets.bin")
+}
+
+/// Move `from` to `to` unless `to` already exists. Returns whether a file was
+/// moved; a missing `from` is not an error.
+pub fn migrate_file(from: &Path, to: &Path) -> io::Result<bool> {
+    if from == to || !from.is_file() || to.exists() {
+        return Ok(false);
+    }
+    if let Some(dir) = to.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    // A rename fails across file systems, e.g. a cache on tmpfs
+    if std::fs::rename(from, to).is_err() {
+        std::fs::copy(from, to)?;
+        std::fs::remove_file(from)?;
+    }
+    Ok(true)
+}
+
+/// Move files older versions kept in the wrong directory. Failures are logged
+/// and the file is left where it was. Returns the files moved.
+pub fn migrate_legacy_files() -> Vec<PathBuf> {
+    let cache = cache_dir();
+    let moves = [
+        (cache.join("search_history.json"), history_file()),
+        (cache.join("tui_session.json"), tui_session_file()),
+    ];
+    let mut moved = Vec::new();
+    for (from, to) in moves {
+        match migrate_file(&from, &to) {
+            Ok(true) => {
+                tracing::info!("Moved {:?} to {:?}", from, to);
+                moved.push(to);
+            }
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Could not move {:?} to {:?}: {}", from, to, e),
+        }
+    }
+    moved
+}
+
+/// Write `contents` to `path` through a temporary file renamed over it, so
+/// readers and other writers (searches running side by side under `serve`, or
+/// another process) never see a half-written file. The last write wins.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    static WRITES: AtomicUsize = AtomicUsize::new(0);
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    // Each writer has its own temporary file
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let partial = PathBuf::from(partial);
+    std::fs::write(&partial, contents)?;
+    std::fs::rename(&partial, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&partial);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn migrate_moves_once_and_never_overwrites() {
+        let dir = tempdir().unwrap();
+        let old = dir.path().join("cache").join("search_history.json");
+        let new = dir.path().join("state").join("search_history.json");
+        std::fs::create_dir_all(old.parent().unwrap()).unwrap();
+        std::fs::write(&old, "[1]").unwrap();
+
+        assert!(migrate_file(&old, &new).unwrap());
+        assert!(!old.exists());
+        assert_eq!(std::fs::read_to_string(&new).unwrap(), "[1]");
+        // Nothing left to move
+        assert!(!migrate_file(&old, &new).unwrap());
+
+        // A file already at the new location wins
+        std::fs::write(&old, "[2]").unwrap();
+        assert!(!migrate_file(&old, &new).unwrap());
+        assert_eq!(std::fs::read_to_string(&new).unwrap(), "[1]");
+        assert!(old.exists());
+    }
+
+    #[test]
+    fn files_are_split_by_kind() {
+        // Only checks the layout; the directories come from the environment
+        assert_eq!(search_cache_file().parent(), Some(cache_dir().as_path()));
+        assert_eq!(history_file().parent(), Some(state_dir().as_path()));
+        assert_eq!(tui_session_file().parent(), Some(state_dir().as_path()));
+        assert_eq!(site_timings_file().parent(), Some(state_dir().as_path()));
+        assert_eq!(site_health_file().parent(), Some(state_dir().as_path()));
+        assert_eq!(
+            learned_patterns_file().parent(),
+            Some(state_dir().as_path())
+        );
+        assert_eq!(
+            query_capability_file().parent(),
+            Some(state_dir().as_path())
+        );
+        assert_eq!(result_index_file().parent(), Some(state_dir().as_path()));
+        assert_eq!(request_budget_file().parent(), Some(state_dir().as_path()));
+        assert_eq!(secrets_key_file().parent(), Some(state_dir().as_path()));
+        assert_eq!(secrets_file().parent(), Some(data_dir().as_path()));
+        assert_eq!(watchlist_file().parent(), Some(data_dir().as_path()));
+        assert_eq!(favorites_file().parent(), Some(data_dir().as_path()));
+        assert_eq!(notes_file().parent(), Some(data_dir().as_path()));
+        assert_eq!(ignore_file().parent(), Some(data_dir().as_path()));
+        assert!(sitemap_dir().starts_with(cache_dir()));
+        assert!(rendered_dir().starts_with(cache_dir()));
+        assert!(thumbnails_dir().starts_with(cache_dir()));
+    }
+
+    #[test]
+    fn atomic_writes_side_by_side_leave_one_whole_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("state").join("site_timings.json");
+        let writers: Vec<_> = (0..8)
+            .map(|n| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    let contents = format!("[{}]", n.to_string().repeat(100_000));
+                    for _ in 0..10 {
+                        write_atomic(&path, &contents).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        let written = std::fs::read_to_string(&path).unwrap();
+        let digits = &written[1..written.len() - 1];
+        assert_eq!(digits.len(), 100_000);
+        assert!(digits.chars().all(|c| digits.starts_with(c)));
+        // No temporary files left behind
+        assert_eq!(
+            std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+            1
+        );
+    }
+}
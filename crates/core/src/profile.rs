@@ -0,0 +1,111 @@
+//! Search profiles: named trade-offs between speed and completeness.
+//!
+//! `fast` fetches each site's search page directly and gives every site a
+//! short budget; `thorough` uses every fallback, follows result pages past
+//! the first and checks that the result links still answer. The default
+//! profile is what the individual flags and settings say.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// Time a site gets under the fast profile, fallbacks included
+pub const FAST_SITE_BUDGET: Duration = Duration::from_secs(5);
+
+/// Result pages read from each site under the thorough profile
+pub const THOROUGH_PAGES: usize = 3;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchProfile {
+    /// Whatever the flags and settings choose
+    #[default]
+    Default,
+    /// Direct HTTP only, no FlareSolverr or Playwright, 5 s per site
+    Fast,
+    /// Every fallback, several result pages and link verification
+    Thorough,
+}
+
+impl SearchProfile {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Fast => "fast",
+            Self::Thorough => "thorough",
+        }
+    }
+
+    /// Whether FlareSolverr may be used, given whether the user allowed it
+    pub fn use_solver(self, allowed: bool) -> bool {
+        match self {
+            Self::Default => allowed,
+            Self::Fast => false,
+            Self::Thorough => true,
+        }
+    }
+
+    /// Whether the Playwright fallback may be used, given whether the user allowed it
+    pub fn use_playwright(self, allowed: bool) -> bool {
+        match self {
+            Self::Default => allowed,
+            Self::Fast => false,
+            Self::Thorough => true,
+        }
+    }
+
+    /// Per-site budget: an explicit one wins, the fast profile has its own
+    pub fn site_budget(self, explicit: Option<Duration>) -> Option<Duration> {
+        match self {
+            Self::Fast => explicit.or(Some(FAST_SITE_BUDGET)),
+            _ => explicit,
+        }
+    }
+
+    /// Result pages to read from each site that has more
+    pub fn pages(self) -> usize {
+        match self {
+            Self::Thorough => THOROUGH_PAGES,
+            _ => 1,
+        }
+    }
+
+    /// Whether result links are checked before they are shown
+    pub fn verifies_links(self) -> bool {
+        self == Self::Thorough
+    }
+}
+
+impl fmt::Display for SearchProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_override_the_flags_they_cover() {
+        let explicit = Some(Duration::from_secs(20));
+        let default = SearchProfile::Default;
+        assert!(!default.use_solver(false) && default.use_playwright(true));
+        assert_eq!(default.site_budget(None), None);
+        assert_eq!((default.pages(), default.verifies_links()), (1, false));
+
+        let fast = SearchProfile::Fast;
+        assert!(!fast.use_solver(true) && !fast.use_playwright(true));
+        assert_eq!(fast.site_budget(None), Some(FAST_SITE_BUDGET));
+        assert_eq!(fast.site_budget(explicit), explicit);
+
+        let thorough = SearchProfile::Thorough;
+        assert!(thorough.use_solver(false) && thorough.use_playwright(false));
+        assert_eq!(thorough.site_budget(None), None);
+        assert_eq!((thorough.pages(), thorough.verifies_links()), (3, true));
+
+        let parsed: SearchProfile = serde_json::from_str("\"thorough\"").unwrap();
+        assert_eq!(parsed, thorough);
+        assert_eq!(fast.to_string(), "fast");
+    }
+}
@@ -1,3 +1,5 @@
+use crate::form;
+use crate::graphql;
 use crate::models::{SearchKind, SiteConfig};
 use crate::query_parser::AdvancedQuery;
 
@@ -9,30 +11,68 @@ pub fn normalize_query(input: &str) -> String {
     advanced.get_search_terms()
 }
 
+/// URL of `site`'s search results for `query`.
+///
+/// The query and the site's `search_params` are percent-encoded, so `&`, `#`
+/// and non-ASCII text reach the site as typed.
 pub fn build_search_url(site: &SiteConfig, query: &str) -> String {
-    match site.search_kind {
+    let url = match site.search_kind {
         SearchKind::QueryParam => {
             let param = site.query_param.as_deref().unwrap_or("s");
-            let qs = serde_urlencoded::to_string([(param, query)])
-                .unwrap_or_else(|_| format!("{}={}", param, query.replace(' ', "+")));
-            format!("{}?{}", site.base_url, qs)
+            with_query(&site.base_url, &[(param, query)])
         }
         SearchKind::PathEncoded => {
-            // Special: spaces must be %20 per PLAN.md
-            let path = query.replace(' ', "%20");
-            format!("{}{}", site.base_url, path)
+            // The query is a path segment: spaces are %20, '/' and '?' are escaped too
+            format!("{}{}", site.base_url, urlencoding::encode(query))
         }
         SearchKind::FrontPage => site.base_url.to_string(),
         SearchKind::ListingPage => site.base_url.to_string(),
+        SearchKind::Sitemap => site
+            .listing_path
+            .clone()
+            .unwrap_or_else(|| format!("{}/sitemap.xml", site.base_url.trim_end_matches('/'))),
+        SearchKind::Feed => site
+            .listing_path
+            .clone()
+            .unwrap_or_else(|| format!("{}/feed/", site.base_url.trim_end_matches('/'))),
         SearchKind::PhpBBSearch => {
-            // phpBB forum search: search.php?keywords=...&fid[]=10&sr=topics&sf=firstpost
-            let encoded = urlencoding::encode(query);
-            format!(
-                "{}search.php?keywords={}&fid%5B%5D=10&sr=topics&sf=firstpost",
-                site.base_url, encoded
+            // phpBB forum search: search.php?keywords=...; forum ids etc. come from search_params
+            let param = site.query_param.as_deref().unwrap_or("keywords");
+            with_query(&format!("{}search.php", site.base_url), &[(param, query)])
+        }
+        SearchKind::PostForm => {
+            // Names the posted fields; see crate::form
+            let action = site.form.as_ref().map(|f| f.action.as_str());
+            let param = site.query_param.as_deref().unwrap_or("keywords");
+            with_query(
+                &form::resolve(&site.base_url, action.unwrap_or_default()),
+                &[(param, query)],
             )
         }
+        // Takes the search_params itself; see crate::graphql
+        SearchKind::GraphQL => return graphql::search_url(site, query),
+    };
+    let extra: Vec<(&str, &str)> = site
+        .search_params
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    with_query(&url, &extra)
+}
+
+/// `base` with `params` appended as a form-encoded query string, after any
+/// query string `base` already has
+pub fn with_query(base: &str, params: &[(&str, &str)]) -> String {
+    let qs = serde_urlencoded::to_string(params).unwrap_or_default();
+    if qs.is_empty() {
+        return base.to_string();
     }
+    let sep = match base.find('?') {
+        None => "?",
+        Some(_) if base.ends_with(['?', '&']) => "",
+        Some(_) => "&",
+    };
+    format!("{base}{sep}{qs}")
 }
 
 #[cfg(test)]
@@ -91,8 +131,9 @@ mod tests {
             base_url: "https://example.com/".to_string(),
             search_kind: SearchKind::QueryParam,
             query_param: Some("s".to_string()),
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "a".to_string(),
+            result_selector: "a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -100,6 +141,10 @@ mod tests {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: Default::default(),
         };
         let url = build_search_url(&cfg, &normalize_query("elden ring"));
         assert!(url.starts_with("https://example.com/?s="));
@@ -113,8 +158,9 @@ mod tests {
             base_url: "https://ankergames.net/search/".to_string(),
             search_kind: SearchKind::PathEncoded,
             query_param: None,
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "a".to_string(),
+            result_selector: "a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -122,6 +168,10 @@ mod tests {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: Default::default(),
         };
         let url = build_search_url(&cfg, &normalize_query("elden ring"));
         assert_eq!(url, "https://ankergames.net/search/elden%20ring");
@@ -134,8 +184,9 @@ mod tests {
             base_url: "https://front.example/".to_string(),
             search_kind: SearchKind::FrontPage,
             query_param: None,
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "a".to_string(),
+            result_selector: "a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -143,6 +194,10 @@ mod tests {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: Default::default(),
         };
         let url = build_search_url(&cfg, &normalize_query("anything"));
         assert_eq!(url, "https://front.example/");
@@ -155,8 +210,9 @@ mod tests {
             base_url: "https://list.example/".to_string(),
             search_kind: SearchKind::ListingPage,
             query_param: None,
+            search_params: Vec::new(),
             listing_path: None,
-            result_selector: "a".to_string(),
+            result_selector: "a".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -164,6 +220,10 @@ mod tests {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: Default::default(),
         };
         let url = build_search_url(&cfg, &normalize_query("anything"));
         assert_eq!(url, "https://list.example/");
@@ -176,8 +236,13 @@ mod tests {
             base_url: "https://cs.rin.ru/forum/".to_string(),
             search_kind: SearchKind::PhpBBSearch,
             query_param: Some("keywords".to_string()),
+            search_params: vec![
+                ("fid[]".to_string(), "10".to_string()),
+                ("sr".to_string(), "topics".to_string()),
+                ("sf".to_string(), "firstpost".to_string()),
+            ],
             listing_path: Some("https://cs.rin.ru/forum/viewforum.php?f=10".to_string()),
-            result_selector: "a.topictitle".to_string(),
+            result_selector: "a.topictitle".into(),
             title_attr: "text".to_string(),
             url_attr: "href".to_string(),
             requires_js: false,
@@ -185,12 +250,64 @@ mod tests {
             timeout_seconds: 30,
             retry_attempts: 3,
             rate_limit_delay_ms: 1000,
+            crawl: None,
+            form: None,
+            graphql: None,
+            rules: Default::default(),
         };
         let url = build_search_url(&cfg, &normalize_query("elden ring"));
         assert!(url.starts_with("https://cs.rin.ru/forum/search.php?"));
-        assert!(url.contains("keywords=elden%20ring"));
+        assert!(url.contains("keywords=elden+ring"));
         assert!(url.contains("fid%5B%5D=10"));
         assert!(url.contains("sr=topics"));
         assert!(url.contains("sf=firstpost"));
     }
+
+    fn configured(name: &str) -> SiteConfig {
+        crate::config::site_configs()
+            .into_iter()
+            .find(|s| s.name == name)
+            .expect("site is configured")
+    }
+
+    #[test]
+    fn tricky_queries_are_encoded_for_every_kind() {
+        let query = "R&D #2 Pokémon";
+        assert_eq!(
+            build_search_url(&configured("fitgirl"), query),
+            "https://fitgirl-repacks.site/?s=R%26D+%232+Pok%C3%A9mon"
+        );
+        assert_eq!(
+            build_search_url(&configured("ankergames"), query),
+            "https://ankergames.net/search/R%26D%20%232%20Pok%C3%A9mon"
+        );
+        assert_eq!(
+            build_search_url(&configured("csrin"), query),
+            "https://cs.rin.ru/forum/search.php?keywords=R%26D+%232+Pok%C3%A9mon\
+             &fid%5B%5D=10&sr=topics&sf=firstpost"
+        );
+    }
+
+    #[test]
+    fn path_queries_cannot_escape_their_segment() {
+        let url = build_search_url(&configured("ankergames"), "a/b?c=d");
+        assert_eq!(url, "https://ankergames.net/search/a%2Fb%3Fc%3Dd");
+    }
+
+    #[test]
+    fn with_query_appends_to_existing_query_strings() {
+        assert_eq!(
+            with_query("https://x.example/search", &[("q", "a b")]),
+            "https://x.example/search?q=a+b"
+        );
+        assert_eq!(
+            with_query("https://x.example/?f=10", &[("start", "25")]),
+            "https://x.example/?f=10&start=25"
+        );
+        assert_eq!(
+            with_query("https://x.example/?", &[("q", "#1")]),
+            "https://x.example/?q=%231"
+        );
+        assert_eq!(with_query("https://x.example/", &[]), "https://x.example/");
+    }
 }
@@ -9,6 +9,7 @@
 
 use crate::models::SearchResult;
 use regex::Regex;
+use std::collections::HashSet;
 
 /// Multi-query container for pipe-separated queries
 /// Each segment can have its own site restrictions
@@ -68,13 +69,6 @@ impl MultiQuery {
     pub fn segments_for_site(&self, site_name: &str) -> Vec<&AdvancedQuery> {
         let site_lower = site_name.to_lowercase();
 
-        // Check if this site is explicitly mentioned in ANY segment
-        let site_mentioned_anywhere = self.segments.iter().any(|seg| {
-            seg.site_restrictions
-                .iter()
-                .any(|s| site_lower.contains(s) || s.contains(&site_lower))
-        });
-
         self.segments
             .iter()
             .filter(|seg| {
@@ -101,6 +95,31 @@ impl MultiQuery {
             .collect()
     }
 
+    /// The distinct searches to run on a site, each with the index of its
+    /// segment: the search terms of each segment that applies to it, or
+    /// `fallback` for the first of them when none has any. Segments with the
+    /// same terms share the first one's search.
+    pub fn site_searches(&self, site_name: &str, fallback: &str) -> Vec<(usize, String)> {
+        let applicable = self.segments_for_site(site_name);
+        let index = |seg: &AdvancedQuery| {
+            self.segments
+                .iter()
+                .position(|s| std::ptr::eq(s, seg))
+                .unwrap_or(0)
+        };
+        let mut seen = HashSet::new();
+        let mut searches: Vec<(usize, String)> = applicable
+            .iter()
+            .map(|seg| (index(seg), seg.get_search_terms()))
+            .filter(|(_, terms)| !terms.is_empty() && seen.insert(terms.to_lowercase()))
+            .collect();
+        if searches.is_empty() {
+            let first = applicable.first().map_or(0, |seg| index(seg));
+            searches.push((first, fallback.to_string()));
+        }
+        searches
+    }
+
     /// Filter results for a specific site using applicable segments
     pub fn filter_results_for_site(
         &self,
@@ -277,6 +296,16 @@ impl AdvancedQuery {
         true
     }
 
+    /// Check if every plain search term appears in the result title or URL
+    pub fn matches_terms(&self, result: &SearchResult) -> bool {
+        let title_lower = result.title.to_lowercase();
+        let url_lower = result.url.to_lowercase();
+        self.terms.iter().all(|t| {
+            let t = t.to_lowercase();
+            title_lower.contains(&t) || url_lower.contains(&t)
+        })
+    }
+
     /// Get site filter list if any restrictions are present
     pub fn get_sites_filter(&self) -> Option<Vec<String>> {
         if self.site_restrictions.is_empty() {
@@ -354,9 +383,10 @@ mod tests {
 
     fn make_result(site: &str, title: &str, url: &str) -> SearchResult {
         SearchResult {
-            site: site.to_string(),
+            site: site.into(),
             title: title.to_string(),
             url: url.to_string(),
+            ..Default::default()
         }
     }
 
@@ -668,6 +698,27 @@ mod tests {
         assert!(terms.contains(&"minecraft".to_string()));
     }
 
+    #[test]
+    fn test_multi_query_site_searches() {
+        let mq = MultiQuery::parse("elden ring | Elden Ring -demo | minecraft site:csrin");
+        // One search per segment, never the terms of several joined together
+        let searches = mq.site_searches("csrin", "x");
+        assert_eq!(
+            searches,
+            [(0, "elden ring".to_string()), (2, "minecraft".to_string())]
+        );
+        assert_eq!(
+            mq.site_searches("dodi", "x"),
+            [(0, "elden ring".to_string())]
+        );
+
+        let only_regex = MultiQuery::parse(r"regex:v1\.\d");
+        assert_eq!(
+            only_regex.site_searches("dodi", "v1 d"),
+            [(0, "v1 d".to_string())]
+        );
+    }
+
     #[test]
     fn test_multi_query_filter_results() {
         let mq = MultiQuery::parse("elden ring site:fitgirl | minecraft site:csrin");
@@ -679,15 +730,38 @@ mod tests {
             make_result("csrin", "Minecraft", "https://c.com/2"),
         ];
 
-        // For fitgirl, only "elden ring" segment applies
+        // For fitgirl, only "elden ring" segment applies; it has no operators,
+        // and which titles match its terms is up to the site's search
         let fitgirl_filtered = mq.filter_results_for_site(results.clone(), "fitgirl");
-        assert_eq!(fitgirl_filtered.len(), 1);
-        assert!(fitgirl_filtered[0].title.contains("Elden Ring"));
+        assert_eq!(fitgirl_filtered.len(), 2);
+        assert!(fitgirl_filtered.iter().all(|r| &*r.site == "fitgirl"));
 
         // For csrin, only "minecraft" segment applies
         let csrin_filtered = mq.filter_results_for_site(results, "csrin");
-        assert_eq!(csrin_filtered.len(), 1);
-        assert!(csrin_filtered[0].title.contains("Minecraft"));
+        assert_eq!(csrin_filtered.len(), 2);
+        assert!(csrin_filtered.iter().all(|r| &*r.site == "csrin"));
+    }
+
+    #[test]
+    fn test_multi_query_filter_results_by_segment_operators() {
+        let mq = MultiQuery::parse("elden ring -nightreign site:fitgirl | gta 5 site:csrin");
+
+        let results = vec![
+            make_result("fitgirl", "Elden Ring", "https://f.com/1"),
+            make_result("fitgirl", "Elden Ring Nightreign", "https://f.com/2"),
+            make_result("csrin", "Elden Ring Nightreign", "https://c.com/1"),
+            make_result("csrin", "Grand Theft Auto V", "https://c.com/2"),
+        ];
+
+        // For fitgirl, only the "elden ring" segment's exclusion applies
+        let fitgirl_filtered = mq.filter_results_for_site(results.clone(), "fitgirl");
+        assert_eq!(fitgirl_filtered.len(), 1);
+        assert_eq!(fitgirl_filtered[0].title, "Elden Ring");
+
+        // "gta 5" finds "Grand Theft Auto V", which has neither term
+        let csrin_filtered = mq.filter_results_for_site(results, "csrin");
+        assert_eq!(csrin_filtered.len(), 2);
+        assert_eq!(csrin_filtered[1].title, "Grand Theft Auto V");
     }
 
     #[test]
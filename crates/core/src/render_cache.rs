@@ -0,0 +1,222 @@
+//! Pages rendered by FlareSolverr or Playwright, kept for a few minutes.
+//!
+//! Rendering a page costs 10–20 seconds, and the same search is often run
+//! again right away while a query is being tuned. Each rendered page is saved
+//! as one JSON file in `rendered/` in the cache directory, named after a hash
+//! of its key (the renderer and URL, plus a hash of any cookie sent). Entries
+//! older than [`RENDER_TTL`] are ignored, and once the directory grows past
+//! [`MAX_BYTES`] the oldest entries are deleted.
+//!
+//! The CLI and the GUI [`activate`] the cache at startup unless search caching
+//! is off; without it, [`through`] just renders.
+
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long a rendered page is reused
+pub const RENDER_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Size of the cache directory past which the oldest pages are deleted
+pub const MAX_BYTES: u64 = 50 * 1024 * 1024;
+
+static ACTIVE: OnceLock<RenderCache> = OnceLock::new();
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    key: String,
+    /// Unix timestamp (seconds) of the render
+    fetched_at: u64,
+    html: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderCache {
+    dir: PathBuf,
+    ttl: Duration,
+    max_bytes: u64,
+}
+
+fn hash_of(value: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Cache key of `target` rendered by `renderer`; a `cookie` sent along makes
+/// a separate entry without being stored
+pub fn key(renderer: &str, target: &str, cookie: Option<&str>) -> String {
+    match cookie.filter(|c| !c.is_empty()) {
+        Some(cookie) => format!("{renderer}:{target}#{:016x}", hash_of(cookie)),
+        None => format!("{renderer}:{target}"),
+    }
+}
+
+impl RenderCache {
+    /// A cache in `dir` with the default TTL and size
+    pub fn new(dir: PathBuf) -> Self {
+        Self::with_limits(dir, RENDER_TTL, MAX_BYTES)
+    }
+
+    pub fn with_limits(dir: PathBuf, ttl: Duration, max_bytes: u64) -> Self {
+        Self {
+            dir,
+            ttl,
+            max_bytes,
+        }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}.json", hash_of(key)))
+    }
+
+    fn read(path: &Path) -> Option<Entry> {
+        serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+    }
+
+    /// The page rendered for `key`, if it is younger than the TTL
+    pub fn get(&self, key: &str) -> Option<String> {
+        let entry = Self::read(&self.path(key)).filter(|e| e.key == key)?;
+        (now_secs().saturating_sub(entry.fetched_at) < self.ttl.as_secs()).then_some(entry.html)
+    }
+
+    /// Save the page rendered for `key`, then delete expired and, past the
+    /// size limit, the oldest pages
+    pub fn put(&self, key: &str, html: &str) -> anyhow::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let entry = Entry {
+            key: key.to_string(),
+            fetched_at: now_secs(),
+            html: html.to_string(),
+        };
+        std::fs::write(self.path(key), serde_json::to_string(&entry)?)?;
+        self.evict();
+        Ok(())
+    }
+
+    fn evict(&self) {
+        let Ok(dir) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        let now = now_secs();
+        let mut kept: Vec<(u64, u64, PathBuf)> = Vec::new();
+        for path in dir.flatten().map(|e| e.path()) {
+            let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            match Self::read(&path) {
+                Some(e) if now.saturating_sub(e.fetched_at) < self.ttl.as_secs() => {
+                    kept.push((e.fetched_at, size, path))
+                }
+                _ => {
+                    let _ = std::fs::remove_file(&path);
+                }
+            }
+        }
+        // Newest first, so the oldest are the ones past the limit
+        kept.sort_by(|a, b| b.0.cmp(&a.0));
+        let mut total = 0;
+        for (_, size, path) in kept {
+            total += size;
+            if total > self.max_bytes {
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+
+    /// Delete every saved page; returns how many there were
+    pub fn clear(&self) -> usize {
+        let Ok(dir) = std::fs::read_dir(&self.dir) else {
+            return 0;
+        };
+        dir.flatten()
+            .filter(|e| std::fs::remove_file(e.path()).is_ok())
+            .count()
+    }
+}
+
+/// Use `cache` for every render of this process. Only the first call counts.
+pub fn activate(cache: RenderCache) {
+    let _ = ACTIVE.set(cache);
+}
+
+/// The cached page for `key`, or the result of `render` (then cached if it
+/// succeeded and is not empty)
+pub async fn through<F, E>(key: &str, render: F) -> Result<String, E>
+where
+    F: Future<Output = Result<String, E>>,
+{
+    let Some(cache) = ACTIVE.get() else {
+        return render.await;
+    };
+    if let Some(html) = cache.get(key) {
+        tracing::debug!("Rendered page for {} reused from the cache", key);
+        return Ok(html);
+    }
+    let rendered = render.await;
+    if let Ok(html) = &rendered
+        && !html.trim().is_empty()
+        && let Err(e) = cache.put(key, html)
+    {
+        tracing::warn!("Failed to cache rendered page for {}: {}", key, e);
+    }
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn pages_expire_and_cookies_make_separate_entries() {
+        let dir = tempdir().unwrap();
+        let cache = RenderCache::new(dir.path().join("rendered"));
+        let anonymous = key("solver", "https://a.example/?s=elden", None);
+        let signed_in = key("solver", "https://a.example/?s=elden", Some("sid=1"));
+        assert_ne!(anonymous, signed_in);
+        assert!(!signed_in.contains("sid=1"));
+
+        cache.put(&anonymous, "<html>results</html>").unwrap();
+        assert_eq!(
+            cache.get(&anonymous).as_deref(),
+            Some("<html>results</html>")
+        );
+        assert_eq!(cache.get(&signed_in), None);
+
+        let expired =
+            RenderCache::with_limits(dir.path().join("rendered"), Duration::ZERO, MAX_BYTES);
+        assert_eq!(expired.get(&anonymous), None);
+        assert_eq!(cache.clear(), 1);
+        assert_eq!(cache.get(&anonymous), None);
+    }
+
+    #[test]
+    fn oldest_pages_go_past_the_size_limit() {
+        let dir = tempdir().unwrap();
+        let page = "x".repeat(1000);
+        // Room for two pages and their JSON framing, not three
+        let cache = RenderCache::with_limits(dir.path().to_path_buf(), RENDER_TTL, 2500);
+        for (i, url) in ["first", "second", "third"].iter().enumerate() {
+            cache.put(url, &page).unwrap();
+            // Give each page its own second so the order is clear
+            let path = cache.path(url);
+            let mut entry = RenderCache::read(&path).unwrap();
+            entry.fetched_at -= 10 - i as u64;
+            std::fs::write(&path, serde_json::to_string(&entry).unwrap()).unwrap();
+        }
+        cache.evict();
+        assert_eq!(cache.get("first"), None);
+        assert!(cache.get("second").is_some());
+        assert!(cache.get("third").is_some());
+    }
+}
@@ -5,12 +5,14 @@
 //! - Error categorization for better error handling
 //! - Fallback strategies for degraded operation
 
+use serde::Serialize;
 use std::sync::atomic::{AtomicU8, AtomicU32, AtomicU64, Ordering};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
 /// Circuit breaker states
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 #[repr(u8)]
 pub enum CircuitState {
     /// Normal operation - requests pass through
@@ -235,7 +237,12 @@ fn current_timestamp() -> u64 {
 
 /// Categorize an error for better handling and metrics
 pub fn categorize_error(err: &anyhow::Error) -> ErrorCategory {
-    let err_str = err.to_string().to_lowercase();
+    categorize_message(&err.to_string())
+}
+
+/// [`categorize_error`] for an error already turned into text
+pub fn categorize_message(message: &str) -> ErrorCategory {
+    let err_str = message.to_lowercase();
 
     // Check for rate limiting
     if err_str.contains("429") || err_str.contains("rate limit") || err_str.contains("too many") {
@@ -268,6 +275,7 @@ pub fn categorize_error(err: &anyhow::Error) -> ErrorCategory {
         || err_str.contains("network")
         || err_str.contains("refused")
         || err_str.contains("reset")
+        || err_str.contains("error sending request")
     {
         return ErrorCategory::Network;
     }
@@ -0,0 +1,234 @@
+//! Every result earlier searches found, searchable without the network.
+//!
+//! The search cache only holds the last few searches. Each time it is saved,
+//! its results are also added to `result_index.json` in the state directory,
+//! where they stay after the cache drops them: one entry per result URL, with
+//! the queries that surfaced it and when. Title words are indexed on load, so
+//! `websearcher grep nightreign` answers from memory. Past [`MAX_RESULTS`]
+//! the results seen longest ago are dropped.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::Path;
+
+use crate::cache::SearchCache;
+
+/// Results kept in the index
+pub const MAX_RESULTS: usize = 20_000;
+
+/// Queries remembered per result, most recent first
+const MAX_QUERIES: usize = 10;
+
+/// A result one or more searches found
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedResult {
+    pub title: String,
+    pub url: String,
+    pub site: String,
+    /// Queries that found it, most recent first
+    pub queries: Vec<String>,
+    /// Unix timestamps (seconds) of the first and last search that found it
+    pub first_seen: u64,
+    pub last_seen: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResultIndex {
+    results: Vec<IndexedResult>,
+    /// Lowercased title words to positions in `results`, rebuilt on load
+    #[serde(skip)]
+    words: BTreeMap<String, BTreeSet<usize>>,
+}
+
+/// Lowercased words of `text`
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+}
+
+impl ResultIndex {
+    /// Load the index, starting empty if the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        let mut index: Self = serde_json::from_str(&content).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid result index {:?}: {}", path, e);
+            Self::default()
+        });
+        index.reindex();
+        index
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    fn reindex(&mut self) {
+        self.words.clear();
+        for (i, result) in self.results.iter().enumerate() {
+            for word in words(&result.title) {
+                self.words.entry(word).or_default().insert(i);
+            }
+        }
+    }
+
+    /// Add the results of every search in `cache`; adding them again changes nothing
+    pub fn absorb(&mut self, cache: &SearchCache) {
+        let mut by_url: BTreeMap<String, usize> = self
+            .results
+            .iter()
+            .enumerate()
+            .map(|(i, r)| (r.url.clone(), i))
+            .collect();
+        for entry in cache.entries() {
+            for found in &entry.results {
+                let i = *by_url.entry(found.url.clone()).or_insert_with(|| {
+                    self.results.push(IndexedResult {
+                        title: found.title.clone(),
+                        url: found.url.clone(),
+                        site: found.site.to_string(),
+                        queries: Vec::new(),
+                        first_seen: entry.timestamp,
+                        last_seen: entry.timestamp,
+                    });
+                    self.results.len() - 1
+                });
+                let result = &mut self.results[i];
+                result.first_seen = result.first_seen.min(entry.timestamp);
+                if entry.timestamp >= result.last_seen {
+                    // The latest search names the result
+                    result.last_seen = entry.timestamp;
+                    result.title = found.title.clone();
+                }
+                if !result.queries.contains(&entry.query) {
+                    result.queries.insert(0, entry.query.clone());
+                    result.queries.truncate(MAX_QUERIES);
+                }
+            }
+        }
+        if self.results.len() > MAX_RESULTS {
+            self.results.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+            self.results.truncate(MAX_RESULTS);
+        }
+        self.reindex();
+    }
+
+    /// Results whose title has every word of `pattern`, each word matching
+    /// the start of a title word; most recently seen first
+    pub fn search(&self, pattern: &str) -> Vec<&IndexedResult> {
+        let mut matched: Option<HashSet<usize>> = None;
+        for word in words(pattern) {
+            let with_word: HashSet<usize> = self
+                .words
+                .range(word.clone()..)
+                .take_while(|(w, _)| w.starts_with(&word))
+                .flat_map(|(_, positions)| positions.iter().copied())
+                .collect();
+            matched = Some(match matched {
+                Some(found) => found.intersection(&with_word).copied().collect(),
+                None => with_word,
+            });
+        }
+        let mut found: Vec<&IndexedResult> = matched
+            .unwrap_or_default()
+            .into_iter()
+            .map(|i| &self.results[i])
+            .collect();
+        found.sort_by(|a, b| {
+            b.last_seen
+                .cmp(&a.last_seen)
+                .then_with(|| a.title.cmp(&b.title))
+        });
+        found
+    }
+
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+}
+
+/// Add the results in `cache` to the index at `path`
+pub fn index_cache(path: &Path, cache: &SearchCache) -> anyhow::Result<()> {
+    let mut index = ResultIndex::load(path);
+    index.absorb(cache);
+    index.save(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SearchResult;
+    use tempfile::tempdir;
+
+    fn result(site: &str, title: &str, url: &str) -> SearchResult {
+        SearchResult {
+            site: site.into(),
+            title: title.to_string(),
+            url: url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn results_outlive_the_cache_and_match_by_word_prefix() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("result_index.json");
+        let mut cache = SearchCache::new(3);
+        cache.add(
+            "elden ring".into(),
+            vec![
+                result(
+                    "fitgirl",
+                    "Elden Ring Nightreign",
+                    "https://a.example/nightreign",
+                ),
+                result("fitgirl", "Elden Ring Deluxe", "https://a.example/deluxe"),
+            ],
+        );
+        index_cache(&path, &cache).unwrap();
+        // Saving again adds nothing
+        index_cache(&path, &cache).unwrap();
+
+        cache.clear();
+        cache.add(
+            "nightreign".into(),
+            vec![result("dodi", "Nightreign", "https://b.example/nr")],
+        );
+        cache.add(
+            "fromsoft".into(),
+            vec![result(
+                "fitgirl",
+                "ELDEN RING: Nightreign",
+                "https://a.example/nightreign",
+            )],
+        );
+        index_cache(&path, &cache).unwrap();
+
+        let index = ResultIndex::load(&path);
+        assert_eq!(index.len(), 3);
+        let found = index.search("nightr");
+        assert_eq!(found.len(), 2);
+        let fitgirl = found.iter().find(|r| r.site == "fitgirl").unwrap();
+        assert_eq!(fitgirl.queries, ["fromsoft", "elden ring"]);
+        assert_eq!(fitgirl.title, "ELDEN RING: Nightreign");
+
+        let titles: Vec<&str> = index
+            .search("Elden nightreign")
+            .iter()
+            .map(|r| r.title.as_str())
+            .collect();
+        assert_eq!(titles, ["ELDEN RING: Nightreign"]);
+        assert!(index.search("hades").is_empty());
+        assert!(index.search("").is_empty());
+    }
+}
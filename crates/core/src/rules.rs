@@ -0,0 +1,167 @@
+//! Applying a site's post-processing rules to its results.
+//!
+//! Rules are declared on each [`SiteConfig`](crate::models::SiteConfig) (or
+//! under `[sites.<name>.rules]` in `sites.toml`) instead of being hard-coded
+//! per site:
+//!
+//! ```toml
+//! [sites.csrin.rules]
+//! url_must_contain = ["viewtopic.php"]
+//! title_strip_prefixes = ["Main Forum •", "Re:"]
+//! ```
+//!
+//! The generic parser applies them while reading a page, and the CLI and GUI
+//! apply them to each site's final results, including those from feeds and
+//! JSON endpoints.
+
+use crate::models::{SearchResult, SiteRules};
+
+/// Whether `text` starts with `prefix`, ignoring ASCII case
+fn starts_with_ignore_case(text: &str, prefix: &str) -> bool {
+    text.get(..prefix.len())
+        .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+}
+
+impl SiteRules {
+    pub fn is_empty(&self) -> bool {
+        *self == SiteRules::default()
+    }
+
+    /// Whether a result at `url` passes the URL rules
+    pub fn keeps_url(&self, url: &str) -> bool {
+        let url = url.to_lowercase();
+        let contains = |needle: &String| url.contains(&needle.to_lowercase());
+        (self.url_must_contain.is_empty() || self.url_must_contain.iter().any(contains))
+            && !self.drop_if_url_contains.iter().any(contains)
+    }
+
+    /// `title` trimmed, with the configured prefixes stripped (repeatedly, so
+    /// "Main Forum • Re: X" becomes "X")
+    pub fn clean_title<'a>(&self, title: &'a str) -> &'a str {
+        let mut title = title.trim();
+        while let Some(prefix) = self
+            .title_strip_prefixes
+            .iter()
+            .find(|p| !p.is_empty() && starts_with_ignore_case(title, p))
+        {
+            title = title[prefix.len()..].trim_start();
+        }
+        title
+    }
+
+    /// The title to show for a result at `url`, or `None` if the rules drop it
+    pub fn apply_to(&self, url: &str, title: &str) -> Option<String> {
+        if !self.keeps_url(url) {
+            return None;
+        }
+        let title = self.clean_title(title);
+        let dropped = self
+            .drop_if_title_starts_with
+            .iter()
+            .any(|p| starts_with_ignore_case(title, p));
+        (!title.is_empty() && !dropped).then(|| title.to_string())
+    }
+
+    /// Drop the results the rules reject and clean the titles of the rest
+    pub fn apply(&self, results: &mut Vec<SearchResult>) {
+        if self.is_empty() {
+            return;
+        }
+        results.retain_mut(|r| match self.apply_to(&r.url, &r.title) {
+            Some(title) => {
+                r.title = title;
+                true
+            }
+            None => false,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::site_configs;
+    use crate::models::{SearchResult, SiteRules};
+
+    fn rules(site: &str) -> SiteRules {
+        site_configs()
+            .into_iter()
+            .find(|s| s.name == site)
+            .map(|s| s.rules)
+            .unwrap()
+    }
+
+    fn result(title: &str, url: &str) -> SearchResult {
+        SearchResult {
+            site: "test".into(),
+            title: title.into(),
+            url: url.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn csrin_titles_lose_forum_and_reply_prefixes() {
+        let csrin = rules("csrin");
+        assert_eq!(csrin.clean_title("Main Forum • Elden Ring"), "Elden Ring");
+        assert_eq!(
+            csrin.clean_title("Re: Elden Ring Discussion"),
+            "Elden Ring Discussion"
+        );
+        assert_eq!(
+            csrin.clean_title("Main Forum • Re: Some Game Title"),
+            "Some Game Title"
+        );
+        assert_eq!(csrin.clean_title("Re:Some Topic"), "Some Topic");
+        assert_eq!(csrin.clean_title("Resident Evil"), "Resident Evil");
+    }
+
+    #[test]
+    fn url_rules_keep_and_drop_results() {
+        let mut results = vec![
+            result(
+                "Main Forum • Elden Ring",
+                "https://cs.rin.ru/forum/viewtopic.php?t=1",
+            ),
+            result("Forum rules", "https://cs.rin.ru/forum/viewforum.php?f=10"),
+        ];
+        rules("csrin").apply(&mut results);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "Elden Ring");
+
+        let gog = rules("gog-games");
+        assert!(gog.keeps_url("https://gog-games.to/game/elden-ring"));
+        assert!(gog.keeps_url("https://gog-games.to/GAMES/elden-ring"));
+        assert!(!gog.keeps_url("https://gog-games.to/search?q=elden"));
+
+        let fitgirl = rules("fitgirl");
+        assert!(!fitgirl.keeps_url("https://fitgirl-repacks.site/Category/games"));
+        assert_eq!(
+            fitgirl.apply_to(
+                "https://fitgirl-repacks.site/elden-ring/",
+                "Continue reading Elden Ring"
+            ),
+            None
+        );
+        assert_eq!(
+            fitgirl
+                .apply_to("https://fitgirl-repacks.site/elden-ring/", " Elden Ring ")
+                .as_deref(),
+            Some("Elden Ring")
+        );
+    }
+
+    #[test]
+    fn rules_load_from_sites_toml_tables() {
+        let rules: SiteRules = toml::from_str(
+            r#"
+            url_must_contain = ["/game/"]
+            title_strip_prefixes = ["[Repack]"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(rules.url_must_contain, ["/game/"]);
+        assert!(rules.drop_if_url_contains.is_empty());
+        assert_eq!(rules.clean_title("[repack] Hades"), "Hades");
+        assert!(SiteRules::default().is_empty() && !rules.is_empty());
+    }
+}
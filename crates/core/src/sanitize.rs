@@ -0,0 +1,108 @@
+//! Cleaning scraped text before it is displayed.
+//!
+//! Titles and descriptions come from untrusted pages. Markup that survived
+//! parsing (e.g. escaped HTML in a feed title), terminal escape sequences and
+//! bidi overrides could break the GUI or table layout, or make a title appear
+//! to be something else, including hiding the URL printed next to it.
+
+use crate::canonical::normalize_url;
+use crate::models::SearchResult;
+use regex::Regex;
+use std::sync::OnceLock;
+
+static TAG: OnceLock<Regex> = OnceLock::new();
+
+/// Invisible formatting characters that change how surrounding text is shown:
+/// bidi embeddings, overrides, isolates and marks, zero-width characters and BOM
+fn is_format_control(c: char) -> bool {
+    matches!(
+        c,
+        '\u{061C}'
+            | '\u{200B}'..='\u{200F}'
+            | '\u{202A}'..='\u{202E}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{2066}'..='\u{2069}'
+            | '\u{FEFF}'
+    )
+}
+
+/// `text` as one plain line: tags removed, control and bidi characters dropped
+/// (line breaks and tabs become spaces) and whitespace collapsed
+pub fn sanitize_text(text: &str) -> String {
+    let tag = TAG.get_or_init(|| Regex::new(r"</?[A-Za-z!][^<>]*>").expect("valid tag regex"));
+    let without_tags = tag.replace_all(text, " ");
+    let cleaned: String = without_tags
+        .chars()
+        .filter(|&c| !is_format_control(c))
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `url` without control, bidi or whitespace characters, which a real link
+/// would have percent-encoded
+pub fn sanitize_url(url: &str) -> String {
+    url.chars()
+        .filter(|&c| !c.is_control() && !c.is_whitespace() && !is_format_control(c))
+        .collect()
+}
+
+/// Sanitize the displayed fields of each result, and drop the tracking
+/// parameters from its URL (see [`normalize_url`])
+pub fn sanitize_results(results: &mut [SearchResult]) {
+    for r in results {
+        r.title = sanitize_text(&r.title);
+        r.url = normalize_url(&sanitize_url(&r.url));
+        if let Some(source) = r.source_url.as_mut() {
+            *source = sanitize_url(source);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_embedded_tags() {
+        assert_eq!(
+            sanitize_text("<b>Elden Ring</b> <img src=x onerror=alert(1)>Deluxe"),
+            "Elden Ring Deluxe"
+        );
+        // Comparisons that are not tags stay
+        assert_eq!(sanitize_text("Cities < 2 > 1"), "Cities < 2 > 1");
+    }
+
+    #[test]
+    fn drops_control_and_bidi_characters() {
+        // An RTL override would display "exe.txt" as "txt.exe"
+        assert_eq!(sanitize_text("Setup\u{202E}txt.exe"), "Setuptxt.exe");
+        assert_eq!(
+            sanitize_text("Elden\u{1b}[31m Ring\u{200B}\n\tDeluxe\u{7}"),
+            "Elden [31m Ring Deluxe"
+        );
+        assert_eq!(sanitize_text("Pokémon 日本語"), "Pokémon 日本語");
+    }
+
+    #[test]
+    fn urls_lose_hidden_characters() {
+        assert_eq!(
+            sanitize_url(" https://a.example/\u{2066}game\u{2069}\n"),
+            "https://a.example/game"
+        );
+        let mut results = vec![SearchResult {
+            site: "fitgirl".into(),
+            title: "<i>Hades</i>\u{202B}".into(),
+            url: "https://a.example/hades\u{0}".into(),
+            source_url: Some("https://a.example/?s=hades\r\n".into()),
+            ..Default::default()
+        }];
+        sanitize_results(&mut results);
+        assert_eq!(results[0].title, "Hades");
+        assert_eq!(results[0].url, "https://a.example/hades");
+        assert_eq!(
+            results[0].source_url.as_deref(),
+            Some("https://a.example/?s=hades")
+        );
+    }
+}
@@ -0,0 +1,350 @@
+//! Secrets kept out of `config.toml`: site passwords, cookies and tokens.
+//!
+//! A secret is stored under a name with `secrets set <name>` (or the GUI's
+//! cookie import) and the config refers to it as `"secret:<name>"` wherever
+//! it would hold the value: account passwords and cookies, saved site
+//! cookies, webhook headers, the MQTT password and `--cookie`.
+//!
+//! Values go to the OS keyring (Keychain, Windows Credential Manager or the
+//! Secret Service) under the service [`SERVICE`]. When there is no keyring,
+//! as on a headless Linux box, or `WEBSITE_SEARCHER_NO_KEYRING` is set, they
+//! go to `secrets.bin` in the data directory instead, encrypted with
+//! ChaCha20-Poly1305 under a random key in `secrets.key` in the state
+//! directory. Backups of the data directory then hold only ciphertext; the
+//! file does not protect secrets from someone who can read both directories.
+//! The encrypted file also lists the names kept in the keyring, which cannot
+//! be enumerated portably.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+
+/// Keyring service the secrets are stored under
+pub const SERVICE: &str = "website-searcher";
+
+/// Prefix of config values that name a secret instead of holding it
+pub const REFERENCE_PREFIX: &str = "secret:";
+
+/// Environment variable that keeps secrets out of the OS keyring
+pub const NO_KEYRING_ENV: &str = "WEBSITE_SEARCHER_NO_KEYRING";
+
+const NONCE_LEN: usize = 12;
+
+/// Where a secret is kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    Keyring,
+    File,
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Keyring => "keyring",
+            Self::File => "encrypted file",
+        })
+    }
+}
+
+/// The name `value` refers to, if it is a `secret:<name>` reference
+pub fn reference_name(value: &str) -> Option<&str> {
+    value
+        .strip_prefix(REFERENCE_PREFIX)
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+}
+
+/// The `secret:<name>` reference to `name`
+pub fn reference(name: &str) -> String {
+    format!("{REFERENCE_PREFIX}{name}")
+}
+
+/// Contents of the encrypted file: values stored there, and `None` for names
+/// whose value is in the keyring
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Vault {
+    secrets: BTreeMap<String, Option<String>>,
+}
+
+/// The keyring, with the encrypted file as fallback
+#[derive(Debug, Clone)]
+pub struct SecretStore {
+    file: PathBuf,
+    key_file: PathBuf,
+    use_keyring: bool,
+}
+
+impl Default for SecretStore {
+    /// The app's store: `secrets.bin` in the data directory and its key in
+    /// the state directory, with the keyring unless it is turned off
+    fn default() -> Self {
+        Self {
+            file: paths::secrets_file(),
+            key_file: paths::secrets_key_file(),
+            use_keyring: std::env::var_os(NO_KEYRING_ENV).is_none(),
+        }
+    }
+}
+
+impl SecretStore {
+    /// A store that only uses the encrypted `file`, keyed by `key_file`
+    pub fn file_only(file: PathBuf, key_file: PathBuf) -> Self {
+        Self {
+            file,
+            key_file,
+            use_keyring: false,
+        }
+    }
+
+    /// Store `value` under `name`, in the keyring if it accepts it
+    pub fn set(&self, name: &str, value: &str) -> Result<Backend> {
+        if name.trim().is_empty() || name.contains(char::is_whitespace) {
+            bail!("invalid secret name \"{}\"", name);
+        }
+        let mut vault = self.read_vault()?;
+        let backend = match self.keyring_entry(name) {
+            Some(entry) => match entry.set_password(value) {
+                Ok(()) => Backend::Keyring,
+                Err(e) => {
+                    tracing::warn!("Keyring unavailable, using the encrypted file: {}", e);
+                    Backend::File
+                }
+            },
+            None => Backend::File,
+        };
+        let stored = (backend == Backend::File).then(|| value.to_string());
+        vault.secrets.insert(name.to_string(), stored);
+        self.write_vault(&vault)?;
+        Ok(backend)
+    }
+
+    /// The value stored under `name`, if any
+    pub fn get(&self, name: &str) -> Result<Option<String>> {
+        let vault = self.read_vault()?;
+        // Names the file does not list were never stored
+        let entry = match vault.secrets.get(name) {
+            Some(Some(value)) => return Ok(Some(value.clone())),
+            Some(None) => self.keyring_entry(name),
+            None => None,
+        };
+        let Some(entry) = entry else {
+            return Ok(None);
+        };
+        match entry.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(anyhow!(
+                "reading secret \"{}\" from the keyring: {}",
+                name,
+                e
+            )),
+        }
+    }
+
+    /// Delete the value stored under `name`; returns false if there was none
+    pub fn remove(&self, name: &str) -> Result<bool> {
+        let mut vault = self.read_vault()?;
+        let Some(stored) = vault.secrets.remove(name) else {
+            return Ok(false);
+        };
+        let entry = stored.is_none().then(|| self.keyring_entry(name)).flatten();
+        match entry.map(|e| e.delete_credential()) {
+            Some(Ok(()) | Err(keyring::Error::NoEntry)) | None => {}
+            Some(Err(e)) => {
+                tracing::warn!("Failed to delete \"{}\" from the keyring: {}", name, e);
+            }
+        }
+        self.write_vault(&vault)?;
+        Ok(true)
+    }
+
+    /// Names of the stored secrets and where each is kept
+    pub fn list(&self) -> Result<Vec<(String, Backend)>> {
+        Ok(self
+            .read_vault()?
+            .secrets
+            .into_iter()
+            .map(|(name, value)| {
+                let backend = if value.is_some() {
+                    Backend::File
+                } else {
+                    Backend::Keyring
+                };
+                (name, backend)
+            })
+            .collect())
+    }
+
+    /// `value` itself, or the secret it refers to as `secret:<name>`
+    pub fn resolve(&self, value: &str) -> Result<String> {
+        match reference_name(value) {
+            Some(name) => self
+                .get(name)?
+                .ok_or_else(|| anyhow!("secret \"{}\" is not stored", name)),
+            None => Ok(value.to_string()),
+        }
+    }
+
+    fn keyring_entry(&self, name: &str) -> Option<keyring::Entry> {
+        if !self.use_keyring {
+            return None;
+        }
+        keyring::Entry::new(SERVICE, name)
+            .inspect_err(|e| tracing::debug!("No keyring entry for {}: {}", name, e))
+            .ok()
+    }
+
+    fn cipher(&self, create: bool) -> Result<Option<ChaCha20Poly1305>> {
+        let key = match std::fs::read(&self.key_file) {
+            Ok(key) => key,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && create => {
+                let mut key = vec![0u8; 32];
+                rand::rngs::OsRng.fill_bytes(&mut key);
+                write_private(&self.key_file, &key)?;
+                key
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).context("reading the secrets key"),
+        };
+        if key.len() != 32 {
+            bail!("secrets key {:?} is damaged", self.key_file);
+        }
+        Ok(Some(ChaCha20Poly1305::new(Key::from_slice(&key))))
+    }
+
+    fn read_vault(&self) -> Result<Vault> {
+        let sealed = match std::fs::read(&self.file) {
+            Ok(sealed) => sealed,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vault::default()),
+            Err(e) => return Err(e).context("reading the secrets file"),
+        };
+        let cipher = self
+            .cipher(false)?
+            .ok_or_else(|| anyhow!("secrets key {:?} is missing", self.key_file))?;
+        if sealed.len() < NONCE_LEN {
+            bail!("secrets file {:?} is damaged", self.file);
+        }
+        let (nonce, data) = sealed.split_at(NONCE_LEN);
+        let plain = cipher
+            .decrypt(Nonce::from_slice(nonce), data)
+            .map_err(|_| anyhow!("secrets file {:?} does not match its key", self.file))?;
+        Ok(serde_json::from_slice(&plain)?)
+    }
+
+    fn write_vault(&self, vault: &Vault) -> Result<()> {
+        let cipher = self.cipher(true)?.expect("key is created");
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+        let data = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                serde_json::to_vec(vault)?.as_slice(),
+            )
+            .map_err(|_| anyhow!("encrypting the secrets failed"))?;
+        write_private(&self.file, &[nonce.as_slice(), &data].concat())
+    }
+}
+
+/// Write `bytes` to `path`, readable only by the user on Unix
+pub(crate) fn write_private(path: &Path, bytes: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // An older file keeps its mode when opened
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    std::io::Write::write_all(&mut options.open(path)?, bytes)?;
+    Ok(())
+}
+
+/// [`SecretStore::resolve`] with the app's store
+pub fn resolve(value: &str) -> Result<String> {
+    if reference_name(value).is_none() {
+        return Ok(value.to_string());
+    }
+    SecretStore::default().resolve(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn file_store_round_trips_without_plaintext() {
+        let dir = tempdir().unwrap();
+        let store = SecretStore::file_only(dir.path().join("secrets.bin"), dir.path().join("key"));
+        assert_eq!(store.get("csrin").unwrap(), None);
+        assert_eq!(store.set("csrin", "hunter2").unwrap(), Backend::File);
+        store.set("webhook", "Bearer abc").unwrap();
+        assert!(store.set("two words", "x").is_err());
+
+        let raw = std::fs::read(dir.path().join("secrets.bin")).unwrap();
+        assert!(!String::from_utf8_lossy(&raw).contains("hunter2"));
+        assert_eq!(store.get("csrin").unwrap().as_deref(), Some("hunter2"));
+        assert_eq!(store.resolve("secret:webhook").unwrap(), "Bearer abc");
+        assert_eq!(store.resolve("plain").unwrap(), "plain");
+        assert!(store.resolve("secret:missing").is_err());
+        assert_eq!(
+            store.list().unwrap(),
+            [
+                ("csrin".to_string(), Backend::File),
+                ("webhook".to_string(), Backend::File)
+            ]
+        );
+
+        assert!(store.remove("csrin").unwrap());
+        assert!(!store.remove("csrin").unwrap());
+        assert_eq!(store.get("csrin").unwrap(), None);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(dir.path().join("key"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn a_wrong_key_is_an_error_not_an_empty_store() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("secrets.bin");
+        SecretStore::file_only(file.clone(), dir.path().join("key"))
+            .set("csrin", "hunter2")
+            .unwrap();
+        std::fs::write(dir.path().join("other"), [7u8; 32]).unwrap();
+        let other = SecretStore::file_only(file, dir.path().join("other"));
+        assert!(other.get("csrin").is_err());
+        assert!(other.set("f95zone", "x").is_err());
+    }
+
+    #[test]
+    fn references_name_secrets() {
+        assert_eq!(
+            reference_name("secret:csrin-password"),
+            Some("csrin-password")
+        );
+        assert_eq!(reference_name("secret: "), None);
+        assert_eq!(reference_name("sid=1"), None);
+        assert_eq!(reference("cookie.csrin"), "secret:cookie.csrin");
+    }
+}
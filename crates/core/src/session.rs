@@ -0,0 +1,330 @@
+//! Recording a run's responses and replaying them offline.
+//!
+//! `--record session.tar` saves every page, feed and solver response a run
+//! fetches; `--replay session.tar` answers later runs from the recording
+//! instead of the network, so parsers can be worked on and bugs reproduced
+//! without network access or rate limits.
+//!
+//! The archive holds `session.json`, an index of the fetched URLs, and one
+//! file per response body under `responses/`, so the pages can be inspected
+//! or edited with any tar tool.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// The session every fetch in this process goes through, if any
+static ACTIVE: OnceLock<Session> = OnceLock::new();
+
+const INDEX: &str = "session.json";
+
+/// What a fetch produced: the body, or why it failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Response {
+    Body(String),
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Fetch from the network and keep every response
+    Record,
+    /// Answer fetches from a recording only
+    Replay,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Index {
+    responses: Vec<Entry>,
+}
+
+/// One fetched URL in `session.json`
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    url: String,
+    /// Body file in the archive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    /// The error, for fetches that failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Responses by URL, being recorded or replayed
+#[derive(Debug)]
+pub struct Session {
+    mode: Mode,
+    path: PathBuf,
+    responses: Mutex<BTreeMap<String, Response>>,
+}
+
+impl Session {
+    /// An empty recording, to be saved to `path`
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            mode: Mode::Record,
+            path: path.into(),
+            responses: Mutex::default(),
+        }
+    }
+
+    /// Replay the recording saved at `path`
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let file = std::fs::File::open(&path)
+            .with_context(|| format!("failed to open recording {}", path.display()))?;
+        let responses =
+            read_archive(file).with_context(|| format!("invalid recording {}", path.display()))?;
+        Ok(Self {
+            mode: Mode::Replay,
+            path,
+            responses: Mutex::new(responses),
+        })
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of URLs recorded
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The response recorded for `url`
+    pub fn get(&self, url: &str) -> Option<Response> {
+        self.lock().get(url).cloned()
+    }
+
+    /// Record `response` for `url`, replacing an earlier one
+    pub fn insert(&self, url: &str, response: Response) {
+        self.lock().insert(url.to_string(), response);
+    }
+
+    /// When replaying, the recorded response for `url` (`fetch` is never run);
+    /// when recording, the result of `fetch`, which is kept
+    pub async fn serve<F>(&self, url: &str, fetch: F) -> Result<String>
+    where
+        F: Future<Output = Result<String>>,
+    {
+        match self.mode {
+            Mode::Replay => match self.get(url) {
+                Some(Response::Body(body)) => Ok(body),
+                Some(Response::Failed(error)) => Err(anyhow::anyhow!(error)),
+                None => anyhow::bail!("not in the recording: {url}"),
+            },
+            Mode::Record => {
+                let fetched = fetch.await;
+                let response = match &fetched {
+                    Ok(body) => Response::Body(body.clone()),
+                    Err(e) => Response::Failed(format!("{e:#}")),
+                };
+                self.insert(url, response);
+                fetched
+            }
+        }
+    }
+
+    /// Write the recording to its path, replacing any file there
+    pub fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        // Written aside first so an interrupted save keeps the old recording
+        let partial = self.path.with_extension("tar.partial");
+        let file = std::fs::File::create(&partial)
+            .with_context(|| format!("failed to create {}", partial.display()))?;
+        write_archive(file, &self.lock())?;
+        std::fs::rename(&partial, &self.path)
+            .with_context(|| format!("failed to save recording {}", self.path.display()))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, Response>> {
+        self.responses.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Make `session` the one every fetch in this process goes through
+pub fn activate(session: Session) -> Result<&'static Session> {
+    ACTIVE
+        .set(session)
+        .map_err(|_| anyhow::anyhow!("a recording or replay is already active"))?;
+    Ok(ACTIVE.get().expect("just set"))
+}
+
+/// The active recording or replay
+pub fn active() -> Option<&'static Session> {
+    ACTIVE.get()
+}
+
+/// Run `fetch` for `url` through the active session; without one, just run it
+pub async fn fetch_through<F>(url: &str, fetch: F) -> Result<String>
+where
+    F: Future<Output = Result<String>>,
+{
+    match active() {
+        Some(session) => session.serve(url, fetch).await,
+        None => fetch.await,
+    }
+}
+
+/// File extension for a body, so the archive opens sensibly
+fn extension(body: &str) -> &'static str {
+    let start = body.trim_start();
+    if start.starts_with('{') || start.starts_with('[') {
+        "json"
+    } else if start.starts_with("<?xml") || start.starts_with("<rss") || start.starts_with("<feed")
+    {
+        "xml"
+    } else {
+        "html"
+    }
+}
+
+fn write_archive(file: std::fs::File, responses: &BTreeMap<String, Response>) -> Result<()> {
+    let mut tar = tar::Builder::new(file);
+    let mut entries = Vec::new();
+    for (i, (url, response)) in responses.iter().enumerate() {
+        match response {
+            Response::Body(body) => {
+                let name = format!("responses/{:04}.{}", i + 1, extension(body));
+                append(&mut tar, &name, body.as_bytes())?;
+                entries.push(Entry {
+                    url: url.clone(),
+                    file: Some(name),
+                    error: None,
+                });
+            }
+            Response::Failed(error) => entries.push(Entry {
+                url: url.clone(),
+                file: None,
+                error: Some(error.clone()),
+            }),
+        }
+    }
+    let index = serde_json::to_vec_pretty(&Index { responses: entries })?;
+    append(&mut tar, INDEX, &index)?;
+    tar.into_inner()?.sync_all()?;
+    Ok(())
+}
+
+fn append(tar: &mut tar::Builder<std::fs::File>, name: &str, data: &[u8]) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    tar.append_data(&mut header, name, data)?;
+    Ok(())
+}
+
+fn read_archive(file: std::fs::File) -> Result<BTreeMap<String, Response>> {
+    let mut files = BTreeMap::new();
+    for entry in tar::Archive::new(file).entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let mut contents = String::new();
+        entry
+            .read_to_string(&mut contents)
+            .with_context(|| format!("{name} is not UTF-8 text"))?;
+        files.insert(name, contents);
+    }
+    let index: Index = serde_json::from_str(files.get(INDEX).context("missing session.json")?)
+        .context("invalid session.json")?;
+    let mut responses = BTreeMap::new();
+    for entry in index.responses {
+        let response = match (entry.file, entry.error) {
+            (Some(name), _) => {
+                let body = files
+                    .remove(&name)
+                    .with_context(|| format!("missing {name}"))?;
+                Response::Body(body)
+            }
+            (None, error) => Response::Failed(error.unwrap_or_default()),
+        };
+        responses.insert(entry.url, response);
+    }
+    Ok(responses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn recorded_responses_replay_without_fetching() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("runs/session.tar");
+        let recording = Session::record(&path);
+        let page = "https://fitgirl-repacks.site/?s=elden+ring";
+        let feed = "https://cs.rin.ru/forum/feed.php?f=10";
+        let body = recording
+            .serve(page, async { Ok("<html>Elden Ring</html>".to_string()) })
+            .await
+            .unwrap();
+        assert_eq!(body, "<html>Elden Ring</html>");
+        let failed = recording
+            .serve(feed, async { anyhow::bail!("Server error: 503") })
+            .await;
+        assert!(failed.is_err());
+        recording.save().unwrap();
+
+        let replay = Session::replay(&path).unwrap();
+        assert_eq!(replay.len(), 2);
+        let never = async { panic!("replay must not fetch") };
+        assert_eq!(
+            replay.serve(page, never).await.unwrap(),
+            "<html>Elden Ring</html>"
+        );
+        let err = replay.serve(feed, async { Ok(String::new()) }).await;
+        assert_eq!(err.unwrap_err().to_string(), "Server error: 503");
+        let missing = replay
+            .serve("https://steamrip.com/?s=hades", async { Ok(String::new()) })
+            .await;
+        assert_eq!(
+            missing.unwrap_err().to_string(),
+            "not in the recording: https://steamrip.com/?s=hades"
+        );
+    }
+
+    #[test]
+    fn archive_keeps_bodies_as_readable_files() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("session.tar");
+        let recording = Session::record(&path);
+        recording.insert(
+            "https://a.example/feed/",
+            Response::Body("<?xml?><rss/>".into()),
+        );
+        recording.insert(
+            "https://a.example/api",
+            Response::Body("{\"data\":[]}".into()),
+        );
+        recording.save().unwrap();
+
+        let file = std::fs::File::open(&path).unwrap();
+        let mut names: Vec<String> = tar::Archive::new(file)
+            .entries()
+            .unwrap()
+            .map(|e| e.unwrap().path().unwrap().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            ["responses/0001.json", "responses/0002.xml", "session.json"]
+        );
+        assert!(Session::replay(dir.path().join("missing.tar")).is_err());
+    }
+}
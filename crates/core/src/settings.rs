@@ -0,0 +1,373 @@
+//! User preferences persisted in `config.toml`, next to `sites.toml`.
+//!
+//! Missing files and missing keys fall back to defaults so older config files
+//! keep working as new settings are added.
+
+use crate::auth::AccountConfig;
+use crate::models::SearchResult;
+use crate::sinks::SinkConfig;
+use crate::template::SearchTemplate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Order of results in the interactive browser
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    /// One box per site, as the search returned them
+    #[default]
+    Site,
+    Title,
+    /// Closest match to the query first
+    Relevance,
+    /// Largest download first
+    Size,
+    /// Newest release first
+    Date,
+}
+
+impl SortKey {
+    /// The next order in the cycle
+    pub fn next(self) -> Self {
+        match self {
+            SortKey::Site => SortKey::Title,
+            SortKey::Title => SortKey::Relevance,
+            SortKey::Relevance => SortKey::Size,
+            SortKey::Size => SortKey::Date,
+            SortKey::Date => SortKey::Site,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SortKey::Site => "site",
+            SortKey::Title => "title",
+            SortKey::Relevance => "relevance",
+            SortKey::Size => "size",
+            SortKey::Date => "date",
+        }
+    }
+}
+
+/// Sort order and visible columns of the results browser
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct TuiView {
+    pub sort: SortKey,
+    pub show_url: bool,
+    pub show_size: bool,
+    pub show_version: bool,
+}
+
+impl Default for TuiView {
+    fn default() -> Self {
+        Self {
+            sort: SortKey::Site,
+            show_url: true,
+            show_size: false,
+            show_version: false,
+        }
+    }
+}
+
+/// External program a result can be handed to from the browser (keys 1-9)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OpenWith {
+    pub name: String,
+    /// Program and arguments. `{url}`, `{title}` and `{site}` are replaced with
+    /// the result's fields; without any placeholder the URL is appended.
+    pub command: Vec<String>,
+}
+
+impl OpenWith {
+    /// Program and expanded arguments for `result`, or `None` if no program is set
+    pub fn command_for(&self, result: &SearchResult) -> Option<(String, Vec<String>)> {
+        let (program, args) = self.command.split_first()?;
+        let has_placeholder = self
+            .command
+            .iter()
+            .any(|a| a.contains("{url}") || a.contains("{title}") || a.contains("{site}"));
+        let expand = |a: &String| {
+            a.replace("{url}", &result.url)
+                .replace("{title}", &result.title)
+                .replace("{site}", &result.site)
+        };
+        let mut args: Vec<String> = args.iter().map(expand).collect();
+        if !has_placeholder {
+            args.push(result.url.clone());
+        }
+        Some((expand(program), args))
+    }
+}
+
+/// Defaults for searches started from the GUI
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct SearchDefaults {
+    /// Sites searched when none are picked; empty means all sites
+    pub sites: Vec<String>,
+    /// Order of sites in the GUI's site picker; unlisted sites follow
+    pub site_order: Vec<String>,
+    /// Maximum results per site
+    pub limit: usize,
+    /// FlareSolverr endpoint; unset uses `CF_URL` or the local default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cf_url: Option<String>,
+    /// Cookie header to send to a site, by site name
+    pub cookies: BTreeMap<String, String>,
+    /// Proxy route by site name: `tor`, `direct` or a proxy URL (see [`crate::route`])
+    pub routes: BTreeMap<String, String>,
+    /// Number of sites searched at once
+    pub concurrency: usize,
+    /// Drop results whose titles mark them as adult content
+    pub hide_nsfw: bool,
+    /// Look up the Steam AppID of each result (see [`crate::steam`])
+    pub steam_appids: bool,
+    /// Search the sites that failed with a network or server error once more:
+    /// at the end of a CLI search, right away in the GUI
+    pub retry_failed: bool,
+    /// Requests a day by site name, `*` for sites without their own (see
+    /// [`crate::budget`])
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub daily_budget: BTreeMap<String, u32>,
+    /// Seconds a GUI search waits before showing what has arrived; sites
+    /// still searching add their results when they finish. Unset waits for all.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deadline_seconds: Option<u64>,
+}
+
+impl Default for SearchDefaults {
+    fn default() -> Self {
+        Self {
+            sites: Vec::new(),
+            site_order: Vec::new(),
+            limit: 10,
+            cf_url: None,
+            cookies: BTreeMap::new(),
+            routes: BTreeMap::new(),
+            concurrency: 3,
+            hide_nsfw: false,
+            steam_appids: false,
+            retry_failed: false,
+            daily_budget: BTreeMap::new(),
+            deadline_seconds: None,
+        }
+    }
+}
+
+impl SearchDefaults {
+    /// Whether `site` is checked by default (all are when none are saved)
+    pub fn site_enabled(&self, site: &str) -> bool {
+        self.sites.is_empty() || self.sites.iter().any(|s| s.eq_ignore_ascii_case(site))
+    }
+
+    /// Position of `site` in the saved order; unlisted sites sort last
+    pub fn site_position(&self, site: &str) -> usize {
+        self.site_order
+            .iter()
+            .position(|s| s.eq_ignore_ascii_case(site))
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Stored cookie for `site`, matched case-insensitively
+    pub fn cookie_for(&self, site: &str) -> Option<&str> {
+        self.cookies
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(site))
+            .map(|(_, cookie)| cookie.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct Settings {
+    pub tui: TuiView,
+    pub search: SearchDefaults,
+    /// Key overrides for the results browser: command name to key names
+    pub keys: BTreeMap<String, Vec<String>>,
+    /// "Open with" actions, bound to keys 1-9 in order
+    pub open_with: Vec<OpenWith>,
+    /// Where every search's results are sent besides the terminal
+    pub sinks: Vec<SinkConfig>,
+    /// Sign-in details by site name, for sites with an [`crate::auth::Authenticator`]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub accounts: BTreeMap<String, AccountConfig>,
+    /// Search templates by name, run with `template run` (see [`crate::template`])
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, SearchTemplate>,
+}
+
+impl Settings {
+    /// Load settings, using defaults if the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        toml::from_str(&content).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid settings file {:?}: {}", path, e);
+            Self::default()
+        })
+    }
+
+    /// Write settings, creating the config directory if needed
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Get the default settings file path
+pub fn default_settings_path() -> PathBuf {
+    crate::paths::config_dir().join("config.toml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn missing_file_uses_defaults() {
+        let dir = tempdir().unwrap();
+        let settings = Settings::load(&dir.path().join("config.toml"));
+        assert_eq!(settings, Settings::default());
+        assert!(settings.tui.show_url);
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("nested").join("config.toml");
+        let mut settings = Settings::default();
+        settings.tui.sort = SortKey::Size;
+        settings.tui.show_version = true;
+        settings.save(&path).unwrap();
+
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(raw.contains("sort = \"size\""));
+        assert_eq!(Settings::load(&path), settings);
+    }
+
+    #[test]
+    fn partial_or_invalid_files_fall_back_to_defaults() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "[tui]\nshow_size = true\n").unwrap();
+        let settings = Settings::load(&path);
+        assert!(settings.tui.show_size);
+        assert!(settings.tui.show_url);
+        assert_eq!(settings.tui.sort, SortKey::Site);
+
+        std::fs::write(&path, "[keys]\nquit = [\"x\"]\n").unwrap();
+        assert_eq!(Settings::load(&path).keys["quit"], ["x"]);
+
+        std::fs::write(&path, "tui = 5").unwrap();
+        assert_eq!(Settings::load(&path), Settings::default());
+    }
+
+    #[test]
+    fn search_defaults_round_trip_and_match_cli_defaults() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "[search]\nsites = [\"fitgirl\"]\nhide_nsfw = true\n\n[search.cookies]\ncsrin = \"sid=1\"\n\n[accounts.f95zone]\nusername = \"alice\"\n",
+        )
+        .unwrap();
+        let settings = Settings::load(&path);
+        assert_eq!(settings.search.limit, 10);
+        assert_eq!(settings.search.concurrency, 3);
+        assert_eq!(settings.search.cf_url, None);
+        assert_eq!(settings.search.cookie_for("CSRIN"), Some("sid=1"));
+        assert!(settings.search.hide_nsfw);
+        assert_eq!(
+            settings.accounts["f95zone"].username.as_deref(),
+            Some("alice")
+        );
+
+        settings.save(&path).unwrap();
+        assert_eq!(Settings::load(&path), settings);
+    }
+
+    #[test]
+    fn site_prefs_default_to_all_enabled_in_config_order() {
+        let mut search = SearchDefaults::default();
+        assert!(search.site_enabled("fitgirl"));
+        assert_eq!(search.site_position("fitgirl"), usize::MAX);
+
+        search.sites = vec!["dodi".into()];
+        search.site_order = vec!["csrin".into(), "dodi".into()];
+        assert!(!search.site_enabled("fitgirl"));
+        assert!(search.site_enabled("DODI"));
+        assert_eq!(search.site_position("dodi"), 1);
+        assert!(search.site_position("fitgirl") > search.site_position("dodi"));
+    }
+
+    #[test]
+    fn sort_cycles_through_every_order() {
+        let mut key = SortKey::Site;
+        let mut seen = Vec::new();
+        for _ in 0..5 {
+            seen.push(key.as_str());
+            key = key.next();
+        }
+        assert_eq!(key, SortKey::Site);
+        assert_eq!(seen, ["site", "title", "relevance", "size", "date"]);
+    }
+
+    #[test]
+    fn open_with_expands_placeholders_or_appends_url() {
+        let result = SearchResult {
+            site: "fitgirl".into(),
+            title: "Elden Ring".into(),
+            url: "https://fitgirl-repacks.site/elden-ring/".into(),
+            ..Default::default()
+        };
+        let aria = OpenWith {
+            name: "aria2".into(),
+            command: vec!["aria2c".into(), "--dir=/dl/{site}".into(), "{url}".into()],
+        };
+        assert_eq!(
+            aria.command_for(&result),
+            Some((
+                "aria2c".to_string(),
+                vec![
+                    "--dir=/dl/fitgirl".to_string(),
+                    "https://fitgirl-repacks.site/elden-ring/".to_string()
+                ]
+            ))
+        );
+
+        let firefox = OpenWith {
+            name: "work".into(),
+            command: vec!["firefox".into(), "-P".into(), "work".into()],
+        };
+        let (_, args) = firefox.command_for(&result).unwrap();
+        assert_eq!(args.last().unwrap(), &result.url);
+
+        let empty = OpenWith {
+            name: "none".into(),
+            command: vec![],
+        };
+        assert_eq!(empty.command_for(&result), None);
+    }
+
+    #[test]
+    fn open_with_round_trips_as_array_of_tables() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "[tui]\nsort = \"title\"\n\n[[open_with]]\nname = \"mpv\"\ncommand = [\"mpv\"]\n",
+        )
+        .unwrap();
+        let settings = Settings::load(&path);
+        assert_eq!(settings.open_with[0].name, "mpv");
+        settings.save(&path).unwrap();
+        assert_eq!(Settings::load(&path), settings);
+    }
+}
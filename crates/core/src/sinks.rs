@@ -0,0 +1,489 @@
+//! Output sinks: where a finished search's results go besides the terminal.
+//!
+//! Sinks are listed as `[[sinks]]` tables in `config.toml` (see [`SinkConfig`])
+//! and every search sends its results to all of them at once, so one search
+//! can print a table, write JSON to a file and notify home automation through
+//! a webhook or an MQTT topic. A sink that fails is reported by the caller and
+//! never fails the search.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, bail};
+use futures::future::{BoxFuture, join_all};
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::models::SearchResult;
+use crate::output::{ExportFormat, render_results};
+use crate::secrets;
+
+/// Longest wait for an MQTT broker to connect or answer
+const MQTT_TIMEOUT: Duration = Duration::from_secs(10);
+const MQTT_DEFAULT_PORT: u16 = 1883;
+/// Largest "remaining length" an MQTT packet can declare
+const MQTT_MAX_LEN: usize = 268_435_455;
+
+/// What a sink is given: the query and the results as shown to the user
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct SearchOutput<'a> {
+    pub query: &'a str,
+    pub results: &'a [SearchResult],
+}
+
+impl SearchOutput<'_> {
+    /// JSON body for webhooks and MQTT: query, time, count and results
+    pub fn payload(&self) -> serde_json::Value {
+        let searched_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        serde_json::json!({
+            "query": self.query,
+            "searched_at": searched_at,
+            "count": self.results.len(),
+            "results": self.results,
+        })
+    }
+}
+
+/// A destination for search results
+pub trait Sink: Send + Sync {
+    /// Short name for messages, e.g. `webhook https://hooks.example/ws`
+    fn describe(&self) -> String;
+
+    fn send<'a>(&'a self, output: SearchOutput<'a>) -> BoxFuture<'a, Result<()>>;
+}
+
+/// One `[[sinks]]` table in `config.toml`, told apart by `kind`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SinkConfig {
+    /// Overwrite `path` with the results of each search
+    File {
+        path: PathBuf,
+        #[serde(default = "default_file_format")]
+        format: ExportFormat,
+    },
+    /// POST the JSON payload to `url`
+    Webhook {
+        url: String,
+        /// Extra request headers, e.g. an `Authorization` token; values may be
+        /// `secret:<name>` references (see [`crate::secrets`])
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        headers: BTreeMap<String, String>,
+    },
+    /// Publish the JSON payload to `topic` (QoS 0) on a broker at
+    /// `mqtt://host[:port]`
+    Mqtt {
+        broker: String,
+        topic: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        username: Option<String>,
+        /// The password or a `secret:<name>` reference
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        password: Option<String>,
+        /// Keep the message on the broker for clients that subscribe later
+        #[serde(default)]
+        retain: bool,
+    },
+}
+
+fn default_file_format() -> ExportFormat {
+    ExportFormat::Json
+}
+
+impl SinkConfig {
+    /// The sink this table describes; `client` is used by webhooks
+    pub fn build(&self, client: &Client) -> Box<dyn Sink> {
+        match self.clone() {
+            SinkConfig::File { path, format } => Box::new(FileSink { path, format }),
+            SinkConfig::Webhook { url, headers } => Box::new(WebhookSink {
+                client: client.clone(),
+                url,
+                headers,
+            }),
+            SinkConfig::Mqtt {
+                broker,
+                topic,
+                username,
+                password,
+                retain,
+            } => Box::new(MqttSink {
+                broker,
+                topic,
+                username,
+                password,
+                retain,
+            }),
+        }
+    }
+}
+
+/// Send `output` to every sink at once; returns the description and error of
+/// each sink that failed
+pub async fn send_all(
+    sinks: &[Box<dyn Sink>],
+    output: SearchOutput<'_>,
+) -> Vec<(String, anyhow::Error)> {
+    let sent =
+        join_all(sinks.iter().map(|sink| async move {
+            sink.send(output).await.err().map(|e| (sink.describe(), e))
+        }))
+        .await;
+    sent.into_iter().flatten().collect()
+}
+
+struct FileSink {
+    path: PathBuf,
+    format: ExportFormat,
+}
+
+impl Sink for FileSink {
+    fn describe(&self) -> String {
+        format!("file {}", self.path.display())
+    }
+
+    fn send<'a>(&'a self, output: SearchOutput<'a>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            let contents = render_results(output.results, self.format);
+            tokio::fs::write(&self.path, contents).await?;
+            Ok(())
+        })
+    }
+}
+
+struct WebhookSink {
+    client: Client,
+    url: String,
+    headers: BTreeMap<String, String>,
+}
+
+impl Sink for WebhookSink {
+    fn describe(&self) -> String {
+        format!("webhook {}", self.url)
+    }
+
+    fn send<'a>(&'a self, output: SearchOutput<'a>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mut request = self.client.post(&self.url).json(&output.payload());
+            for (name, value) in &self.headers {
+                request = request.header(name, secrets::resolve(value)?);
+            }
+            let response = request.send().await?;
+            if !response.status().is_success() {
+                bail!("answered {}", response.status());
+            }
+            Ok(())
+        })
+    }
+}
+
+struct MqttSink {
+    broker: String,
+    topic: String,
+    username: Option<String>,
+    password: Option<String>,
+    retain: bool,
+}
+
+impl Sink for MqttSink {
+    fn describe(&self) -> String {
+        format!("mqtt {} {}", self.broker, self.topic)
+    }
+
+    fn send<'a>(&'a self, output: SearchOutput<'a>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let payload = serde_json::to_vec(&output.payload())?;
+            tokio::time::timeout(MQTT_TIMEOUT, self.publish(&payload))
+                .await
+                .context("timed out")?
+        })
+    }
+}
+
+impl MqttSink {
+    /// Connect, publish once at QoS 0 and disconnect (MQTT 3.1.1)
+    async fn publish(&self, payload: &[u8]) -> Result<()> {
+        let broker = Url::parse(&self.broker).context("invalid broker URL")?;
+        if !matches!(broker.scheme(), "mqtt" | "tcp") {
+            bail!(
+                "unsupported broker scheme {:?} (use mqtt://)",
+                broker.scheme()
+            );
+        }
+        let host = broker.host_str().context("broker URL has no host")?;
+        let port = broker.port().unwrap_or(MQTT_DEFAULT_PORT);
+        let password = self.password.as_deref().map(secrets::resolve).transpose()?;
+        let mut stream = TcpStream::connect((host, port)).await?;
+
+        let mut connect = Vec::new();
+        put_str(&mut connect, "MQTT");
+        connect.push(4); // protocol level 3.1.1
+        let mut flags = 0x02; // clean session
+        if self.username.is_some() {
+            flags |= 0x80;
+        }
+        if password.is_some() {
+            flags |= 0x40;
+        }
+        connect.push(flags);
+        connect.extend(60u16.to_be_bytes()); // keep-alive seconds
+        put_str(&mut connect, &format!("websearcher-{}", std::process::id()));
+        for field in [&self.username, &password].into_iter().flatten() {
+            put_str(&mut connect, field);
+        }
+        write_packet(&mut stream, 0x10, &connect).await?;
+
+        let mut connack = [0u8; 4];
+        stream.read_exact(&mut connack).await?;
+        if connack[0] != 0x20 {
+            bail!("unexpected reply from the broker");
+        }
+        if connack[3] != 0 {
+            bail!("broker refused the connection (code {})", connack[3]);
+        }
+
+        let mut publish = Vec::with_capacity(self.topic.len() + payload.len() + 2);
+        put_str(&mut publish, &self.topic);
+        publish.extend_from_slice(payload);
+        write_packet(&mut stream, 0x30 | u8::from(self.retain), &publish).await?;
+        write_packet(&mut stream, 0xe0, &[]).await?; // DISCONNECT
+        stream.shutdown().await?;
+        Ok(())
+    }
+}
+
+/// MQTT UTF-8 string: 2-byte big-endian length, then the bytes
+fn put_str(out: &mut Vec<u8>, s: &str) {
+    let len = u16::try_from(s.len()).unwrap_or(u16::MAX);
+    out.extend(len.to_be_bytes());
+    out.extend_from_slice(&s.as_bytes()[..usize::from(len)]);
+}
+
+/// Fixed header (packet type and flags, variable-length size) and `body`
+async fn write_packet(stream: &mut TcpStream, header: u8, body: &[u8]) -> Result<()> {
+    if body.len() > MQTT_MAX_LEN {
+        bail!("message too large for MQTT ({} bytes)", body.len());
+    }
+    let mut packet = vec![header];
+    let mut len = body.len();
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if len == 0 {
+            break;
+        }
+    }
+    packet.extend_from_slice(body);
+    stream.write_all(&packet).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Settings;
+    use tempfile::tempdir;
+    use tokio::net::TcpListener;
+
+    fn results() -> Vec<SearchResult> {
+        vec![SearchResult {
+            site: "fitgirl".into(),
+            title: "Elden Ring".into(),
+            url: "https://a.example/elden-ring/".into(),
+            ..Default::default()
+        }]
+    }
+
+    #[test]
+    fn sinks_are_read_from_config_toml() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            r#"
+[[sinks]]
+kind = "file"
+path = "/tmp/latest.csv"
+format = "csv"
+
+[[sinks]]
+kind = "webhook"
+url = "https://hooks.example/ws"
+headers = { Authorization = "Bearer x" }
+
+[[sinks]]
+kind = "mqtt"
+broker = "mqtt://192.168.1.5"
+topic = "home/websearcher"
+"#,
+        )
+        .unwrap();
+        let sinks = Settings::load(&path).sinks;
+        assert_eq!(
+            sinks[0],
+            SinkConfig::File {
+                path: "/tmp/latest.csv".into(),
+                format: ExportFormat::Csv
+            }
+        );
+        assert!(matches!(&sinks[1], SinkConfig::Webhook { headers, .. } if headers.len() == 1));
+        assert!(matches!(
+            &sinks[2],
+            SinkConfig::Mqtt {
+                retain: false,
+                username: None,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn file_and_webhook_sinks_get_the_results() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("out").join("latest.json");
+        let mut server = mockito::Server::new_async().await;
+        let hook = server
+            .mock("POST", "/hook")
+            .match_header("authorization", "Bearer x")
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"query": "elden ring", "count": 1}"#.into(),
+            ))
+            .with_status(204)
+            .create_async()
+            .await;
+        let failing = server
+            .mock("POST", "/down")
+            .with_status(500)
+            .create_async()
+            .await;
+
+        let configs = [
+            SinkConfig::File {
+                path: path.clone(),
+                format: ExportFormat::Json,
+            },
+            SinkConfig::Webhook {
+                url: format!("{}/hook", server.url()),
+                headers: BTreeMap::from([("Authorization".into(), "Bearer x".into())]),
+            },
+            SinkConfig::Webhook {
+                url: format!("{}/down", server.url()),
+                headers: BTreeMap::new(),
+            },
+        ];
+        let client = Client::new();
+        let sinks: Vec<_> = configs.iter().map(|c| c.build(&client)).collect();
+        let results = results();
+        let failed = send_all(
+            &sinks,
+            SearchOutput {
+                query: "elden ring",
+                results: &results,
+            },
+        )
+        .await;
+
+        hook.assert_async().await;
+        failing.assert_async().await;
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0].0.ends_with("/down"), "{}", failed[0].0);
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(written.contains("\"count\": 1"), "{written}");
+    }
+
+    #[tokio::test]
+    async fn mqtt_sink_publishes_to_the_topic() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        // A broker that accepts one client and hands back what it published
+        let broker = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut received = Vec::new();
+            let mut buf = [0u8; 4096];
+            loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                if received.is_empty() {
+                    assert_eq!(buf[0], 0x10, "CONNECT first");
+                    socket.write_all(&[0x20, 0x02, 0x00, 0x00]).await.unwrap();
+                }
+                if n == 0 {
+                    break;
+                }
+                received.extend_from_slice(&buf[..n]);
+            }
+            received
+        });
+
+        let sink = SinkConfig::Mqtt {
+            broker: format!("mqtt://127.0.0.1:{port}"),
+            topic: "home/websearcher".into(),
+            username: Some("ws".into()),
+            password: Some("secret".into()),
+            retain: true,
+        }
+        .build(&Client::new());
+        let results = results();
+        let output = SearchOutput {
+            query: "elden ring",
+            results: &results,
+        };
+        sink.send(output).await.unwrap();
+
+        let received = broker.await.unwrap();
+        let as_text = String::from_utf8_lossy(&received);
+        assert!(
+            as_text.contains("secret"),
+            "credentials are sent on CONNECT"
+        );
+        let publish = received
+            .iter()
+            .position(|&b| b == 0x31)
+            .expect("retained QoS 0 PUBLISH");
+        let topic = b"\x00\x10home/websearcher";
+        assert!(received[publish..].windows(topic.len()).any(|w| w == topic));
+        assert!(as_text.contains("\"query\":\"elden ring\""));
+        assert_eq!(received.last(), Some(&0x00), "ends with DISCONNECT");
+        assert_eq!(received[received.len() - 2], 0xe0);
+    }
+
+    #[tokio::test]
+    async fn mqtt_sink_reports_a_refused_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 256];
+            let _ = socket.read(&mut buf).await;
+            // 5: not authorized
+            socket.write_all(&[0x20, 0x02, 0x00, 0x05]).await.unwrap();
+        });
+        let sink = SinkConfig::Mqtt {
+            broker: format!("mqtt://127.0.0.1:{port}"),
+            topic: "t".into(),
+            username: None,
+            password: None,
+            retain: false,
+        }
+        .build(&Client::new());
+        let err = sink
+            .send(SearchOutput {
+                query: "q",
+                results: &[],
+            })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("code 5"), "{err}");
+    }
+}
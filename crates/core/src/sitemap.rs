@@ -0,0 +1,346 @@
+//! Searching a site through its sitemap.xml.
+//!
+//! For sites whose HTML search breaks often: the sitemap (and the child
+//! sitemaps of a sitemap index) is downloaded once a day and cached on disk,
+//! query tokens are matched against URL slugs locally, and only the matching
+//! pages are fetched to confirm their titles.
+
+use crate::feed::push_text;
+use crate::models::{Provenance, SearchResult, SiteConfig};
+use crate::parser::{fetched_now, stamp_source};
+use crate::query::build_search_url;
+use quick_xml::Reader;
+use quick_xml::events::Event;
+use reqwest::Url;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// How long a downloaded sitemap is reused, in seconds
+pub const SITEMAP_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Child sitemaps followed from a sitemap index
+const MAX_CHILD_SITEMAPS: usize = 20;
+
+/// A parsed sitemap document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SitemapDoc {
+    /// `<sitemapindex>`: URLs of further sitemaps
+    Index(Vec<String>),
+    /// `<urlset>`: page URLs
+    Urls(Vec<String>),
+}
+
+/// Parse a sitemap or sitemap index; `None` if `xml` is neither
+pub fn parse_sitemap(xml: &str) -> Option<SitemapDoc> {
+    let mut reader = Reader::from_str(xml);
+    let mut is_index = None;
+    let mut in_loc = false;
+    let mut loc = String::new();
+    let mut locs = Vec::new();
+    loop {
+        match reader.read_event().ok()? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"sitemapindex" => is_index = Some(true),
+                b"urlset" => is_index = Some(false),
+                b"loc" => {
+                    in_loc = true;
+                    loc.clear();
+                }
+                _ => {}
+            },
+            Event::End(e) if e.local_name().as_ref() == b"loc" => {
+                in_loc = false;
+                let url = loc.trim();
+                if !url.is_empty() {
+                    locs.push(url.to_string());
+                }
+            }
+            Event::Eof => break,
+            event if in_loc => push_text(&mut loc, &event),
+            _ => {}
+        }
+    }
+    match is_index? {
+        true => Some(SitemapDoc::Index(locs)),
+        false => Some(SitemapDoc::Urls(locs)),
+    }
+}
+
+/// Lowercase alphanumeric words of `s`
+fn tokens(s: &str) -> impl Iterator<Item = String> + '_ {
+    s.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// Whether every word of `query` is a word of the URL's path (its slug)
+pub fn slug_matches(url: &str, query: &str) -> bool {
+    let path = Url::parse(url)
+        .map(|u| u.path().to_string())
+        .unwrap_or_else(|_| url.to_string());
+    let words: Vec<String> = tokens(&path).collect();
+    let mut query_words = tokens(query).peekable();
+    query_words.peek().is_some() && query_words.all(|q| words.contains(&q))
+}
+
+/// Title of a fetched page: `og:title`, else the first `<h1>`, else `<title>`
+pub fn page_title(html: &str) -> Option<String> {
+    let doc = Html::parse_document(html);
+    let meta = Selector::parse("meta[property='og:title']").ok()?;
+    let heading = Selector::parse("h1").ok()?;
+    let title = Selector::parse("title").ok()?;
+    doc.select(&meta)
+        .filter_map(|m| m.value().attr("content"))
+        .map(str::to_string)
+        .chain(doc.select(&heading).map(|h| h.text().collect()))
+        .chain(doc.select(&title).map(|t| t.text().collect()))
+        .map(|t: String| t.split_whitespace().collect::<Vec<_>>().join(" "))
+        .find(|t| !t.is_empty())
+}
+
+/// A site's page URLs as last downloaded
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CachedSitemap {
+    /// Unix timestamp (seconds) of the download
+    pub fetched_at: u64,
+    pub urls: Vec<String>,
+}
+
+impl CachedSitemap {
+    fn path(dir: &Path, site: &str) -> PathBuf {
+        dir.join(format!("{site}.json"))
+    }
+
+    /// The cached sitemap of `site`, if it is younger than [`SITEMAP_TTL_SECS`]
+    pub fn load(dir: &Path, site: &str) -> Option<Self> {
+        let content = std::fs::read_to_string(Self::path(dir, site)).ok()?;
+        let cached: Self = serde_json::from_str(&content).ok()?;
+        (fetched_now().saturating_sub(cached.fetched_at) < SITEMAP_TTL_SECS).then_some(cached)
+    }
+
+    pub fn save(&self, dir: &Path, site: &str) -> anyhow::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(Self::path(dir, site), serde_json::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// Download all page URLs of `site`'s sitemap, following one level of index
+async fn download_urls<F, Fut>(root: &str, fetch: &mut F) -> Vec<String>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Option<String>>,
+{
+    let Some(xml) = fetch(root.to_string()).await else {
+        return Vec::new();
+    };
+    match parse_sitemap(&xml) {
+        Some(SitemapDoc::Urls(urls)) => urls,
+        Some(SitemapDoc::Index(children)) => {
+            let mut urls = Vec::new();
+            for child in children.into_iter().take(MAX_CHILD_SITEMAPS) {
+                if let Some(xml) = fetch(child).await
+                    && let Some(SitemapDoc::Urls(child_urls)) = parse_sitemap(&xml)
+                {
+                    urls.extend(child_urls);
+                }
+            }
+            urls
+        }
+        None => Vec::new(),
+    }
+}
+
+/// Search `site` (a [`SearchKind::Sitemap`](crate::models::SearchKind::Sitemap) site)
+/// for `query`.
+///
+/// `fetch` downloads a URL (sitemaps and pages alike), so callers keep their
+/// own solver and rate-limit handling. The page URLs are cached in `cache_dir`
+/// when given. At most `max_pages` slug matches are fetched; a result is kept
+/// when the page title contains every query word.
+pub async fn search_sitemap<F, Fut>(
+    site: &SiteConfig,
+    query: &str,
+    max_pages: usize,
+    cache_dir: Option<&Path>,
+    mut fetch: F,
+) -> Vec<SearchResult>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Option<String>>,
+{
+    let root = build_search_url(site, query);
+    let cached = cache_dir.and_then(|dir| CachedSitemap::load(dir, &site.name));
+    let urls = match cached {
+        Some(cached) => cached.urls,
+        None => {
+            let urls = download_urls(&root, &mut fetch).await;
+            if let Some(dir) = cache_dir
+                && !urls.is_empty()
+            {
+                let cached = CachedSitemap {
+                    fetched_at: fetched_now(),
+                    urls: urls.clone(),
+                };
+                if let Err(e) = cached.save(dir, &site.name) {
+                    tracing::warn!("Failed to cache sitemap of {}: {}", site.name, e);
+                }
+            }
+            urls
+        }
+    };
+
+    let site_name: Arc<str> = Arc::from(site.name.as_str());
+    let query_words: Vec<String> = tokens(query).collect();
+    let mut results = Vec::new();
+    for url in urls
+        .into_iter()
+        .filter(|u| slug_matches(u, query))
+        .take(max_pages)
+    {
+        let Some(html) = fetch(url.clone()).await else {
+            continue;
+        };
+        let Some(title) = page_title(&html) else {
+            continue;
+        };
+        let title_words: Vec<String> = tokens(&title).collect();
+        if query_words.iter().all(|q| title_words.contains(q)) {
+            results.push(SearchResult {
+                site: site_name.clone(),
+                title,
+                url,
+                ..Default::default()
+            });
+        }
+    }
+    stamp_source(&mut results, &root, fetched_now(), Provenance::Sitemap);
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SearchKind;
+    use std::collections::HashMap;
+
+    fn site() -> SiteConfig {
+        SiteConfig {
+            name: "example".to_string(),
+            base_url: "https://example.com/".to_string(),
+            search_kind: SearchKind::Sitemap,
+            result_selector: "a".into(),
+            ..Default::default()
+        }
+    }
+
+    const INDEX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <sitemap><loc>https://example.com/post-sitemap.xml</loc></sitemap>
+</sitemapindex>"#;
+
+    const POSTS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url><loc>https://example.com/elden-ring-shadow-of-the-erdtree/</loc></url>
+  <url><loc>https://example.com/hollow-knight/</loc></url>
+  <url><loc>https://example.com/ring-of-elden-fan-art/</loc></url>
+  <url><loc><![CDATA[https://example.com/?p=1&x=elden]]></loc></url>
+</urlset>"#;
+
+    #[test]
+    fn parses_index_and_urlset() {
+        assert_eq!(
+            parse_sitemap(INDEX),
+            Some(SitemapDoc::Index(vec![
+                "https://example.com/post-sitemap.xml".to_string()
+            ]))
+        );
+        let Some(SitemapDoc::Urls(urls)) = parse_sitemap(POSTS) else {
+            panic!("urlset expected");
+        };
+        assert_eq!(urls.len(), 4);
+        assert_eq!(urls[3], "https://example.com/?p=1&x=elden");
+        let escaped = "<urlset><url><loc>https://example.com/?a=1&amp;b=2</loc></url></urlset>";
+        assert_eq!(
+            parse_sitemap(escaped),
+            Some(SitemapDoc::Urls(vec![
+                "https://example.com/?a=1&b=2".to_string()
+            ]))
+        );
+        assert_eq!(parse_sitemap("<html><body>not xml</body></html>"), None);
+    }
+
+    #[test]
+    fn slugs_match_on_whole_words() {
+        assert!(slug_matches(
+            "https://example.com/elden-ring-shadow-of-the-erdtree/",
+            "Elden Ring"
+        ));
+        assert!(!slug_matches(
+            "https://example.com/eldenring/",
+            "elden ring"
+        ));
+        // The host does not count as slug
+        assert!(!slug_matches(
+            "https://elden.example.com/ring/",
+            "elden ring"
+        ));
+        assert!(!slug_matches("https://example.com/elden-ring/", ""));
+    }
+
+    #[test]
+    fn page_title_prefers_og_title() {
+        let html = r#"<html><head><title>Site - Elden Ring</title><meta property="og:title" content="Elden Ring: Deluxe"></head><body><h1>Heading</h1></body></html>"#;
+        assert_eq!(page_title(html).as_deref(), Some("Elden Ring: Deluxe"));
+        let html = "<html><head><title>Elden   Ring</title></head><body></body></html>";
+        assert_eq!(page_title(html).as_deref(), Some("Elden Ring"));
+    }
+
+    #[tokio::test]
+    async fn search_fetches_only_matching_pages_and_caches_the_sitemap() {
+        let pages: HashMap<&str, &str> = HashMap::from([
+            ("https://example.com/sitemap.xml", INDEX),
+            ("https://example.com/post-sitemap.xml", POSTS),
+            (
+                "https://example.com/elden-ring-shadow-of-the-erdtree/",
+                "<html><head><title>Elden Ring Shadow of the Erdtree</title></head></html>",
+            ),
+            (
+                "https://example.com/ring-of-elden-fan-art/",
+                "<html><head><title>Fan art</title></head></html>",
+            ),
+        ]);
+        let dir = tempfile::tempdir().unwrap();
+        let mut fetched = Vec::new();
+        let results = search_sitemap(&site(), "elden ring", 10, Some(dir.path()), |url| {
+            fetched.push(url.clone());
+            let body = pages.get(url.as_str()).map(|b| b.to_string());
+            async move { body }
+        })
+        .await;
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "Elden Ring Shadow of the Erdtree");
+        assert_eq!(results[0].rank, Some(1));
+        assert_eq!(
+            results[0].source_url.as_deref(),
+            Some("https://example.com/sitemap.xml")
+        );
+        // hollow-knight never matched its slug, so it was not fetched
+        assert!(!fetched.iter().any(|u| u.contains("hollow-knight")));
+        assert_eq!(fetched.len(), 4);
+
+        // The second search reads the sitemap from the cache
+        let mut refetched = Vec::new();
+        search_sitemap(&site(), "elden ring", 10, Some(dir.path()), |url| {
+            refetched.push(url.clone());
+            let body = pages.get(url.as_str()).map(|b| b.to_string());
+            async move { body }
+        })
+        .await;
+        assert!(!refetched.iter().any(|u| u.ends_with(".xml")));
+    }
+}
@@ -0,0 +1,437 @@
+//! Moving everything the app keeps to another machine, or back from a backup.
+//!
+//! `state export state.zip` bundles the files a user would miss: the config,
+//! the sites file with their site overrides, the search cache, history,
+//! favorites, notes, the ignore list and the watchlist. Each is stored under
+//! its kind (`config/config.toml`, `data/watchlist.json`, ...) next to
+//! `manifest.json`, which lists them.
+//!
+//! `state import` either replaces the local files with the archive's, or
+//! merges the two: TOML tables gain the keys only the archive has, notes the
+//! URLs only it has, and lists (searches, history, favorites, rules, watches)
+//! the records only it has, with fresh ids where the list numbers them. What
+//! is already here always wins.
+//!
+//! Secrets stay behind: the keyring cannot be exported and the encrypted file
+//! is useless without its key. Store them again with `secrets set`.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use zip::write::SimpleFileOptions;
+
+use crate::{config, paths, settings};
+
+const MANIFEST: &str = "manifest.json";
+
+/// Layout of the archive; bumped when an older app could not import it
+const FORMAT: u32 = 1;
+
+/// How a file is merged into the local one
+#[derive(Debug, Clone, Copy)]
+enum Merge {
+    /// Tables gain the keys only the archive has, recursively
+    Toml,
+    /// The object at this field gains the keys only the archive has
+    Map(&'static str),
+    /// Records in each listed array are added unless one with the same
+    /// values at the key fields is here already
+    Records {
+        lists: &'static [(&'static str, &'static [&'static str])],
+        /// Field the merged lists are sorted by, oldest first
+        order: Option<&'static str>,
+    },
+}
+
+/// A file in the archive
+struct Bundled {
+    name: &'static str,
+    path: fn() -> PathBuf,
+    merge: Merge,
+}
+
+fn bundled() -> [Bundled; 8] {
+    [
+        Bundled {
+            name: "config/config.toml",
+            path: settings::default_settings_path,
+            merge: Merge::Toml,
+        },
+        Bundled {
+            name: "config/sites.toml",
+            path: config::default_config_path,
+            merge: Merge::Toml,
+        },
+        Bundled {
+            name: "cache/search_cache.json",
+            path: paths::search_cache_file,
+            merge: Merge::Records {
+                lists: &[("entries", &["query"])],
+                order: Some("timestamp"),
+            },
+        },
+        Bundled {
+            name: "state/search_history.json",
+            path: paths::history_file,
+            merge: Merge::Records {
+                lists: &[("entries", &["query", "timestamp"])],
+                order: Some("timestamp"),
+            },
+        },
+        Bundled {
+            name: "data/favorites.json",
+            path: paths::favorites_file,
+            merge: Merge::Records {
+                lists: &[("items", &["url"])],
+                order: Some("added_at"),
+            },
+        },
+        Bundled {
+            name: "data/notes.json",
+            path: paths::notes_file,
+            merge: Merge::Map("notes"),
+        },
+        Bundled {
+            name: "data/ignore.json",
+            path: paths::ignore_file,
+            merge: Merge::Records {
+                lists: &[("rules", &["kind", "value"])],
+                order: None,
+            },
+        },
+        Bundled {
+            name: "data/watchlist.json",
+            path: paths::watchlist_file,
+            merge: Merge::Records {
+                lists: &[("entries", &["query"]), ("pages", &["url"])],
+                order: None,
+            },
+        },
+    ]
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    format: u32,
+    /// Unix timestamp (seconds) of the export
+    exported_at: u64,
+    files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Combine each file with the local one; local entries win
+    #[default]
+    Merge,
+    /// Make the local files those of the archive, removing the ones it lacks
+    Replace,
+}
+
+impl FromStr for ImportMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "merge" => Ok(Self::Merge),
+            "replace" => Ok(Self::Replace),
+            _ => Err(format!("unknown mode {s:?} (expected merge or replace)")),
+        }
+    }
+}
+
+/// What an import did to one file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Change {
+    /// There was no local file
+    Added,
+    Merged,
+    Replaced,
+    /// The archive has no such file
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Imported {
+    pub file: &'static str,
+    pub change: Change,
+}
+
+/// Write every bundled file that exists into a new zip at `path`; returns
+/// their names in the archive
+pub fn export(path: &Path) -> Result<Vec<&'static str>> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut zip = zip::ZipWriter::new(
+        std::fs::File::create(path).with_context(|| format!("cannot create {}", path.display()))?,
+    );
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let mut names = Vec::new();
+    for file in bundled() {
+        let Ok(data) = std::fs::read((file.path)()) else {
+            continue;
+        };
+        zip.start_file(file.name, options)?;
+        zip.write_all(&data)?;
+        names.push(file.name);
+    }
+    let manifest = Manifest {
+        format: FORMAT,
+        exported_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        files: names.iter().map(|n| n.to_string()).collect(),
+    };
+    zip.start_file(MANIFEST, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+    zip.finish()?.sync_all()?;
+    Ok(names)
+}
+
+/// Restore the files in the zip at `path`. Nothing is written unless every
+/// file in the archive could be read and merged.
+pub fn import(path: &Path, mode: ImportMode) -> Result<Vec<Imported>> {
+    let file =
+        std::fs::File::open(path).with_context(|| format!("cannot open {}", path.display()))?;
+    let mut zip = zip::ZipArchive::new(file).context("not a state archive")?;
+    let manifest: Manifest = {
+        let mut raw = String::new();
+        zip.by_name(MANIFEST)
+            .context("not a state archive: missing manifest.json")?
+            .read_to_string(&mut raw)?;
+        serde_json::from_str(&raw).context("invalid manifest.json")?
+    };
+    if manifest.format > FORMAT {
+        bail!("the archive was exported by a newer version; update to import it");
+    }
+
+    let mut writes: Vec<(PathBuf, Option<String>, Imported)> = Vec::new();
+    for file in bundled() {
+        let local_path = (file.path)();
+        let incoming = if manifest.files.iter().any(|n| n == file.name) {
+            let mut raw = String::new();
+            zip.by_name(file.name)
+                .with_context(|| format!("the archive lists {} but lacks it", file.name))?
+                .read_to_string(&mut raw)
+                .with_context(|| format!("{} is not UTF-8 text", file.name))?;
+            Some(raw)
+        } else {
+            None
+        };
+        let local = std::fs::read_to_string(&local_path).ok();
+        let (content, change) = match (mode, incoming, local) {
+            (_, Some(incoming), None) => (Some(incoming), Change::Added),
+            (ImportMode::Replace, Some(incoming), Some(_)) => (Some(incoming), Change::Replaced),
+            (ImportMode::Replace, None, Some(_)) => (None, Change::Removed),
+            (ImportMode::Merge, Some(incoming), Some(local)) => {
+                let merged = merge(file.merge, &local, &incoming)
+                    .with_context(|| format!("cannot merge {}", file.name))?;
+                // Left alone, comments and all, when the archive adds nothing
+                let Some(merged) = merged else { continue };
+                (Some(merged), Change::Merged)
+            }
+            (_, None, _) => continue,
+        };
+        writes.push((
+            local_path,
+            content,
+            Imported {
+                file: file.name,
+                change,
+            },
+        ));
+    }
+
+    let mut imported = Vec::new();
+    for (path, content, done) in writes {
+        match content {
+            Some(content) => {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&path, content)
+                    .with_context(|| format!("cannot write {}", path.display()))?;
+            }
+            None => std::fs::remove_file(&path)
+                .with_context(|| format!("cannot remove {}", path.display()))?,
+        }
+        imported.push(done);
+    }
+    Ok(imported)
+}
+
+/// The local file's content with what only the archive's has added, or
+/// `None` if it has nothing to add
+fn merge(how: Merge, local: &str, incoming: &str) -> Result<Option<String>> {
+    match how {
+        Merge::Toml => {
+            let mut local: toml::Table = toml::from_str(local).context("local file")?;
+            let before = local.clone();
+            let incoming: toml::Table = toml::from_str(incoming).context("archived file")?;
+            merge_tables(&mut local, incoming);
+            if local == before {
+                return Ok(None);
+            }
+            Ok(Some(toml::to_string_pretty(&local)?))
+        }
+        Merge::Map(field) => {
+            let (mut local, incoming) = parse_json(local, incoming)?;
+            let before = local.clone();
+            if let (Some(here), Some(Value::Object(there))) = (
+                local.get_mut(field).and_then(Value::as_object_mut),
+                incoming.get(field),
+            ) {
+                for (key, value) in there {
+                    here.entry(key.clone()).or_insert_with(|| value.clone());
+                }
+            }
+            if local == before {
+                return Ok(None);
+            }
+            Ok(Some(serde_json::to_string_pretty(&local)?))
+        }
+        Merge::Records { lists, order } => {
+            let (mut local, incoming) = parse_json(local, incoming)?;
+            let mut next_id = local.get("next_id").and_then(Value::as_u64);
+            let mut added = false;
+            for (field, key) in lists {
+                let Some(there) = incoming.get(field).and_then(Value::as_array) else {
+                    continue;
+                };
+                let Some(here) = local.get_mut(field).and_then(Value::as_array_mut) else {
+                    continue;
+                };
+                let record_key = |record: &Value| -> Vec<Value> {
+                    key.iter().map(|k| record[k].clone()).collect()
+                };
+                let known: HashSet<String> = here
+                    .iter()
+                    .map(|r| Value::Array(record_key(r)).to_string())
+                    .collect();
+                for record in there {
+                    if known.contains(&Value::Array(record_key(record)).to_string()) {
+                        continue;
+                    }
+                    let mut record = record.clone();
+                    if let (Some(id), Some(fields)) = (next_id.as_mut(), record.as_object_mut())
+                        && fields.contains_key("id")
+                    {
+                        *id += 1;
+                        fields.insert("id".to_string(), Value::from(*id));
+                    }
+                    here.push(record);
+                    added = true;
+                }
+                if let Some(order) = order {
+                    here.sort_by_key(|r| r.get(order).and_then(Value::as_u64).unwrap_or(0));
+                }
+            }
+            if !added {
+                return Ok(None);
+            }
+            if let Some(next_id) = next_id {
+                local["next_id"] = Value::from(next_id);
+            }
+            Ok(Some(serde_json::to_string_pretty(&local)?))
+        }
+    }
+}
+
+fn parse_json(local: &str, incoming: &str) -> Result<(Value, Value)> {
+    Ok((
+        serde_json::from_str(local).context("local file")?,
+        serde_json::from_str(incoming).context("archived file")?,
+    ))
+}
+
+fn merge_tables(here: &mut toml::Table, there: toml::Table) {
+    for (key, value) in there {
+        match (here.get_mut(&key), value) {
+            (Some(toml::Value::Table(here)), toml::Value::Table(there)) => {
+                merge_tables(here, there)
+            }
+            (Some(_), _) => {}
+            (None, value) => {
+                here.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merging_adds_only_what_is_missing_here() {
+        let local = r#"{"entries":[{"id":1,"query":"hades","sites":[]}],"pages":[],"next_id":1}"#;
+        let incoming = r#"{"entries":[{"id":1,"query":"celeste","sites":[]},{"id":2,"query":"hades","sites":["fitgirl"]}],"pages":[{"id":3,"url":"https://a.example/tunic"}],"next_id":3}"#;
+        let lists: &[(&str, &[&str])] = &[("entries", &["query"]), ("pages", &["url"])];
+        let merged: Value = serde_json::from_str(
+            &merge(Merge::Records { lists, order: None }, local, incoming)
+                .unwrap()
+                .unwrap(),
+        )
+        .unwrap();
+        // The local watch of "hades" stays as it was; the others get new ids
+        assert_eq!(merged["entries"][0]["sites"], serde_json::json!([]));
+        assert_eq!(merged["entries"][1]["query"], "celeste");
+        assert_eq!(merged["entries"][1]["id"], 2);
+        assert_eq!(merged["pages"][0]["id"], 3);
+        assert_eq!(merged["next_id"], 3);
+
+        let local = r#"{"entries":[{"query":"hades","timestamp":20}],"max_size":3}"#;
+        let incoming = r#"{"entries":[{"query":"tunic","timestamp":10}],"max_size":5}"#;
+        let lists: &[(&str, &[&str])] = &[("entries", &["query"])];
+        let merged: Value = serde_json::from_str(
+            &merge(
+                Merge::Records {
+                    lists,
+                    order: Some("timestamp"),
+                },
+                local,
+                incoming,
+            )
+            .unwrap()
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(merged["entries"][0]["query"], "tunic");
+        assert_eq!(merged["max_size"], 3);
+
+        let local = r#"{"notes":{"https://a.example/1":{"text":"mine"}}}"#;
+        let incoming = r#"{"notes":{"https://a.example/1":{"text":"theirs"},"https://a.example/2":{"text":"new"}}}"#;
+        let merged: Value = serde_json::from_str(
+            &merge(Merge::Map("notes"), local, incoming)
+                .unwrap()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(merged["notes"]["https://a.example/1"]["text"], "mine");
+        assert_eq!(merged["notes"]["https://a.example/2"]["text"], "new");
+
+        let local = "language = \"ru\"\n[sites.fitgirl]\ncookie = \"a\"\n";
+        let incoming = "language = \"en\"\nproxy = \"socks5://h:1\"\n[sites.fitgirl]\ncookie = \"b\"\nenabled = false\n[sites.dodi]\nenabled = true\n";
+        let merged: toml::Table =
+            toml::from_str(&merge(Merge::Toml, local, incoming).unwrap().unwrap()).unwrap();
+        assert_eq!(merged["language"].as_str(), Some("ru"));
+        assert_eq!(merged["proxy"].as_str(), Some("socks5://h:1"));
+        assert_eq!(merged["sites"]["fitgirl"]["cookie"].as_str(), Some("a"));
+        assert_eq!(merged["sites"]["fitgirl"]["enabled"].as_bool(), Some(false));
+        assert!(merged["sites"].get("dodi").is_some());
+        assert!(merge(Merge::Toml, local, "not = [toml").is_err());
+        // Nothing new leaves the file as it is
+        assert_eq!(merge(Merge::Toml, local, local).unwrap(), None);
+        assert_eq!(
+            merge(Merge::Map("notes"), "{\"notes\":{}}", "{}").unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,377 @@
+//! Steam AppIDs for result titles.
+//!
+//! Steam's full app list (every AppID and name) is downloaded once a week and
+//! cached on disk. A result title is cut down to the game's name (release
+//! tags, versions, bracketed notes and edition suffixes removed) and looked up
+//! by exact name, so a result links to a store page only when the name is
+//! unambiguous. `WEBSITE_SEARCHER_STEAM_APPS_URL` replaces the download URL.
+
+use crate::fetcher::fetch_with_retry;
+use crate::models::SearchResult;
+use crate::parser::fetched_now;
+use crate::paths;
+use anyhow::Context;
+use regex::Regex;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Steam's list of every app
+pub const APP_LIST_URL: &str = "https://api.steampowered.com/ISteamApps/GetAppList/v2/";
+
+/// How long a downloaded app list is reused, in seconds
+pub const STEAM_APPS_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// After a failed download, how long a stale list is used before trying again
+const RETRY_AFTER_SECS: u64 = 60 * 60;
+
+/// The app list loaded in this process, shared by every search
+static LOADED: Mutex<Option<Arc<SteamApps>>> = Mutex::new(None);
+
+/// Store page of `appid`
+pub fn store_url(appid: u32) -> String {
+    format!("https://store.steampowered.com/app/{appid}/")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct App {
+    pub appid: u32,
+    pub name: String,
+}
+
+/// The app list as last downloaded
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CachedAppList {
+    /// Unix timestamp (seconds) of the download
+    pub fetched_at: u64,
+    pub apps: Vec<App>,
+}
+
+impl CachedAppList {
+    /// The list cached at `path`, fresh or not
+    pub fn load(path: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Younger than [`STEAM_APPS_TTL_SECS`]
+    pub fn is_fresh(&self) -> bool {
+        fetched_now().saturating_sub(self.fetched_at) < STEAM_APPS_TTL_SECS
+    }
+}
+
+/// `GetAppList` response
+#[derive(Deserialize)]
+struct AppListResponse {
+    applist: AppListBody,
+}
+
+#[derive(Deserialize)]
+struct AppListBody {
+    apps: Vec<App>,
+}
+
+/// Parse a `GetAppList` response
+pub fn parse_app_list(json: &str) -> anyhow::Result<Vec<App>> {
+    let response: AppListResponse = serde_json::from_str(json).context("invalid Steam app list")?;
+    Ok(response.applist.apps)
+}
+
+/// AppIDs by normalized name
+#[derive(Debug, Default)]
+pub struct SteamApps {
+    by_name: HashMap<String, u32>,
+    /// Unix timestamp (seconds) after which the list is loaded again
+    expires_at: u64,
+}
+
+impl SteamApps {
+    /// Index `apps` by name. Names shared by several apps keep the lowest
+    /// AppID, which is usually the game rather than a later re-release.
+    pub fn new(apps: &[App]) -> Self {
+        let mut by_name: HashMap<String, u32> = HashMap::with_capacity(apps.len());
+        for app in apps {
+            let key = normalize_name(&app.name);
+            if key.is_empty() {
+                continue;
+            }
+            by_name
+                .entry(key)
+                .and_modify(|id| *id = (*id).min(app.appid))
+                .or_insert(app.appid);
+        }
+        Self {
+            by_name,
+            expires_at: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_name.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_name.is_empty()
+    }
+
+    /// AppID of the game a result `title` names, if one matches exactly
+    pub fn lookup(&self, title: &str) -> Option<u32> {
+        title_candidates(title)
+            .iter()
+            .find_map(|name| self.by_name.get(&normalize_name(name)).copied())
+    }
+
+    /// Set `steam_appid` on every result whose title matches an app.
+    /// Returns the number of results matched.
+    pub fn annotate(&self, results: &mut [SearchResult]) -> usize {
+        let mut matched = 0;
+        for r in results {
+            r.steam_appid = self.lookup(&r.title);
+            matched += usize::from(r.steam_appid.is_some());
+        }
+        matched
+    }
+}
+
+/// Lowercase words of `name`, without punctuation or trademark signs
+fn normalize_name(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            '\'' | '’' | '™' | '®' | '©' => {}
+            c if c.is_alphanumeric() => out.extend(c.to_lowercase()),
+            _ => out.push(' '),
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+static BRACKETS: OnceLock<Regex> = OnceLock::new();
+static RELEASE_TAIL: OnceLock<Regex> = OnceLock::new();
+static EDITION: OnceLock<Regex> = OnceLock::new();
+
+/// Names a result title may be using, most specific first: the title without
+/// release details, then without an edition suffix, then before a subtitle
+fn title_candidates(title: &str) -> Vec<String> {
+    let brackets = BRACKETS.get_or_init(|| {
+        Regex::new(r"\[[^\]]*\]|\([^)]*\)|\{[^}]*\}").expect("valid bracket pattern")
+    });
+    // Separators and markers that start the release details, e.g.
+    // "– v1.2 + 5 DLCs", "Build 123", "Free Download", "MULTi12"
+    let release_tail = RELEASE_TAIL.get_or_init(|| {
+        Regex::new(
+            r"(?i)\s[-–—|+/]\s|\s+v\d|\s+build\s*\d|\s+(?:free\s+download|repack|update\s+\d|multi\s?\d+)\b|,\s",
+        )
+        .expect("valid release pattern")
+    });
+    let edition = EDITION.get_or_init(|| {
+        Regex::new(
+            r"(?i)[\s:–-]*\b(?:(?:digital\s+)?(?:deluxe|ultimate|gold|complete|definitive|premium|enhanced|anniversary|legendary|special|collector'?s|game\s+of\s+the\s+year|goty|standard)\s+edition|goty|director'?s\s+cut)\s*$",
+        )
+        .expect("valid edition pattern")
+    });
+
+    let unbracketed = brackets.replace_all(title, " ");
+    let base = match release_tail.find(&unbracketed) {
+        Some(m) => &unbracketed[..m.start()],
+        None => &unbracketed,
+    };
+    let base = base.trim();
+    let without_edition = edition.replace(base, "");
+    let without_edition = without_edition.trim();
+
+    let mut candidates: Vec<String> = Vec::new();
+    for name in [base, without_edition]
+        .into_iter()
+        .flat_map(|n| [Some(n), n.split_once(':').map(|(before, _)| before.trim())])
+        .flatten()
+    {
+        if !name.is_empty() && !candidates.iter().any(|c| c == name) {
+            candidates.push(name.to_string());
+        }
+    }
+    candidates
+}
+
+/// Where to download the app list from
+fn app_list_url() -> String {
+    std::env::var("WEBSITE_SEARCHER_STEAM_APPS_URL")
+        .ok()
+        .filter(|u| !u.trim().is_empty())
+        .unwrap_or_else(|| APP_LIST_URL.to_string())
+}
+
+async fn download(client: &Client) -> anyhow::Result<CachedAppList> {
+    let body = fetch_with_retry(client, &app_list_url(), None, Some("steam")).await?;
+    let apps = parse_app_list(&body)?;
+    anyhow::ensure!(!apps.is_empty(), "empty Steam app list");
+    Ok(CachedAppList {
+        fetched_at: fetched_now(),
+        apps,
+    })
+}
+
+/// The app list, downloading it when the cached copy is older than a week.
+/// A stale copy is used if the download fails; `None` if there is no list.
+pub async fn load(client: &Client) -> Option<Arc<SteamApps>> {
+    let now = fetched_now();
+    if let Some(apps) = lock_loaded().clone()
+        && now < apps.expires_at
+    {
+        return Some(apps);
+    }
+
+    let path = paths::steam_apps_file();
+    let cached = CachedAppList::load(&path);
+    let (list, expires_at) = match cached {
+        Some(list) if list.is_fresh() => {
+            let expires_at = list.fetched_at + STEAM_APPS_TTL_SECS;
+            (list, expires_at)
+        }
+        stale => match download(client).await {
+            Ok(list) => {
+                if let Err(e) = list.save(&path) {
+                    tracing::warn!("Failed to cache the Steam app list: {}", e);
+                }
+                (list, now + STEAM_APPS_TTL_SECS)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to download the Steam app list: {:#}", e);
+                (stale?, now + RETRY_AFTER_SECS)
+            }
+        },
+    };
+
+    let apps = Arc::new(SteamApps {
+        expires_at,
+        ..SteamApps::new(&list.apps)
+    });
+    *lock_loaded() = Some(apps.clone());
+    Some(apps)
+}
+
+/// Look up the Steam AppID of every result. Results are left without one
+/// when no app list can be had.
+pub async fn annotate(client: &Client, results: &mut [SearchResult]) -> usize {
+    match load(client).await {
+        Some(apps) => apps.annotate(results),
+        None => 0,
+    }
+}
+
+fn lock_loaded() -> std::sync::MutexGuard<'static, Option<Arc<SteamApps>>> {
+    LOADED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apps() -> SteamApps {
+        let list = r#"{"applist":{"apps":[
+            {"appid":1245620,"name":"ELDEN RING"},
+            {"appid":2622380,"name":"ELDEN RING NIGHTREIGN"},
+            {"appid":1145360,"name":"Hades"},
+            {"appid":1145350,"name":"Hades II"},
+            {"appid":1086940,"name":"Baldur's Gate 3"},
+            {"appid":1091500,"name":"Cyberpunk 2077"},
+            {"appid":9999999,"name":"Cyberpunk 2077"},
+            {"appid":1196590,"name":"Resident Evil Village"},
+            {"appid":5,"name":""}
+        ]}}"#;
+        SteamApps::new(&parse_app_list(list).unwrap())
+    }
+
+    #[test]
+    fn release_titles_resolve_to_the_game() {
+        let apps = apps();
+        assert_eq!(
+            apps.lookup("Elden Ring: Deluxe Edition – v1.16 + 5 DLCs"),
+            Some(1245620)
+        );
+        assert_eq!(apps.lookup("Elden Ring Deluxe Edition"), Some(1245620));
+        assert_eq!(
+            apps.lookup("ELDEN RING NIGHTREIGN Free Download (v1.01)"),
+            Some(2622380)
+        );
+        assert_eq!(apps.lookup("Hades II [FitGirl Repack]"), Some(1145350));
+        assert_eq!(apps.lookup("Hades v1.38290"), Some(1145360));
+        assert_eq!(
+            apps.lookup("Baldurs Gate 3 - Digital Deluxe Edition"),
+            Some(1086940)
+        );
+        assert_eq!(
+            apps.lookup("Resident Evil Village: Gold Edition, MULTi13"),
+            Some(1196590)
+        );
+        // The lowest AppID wins a shared name
+        assert_eq!(
+            apps.lookup("Cyberpunk 2077 Ultimate Edition"),
+            Some(1091500)
+        );
+    }
+
+    #[test]
+    fn only_exact_names_match() {
+        let apps = apps();
+        assert_eq!(apps.len(), 7);
+        assert_eq!(apps.lookup("Elden Ringo"), None);
+        assert_eq!(apps.lookup("Hades III"), None);
+        assert_eq!(apps.lookup(""), None);
+    }
+
+    #[test]
+    fn annotate_sets_and_clears_appids() {
+        let mut results = vec![
+            SearchResult {
+                site: "fitgirl".into(),
+                title: "Hades II (v0.1)".into(),
+                url: "https://a.example/hades-ii/".into(),
+                ..Default::default()
+            },
+            SearchResult {
+                site: "fitgirl".into(),
+                title: "Some Unknown Game".into(),
+                url: "https://a.example/unknown/".into(),
+                steam_appid: Some(1),
+                ..Default::default()
+            },
+        ];
+        assert_eq!(apps().annotate(&mut results), 1);
+        assert_eq!(results[0].steam_appid, Some(1145350));
+        assert_eq!(results[1].steam_appid, None);
+    }
+
+    #[test]
+    fn cached_list_expires_after_a_week() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("steam_apps.json");
+        let list = CachedAppList {
+            fetched_at: fetched_now(),
+            apps: vec![App {
+                appid: 10,
+                name: "Counter-Strike".into(),
+            }],
+        };
+        list.save(&path).unwrap();
+        let loaded = CachedAppList::load(&path).unwrap();
+        assert!(loaded.is_fresh());
+        assert_eq!(loaded.apps, list.apps);
+
+        let old = CachedAppList {
+            fetched_at: fetched_now() - STEAM_APPS_TTL_SECS - 1,
+            ..list
+        };
+        assert!(!old.is_fresh());
+    }
+}
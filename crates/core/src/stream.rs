@@ -0,0 +1,88 @@
+//! Results handed over site by site, as each site's search finishes.
+//!
+//! A search runs every site at once, and the slowest one decides when the
+//! combined results are ready. [`SearchStream::channel`] gives the search a
+//! [`StreamSender`] to pass each site's results to as soon as that site is
+//! done, and the caller the [`SearchStream`] they arrive on, in the order the
+//! sites finish. The stream ends once every sender has been dropped, i.e. when
+//! the search is over.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use tokio::sync::mpsc;
+
+use crate::models::SearchResult;
+
+/// The results of one site that finished
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiteResults {
+    pub site: String,
+    pub results: Vec<SearchResult>,
+}
+
+/// The search's end of a [`SearchStream`]
+#[derive(Debug, Clone)]
+pub struct StreamSender(mpsc::UnboundedSender<SiteResults>);
+
+impl StreamSender {
+    /// Hand over `site`'s results; ignored once the stream was dropped, so a
+    /// reader going away doesn't fail the search
+    pub fn send(&self, site: &str, results: Vec<SearchResult>) {
+        let _ = self.0.send(SiteResults {
+            site: site.to_string(),
+            results,
+        });
+    }
+}
+
+/// Each site's results, in the order the sites finish
+#[derive(Debug)]
+pub struct SearchStream(mpsc::UnboundedReceiver<SiteResults>);
+
+impl SearchStream {
+    /// A stream and the sender the search hands results to
+    pub fn channel() -> (StreamSender, SearchStream) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (StreamSender(tx), SearchStream(rx))
+    }
+}
+
+impl Stream for SearchStream {
+    type Item = SiteResults;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<SiteResults>> {
+        self.0.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn sites_arrive_as_they_finish_until_the_search_ends() {
+        let (sender, stream) = SearchStream::channel();
+        let result = |site: &str| SearchResult {
+            site: site.into(),
+            title: "Elden Ring".into(),
+            url: format!("https://{site}.example/elden-ring/"),
+            ..Default::default()
+        };
+        for (site, delay) in [("slow", 40), ("fast", 0)] {
+            let sender = sender.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                sender.send(site, vec![result(site)]);
+            });
+        }
+        drop(sender);
+        let arrived: Vec<SiteResults> = stream.collect().await;
+        let sites: Vec<&str> = arrived.iter().map(|s| s.site.as_str()).collect();
+        assert_eq!(sites, ["fast", "slow"]);
+        assert_eq!(arrived[1].results, [result("slow")]);
+    }
+}
@@ -0,0 +1,140 @@
+//! Search templates: recurring structured lookups kept in `config.toml`.
+//!
+//! ```toml
+//! [templates.switch-roms]
+//! query = "{title} -demo -beta"
+//! sites = ["nswpedia"]
+//! format = "table"
+//! ```
+//!
+//! `websearcher template run switch-roms --title "zelda"` replaces each
+//! `{name}` placeholder of the query with the value given as `--name` and
+//! searches the result like a typed query, so the advanced operators (`-term`,
+//! `"phrase"`, `regex:`) act as the template's filters.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::notes::NoteStatus;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct SearchTemplate {
+    /// Query with `{name}` placeholders
+    pub query: String,
+    /// Sites searched; empty leaves them to `--sites`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sites: Vec<String>,
+    /// Maximum results per site
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// Output format, `json` or `table`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    /// Results with these statuses are left out (see [`crate::notes`])
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hide_status: Vec<NoteStatus>,
+}
+
+impl SearchTemplate {
+    /// Names of the query's placeholders, each once, in order. A name starts
+    /// with a letter, so `regex:v[0-9]{2}` keeps its repetition.
+    pub fn placeholders(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        let mut rest = self.query.as_str();
+        while let Some(start) = rest.find('{') {
+            rest = &rest[start + 1..];
+            let Some(end) = rest.find('}') else {
+                break;
+            };
+            let name = &rest[..end];
+            let valid = name.starts_with(|c: char| c.is_ascii_alphabetic())
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            if valid && !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// The query with every placeholder replaced by its value; each
+    /// placeholder needs a value and each value a placeholder
+    pub fn fill(&self, values: &BTreeMap<String, String>) -> anyhow::Result<String> {
+        let placeholders = self.placeholders();
+        if let Some(name) = values.keys().find(|n| !placeholders.contains(&n.as_str())) {
+            anyhow::bail!("the template has no {{{name}}} placeholder");
+        }
+        let mut query = self.query.clone();
+        for name in placeholders {
+            let value = values.get(name).map(|v| v.trim()).unwrap_or_default();
+            if value.is_empty() {
+                anyhow::bail!("missing --{name}");
+            }
+            query = query.replace(&format!("{{{name}}}"), value);
+        }
+        Ok(query.split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+}
+
+/// Placeholder values of `template run`, given as `--name value` or
+/// `--name=value`; a repeated name keeps its last value
+pub fn parse_values(args: &[String]) -> anyhow::Result<BTreeMap<String, String>> {
+    let mut values = BTreeMap::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let Some(flag) = arg.strip_prefix("--").filter(|f| !f.is_empty()) else {
+            anyhow::bail!("unexpected argument {arg:?}; give values as --name value");
+        };
+        let (name, value) = match flag.split_once('=') {
+            Some((name, value)) => (name, value.to_string()),
+            None => match args.next() {
+                Some(value) => (flag, value.clone()),
+                None => anyhow::bail!("--{flag} needs a value"),
+            },
+        };
+        values.insert(name.to_string(), value);
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn placeholders_are_filled_from_the_given_values() {
+        let template = SearchTemplate {
+            query: "{title} {edition} regex:v[0-9]{2} -demo -beta {title}".into(),
+            ..Default::default()
+        };
+        assert_eq!(template.placeholders(), ["title", "edition"]);
+
+        let values = parse_values(&args(&["--title", "zelda", "--edition=  "])).unwrap();
+        assert_eq!(
+            template.fill(&values).unwrap_err().to_string(),
+            "missing --edition"
+        );
+        let values = parse_values(&args(&["--title", " breath of the wild", "--edition=goty"]));
+        assert_eq!(
+            template.fill(&values.unwrap()).unwrap(),
+            "breath of the wild goty regex:v[0-9]{2} -demo -beta breath of the wild"
+        );
+
+        let values = parse_values(&args(&["--title", "zelda", "--platform", "nsp"])).unwrap();
+        assert_eq!(
+            template.fill(&values).unwrap_err().to_string(),
+            "the template has no {platform} placeholder"
+        );
+        assert_eq!(
+            parse_values(&args(&["--title"])).unwrap_err().to_string(),
+            "--title needs a value"
+        );
+        assert!(parse_values(&args(&["zelda"])).is_err());
+    }
+}
@@ -0,0 +1,217 @@
+//! Result images, fetched once and kept on disk.
+//!
+//! A card in the GUI loading a result's image straight from the site would
+//! tell the site (and its CDN) the user's IP each time the card is drawn.
+//! The GUI fetches each image once instead, through the site's route, and
+//! keeps it in `thumbnails/` in the cache directory, named after the SHA-256
+//! of its URL. Each file starts with the URL on its own line, checked on read
+//! so an image is never served for another URL. Only the [`IMAGE_TYPES`] are
+//! kept, images over [`MAX_IMAGE_BYTES`] are
+//! refused, and once the directory grows past [`MAX_BYTES`] the oldest images
+//! are deleted.
+
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Largest image kept
+pub const MAX_IMAGE_BYTES: u64 = 512 * 1024;
+
+/// Size of the directory past which the oldest images are deleted
+pub const MAX_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Content types kept, with the extension they are saved under. SVG is left
+/// out: it can carry scripts and links back to the site.
+pub const IMAGE_TYPES: [(&str, &str); 5] = [
+    ("image/jpeg", "jpg"),
+    ("image/png", "png"),
+    ("image/gif", "gif"),
+    ("image/webp", "webp"),
+    ("image/avif", "avif"),
+];
+
+/// A saved image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thumbnail {
+    pub content_type: &'static str,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ThumbnailCache {
+    dir: PathBuf,
+    max_image_bytes: u64,
+    max_bytes: u64,
+}
+
+/// Hex SHA-256 of `value`, the same on every platform and toolchain
+fn hash_of(value: &str) -> String {
+    Sha256::digest(value.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// The kept content type `content_type` names, ignoring parameters and case,
+/// with its extension
+pub fn image_type(content_type: &str) -> Option<(&'static str, &'static str)> {
+    let essence = content_type.split(';').next()?.trim();
+    IMAGE_TYPES
+        .into_iter()
+        .find(|(kept, _)| kept.eq_ignore_ascii_case(essence))
+}
+
+impl ThumbnailCache {
+    /// A cache in `dir` with the default limits
+    pub fn new(dir: PathBuf) -> Self {
+        Self::with_limits(dir, MAX_IMAGE_BYTES, MAX_BYTES)
+    }
+
+    pub fn with_limits(dir: PathBuf, max_image_bytes: u64, max_bytes: u64) -> Self {
+        Self {
+            dir,
+            max_image_bytes,
+            max_bytes,
+        }
+    }
+
+    /// Largest image [`put`](Self::put) keeps, so a download can stop early
+    pub fn max_image_bytes(&self) -> u64 {
+        self.max_image_bytes
+    }
+
+    fn path(&self, url: &str, extension: &str) -> PathBuf {
+        self.dir.join(format!("{}.{extension}", hash_of(url)))
+    }
+
+    /// The image saved for `url`
+    pub fn get(&self, url: &str) -> Option<Thumbnail> {
+        IMAGE_TYPES
+            .into_iter()
+            .find_map(|(content_type, extension)| {
+                let mut bytes = std::fs::read(self.path(url, extension)).ok()?;
+                let end = bytes.iter().position(|&b| b == b'\n')?;
+                if bytes[..end] != *url.as_bytes() {
+                    return None;
+                }
+                bytes.drain(..=end);
+                Some(Thumbnail {
+                    content_type,
+                    bytes,
+                })
+            })
+    }
+
+    /// Save the image fetched from `url`, unless it is too large or not an
+    /// image, then delete the oldest images past the size limit
+    pub fn put(&self, url: &str, content_type: &str, bytes: Vec<u8>) -> anyhow::Result<Thumbnail> {
+        let Some((content_type, extension)) = image_type(content_type) else {
+            anyhow::bail!("not an image ({content_type})");
+        };
+        if bytes.len() as u64 > self.max_image_bytes {
+            anyhow::bail!("image larger than {} KiB", self.max_image_bytes / 1024);
+        }
+        if url.contains('\n') {
+            anyhow::bail!("invalid image URL");
+        }
+        std::fs::create_dir_all(&self.dir)?;
+        let mut file = Vec::with_capacity(url.len() + 1 + bytes.len());
+        file.extend_from_slice(url.as_bytes());
+        file.push(b'\n');
+        file.extend_from_slice(&bytes);
+        std::fs::write(self.path(url, extension), file)?;
+        self.evict();
+        Ok(Thumbnail {
+            content_type,
+            bytes,
+        })
+    }
+
+    fn evict(&self) {
+        let Ok(dir) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        let mut kept: Vec<(SystemTime, u64, PathBuf)> = dir
+            .flatten()
+            .filter_map(|entry| {
+                let meta = entry.metadata().ok()?;
+                Some((meta.modified().ok()?, meta.len(), entry.path()))
+            })
+            .collect();
+        // Newest first, so the oldest are the ones past the limit
+        kept.sort_by(|a, b| b.0.cmp(&a.0));
+        let mut total = 0;
+        for (_, size, path) in kept {
+            total += size;
+            if total > self.max_bytes {
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+
+    /// Delete every saved image; returns how many there were
+    pub fn clear(&self) -> usize {
+        let Ok(dir) = std::fs::read_dir(&self.dir) else {
+            return 0;
+        };
+        dir.flatten()
+            .filter(|e| std::fs::remove_file(e.path()).is_ok())
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn images_are_kept_within_their_limits() {
+        let dir = tempdir().unwrap();
+        let cache = ThumbnailCache::with_limits(dir.path().join("thumbnails"), 1024, 2110);
+        let cover = "https://cdn.example/cover.jpg";
+        assert_eq!(cache.get(cover), None);
+
+        let saved = cache.put(cover, "Image/JPEG; q=1", vec![1, 2, 3]).unwrap();
+        assert_eq!(saved.content_type, "image/jpeg");
+        assert_eq!(cache.get(cover), Some(saved));
+
+        let err = cache.put("https://cdn.example/big.png", "image/png", vec![0; 1025]);
+        assert_eq!(err.unwrap_err().to_string(), "image larger than 1 KiB");
+        let err = cache.put("https://cdn.example/x.svg", "image/svg+xml", vec![0]);
+        assert_eq!(err.unwrap_err().to_string(), "not an image (image/svg+xml)");
+        assert_eq!(cache.get("https://cdn.example/big.png"), None);
+
+        // Past the directory's limit only the newest two (with their URLs) fit
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        cache
+            .put("https://cdn.example/a.png", "image/png", vec![0; 1024])
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        cache
+            .put("https://cdn.example/b.png", "image/png", vec![0; 1024])
+            .unwrap();
+        assert_eq!(cache.get(cover), None);
+        assert!(cache.get("https://cdn.example/a.png").is_some());
+        assert_eq!(cache.clear(), 2);
+    }
+
+    #[test]
+    fn files_are_named_by_a_stable_hash_and_checked_against_their_url() {
+        let dir = tempdir().unwrap();
+        let cache = ThumbnailCache::new(dir.path().to_path_buf());
+        let cover = "https://cdn.example/cover.jpg";
+        cache.put(cover, "image/jpeg", vec![1, 2, 3]).unwrap();
+        let saved = dir
+            .path()
+            .join("11c44cdfb86d2076a841e0285bbc7264d2ed7e30bd19501720dd685c02f1f4dd.jpg");
+        assert_eq!(
+            std::fs::read(&saved).unwrap(),
+            b"https://cdn.example/cover.jpg\n\x01\x02\x03"
+        );
+
+        // Another URL's image in the file, as after a hash collision, is not served
+        std::fs::write(&saved, b"https://cdn.example/other.jpg\n\x04").unwrap();
+        assert_eq!(cache.get(cover), None);
+    }
+}
@@ -14,16 +14,99 @@ vi.mock("./api", () => ({
   clearCache: vi.fn().mockResolvedValue(undefined),
   getCacheSettings: vi.fn().mockResolvedValue(100),
   setCacheSize: vi.fn().mockResolvedValue(undefined),
+  // Settings API mocks
+  getSettings: vi.fn().mockResolvedValue({
+    sites: [],
+    limit: 10,
+    cookies: {},
+    concurrency: 3,
+    hide_nsfw: false,
+    steam_appids: false,
+  }),
+  setSettings: vi.fn().mockResolvedValue(undefined),
+  openResult: vi.fn().mockResolvedValue(undefined),
+  getNotes: vi.fn().mockResolvedValue({}),
+  setNote: vi.fn().mockResolvedValue(null),
+  getIgnoreRules: vi.fn().mockResolvedValue([]),
+  addIgnoreRule: vi.fn(),
+  removeIgnoreRule: vi.fn().mockResolvedValue(false),
+  steamStoreUrl: (appid: number) => `https://store.steampowered.com/app/${appid}/`,
+  copyText: vi.fn().mockResolvedValue(undefined),
+  getResultDetails: vi.fn(),
+  getThumbnail: vi.fn().mockResolvedValue("data:image/png;base64,"),
+  exportResults: vi.fn().mockResolvedValue(null),
+  captureParserReport: vi.fn(),
+  checkEnvironment: vi.fn().mockResolvedValue({
+    flaresolverr: { ok: true, detail: "reachable" },
+    node: { ok: true, detail: "node v20" },
+    playwright: { ok: false, detail: "not installed" },
+    cache_dir: { ok: true, detail: "writable" },
+    sites: [],
+  }),
+  getSolverStatus: vi.fn().mockResolvedValue({
+    url: "http://localhost:8191/v1",
+    source: "default",
+    reachable: true,
+    version: "3.3.21",
+    sessions: [],
+  }),
+  setSolverUrl: vi.fn(),
+  resetSolverSessions: vi.fn().mockResolvedValue(0),
+  importCookie: vi.fn(),
+  takeDeepLink: vi.fn().mockResolvedValue(null),
+  onDeepLink: vi.fn().mockResolvedValue(() => {}),
+  getMetricsSnapshot: vi.fn().mockResolvedValue({
+    uptime_seconds: 0,
+    requests: 0,
+    successes: 0,
+    failures: 0,
+    cache_hits: 0,
+    cache_misses: 0,
+    cache_hit_rate: null,
+    sites: [],
+    breakers: [],
+    likely_broken: [],
+    ignores_query: [],
+  }),
+  // Favorites API mocks
+  addFavorite: vi.fn().mockResolvedValue(true),
+  listFavorites: vi.fn().mockResolvedValue([]),
+  removeFavorite: vi.fn().mockResolvedValue(true),
+  addWatch: vi.fn().mockResolvedValue({}),
+  listWatches: vi.fn().mockResolvedValue([]),
+  deleteWatch: vi.fn().mockResolvedValue(true),
+  onWatchlistUpdated: vi.fn().mockResolvedValue(() => {}),
+  listPageWatches: vi.fn().mockResolvedValue([]),
+  watchPage: vi.fn(),
+  deletePageWatch: vi.fn().mockResolvedValue(false),
+  onPageUpdated: vi.fn().mockResolvedValue(() => {}),
 }));
 
-import { invokeSearch, fetchSites } from "./api";
+import { invokeSearch, fetchSites, copyText, checkEnvironment, takeDeepLink } from "./api";
 const mockedInvokeSearch = vi.mocked(invokeSearch);
+const mockedCheckEnvironment = vi.mocked(checkEnvironment);
 const mockedFetchSites = vi.mocked(fetchSites);
+const mockedCopyText = vi.mocked(copyText);
+const mockedTakeDeepLink = vi.mocked(takeDeepLink);
 
 describe("App", () => {
   beforeEach(() => {
     vi.clearAllMocks();
     mockedFetchSites.mockResolvedValue(["fitgirl", "dodi", "gog-games"]);
+    // Skip the first-run setup check unless a test clears this
+    localStorage.setItem("setupChecked", "1");
+  });
+
+  it("runs the setup check on first launch only", async () => {
+    localStorage.removeItem("setupChecked");
+    const { unmount } = render(<App />);
+    await waitFor(() => {
+      expect(screen.getByText(/Playwright \(cs.rin.ru\): not installed/)).toBeInTheDocument();
+    });
+    unmount();
+
+    render(<App />);
+    expect(mockedCheckEnvironment).toHaveBeenCalledTimes(1);
   });
 
   it("renders search input and button", () => {
@@ -64,7 +147,7 @@ describe("App", () => {
   });
 
   it("calls invokeSearch when search button clicked with query", async () => {
-    mockedInvokeSearch.mockResolvedValue([]);
+    mockedInvokeSearch.mockResolvedValue({ results: [] });
     render(<App />);
 
     const input = screen.getByPlaceholderText("e.g., elden ring");
@@ -78,12 +161,28 @@ describe("App", () => {
     });
   });
 
+  it("sends the chosen search profile", async () => {
+    mockedInvokeSearch.mockResolvedValue({ results: [] });
+    render(<App />);
+
+    fireEvent.change(screen.getByLabelText("Search profile"), { target: { value: "fast" } });
+    const input = screen.getByPlaceholderText("e.g., elden ring");
+    fireEvent.change(input, { target: { value: "hades" } });
+    fireEvent.click(screen.getByRole("button", { name: /search/i }));
+
+    await waitFor(() => {
+      expect(mockedInvokeSearch).toHaveBeenCalledWith(expect.objectContaining({ profile: "fast" }));
+    });
+  });
+
   it("displays results grouped by site", async () => {
-    mockedInvokeSearch.mockResolvedValue([
-      { site: "fitgirl", title: "Game 1", url: "http://example.com/1" },
-      { site: "fitgirl", title: "Game 2", url: "http://example.com/2" },
-      { site: "dodi", title: "Game 3", url: "http://example.com/3" },
-    ]);
+    mockedInvokeSearch.mockResolvedValue({
+      results: [
+        { site: "fitgirl", title: "Game 1", url: "http://example.com/1" },
+        { site: "fitgirl", title: "Game 2", url: "http://example.com/2" },
+        { site: "dodi", title: "Game 3", url: "http://example.com/3" },
+      ],
+    });
 
     render(<App />);
     const input = screen.getByPlaceholderText("e.g., elden ring");
@@ -99,6 +198,47 @@ describe("App", () => {
     });
   });
 
+  it("asks for duplicate groups and shows one card per game", async () => {
+    mockedInvokeSearch.mockResolvedValue({
+      results: [
+        { site: "fitgirl", title: "Hades [FitGirl Repack]", url: "http://example.com/1" },
+        { site: "dodi", title: "Hades - DODI Repack", url: "http://example.com/2" },
+      ],
+      groups: [
+        {
+          title: "Hades",
+          sites: ["fitgirl", "dodi"],
+          members: [
+            { site: "fitgirl", title: "Hades [FitGirl Repack]", url: "http://example.com/1", metadata: {} },
+            { site: "dodi", title: "Hades - DODI Repack", url: "http://example.com/2", metadata: {} },
+          ],
+        },
+      ],
+    });
+
+    render(<App />);
+    fireEvent.click(screen.getByLabelText("Group duplicates"));
+    const input = screen.getByPlaceholderText("e.g., elden ring");
+    fireEvent.change(input, { target: { value: "hades" } });
+    fireEvent.click(screen.getByRole("button", { name: /search/i }));
+
+    await waitFor(() => {
+      const headings = screen.getAllByRole("heading", { level: 3 }).map((h) => h.textContent);
+      expect(headings).toEqual(["Hades"]);
+    });
+    expect(mockedInvokeSearch).toHaveBeenCalledWith(expect.objectContaining({ group: true }));
+  });
+
+  it("runs the search from a websearcher:// link on launch", async () => {
+    mockedTakeDeepLink.mockResolvedValueOnce({ query: "hades", sites: ["dodi"] });
+    render(<App />);
+
+    await waitFor(() => {
+      expect(screen.getByPlaceholderText("e.g., elden ring")).toHaveValue("hades");
+    });
+    expect(mockedTakeDeepLink).toHaveBeenCalled();
+  });
+
   it('shows "No results yet" when no results and not loading', () => {
     render(<App />);
     expect(screen.getByText("No results yet.")).toBeInTheDocument();
@@ -147,15 +287,12 @@ describe("App", () => {
   });
 
   it("copies URL to clipboard when link clicked", async () => {
-    const writeTextMock = vi.fn().mockResolvedValue(undefined);
-    Object.assign(navigator, {
-      clipboard: { writeText: writeTextMock },
+    mockedInvokeSearch.mockResolvedValue({
+      results: [
+        { site: "fitgirl", title: "Game", url: "http://example.com/game" },
+      ],
     });
 
-    mockedInvokeSearch.mockResolvedValue([
-      { site: "fitgirl", title: "Game", url: "http://example.com/game" },
-    ]);
-
     render(<App />);
     const input = screen.getByPlaceholderText("e.g., elden ring");
     fireEvent.change(input, { target: { value: "game" } });
@@ -169,20 +306,17 @@ describe("App", () => {
     fireEvent.click(link);
 
     await waitFor(() => {
-      expect(writeTextMock).toHaveBeenCalledWith("http://example.com/game");
+      expect(mockedCopyText).toHaveBeenCalledWith("http://example.com/game");
     });
   });
 
   it("shows copied toast after copying", async () => {
-    const writeTextMock = vi.fn().mockResolvedValue(undefined);
-    Object.assign(navigator, {
-      clipboard: { writeText: writeTextMock },
+    mockedInvokeSearch.mockResolvedValue({
+      results: [
+        { site: "fitgirl", title: "Game", url: "http://example.com/game" },
+      ],
     });
 
-    mockedInvokeSearch.mockResolvedValue([
-      { site: "fitgirl", title: "Game", url: "http://example.com/game" },
-    ]);
-
     render(<App />);
     const input = screen.getByPlaceholderText("e.g., elden ring");
     fireEvent.change(input, { target: { value: "game" } });
@@ -274,7 +408,7 @@ describe("App", () => {
   });
 
   it("passes selected sites to invokeSearch", async () => {
-    mockedInvokeSearch.mockResolvedValue([]);
+    mockedInvokeSearch.mockResolvedValue({ results: [] });
     render(<App />);
 
     await waitFor(() => {
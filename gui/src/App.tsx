@@ -11,8 +11,59 @@ import {
   removeCacheEntry,
   clearCache as apiClearCache,
   getCacheSettings,
-  setCacheSize as apiSetCacheSize
+  setCacheSize as apiSetCacheSize,
+  getSettings,
+  setSettings,
+  type SearchDefaults,
+  addFavorite,
+  listFavorites,
+  removeFavorite,
+  type Favorite,
+  addWatch,
+  listWatches,
+  deleteWatch,
+  onWatchlistUpdated,
+  type WatchEntry,
+  watchPage,
+  listPageWatches,
+  deletePageWatch,
+  onPageUpdated,
+  type PageWatch,
+  openResult,
+  steamStoreUrl,
+  getNotes,
+  setNote,
+  type Note,
+  type NoteStatus,
+  type SearchProfile,
+  getIgnoreRules,
+  addIgnoreRule,
+  removeIgnoreRule,
+  type IgnoreKind,
+  type IgnoreRule,
+  copyText,
+  getResultDetails,
+  getThumbnail,
+  exportResults,
+  captureParserReport,
+  type ExportFormat,
+  checkEnvironment,
+  type EnvironmentReport,
+  getSolverStatus,
+  setSolverUrl,
+  resetSolverSessions,
+  type SolverStatus,
+  importCookie,
+  takeDeepLink,
+  onDeepLink,
+  type DeepLinkSearch,
+  getMetricsSnapshot,
+  type MetricsSnapshot,
+  type SegmentMatch,
+  type DuplicateGroup,
+  type ResultDetails
 } from './api'
+import { ApiError, errorOf } from './errors'
 import { useRealtimeSearch, type SiteProgress } from './hooks/useRealtimeSearch'
 
 // Cache configuration constants
@@ -24,18 +75,40 @@ const getStatusEmoji = (status: SiteProgress['status']) => {
   switch (status) {
     case 'pending': return '⏳'
     case 'fetching': return '🔄'
-    case 'parsing': return '📄'
     case 'completed': return '✅'
     case 'failed': return '❌'
     default: return '⏳'
   }
 }
 
+// A result's image, from the backend's cache so the site never sees the card drawn
+function Thumbnail({ url, site }: { url: string; site: string }) {
+  const [src, setSrc] = useState<string | null>(null)
+  useEffect(() => {
+    let cancelled = false
+    getThumbnail(url, site)
+      .then((data) => { if (!cancelled) setSrc(data) })
+      // A missing image just leaves the card without one
+      .catch(() => {})
+    return () => { cancelled = true }
+  }, [url, site])
+  return src ? <img src={src} alt="" className="result-thumbnail" /> : null
+}
+
 function App() {
   const [q, setQ] = useState('')
   const [loading, setLoading] = useState(false)
-  const [error, setError] = useState<string | null>(null)
+  const [error, setError] = useState<ApiError | string | null>(null)
   const [results, setResults] = useState<SearchResult[]>([])
+  const [segments, setSegments] = useState<SegmentMatch[]>([])
+  const [groups, setGroups] = useState<DuplicateGroup[]>([])
+  const [groupDuplicates, setGroupDuplicates] = useState<boolean>(false)
+  // Notes by result URL; the source of truth for what results show
+  const [notes, setNotes] = useState<Record<string, Note>>({})
+  const [hideIgnored, setHideIgnored] = useState<boolean>(false)
+  const [ignoreRules, setIgnoreRules] = useState<IgnoreRule[]>([])
+  const [ignoreKind, setIgnoreKind] = useState<IgnoreKind>('domain')
+  const [ignoreValue, setIgnoreValue] = useState<string>('')
   const [siteOptions, setSiteOptions] = useState<string[]>([])
   const [selectedSites, setSelectedSites] = useState<string[]>([])
   const [limit, setLimit] = useState<number>(10)
@@ -43,10 +116,17 @@ function App() {
   const [noCf, setNoCf] = useState<boolean>(false)
   const [cfUrl, setCfUrl] = useState<string>('')
   const [cookie, setCookie] = useState<string>('')
+  const [cookieSite, setCookieSite] = useState<string>('csrin')
+  const [cookieMessage, setCookieMessage] = useState<string | null>(null)
+  const [reportSite, setReportSite] = useState<string>('csrin')
+  const [reportMessage, setReportMessage] = useState<string | null>(null)
+  // "Load more" state per site of the last streaming search
+  const [moreState, setMoreState] = useState<Record<string, { loading?: boolean; done?: boolean; error?: string }>>({})
   const [csrinPages, setCsrinPages] = useState<number>(1)
   const [csrinSearch, setCsrinSearch] = useState<boolean>(false)
   const [noPlaywright, setNoPlaywright] = useState<boolean>(false)
   const [noRateLimit, setNoRateLimit] = useState<boolean>(false)
+  const [profile, setProfile] = useState<SearchProfile>('default')
   const [debug, setDebug] = useState<boolean>(false)
   const [verbose, setVerbose] = useState<boolean>(false)
   const [copiedUrl, setCopiedUrl] = useState<string | null>(null)
@@ -60,6 +140,21 @@ function App() {
   const [cacheSize, setCacheSize] = useState<number>(MIN_CACHE_SIZE)
   const [showSettings, setShowSettings] = useState(false)
   const [cacheHit, setCacheHit] = useState(false)
+  const [defaults, setDefaults] = useState<SearchDefaults | null>(null)
+  const [favorites, setFavorites] = useState<Favorite[]>([])
+  const [watches, setWatches] = useState<WatchEntry[]>([])
+  const [pageWatches, setPageWatches] = useState<PageWatch[]>([])
+  const [exportFormat, setExportFormat] = useState<ExportFormat>('json')
+  const [exportedTo, setExportedTo] = useState<string | null>(null)
+  const [envReport, setEnvReport] = useState<EnvironmentReport | null>(null)
+  const [checkingEnv, setCheckingEnv] = useState(false)
+  const [solver, setSolver] = useState<SolverStatus | null>(null)
+  const [solverUrlInput, setSolverUrlInput] = useState('')
+  const [solverMessage, setSolverMessage] = useState<string | null>(null)
+  const [health, setHealth] = useState<MetricsSnapshot | null>(null)
+  const [deepLink, setDeepLink] = useState<DeepLinkSearch | null>(null)
+  // Detail drawer for one result: loading (null details), loaded, or failed
+  const [details, setDetails] = useState<{ url: string; data: ResultDetails | null; error?: string } | null>(null)
 
   // Load cache and settings from Tauri backend
   useEffect(() => {
@@ -67,6 +162,289 @@ function App() {
     getCacheSettings().then(setCacheSize).catch(console.error)
   }, [])
 
+  // Start from the saved search defaults
+  useEffect(() => {
+    getSettings()
+      .then((s) => {
+        setDefaults(s)
+        setLimit(s.limit)
+        setSelectedSites(s.sites)
+        setCfUrl(s.cf_url ?? '')
+      })
+      .catch(console.error)
+  }, [])
+
+  const saveDefaults = useCallback(async () => {
+    if (!defaults) return
+    const updated = { ...defaults, sites: selectedSites, limit, cf_url: cfUrl || undefined }
+    try {
+      await setSettings(updated)
+      setDefaults(updated)
+    } catch (e) {
+      console.error('Failed to save defaults:', e)
+    }
+  }, [defaults, selectedSites, limit, cfUrl])
+
+  useEffect(() => {
+    getNotes().then(setNotes).catch(console.error)
+  }, [])
+
+  const updateNote = useCallback(async (url: string, status?: NoteStatus, text?: string) => {
+    try {
+      const saved = await setNote(url, status, text)
+      setNotes((prev) => {
+        const next = { ...prev }
+        if (saved) next[url] = saved
+        else delete next[url]
+        return next
+      })
+    } catch (e) {
+      setError(errorOf(e))
+    }
+  }, [])
+
+  const editNoteText = useCallback((url: string) => {
+    const current = notes[url]
+    const text = window.prompt('Note', current?.text ?? '')
+    if (text !== null) updateNote(url, current?.status, text)
+  }, [notes, updateNote])
+
+  useEffect(() => {
+    getIgnoreRules().then(setIgnoreRules).catch(console.error)
+  }, [])
+
+  const addIgnore = useCallback(async (kind: IgnoreKind, value: string) => {
+    try {
+      const rule = await addIgnoreRule(kind, value)
+      setIgnoreRules((prev) =>
+        prev.some((r) => r.kind === rule.kind && r.value === rule.value) ? prev : [...prev, rule],
+      )
+      if (kind !== 'url') setIgnoreValue('')
+    } catch (e) {
+      setError(errorOf(e))
+    }
+  }, [])
+
+  const removeIgnore = useCallback(async (rule: IgnoreRule) => {
+    try {
+      await removeIgnoreRule(rule)
+      setIgnoreRules((prev) => prev.filter((r) => r.kind !== rule.kind || r.value !== rule.value))
+    } catch (e) {
+      setError(errorOf(e))
+    }
+  }, [])
+
+  const saveSteamAppids = useCallback(async (steamAppids: boolean) => {
+    if (!defaults) return
+    const updated = { ...defaults, steam_appids: steamAppids }
+    try {
+      await setSettings(updated)
+      setDefaults(updated)
+    } catch (e) {
+      console.error('Failed to save defaults:', e)
+    }
+  }, [defaults])
+
+  const saveDeadline = useCallback(async (seconds: number) => {
+    if (!defaults) return
+    const updated = { ...defaults, deadline_seconds: seconds > 0 ? seconds : undefined }
+    try {
+      await setSettings(updated)
+      setDefaults(updated)
+    } catch (e) {
+      console.error('Failed to save defaults:', e)
+    }
+  }, [defaults])
+
+  useEffect(() => {
+    listFavorites().then(setFavorites).catch(console.error)
+  }, [])
+
+  const toggleFavorite = useCallback(async (result: SearchResult) => {
+    try {
+      if (favorites.some((f) => f.url === result.url)) {
+        await removeFavorite(result.url)
+      } else {
+        await addFavorite(result)
+      }
+      setFavorites(await listFavorites())
+    } catch (e) {
+      console.error('Failed to update favorites:', e)
+    }
+  }, [favorites])
+
+  useEffect(() => {
+    listWatches().then(setWatches).catch(console.error)
+    // Background checks store new results; reload to show them
+    const unlisten = onWatchlistUpdated(() => {
+      listWatches().then(setWatches).catch(console.error)
+    })
+    return () => {
+      unlisten.then((fn) => fn()).catch(console.error)
+    }
+  }, [])
+
+  useEffect(() => {
+    listPageWatches().then(setPageWatches).catch(console.error)
+    const unlisten = onPageUpdated(() => {
+      listPageWatches().then(setPageWatches).catch(console.error)
+    })
+    return () => {
+      unlisten.then((fn) => fn()).catch(console.error)
+    }
+  }, [])
+
+  // Searches from websearcher:// links, including the one that launched the app
+  useEffect(() => {
+    const take = () => {
+      takeDeepLink()
+        .then((link) => link && setDeepLink(link))
+        .catch(console.error)
+    }
+    take()
+    const unlisten = onDeepLink(take)
+    return () => {
+      unlisten.then((fn) => fn()).catch(console.error)
+    }
+  }, [])
+
+  const runEnvCheck = useCallback(async () => {
+    setCheckingEnv(true)
+    try {
+      setEnvReport(await checkEnvironment())
+    } catch (e) {
+      console.error('Environment check failed:', e)
+    } finally {
+      setCheckingEnv(false)
+    }
+  }, [])
+
+  const refreshSolver = useCallback(async () => {
+    try {
+      const status = await getSolverStatus()
+      setSolver(status)
+      setSolverUrlInput(status.source === 'settings' ? status.url : '')
+    } catch (e) {
+      console.error('Failed to get solver status:', e)
+    }
+  }, [])
+
+  const refreshHealth = useCallback(async () => {
+    try {
+      setHealth(await getMetricsSnapshot())
+    } catch (e) {
+      console.error('Failed to get metrics:', e)
+    }
+  }, [])
+
+  useEffect(() => {
+    if (!showSettings) return
+    refreshSolver()
+    refreshHealth()
+  }, [showSettings, refreshSolver, refreshHealth])
+
+  const applySolverUrl = useCallback(async () => {
+    try {
+      const status = await setSolverUrl(solverUrlInput)
+      setSolver(status)
+      setSolverMessage(null)
+    } catch (e) {
+      setSolverMessage(String(e))
+    }
+  }, [solverUrlInput])
+
+  const resetSessions = useCallback(async () => {
+    try {
+      const closed = await resetSolverSessions()
+      setSolverMessage(`Closed ${closed} session${closed === 1 ? '' : 's'}`)
+      await refreshSolver()
+    } catch (e) {
+      setSolverMessage(String(e))
+    }
+  }, [refreshSolver])
+
+  const saveCookie = useCallback(async (fromFile: boolean) => {
+    try {
+      const imported = await importCookie(cookieSite, fromFile ? undefined : cookie)
+      if (!imported) return
+      const n = imported.names.length
+      const skipped = imported.other_domains.length > 0 ? `; skipped ${imported.other_domains.join(', ')}` : ''
+      setCookieMessage(`Saved ${n} cookie${n === 1 ? '' : 's'} for ${imported.site} (${imported.domains.join(', ')})${skipped}`)
+    } catch (e) {
+      setCookieMessage(String(e))
+    }
+  }, [cookieSite, cookie])
+
+  const reportParser = useCallback(async () => {
+    setReportMessage('Fetching…')
+    try {
+      const { path, diagnostics: d } = await captureParserReport(reportSite, q)
+      const challenge = d.looks_like_challenge ? ', looks like a Cloudflare challenge' : ''
+      setReportMessage(`${d.results} results, selector matched ${d.selector_matches ?? 'invalid'}${challenge}. Saved ${path}`)
+    } catch (e) {
+      setReportMessage(String(e))
+    }
+  }, [reportSite, q])
+
+  // Show the setup checklist once, on the first launch
+  useEffect(() => {
+    if (localStorage.getItem('setupChecked')) return
+    localStorage.setItem('setupChecked', '1')
+    setShowSettings(true)
+    runEnvCheck()
+  }, [runEnvCheck])
+
+  const watchQuery = useCallback(async () => {
+    if (!q.trim()) return
+    try {
+      await addWatch(q, selectedSites.length ? selectedSites : undefined)
+      setWatches(await listWatches())
+    } catch (e) {
+      console.error('Failed to add watch:', e)
+    }
+  }, [q, selectedSites])
+
+  const watchResultPage = useCallback(async (url: string, site: string, title: string) => {
+    try {
+      await watchPage(url, site, title)
+      setPageWatches(await listPageWatches())
+    } catch (e) {
+      setError(errorOf(e))
+    }
+  }, [])
+
+  const unwatchPage = useCallback(async (id: number) => {
+    try {
+      await deletePageWatch(id)
+      setPageWatches(await listPageWatches())
+    } catch (e) {
+      console.error('Failed to remove page watch:', e)
+    }
+  }, [])
+
+  const unwatch = useCallback(async (id: number) => {
+    try {
+      await deleteWatch(id)
+      setWatches(await listWatches())
+    } catch (e) {
+      console.error('Failed to remove watch:', e)
+    }
+  }, [])
+
+  const showDetails = useCallback(async (url: string, site: string) => {
+    if (details?.url === url) {
+      setDetails(null)
+      return
+    }
+    setDetails({ url, data: null })
+    try {
+      const data = await getResultDetails(url, site)
+      setDetails((d) => (d?.url === url ? { url, data } : d))
+    } catch (e) {
+      setDetails((d) => (d?.url === url ? { url, data: null, error: String(e) } : d))
+    }
+  }, [details])
+
   // Reload cache from backend
   const reloadCache = useCallback(async () => {
     try {
@@ -79,7 +457,7 @@ function App() {
 
   const copyToClipboard = useCallback(async (url: string) => {
     try {
-      await navigator.clipboard.writeText(url)
+      await copyText(url)
       setCopiedUrl(url)
       setTimeout(() => setCopiedUrl(null), 1500)
     } catch (err) {
@@ -91,17 +469,43 @@ function App() {
   const displayResults = useStreaming ? streaming.results : results
   const isLoading = useStreaming ? streaming.isSearching : loading
   const displayError = useStreaming ? streaming.error : error
+  const displaySegments = useStreaming ? streaming.completionInfo?.segments ?? [] : segments
+  const displayGroups = useStreaming ? streaming.completionInfo?.groups ?? [] : groups
+
+  const canLoadMore = useStreaming && !isLoading && !!streaming.completionInfo && !streaming.completionInfo.cancelled
+
+  const loadMore = useCallback(async (site: string) => {
+    setMoreState((m) => ({ ...m, [site]: { loading: true } }))
+    try {
+      const hasMore = await streaming.loadMore(site)
+      setMoreState((m) => ({ ...m, [site]: { done: !hasMore } }))
+    } catch (e) {
+      setMoreState((m) => ({ ...m, [site]: { error: String(e) } }))
+    }
+  }, [streaming])
+
+  const onExport = useCallback(async () => {
+    try {
+      const path = await exportResults(exportFormat, displayResults)
+      if (path) setExportedTo(path)
+    } catch (e) {
+      console.error('Failed to export results:', e)
+    }
+  }, [exportFormat, displayResults])
 
   // Group results by site (use displayResults for streaming support)
   // Sorted by site name (A→Z), then items by title (A→Z)
   const groupedResults = useMemo(() => {
-    const groups = new Map<string, { site: string; items: { title: string; url: string }[] }>()
+    const groups = new Map<string, { site: string; items: { title: string; url: string; image?: string; steam_appid?: number }[] }>()
     for (const r of displayResults) {
+      if (hideIgnored && notes[r.url]?.status === 'ignored') continue
+      // Domain and title rules take effect from the next search
+      if (ignoreRules.some((rule) => rule.kind === 'url' && rule.value === r.url)) continue
       const key = r.site.toLowerCase()
       if (!groups.has(key)) {
         groups.set(key, { site: r.site, items: [] })
       }
-      groups.get(key)!.items.push({ title: r.title, url: r.url })
+      groups.get(key)!.items.push({ title: r.title, url: r.url, image: r.image, steam_appid: r.steam_appid })
     }
     // Sort groups by site name (A→Z)
     const sortedGroups = Array.from(groups.values()).sort((a, b) => 
@@ -114,7 +518,7 @@ function App() {
       )
     }
     return sortedGroups
-  }, [displayResults])
+  }, [displayResults, hideIgnored, notes, ignoreRules])
 
   // Load site list once
   useEffect(() => {
@@ -168,22 +572,25 @@ function App() {
     }
   }, [reloadCache])
 
-  async function onSearch() {
+  // `link` runs a search from a websearcher:// link instead of the form's phrase and sites
+  async function onSearch(link?: DeepLinkSearch) {
+    const query = link?.query ?? q
+    const sites = link ? link.sites ?? undefined : selectedSites.length ? selectedSites : undefined
     setError(null)
     setCacheHit(false)
-    if (!q.trim()) {
+    if (!query.trim()) {
       setError('Enter a search phrase')
       return
     }
 
     // Check cache first (for both modes)
     try {
-      const cached = await getCachedResults(q)
+      const cached = await getCachedResults(query)
       if (cached) {
         setResults(cached)
         setCacheHit(true)
         setTimeout(() => setCacheHit(false), 2000)
-        console.log('Cache hit for:', q)
+        console.log('Cache hit for:', query)
         return
       }
     } catch (e) {
@@ -191,10 +598,10 @@ function App() {
     }
 
     const searchArgs = {
-      query: q,
+      query,
       limit,
       cutoff: cutoff || undefined,
-      sites: selectedSites.length ? selectedSites : undefined,
+      sites,
       debug,
       verbose,
       no_cf: noCf,
@@ -204,30 +611,36 @@ function App() {
       csrin_search: csrinSearch,
       no_playwright: noPlaywright,
       no_rate_limit: noRateLimit,
+      group: groupDuplicates || undefined,
+      hide_status: hideIgnored ? (['ignored'] as NoteStatus[]) : undefined,
+      profile: profile === 'default' ? undefined : profile,
     }
 
     if (useStreaming) {
       // Use streaming mode - results update in real-time via hook
       setResults([]) // Clear previous results
+      setMoreState({})
       try {
-        await streaming.startSearch(searchArgs)
+        const rs = await streaming.startSearch(searchArgs)
         // After streaming completes, cache the results
-        if (streaming.results.length > 0) {
-          await addToCache(q, streaming.results)
+        if (rs && rs.length > 0) {
+          await addToCache(query, rs)
           await reloadCache()
         }
       } catch (e) {
-        setError((e as Error).message)
+        setError(errorOf(e))
       }
     } else {
       // Traditional mode - wait for all results
       setLoading(true)
       try {
-        const rs = await invokeSearch(searchArgs)
+        const { results: rs, segments: segs, groups: grps } = await invokeSearch(searchArgs)
         setResults(rs)
+        setSegments(segs ?? [])
+        setGroups(grps ?? [])
         if (rs.length > 0) {
           try {
-            await addToCache(q, rs)
+            await addToCache(query, rs)
             await reloadCache()
           } catch (e) {
             console.error('Failed to cache results:', e)
@@ -235,13 +648,23 @@ function App() {
         }
         console.log('results', rs)
       } catch (e) {
-        setError((e as Error).message)
+        setError(errorOf(e))
       } finally {
         setLoading(false)
       }
     }
   }
 
+  useEffect(() => {
+    if (!deepLink) return
+    setDeepLink(null)
+    setQ(deepLink.query)
+    if (deepLink.sites) setSelectedSites(deepLink.sites)
+    onSearch(deepLink)
+    // onSearch is recreated every render; only a new link should run it
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [deepLink])
+
   return (
     <div style={{ padding: 16 }}>
       <div style={{ display: 'flex', justifyContent: 'space-between', alignItems: 'center', marginBottom: 8 }}>
@@ -281,10 +704,235 @@ function App() {
             <button onClick={handleClearCache} style={{ padding: '6px 12px' }}>
               🗑️ Clear Cache ({cache.length} entries)
             </button>
+            <button onClick={saveDefaults} disabled={!defaults} style={{ padding: '6px 12px' }}>
+              💾 Save sites, limit and solver URL as defaults
+            </button>
+          </div>
+          <label style={{ display: 'flex', alignItems: 'center', gap: 8, marginTop: 12 }} title="Downloads Steam's app list once a week">
+            <input
+              type="checkbox"
+              checked={defaults?.steam_appids ?? false}
+              disabled={!defaults}
+              onChange={(e) => saveSteamAppids(e.target.checked)}
+            />
+            Link results to their Steam store page
+          </label>
+          <label style={{ display: 'flex', alignItems: 'center', gap: 8, marginTop: 8 }} title="Slower sites keep searching and add their results when they finish; 0 waits for every site">
+            Show results after
+            <input
+              type="number"
+              min={0}
+              style={{ width: 60 }}
+              value={defaults?.deadline_seconds ?? 0}
+              disabled={!defaults}
+              onChange={(e) => saveDeadline(Number(e.target.value))}
+            />
+            seconds (streaming mode)
+          </label>
+          <div className="ignore-list" style={{ marginTop: 12, fontSize: 12 }}>
+            <div style={{ display: 'flex', gap: 8, alignItems: 'center' }}>
+              <select value={ignoreKind} onChange={(e) => setIgnoreKind(e.target.value as IgnoreKind)} aria-label="Ignore rule kind">
+                <option value="domain">domain</option>
+                <option value="title">title regex</option>
+                <option value="url">url</option>
+              </select>
+              <input
+                value={ignoreValue}
+                onChange={(e) => setIgnoreValue(e.target.value)}
+                placeholder="Leave out of every search"
+                aria-label="Ignore rule"
+                style={{ flex: 1, padding: 4 }}
+              />
+              <button onClick={() => addIgnore(ignoreKind, ignoreValue)} disabled={!ignoreValue.trim()}>
+                Ignore
+              </button>
+            </div>
+            {ignoreRules.map((rule) => (
+              <div key={`${rule.kind} ${rule.value}`} style={{ display: 'flex', gap: 8, color: '#888', marginTop: 4 }}>
+                <span style={{ minWidth: 48 }}>{rule.kind}</span>
+                <span style={{ flex: 1, wordBreak: 'break-all' }}>{rule.value}</span>
+                <button onClick={() => removeIgnore(rule)} title="Remove rule">
+                  ✕
+                </button>
+              </div>
+            ))}
           </div>
           <p style={{ fontSize: 12, color: '#888', marginBottom: 0 }}>
-            Cache is shared with CLI/TUI
+            Cache and defaults (config.toml) are shared with CLI/TUI
           </p>
+          <div className="solver" style={{ marginTop: 12, fontSize: 12 }}>
+            <div style={{ display: 'flex', gap: 8, alignItems: 'center' }}>
+              <input
+                value={solverUrlInput}
+                onChange={(e) => setSolverUrlInput(e.target.value)}
+                placeholder={solver?.source === 'settings' ? '' : solver?.url ?? 'http://localhost:8191/v1'}
+                aria-label="FlareSolverr URL"
+                style={{ flex: 1, padding: 4 }}
+              />
+              <button onClick={applySolverUrl}>Use solver</button>
+              <button onClick={resetSessions} disabled={!solver?.reachable}>
+                Reset sessions
+              </button>
+            </div>
+            {solver && (
+              <div style={{ color: '#888', marginTop: 4 }}>
+                {solver.reachable ? '✅' : '❌'} {solver.url} ({solver.source})
+                {solver.reachable
+                  ? ` · ${solver.version ? `v${solver.version} · ` : ''}${solver.sessions.length} open session${solver.sessions.length === 1 ? '' : 's'}`
+                  : ` · ${solver.error}`}
+              </div>
+            )}
+            {solverMessage && <div style={{ color: '#888' }}>{solverMessage}</div>}
+          </div>
+          <div className="health" style={{ marginTop: 12, fontSize: 12 }}>
+            <button onClick={refreshHealth} style={{ padding: '6px 12px' }}>
+              📊 Refresh health
+            </button>
+            {health && (
+              <>
+                <div style={{ color: '#888', margin: '4px 0' }}>
+                  {health.requests} requests, {health.failures} failed · cache hit rate{' '}
+                  {health.cache_hit_rate === null ? '–' : `${Math.round(health.cache_hit_rate * 100)}%`}
+                </div>
+                {(health.likely_broken.length > 0 || health.ignores_query.length > 0) && (
+                  <ul style={{ color: '#c60', margin: '4px 0', paddingLeft: 0, listStyle: 'none' }}>
+                    {health.likely_broken.map((b) => (
+                      <li key={b.site} title={`Empty for: ${b.queries.join(', ')}`}>
+                        ⚠ {b.site}: likely broken parser (nothing for {b.streak} queries other sites matched)
+                      </li>
+                    ))}
+                    {health.ignores_query.map((site) => (
+                      <li key={`${site}-query`}>⚠ {site}: search ignores the query, its listing page is filtered instead</li>
+                    ))}
+                  </ul>
+                )}
+                {health.sites.length > 0 && (
+                  <table style={{ borderCollapse: 'collapse' }}>
+                    <thead>
+                      <tr>
+                        <th style={{ textAlign: 'left', paddingRight: 12 }}>Site</th>
+                        <th style={{ textAlign: 'right', paddingRight: 12 }}>Success</th>
+                        <th style={{ textAlign: 'right', paddingRight: 12 }}>Avg</th>
+                        <th style={{ textAlign: 'left' }}>Breaker</th>
+                      </tr>
+                    </thead>
+                    <tbody>
+                      {health.sites.map((s) => {
+                        const breaker = health.breakers.find((b) => b.site.toLowerCase() === s.site.toLowerCase())
+                        return (
+                          <tr key={s.site}>
+                            <td style={{ paddingRight: 12 }}>{s.site}</td>
+                            <td style={{ textAlign: 'right', paddingRight: 12 }}>
+                              {Math.round(s.success_rate * 100)}% of {s.requests}
+                            </td>
+                            <td style={{ textAlign: 'right', paddingRight: 12 }}>{s.avg_response_ms} ms</td>
+                            <td>{breaker ? breaker.state.replace('_', '-') : 'closed'}</td>
+                          </tr>
+                        )
+                      })}
+                    </tbody>
+                  </table>
+                )}
+              </>
+            )}
+          </div>
+          <div className="setup-check" style={{ marginTop: 12 }}>
+            <button onClick={runEnvCheck} disabled={checkingEnv} style={{ padding: '6px 12px' }}>
+              {checkingEnv ? 'Checking…' : '🩺 Check setup'}
+            </button>
+            {envReport && (
+              <ul style={{ fontSize: 12, listStyle: 'none', paddingLeft: 0 }}>
+                {[
+                  ['FlareSolverr', envReport.flaresolverr],
+                  ['Node', envReport.node],
+                  ['Playwright (cs.rin.ru)', envReport.playwright],
+                  ['Cache directory', envReport.cache_dir],
+                  ...envReport.sites.map((s) => [`Network: ${s.site}`, s] as const)
+                ].map(([label, check]) => (
+                  <li key={label as string}>
+                    {(check as { ok: boolean }).ok ? '✅' : '❌'} {label as string}:{' '}
+                    {(check as { detail: string }).detail}
+                  </li>
+                ))}
+              </ul>
+            )}
+          </div>
+        </div>
+      )}
+
+      {/* Favorites */}
+      {favorites.length > 0 && (
+        <div className="favorites" style={{ marginBottom: 16 }}>
+          <div style={{ fontSize: 12, color: '#888', marginBottom: 4 }}>Favorites:</div>
+          {favorites.map((f) => (
+            <div key={f.url} style={{ fontSize: 12, display: 'flex', gap: 6, alignItems: 'center' }}>
+              <button
+                onClick={() => toggleFavorite(f)}
+                title="Remove from favorites"
+                style={{ background: 'none', border: 'none', cursor: 'pointer', padding: 0 }}
+              >
+                ★
+              </button>
+              <span>[{f.site}] {f.title}</span>
+              <span className="copy-link" onClick={() => copyToClipboard(f.url)} title="Click to copy">
+                {f.url}
+              </span>
+            </div>
+          ))}
+        </div>
+      )}
+
+      {/* Watchlist */}
+      {(watches.length > 0 || pageWatches.length > 0) && (
+        <div className="watchlist" style={{ marginBottom: 16 }}>
+          <div style={{ fontSize: 12, color: '#888', marginBottom: 4 }}>Watching:</div>
+          {watches.map((w) => (
+            <div key={w.id} style={{ fontSize: 12 }}>
+              <div style={{ display: 'flex', gap: 6, alignItems: 'center' }}>
+                <button
+                  onClick={() => unwatch(w.id)}
+                  title="Stop watching"
+                  style={{ background: 'none', border: 'none', cursor: 'pointer', padding: 0 }}
+                >
+                  ✕
+                </button>
+                <span>
+                  {w.query} (every {w.interval_minutes} min
+                  {w.last_checked ? `, checked ${new Date(w.last_checked * 1000).toLocaleString()}` : ''})
+                </span>
+              </div>
+              {w.new_results.map((r) => (
+                <div key={r.url} style={{ paddingLeft: 18 }}>
+                  <span>New: [{r.site}] {r.title} </span>
+                  <span className="copy-link" onClick={() => copyToClipboard(r.url)} title="Click to copy">
+                    {r.url}
+                  </span>
+                </div>
+              ))}
+            </div>
+          ))}
+          {pageWatches.map((p) => (
+            <div key={p.id} style={{ fontSize: 12, display: 'flex', gap: 6, alignItems: 'center' }}>
+              <button
+                onClick={() => unwatchPage(p.id)}
+                title="Stop watching"
+                style={{ background: 'none', border: 'none', cursor: 'pointer', padding: 0 }}
+              >
+                ✕
+              </button>
+              <span>
+                [{p.site}] {p.title}{' '}
+                {p.last_changed
+                  ? <strong>updated {new Date(p.last_changed * 1000).toLocaleString()}</strong>
+                  : p.last_checked
+                    ? `unchanged, checked ${new Date(p.last_checked * 1000).toLocaleString()}`
+                    : 'not checked yet'}{' '}
+              </span>
+              <span className="copy-link" onClick={() => copyToClipboard(p.url)} title="Click to copy">
+                {p.url}
+              </span>
+            </div>
+          ))}
         </div>
       )}
 
@@ -351,9 +999,15 @@ function App() {
           style={{ flex: 1, padding: 8 }}
           onKeyDown={(e) => e.key === 'Enter' && onSearch()}
         />
-        <button onClick={onSearch} disabled={isLoading}>
+        <button onClick={() => onSearch()} disabled={isLoading}>
           {isLoading ? 'Searching…' : 'Search'}
         </button>
+        <button onClick={watchQuery} disabled={!q.trim()} title="Re-run this search in the background and notify on new results">
+          Watch
+        </button>
+        {useStreaming && streaming.isSearching && (
+          <button onClick={() => streaming.cancel()}>Cancel</button>
+        )}
       </div>
 
       {/* Streaming mode toggle and progress */}
@@ -366,6 +1020,22 @@ function App() {
           />
           Real-time streaming
         </label>
+        <label style={{ display: 'flex', alignItems: 'center', gap: 4 }} title="Show one card per game with the sites that have it">
+          <input
+            type="checkbox"
+            checked={groupDuplicates}
+            onChange={(e) => setGroupDuplicates(e.target.checked)}
+          />
+          Group duplicates
+        </label>
+        <label style={{ display: 'flex', alignItems: 'center', gap: 4 }} title="Leave out results marked as ignored">
+          <input
+            type="checkbox"
+            checked={hideIgnored}
+            onChange={(e) => setHideIgnored(e.target.checked)}
+          />
+          Hide ignored
+        </label>
         {useStreaming && streaming.progress.size > 0 && (
           <div style={{ display: 'flex', gap: 8, flexWrap: 'wrap' }}>
             {Array.from(streaming.progress.entries()).map(([siteName, prog]) => (
@@ -465,6 +1135,35 @@ function App() {
             <span>Cookie</span>
             <input value={cookie} onChange={(e) => setCookie(e.target.value)} placeholder="key=value; other=value2" style={{ width: '100%', padding: 6 }} />
           </label>
+          <div style={{ gridColumn: '1 / span 2', display: 'flex', gap: 8, alignItems: 'center', fontSize: 12 }}>
+            <select value={cookieSite} onChange={(e) => setCookieSite(e.target.value)} aria-label="Cookie site">
+              {siteOptions.map((s) => (
+                <option key={s} value={s}>{s}</option>
+              ))}
+            </select>
+            <button onClick={() => saveCookie(false)} disabled={!cookie.trim()}>Save cookie for site</button>
+            <button onClick={() => saveCookie(true)}>Import cookies.txt…</button>
+            {cookieMessage && <span style={{ color: '#888' }}>{cookieMessage}</span>}
+          </div>
+          <div style={{ gridColumn: '1 / span 2', display: 'flex', gap: 8, alignItems: 'center', fontSize: 12 }}>
+            <select value={reportSite} onChange={(e) => setReportSite(e.target.value)} aria-label="Report site">
+              {siteOptions.map((s) => (
+                <option key={s} value={s}>{s}</option>
+              ))}
+            </select>
+            <button onClick={reportParser} disabled={!q.trim()} title="Save the raw search page and parser diagnostics to a zip for a bug report">
+              Report broken parser
+            </button>
+            {reportMessage && <span style={{ color: '#888' }}>{reportMessage}</span>}
+          </div>
+          <label title="fast: direct HTTP only, 5s per site. thorough: every fallback, up to 3 pages per site, dead links marked">
+            <span>profile</span>
+            <select value={profile} onChange={(e) => setProfile(e.target.value as SearchProfile)} aria-label="Search profile" style={{ width: '100%', padding: 6 }}>
+              <option value="default">default</option>
+              <option value="fast">fast</option>
+              <option value="thorough">thorough</option>
+            </select>
+          </label>
           <label>
             <span>csrin_pages</span>
             <input type="number" min={1} value={csrinPages} onChange={(e) => setCsrinPages(Number(e.target.value) || 1)} style={{ width: '100%', padding: 6 }} />
@@ -489,14 +1188,96 @@ function App() {
           </label>
         </div>
       </div>
-      {displayError && <p style={{ color: 'tomato' }}>{displayError}</p>}
+      {displayError && (
+        <p style={{ color: 'tomato', display: 'flex', gap: 8, alignItems: 'center' }}>
+          <span>{String(displayError)}</span>
+          {displayError instanceof ApiError && displayError.category === 'solver_unavailable' && (
+            <button onClick={() => setShowSettings(true)}>Solver settings</button>
+          )}
+          {displayError instanceof ApiError && displayError.category === 'auth' && displayError.site && (
+            <button
+              onClick={() => {
+                setCookieSite(displayError.site!)
+                setShowSettings(true)
+              }}
+            >
+              Set cookie for {displayError.site}
+            </button>
+          )}
+          {displayError instanceof ApiError && displayError.retryable && (
+            <button onClick={() => onSearch()}>Retry</button>
+          )}
+        </p>
+      )}
+      {displaySegments.length > 0 && !isLoading && (
+        <ul className="segments" style={{ fontSize: 12, color: '#888', margin: '0 0 8px', paddingLeft: 16 }}>
+          {displaySegments.map((seg) => (
+            <li key={seg.index}>
+              Part {seg.index + 1} “{seg.query}”: {seg.results.length} result{seg.results.length === 1 ? '' : 's'}
+            </li>
+          ))}
+        </ul>
+      )}
+      {displayResults.length > 0 && !isLoading && (
+        <div className="export" style={{ display: 'flex', alignItems: 'center', gap: 8, marginBottom: 8, fontSize: 12 }}>
+          <select value={exportFormat} onChange={(e) => setExportFormat(e.target.value as ExportFormat)}>
+            <option value="json">JSON</option>
+            <option value="csv">CSV</option>
+            <option value="markdown">Markdown</option>
+            <option value="html">HTML</option>
+          </select>
+          <button onClick={onExport}>Export results…</button>
+          {exportedTo && <span style={{ color: '#888' }}>Saved to {exportedTo}</span>}
+        </div>
+      )}
       <div className="results-container">
-        {groupedResults.map((group, i) => (
+        {!isLoading && displayGroups.map((group, i) => (
+          <div key={`dup-${i}`} className="result-card">
+            <h3 className="result-title">{group.title}</h3>
+            <div style={{ display: 'flex', gap: 4, flexWrap: 'wrap', marginBottom: 4 }}>
+              {group.sites.map((site) => (
+                <span key={site} className="site-badge" style={{ padding: '2px 8px', fontSize: 12, borderRadius: 4, background: '#333' }}>
+                  {site}
+                </span>
+              ))}
+            </div>
+            <div className="result-links">
+              {group.members.map((member) => (
+                <div key={member.url} className="link-row">
+                  <span style={{ fontSize: 12, color: '#888', padding: '0 4px' }}>{member.site}</span>
+                  <span className="copy-link" onClick={() => copyToClipboard(member.url)} title="Click to copy">
+                    {member.url}
+                    {copiedUrl === member.url && <span className="copied-toast">Copied!</span>}
+                  </span>
+                  <button
+                    onClick={() => openResult(member.url).catch((e) => setError(errorOf(e)))}
+                    title="Open in browser"
+                    style={{ background: 'none', border: 'none', cursor: 'pointer', padding: '0 4px' }}
+                  >
+                    ↗
+                  </button>
+                  <span style={{ fontSize: 12, color: '#888' }}>
+                    {[member.metadata.file_size, member.metadata.version].filter(Boolean).join(' · ')}
+                  </span>
+                </div>
+              ))}
+            </div>
+          </div>
+        ))}
+        {displayGroups.length === 0 && groupedResults.map((group, i) => (
           <div key={i} className="result-card">
             <h3 className="result-title">{group.site}</h3>
             <div className="result-links">
               {group.items.map((item, j) => (
                 <div key={j} className="link-row">
+                  {item.image && <Thumbnail url={item.image} site={group.site} />}
+                  <button
+                    onClick={() => toggleFavorite({ site: group.site, ...item })}
+                    title={favorites.some((f) => f.url === item.url) ? 'Remove from favorites' : 'Add to favorites'}
+                    style={{ background: 'none', border: 'none', cursor: 'pointer', padding: '0 4px' }}
+                  >
+                    {favorites.some((f) => f.url === item.url) ? '★' : '☆'}
+                  </button>
                   <span
                     className="copy-link"
                     onClick={() => copyToClipboard(item.url)}
@@ -505,9 +1286,99 @@ function App() {
                     {item.url}
                     {copiedUrl === item.url && <span className="copied-toast">Copied!</span>}
                   </span>
+                  <button
+                    onClick={() => openResult(item.url).catch((e) => setError(errorOf(e)))}
+                    title="Open in browser"
+                    style={{ background: 'none', border: 'none', cursor: 'pointer', padding: '0 4px' }}
+                  >
+                    ↗
+                  </button>
+                  <select
+                    value={notes[item.url]?.status ?? ''}
+                    onChange={(e) => updateNote(item.url, (e.target.value || undefined) as NoteStatus | undefined, notes[item.url]?.text)}
+                    title="Status"
+                    style={{ fontSize: 12, margin: '0 4px' }}
+                  >
+                    <option value="">—</option>
+                    <option value="interested">interested</option>
+                    <option value="downloaded">downloaded</option>
+                    <option value="ignored">ignored</option>
+                  </select>
+                  <button
+                    onClick={() => editNoteText(item.url)}
+                    title="Edit note"
+                    style={{ background: 'none', border: 'none', cursor: 'pointer', padding: '0 4px' }}
+                  >
+                    ✎
+                  </button>
+                  <button
+                    onClick={() => watchResultPage(item.url, group.site, item.title)}
+                    title="Watch this page and notify when it is updated"
+                    style={{ background: 'none', border: 'none', cursor: 'pointer', padding: '0 4px' }}
+                  >
+                    👁
+                  </button>
+                  <button
+                    onClick={() => addIgnore('url', item.url)}
+                    title="Never show this result again"
+                    style={{ background: 'none', border: 'none', cursor: 'pointer', padding: '0 4px' }}
+                  >
+                    ⊘
+                  </button>
+                  {notes[item.url]?.text && (
+                    <span style={{ fontSize: 12, color: '#888', padding: '0 4px' }}>{notes[item.url]!.text}</span>
+                  )}
+                  {item.steam_appid !== undefined && (
+                    <button
+                      onClick={() => openResult(steamStoreUrl(item.steam_appid!)).catch((e) => setError(errorOf(e)))}
+                      title={`Open the Steam store page (AppID ${item.steam_appid})`}
+                      style={{ background: 'none', border: 'none', cursor: 'pointer', padding: '0 4px', fontSize: 12 }}
+                    >
+                      Steam
+                    </button>
+                  )}
+                  <button
+                    onClick={() => showDetails(item.url, group.site)}
+                    title="Show details and download links"
+                    style={{ background: 'none', border: 'none', cursor: 'pointer', padding: '0 4px' }}
+                  >
+                    ℹ
+                  </button>
+                  {details?.url === item.url && (
+                    <div className="result-details" style={{ fontSize: 12, padding: '4px 8px', color: '#ccc' }}>
+                      {!details.data && !details.error && <div>Loading details…</div>}
+                      {details.error && <div style={{ color: 'tomato' }}>{details.error}</div>}
+                      {details.data && (
+                        <>
+                          {details.data.description && <p style={{ margin: '4px 0' }}>{details.data.description}</p>}
+                          <div>
+                            {[details.data.metadata.file_size, details.data.metadata.version, details.data.metadata.release_date]
+                              .filter(Boolean)
+                              .join(' · ')}
+                          </div>
+                          {[...details.data.magnets, ...details.data.torrents, ...details.data.mirrors].map((link) => (
+                            <div key={link} className="copy-link" onClick={() => copyToClipboard(link)} title="Click to copy">
+                              {link}
+                            </div>
+                          ))}
+                          {details.data.magnets.length + details.data.torrents.length + details.data.mirrors.length === 0 && (
+                            <div>No download links found on the page.</div>
+                          )}
+                        </>
+                      )}
+                    </div>
+                  )}
                 </div>
               ))}
             </div>
+            {canLoadMore && !moreState[group.site]?.done && (
+              <div style={{ marginTop: 8, fontSize: 12, display: 'flex', gap: 8, alignItems: 'center' }}>
+                <button onClick={() => loadMore(group.site)} disabled={moreState[group.site]?.loading}>
+                  {moreState[group.site]?.loading ? 'Loading…' : `Load more from ${group.site}`}
+                </button>
+                {moreState[group.site]?.error && <span style={{ color: 'tomato' }}>{moreState[group.site].error}</span>}
+              </div>
+            )}
           </div>
         ))}
         {displayResults.length === 0 && !isLoading && <p>No results yet.</p>}
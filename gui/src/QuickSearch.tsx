@@ -0,0 +1,79 @@
+import { useState, type KeyboardEvent } from 'react'
+import {
+  quickSearch,
+  openInMainWindow,
+  hideQuickSearch,
+  openResult,
+  type SearchResult,
+} from './api'
+
+// The small always-on-top window opened from the tray or the global shortcut.
+// Searches only the fast sites; Shift+Enter runs a full search in the main window.
+function QuickSearch() {
+  const [q, setQ] = useState('')
+  const [results, setResults] = useState<SearchResult[]>([])
+  const [timedOut, setTimedOut] = useState<string[]>([])
+  const [loading, setLoading] = useState(false)
+  const [error, setError] = useState<string | null>(null)
+
+  async function onSearch() {
+    if (!q.trim()) return
+    setLoading(true)
+    setError(null)
+    try {
+      const response = await quickSearch(q)
+      setResults(response.results)
+      setTimedOut(response.timed_out)
+    } catch (e) {
+      setError(String(e))
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  function onKeyDown(e: KeyboardEvent<HTMLInputElement>) {
+    if (e.key === 'Escape') {
+      hideQuickSearch().catch(console.error)
+    } else if (e.key === 'Enter' && e.shiftKey) {
+      openInMainWindow(q).catch((err) => setError(String(err)))
+    } else if (e.key === 'Enter') {
+      onSearch()
+    }
+  }
+
+  return (
+    <div style={{ padding: 12 }}>
+      <input
+        autoFocus
+        value={q}
+        onChange={(e) => setQ(e.target.value)}
+        onKeyDown={onKeyDown}
+        placeholder="Quick search (Enter), full search (Shift+Enter), Esc to close"
+        style={{ width: '100%', padding: 8, boxSizing: 'border-box' }}
+      />
+      {loading && <p style={{ fontSize: 12, color: '#888' }}>Searching…</p>}
+      {error && <p style={{ color: 'tomato', fontSize: 12 }}>{error}</p>}
+      <ul style={{ listStyle: 'none', paddingLeft: 0, fontSize: 13, maxHeight: 280, overflowY: 'auto' }}>
+        {results.map((r) => (
+          <li key={r.url} style={{ padding: '2px 0' }}>
+            <span style={{ color: '#888', marginRight: 6 }}>{r.site}</span>
+            <a
+              href={r.url}
+              onClick={(e) => {
+                e.preventDefault()
+                openResult(r.url).catch((err) => setError(String(err)))
+              }}
+            >
+              {r.title}
+            </a>
+          </li>
+        ))}
+      </ul>
+      {!loading && timedOut.length > 0 && (
+        <p style={{ fontSize: 12, color: '#888' }}>No answer in time from {timedOut.join(', ')}</p>
+      )}
+    </div>
+  )
+}
+
+export default QuickSearch
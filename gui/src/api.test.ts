@@ -1,5 +1,21 @@
 import { describe, it, expect, vi, beforeEach } from "vitest";
-import { invokeSearch, fetchSites } from "./api";
+import {
+  invokeSearch,
+  fetchSites,
+  startSearch,
+  cancelSearch,
+  searchMore,
+  openResult,
+  copyText,
+  setSitePrefs,
+  getHistory,
+  addWatch,
+  setSolverUrl,
+  importCookie,
+  quickSearch,
+  captureParserReport,
+} from "./api";
+import { ApiError } from "./errors";
 
 // Mock Tauri invoke
 vi.mock("@tauri-apps/api/core", () => ({
@@ -28,7 +44,7 @@ describe("api", () => {
     });
 
     it("calls invoke with correct command name", async () => {
-      mockedInvoke.mockResolvedValue([]);
+      mockedInvoke.mockResolvedValue({ results: [] });
       await invokeSearch({ query: "elden ring" });
       expect(mockedInvoke).toHaveBeenCalledWith("search_gui", {
         args: { query: "elden ring" },
@@ -36,7 +52,7 @@ describe("api", () => {
     });
 
     it("passes all options to invoke", async () => {
-      mockedInvoke.mockResolvedValue([]);
+      mockedInvoke.mockResolvedValue({ results: [] });
       await invokeSearch({
         query: "test",
         limit: 5,
@@ -59,24 +75,195 @@ describe("api", () => {
       const mockResults = [
         { site: "fitgirl", title: "Game", url: "http://example.com" },
       ];
-      mockedInvoke.mockResolvedValue(mockResults);
+      mockedInvoke.mockResolvedValue({ results: mockResults });
       const result = await invokeSearch({ query: "game" });
-      expect(result).toEqual(mockResults);
+      expect(result.results).toEqual(mockResults);
+      expect(result.segments).toBeUndefined();
+    });
+
+    it("returns segment matches of multi-part queries", async () => {
+      const segments = [
+        { index: 0, query: "elden ring site:fitgirl", sites: ["fitgirl"], results: [0] },
+      ];
+      mockedInvoke.mockResolvedValue({ results: [], segments });
+      const result = await invokeSearch({ query: "elden ring site:fitgirl | hades", advanced: true });
+      expect(result.segments).toEqual(segments);
     });
   });
 
   describe("fetchSites", () => {
+    const site = (name: string) => ({
+      name,
+      base_url: `https://${name}.example`,
+      search_kind: "QueryParam",
+      requires_js: false,
+      requires_cloudflare: false,
+      enabled: true,
+    });
+
     it("calls invoke with list_sites command", async () => {
-      mockedInvoke.mockResolvedValue(["fitgirl", "dodi"]);
+      mockedInvoke.mockResolvedValue([site("fitgirl"), site("dodi")]);
       await fetchSites();
       expect(mockedInvoke).toHaveBeenCalledWith("list_sites");
     });
 
-    it("returns site list from invoke", async () => {
-      const mockSites = ["fitgirl", "dodi", "gog-games"];
-      mockedInvoke.mockResolvedValue(mockSites);
+    it("returns site names in the backend's order", async () => {
+      mockedInvoke.mockResolvedValue([site("gog-games"), site("fitgirl"), site("dodi")]);
       const result = await fetchSites();
-      expect(result).toEqual(mockSites);
+      expect(result).toEqual(["gog-games", "fitgirl", "dodi"]);
+    });
+  });
+
+  describe("setSitePrefs", () => {
+    it("passes enabled sites and order", async () => {
+      mockedInvoke.mockResolvedValue(undefined);
+      await setSitePrefs(["dodi"], ["dodi", "fitgirl"]);
+      expect(mockedInvoke).toHaveBeenCalledWith("set_site_prefs", {
+        enabled: ["dodi"],
+        order: ["dodi", "fitgirl"],
+      });
+    });
+  });
+
+  describe("startSearch", () => {
+    it("throws on empty query", async () => {
+      await expect(startSearch({ query: " " })).rejects.toThrow(
+        "Query is required"
+      );
+    });
+
+    it("returns the job id from start_search", async () => {
+      mockedInvoke.mockResolvedValue(7);
+      const jobId = await startSearch({ query: "elden ring" });
+      expect(jobId).toBe(7);
+      expect(mockedInvoke).toHaveBeenCalledWith("start_search", {
+        args: { query: "elden ring" },
+      });
+    });
+  });
+
+  describe("cancelSearch", () => {
+    it("passes the job id to cancel_search", async () => {
+      mockedInvoke.mockResolvedValue(true);
+      expect(await cancelSearch(7)).toBe(true);
+      expect(mockedInvoke).toHaveBeenCalledWith("cancel_search", { jobId: 7 });
+    });
+  });
+
+  describe("command errors", () => {
+    it("rethrows backend errors as ApiError with their category", async () => {
+      mockedInvoke.mockRejectedValue({
+        category: "solver_unavailable",
+        message: "send flaresolverr request: connection refused",
+        site: "fitgirl",
+        retryable: true,
+      });
+      const err = await cancelSearch(7).catch((e) => e);
+      expect(err).toBeInstanceOf(ApiError);
+      expect(err.category).toBe("solver_unavailable");
+      expect(err.site).toBe("fitgirl");
+      expect(err.retryable).toBe(true);
+      expect(String(err)).toBe("send flaresolverr request: connection refused");
+    });
+
+    it("passes other rejections through unchanged", async () => {
+      mockedInvoke.mockRejectedValue("plain failure");
+      await expect(cancelSearch(7)).rejects.toBe("plain failure");
+    });
+  });
+
+  describe("searchMore", () => {
+    it("asks for the next results of one site of a job", async () => {
+      mockedInvoke.mockResolvedValue({ results: [], has_more: false });
+      expect(await searchMore(7, "fitgirl")).toEqual({ results: [], has_more: false });
+      expect(mockedInvoke).toHaveBeenCalledWith("search_more", { jobId: 7, site: "fitgirl" });
+    });
+  });
+
+  describe("openResult / copyText", () => {
+    it("delegate to the backend commands", async () => {
+      mockedInvoke.mockResolvedValue(undefined);
+      await openResult("https://example.com/game");
+      expect(mockedInvoke).toHaveBeenCalledWith("open_result", {
+        url: "https://example.com/game",
+      });
+      await copyText("magnet:?xt=urn:btih:abc");
+      expect(mockedInvoke).toHaveBeenCalledWith("copy_to_clipboard", {
+        text: "magnet:?xt=urn:btih:abc",
+      });
+    });
+  });
+
+  describe("getHistory", () => {
+    it("requests the first page by default", async () => {
+      mockedInvoke.mockResolvedValue({ entries: [], total: 0, page: 0, page_size: 50 });
+      await getHistory();
+      expect(mockedInvoke).toHaveBeenCalledWith("get_history", { page: 0, pageSize: 50 });
+    });
+  });
+
+  describe("addWatch", () => {
+    it("passes sites and interval in camelCase", async () => {
+      mockedInvoke.mockResolvedValue({ id: 1 });
+      await addWatch("elden ring", ["fitgirl"], 30);
+      expect(mockedInvoke).toHaveBeenCalledWith("add_watch", {
+        query: "elden ring",
+        sites: ["fitgirl"],
+        intervalMinutes: 30,
+      });
+    });
+  });
+
+  describe("setSolverUrl", () => {
+    it("sends a blank URL as null to go back to the default", async () => {
+      mockedInvoke.mockResolvedValue({ url: "http://localhost:8191/v1", sessions: [] });
+      await setSolverUrl("  ");
+      expect(mockedInvoke).toHaveBeenCalledWith("set_solver_url", { url: null });
+      await setSolverUrl(" http://solver.lan:8191/v1 ");
+      expect(mockedInvoke).toHaveBeenCalledWith("set_solver_url", {
+        url: "http://solver.lan:8191/v1",
+      });
+    });
+  });
+
+  describe("importCookie", () => {
+    it("sends a pasted cookie, or nothing so the backend asks for a file", async () => {
+      mockedInvoke.mockResolvedValue(null);
+      await importCookie("csrin", " sid=1 ");
+      expect(mockedInvoke).toHaveBeenCalledWith("import_cookie", {
+        site: "csrin",
+        cookie: "sid=1",
+        path: null,
+      });
+      await importCookie("csrin");
+      expect(mockedInvoke).toHaveBeenLastCalledWith("import_cookie", {
+        site: "csrin",
+        cookie: null,
+        path: null,
+      });
+    });
+  });
+
+  describe("quickSearch", () => {
+    it("rejects empty queries without calling the backend", async () => {
+      await expect(quickSearch("  ")).rejects.toThrow("Query is required");
+      expect(mockedInvoke).not.toHaveBeenCalled();
+      mockedInvoke.mockResolvedValue({ results: [], timed_out: [] });
+      await quickSearch("hades");
+      expect(mockedInvoke).toHaveBeenCalledWith("quick_search", { query: "hades" });
+    });
+  });
+
+  describe("captureParserReport", () => {
+    it("rejects empty queries and sends the trimmed query", async () => {
+      await expect(captureParserReport("fitgirl", " ")).rejects.toThrow("Query is required");
+      expect(mockedInvoke).not.toHaveBeenCalled();
+      mockedInvoke.mockResolvedValue({ path: "/tmp/fitgirl-1.zip", diagnostics: {} });
+      await captureParserReport("fitgirl", " hades ");
+      expect(mockedInvoke).toHaveBeenCalledWith("capture_parser_report", {
+        site: "fitgirl",
+        query: "hades",
+      });
     });
   });
 });
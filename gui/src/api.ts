@@ -1,9 +1,47 @@
-import { invoke } from '@tauri-apps/api/core'
+import { invoke as tauriInvoke, type InvokeArgs } from '@tauri-apps/api/core'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import { toApiError } from './errors'
+
+// Command rejections are rethrown as ApiError; `rest` keeps calls without args argument-free
+async function invoke<T>(cmd: string, ...rest: [args?: InvokeArgs]): Promise<T> {
+  try {
+    return await tauriInvoke<T>(cmd, ...rest)
+  } catch (e) {
+    throw toApiError(e)
+  }
+}
 
 export type SearchResult = {
   site: string
   title: string
   url: string
+  // Search page the result was parsed from, its 1-based position there and
+  // the fetch time (Unix seconds); absent on results saved by older versions
+  source_url?: string
+  rank?: number
+  // Image on the result's card; shown through getThumbnail, never loaded from the site
+  image?: string
+  fetched_at?: number
+  // Fetch path that produced the result
+  provenance?: 'html' | 'solver' | 'playwright' | 'feed' | 'ajax' | 'sitemap'
+  // Steam AppID of the game the title names, when Steam lookups are on
+  steam_appid?: number
+  // The user's status and note for this URL
+  note?: Note
+  // Set on cached results by the CLI's verify-cache
+  link_check?: { state: 'alive' | 'dead' | 'blocked' | 'unreachable'; status?: number; checked_at: number }
+}
+
+export type NoteStatus = 'interested' | 'downloaded' | 'ignored'
+
+// fast: direct HTTP only, 5s per site; thorough: every fallback, more pages, link checks
+export type SearchProfile = 'default' | 'fast' | 'thorough'
+
+// Status and text attached to a result URL, shared with the CLI's annotate command
+export type Note = {
+  status?: NoteStatus
+  text?: string
+  updated_at: number
 }
 
 export type SearchArgs = {
@@ -20,17 +58,110 @@ export type SearchArgs = {
   csrin_search?: boolean
   no_playwright?: boolean
   no_rate_limit?: boolean
+  // Read site:, -term, "phrase", regex: and | as operators; unset detects them
+  advanced?: boolean
+  // Merge the same release found on several sites into one group
+  group?: boolean
+  // Leave out results whose note has one of these statuses
+  hide_status?: NoteStatus[]
+  // Overrides no_cf, no_playwright and csrin_pages
+  profile?: SearchProfile
+}
+
+// One |-separated part of an advanced query; `results` are indexes into the results
+export type SegmentMatch = {
+  index: number
+  query: string
+  sites: string[]
+  results: number[]
+}
+
+// A result in a duplicate group, with what could be read from its title
+export type GroupMember = SearchResult & {
+  metadata: {
+    file_size?: string
+    release_date?: string
+    version?: string
+    build?: string
+  }
+}
+
+// The same release found on one or more sites
+export type DuplicateGroup = {
+  title: string
+  sites: string[]
+  members: GroupMember[]
+}
+
+// Where a search's time went, in milliseconds; phases are queue, fetch,
+// solver, render, parse and post_process
+export type TimingBreakdown = {
+  total_ms: number
+  phases: Record<string, number>
+  sites: Record<string, Record<string, number>>
 }
 
-export async function invokeSearch(args: SearchArgs): Promise<SearchResult[]> {
+export type SearchResponse = {
+  results: SearchResult[]
+  // Only present for multi-part (|) queries
+  segments?: SegmentMatch[]
+  // Only present when `group` was requested
+  groups?: DuplicateGroup[]
+  timings?: TimingBreakdown
+}
+
+export async function invokeSearch(args: SearchArgs): Promise<SearchResponse> {
   if (!args.query || !args.query.trim()) {
     throw new Error('Query is required')
   }
-  return await invoke<SearchResult[]>('search_gui', { args })
+  return await invoke<SearchResponse>('search_gui', { args })
+}
+
+// One |-separated part of a query, operator by operator
+export type QuerySegment = {
+  query: string
+  terms: string[]
+  phrases: string[]
+  exclude: string[]
+  regex: string[]
+  // Sites named with site:; empty means all
+  sites: string[]
+}
+
+// How a search would read a query; `terms` holds one search per segment that
+// applies to the site, and is empty for sites site: leaves out
+export type QueryBreakdown = {
+  advanced: boolean
+  segments: QuerySegment[]
+  sites: { site: string; terms: string[] }[]
+}
+
+// Reads `query`, `sites` and `advanced` of `args`; nothing is searched
+export async function parseQuery(args: SearchArgs): Promise<QueryBreakdown> {
+  return await invoke<QueryBreakdown>('parse_query', { args })
+}
+
+export type SiteInfo = {
+  name: string
+  base_url: string
+  search_kind: string
+  requires_js: boolean
+  requires_cloudflare: boolean
+  // Checked by default in the site picker
+  enabled: boolean
+}
+
+// Sites in the user's saved order
+export async function listSites(): Promise<SiteInfo[]> {
+  return await invoke<SiteInfo[]>('list_sites')
 }
 
 export async function fetchSites(): Promise<string[]> {
-  return await invoke<string[]>('list_sites')
+  return (await listSites()).map((s) => s.name)
+}
+
+export async function setSitePrefs(enabled: string[], order: string[]): Promise<void> {
+  await invoke('set_site_prefs', { enabled, order })
 }
 
 // Cache types
@@ -69,29 +200,464 @@ export async function setCacheSize(size: number): Promise<void> {
   await invoke('set_cache_size', { size })
 }
 
+// Search history (every search run), separate from the result cache
+export type HistoryEntry = {
+  id: number
+  query: string
+  timestamp: number
+  // Empty when all sites were searched
+  sites: string[]
+  result_count: number
+  site_counts: Record<string, number>
+}
+
+export type HistoryPage = {
+  entries: HistoryEntry[]
+  total: number
+  page: number
+  page_size: number
+}
+
+export async function getHistory(page = 0, pageSize = 50): Promise<HistoryPage> {
+  return await invoke<HistoryPage>('get_history', { page, pageSize })
+}
+
+export async function deleteHistoryEntry(id: number): Promise<boolean> {
+  return await invoke<boolean>('delete_history_entry', { id })
+}
+
+export async function clearHistory(): Promise<void> {
+  await invoke('clear_history')
+}
+
+// Opens http(s) and magnet links with the system handler; other schemes are rejected
+// Steam store page of an app, for results with a steam_appid
+export function steamStoreUrl(appid: number): string {
+  return `https://store.steampowered.com/app/${appid}/`
+}
+
+export async function openResult(url: string): Promise<void> {
+  await invoke('open_result', { url })
+}
+
+export async function copyText(text: string): Promise<void> {
+  await invoke('copy_to_clipboard', { text })
+}
+
+export type ResultDetails = {
+  title?: string
+  description?: string
+  metadata: {
+    file_size?: string
+    release_date?: string
+    version?: string
+    build?: string
+  }
+  magnets: string[]
+  torrents: string[]
+  mirrors: string[]
+}
+
+// Fetches the result page through the site's solver/cookie path
+export async function getResultDetails(url: string, site: string): Promise<ResultDetails> {
+  return await invoke<ResultDetails>('get_result_details', { url, site })
+}
+
+// A result's image as a data: URL, fetched once through the site's route and
+// then served from the cache directory
+export async function getThumbnail(url: string, site: string): Promise<string> {
+  return await invoke<string>('get_thumbnail', { url, site })
+}
+
+// What the parser made of a site's search page
+export type ParserDiagnostics = {
+  site: string
+  query: string
+  html_bytes: number
+  page_title: string | null
+  looks_like_challenge: boolean
+  // One selector, or several tried in order
+  result_selector: string | string[]
+  // Position in result_selector of the one the results came from
+  matched_selector: number | null
+  // Whether the results came from the page's <noscript> HTML
+  noscript: boolean
+  selector_matches: number | null
+  title_attr: string
+  url_attr: string
+  // Matches a URL was found in with url_attr
+  selector_urls: number | null
+  results: number
+  anchors: number
+  anchors_matching_query: { text: string; href: string }[]
+  articles: number
+  entry_titles: number
+}
+
+export type ParserReport = { path: string; diagnostics: ParserDiagnostics }
+
+// Saves the raw search page and diagnostics to a zip in the app data dir
+export async function captureParserReport(site: string, query: string): Promise<ParserReport> {
+  if (!query.trim()) throw new Error('Query is required')
+  return await invoke<ParserReport>('capture_parser_report', { site, query: query.trim() })
+}
+
+export type ExportFormat = 'json' | 'csv' | 'markdown' | 'html'
+
+// Without a path the backend asks for one in a save dialog; resolves to the
+// written path, or null if the dialog was cancelled
+export async function exportResults(
+  format: ExportFormat,
+  results: SearchResult[],
+  path?: string
+): Promise<string | null> {
+  return await invoke<string | null>('export_results', { format, path, results })
+}
+
+// First-run setup checklist
+export type EnvCheck = { ok: boolean; detail: string }
+
+export type EnvironmentReport = {
+  flaresolverr: EnvCheck
+  node: EnvCheck
+  playwright: EnvCheck
+  cache_dir: EnvCheck
+  sites: (EnvCheck & { site: string; url: string })[]
+}
+
+export async function checkEnvironment(): Promise<EnvironmentReport> {
+  return await invoke<EnvironmentReport>('check_environment')
+}
+
+// FlareSolverr the searches go through; the URL is saved in config.toml
+export type SolverStatus = {
+  url: string
+  source: 'settings' | 'env' | 'default'
+  reachable: boolean
+  version?: string | null
+  sessions: string[]
+  error?: string | null
+}
+
+export async function getSolverStatus(): Promise<SolverStatus> {
+  return await invoke<SolverStatus>('get_solver_status')
+}
+
+// An empty URL goes back to CF_URL or the local default
+export async function setSolverUrl(url: string): Promise<SolverStatus> {
+  return await invoke<SolverStatus>('set_solver_url', { url: url.trim() || null })
+}
+
+// Destroys all solver sessions; returns how many were open
+export async function resetSolverSessions(): Promise<number> {
+  return await invoke<number>('reset_solver_sessions')
+}
+
+// Health dashboard data, counted since the app started
+export type SiteStats = {
+  site: string
+  requests: number
+  successes: number
+  failures: number
+  success_rate: number
+  avg_response_ms: number
+}
+
+export type BreakerStatus = {
+  site: string
+  state: 'closed' | 'open' | 'half_open'
+  failures: number
+}
+
+// A site that keeps finding nothing for queries other sites match
+export type BrokenSite = {
+  site: string
+  streak: number
+  queries: string[]
+}
+
+export type MetricsSnapshot = {
+  uptime_seconds: number
+  requests: number
+  successes: number
+  failures: number
+  cache_hits: number
+  cache_misses: number
+  cache_hit_rate: number | null
+  sites: SiteStats[]
+  breakers: BreakerStatus[]
+  likely_broken: BrokenSite[]
+  // Sites whose search ignores the query, searched as listing sites
+  ignores_query: string[]
+}
+
+export async function getMetricsSnapshot(): Promise<MetricsSnapshot> {
+  return await invoke<MetricsSnapshot>('get_metrics_snapshot')
+}
+
+// Favorites, stored in the app data dir
+export type Favorite = SearchResult & {
+  added_at: number
+}
+
+export async function addFavorite(result: SearchResult): Promise<boolean> {
+  return await invoke<boolean>('add_favorite', { result })
+}
+
+export async function listFavorites(): Promise<Favorite[]> {
+  return await invoke<Favorite[]>('list_favorites')
+}
+
+export async function removeFavorite(url: string): Promise<boolean> {
+  return await invoke<boolean>('remove_favorite', { url })
+}
+
+// Notes by result URL
+export async function getNotes(): Promise<Record<string, Note>> {
+  return await invoke<Record<string, Note>>('get_notes')
+}
+
+// Replaces the note on a URL; with neither status nor text it is removed and null returned
+export async function setNote(url: string, status?: NoteStatus, text?: string): Promise<Note | null> {
+  return await invoke<Note | null>('set_note', { url, status, text })
+}
+
+export async function deleteNote(url: string): Promise<boolean> {
+  return await invoke<boolean>('delete_note', { url })
+}
+
+// Results matching an ignore rule are left out of every search
+export type IgnoreKind = 'url' | 'domain' | 'title'
+
+export type IgnoreRule = {
+  kind: IgnoreKind
+  // An exact URL, a domain (subdomains included) or a case-insensitive title regex
+  value: string
+}
+
+export async function getIgnoreRules(): Promise<IgnoreRule[]> {
+  return await invoke<IgnoreRule[]>('get_ignore_rules')
+}
+
+// Returns the rule as saved, with domains normalized
+export async function addIgnoreRule(kind: IgnoreKind, value: string): Promise<IgnoreRule> {
+  return await invoke<IgnoreRule>('add_ignore_rule', { kind, value })
+}
+
+export async function removeIgnoreRule(rule: IgnoreRule): Promise<boolean> {
+  return await invoke<boolean>('remove_ignore_rule', { rule })
+}
+
+// Watched searches, re-run in the background by the app
+export type WatchEntry = {
+  id: number
+  query: string
+  sites: string[]
+  interval_minutes: number
+  created_at: number
+  last_checked?: number
+  new_results: SearchResult[]
+}
+
+export type WatchUpdate = {
+  id: number
+  query: string
+  new_results: SearchResult[]
+}
+
+export async function addWatch(query: string, sites?: string[], intervalMinutes?: number): Promise<WatchEntry> {
+  return await invoke<WatchEntry>('add_watch', { query, sites, intervalMinutes })
+}
+
+export async function listWatches(): Promise<WatchEntry[]> {
+  return await invoke<WatchEntry[]>('list_watches')
+}
+
+export async function deleteWatch(id: number): Promise<boolean> {
+  return await invoke<boolean>('delete_watch', { id })
+}
+
+export async function onWatchlistUpdated(handler: (update: WatchUpdate) => void): Promise<UnlistenFn> {
+  return await listen<WatchUpdate>('watchlist://updated', (event) => handler(event.payload))
+}
+
+// Result pages re-fetched in the background and reported when their content changes
+export type PageWatch = {
+  id: number
+  url: string
+  site: string
+  title: string
+  interval_minutes: number
+  created_at: number
+  last_checked?: number
+  content_hash?: string
+  // When a check last found the page changed
+  last_changed?: number
+}
+
+export type PageUpdate = {
+  id: number
+  url: string
+  title: string
+  changed_at: number
+}
+
+// Watching a URL that is already watched returns the existing watch
+export async function watchPage(url: string, site: string, title: string, intervalMinutes?: number): Promise<PageWatch> {
+  return await invoke<PageWatch>('watch_page', { url, site, title, intervalMinutes })
+}
+
+export async function listPageWatches(): Promise<PageWatch[]> {
+  return await invoke<PageWatch[]>('list_page_watches')
+}
+
+export async function deletePageWatch(id: number): Promise<boolean> {
+  return await invoke<boolean>('delete_page_watch', { id })
+}
+
+export async function onPageUpdated(handler: (update: PageUpdate) => void): Promise<UnlistenFn> {
+  return await listen<PageUpdate>('watchlist://page_updated', (event) => handler(event.payload))
+}
+
+// Quick search from the tray window: fast sites only, a few results each,
+// returning what arrived within a few seconds
+export type QuickSearchResponse = {
+  results: SearchResult[]
+  timed_out: string[]
+}
+
+export async function quickSearch(query: string): Promise<QuickSearchResponse> {
+  if (!query.trim()) {
+    throw new Error('Query is required')
+  }
+  return await invoke<QuickSearchResponse>('quick_search', { query })
+}
+
+// Runs the query as a full search in the main window
+export async function openInMainWindow(query: string): Promise<void> {
+  await invoke('open_in_main_window', { query })
+}
+
+export async function hideQuickSearch(): Promise<void> {
+  await invoke('hide_quick_search')
+}
+
+// A search requested by a websearcher://search?q=...&sites=a,b link
+export type DeepLinkSearch = {
+  query: string
+  sites: string[] | null
+}
+
+// The search from the latest link, if it has not been taken yet
+export async function takeDeepLink(): Promise<DeepLinkSearch | null> {
+  return await invoke<DeepLinkSearch | null>('take_deep_link')
+}
+
+// Called when a link arrives while the app is open; take it with takeDeepLink
+export async function onDeepLink(handler: () => void): Promise<UnlistenFn> {
+  return await listen('deeplink://search', () => handler())
+}
+
+// Search defaults, stored in config.toml next to the CLI's settings
+export type SearchDefaults = {
+  sites: string[]
+  limit: number
+  cf_url?: string
+  cookies: Record<string, string>
+  concurrency: number
+  hide_nsfw: boolean
+  // Look up each result's Steam AppID (app list downloaded weekly)
+  steam_appids: boolean
+  // Seconds a streaming search waits before showing what has arrived; unset waits for all sites
+  deadline_seconds?: number
+}
+
+// What import_cookie stored for a site
+export type CookieImport = {
+  site: string
+  names: string[]
+  domains: string[]
+  other_domains: string[]
+  expired: number
+}
+
+// Saves a pasted Cookie header or cookies.txt text as the site's cookie; with
+// neither a cookie nor a path the backend asks for a cookies.txt file and
+// resolves to null if that dialog is cancelled
+export async function importCookie(
+  site: string,
+  cookie?: string,
+  path?: string,
+): Promise<CookieImport | null> {
+  return await invoke<CookieImport | null>('import_cookie', {
+    site,
+    cookie: cookie?.trim() || null,
+    path: path ?? null,
+  })
+}
+
+export async function getSettings(): Promise<SearchDefaults> {
+  return await invoke<SearchDefaults>('get_settings')
+}
+
+export async function setSettings(settings: SearchDefaults): Promise<void> {
+  await invoke('set_settings', { settings })
+}
+
 // Streaming search types
 export type SearchProgress = {
+  job_id: number
   site: string
-  status: 'pending' | 'fetching' | 'parsing' | 'completed' | 'failed'
+  status: 'pending' | 'fetching' | 'completed'
   results_count: number
-  message?: string
 }
 
-export type StreamedResult = {
+export type SiteResults = {
+  job_id: number
+  site: string
+  results: SearchResult[]
+}
+
+// Results of a site that finished after the search's deadline; the backend
+// has merged them into the cached search already
+export type LateResults = {
+  job_id: number
   site: string
-  result: SearchResult
+  results: SearchResult[]
+  // Sites still searching
+  pending: string[]
 }
 
-export type SearchComplete = {
+export type SearchDone = {
+  job_id: number
   total_results: number
   sites_completed: number
-  sites_failed: number
+  cancelled: boolean
+  // Sites still searching when the deadline passed; see LateResults
+  late_sites?: string[]
+  results: SearchResult[]
+  segments?: SegmentMatch[]
+  groups?: DuplicateGroup[]
+  timings?: TimingBreakdown
 }
 
-// Streaming search invocation (use with event listeners)
-export async function invokeSearchStreaming(args: SearchArgs): Promise<SearchResult[]> {
+// Starts a background search and returns its job id; results arrive as
+// search://progress, search://site_results and search://done events, then
+// search://late_results for sites that missed the deadline
+export async function startSearch(args: SearchArgs): Promise<number> {
   if (!args.query || !args.query.trim()) {
     throw new Error('Query is required')
   }
-  return await invoke<SearchResult[]>('search_gui_streaming', { args })
+  return await invoke<number>('start_search', { args })
+}
+
+export async function cancelSearch(jobId: number): Promise<boolean> {
+  return await invoke<boolean>('cancel_search', { jobId })
+}
+
+export type MoreResults = { results: SearchResult[]; has_more: boolean }
+
+// Next results of one site for a finished streaming search, without the ones shown already
+export async function searchMore(jobId: number, site: string): Promise<MoreResults> {
+  return await invoke<MoreResults>('search_more', { jobId, site })
 }
@@ -0,0 +1,56 @@
+// Errors from backend commands: every command rejects with
+// { category, message, site, retryable } so the UI can offer a fitting action
+
+export type ApiErrorCategory =
+  | 'invalid_input'
+  | 'not_found'
+  | 'solver_unavailable'
+  | 'network'
+  | 'rate_limit'
+  | 'auth'
+  | 'server_error'
+  | 'parse'
+  | 'circuit_open'
+  | 'storage'
+  | 'internal'
+
+type ApiErrorPayload = {
+  category: ApiErrorCategory
+  message: string
+  site: string | null
+  retryable: boolean
+}
+
+export class ApiError extends Error {
+  category: ApiErrorCategory
+  site: string | null
+  retryable: boolean
+
+  constructor(payload: ApiErrorPayload) {
+    super(payload.message)
+    this.name = 'ApiError'
+    this.category = payload.category
+    this.site = payload.site
+    this.retryable = payload.retryable
+  }
+
+  // String(e) shows the backend's message without an "ApiError:" prefix
+  toString(): string {
+    return this.message
+  }
+}
+
+function isPayload(e: unknown): e is ApiErrorPayload {
+  return typeof e === 'object' && e !== null && 'category' in e && 'message' in e
+}
+
+// The rejection of a command as an ApiError; anything else is returned unchanged
+export function toApiError(e: unknown): unknown {
+  return isPayload(e) ? new ApiError(e) : e
+}
+
+// What to keep in error state: the ApiError itself, else a message
+export function errorOf(e: unknown): ApiError | string {
+  if (e instanceof ApiError) return e
+  return e instanceof Error ? e.message : String(e)
+}
@@ -1,17 +1,21 @@
 import { useState, useEffect, useCallback, useRef } from 'react'
 import { listen, type UnlistenFn } from '@tauri-apps/api/event'
 import { 
-  invokeSearchStreaming, 
+  startSearch as apiStartSearch, 
+  cancelSearch, 
+  searchMore,
   type SearchArgs, 
   type SearchResult, 
   type SearchProgress, 
-  type StreamedResult, 
-  type SearchComplete 
+  type SiteResults, 
+  type SearchDone,
+  type LateResults
 } from '../api'
+import { errorOf, type ApiError } from '../errors'
 
 export type SiteProgress = {
   site: string
-  status: 'pending' | 'fetching' | 'parsing' | 'completed' | 'failed'
+  status: 'pending' | 'fetching' | 'completed' | 'failed'
   resultsCount: number
   message?: string
 }
@@ -20,10 +24,18 @@ type UseRealtimeSearchReturn = {
   results: SearchResult[]
   progress: Map<string, SiteProgress>
   isSearching: boolean
-  error: string | null
-  completionInfo: SearchComplete | null
-  startSearch: (args: SearchArgs) => Promise<void>
+  error: ApiError | string | null
+  completionInfo: SearchDone | null
+  /**
+   * Resolves with the final results once the search is done, or null if it was
+   * cancelled or sites are still searching past the deadline (the backend then
+   * caches the results itself, late ones included)
+   */
+  startSearch: (args: SearchArgs) => Promise<SearchResult[] | null>
+  cancel: () => Promise<void>
   clearResults: () => void
+  /** Append the next results of `site` from the finished search; resolves to whether it has more */
+  loadMore: (site: string) => Promise<boolean>
 }
 
 /**
@@ -31,7 +43,7 @@ type UseRealtimeSearchReturn = {
  * 
  * @example
  * ```tsx
- * const { results, progress, isSearching, startSearch } = useRealtimeSearch()
+ * const { results, progress, isSearching, startSearch, cancel } = useRealtimeSearch()
  * 
  * // Start a streaming search
  * await startSearch({ query: 'elden ring', limit: 10 })
@@ -44,11 +56,15 @@ export function useRealtimeSearch(): UseRealtimeSearchReturn {
   const [results, setResults] = useState<SearchResult[]>([])
   const [progress, setProgress] = useState<Map<string, SiteProgress>>(new Map())
   const [isSearching, setIsSearching] = useState(false)
-  const [error, setError] = useState<string | null>(null)
-  const [completionInfo, setCompletionInfo] = useState<SearchComplete | null>(null)
+  const [error, setError] = useState<ApiError | string | null>(null)
+  const [completionInfo, setCompletionInfo] = useState<SearchDone | null>(null)
   
   // Track unsubscribe functions
   const unlistenRefs = useRef<UnlistenFn[]>([])
+  // Job id of the running search; events from other jobs are ignored
+  const jobRef = useRef<number | null>(null)
+  // Job id of the last finished search, which `loadMore` continues
+  const doneJobRef = useRef<number | null>(null)
 
   // Cleanup listeners on unmount
   useEffect(() => {
@@ -59,13 +75,26 @@ export function useRealtimeSearch(): UseRealtimeSearchReturn {
   }, [])
 
   const clearResults = useCallback(() => {
+    doneJobRef.current = null
     setResults([])
     setProgress(new Map())
     setError(null)
     setCompletionInfo(null)
   }, [])
 
-  const startSearch = useCallback(async (args: SearchArgs) => {
+  const cancel = useCallback(async () => {
+    if (jobRef.current !== null) {
+      await cancelSearch(jobRef.current)
+    }
+  }, [])
+
+  const startSearch = useCallback(async (args: SearchArgs): Promise<SearchResult[] | null> => {
+    // Only one search at a time
+    if (jobRef.current !== null) {
+      await cancelSearch(jobRef.current)
+    }
+    jobRef.current = null
+
     // Clear previous state
     clearResults()
     setIsSearching(true)
@@ -74,44 +103,90 @@ export function useRealtimeSearch(): UseRealtimeSearchReturn {
     unlistenRefs.current.forEach((unlisten) => unlisten())
     unlistenRefs.current = []
 
+    // Events can arrive before start_search returns the job id
+    const isCurrent = (jobId: number) => jobRef.current === null || jobRef.current === jobId
+    // Partial results stay on screen after a cancel
+    let partial: SearchResult[] = []
+
     try {
+      let resolveDone: (rs: SearchResult[] | null) => void = () => {}
+      const done = new Promise<SearchResult[] | null>((resolve) => {
+        resolveDone = resolve
+      })
+
       // Set up event listeners before starting the search
-      const unlistenProgress = await listen<SearchProgress>('search:progress', (event) => {
+      const unlistenDone = await listen<SearchDone>('search://done', (event) => {
+        const data = event.payload
+        if (!isCurrent(data.job_id)) return
+        jobRef.current = null
+        setCompletionInfo(data)
+        setIsSearching(false)
+        if (data.cancelled) {
+          resolveDone(null)
+        } else {
+          doneJobRef.current = data.job_id
+          setResults(data.results)
+          resolveDone(data.late_sites?.length ? null : data.results)
+        }
+      })
+      unlistenRefs.current.push(unlistenDone)
+
+      const unlistenLate = await listen<LateResults>('search://late_results', (event) => {
+        const data = event.payload
+        if (doneJobRef.current !== data.job_id) return
+        setResults((prev) => [...prev, ...data.results])
+        setProgress((prev) => {
+          const updated = new Map(prev)
+          updated.set(data.site, { site: data.site, status: 'completed', resultsCount: data.results.length })
+          return updated
+        })
+      })
+      unlistenRefs.current.push(unlistenLate)
+
+      const unlistenProgress = await listen<SearchProgress>('search://progress', (event) => {
         const data = event.payload
+        if (!isCurrent(data.job_id)) return
         setProgress((prev) => {
           const updated = new Map(prev)
           updated.set(data.site, {
             site: data.site,
-            status: data.status as SiteProgress['status'],
+            status: data.status,
             resultsCount: data.results_count,
-            message: data.message,
           })
           return updated
         })
       })
       unlistenRefs.current.push(unlistenProgress)
 
-      const unlistenResult = await listen<StreamedResult>('search:result', (event) => {
+      const unlistenSite = await listen<SiteResults>('search://site_results', (event) => {
         const data = event.payload
-        setResults((prev) => [...prev, data.result])
+        if (!isCurrent(data.job_id)) return
+        partial = [...partial, ...data.results]
+        setResults(partial)
       })
-      unlistenRefs.current.push(unlistenResult)
-
-      const unlistenComplete = await listen<SearchComplete>('search:complete', (event) => {
-        setCompletionInfo(event.payload)
-        setIsSearching(false)
-      })
-      unlistenRefs.current.push(unlistenComplete)
+      unlistenRefs.current.push(unlistenSite)
 
       // Start the streaming search
-      await invokeSearchStreaming(args)
-
+      jobRef.current = await apiStartSearch(args)
+      return await done
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err))
+      setError(errorOf(err))
       setIsSearching(false)
+      return null
     }
   }, [clearResults])
 
+  const loadMore = useCallback(async (site: string): Promise<boolean> => {
+    const jobId = doneJobRef.current
+    if (jobId === null) return false
+    const more = await searchMore(jobId, site)
+    // Ignore pages that arrive after a new search started
+    if (doneJobRef.current === jobId && more.results.length > 0) {
+      setResults((prev) => [...prev, ...more.results])
+    }
+    return more.has_more
+  }, [])
+
   return {
     results,
     progress,
@@ -119,7 +194,9 @@ export function useRealtimeSearch(): UseRealtimeSearchReturn {
     error,
     completionInfo,
     startSearch,
+    cancel,
     clearResults,
+    loadMore,
   }
 }
 
@@ -2,9 +2,13 @@ import { StrictMode } from 'react'
 import { createRoot } from 'react-dom/client'
 import './index.css'
 import App from './App.tsx'
+import QuickSearch from './QuickSearch.tsx'
+
+// The quick-search window loads the same page with ?view=quick
+const quick = new URLSearchParams(window.location.search).get('view') === 'quick'
 
 createRoot(document.getElementById('root')!).render(
   <StrictMode>
-    <App />
+    {quick ? <QuickSearch /> : <App />}
   </StrictMode>,
 )
@@ -0,0 +1,156 @@
+//! `websearcher://search?q=...` links, so bookmarks and other apps can start a
+//! search in the GUI.
+//!
+//! Links are validated here and kept until the frontend takes them with
+//! `take_deep_link`; "deeplink://search" tells an open window that one arrived.
+//! A link that launches the app waits until the window asks for it.
+
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{Emitter, Manager, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+pub const SCHEME: &str = "websearcher";
+/// Longest query accepted from a link
+const MAX_QUERY_LEN: usize = 200;
+
+/// A search requested by a link
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct DeepLinkSearch {
+    pub query: String,
+    /// Sites from `sites=a,b`; unset searches the default sites
+    pub sites: Option<Vec<String>>,
+}
+
+/// The last valid link the frontend has not taken yet
+#[derive(Default)]
+pub struct PendingDeepLink(Mutex<Option<DeepLinkSearch>>);
+
+/// Read a `websearcher://search?q=...&sites=a,b` link
+pub fn parse_search_url(url: &Url) -> Result<DeepLinkSearch, String> {
+    if url.scheme() != SCHEME || url.host_str() != Some("search") {
+        return Err(format!("not a {SCHEME}://search link: {url}"));
+    }
+    let mut query = None;
+    let mut sites = None;
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "q" => query = Some(value.trim().to_string()),
+            "sites" => sites = Some(value.into_owned()),
+            _ => {}
+        }
+    }
+    let query = query
+        .filter(|q| !q.is_empty())
+        .ok_or("link has no search phrase (q=...)")?;
+    if query.chars().count() > MAX_QUERY_LEN {
+        return Err(format!(
+            "search phrase is longer than {MAX_QUERY_LEN} characters"
+        ));
+    }
+    if query.chars().any(char::is_control) {
+        return Err("search phrase contains control characters".to_string());
+    }
+
+    let known = crate::known_sites();
+    let sites = match sites {
+        Some(list) => {
+            let mut names = Vec::new();
+            for name in list.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                let site = known
+                    .iter()
+                    .find(|s| s.name.eq_ignore_ascii_case(name))
+                    .ok_or_else(|| format!("unknown site in link: {name}"))?;
+                names.push(site.name.clone());
+            }
+            (!names.is_empty()).then_some(names)
+        }
+        None => None,
+    };
+    Ok(DeepLinkSearch { query, sites })
+}
+
+fn handle_urls(app_handle: &tauri::AppHandle, urls: Vec<Url>) {
+    // Only the last valid link counts when several arrive at once
+    let Some(search) = urls
+        .iter()
+        .filter_map(|url| {
+            parse_search_url(url)
+                .inspect_err(|e| log::warn!("Ignoring link: {}", e))
+                .ok()
+        })
+        .next_back()
+    else {
+        return;
+    };
+    queue_search(app_handle, search);
+}
+
+/// Hand a search to the main window, bringing it to the front
+pub fn queue_search(app_handle: &tauri::AppHandle, search: DeepLinkSearch) {
+    let pending = app_handle.state::<PendingDeepLink>();
+    *pending.0.lock().unwrap_or_else(|e| e.into_inner()) = Some(search);
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    let _ = app_handle.emit("deeplink://search", ());
+}
+
+/// Register the scheme where that happens at runtime and start listening for links
+pub fn init(app: &tauri::App) {
+    app.manage(PendingDeepLink::default());
+    // Installed builds register the scheme from the bundle; dev builds need this
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(e) = app.deep_link().register_all() {
+        log::warn!("Failed to register {}:// links: {}", SCHEME, e);
+    }
+
+    let app_handle = app.handle().clone();
+    app.deep_link()
+        .on_open_url(move |event| handle_urls(&app_handle, event.urls()));
+    if let Ok(Some(urls)) = app.deep_link().get_current() {
+        handle_urls(app.handle(), urls);
+    }
+}
+
+/// The search from the most recent link, if the frontend has not run it yet
+#[tauri::command]
+pub fn take_deep_link(pending: tauri::State<'_, PendingDeepLink>) -> Option<DeepLinkSearch> {
+    pending.0.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(url: &str) -> Result<DeepLinkSearch, String> {
+        parse_search_url(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn search_links_are_parsed() {
+        assert_eq!(
+            parse("websearcher://search?q=elden%20ring").unwrap(),
+            DeepLinkSearch {
+                query: "elden ring".into(),
+                sites: None,
+            }
+        );
+        let search = parse("websearcher://search?q=hades&sites=FitGirl,%20dodi").unwrap();
+        assert_eq!(search.sites.unwrap(), ["fitgirl", "dodi"]);
+    }
+
+    #[test]
+    fn invalid_links_are_rejected() {
+        assert!(parse("https://search?q=hades").is_err());
+        assert!(parse("websearcher://open?q=hades").is_err());
+        assert!(parse("websearcher://search?q=%20").is_err());
+        assert!(parse("websearcher://search").is_err());
+        assert!(parse("websearcher://search?q=a%0Ab").is_err());
+        assert!(parse(&format!("websearcher://search?q={}", "a".repeat(201))).is_err());
+        assert!(parse("websearcher://search?q=hades&sites=nope").is_err());
+    }
+}
@@ -0,0 +1,217 @@
+//! First-run checks for what searches depend on: FlareSolverr, Node and
+//! Playwright (csrin), a writable cache directory and network access to sites.
+
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Serialize;
+use website_searcher_core::config;
+use website_searcher_core::error::ApiError;
+use website_searcher_core::settings::{Settings, default_settings_path};
+
+use crate::state::AppState;
+
+/// Give up on any single check after this long
+const CHECK_TIMEOUT: Duration = Duration::from_secs(8);
+/// Number of sites probed for network access
+const PROBED_SITES: usize = 2;
+
+/// Outcome of one check, with a human-readable detail either way
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Check {
+    pub ok: bool,
+    pub detail: String,
+}
+
+impl Check {
+    fn ok(detail: impl Into<String>) -> Self {
+        Self {
+            ok: true,
+            detail: detail.into(),
+        }
+    }
+
+    fn failed(detail: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            detail: detail.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SiteCheck {
+    pub site: String,
+    pub url: String,
+    #[serde(flatten)]
+    pub check: Check,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EnvironmentReport {
+    /// The solver is only needed for Cloudflare-protected sites
+    pub flaresolverr: Check,
+    pub node: Check,
+    /// Playwright and the csrin helper script, used for cs.rin.ru
+    pub playwright: Check,
+    pub cache_dir: Check,
+    pub sites: Vec<SiteCheck>,
+}
+
+async fn check_flaresolverr(client: &reqwest::Client, cf_url: &str) -> Check {
+    let request = client
+        .post(cf_url)
+        .json(&serde_json::json!({ "cmd": "sessions.list" }))
+        .timeout(CHECK_TIMEOUT)
+        .send();
+    match request.await {
+        Ok(resp) if resp.status().is_success() => Check::ok(format!("reachable at {cf_url}")),
+        Ok(resp) => Check::failed(format!("{cf_url} answered HTTP {}", resp.status())),
+        Err(e) => Check::failed(format!("not reachable at {cf_url}: {e}")),
+    }
+}
+
+/// Run `node` with `args` in `dir`, returning trimmed stdout on success
+async fn run_node(args: &[&str], dir: Option<&Path>) -> Result<String, String> {
+    let mut cmd = tokio::process::Command::new("node");
+    cmd.args(args).stdin(std::process::Stdio::null());
+    if let Some(dir) = dir {
+        cmd.current_dir(dir);
+    }
+    let output = tokio::time::timeout(CHECK_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| "timed out".to_string())?
+        .map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+async fn check_node_and_playwright() -> (Check, Check) {
+    let node = match run_node(&["--version"], None).await {
+        Ok(version) => Check::ok(format!("node {version}")),
+        Err(e) => {
+            let node = Check::failed(format!("node not found: {e}"));
+            return (node, Check::failed("needs node"));
+        }
+    };
+    let Some(script) = crate::resolve_csrin_script_path() else {
+        return (
+            node,
+            Check::failed("csrin_search.cjs helper script not found"),
+        );
+    };
+    // Resolve from the script's directory, where its node_modules would be
+    let playwright = match run_node(
+        &["-e", "console.log(require.resolve('playwright'))"],
+        script.parent(),
+    )
+    .await
+    {
+        Ok(_) => Check::ok(format!("playwright found for {}", script.display())),
+        Err(_) => Check::failed("playwright is not installed (npm install playwright)"),
+    };
+    (node, playwright)
+}
+
+/// Whether a file can be created in `dir`, creating the directory if needed
+fn check_dir_writable(dir: &Path) -> Check {
+    let probe = dir.join(".write-test");
+    let written = std::fs::create_dir_all(dir).and_then(|_| std::fs::write(&probe, b"ok"));
+    match written {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            Check::ok(format!("{} is writable", dir.display()))
+        }
+        Err(e) => Check::failed(format!("cannot write to {}: {e}", dir.display())),
+    }
+}
+
+async fn check_site(client: &reqwest::Client, site: &str, url: &str) -> SiteCheck {
+    let check = match client.get(url).timeout(CHECK_TIMEOUT).send().await {
+        Ok(resp) => {
+            let status = resp.status();
+            // Cloudflare challenges still prove the site is reachable
+            if status.is_success() || status.as_u16() == 403 || status.as_u16() == 503 {
+                Check::ok(format!("HTTP {status}"))
+            } else {
+                Check::failed(format!("HTTP {status}"))
+            }
+        }
+        Err(e) => Check::failed(e.to_string()),
+    };
+    SiteCheck {
+        site: site.to_string(),
+        url: url.to_string(),
+        check,
+    }
+}
+
+/// Check everything a first search needs, so the GUI can show a setup checklist
+#[tauri::command]
+pub async fn check_environment(
+    state: tauri::State<'_, AppState>,
+) -> Result<EnvironmentReport, ApiError> {
+    let defaults = Settings::load(&default_settings_path()).search;
+    let cf_url = crate::resolve_cf_url(None, &defaults);
+    let client = &state.client;
+    let cache_dir = crate::get_cache_path()
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+
+    // Sites that answer without the solver, so the probe only tests the network
+    let probes: Vec<_> = config::site_configs()
+        .into_iter()
+        .filter(|s| !s.requires_cloudflare)
+        .take(PROBED_SITES)
+        .collect();
+    let site_checks = probes
+        .iter()
+        .map(|s| check_site(client, &s.name, &s.base_url));
+
+    let (flaresolverr, (node, playwright), sites) = tokio::join!(
+        check_flaresolverr(client, &cf_url),
+        check_node_and_playwright(),
+        futures::future::join_all(site_checks),
+    );
+    Ok(EnvironmentReport {
+        flaresolverr,
+        node,
+        playwright,
+        cache_dir: check_dir_writable(&cache_dir),
+        sites,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dir_check_creates_missing_dirs_and_reports_failures() {
+        let dir = std::env::temp_dir().join(format!("ws-env-{}", std::process::id()));
+        let nested = dir.join("cache");
+        assert!(check_dir_writable(&nested).ok);
+        assert!(nested.exists());
+        assert!(!nested.join(".write-test").exists());
+
+        // A directory can't be created below a regular file
+        let file = dir.join("file");
+        std::fs::write(&file, b"x").unwrap();
+        let check = check_dir_writable(&file.join("cache"));
+        assert!(!check.ok);
+        assert!(check.detail.starts_with("cannot write to"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn unreachable_solver_is_reported() {
+        let client = reqwest::Client::new();
+        let check = check_flaresolverr(&client, "http://127.0.0.1:9/v1").await;
+        assert!(!check.ok);
+        assert!(check.detail.contains("127.0.0.1:9"));
+    }
+}
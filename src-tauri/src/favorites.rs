@@ -0,0 +1,163 @@
+//! Saved results ("favorites"), kept in `favorites.json` in the user data
+//! directory (see [`paths`]).
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use website_searcher_core::error::ApiError;
+use website_searcher_core::models::SearchResult;
+use website_searcher_core::paths;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Favorite {
+    #[serde(flatten)]
+    pub result: SearchResult,
+    /// Unix timestamp (seconds) when the result was saved
+    pub added_at: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Favorites {
+    items: Vec<Favorite>,
+}
+
+impl Favorites {
+    /// Load favorites, starting empty if the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Save a result; returns false if its URL is already a favorite
+    pub fn add(&mut self, result: SearchResult) -> bool {
+        if self.items.iter().any(|f| f.result.url == result.url) {
+            return false;
+        }
+        let added_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.items.push(Favorite { result, added_at });
+        true
+    }
+
+    /// Remove the favorite with `url`; returns false if there was none
+    pub fn remove(&mut self, url: &str) -> bool {
+        let before = self.items.len();
+        self.items.retain(|f| f.result.url != url);
+        self.items.len() != before
+    }
+
+    /// Favorites, most recently added first
+    pub fn list(&self) -> Vec<Favorite> {
+        self.items.iter().rev().cloned().collect()
+    }
+}
+
+/// The favorites file, after moving one older versions kept in Tauri's app
+/// data directory
+fn favorites_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, ApiError> {
+    let path = paths::favorites_file();
+    if let Ok(dir) = app_handle.path().app_data_dir() {
+        paths::migrate_file(&dir.join("favorites.json"), &path)
+            .map_err(|e| ApiError::storage(format!("failed to move favorites: {e}")))?;
+    }
+    Ok(path)
+}
+
+fn save(favorites: &Favorites, path: &Path) -> Result<(), ApiError> {
+    favorites
+        .save(path)
+        .map_err(|e| ApiError::storage(format!("failed to save favorites: {e}")))
+}
+
+#[tauri::command]
+pub async fn add_favorite(
+    app_handle: tauri::AppHandle,
+    result: SearchResult,
+) -> Result<bool, ApiError> {
+    let path = favorites_path(&app_handle)?;
+    let mut favorites = Favorites::load(&path);
+    let added = favorites.add(result);
+    if added {
+        save(&favorites, &path)?;
+    }
+    Ok(added)
+}
+
+#[tauri::command]
+pub async fn list_favorites(app_handle: tauri::AppHandle) -> Result<Vec<Favorite>, ApiError> {
+    Ok(Favorites::load(&favorites_path(&app_handle)?).list())
+}
+
+#[tauri::command]
+pub async fn remove_favorite(app_handle: tauri::AppHandle, url: String) -> Result<bool, ApiError> {
+    let path = favorites_path(&app_handle)?;
+    let mut favorites = Favorites::load(&path);
+    let removed = favorites.remove(&url);
+    if removed {
+        save(&favorites, &path)?;
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(title: &str, url: &str) -> SearchResult {
+        SearchResult {
+            site: "fitgirl".into(),
+            title: title.into(),
+            url: url.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn add_skips_duplicate_urls_and_lists_newest_first() {
+        let mut favorites = Favorites::default();
+        assert!(favorites.add(result("Elden Ring", "https://a.example/elden")));
+        assert!(favorites.add(result("Hades", "https://a.example/hades")));
+        assert!(!favorites.add(result("Elden Ring (again)", "https://a.example/elden")));
+
+        let titles: Vec<String> = favorites
+            .list()
+            .into_iter()
+            .map(|f| f.result.title)
+            .collect();
+        assert_eq!(titles, ["Hades", "Elden Ring"]);
+
+        assert!(favorites.remove("https://a.example/elden"));
+        assert!(!favorites.remove("https://a.example/elden"));
+        assert_eq!(favorites.list().len(), 1);
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let dir = std::env::temp_dir().join(format!("ws-favorites-{}", std::process::id()));
+        let path = dir.join("favorites.json");
+        let mut favorites = Favorites::default();
+        favorites.add(result("Celeste", "https://a.example/celeste"));
+        favorites.save(&path).unwrap();
+
+        let loaded = Favorites::load(&path);
+        assert_eq!(loaded.list(), favorites.list());
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(raw.contains("\"url\": \"https://a.example/celeste\""));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(Favorites::load(&path).list().is_empty());
+    }
+}
@@ -0,0 +1,52 @@
+//! The ignore list in `ignore.json` in the user data directory, shared with
+//! the CLI's `ignore` command (see [`IgnoreList`]).
+
+use std::sync::Mutex;
+
+use website_searcher_core::error::ApiError;
+use website_searcher_core::ignore::{IgnoreKind, IgnoreList, IgnoreRule};
+use website_searcher_core::models::SearchResult;
+use website_searcher_core::paths;
+
+/// Serializes load/modify/save of the file between commands
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Drop the results an ignore rule matches
+pub fn apply(results: &mut Vec<SearchResult>) {
+    IgnoreList::load(&paths::ignore_file()).apply(results);
+}
+
+fn save(list: &IgnoreList) -> Result<(), ApiError> {
+    list.save(&paths::ignore_file())
+        .map_err(|e| ApiError::storage(format!("failed to save the ignore list: {e:#}")))
+}
+
+/// Every rule, oldest first
+#[tauri::command]
+pub async fn get_ignore_rules() -> Result<Vec<IgnoreRule>, ApiError> {
+    Ok(IgnoreList::load(&paths::ignore_file()).rules().to_vec())
+}
+
+/// Add a rule and return it as saved (domains are normalized); adding a rule
+/// that is already listed is not an error
+#[tauri::command]
+pub async fn add_ignore_rule(kind: IgnoreKind, value: String) -> Result<IgnoreRule, ApiError> {
+    let rule = IgnoreRule::new(kind, &value).map_err(|e| ApiError::invalid_input(e.to_string()))?;
+    let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut list = IgnoreList::load(&paths::ignore_file());
+    if list.add(rule.clone()) {
+        save(&list)?;
+    }
+    Ok(rule)
+}
+
+#[tauri::command]
+pub async fn remove_ignore_rule(rule: IgnoreRule) -> Result<bool, ApiError> {
+    let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut list = IgnoreList::load(&paths::ignore_file());
+    let removed = list.remove(&rule);
+    if removed {
+        save(&list)?;
+    }
+    Ok(removed)
+}
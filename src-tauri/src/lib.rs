@@ -1,24 +1,66 @@
+mod deep_link;
+mod environment;
+mod favorites;
+mod ignore;
+mod notes;
+mod pages;
+mod quick;
+mod report;
+mod solver;
+mod state;
+mod thumbnails;
+mod watchlist;
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
 use futures::stream::{FuturesUnordered, StreamExt};
 use reqwest::header::{
     ACCEPT, COOKIE, HeaderMap as ReqHeaderMap, HeaderName, HeaderValue, REFERER,
 };
+use state::{AppState, Breakers};
+use tauri::{Emitter, Manager};
 use tokio::sync::Semaphore;
-use website_searcher_core::cache::{MIN_CACHE_SIZE, SearchCache};
-use website_searcher_core::query_parser::{AdvancedQuery, filter_results};
+use website_searcher_core::auth::Accounts;
+use website_searcher_core::budget::{DailyBudget, RequestLog};
+use website_searcher_core::capability::{self, QueryCapabilities, QuerySupport};
+use website_searcher_core::details::{self, ResultDetails};
+use website_searcher_core::error::ApiError;
+use website_searcher_core::fallback::{FallbackReport, Outcome};
+use website_searcher_core::fetcher::Fetched;
+use website_searcher_core::history::{HistoryPage, SearchHistory};
+use website_searcher_core::learned::LearnedPatterns;
+use website_searcher_core::monitoring::{SearchPhase, SearchTimings, TimingBreakdown};
+use website_searcher_core::notes::NoteStatus;
+use website_searcher_core::output::{self, ExportFormat};
+use website_searcher_core::page_store::PageStore;
+use website_searcher_core::profile::SearchProfile;
+use website_searcher_core::query_parser::{MultiQuery, filter_results};
 use website_searcher_core::rate_limiter::RateLimiter;
-use website_searcher_core::{cf, config, fetcher, models, parser, query};
+use website_searcher_core::route::{Route, Routes};
+use website_searcher_core::secrets::{self, SecretStore};
+use website_searcher_core::settings::{SearchDefaults, Settings, default_settings_path};
+use website_searcher_core::{
+    analyzer, browser, canonical, cf, config, cookies, crawl, feed, fetcher, limits, liveness,
+    models, monitoring, pagination, parser, paths, query, render_cache, resilience, sanitize,
+    sinks, sitemap, steam,
+};
+
+/// FlareSolverr endpoint used when none is configured
+const DEFAULT_CF_URL: &str = "http://localhost:8191/v1";
+
+/// Pause before a site that failed with a network or server error is
+/// searched once more, for the network or the site to recover
+const RETRY_DELAY: Duration = Duration::from_secs(1);
 
 /// Get the shared cache file path (same as CLI uses)
 fn get_cache_path() -> std::path::PathBuf {
-    dirs::cache_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("website-searcher")
-        .join("search_cache.json")
+    paths::search_cache_file()
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, Default, Clone)]
 struct SearchArgs {
     query: String,
     limit: Option<usize>,
@@ -33,39 +75,364 @@ struct SearchArgs {
     csrin_search: Option<bool>,
     no_playwright: Option<bool>,
     no_rate_limit: Option<bool>,
+    /// Read `site:`, `-term`, `"phrase"`, `regex:` and `|` as operators;
+    /// unset turns them on when the query contains any
+    advanced: Option<bool>,
+    /// Also return the results grouped into cross-site duplicates
+    group: Option<bool>,
+    /// Leave out results whose note has one of these statuses
+    hide_status: Option<Vec<NoteStatus>>,
+    /// "fast" or "thorough"; overrides no_cf, no_playwright and csrin_pages
+    profile: Option<SearchProfile>,
+}
+
+impl SearchArgs {
+    fn hidden_statuses(&self) -> &[NoteStatus] {
+        self.hide_status.as_deref().unwrap_or_default()
+    }
 }
 
-/// Progress event for streaming search updates
+/// Per-site status update of a streaming search
 #[derive(serde::Serialize, Clone)]
 struct SearchProgress {
+    job_id: u64,
     site: String,
-    status: String, // "started", "fetching", "parsing", "completed", "failed"
+    status: String, // "pending", "fetching", "completed"
     results_count: usize,
-    message: Option<String>,
 }
 
-/// Individual result event for streaming
+/// All results of one site, emitted as soon as it finishes
 #[derive(serde::Serialize, Clone)]
-struct StreamedResult {
+struct SiteResults {
+    job_id: u64,
     site: String,
-    result: models::SearchResult,
+    results: Vec<models::SearchResult>,
 }
 
-/// Final completion event
+/// Results of a site that finished after the search's deadline
 #[derive(serde::Serialize, Clone)]
-struct SearchComplete {
+struct LateResults {
+    job_id: u64,
+    site: String,
+    results: Vec<models::SearchResult>,
+    /// Sites still searching
+    pending: Vec<String>,
+}
+
+/// Final event of a streaming search
+#[derive(serde::Serialize, Clone)]
+struct SearchDone {
+    job_id: u64,
     total_results: usize,
     sites_completed: usize,
-    sites_failed: usize,
+    cancelled: bool,
+    /// Sites still searching when the deadline passed; their results follow
+    /// as "search://late_results" and are merged into the cache
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    late_sites: Vec<String>,
+    /// Sorted, de-duplicated and cut-off results; empty when cancelled
+    results: Vec<models::SearchResult>,
+    /// Which results each `|`-separated part matched; empty for single queries
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    segments: Vec<SegmentMatch>,
+    /// One entry per game across sites, when the search asked for `group`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    groups: Option<Vec<analyzer::DuplicateGroup>>,
+    /// Where the search's time went, per phase and site; late sites excluded
+    timings: TimingBreakdown,
 }
 
-#[tauri::command]
-async fn list_sites() -> Result<Vec<String>, String> {
-    let names: Vec<String> = config::site_configs()
+/// Results of `search_gui`
+#[derive(serde::Serialize, Debug)]
+struct SearchResponse {
+    results: Vec<models::SearchResult>,
+    /// Which results each `|`-separated part matched; empty for single queries
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    segments: Vec<SegmentMatch>,
+    /// One entry per game across sites, when the search asked for `group`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    groups: Option<Vec<analyzer::DuplicateGroup>>,
+    /// Where the search's time went, per phase and site
+    timings: TimingBreakdown,
+}
+
+/// One `|`-separated part of an advanced query and the results it matched
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+struct SegmentMatch {
+    /// Zero-based position of the part in the query
+    index: usize,
+    query: String,
+    /// Sites named with `site:` in this part; empty means all
+    sites: Vec<String>,
+    /// Positions in `results` of the results this part matched
+    results: Vec<usize>,
+}
+
+/// A site as shown in the GUI's site picker
+#[derive(serde::Serialize, Clone)]
+struct SiteInfo {
+    name: String,
+    base_url: String,
+    search_kind: models::SearchKind,
+    requires_js: bool,
+    requires_cloudflare: bool,
+    /// Checked by default in the site picker
+    enabled: bool,
+}
+
+/// Sites in the user's saved order (unlisted ones keep config order) with enabled state
+fn site_infos(sites: Vec<models::SiteConfig>, defaults: &SearchDefaults) -> Vec<SiteInfo> {
+    let mut infos: Vec<SiteInfo> = sites
         .into_iter()
-        .map(|s| s.name.to_string())
+        .map(|s| SiteInfo {
+            enabled: defaults.site_enabled(&s.name),
+            name: s.name,
+            base_url: s.base_url,
+            search_kind: s.search_kind,
+            requires_js: s.requires_js,
+            requires_cloudflare: s.requires_cloudflare,
+        })
         .collect();
-    Ok(names)
+    infos.sort_by_key(|s| defaults.site_position(&s.name));
+    infos
+}
+
+#[tauri::command]
+async fn list_sites() -> Result<Vec<SiteInfo>, ApiError> {
+    let defaults = Settings::load(&default_settings_path()).search;
+    Ok(site_infos(known_sites(), &defaults))
+}
+
+/// Save which sites are checked by default and their order in the site picker
+#[tauri::command]
+async fn set_site_prefs(enabled: Vec<String>, order: Vec<String>) -> Result<(), ApiError> {
+    let path = default_settings_path();
+    let mut settings = Settings::load(&path);
+    settings.search.sites = enabled;
+    settings.search.site_order = order;
+    save_settings(&settings, &path)
+}
+
+/// Write `config.toml`
+fn save_settings(settings: &Settings, path: &std::path::Path) -> Result<(), ApiError> {
+    settings
+        .save(path)
+        .map_err(|e| ApiError::storage(format!("failed to save settings: {e:#}")))
+}
+
+/// Parse a result URL, allowing only web and magnet links to be opened
+fn validate_open_url(url: &str) -> Result<reqwest::Url, ApiError> {
+    browser::checked_url(url).map_err(|e| ApiError::invalid_input(format!("{e:#}")))
+}
+
+/// Open a result in the default browser (or torrent client for magnets)
+#[tauri::command]
+async fn open_result(url: String) -> Result<(), ApiError> {
+    let url = validate_open_url(&url)?;
+    browser::open_url(url.as_str()).map_err(|e| ApiError::internal(format!("{e:#}")))
+}
+
+#[tauri::command]
+async fn copy_to_clipboard(app_handle: tauri::AppHandle, text: String) -> Result<(), ApiError> {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+
+    app_handle
+        .clipboard()
+        .write_text(text)
+        .map_err(|e| ApiError::internal(e.to_string()))
+}
+
+/// Fetch a result's page the way its site is searched (solver and saved cookie
+/// when needed) and return its description, metadata and download links
+#[tauri::command]
+async fn get_result_details(
+    state: tauri::State<'_, AppState>,
+    url: String,
+    site: String,
+) -> Result<ResultDetails, ApiError> {
+    let parsed = validate_open_url(&url)?;
+    if parsed.scheme() == "magnet" {
+        return Err(ApiError::invalid_input("magnet links have no detail page"));
+    }
+    let defaults = Settings::load(&default_settings_path()).search;
+    let html = fetch_page(&state.client, &defaults, &site, parsed.as_str()).await?;
+    Ok(details::extract_details(&html, parsed.as_str()))
+}
+
+/// Fetch one page of `site` with its saved cookie, through the solver when the
+/// site is behind Cloudflare
+async fn fetch_page(
+    client: &reqwest::Client,
+    defaults: &SearchDefaults,
+    site: &str,
+    url: &str,
+) -> Result<String, ApiError> {
+    let config = known_sites()
+        .into_iter()
+        .find(|s| s.name.eq_ignore_ascii_case(site));
+    let cookie_headers = config
+        .as_ref()
+        .and_then(|config| page_cookie_headers(defaults, config, url));
+    let needs_solver = config.is_some_and(|s| s.requires_cloudflare);
+    if needs_solver {
+        let cf_url = resolve_cf_url(None, defaults);
+        cf::fetch_via_solver_with_headers(client, url, &cf_url, cookie_headers).await
+    } else {
+        let client = Routes::new(&defaults.routes)
+            .client_for(site, client)
+            .map_err(|e| ApiError::invalid_input(e.to_string()))?;
+        fetcher::fetch_with_retry_headers(&client, url, cookie_headers, None, Some(site)).await
+    }
+    .map_err(|e| ApiError::from_fetch(&e).for_site(site))
+}
+
+/// The saved cookie of `site` for fetching `url`, only when `url` is on the
+/// site's host or one of its subdomains: a result linking elsewhere must not
+/// receive the site's session
+fn page_cookie_headers(
+    defaults: &SearchDefaults,
+    site: &models::SiteConfig,
+    url: &str,
+) -> Option<ReqHeaderMap> {
+    let host = |url: &str| {
+        reqwest::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+    };
+    let on_site = matches!(
+        (host(&site.base_url), host(url)),
+        (Some(base), Some(host)) if cookies::domain_matches(&base, &host)
+    );
+    if !on_site {
+        log::debug!("Not sending {}'s cookie to {}", site.name, url);
+        return None;
+    }
+    defaults
+        .cookie_for(&site.name)
+        .and_then(|cookie| saved_cookie(&site.name, cookie))
+        .and_then(|cookie| cookie_header(&cookie))
+}
+
+/// A saved site cookie, read from the secret store when the config refers to it
+fn saved_cookie(site: &str, cookie: &str) -> Option<String> {
+    secrets::resolve(cookie)
+        .map_err(|e| log::warn!("Saved cookie for {} not used: {:#}", site, e))
+        .ok()
+}
+
+/// Write results to `path`, or to a file picked in a native save dialog when no
+/// path is given. Returns the written path, or `None` if the dialog was cancelled.
+#[tauri::command]
+async fn export_results(
+    app_handle: tauri::AppHandle,
+    format: ExportFormat,
+    path: Option<String>,
+    results: Vec<models::SearchResult>,
+) -> Result<Option<String>, ApiError> {
+    let path = match path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => std::path::PathBuf::from(path),
+        None => match pick_export_path(&app_handle, format).await {
+            Some(path) => path,
+            None => return Ok(None),
+        },
+    };
+    tokio::fs::write(&path, output::render_results(&results, format))
+        .await
+        .map_err(|e| ApiError::storage(format!("failed to write {}: {}", path.display(), e)))?;
+    Ok(Some(path.display().to_string()))
+}
+
+async fn pick_export_path(
+    app_handle: &tauri::AppHandle,
+    format: ExportFormat,
+) -> Option<std::path::PathBuf> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let ext = format.extension();
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app_handle
+        .dialog()
+        .file()
+        .add_filter(ext.to_uppercase(), &[ext])
+        .set_file_name(format!("search-results.{ext}"))
+        .save_file(move |file| {
+            let _ = tx.send(file);
+        });
+    rx.await.ok().flatten()?.into_path().ok()
+}
+
+/// Search history file
+fn get_history_path() -> std::path::PathBuf {
+    paths::history_file()
+}
+
+/// Record a finished search in the history; failures are only logged
+fn record_history(query: &str, sites: &[models::SiteConfig], results: &[models::SearchResult]) {
+    let names = if sites.len() == known_sites().len() {
+        Vec::new()
+    } else {
+        sites.iter().map(|s| s.name.clone()).collect()
+    };
+    let path = get_history_path();
+    let mut history = SearchHistory::load(&path);
+    history.record(query, names, results);
+    if let Err(e) = history.save(&path) {
+        log::warn!("Failed to save search history: {e}");
+    }
+}
+
+/// Send a finished search to the `[[sinks]]` in `config.toml` in the
+/// background; sinks that fail are only logged
+fn send_to_sinks(client: &reqwest::Client, query: &str, results: &[models::SearchResult]) {
+    let configs = Settings::load(&default_settings_path()).sinks;
+    if configs.is_empty() {
+        return;
+    }
+    let sinks: Vec<_> = configs.iter().map(|c| c.build(client)).collect();
+    let query = query.to_string();
+    let results = results.to_vec();
+    tauri::async_runtime::spawn(async move {
+        let output = sinks::SearchOutput {
+            query: &query,
+            results: &results,
+        };
+        for (sink, e) in sinks::send_all(&sinks, output).await {
+            log::warn!("Failed to send results to {sink}: {e:#}");
+        }
+    });
+}
+
+/// One page of past searches, newest first (`page` is zero-based)
+#[tauri::command]
+async fn get_history(
+    page: Option<usize>,
+    page_size: Option<usize>,
+) -> Result<HistoryPage, ApiError> {
+    Ok(SearchHistory::load(&get_history_path()).page(page.unwrap_or(0), page_size.unwrap_or(50)))
+}
+
+#[tauri::command]
+async fn delete_history_entry(id: u64) -> Result<bool, ApiError> {
+    let path = get_history_path();
+    let mut history = SearchHistory::load(&path);
+    let removed = history.remove(id);
+    if removed {
+        history
+            .save(&path)
+            .map_err(|e| ApiError::storage(format!("failed to save search history: {e:#}")))?;
+    }
+    Ok(removed)
+}
+
+#[tauri::command]
+async fn clear_history() -> Result<(), ApiError> {
+    let path = get_history_path();
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| {
+            ApiError::storage(format!("failed to remove {}: {}", path.display(), e))
+        })?;
+    }
+    Ok(())
 }
 
 /// Cache entry for serialization to frontend
@@ -78,15 +445,8 @@ struct CacheEntryResponse {
 
 /// Get all cached searches
 #[tauri::command]
-async fn get_cache() -> Result<Vec<CacheEntryResponse>, String> {
-    let path = get_cache_path();
-    if !path.exists() {
-        return Ok(vec![]);
-    }
-    let cache = SearchCache::load_from_file(&path)
-        .await
-        .map_err(|e| e.to_string())?;
-
+async fn get_cache(state: tauri::State<'_, AppState>) -> Result<Vec<CacheEntryResponse>, ApiError> {
+    let cache = state.cache().await;
     let entries: Vec<CacheEntryResponse> = cache
         .entries()
         .iter()
@@ -102,701 +462,1590 @@ async fn get_cache() -> Result<Vec<CacheEntryResponse>, String> {
 
 /// Get cached results for a specific query
 #[tauri::command]
-async fn get_cached_results(query: String) -> Result<Option<Vec<models::SearchResult>>, String> {
-    let path = get_cache_path();
-    if !path.exists() {
-        return Ok(None);
-    }
-    let cache = SearchCache::load_from_file(&path)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if let Some(entry) = cache.get(&query) {
-        Ok(Some(entry.results.clone()))
-    } else {
-        Ok(None)
-    }
+async fn get_cached_results(
+    state: tauri::State<'_, AppState>,
+    query: String,
+) -> Result<Option<Vec<models::SearchResult>>, ApiError> {
+    let cache = state.cache().await;
+    Ok(cache.get(&query).map(|entry| entry.results.clone()))
 }
 
 /// Add search results to cache
 #[tauri::command]
-async fn add_to_cache(query: String, results: Vec<models::SearchResult>) -> Result<(), String> {
-    let path = get_cache_path();
-    let mut cache = if path.exists() {
-        SearchCache::load_from_file(&path)
-            .await
-            .unwrap_or_else(|_| SearchCache::with_default_size())
-    } else {
-        SearchCache::with_default_size()
-    };
-
+async fn add_to_cache(
+    state: tauri::State<'_, AppState>,
+    query: String,
+    results: Vec<models::SearchResult>,
+) -> Result<(), ApiError> {
+    let mut cache = state.cache().await;
     cache.add(query, results);
-    cache.save_to_file(&path).await.map_err(|e| e.to_string())?;
-    Ok(())
+    state.save_cache(&cache).await
 }
 
 /// Remove a specific cache entry by query
 #[tauri::command]
-async fn remove_cache_entry(query: String) -> Result<bool, String> {
-    let path = get_cache_path();
-    if !path.exists() {
-        return Ok(false);
-    }
-    let mut cache = SearchCache::load_from_file(&path)
-        .await
-        .map_err(|e| e.to_string())?;
-
+async fn remove_cache_entry(
+    state: tauri::State<'_, AppState>,
+    query: String,
+) -> Result<bool, ApiError> {
+    let mut cache = state.cache().await;
     let removed = cache.remove(&query);
-    cache.save_to_file(&path).await.map_err(|e| e.to_string())?;
+    if removed {
+        state.save_cache(&cache).await?;
+    }
     Ok(removed)
 }
 
-/// Clear all cache entries
+/// Clear all cache entries, the pages the solver or Playwright rendered and
+/// the saved result images
 #[tauri::command]
-async fn clear_cache() -> Result<(), String> {
-    let path = get_cache_path();
-    if path.exists() {
-        std::fs::remove_file(&path).map_err(|e| e.to_string())?;
-    }
-    Ok(())
+async fn clear_cache(state: tauri::State<'_, AppState>) -> Result<(), ApiError> {
+    let mut cache = state.cache().await;
+    cache.clear();
+    render_cache::RenderCache::new(paths::rendered_dir()).clear();
+    thumbnails::clear();
+    state.save_cache(&cache).await
 }
 
 /// Get cache settings (size)
 #[tauri::command]
-async fn get_cache_settings() -> Result<usize, String> {
-    let path = get_cache_path();
-    if path.exists() {
-        let cache = SearchCache::load_from_file(&path)
-            .await
-            .unwrap_or_else(|_| SearchCache::with_default_size());
-        Ok(cache.max_size())
-    } else {
-        Ok(MIN_CACHE_SIZE)
-    }
+async fn get_cache_settings(state: tauri::State<'_, AppState>) -> Result<usize, ApiError> {
+    Ok(state.cache().await.max_size())
 }
 
 /// Set cache size
 #[tauri::command]
-async fn set_cache_size(size: usize) -> Result<(), String> {
-    let path = get_cache_path();
-    let mut cache = if path.exists() {
-        SearchCache::load_from_file(&path)
+async fn set_cache_size(state: tauri::State<'_, AppState>, size: usize) -> Result<(), ApiError> {
+    let mut cache = state.cache().await;
+    cache.set_max_size(size);
+    state.save_cache(&cache).await
+}
+
+/// Settings shared by every per-site fetch of one search
+struct SiteSearch {
+    client: reqwest::Client,
+    query: String,
+    limit: usize,
+    use_cf: bool,
+    cf_url: String,
+    /// Cookie typed into the search form, sent to every site
+    cookie: Option<String>,
+    /// Saved cookies by site name, used when the form has none
+    site_cookies: BTreeMap<String, String>,
+    /// Accounts from `[accounts]` in config.toml, signed in to when their
+    /// site is first fetched
+    accounts: Accounts,
+    /// Saved proxy routes, for sites fetched through Tor or another proxy
+    routes: Routes,
+    csrin_pages: usize,
+    csrin_search: bool,
+    no_playwright: bool,
+    profile: SearchProfile,
+    hide_nsfw: bool,
+    /// Search a site that failed with a network or server error once more
+    retry_failed: bool,
+    concurrency: usize,
+    rate_limiter: Option<Arc<tokio::sync::Mutex<RateLimiter>>>,
+    breakers: Arc<Breakers>,
+    /// Parsed operators when the query is read as an advanced query
+    multi_query: Option<MultiQuery>,
+    /// Where each site's results stopped, for `search_more`
+    cursors: std::sync::Mutex<HashMap<String, pages::PageCursor>>,
+    /// Pages fetched so far, so one reached by several URLs is fetched once
+    pages: PageStore,
+    /// Link patterns learned for sites whose result selector stopped matching
+    learned: std::sync::Mutex<LearnedPatterns>,
+    /// Whether each site's search honors the query
+    capabilities: std::sync::Mutex<QueryCapabilities>,
+    /// Today's requests per site and their budgets
+    budget: DailyBudget,
+    /// Where the search's time went
+    timings: Arc<SearchTimings>,
+}
+
+impl SiteSearch {
+    /// Combine the search form with the saved defaults; the form wins.
+    /// The client, rate limiter and breakers are the app-wide ones from `state`.
+    fn new(args: &SearchArgs, defaults: &SearchDefaults, state: &AppState) -> Self {
+        let cf_url = resolve_cf_url(args.cf_url.clone(), defaults);
+        let rate_limiter = if !args.no_rate_limit.unwrap_or(false) {
+            Some(state.rate_limiter.clone())
+        } else {
+            None
+        };
+
+        let query = query::normalize_query(&args.query);
+        let profile = args.profile.unwrap_or_default();
+        if args.debug.unwrap_or(false) || args.verbose.unwrap_or(false) {
+            log::info!("Searching {:?} (solver: {})", query, cf_url);
+        }
+
+        Self {
+            client: state.client.clone(),
+            query,
+            limit: args.limit.unwrap_or(defaults.limit),
+            use_cf: profile.use_solver(!args.no_cf.unwrap_or(false)),
+            cf_url,
+            cookie: args.cookie.clone().filter(|c| !c.trim().is_empty()),
+            site_cookies: defaults.cookies.clone(),
+            accounts: Accounts::new(
+                Settings::load(&default_settings_path()).accounts,
+                SecretStore::default(),
+            ),
+            routes: Routes::new(&defaults.routes),
+            csrin_pages: args.csrin_pages.unwrap_or(1).max(profile.pages()),
+            csrin_search: args.csrin_search.unwrap_or(false),
+            no_playwright: !profile.use_playwright(!args.no_playwright.unwrap_or(false)),
+            profile,
+            hide_nsfw: defaults.hide_nsfw,
+            retry_failed: defaults.retry_failed,
+            concurrency: defaults.concurrency.max(1),
+            rate_limiter,
+            breakers: state.breakers.clone(),
+            multi_query: parse_multi_query(args),
+            cursors: Default::default(),
+            pages: PageStore::default(),
+            learned: std::sync::Mutex::new(LearnedPatterns::load(&paths::learned_patterns_file())),
+            capabilities: std::sync::Mutex::new(QueryCapabilities::load(
+                &paths::query_capability_file(),
+            )),
+            budget: DailyBudget::new(
+                defaults.daily_budget.clone(),
+                RequestLog::load(&paths::request_budget_file()),
+            ),
+            timings: Arc::new(SearchTimings::new()),
+        }
+    }
+
+    /// Add the time since `since` to `site`'s time in `phase`
+    fn time(&self, site: &str, phase: SearchPhase, since: std::time::Instant) {
+        self.timings.record_since(site, phase, since);
+    }
+
+    /// Parse a page of `site`, trying its learned link pattern before scanning
+    /// every link and remembering the pattern the results came from
+    async fn parse(
+        &self,
+        site: &models::SiteConfig,
+        html: String,
+        query: &str,
+    ) -> Vec<models::SearchResult> {
+        let hint = {
+            let learned = self.learned.lock().unwrap_or_else(|e| e.into_inner());
+            learned.get(&site.name).cloned()
+        };
+        let parsing = std::time::Instant::now();
+        let parsed = parser::parse_results_blocking_with(site, html, query, hint).await;
+        self.time(&site.name, SearchPhase::Parse, parsing);
+        if let Some(pattern) = parsed.learned {
+            let mut learned = self.learned.lock().unwrap_or_else(|e| e.into_inner());
+            learned.learn(&site.name, pattern);
+        }
+        parsed.results
+    }
+
+    /// `site` as it is searched: a listing site if its search ignores the query
+    fn searched_as(&self, site: &models::SiteConfig) -> models::SiteConfig {
+        let mut site = site.clone();
+        let capabilities = self.capabilities.lock().unwrap_or_else(|e| e.into_inner());
+        if capabilities.downgrade(&mut site) {
+            log::info!(
+                "{}: search ignores the query, filtering its listing page",
+                site.name
+            );
+        }
+        site
+    }
+
+    /// Probe `site` after its search page had nothing for the query, unless it
+    /// was probed recently: when the page still had result links, search for
+    /// each probe query and record whether the same links come back every
+    /// time. Pages from a stand-in solver aren't probed.
+    async fn probe_query_support(&self, site: &models::SiteConfig, html: &str) {
+        if self.uses_solver(site) && !capability::trusted_solver(&self.cf_url) {
+            return;
+        }
+        let lock = || self.capabilities.lock().unwrap_or_else(|e| e.into_inner());
+        if !lock().needs_probe(site) {
+            return;
+        }
+        let searched = capability::result_links(site, html);
+        if searched.is_empty() {
+            return;
+        }
+        let mut support = QuerySupport::Ignored;
+        for url in capability::probe_urls(site) {
+            // No verdict without every probe's page
+            let Ok(probe) = self.fetch(site, &url).await else {
+                return;
+            };
+            support = capability::judge(&searched, &capability::result_links(site, &probe.body));
+            if support == QuerySupport::Honored {
+                break;
+            }
+        }
+        if support == QuerySupport::Ignored {
+            log::warn!(
+                "{} answers every search with the same page; filtering its listing page for a week",
+                site.name
+            );
+        }
+        lock().record(&site.name, support);
+    }
+
+    /// Save the learned link patterns, query support and request counts for
+    /// later searches
+    fn save_learned(&self) {
+        let learned = self.learned.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = learned.save(&paths::learned_patterns_file()) {
+            log::warn!("Failed to save learned patterns: {e}");
+        }
+        let capabilities = self.capabilities.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = capabilities.save(&paths::query_capability_file()) {
+            log::warn!("Failed to save query capabilities: {e}");
+        }
+        if let Err(e) = self.budget.save(&paths::request_budget_file()) {
+            log::warn!("Failed to save request counts: {e}");
+        }
+    }
+
+    /// Searches run on `site` (see [`site_queries`])
+    fn queries_for(&self, site: &str) -> Vec<String> {
+        site_queries(self.multi_query.as_ref(), site, &self.query)
+    }
+
+    /// The first search run on `site`, the one `search_more` pages through
+    fn query_for(&self, site: &str) -> String {
+        self.queries_for(site).swap_remove(0)
+    }
+
+    /// See [`searches_site`]
+    fn searches_site(&self, site: &str) -> bool {
+        searches_site(self.multi_query.as_ref(), site)
+    }
+
+    /// Cookie header for `site`: the form's cookie, else the session of the
+    /// site's account, else the saved cookie
+    async fn cookie_headers_for(&self, site: &models::SiteConfig) -> Option<ReqHeaderMap> {
+        if let Some(cookie) = &self.cookie {
+            return cookie_header(cookie);
+        }
+        if let Some(session) = self.accounts.cookie_for(site).await {
+            return cookie_header(&session);
+        }
+        let (_, saved) = self
+            .site_cookies
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(&site.name))?;
+        cookie_header(&saved_cookie(&site.name, saved)?)
+    }
+
+    /// Whether pages of `site` are fetched through the solver
+    fn uses_solver(&self, site: &models::SiteConfig) -> bool {
+        let cf_url = &self.cf_url;
+        let cf_local = cf_url.contains("127.0.0.1") || cf_url.contains("localhost");
+        let non_default_cf = cf_url != DEFAULT_CF_URL;
+        let allow_env = std::env::var("ALLOW_CSRIN_SOLVER")
+            .ok()
+            .map(|v| v == "1")
+            .unwrap_or(false);
+        let csrin_solver_allowed =
+            site.name.eq_ignore_ascii_case("csrin") && (allow_env || cf_local || non_default_cf);
+        // The solver only fetches pages; forms and GraphQL queries are posted directly
+        let posts = matches!(
+            site.search_kind,
+            models::SearchKind::PostForm | models::SearchKind::GraphQL
+        );
+        self.use_cf && !posts && (site.requires_cloudflare || csrin_solver_allowed)
+    }
+
+    /// Fetch one page of `site`, through the solver when the site needs it
+    /// Fetch `url` of `site`, unless this search already fetched it or a URL
+    /// that redirected to it
+    async fn fetch(&self, site: &models::SiteConfig, url: &str) -> anyhow::Result<Fetched> {
+        self.pages
+            .get_or_fetch(url, self.fetch_live(site, url))
             .await
-            .unwrap_or_else(|_| SearchCache::with_default_size())
-    } else {
-        SearchCache::with_default_size()
-    };
+    }
 
-    cache.set_max_size(size);
-    cache.save_to_file(&path).await.map_err(|e| e.to_string())?;
-    Ok(())
+    async fn fetch_live(&self, site: &models::SiteConfig, url: &str) -> anyhow::Result<Fetched> {
+        self.budget.spend(&site.name)?;
+        let client = &self.client;
+        let cf_url = &self.cf_url;
+        let cookie_headers = self.cookie_headers_for(site).await;
+        if self.uses_solver(site) {
+            // Direct fetches record their own metrics; solver ones are timed here
+            let started = std::time::Instant::now();
+            let fetched = if cookie_headers.is_some() {
+                cf::fetch_via_solver_with_headers(client, url, cf_url, cookie_headers).await
+            } else {
+                cf::fetch_via_solver(client, url, cf_url).await
+            };
+            monitoring::get_metrics()
+                .record_request(&site.name, started.elapsed(), fetched.is_ok())
+                .await;
+            self.time(&site.name, SearchPhase::Solver, started);
+            // The solver follows redirects without reporting where they led
+            fetched.map(|body| Fetched {
+                url: url.to_string(),
+                body,
+            })
+        } else {
+            // Routed sites fetch directly through their proxy, not through the solver
+            let client = &self.routes.client_for(&site.name, client)?;
+            let mut limiter = match &self.rate_limiter {
+                Some(rl) => Some(rl.lock().await),
+                None => None,
+            };
+            let rate_limiter_ref = limiter.as_deref_mut();
+            let started = std::time::Instant::now();
+            let fetched =
+                fetcher::fetch_site_page(client, site, url, cookie_headers, rate_limiter_ref).await;
+            self.time(&site.name, SearchPhase::Fetch, started);
+            fetched
+        }
+    }
+
+    /// Remember that page `page` of `site` was fetched from `url`
+    fn note_page(
+        &self,
+        site: &models::SiteConfig,
+        query: &str,
+        page: usize,
+        url: &str,
+        html: &str,
+    ) {
+        // Later segments' searches would page through a different query
+        if query != self.query_for(&site.name) {
+            return;
+        }
+        let next = pagination::next_page(site, query, page, url, html);
+        let mut cursors = self.cursors.lock().unwrap_or_else(|e| e.into_inner());
+        let cursor = cursors.entry(site.name.clone()).or_default();
+        cursor.page = page;
+        cursor.next = next;
+    }
+
+    /// Keep results past the per-site limit for `search_more`
+    fn keep_leftover(&self, site: &str, leftover: Vec<models::SearchResult>) {
+        let mut cursors = self.cursors.lock().unwrap_or_else(|e| e.into_inner());
+        cursors.entry(site.to_string()).or_default().leftover = leftover;
+    }
+
+    /// Where `site`'s results stopped; a site whose pages were not seen
+    /// continues from page 2
+    fn take_cursor(&self, site: &models::SiteConfig) -> pages::PageCursor {
+        let mut cursors = self.cursors.lock().unwrap_or_else(|e| e.into_inner());
+        cursors
+            .remove(&site.name)
+            .unwrap_or_else(|| pages::PageCursor {
+                page: 1,
+                next: pagination::page_url(site, &self.query_for(&site.name), 2),
+                ..Default::default()
+            })
+    }
+
+    /// Drop results the user would not want: those the site's rules reject, csrin
+    /// stickies and, if asked, NSFW titles
+    fn retain_wanted(
+        &self,
+        site: &models::SiteConfig,
+        query: &str,
+        results: &mut Vec<models::SearchResult>,
+    ) {
+        site.rules.apply(results);
+        // Keep csrin topics whose title matches the query. This removes sticky
+        // posts like "FAQ", "Forum rules", "Donations", etc.
+        if site.name.eq_ignore_ascii_case("csrin") {
+            let q_lower = query.to_lowercase();
+            let mut tl = String::new();
+            results.retain(|r| {
+                parser::lowercase_into(&mut tl, &r.title);
+                tl.contains(&q_lower)
+            });
+        }
+        if self.hide_nsfw {
+            results.retain(|r| !analyzer::looks_nsfw(&r.title));
+        }
+    }
+}
+
+/// The query as an advanced query, if the form asks for it or it has operators
+fn parse_multi_query(args: &SearchArgs) -> Option<MultiQuery> {
+    let multi = MultiQuery::parse(&args.query);
+    let detected = !multi.is_single() || multi.first().is_some_and(|q| q.has_operators());
+    args.advanced.unwrap_or(detected).then_some(multi)
+}
+
+/// Searches run on `site`: one for each of its segments of an advanced query
+/// (see [`MultiQuery::site_searches`]), else `query` (the normalized query)
+fn site_queries(multi_query: Option<&MultiQuery>, site: &str, query: &str) -> Vec<String> {
+    match multi_query {
+        Some(m) => m
+            .site_searches(site, query)
+            .iter()
+            .map(|(_, terms)| query::normalize_query(terms))
+            .collect(),
+        None => vec![query.to_string()],
+    }
+}
+
+/// False when every segment of an advanced query is restricted to other sites
+fn searches_site(multi_query: Option<&MultiQuery>, site: &str) -> bool {
+    multi_query.is_none_or(|m| !m.segments_for_site(site).is_empty())
+}
+
+/// One `|`-separated part of a query, operator by operator
+#[derive(serde::Serialize, Clone, Debug, PartialEq, Eq)]
+struct QuerySegment {
+    query: String,
+    terms: Vec<String>,
+    phrases: Vec<String>,
+    exclude: Vec<String>,
+    regex: Vec<String>,
+    /// Sites named with `site:`; empty means all
+    sites: Vec<String>,
+}
+
+/// What a site is searched for: one search per segment that applies to it,
+/// none when `site:` leaves it out
+#[derive(serde::Serialize, Clone, Debug, PartialEq, Eq)]
+struct SiteQuery {
+    site: String,
+    terms: Vec<String>,
+}
+
+/// How a search would read a query, for the search form to show as it is typed
+#[derive(serde::Serialize, Clone, Debug, PartialEq, Eq)]
+struct QueryBreakdown {
+    /// Whether operators apply; `false` searches the text as typed
+    advanced: bool,
+    segments: Vec<QuerySegment>,
+    sites: Vec<SiteQuery>,
+}
+
+/// The breakdown of `args.query` over `sites`
+fn query_breakdown(args: &SearchArgs, sites: &[String]) -> QueryBreakdown {
+    let multi_query = parse_multi_query(args);
+    let query = query::normalize_query(&args.query);
+    let segments = multi_query
+        .iter()
+        .flat_map(|m| &m.segments)
+        .map(|seg| QuerySegment {
+            query: seg.raw().trim().to_string(),
+            terms: seg.terms.clone(),
+            phrases: seg.exact_phrases.clone(),
+            exclude: seg.exclude_terms.clone(),
+            regex: seg.regex_patterns.iter().map(|r| r.to_string()).collect(),
+            sites: seg.site_restrictions.clone(),
+        })
+        .collect();
+    let sites = sites
+        .iter()
+        .map(|site| SiteQuery {
+            site: site.clone(),
+            terms: if searches_site(multi_query.as_ref(), site) {
+                site_queries(multi_query.as_ref(), site, &query)
+            } else {
+                Vec::new()
+            },
+        })
+        .collect();
+    QueryBreakdown {
+        advanced: multi_query.is_some(),
+        segments,
+        sites,
+    }
 }
 
+/// Break a query down into its operators and what each site would be
+/// searched for; `args.sites` defaults to every site
 #[tauri::command]
-async fn search_gui(args: SearchArgs) -> Result<Vec<models::SearchResult>, String> {
-    if args.query.trim().is_empty() {
-        return Err("empty search phrase".to_string());
-    }
-    let limit = args.limit.unwrap_or(10);
-    let _debug = args.debug.unwrap_or(false);
-    let use_cf = !args.no_cf.unwrap_or(false);
-    let mut cf_url = args
-        .cf_url
-        .unwrap_or_else(|| "http://localhost:8191/v1".to_string());
-    if cf_url == "http://localhost:8191/v1"
+async fn parse_query(args: SearchArgs) -> Result<QueryBreakdown, ApiError> {
+    let sites = match &args.sites {
+        Some(sites) => sites.clone(),
+        None => known_sites().into_iter().map(|s| s.name).collect(),
+    };
+    Ok(query_breakdown(&args, &sites))
+}
+
+/// Solver URL from the form, else the saved one, else `CF_URL` or the local default
+fn resolve_cf_url(form: Option<String>, defaults: &SearchDefaults) -> String {
+    let cf_url = form
+        .or_else(|| defaults.cf_url.clone())
+        .unwrap_or_else(|| DEFAULT_CF_URL.to_string());
+    if cf_url == DEFAULT_CF_URL
         && let Ok(env_cf) = std::env::var("CF_URL")
         && !env_cf.trim().is_empty()
     {
-        cf_url = env_cf;
+        return env_cf;
     }
+    cf_url
+}
 
-    let normalized = query::normalize_query(&args.query);
-    let all_sites = config::site_configs();
-    let selected_sites: Vec<models::SiteConfig> = if let Some(names) = args.sites {
-        let wanted: Vec<String> = names
-            .into_iter()
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect();
-        all_sites
-            .into_iter()
-            .filter(|s| wanted.iter().any(|w| w.eq_ignore_ascii_case(&s.name)))
-            .collect()
-    } else {
-        all_sites
+fn cookie_header(cookie: &str) -> Option<ReqHeaderMap> {
+    let value = HeaderValue::from_str(cookie).ok()?;
+    let mut h = ReqHeaderMap::new();
+    h.insert(COOKIE, value);
+    Some(h)
+}
+
+/// The built-in sites and those the user added in `sites.toml` (see
+/// [`config::active_site_configs`]); the built-in ones alone, with a warning,
+/// if the user's file doesn't load
+pub(crate) fn known_sites() -> Vec<models::SiteConfig> {
+    config::active_site_configs().unwrap_or_else(|e| {
+        log::warn!("{e:#}");
+        config::site_configs()
+    })
+}
+
+/// Sites picked in the form, else the saved default sites, else every site
+fn selected_sites(args: &SearchArgs, defaults: &SearchDefaults) -> Vec<models::SiteConfig> {
+    let all_sites = known_sites();
+    let names = match &args.sites {
+        Some(names) => names,
+        None if !defaults.sites.is_empty() => &defaults.sites,
+        None => return all_sites,
     };
+    let wanted: Vec<&str> = names
+        .iter()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect();
+    all_sites
+        .into_iter()
+        .filter(|s| wanted.iter().any(|w| w.eq_ignore_ascii_case(&s.name)))
+        .collect()
+}
 
-    let client = fetcher::build_http_client();
-    let semaphore = Arc::new(Semaphore::new(3));
-    let rate_limiter = if !args.no_rate_limit.unwrap_or(false) {
-        Some(Arc::new(tokio::sync::Mutex::new(RateLimiter::new())))
-    } else {
-        None
+/// The configured site named `name` (case-insensitive)
+fn find_site(name: &str) -> Result<models::SiteConfig, ApiError> {
+    known_sites()
+        .into_iter()
+        .find(|s| s.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| ApiError::invalid_input(format!("unknown site: {name}")))
+}
+
+/// First page fetched when searching `site`
+fn search_page_url(site: &models::SiteConfig, query: &str) -> String {
+    match site.search_kind {
+        models::SearchKind::ListingPage => {
+            site.listing_path.clone().unwrap_or(site.base_url.clone())
+        }
+        _ => query::build_search_url(site, query),
+    }
+}
+
+/// Fetch and parse one site, including the csrin and gog-games fallbacks
+async fn search_site(ctx: &SiteSearch, site: &models::SiteConfig) -> Vec<models::SearchResult> {
+    if !ctx.searches_site(&site.name) {
+        return Vec::new();
+    }
+    let queries = &ctx.queries_for(&site.name);
+    let site = &ctx.searched_as(site);
+    if ctx.breakers.get(&site.name).check().is_err() {
+        log::info!("Skipping {}: too many recent failures", site.name);
+        return Vec::new();
+    }
+    if let Some(limit) = ctx.budget.spent(&site.name) {
+        log::warn!(
+            "Skipping {}: its daily budget of {limit} requests is spent until midnight UTC",
+            site.name
+        );
+        return Vec::new();
+    }
+    // A site whose route is broken is skipped rather than fetched without its proxy
+    let routed = match ctx.routes.client_for(&site.name, &ctx.client) {
+        Ok(routed) => routed,
+        Err(e) => {
+            log::warn!("Skipping {}: {e:#}", site.name);
+            return Vec::new();
+        }
     };
+    let route = ctx.routes.route_for(&site.name);
+    if route != Route::Direct {
+        log::info!("Fetching {} via {}", site.name, route);
+    }
 
-    // Optional Cookie header
-    let cookie_headers: Option<ReqHeaderMap> = if let Some(c) = args.cookie.as_deref() {
-        match HeaderValue::from_str(c) {
-            Ok(v) => {
-                let mut h = ReqHeaderMap::new();
-                h.insert(COOKIE, v);
-                Some(h)
+    let routed = &routed;
+    // Each segment that applies to the site is searched on its own, like the
+    // CLI does: "a | b" finds a and b, never "a b"
+    let searched = || async move {
+        let mut found = Vec::with_capacity(queries.len());
+        let mut report = FallbackReport::new(&site.name);
+        for query in queries {
+            let (results, tried) = if site.name.eq_ignore_ascii_case("csrin") {
+                race_csrin_feed(ctx, site, query, routed).await
+            } else {
+                fetch_site_results(ctx, site, query, routed).await
+            };
+            found.push(results);
+            report.attempts.extend(tried.attempts);
+        }
+        (found, report)
+    };
+    let mut retrying = false;
+    let found = loop {
+        // Dropping the future on timeout aborts the site, fallbacks included
+        let (found, report) = match ctx.profile.site_budget(None) {
+            Some(budget) => match tokio::time::timeout(budget, searched()).await {
+                Ok(searched) => searched,
+                Err(_) => {
+                    log::warn!("{} timed out after {}s", site.name, budget.as_secs());
+                    return Vec::new();
+                }
+            },
+            None => searched().await,
+        };
+        if found.iter().all(Vec::is_empty) && report.exhausted() {
+            log::warn!("No results from any strategy for {report}");
+            // Unlike the CLI, which waits for the other sites, the GUI retries
+            // the site in its own slot so its progress stays one event per site
+            if ctx.retry_failed && !retrying && report.transient() {
+                log::info!(
+                    "Searching {} once more after network or server errors",
+                    site.name
+                );
+                retrying = true;
+                tokio::time::sleep(RETRY_DELAY).await;
+                continue;
             }
-            Err(_) => None,
         }
+        break found;
+    };
+    let mut results = Vec::new();
+    let mut leftover = Vec::new();
+    for (query, mut found) in queries.iter().zip(found) {
+        // Scraped titles are untrusted: no markup or bidi tricks in the UI
+        sanitize::sanitize_results(&mut found);
+        ctx.retain_wanted(site, query, &mut found);
+        // Truncate per-site, for each search
+        if found.len() > ctx.limit {
+            leftover.extend(found.split_off(ctx.limit));
+        }
+        results.append(&mut found);
+    }
+    if !leftover.is_empty() {
+        ctx.keep_leftover(&site.name, leftover);
+    }
+    results
+}
+
+/// csrin's search (Playwright, then its search pages) raced against its Atom
+/// feed (see [`feed::race_feed`])
+async fn race_csrin_feed(
+    ctx: &SiteSearch,
+    site: &models::SiteConfig,
+    query: &str,
+    routed: &reqwest::Client,
+) -> (Vec<models::SearchResult>, FallbackReport) {
+    let mut searched = false;
+    let search = async {
+        let found = fetch_site_results(ctx, site, query, routed).await;
+        searched = true;
+        found
+    };
+    let fed = async {
+        let started = std::time::Instant::now();
+        let fed =
+            feed::fetch_forum_feed(routed, site, query, ctx.rate_limiter.as_deref(), false).await;
+        ctx.time(&site.name, SearchPhase::Fetch, started);
+        fed
+    };
+    let raced = feed::race_feed(site, query, search, fed).await;
+    if !searched {
+        log::info!("{}: feed answered before the search", site.name);
+    }
+    raced
+}
+
+/// Run `site`'s fetch strategies for `query`: Playwright or the sitemap, the
+/// search pages and the gog-games and csrin fallbacks
+async fn fetch_site_results(
+    ctx: &SiteSearch,
+    site: &models::SiteConfig,
+    query: &str,
+    routed: &reqwest::Client,
+) -> (Vec<models::SearchResult>, FallbackReport) {
+    let client = &ctx.client;
+    let cf_url = &ctx.cf_url;
+    let use_cf = ctx.use_cf;
+    let cookie_headers = &ctx.cookie_headers_for(site).await;
+    let rate_limiter = &ctx.rate_limiter;
+    let breaker = ctx.breakers.get(&site.name);
+    let base_url = search_page_url(site, query);
+    let mut page_urls: VecDeque<String> = if site.name.eq_ignore_ascii_case("csrin") {
+        let mut urls = Vec::new();
+        if ctx.csrin_search {
+            // Topic search over all posts, not just first posts
+            let search_base = format!("{}search.php", site.base_url);
+            urls.push(query::with_query(
+                &search_base,
+                &[("keywords", query), ("sr", "topics"), ("fid[]", "10")],
+            ));
+        } else {
+            let pages = ctx.csrin_pages.max(1);
+            urls.push(base_url.clone());
+            for i in 1..pages {
+                let start = (i * 100).to_string();
+                urls.push(query::with_query(&base_url, &[("start", &start)]));
+            }
+        }
+        urls.into()
     } else {
-        None
+        VecDeque::from([base_url.clone()])
     };
 
-    let mut tasks = FuturesUnordered::new();
-    for site in selected_sites {
-        let permit = semaphore
-            .clone()
-            .acquire_owned()
-            .await
-            .map_err(|e| e.to_string())?;
-        let client = client.clone();
-        let query = normalized.clone();
-        let cf_url = cf_url.clone();
-        let cookie_headers = cookie_headers.clone();
-        let csrin_pages = args.csrin_pages.unwrap_or(1);
-        let csrin_search = args.csrin_search.unwrap_or(false);
-        let no_playwright = args.no_playwright.unwrap_or(false);
-        let rate_limiter = rate_limiter.clone();
-        tasks.push(tokio::spawn(async move {
-            let _permit = permit;
-            let base_url = match site.search_kind {
-                models::SearchKind::ListingPage => site
-                    .listing_path
-                    .clone()
-                    .unwrap_or(site.base_url.clone())
-                    .to_string(),
-                _ => query::build_search_url(&site, &query),
-            };
-            let page_urls: Vec<String> = if site.name.eq_ignore_ascii_case("csrin") {
-                let mut urls = Vec::new();
-                if csrin_search {
-                    let qenc = serde_urlencoded::to_string([
-                        ("keywords", query.as_str()),
-                        ("sr", "topics"),
-                    ])
-                    .unwrap_or_else(|_| format!("keywords={}&sr=topics", query.replace(' ', "+")));
-                    let search_base = "https://cs.rin.ru/forum/search.php";
-                    urls.push(format!("{}?{}&fid%5B%5D=10", search_base, qenc));
-                } else {
-                    let pages = csrin_pages.max(1);
-                    urls.push(base_url.clone());
-                    for i in 1..pages {
-                        let start = i * 100;
-                        if base_url.contains('?') {
-                            urls.push(format!("{}&start={}", base_url, start));
-                        } else {
-                            urls.push(format!("{}?start={}", base_url, start));
-                        }
-                    }
+    let mut results: Vec<models::SearchResult> = Vec::new();
+    let mut report = FallbackReport::new(&site.name);
+    // Try Playwright path for csrin only when solver not explicitly preferred
+    let cf_local = cf_url.contains("127.0.0.1") || cf_url.contains("localhost");
+    let non_default_cf = cf_url != DEFAULT_CF_URL;
+    let prefer_solver = use_cf && (cf_local || non_default_cf);
+    if site.name.eq_ignore_ascii_case("csrin") && !ctx.no_playwright && !prefer_solver {
+        let cookie_val = cookie_headers
+            .as_ref()
+            .and_then(|h| h.get(COOKIE))
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string());
+        let rendering = std::time::Instant::now();
+        let rendered = fetch_csrin_playwright_html(query, cookie_val).await;
+        ctx.time(&site.name, SearchPhase::Render, rendering);
+        match rendered {
+            Ok(html) => {
+                let parsing = std::time::Instant::now();
+                results = parser::parse_results_blocking(site, html.clone(), query).await;
+                ctx.time(&site.name, SearchPhase::Parse, parsing);
+                parser::stamp_source(
+                    &mut results,
+                    &base_url,
+                    parser::fetched_now(),
+                    models::Provenance::Playwright,
+                );
+                let outcome = Outcome::of_page(site, &html, query, results.len());
+                report.record(models::Provenance::Playwright, outcome);
+            }
+            Err(outcome) => report.record(models::Provenance::Playwright, outcome),
+        }
+    }
+    if site.search_kind == models::SearchKind::Sitemap {
+        // Sitemap sites fetch the sitemap and matching pages instead of a search page
+        page_urls.clear();
+        let cache_dir = paths::sitemap_dir();
+        results =
+            sitemap::search_sitemap(site, query, ctx.limit, Some(&cache_dir), |url| async move {
+                ctx.fetch(site, &url).await.ok().map(|page| page.body)
+            })
+            .await;
+    }
+    if results.is_empty() {
+        // Listing sites with a crawl config queue their archive pages as they go
+        let (mut page, mut crawl_depth) = (0, 0);
+        // Final URLs already parsed for this query, so a page reached twice is read once
+        let mut parsed: HashSet<String> = HashSet::new();
+        while let Some(url) = page_urls.pop_front() {
+            page += 1;
+            let via_solver = ctx.uses_solver(site);
+            let fetched = ctx.fetch(site, &url).await;
+            let (html, fetch_failure) = match fetched {
+                Ok(fetched) if !parsed.insert(fetched.url.clone()) => {
+                    log::debug!("{} led to already parsed {}", url, fetched.url);
+                    continue;
                 }
-                urls
-            } else {
-                vec![base_url.clone()]
-            };
-
-            let mut results: Vec<models::SearchResult> = Vec::new();
-            // Try Playwright path for csrin only when solver not explicitly preferred
-            let cf_local = cf_url.contains("127.0.0.1") || cf_url.contains("localhost");
-            let non_default_cf = cf_url != "http://localhost:8191/v1";
-            let prefer_solver = use_cf && (cf_local || non_default_cf);
-            if site.name.eq_ignore_ascii_case("csrin") && !no_playwright && !prefer_solver {
-                let cookie_val = cookie_headers
-                    .as_ref()
-                    .and_then(|h| h.get(COOKIE))
-                    .and_then(|v| v.to_str().ok())
-                    .map(|s| s.to_string());
-                if let Some(html) = fetch_csrin_playwright_html(&query, cookie_val).await {
-                    results = parser::parse_results(&site, &html, &query);
+                Ok(fetched) => {
+                    breaker.record_success();
+                    (fetched.body, None)
                 }
-            }
-            if results.is_empty() {
-                for url in page_urls {
-                    let allow_env = std::env::var("ALLOW_CSRIN_SOLVER")
-                        .ok()
-                        .map(|v| v == "1")
-                        .unwrap_or(false);
-                    let cf_local = cf_url.contains("127.0.0.1") || cf_url.contains("localhost");
-                    let non_default_cf = cf_url != "http://localhost:8191/v1";
-                    let csrin_solver_allowed = site.name.eq_ignore_ascii_case("csrin")
-                        && (allow_env || cf_local || non_default_cf);
-                    let use_solver_for_this =
-                        use_cf && (site.requires_cloudflare || csrin_solver_allowed);
-                    let html = if use_solver_for_this {
-                        (if cookie_headers.is_some() {
-                            cf::fetch_via_solver_with_headers(
-                                &client,
-                                &url,
-                                &cf_url,
-                                cookie_headers.clone(),
-                            )
-                            .await
-                        } else {
-                            cf::fetch_via_solver(&client, &url, &cf_url).await
-                        })
-                        .unwrap_or_default()
-                    } else {
-                        let rate_limiter_ref = if let Some(ref rl) = rate_limiter {
-                            Some(&mut *rl.lock().await)
-                        } else {
-                            None
-                        };
-
-                        (if cookie_headers.is_some() {
-                            fetcher::fetch_with_retry_headers(
-                                &client,
-                                &url,
-                                cookie_headers.clone(),
-                                rate_limiter_ref,
-                                Some(&site.name),
-                            )
-                            .await
-                        } else {
-                            fetcher::fetch_with_retry(
-                                &client,
-                                &url,
-                                rate_limiter_ref,
-                                Some(&site.name),
-                            )
-                            .await
-                        })
-                        .unwrap_or_default()
-                    };
-                    let mut page_results = parser::parse_results(&site, &html, &query);
-                    // gog-games: try AJAX/JSON fragment fallbacks when DOM parse is empty
-                    if page_results.is_empty() && site.name.eq_ignore_ascii_case("gog-games") {
-                        let rate_limiter_ref = if let Some(ref rl) = rate_limiter {
-                            Some(&mut *rl.lock().await)
-                        } else {
-                            None
-                        };
-
-                        if let Some(r) = fetch_gog_games_ajax_json(
-                            &client,
-                            &site,
-                            &query,
-                            use_cf,
-                            &cf_url,
-                            cookie_headers.clone(),
-                            rate_limiter_ref,
-                        )
-                        .await
-                            && !r.is_empty()
-                        {
-                            page_results = r;
-                        }
-                    }
-                    if site.name.eq_ignore_ascii_case("gog-games") {
-                        filter_results_by_query_strict(&mut page_results, &query);
-                    }
-                    // csrin: Atom feed fallback
-                    if page_results.is_empty() && site.name.eq_ignore_ascii_case("csrin") {
-                        let rate_limiter_ref = if let Some(ref rl) = rate_limiter {
-                            Some(&mut *rl.lock().await)
-                        } else {
-                            None
-                        };
-
-                        if let Some(feed_results) =
-                            fetch_csrin_feed(&client, &site, &query, rate_limiter_ref).await
-                        {
-                            page_results = feed_results;
-                        }
-                    }
-                    results.extend(page_results);
-                    if results.len() >= 5000 {
-                        break;
+                Err(e) => {
+                    if resilience::should_trip_circuit(resilience::categorize_error(&e)) {
+                        breaker.record_failure();
                     }
+                    (String::new(), Some(Outcome::fetch_error(via_solver, &e)))
                 }
+            };
+            ctx.note_page(site, query, page, &url, &html);
+            if let Some(next) =
+                crawl::next_archive_page(site, crawl_depth, &url, &html, parser::fetched_now())
+            {
+                crawl_depth += 1;
+                page_urls.push_back(next);
+            }
+            let mut page_results = ctx.parse(site, html.clone(), query).await;
+            if page == 1 && page_results.is_empty() && fetch_failure.is_none() {
+                ctx.probe_query_support(site, &html).await;
             }
-            // Final csrin Playwright fallback if still empty
-            if site.name.eq_ignore_ascii_case("csrin") && results.is_empty() && !no_playwright {
-                let cookie_val = cookie_headers
-                    .as_ref()
-                    .and_then(|h| h.get(COOKIE))
-                    .and_then(|v| v.to_str().ok())
-                    .map(|s| s.to_string());
-                if let Some(html) = fetch_csrin_playwright_html(&query, cookie_val).await {
-                    let rs = parser::parse_results(&site, &html, &query);
-                    if !rs.is_empty() {
-                        results = rs;
+            let mut provenance = models::Provenance::of_page(site.search_kind, via_solver);
+            report.record(
+                provenance,
+                fetch_failure
+                    .unwrap_or_else(|| Outcome::of_page(site, &html, query, page_results.len())),
+            );
+            // gog-games: try AJAX/JSON fragment fallbacks when DOM parse is empty
+            if page_results.is_empty() && site.name.eq_ignore_ascii_case("gog-games") {
+                let rate_limiter_ref = if let Some(rl) = rate_limiter {
+                    Some(&mut *rl.lock().await)
+                } else {
+                    None
+                };
+
+                match fetch_gog_games_ajax_json(
+                    if use_cf { client } else { routed },
+                    site,
+                    query,
+                    use_cf,
+                    cf_url,
+                    cookie_headers.clone(),
+                    rate_limiter_ref,
+                )
+                .await
+                {
+                    Ok(r) => {
+                        report.record(models::Provenance::Ajax, Outcome::Found(r.len()));
+                        page_results = r;
+                        provenance = models::Provenance::Ajax;
                     }
+                    Err(outcome) => report.record(models::Provenance::Ajax, outcome),
                 }
             }
-            // Filter csrin results: only keep viewtopic.php links with title matching query
-            // This removes sticky posts like "FAQ", "Forum rules", "Donations", etc.
-            if site.name.eq_ignore_ascii_case("csrin") {
-                let q_lower = query.to_lowercase();
-                results.retain(|r| r.url.contains("viewtopic.php"));
-                results.retain(|r| r.title.to_lowercase().contains(&q_lower));
+            parser::stamp_source(&mut page_results, &url, parser::fetched_now(), provenance);
+            if site.name.eq_ignore_ascii_case("gog-games") {
+                filter_results_by_query_strict(&mut page_results, query);
             }
-            // Truncate per-site
-            if !results.is_empty() {
-                results.truncate(limit);
+            // The thorough profile reads further result pages while they find something
+            if !page_results.is_empty()
+                && page < ctx.profile.pages()
+                && site.crawl.is_none()
+                && page_urls.is_empty()
+                && let Some(next) = pagination::next_page(site, query, page, &url, &html)
+            {
+                page_urls.push_back(next);
             }
-            results
-        }));
+            results.extend(page_results);
+            if results.len() >= 5000 {
+                break;
+            }
+        }
     }
-
-    let mut combined: Vec<models::SearchResult> = Vec::new();
-    while let Some(joined) = tasks.next().await {
-        if let Ok(mut site_results) = joined {
-            combined.append(&mut site_results);
+    // Final csrin Playwright fallback if the search pages found nothing
+    if site.name.eq_ignore_ascii_case("csrin") && results.is_empty() && !ctx.no_playwright {
+        let cookie_val = cookie_headers
+            .as_ref()
+            .and_then(|h| h.get(COOKIE))
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string());
+        let rendering = std::time::Instant::now();
+        let rendered = fetch_csrin_playwright_html(query, cookie_val).await;
+        ctx.time(&site.name, SearchPhase::Render, rendering);
+        match rendered {
+            Ok(html) => {
+                let parsing = std::time::Instant::now();
+                let mut rs = parser::parse_results_blocking(site, html.clone(), query).await;
+                ctx.time(&site.name, SearchPhase::Parse, parsing);
+                parser::stamp_source(
+                    &mut rs,
+                    &base_url,
+                    parser::fetched_now(),
+                    models::Provenance::Playwright,
+                );
+                let outcome = Outcome::of_page(site, &html, query, rs.len());
+                report.record(models::Provenance::Playwright, outcome);
+                if !rs.is_empty() {
+                    results = rs;
+                }
+            }
+            Err(outcome) => report.record(models::Provenance::Playwright, outcome),
         }
     }
-    // Dedup + sort
-    combined.sort_by(|a, b| a.site.cmp(&b.site).then_with(|| a.title.cmp(&b.title)));
-    combined.dedup_by(|a, b| a.site == b.site && a.url == b.url);
+    (results, report)
+}
+
+/// Drop results of `site` that its segments of an advanced query don't match
+fn filter_site_results(
+    multi_query: Option<&MultiQuery>,
+    site: &str,
+    results: Vec<models::SearchResult>,
+) -> Vec<models::SearchResult> {
+    match multi_query {
+        None => results,
+        Some(m) if m.is_single() => match m.first() {
+            Some(first) => filter_results(results, first),
+            None => results,
+        },
+        Some(m) => m.filter_results_for_site(results, site),
+    }
+}
+
+/// Sort, de-duplicate, apply advanced operators and the overall cutoff
+fn finish_results(
+    mut combined: Vec<models::SearchResult>,
+    multi_query: Option<&MultiQuery>,
+    cutoff: Option<usize>,
+) -> Vec<models::SearchResult> {
+    combined.sort_by(models::SearchResult::output_order);
+    // One copy of each page per site, however its URL was spelled
+    canonical::dedup_results(&mut combined);
 
     // Apply advanced query filtering (site:, -exclude, "phrase", regex: operators)
-    let advanced_query = AdvancedQuery::parse(&args.query);
-    let mut combined = filter_results(combined, &advanced_query);
+    // per site, so each site keeps what its own segments match
+    let mut filtered = Vec::with_capacity(combined.len());
+    let mut rest = combined.into_iter().peekable();
+    while let Some(first) = rest.next() {
+        let site = first.site.clone();
+        let mut group = vec![first];
+        while let Some(r) = rest.next_if(|r| r.site == site) {
+            group.push(r);
+        }
+        filtered.extend(filter_site_results(multi_query, &site, group));
+    }
 
-    // Apply overall cutoff if specified (0 means no cutoff)
-    if let Some(cutoff) = args.cutoff
-        && cutoff > 0
-        && combined.len() > cutoff
-    {
-        combined.truncate(cutoff);
+    // Overall cutoff (0 means no cutoff); sites take turns so none crowds out the rest
+    limits::round_robin_truncate(filtered, cutoff.unwrap_or(0))
+}
+
+/// Cross-site duplicate groups of `results`, if the search asked for them
+fn duplicate_groups(
+    args: &SearchArgs,
+    results: &[models::SearchResult],
+) -> Option<Vec<analyzer::DuplicateGroup>> {
+    args.group
+        .unwrap_or(false)
+        .then(|| analyzer::group_duplicates(results))
+}
+
+/// Results matched by each part of a multi-part (`|`) query
+fn segment_matches(
+    multi_query: Option<&MultiQuery>,
+    results: &[models::SearchResult],
+) -> Vec<SegmentMatch> {
+    let Some(multi) = multi_query.filter(|m| !m.is_single()) else {
+        return Vec::new();
+    };
+    multi
+        .segments
+        .iter()
+        .enumerate()
+        .map(|(index, seg)| SegmentMatch {
+            index,
+            query: seg.raw().trim().to_string(),
+            sites: seg.site_restrictions.clone(),
+            results: results
+                .iter()
+                .enumerate()
+                .filter(|(_, r)| {
+                    multi
+                        .segments_for_site(&r.site)
+                        .iter()
+                        .any(|s| std::ptr::eq(*s, seg))
+                        && seg.matches_result(r)
+                        && seg.matches_terms(r)
+                })
+                .map(|(i, _)| i)
+                .collect(),
+        })
+        .collect()
+}
+
+/// `sites` in the order their searches start: slowest first by recent timings,
+/// so slow sites don't wait for a slot behind quick ones
+fn start_order<'a>(
+    timings: &monitoring::SiteTimings,
+    sites: &'a [models::SiteConfig],
+) -> Vec<&'a models::SiteConfig> {
+    let mut order: Vec<_> = sites.iter().collect();
+    timings.slowest_first(&mut order, |s| s.name.as_str());
+    order
+}
+
+fn save_timings(timings: &monitoring::SiteTimings) {
+    if let Err(e) = timings.save(&paths::site_timings_file()) {
+        log::warn!("Failed to save site timings: {e}");
     }
+}
 
-    Ok(combined)
+/// Add how many results each site found for `query` to the site health streaks
+fn record_site_health(query: &str, found: &[(String, usize)]) {
+    let path = paths::site_health_file();
+    let mut health = monitoring::SiteHealth::load(&path);
+    let counts = found.iter().map(|(site, count)| (site.as_str(), *count));
+    for site in health.record_search(query, counts) {
+        log::warn!(
+            "{site} found nothing for {} queries other sites matched; its parser is likely broken",
+            monitoring::BROKEN_AFTER
+        );
+    }
+    if let Err(e) = health.save(&path) {
+        log::warn!("Failed to save site health: {e}");
+    }
+}
+
+/// Search `sites` concurrently and return the finished, merged list with
+/// where the search's time went so far
+async fn search_sites(
+    state: &AppState,
+    args: &SearchArgs,
+    defaults: &SearchDefaults,
+    sites: &[models::SiteConfig],
+) -> Result<(Vec<models::SearchResult>, Arc<SearchTimings>), ApiError> {
+    let ctx = Arc::new(SiteSearch::new(args, defaults, state));
+    let semaphore = Arc::new(Semaphore::new(ctx.concurrency));
+    let mut timings = monitoring::SiteTimings::load(&paths::site_timings_file());
+
+    let mut tasks = FuturesUnordered::new();
+    let queued = std::time::Instant::now();
+    for site in start_order(&timings, sites) {
+        let permit = semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|e| ApiError::internal(e.to_string()))?;
+        ctx.time(&site.name, SearchPhase::Queue, queued);
+        let ctx = ctx.clone();
+        let site = site.clone();
+        tasks.push(tokio::spawn(async move {
+            let _permit = permit;
+            let started = std::time::Instant::now();
+            let results = search_site(&ctx, &site).await;
+            (site.name, started.elapsed(), results)
+        }));
+    }
+
+    let mut combined: Vec<models::SearchResult> = Vec::new();
+    let mut found = Vec::new();
+    while let Some(joined) = tasks.next().await {
+        if let Ok((site, elapsed, mut site_results)) = joined {
+            timings.record(&site, elapsed);
+            found.push((site, site_results.len()));
+            combined.append(&mut site_results);
+        }
+    }
+    save_timings(&timings);
+    record_site_health(&ctx.query, &found);
+    ctx.save_learned();
+    let finishing = std::time::Instant::now();
+    let results = finish_results(combined, ctx.multi_query.as_ref(), args.cutoff);
+    ctx.time("", SearchPhase::PostProcess, finishing);
+    Ok((results, ctx.timings.clone()))
 }
 
-/// Streaming search that emits events as results arrive
-/// Events emitted:
-/// - "search:progress" - SearchProgress for status updates
-/// - "search:result" - StreamedResult for individual results
-/// - "search:complete" - SearchComplete when done
 #[tauri::command]
-async fn search_gui_streaming(
-    app_handle: tauri::AppHandle,
+async fn search_gui(
+    state: tauri::State<'_, AppState>,
     args: SearchArgs,
-) -> Result<Vec<models::SearchResult>, String> {
-    use tauri::Emitter;
+) -> Result<SearchResponse, ApiError> {
+    search_once(&state, args).await
+}
 
+/// Search the selected sites and record the search in the history
+async fn search_once(state: &AppState, args: SearchArgs) -> Result<SearchResponse, ApiError> {
     if args.query.trim().is_empty() {
-        return Err("empty search phrase".to_string());
-    }
-    let limit = args.limit.unwrap_or(10);
-    let use_cf = !args.no_cf.unwrap_or(false);
-    let mut cf_url = args
-        .cf_url
-        .unwrap_or_else(|| "http://localhost:8191/v1".to_string());
-    if cf_url == "http://localhost:8191/v1"
-        && let Ok(env_cf) = std::env::var("CF_URL")
-        && !env_cf.trim().is_empty()
-    {
-        cf_url = env_cf;
+        return Err(ApiError::invalid_input("empty search phrase"));
+    }
+    let defaults = Settings::load(&default_settings_path()).search;
+    let sites = selected_sites(&args, &defaults);
+    let (mut results, timings) = search_sites(state, &args, &defaults, &sites).await?;
+    let post_processing = std::time::Instant::now();
+    if defaults.steam_appids {
+        steam::annotate(&state.client, &mut results).await;
+    }
+    if args.profile.unwrap_or_default().verifies_links() {
+        liveness::check_results(&state.client, &mut results).await;
     }
+    record_history(&args.query, &sites, &results);
+    ignore::apply(&mut results);
+    notes::apply(&mut results, args.hidden_statuses());
+    timings.record_since("", SearchPhase::PostProcess, post_processing);
+    send_to_sinks(&state.client, &args.query, &results);
+    let segments = segment_matches(parse_multi_query(&args).as_ref(), &results);
+    let groups = duplicate_groups(&args, &results);
+    Ok(SearchResponse {
+        results,
+        segments,
+        groups,
+        timings: timings.breakdown(),
+    })
+}
+
+/// Saved search defaults from `config.toml`, shared with the CLI
+#[tauri::command]
+async fn get_settings() -> Result<SearchDefaults, ApiError> {
+    Ok(Settings::load(&default_settings_path()).search)
+}
 
-    let normalized = query::normalize_query(&args.query);
-    let all_sites = config::site_configs();
-    let selected_sites: Vec<models::SiteConfig> = if let Some(names) = args.sites {
-        let wanted: Vec<String> = names
-            .into_iter()
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect();
-        all_sites
-            .into_iter()
-            .filter(|s| wanted.iter().any(|w| w.eq_ignore_ascii_case(&s.name)))
-            .collect()
-    } else {
-        all_sites
-    };
+/// Save search defaults, keeping the rest of `config.toml` as it was
+#[tauri::command]
+async fn set_settings(settings: SearchDefaults) -> Result<(), ApiError> {
+    let path = default_settings_path();
+    let mut all = Settings::load(&path);
+    all.search = settings;
+    save_settings(&all, &path)
+}
 
-    let _total_sites = selected_sites.len();
-
-    // Emit initial progress for all sites
-    for site in &selected_sites {
-        let _ = app_handle.emit(
-            "search:progress",
-            SearchProgress {
-                site: site.name.clone(),
-                status: "pending".to_string(),
-                results_count: 0,
-                message: None,
-            },
-        );
+/// What `import_cookie` stored for a site
+#[derive(serde::Serialize, Debug, PartialEq)]
+struct CookieImport {
+    site: String,
+    /// Names of the stored cookies
+    names: Vec<String>,
+    /// Domains the stored cookies were set for; the site's host for a pasted header
+    domains: Vec<String>,
+    /// cookies.txt entries left out because they were for other domains
+    other_domains: Vec<String>,
+    /// cookies.txt entries left out because they had expired
+    expired: usize,
+}
+
+/// Pick the cookies in `input` that `site` would be sent, as a header value
+fn site_cookie(site: &models::SiteConfig, input: &str) -> Result<(String, CookieImport), ApiError> {
+    let host = reqwest::Url::parse(&site.base_url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_lowercase))
+        .ok_or_else(|| {
+            ApiError::internal(format!(
+                "{} has no host to match cookies against",
+                site.name
+            ))
+        })?;
+    let parsed = cookies::parse(input).map_err(|e| ApiError::invalid_input(e.to_string()))?;
+
+    let mut kept = Vec::new();
+    let mut domains = Vec::new();
+    let mut other_domains = Vec::new();
+    for cookie in parsed.cookies {
+        let domain = cookie.domain.clone().unwrap_or_else(|| host.clone());
+        let list = if cookies::domain_matches(&domain, &host) {
+            kept.push(cookie);
+            &mut domains
+        } else {
+            &mut other_domains
+        };
+        if !list.contains(&domain) {
+            list.push(domain);
+        }
+    }
+    if kept.is_empty() {
+        let found = if other_domains.is_empty() {
+            "only expired cookies".to_string()
+        } else {
+            other_domains.join(", ")
+        };
+        return Err(ApiError::invalid_input(format!(
+            "no cookies for {host} (found {found})"
+        )));
     }
 
-    let client = fetcher::build_http_client();
-    let semaphore = Arc::new(Semaphore::new(3));
-    let rate_limiter = if !args.no_rate_limit.unwrap_or(false) {
-        Some(Arc::new(tokio::sync::Mutex::new(RateLimiter::new())))
-    } else {
-        None
+    let header = cookies::to_header(&kept);
+    HeaderValue::from_str(&header)
+        .map_err(|_| ApiError::invalid_input("cookie contains invalid characters"))?;
+    let import = CookieImport {
+        site: site.name.clone(),
+        names: kept.into_iter().map(|c| c.name).collect(),
+        domains,
+        other_domains,
+        expired: parsed.expired,
     };
+    Ok((header, import))
+}
 
-    // Optional Cookie header
-    let cookie_headers: Option<ReqHeaderMap> = if let Some(c) = args.cookie.as_deref() {
-        match HeaderValue::from_str(c) {
-            Ok(v) => {
-                let mut h = ReqHeaderMap::new();
-                h.insert(COOKIE, v);
-                Some(h)
-            }
-            Err(_) => None,
+/// Store a cookie for `site` from a pasted `Cookie` header or cookies.txt text,
+/// or from a cookies.txt file at `path`, replacing the site's saved cookie.
+/// The cookie goes to the secret store; the config only refers to it.
+///
+/// With neither, asks for the file in an open dialog; returns `None` if that
+/// was cancelled.
+#[tauri::command]
+async fn import_cookie(
+    app_handle: tauri::AppHandle,
+    site: String,
+    cookie: Option<String>,
+    path: Option<String>,
+) -> Result<Option<CookieImport>, ApiError> {
+    let site_config = find_site(&site)?;
+    let input = match cookie.filter(|c| !c.trim().is_empty()) {
+        Some(cookie) => cookie,
+        None => {
+            let path = match path.filter(|p| !p.trim().is_empty()) {
+                Some(path) => std::path::PathBuf::from(path),
+                None => match pick_cookie_file(&app_handle).await {
+                    Some(path) => path,
+                    None => return Ok(None),
+                },
+            };
+            tokio::fs::read_to_string(&path).await.map_err(|e| {
+                ApiError::invalid_input(format!("cannot read {}: {}", path.display(), e))
+            })?
         }
-    } else {
-        None
     };
+    let (header, import) = site_cookie(&site_config, &input)?;
+    let name = format!("cookie.{}", site_config.name);
+    SecretStore::default()
+        .set(&name, &header)
+        .map_err(|e| ApiError::storage(format!("failed to store cookie: {e:#}")))?;
+
+    let settings_path = default_settings_path();
+    let mut settings = Settings::load(&settings_path);
+    let cookies = &mut settings.search.cookies;
+    cookies.retain(|name, _| !name.eq_ignore_ascii_case(&site_config.name));
+    cookies.insert(site_config.name, secrets::reference(&name));
+    save_settings(&settings, &settings_path)?;
+    Ok(Some(import))
+}
 
-    let mut tasks = FuturesUnordered::new();
-    for site in selected_sites {
-        let permit = semaphore
-            .clone()
-            .acquire_owned()
-            .await
-            .map_err(|e| e.to_string())?;
-        let client = client.clone();
-        let query = normalized.clone();
-        let cf_url = cf_url.clone();
-        let cookie_headers = cookie_headers.clone();
-        let csrin_pages = args.csrin_pages.unwrap_or(1);
-        let csrin_search = args.csrin_search.unwrap_or(false);
-        let no_playwright = args.no_playwright.unwrap_or(false);
-        let rate_limiter = rate_limiter.clone();
-        let app_handle = app_handle.clone();
-
-        tasks.push(tokio::spawn(async move {
-            let _permit = permit;
-            let site_name = site.name.clone();
-
-            // Emit "fetching" status
-            let _ = app_handle.emit(
-                "search:progress",
-                SearchProgress {
-                    site: site_name.clone(),
-                    status: "fetching".to_string(),
-                    results_count: 0,
-                    message: Some("Fetching results...".to_string()),
-                },
-            );
+async fn pick_cookie_file(app_handle: &tauri::AppHandle) -> Option<std::path::PathBuf> {
+    use tauri_plugin_dialog::DialogExt;
 
-            let base_url = match site.search_kind {
-                models::SearchKind::ListingPage => site
-                    .listing_path
-                    .clone()
-                    .unwrap_or(site.base_url.clone())
-                    .to_string(),
-                _ => query::build_search_url(&site, &query),
-            };
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app_handle
+        .dialog()
+        .file()
+        .add_filter("cookies.txt", &["txt"])
+        .pick_file(move |file| {
+            let _ = tx.send(file);
+        });
+    rx.await.ok().flatten()?.into_path().ok()
+}
 
-            // Build page URLs - same logic as non-streaming search_gui
-            let page_urls: Vec<String> = if site.name.eq_ignore_ascii_case("csrin") {
-                let mut urls = Vec::new();
-                if csrin_search {
-                    let qenc = serde_urlencoded::to_string([
-                        ("keywords", query.as_str()),
-                        ("sr", "topics"),
-                    ])
-                    .unwrap_or_else(|_| format!("keywords={}&sr=topics", query.replace(' ', "+")));
-                    let search_base = "https://cs.rin.ru/forum/search.php";
-                    urls.push(format!("{}?{}&fid%5B%5D=10", search_base, qenc));
-                } else {
-                    let pages = csrin_pages.max(1);
-                    urls.push(base_url.clone());
-                    for i in 1..pages {
-                        let start = i * 100;
-                        if base_url.contains('?') {
-                            urls.push(format!("{}&start={}", base_url, start));
-                        } else {
-                            urls.push(format!("{}?start={}", base_url, start));
-                        }
-                    }
-                }
-                urls
-            } else {
-                vec![base_url.clone()]
-            };
+/// Data for the GUI's health dashboard
+#[derive(serde::Serialize)]
+struct HealthSnapshot {
+    #[serde(flatten)]
+    metrics: monitoring::MetricsSnapshot,
+    breakers: Vec<state::BreakerStatus>,
+    /// Sites that keep finding nothing for queries other sites match
+    likely_broken: Vec<monitoring::BrokenSite>,
+    /// Sites whose search ignores the query, searched as listing sites
+    ignores_query: Vec<String>,
+}
 
-            let mut results: Vec<models::SearchResult> = Vec::new();
+/// Per-site success rates and latencies, cache hit rate and circuit breaker
+/// states since the app started, and the sites whose parser looks broken
+#[tauri::command]
+async fn get_metrics_snapshot(
+    state: tauri::State<'_, AppState>,
+) -> Result<HealthSnapshot, ApiError> {
+    Ok(HealthSnapshot {
+        metrics: monitoring::get_metrics().snapshot().await,
+        breakers: state.breakers.snapshot(),
+        likely_broken: monitoring::SiteHealth::load(&paths::site_health_file()).likely_broken(),
+        ignores_query: QueryCapabilities::load(&paths::query_capability_file())
+            .ignored()
+            .into_iter()
+            .map(String::from)
+            .collect(),
+    })
+}
 
-            // Simplified fetch - use solver or direct based on site config
-            let cf_local = cf_url.contains("127.0.0.1") || cf_url.contains("localhost");
-            let non_default_cf = cf_url != "http://localhost:8191/v1";
-            let use_solver = use_cf && (site.requires_cloudflare || cf_local || non_default_cf);
-
-            // Try Playwright for csrin first (when solver not preferred)
-            let prefer_solver = use_solver;
-            if site.name.eq_ignore_ascii_case("csrin") && !no_playwright && !prefer_solver {
-                let cookie_val = cookie_headers
-                    .as_ref()
-                    .and_then(|h| h.get(COOKIE))
-                    .and_then(|v| v.to_str().ok())
-                    .map(|s| s.to_string());
-                if let Some(html) = fetch_csrin_playwright_html(&query, cookie_val).await {
-                    results = parser::parse_results(&site, &html, &query);
-                }
-            }
+/// Running streaming searches, so they can be cancelled
+#[derive(Default)]
+struct SearchJobs {
+    next_id: AtomicU64,
+    running: std::sync::Mutex<HashMap<u64, tauri::async_runtime::JoinHandle<()>>>,
+}
 
-            // If Playwright didn't yield results, fetch from page URLs
-            if results.is_empty() {
-                for url in page_urls {
-                    let html = if use_solver {
-                        (if cookie_headers.is_some() {
-                            cf::fetch_via_solver_with_headers(
-                                &client,
-                                &url,
-                                &cf_url,
-                                cookie_headers.clone(),
-                            )
-                            .await
-                        } else {
-                            cf::fetch_via_solver(&client, &url, &cf_url).await
-                        })
-                        .unwrap_or_default()
-                    } else {
-                        let rate_limiter_ref = if let Some(ref rl) = rate_limiter {
-                            Some(&mut *rl.lock().await)
-                        } else {
-                            None
-                        };
-                        (if cookie_headers.is_some() {
-                            fetcher::fetch_with_retry_headers(
-                                &client,
-                                &url,
-                                cookie_headers.clone(),
-                                rate_limiter_ref,
-                                Some(&site.name),
-                            )
-                            .await
-                        } else {
-                            fetcher::fetch_with_retry(
-                                &client,
-                                &url,
-                                rate_limiter_ref,
-                                Some(&site.name),
-                            )
-                            .await
-                        })
-                        .unwrap_or_default()
-                    };
-
-                    // Emit "parsing" status
-                    let _ = app_handle.emit(
-                        "search:progress",
-                        SearchProgress {
-                            site: site_name.clone(),
-                            status: "parsing".to_string(),
-                            results_count: 0,
-                            message: Some("Parsing results...".to_string()),
-                        },
-                    );
-
-                    let page_results = parser::parse_results(&site, &html, &query);
-                    results.extend(page_results);
-
-                    if results.len() >= 5000 {
-                        break; // Safety cap
-                    }
-                }
-            }
+impl SearchJobs {
+    /// Forget a job, returning its handle if it was still running
+    fn finish(&self, job_id: u64) -> Option<tauri::async_runtime::JoinHandle<()>> {
+        self.running.lock().ok()?.remove(&job_id)
+    }
+}
 
-            // Final Playwright fallback for csrin if still empty
-            if site.name.eq_ignore_ascii_case("csrin") && results.is_empty() && !no_playwright {
-                let cookie_val = cookie_headers
-                    .as_ref()
-                    .and_then(|h| h.get(COOKIE))
-                    .and_then(|v| v.to_str().ok())
-                    .map(|s| s.to_string());
-                if let Some(html) = fetch_csrin_playwright_html(&query, cookie_val).await {
-                    let rs = parser::parse_results(&site, &html, &query);
-                    results.extend(rs);
-                }
-            }
+fn emit_progress(
+    app_handle: &tauri::AppHandle,
+    job_id: u64,
+    site: &str,
+    status: &str,
+    results_count: usize,
+) {
+    let _ = app_handle.emit(
+        "search://progress",
+        SearchProgress {
+            job_id,
+            site: site.to_string(),
+            status: status.to_string(),
+            results_count,
+        },
+    );
+}
 
-            // Apply csrin filtering - only keep viewtopic.php links with title matching query
-            if site.name.eq_ignore_ascii_case("csrin") {
-                let q_lower = query.to_lowercase();
-                results.retain(|r| r.url.contains("viewtopic.php"));
-                results.retain(|r| r.title.to_lowercase().contains(&q_lower));
-            }
+/// Start a search in the background and return its job id.
+///
+/// Events emitted, all tagged with the job id:
+/// - "search://progress" - SearchProgress per site status change
+/// - "search://site_results" - SiteResults once a site has finished
+/// - "search://done" - SearchDone with the final merged list, or on cancel;
+///   with a deadline set, the list so far once it passes
+/// - "search://late_results" - LateResults of each site that finished after
+///   the deadline
+#[tauri::command]
+async fn start_search(
+    app_handle: tauri::AppHandle,
+    jobs: tauri::State<'_, SearchJobs>,
+    args: SearchArgs,
+) -> Result<u64, ApiError> {
+    if args.query.trim().is_empty() {
+        return Err(ApiError::invalid_input("empty search phrase"));
+    }
+    let job_id = jobs.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+    // Hold the lock while spawning so a fast job can't finish before it is registered
+    let mut running = jobs
+        .running
+        .lock()
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+    app_handle
+        .state::<pages::SearchPages>()
+        .start(job_id, args.clone());
+    let handle = tauri::async_runtime::spawn(run_search_job(app_handle, job_id, args));
+    running.insert(job_id, handle);
+    Ok(job_id)
+}
 
-            // Truncate per-site
-            results.truncate(limit);
+async fn run_search_job(app_handle: tauri::AppHandle, job_id: u64, args: SearchArgs) {
+    let defaults = Settings::load(&default_settings_path()).search;
+    let ctx = Arc::new(SiteSearch::new(
+        &args,
+        &defaults,
+        &app_handle.state::<AppState>(),
+    ));
+    let sites = selected_sites(&args, &defaults);
+    for site in &sites {
+        emit_progress(&app_handle, job_id, &site.name, "pending", 0);
+    }
 
-            // Emit each result as it's processed
-            for result in &results {
-                let _ = app_handle.emit(
-                    "search:result",
-                    StreamedResult {
-                        site: site_name.clone(),
-                        result: result.clone(),
+    // Sites run as futures of this task (not spawned) so aborting the job
+    // stops them all, up to the deadline
+    let semaphore = Arc::new(Semaphore::new(ctx.concurrency));
+    let mut timings = monitoring::SiteTimings::load(&paths::site_timings_file());
+    let hidden: Arc<[NoteStatus]> = args.hidden_statuses().into();
+    let queued = std::time::Instant::now();
+    let mut tasks: FuturesUnordered<_> = start_order(&timings, &sites)
+        .into_iter()
+        .map(|site| {
+            let (site, ctx, semaphore) = (site.clone(), ctx.clone(), semaphore.clone());
+            let (app, hidden) = (app_handle.clone(), hidden.clone());
+            async move {
+                let _permit = semaphore.acquire().await;
+                ctx.time(&site.name, SearchPhase::Queue, queued);
+                let started = std::time::Instant::now();
+                emit_progress(&app, job_id, &site.name, "fetching", 0);
+                let mut results = filter_site_results(
+                    ctx.multi_query.as_ref(),
+                    &site.name,
+                    search_site(&ctx, &site).await,
+                );
+                app.state::<pages::SearchPages>().record(
+                    job_id,
+                    &site.name,
+                    ctx.take_cursor(&site),
+                    &results,
+                );
+                ignore::apply(&mut results);
+                notes::apply(&mut results, &hidden);
+                let _ = app.emit(
+                    "search://site_results",
+                    SiteResults {
+                        job_id,
+                        site: site.name.clone(),
+                        results: results.clone(),
                     },
                 );
+                emit_progress(&app, job_id, &site.name, "completed", results.len());
+                (site.name, started.elapsed(), results)
             }
+        })
+        .collect();
 
-            // Emit "completed" status
-            let _ = app_handle.emit(
-                "search:progress",
-                SearchProgress {
-                    site: site_name.clone(),
-                    status: "completed".to_string(),
-                    results_count: results.len(),
-                    message: None,
-                },
-            );
-
-            results
-        }));
-    }
-
+    let deadline = async {
+        match defaults.deadline_seconds.filter(|s| *s > 0) {
+            Some(seconds) => tokio::time::sleep(Duration::from_secs(seconds)).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::pin!(deadline);
+    let mut pending: Vec<String> = sites.iter().map(|s| s.name.clone()).collect();
     let mut combined: Vec<models::SearchResult> = Vec::new();
-    let mut sites_completed = 0usize;
-    let mut sites_failed = 0usize;
-
-    while let Some(joined) = tasks.next().await {
-        match joined {
-            Ok(site_results) => {
-                combined.extend(site_results);
-                sites_completed += 1;
-            }
-            Err(_) => {
-                sites_failed += 1;
+    let mut found = Vec::new();
+    loop {
+        tokio::select! {
+            next = tasks.next() => {
+                let Some((site, elapsed, mut site_results)) = next else {
+                    break;
+                };
+                timings.record(&site, elapsed);
+                pending.retain(|name| *name != site);
+                found.push((site, site_results.len()));
+                combined.append(&mut site_results);
             }
+            _ = &mut deadline => break,
         }
     }
+    save_timings(&timings);
+    record_site_health(&ctx.query, &found);
+    ctx.save_learned();
+
+    let post_processing = std::time::Instant::now();
+    let mut results = finish_results(combined, ctx.multi_query.as_ref(), args.cutoff);
+    if defaults.steam_appids {
+        steam::annotate(&ctx.client, &mut results).await;
+    }
+    if ctx.profile.verifies_links() {
+        liveness::check_results(&ctx.client, &mut results).await;
+    }
+    record_history(&args.query, &sites, &results);
+    ignore::apply(&mut results);
+    notes::apply(&mut results, args.hidden_statuses());
+    ctx.time("", SearchPhase::PostProcess, post_processing);
+    send_to_sinks(&ctx.client, &args.query, &results);
+    let segments = segment_matches(ctx.multi_query.as_ref(), &results);
+    let groups = duplicate_groups(&args, &results);
+    if app_handle.state::<SearchJobs>().finish(job_id).is_none() {
+        return;
+    }
+    if !tasks.is_empty() {
+        // The deadline passed: the results so far are cached now, and the
+        // slow sites keep going in the background
+        log::info!(
+            "Showing {} results after {}s; still searching {}",
+            results.len(),
+            defaults.deadline_seconds.unwrap_or_default(),
+            pending.join(", ")
+        );
+        cache_results(&app_handle, &args.query, &results).await;
+        let late = LateSearch {
+            app_handle: app_handle.clone(),
+            job_id,
+            query: args.query.clone(),
+            ctx: ctx.clone(),
+            results: results.clone(),
+            pending: pending.clone(),
+        };
+        tauri::async_runtime::spawn(late.finish(tasks));
+    }
+    let _ = app_handle.emit(
+        "search://done",
+        SearchDone {
+            job_id,
+            total_results: results.len(),
+            sites_completed: sites.len() - pending.len(),
+            cancelled: false,
+            late_sites: pending,
+            results,
+            segments,
+            groups,
+            timings: ctx.timings.breakdown(),
+        },
+    );
+}
 
-    // Dedup + sort
-    combined.sort_by(|a, b| a.site.cmp(&b.site).then_with(|| a.title.cmp(&b.title)));
-    combined.dedup_by(|a, b| a.site == b.site && a.url == b.url);
+/// The sites of a streaming search still running when its deadline passed
+struct LateSearch {
+    app_handle: tauri::AppHandle,
+    job_id: u64,
+    query: String,
+    ctx: Arc<SiteSearch>,
+    /// The results shown so far, the late ones included
+    results: Vec<models::SearchResult>,
+    /// Sites not finished yet
+    pending: Vec<String>,
+}
 
-    // Apply advanced query filtering (site:, -exclude, "phrase", regex: operators)
-    let advanced_query = AdvancedQuery::parse(&args.query);
-    let mut combined = filter_results(combined, &advanced_query);
+impl LateSearch {
+    /// Wait for each remaining site, merge its results into the cached search
+    /// and emit them as "search://late_results"
+    async fn finish(
+        mut self,
+        mut tasks: impl futures::Stream<Item = (String, Duration, Vec<models::SearchResult>)> + Unpin,
+    ) {
+        let mut found = Vec::new();
+        while let Some((site, elapsed, site_results)) = tasks.next().await {
+            let mut timings = monitoring::SiteTimings::load(&paths::site_timings_file());
+            timings.record(&site, elapsed);
+            save_timings(&timings);
+            self.pending.retain(|name| *name != site);
+            found.push((site.clone(), site_results.len()));
+
+            self.results.extend(site_results.iter().cloned());
+            self.results.sort_by(models::SearchResult::output_order);
+            canonical::dedup_results(&mut self.results);
+            cache_results(&self.app_handle, &self.query, &self.results).await;
+            let _ = self.app_handle.emit(
+                "search://late_results",
+                LateResults {
+                    job_id: self.job_id,
+                    site,
+                    results: site_results,
+                    pending: self.pending.clone(),
+                },
+            );
+        }
+        record_site_health(&self.query, &found);
+        self.ctx.save_learned();
+    }
+}
 
-    // Apply overall cutoff if specified
-    if let Some(cutoff) = args.cutoff
-        && cutoff > 0
-        && combined.len() > cutoff
-    {
-        combined.truncate(cutoff);
+/// Replace the cached results of `query`
+async fn cache_results(
+    app_handle: &tauri::AppHandle,
+    query: &str,
+    results: &[models::SearchResult],
+) {
+    let state = app_handle.state::<AppState>();
+    let mut cache = state.cache().await;
+    cache.add(query.to_string(), results.to_vec());
+    if let Err(e) = state.save_cache(&cache).await {
+        log::warn!("Failed to cache the results of {query:?}: {e}");
     }
+}
 
-    // Emit completion event
+/// Abort a running search; returns false if it had already finished
+#[tauri::command]
+fn cancel_search(
+    app_handle: tauri::AppHandle,
+    jobs: tauri::State<'_, SearchJobs>,
+    job_id: u64,
+) -> bool {
+    let Some(handle) = jobs.finish(job_id) else {
+        return false;
+    };
+    handle.abort();
+    app_handle.state::<pages::SearchPages>().forget(job_id);
     let _ = app_handle.emit(
-        "search:complete",
-        SearchComplete {
-            total_results: combined.len(),
-            sites_completed,
-            sites_failed,
+        "search://done",
+        SearchDone {
+            job_id,
+            total_results: 0,
+            sites_completed: 0,
+            cancelled: true,
+            late_sites: Vec::new(),
+            results: Vec::new(),
+            segments: Vec::new(),
+            groups: None,
+            timings: TimingBreakdown::default(),
         },
     );
-
-    Ok(combined)
+    true
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    let mut builder = tauri::Builder::default();
+    // A link opened while the app runs starts a second instance on Windows and
+    // Linux; this hands the link to the running one (must be the first plugin)
+    #[cfg(desktop)]
+    {
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.set_focus();
+            }
+        }));
+    }
+    builder
+        .manage(SearchJobs::default())
+        .manage(pages::SearchPages::default())
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_dialog::init())
         .setup(|app| {
             // Check environment for log level override
             let log_level = std::env::var("LOG_LEVEL")
@@ -822,119 +2071,73 @@ pub fn run() {
                     .level(log_level)
                     .build(),
             )?;
+            paths::migrate_legacy_files();
+            render_cache::activate(render_cache::RenderCache::new(paths::rendered_dir()));
+            app.manage(AppState::load(get_cache_path()));
+            watchlist::spawn_checker(app.handle().clone());
+            deep_link::init(app);
+            quick::init(app)?;
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             search_gui,
-            search_gui_streaming,
+            parse_query,
+            start_search,
+            cancel_search,
+            pages::search_more,
             list_sites,
+            set_site_prefs,
+            open_result,
+            copy_to_clipboard,
+            get_result_details,
+            report::capture_parser_report,
+            export_results,
+            environment::check_environment,
+            solver::get_solver_status,
+            solver::set_solver_url,
+            solver::reset_solver_sessions,
             get_cache,
             get_cached_results,
             add_to_cache,
             remove_cache_entry,
             clear_cache,
+            get_history,
+            delete_history_entry,
+            clear_history,
             get_cache_settings,
-            set_cache_size
+            set_cache_size,
+            get_settings,
+            set_settings,
+            import_cookie,
+            get_metrics_snapshot,
+            deep_link::take_deep_link,
+            quick::quick_search,
+            quick::open_in_main_window,
+            quick::hide_quick_search,
+            favorites::add_favorite,
+            favorites::list_favorites,
+            favorites::remove_favorite,
+            notes::get_notes,
+            notes::set_note,
+            notes::delete_note,
+            ignore::get_ignore_rules,
+            ignore::add_ignore_rule,
+            ignore::remove_ignore_rule,
+            watchlist::add_watch,
+            watchlist::list_watches,
+            watchlist::delete_watch,
+            watchlist::watch_page,
+            watchlist::list_page_watches,
+            watchlist::delete_page_watch,
+            thumbnails::get_thumbnail
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
-
-// Minimal feed + playwright helpers adapted for GUI context
-async fn fetch_csrin_feed(
-    client: &reqwest::Client,
-    site: &website_searcher_core::models::SiteConfig,
-    query: &str,
-    rate_limiter: Option<&mut RateLimiter>,
-) -> Option<Vec<models::SearchResult>> {
-    let feed_url = "https://cs.rin.ru/forum/feed.php?f=10";
-    let body = fetcher::fetch_with_retry(client, feed_url, rate_limiter, Some("csrin"))
-        .await
-        .ok()?;
-    if body.is_empty() {
-        return None;
-    }
-    let mut results: Vec<models::SearchResult> = Vec::new();
-    let ql = query.to_lowercase();
-    let mut i = 0usize;
-    let mut xml = body;
-    if let Some(pre_idx) = xml.find("<pre")
-        && let Some(tag_end) = xml[pre_idx..].find('>')
-    {
-        let content_start = pre_idx + tag_end + 1;
-        if let Some(close_rel) = xml[content_start..].find("</pre>") {
-            let content_end = content_start + close_rel;
-            let inner = &xml[content_start..content_end];
-            xml = inner
-                .replace("&lt;", "<")
-                .replace("&gt;", ">")
-                .replace("&amp;", "&")
-                .replace("&quot;", "\"")
-                .replace("&#39;", "'");
-        }
-    }
-    while let Some(tidx) = xml[i..].find("<entry>") {
-        let start = i + tidx;
-        let end = xml[start..]
-            .find("</entry>")
-            .map(|e| start + e + 8)
-            .unwrap_or(xml.len());
-        let entry = &xml[start..end];
-        let mut title = "";
-        if let Some(t_open_rel) = entry.find("<title") {
-            let after_tag_rel = entry[t_open_rel..].find('>').map(|p| t_open_rel + p + 1);
-            if let Some(content_start) = after_tag_rel
-                && let Some(close_rel) = entry[content_start..].find("</title>")
-            {
-                let raw = &entry[content_start..content_start + close_rel];
-                let raw = raw.trim();
-                if let Some(inner) = raw.strip_prefix("<![CDATA[") {
-                    if let Some(inner2) = inner.strip_suffix("]]") {
-                        title = inner2.trim();
-                    } else {
-                        title = inner.trim();
-                    }
-                } else {
-                    title = raw;
-                }
-            }
-        }
-        if title.is_empty() {
-            title = entry
-                .split_once("<title>")
-                .and_then(|(_, rest)| rest.split_once("</title>").map(|(t, _)| t))
-                .unwrap_or("")
-                .trim();
-        }
-        let href = entry
-            .split_once("<link href=\"")
-            .and_then(|(_, rest)| rest.split_once('"').map(|(u, _)| u))
-            .unwrap_or("");
-        if !title.is_empty() && href.contains("viewtopic.php") {
-            let tl = title.to_lowercase();
-            if tl.contains(&ql) || href.to_lowercase().contains(&ql.replace(' ', "+")) {
-                let url = if href.starts_with("http") {
-                    href.to_string()
-                } else {
-                    format!("https://cs.rin.ru/forum/{}", href.trim_start_matches('/'))
-                };
-                results.push(models::SearchResult {
-                    site: site.name.to_string(),
-                    title: title.to_string(),
-                    url,
-                });
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                app_handle.state::<AppState>().flush();
             }
-        }
-        i = end;
-        if results.len() >= 50 {
-            break;
-        }
-    }
-    if results.is_empty() {
-        None
-    } else {
-        Some(results)
-    }
+        });
 }
 
 /// Resolve the csrin_search.cjs script path with fallback search order:
@@ -997,16 +2200,28 @@ fn resolve_csrin_script_path() -> Option<std::path::PathBuf> {
     None
 }
 
-async fn fetch_csrin_playwright_html(query: &str, cookie: Option<String>) -> Option<String> {
+async fn fetch_csrin_playwright_html(
+    query: &str,
+    cookie: Option<String>,
+) -> Result<String, Outcome> {
     // Allow tests/dev to inject HTML
     if let Ok(fake) = std::env::var("CS_PLAYWRIGHT_HTML")
         && !fake.trim().is_empty()
     {
-        return Some(fake);
+        return Ok(fake);
     }
+    // The rendered search depends on the page count, so it is part of the key
+    let pages = std::env::var("CSRIN_PAGES").unwrap_or_default();
+    let target = format!("csrin?pages={}&q={}", pages.trim(), query);
+    let key = render_cache::key("playwright", &target, cookie.as_deref());
+    render_cache::through(&key, run_csrin_playwright(query, cookie)).await
+}
 
+/// Render cs.rin.ru's search for `query` with the Node Playwright helper
+async fn run_csrin_playwright(query: &str, cookie: Option<String>) -> Result<String, Outcome> {
     // Resolve script path with fallback order
-    let script_path = resolve_csrin_script_path()?;
+    let script_path = resolve_csrin_script_path()
+        .ok_or_else(|| Outcome::Unavailable("csrin_search.cjs not found".to_string()))?;
 
     let mut cmd = tokio::process::Command::new("node");
     use std::process::Stdio;
@@ -1022,7 +2237,9 @@ async fn fetch_csrin_playwright_html(query: &str, cookie: Option<String>) -> Opt
     cmd.stdin(Stdio::null());
     cmd.stderr(Stdio::inherit());
     cmd.stdout(Stdio::piped());
-    let mut child = cmd.spawn().ok()?;
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| Outcome::Unavailable(format!("failed to start node: {e}")))?;
     use tokio::io::AsyncReadExt;
     let mut out = String::new();
     if let Some(mut so) = child.stdout.take() {
@@ -1030,9 +2247,9 @@ async fn fetch_csrin_playwright_html(query: &str, cookie: Option<String>) -> Opt
     }
     let _ = child.wait().await;
     if out.trim().is_empty() {
-        None
+        Err(Outcome::EmptyResponse)
     } else {
-        Some(out)
+        Ok(out)
     }
 }
 
@@ -1045,15 +2262,17 @@ async fn fetch_gog_games_ajax_json(
     cf_url: &str,
     cookie_headers: Option<ReqHeaderMap>,
     mut rate_limiter: Option<&mut RateLimiter>,
-) -> Option<Vec<models::SearchResult>> {
-    let qenc = urlencoding::encode(query);
-    let urls = vec![
-        format!(
-            "https://gog-games.to/search?search={}&page=1&den_filter=none",
-            qenc
+) -> Result<Vec<models::SearchResult>, Outcome> {
+    // The search page itself, then the endpoints its script calls
+    let search_page = query::build_search_url(site, query);
+    let endpoint = format!("{}search", site.base_url);
+    let urls = [
+        query::with_query(
+            &endpoint,
+            &[("search", query), ("page", "1"), ("den_filter", "none")],
         ),
-        format!("https://gog-games.to/search?page=1&search={}", qenc),
-        format!("https://gog-games.to/?search={}", qenc),
+        query::with_query(&endpoint, &[("page", "1"), ("search", query)]),
+        search_page.clone(),
     ];
 
     // build headers
@@ -1068,7 +2287,7 @@ async fn fetch_gog_games_ajax_json(
     );
     headers.insert(
         REFERER,
-        HeaderValue::from_str(&format!("https://gog-games.to/?search={}", qenc))
+        HeaderValue::from_str(&search_page)
             .unwrap_or(HeaderValue::from_static("https://gog-games.to/")),
     );
     if let Some(ch) = &cookie_headers {
@@ -1077,24 +2296,40 @@ async fn fetch_gog_games_ajax_json(
         }
     }
 
+    // Why the last endpoint tried gave nothing
+    let mut outcome = Outcome::EmptyResponse;
+    // Endpoints often redirect to one another; each final page is read once
+    let mut seen: HashSet<String> = HashSet::new();
     for u in urls.into_iter() {
-        let body: String = if use_cf {
-            (cf::fetch_via_solver_with_headers(client, &u, cf_url, Some(headers.clone())).await)
-                .unwrap_or_default()
+        let fetched = if use_cf {
+            cf::fetch_via_solver_with_headers(client, &u, cf_url, Some(headers.clone()))
+                .await
+                .map(|body| Fetched {
+                    url: u.clone(),
+                    body,
+                })
         } else {
-            (fetcher::fetch_with_retry_headers(
+            fetcher::fetch_with_final_url(
                 client,
                 &u,
                 Some(headers.clone()),
                 rate_limiter.as_deref_mut(),
                 Some("gog-games"),
             )
-            .await)
-                .unwrap_or_default()
+            .await
+        };
+        let body = match fetched {
+            Ok(page) if !seen.insert(page.url.clone()) => continue,
+            Ok(page) if !page.body.is_empty() => page.body,
+            Ok(_) => {
+                outcome = Outcome::EmptyResponse;
+                continue;
+            }
+            Err(e) => {
+                outcome = Outcome::fetch_error(use_cf, &e);
+                continue;
+            }
         };
-        if body.is_empty() {
-            continue;
-        }
         let trimmed = body.trim_start();
         if trimmed.starts_with('<') {
             // Try to extract JSON inside <pre>...</pre>
@@ -1106,7 +2341,7 @@ async fn fetch_gog_games_ajax_json(
                         let mut results: Vec<models::SearchResult> = Vec::new();
                         collect_title_url_pairs(&v, &mut results);
                         if !results.is_empty() {
-                            return Some(results);
+                            return Ok(results);
                         }
                     }
                 }
@@ -1114,15 +2349,16 @@ async fn fetch_gog_games_ajax_json(
             // else treat as HTML fragment
             let rs = parser::parse_results(site, &body, query);
             if !rs.is_empty() {
-                return Some(rs);
+                return Ok(rs);
             }
+            outcome = Outcome::of_page(site, &body, query, 0);
             continue;
         }
         if let Ok(v) = serde_json::from_str::<serde_json::Value>(&body) {
             if let Some(html) = v.get("html").and_then(|x| x.as_str()) {
                 let rs = parser::parse_results(site, html, query);
                 if !rs.is_empty() {
-                    return Some(rs);
+                    return Ok(rs);
                 }
             }
             if let Some(html) = v
@@ -1132,17 +2368,18 @@ async fn fetch_gog_games_ajax_json(
             {
                 let rs = parser::parse_results(site, html, query);
                 if !rs.is_empty() {
-                    return Some(rs);
+                    return Ok(rs);
                 }
             }
             let mut results: Vec<models::SearchResult> = Vec::new();
             collect_title_url_pairs(&v, &mut results);
             if !results.is_empty() {
-                return Some(results);
+                return Ok(results);
             }
+            outcome = Outcome::NoMatches(0);
         }
     }
-    None
+    Err(outcome)
 }
 
 #[allow(clippy::collapsible_if)]
@@ -1184,9 +2421,10 @@ fn collect_title_url_pairs(v: &serde_json::Value, out: &mut Vec<models::SearchRe
                     u
                 };
                 out.push(models::SearchResult {
-                    site: "gog-games".to_string(),
+                    site: "gog-games".into(),
                     title: t.to_string(),
                     url: u_abs,
+                    ..Default::default()
                 });
             }
             for val in map.values() {
@@ -1203,36 +2441,76 @@ fn collect_title_url_pairs(v: &serde_json::Value, out: &mut Vec<models::SearchRe
 }
 
 fn filter_results_by_query_strict(results: &mut Vec<models::SearchResult>, query: &str) {
-    let ql = query.to_lowercase();
-    let ql_dash = ql.replace(' ', "-");
-    let ql_plus = ql.replace(' ', "+");
-    let ql_encoded = ql.replace(' ', "%20");
-    let ql_stripped = ql.replace(' ', "");
+    let variants = parser::QueryVariants::new(query);
+    let (mut tl, mut ul) = (String::new(), String::new());
     results.retain(|r| {
-        let tl = r.title.to_lowercase();
-        let ul = r.url.to_lowercase();
-        let matches = tl.contains(&ql)
-            || ul.contains(&ql)
-            || ul.contains(&ql_dash)
-            || ul.contains(&ql_plus)
-            || ul.contains(&ql_encoded)
-            || ul.contains(&ql_stripped);
-        let gog_path_ok = ul.contains("/game/") || ul.contains("/games/");
-        matches && gog_path_ok
+        parser::lowercase_into(&mut tl, &r.title);
+        parser::lowercase_into(&mut ul, &r.url);
+        variants.matches(&tl, &ul)
     });
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use website_searcher_core::error::ApiErrorCategory;
 
     #[tokio::test]
     async fn list_sites_returns_all_configs() {
         let sites = list_sites().await.unwrap();
         assert!(!sites.is_empty());
         // Should contain well-known sites
-        assert!(sites.iter().any(|s| s.eq_ignore_ascii_case("fitgirl")));
-        assert!(sites.iter().any(|s| s.eq_ignore_ascii_case("dodi")));
+        assert!(sites.iter().any(|s| s.name.eq_ignore_ascii_case("fitgirl")));
+        assert!(sites.iter().any(|s| s.name.eq_ignore_ascii_case("dodi")));
+    }
+
+    #[test]
+    fn site_infos_follow_saved_order_and_enabled_sites() {
+        let defaults = SearchDefaults {
+            sites: vec!["dodi".into()],
+            site_order: vec!["dodi".into(), "fitgirl".into()],
+            ..SearchDefaults::default()
+        };
+        let infos = site_infos(config::site_configs(), &defaults);
+        assert_eq!(infos.len(), config::site_configs().len());
+        assert_eq!(infos[0].name, "dodi");
+        assert_eq!(infos[1].name, "fitgirl");
+        assert!(infos[0].enabled);
+        assert!(!infos[1].enabled);
+    }
+
+    #[test]
+    fn validate_open_url_allows_only_web_and_magnet_links() {
+        assert!(validate_open_url("https://fitgirl-repacks.site/elden-ring/").is_ok());
+        assert!(validate_open_url(" http://a.example/x ").is_ok());
+        assert!(validate_open_url("magnet:?xt=urn:btih:abc").is_ok());
+        assert!(validate_open_url("file:///etc/passwd").is_err());
+        assert!(validate_open_url("javascript:alert(1)").is_err());
+        assert!(validate_open_url("not a url").is_err());
+    }
+
+    #[test]
+    fn site_cookie_keeps_only_cookies_for_the_site() {
+        let csrin = config::site_configs()
+            .into_iter()
+            .find(|s| s.name == "csrin")
+            .unwrap();
+        let (header, import) = site_cookie(&csrin, "sid=1; cf_clearance=x").unwrap();
+        assert_eq!(header, "sid=1; cf_clearance=x");
+        assert_eq!(import.domains, ["cs.rin.ru"]);
+
+        let txt = "# Netscape HTTP Cookie File\n\
+            .rin.ru\tTRUE\t/\tTRUE\t0\tsid\tabc\n\
+            .fitgirl-repacks.site\tTRUE\t/\tTRUE\t0\tother\tx\n";
+        let (header, import) = site_cookie(&csrin, txt).unwrap();
+        assert_eq!(header, "sid=abc");
+        assert_eq!(import.names, ["sid"]);
+        assert_eq!(import.domains, ["rin.ru"]);
+        assert_eq!(import.other_domains, ["fitgirl-repacks.site"]);
+
+        let err = site_cookie(&csrin, ".example.com\tTRUE\t/\tTRUE\t0\ta\tb\n").unwrap_err();
+        assert!(err.message.contains("example.com"));
+        assert_eq!(err.category, ApiErrorCategory::InvalidInput);
     }
 
     #[test]
@@ -1242,11 +2520,13 @@ mod tests {
                 site: "gog-games".into(),
                 title: "Elden Ring".into(),
                 url: "https://gog-games.to/game/elden-ring".into(),
+                ..Default::default()
             },
             models::SearchResult {
                 site: "gog-games".into(),
                 title: "Other Game".into(),
                 url: "https://gog-games.to/game/other".into(),
+                ..Default::default()
             },
         ];
         filter_results_by_query_strict(&mut results, "elden ring");
@@ -1260,6 +2540,7 @@ mod tests {
             site: "gog-games".into(),
             title: "A Long Title".into(),
             url: "https://gog-games.to/game/elden-ring".into(),
+            ..Default::default()
         }];
         filter_results_by_query_strict(&mut results, "elden ring");
         assert_eq!(results.len(), 1);
@@ -1317,14 +2598,21 @@ mod tests {
                 site: "gog-games".into(),
                 title: "Elden Ring".into(),
                 url: "https://gog-games.to/game/elden-ring".into(),
+                ..Default::default()
             },
             models::SearchResult {
                 site: "gog-games".into(),
                 title: "Other".into(),
                 url: "https://gog-games.to/search?q=elden".into(),
+                ..Default::default()
             },
         ];
         filter_results_by_query_strict(&mut results, "elden ring");
+        let gog = config::site_configs()
+            .into_iter()
+            .find(|s| s.name == "gog-games")
+            .unwrap();
+        gog.rules.apply(&mut results);
         assert_eq!(results.len(), 1);
         assert!(results[0].url.contains("/game/"));
     }
@@ -1336,11 +2624,13 @@ mod tests {
                 site: "gog-games".into(),
                 title: "Some Title".into(),
                 url: "https://gog-games.to/game/elden%20ring".into(),
+                ..Default::default()
             },
             models::SearchResult {
                 site: "gog-games".into(),
                 title: "Some Title".into(),
                 url: "https://gog-games.to/games/elden+ring".into(),
+                ..Default::default()
             },
         ];
         filter_results_by_query_strict(&mut results, "elden ring");
@@ -1380,7 +2670,7 @@ mod tests {
         let result = fetch_csrin_playwright_html("test", None).await;
         // SAFETY: Cleaning up test env var
         unsafe { std::env::remove_var("CS_PLAYWRIGHT_HTML") };
-        assert!(result.is_some());
+        assert!(result.is_ok());
         assert!(result.unwrap().contains("test content"));
     }
 
@@ -1400,10 +2690,16 @@ mod tests {
             csrin_search: None,
             no_playwright: None,
             no_rate_limit: None,
+            advanced: None,
+            group: None,
+            hide_status: None,
+            profile: None,
         };
-        let result = search_gui(args).await;
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("empty"));
+        let state = AppState::load(std::env::temp_dir().join("ws-missing-cache.json"));
+        let result = search_once(&state, args).await;
+        let err = result.unwrap_err();
+        assert!(err.message.contains("empty"));
+        assert_eq!(err.category, ApiErrorCategory::InvalidInput);
     }
 
     #[test]
@@ -1444,6 +2740,7 @@ mod tests {
             site: "gog-games".into(),
             title: "Some Title".into(),
             url: "https://gog-games.to/game/elden+ring".into(),
+            ..Default::default()
         }];
         filter_results_by_query_strict(&mut results, "elden ring");
         assert_eq!(results.len(), 1);
@@ -1455,6 +2752,7 @@ mod tests {
             site: "gog-games".into(),
             title: "Some Title".into(),
             url: "https://gog-games.to/game/eldenring".into(),
+            ..Default::default()
         }];
         filter_results_by_query_strict(&mut results, "elden ring");
         assert_eq!(results.len(), 1);
@@ -1469,6 +2767,275 @@ mod tests {
         assert!(out[0].url.contains("href"));
     }
 
+    #[test]
+    fn finish_results_dedups_filters_and_cuts_off() {
+        let result = |site: &str, title: &str, url: &str| models::SearchResult {
+            site: site.into(),
+            title: title.into(),
+            url: url.into(),
+            ..Default::default()
+        };
+        let combined = vec![
+            result("fitgirl", "Elden Ring", "https://a.example/1"),
+            result("dodi", "Elden Ring", "https://b.example/1"),
+            result("fitgirl", "Elden Ring", "https://a.example/1"),
+            result("fitgirl", "Elden Ring Demo", "https://a.example/2"),
+        ];
+        let query = MultiQuery::parse("elden ring -demo");
+        let results = finish_results(combined.clone(), Some(&query), None);
+        assert_eq!(results.len(), 2);
+        assert_eq!(&*results[0].site, "dodi");
+
+        assert_eq!(finish_results(combined, None, Some(1)).len(), 1);
+    }
+
+    #[test]
+    fn advanced_queries_route_segments_to_their_sites() {
+        let mut args = SearchArgs {
+            query: "elden ring site:fitgirl | hades -demo".to_string(),
+            ..Default::default()
+        };
+        let state = AppState::load(std::env::temp_dir().join("ws-missing-cache.json"));
+        let ctx = SiteSearch::new(&args, &SearchDefaults::default(), &state);
+        assert_eq!(ctx.queries_for("fitgirl"), ["elden ring", "hades"]);
+        assert_eq!(ctx.queries_for("dodi"), ["hades"]);
+
+        let result = |site: &str, title: &str| models::SearchResult {
+            site: site.into(),
+            title: title.into(),
+            url: format!("https://{site}.example/{}", title.replace(' ', "-")),
+            ..Default::default()
+        };
+        let combined = vec![
+            result("fitgirl", "Elden Ring"),
+            result("fitgirl", "Hades"),
+            result("dodi", "Hades"),
+            result("dodi", "Hades Demo"),
+            result("dodi", "Elden Ring"),
+        ];
+        let results = finish_results(combined, ctx.multi_query.as_ref(), None);
+        let titles: Vec<_> = results
+            .iter()
+            .map(|r| format!("{}:{}", r.site, r.title))
+            .collect();
+        assert_eq!(
+            titles,
+            ["dodi:Hades", "fitgirl:Elden Ring", "fitgirl:Hades"]
+        );
+
+        let segments = segment_matches(ctx.multi_query.as_ref(), &results);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].sites, ["fitgirl"]);
+        assert_eq!(segments[0].results, [1]);
+        assert_eq!(segments[1].query, "hades -demo");
+        assert_eq!(segments[1].results, [0, 2]);
+
+        // Plain queries and an explicit opt-out skip the operators
+        args.advanced = Some(false);
+        assert!(parse_multi_query(&args).is_none());
+        args.query = "elden ring".into();
+        args.advanced = None;
+        assert!(parse_multi_query(&args).is_none());
+    }
+
+    #[test]
+    fn segments_for_the_same_site_are_searched_separately() {
+        let args = SearchArgs {
+            query: "elden ring | minecraft".to_string(),
+            ..Default::default()
+        };
+        let state = AppState::load(std::env::temp_dir().join("ws-missing-cache.json"));
+        let ctx = SiteSearch::new(&args, &SearchDefaults::default(), &state);
+        assert_eq!(ctx.queries_for("fitgirl"), ["elden ring", "minecraft"]);
+        assert_eq!(ctx.queries_for("dodi"), ["elden ring", "minecraft"]);
+
+        // Load more pages through the first search, whichever finished last
+        let site = models::SiteConfig {
+            name: "repacks".into(),
+            base_url: "https://a.example/".into(),
+            query_param: Some("s".into()),
+            ..Default::default()
+        };
+        ctx.note_page(
+            &site,
+            "elden ring",
+            1,
+            "https://a.example/?s=elden+ring",
+            "",
+        );
+        ctx.note_page(&site, "minecraft", 1, "https://a.example/?s=minecraft", "");
+        let next = ctx.take_cursor(&site).next.unwrap();
+        assert!(
+            next.contains("/page/2/") && next.contains("elden"),
+            "{next}"
+        );
+    }
+
+    #[test]
+    fn query_breakdown_shows_operators_and_each_sites_terms() {
+        let args = SearchArgs {
+            query: r#"elden ring -demo site:fitgirl | "hollow knight" regex:v1\.\d site:dodi"#
+                .to_string(),
+            ..Default::default()
+        };
+        let sites = ["fitgirl".to_string(), "dodi".into(), "elamigos".into()];
+        let breakdown = query_breakdown(&args, &sites);
+        assert!(breakdown.advanced);
+        assert_eq!(
+            breakdown.segments[0],
+            QuerySegment {
+                query: "elden ring -demo site:fitgirl".into(),
+                terms: vec!["elden".into(), "ring".into()],
+                phrases: Vec::new(),
+                exclude: vec!["demo".into()],
+                regex: Vec::new(),
+                sites: vec!["fitgirl".into()],
+            }
+        );
+        assert_eq!(breakdown.segments[1].phrases, ["hollow knight"]);
+        assert_eq!(breakdown.segments[1].regex, [r"v1\.\d"]);
+        let terms: Vec<&[String]> = breakdown.sites.iter().map(|s| &s.terms[..]).collect();
+        assert_eq!(terms, [&["elden ring"][..], &["hollow knight"], &[]]);
+
+        // Segments that both apply to a site are two searches, not one
+        let both = SearchArgs {
+            query: "elden ring | minecraft site:dodi".to_string(),
+            ..Default::default()
+        };
+        let breakdown = query_breakdown(&both, &sites[..2]);
+        assert_eq!(breakdown.sites[0].terms, ["elden ring"]);
+        assert_eq!(breakdown.sites[1].terms, ["elden ring", "minecraft"]);
+
+        let plain = SearchArgs {
+            query: "elden ring".to_string(),
+            ..Default::default()
+        };
+        let breakdown = query_breakdown(&plain, &sites[..1]);
+        assert!(!breakdown.advanced && breakdown.segments.is_empty());
+        assert_eq!(breakdown.sites[0].terms, ["elden ring"]);
+    }
+
+    #[test]
+    fn duplicate_groups_are_only_built_on_request() {
+        let results = vec![
+            models::SearchResult {
+                site: "fitgirl".into(),
+                title: "Hades v1.38".into(),
+                url: "https://a.example/hades".into(),
+                ..Default::default()
+            },
+            models::SearchResult {
+                site: "dodi".into(),
+                title: "Hades".into(),
+                url: "https://b.example/hades".into(),
+                ..Default::default()
+            },
+        ];
+        let mut args = SearchArgs {
+            query: "hades".into(),
+            ..Default::default()
+        };
+        assert!(duplicate_groups(&args, &results).is_none());
+
+        args.group = Some(true);
+        let groups = duplicate_groups(&args, &results).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].title, "Hades");
+        assert_eq!(groups[0].sites, ["fitgirl", "dodi"]);
+    }
+
+    #[tokio::test]
+    async fn form_values_override_saved_defaults() {
+        let mut defaults = SearchDefaults {
+            sites: vec!["fitgirl".into()],
+            limit: 25,
+            ..SearchDefaults::default()
+        };
+        defaults.cookies.insert("csrin".into(), "sid=saved".into());
+
+        let mut args = SearchArgs {
+            query: "elden ring".to_string(),
+            limit: None,
+            cutoff: None,
+            sites: None,
+            debug: None,
+            verbose: None,
+            no_cf: None,
+            cf_url: None,
+            cookie: None,
+            csrin_pages: None,
+            csrin_search: None,
+            no_playwright: None,
+            no_rate_limit: None,
+            advanced: None,
+            group: None,
+            hide_status: None,
+            profile: None,
+        };
+        let state = AppState::load(std::env::temp_dir().join("ws-missing-cache.json"));
+        let ctx = SiteSearch::new(&args, &defaults, &state);
+        assert_eq!(ctx.limit, 25);
+        assert!(Arc::ptr_eq(
+            ctx.rate_limiter.as_ref().unwrap(),
+            &state.rate_limiter
+        ));
+        let site = |name: &str| {
+            config::site_configs()
+                .into_iter()
+                .find(|s| s.name == name)
+                .unwrap()
+        };
+        assert_eq!(
+            ctx.cookie_headers_for(&site("csrin")).await.unwrap()[COOKIE],
+            "sid=saved"
+        );
+        assert!(ctx.cookie_headers_for(&site("dodi")).await.is_none());
+        let sites = selected_sites(&args, &defaults);
+        assert_eq!(sites.len(), 1);
+        assert_eq!(sites[0].name, "fitgirl");
+
+        args.limit = Some(5);
+        args.cookie = Some("sid=typed".into());
+        args.sites = Some(vec!["dodi".into()]);
+        let ctx = SiteSearch::new(&args, &defaults, &state);
+        assert_eq!(ctx.limit, 5);
+        assert_eq!(
+            ctx.cookie_headers_for(&site("csrin")).await.unwrap()[COOKIE],
+            "sid=typed"
+        );
+        assert_eq!(selected_sites(&args, &defaults)[0].name, "dodi");
+    }
+
+    #[test]
+    fn result_pages_off_the_site_get_no_cookie() {
+        let mut defaults = SearchDefaults::default();
+        defaults.cookies.insert("csrin".into(), "sid=saved".into());
+        let site = models::SiteConfig {
+            name: "csrin".into(),
+            base_url: "https://cs.rin.example/forum/".into(),
+            ..Default::default()
+        };
+        let cookie =
+            |url: &str| page_cookie_headers(&defaults, &site, url).map(|h| h[COOKIE].clone());
+        assert_eq!(
+            cookie("https://cs.rin.example/forum/viewtopic.php?t=1").unwrap(),
+            "sid=saved"
+        );
+        assert!(cookie("https://files.cs.rin.example/t/1").is_some());
+        assert!(cookie("https://evil.example/forum/viewtopic.php?t=1").is_none());
+        assert!(cookie("https://notcs.rin.example.evil/").is_none());
+        assert!(cookie("https://rin.example/").is_none());
+    }
+
+    #[test]
+    fn search_jobs_finish_only_once() {
+        let jobs = SearchJobs::default();
+        let handle = tauri::async_runtime::spawn(async {});
+        jobs.running.lock().unwrap().insert(1, handle);
+        assert!(jobs.finish(1).is_some());
+        assert!(jobs.finish(1).is_none());
+    }
+
     #[tokio::test]
     async fn search_gui_with_site_filter() {
         // Test with a specific site filter that should return immediately
@@ -1486,11 +3053,16 @@ mod tests {
             csrin_search: None,
             no_playwright: Some(true),
             no_rate_limit: None,
+            advanced: None,
+            group: None,
+            hide_status: None,
+            profile: None,
         };
-        let result = search_gui(args).await;
+        let state = AppState::load(std::env::temp_dir().join("ws-missing-cache.json"));
+        let result = search_once(&state, args).await;
         // Should succeed but return empty (no matching site)
         assert!(result.is_ok());
-        assert!(result.unwrap().is_empty());
+        assert!(result.unwrap().results.is_empty());
     }
 
     #[tokio::test]
@@ -1498,16 +3070,16 @@ mod tests {
         unsafe { std::env::set_var("CS_PLAYWRIGHT_HTML", "<html>cookie test</html>") };
         let result = fetch_csrin_playwright_html("test", Some("session=abc".to_string())).await;
         unsafe { std::env::remove_var("CS_PLAYWRIGHT_HTML") };
-        assert!(result.is_some());
+        assert!(result.is_ok());
         assert!(result.unwrap().contains("cookie test"));
     }
 
     #[tokio::test]
-    async fn fetch_csrin_playwright_empty_env_returns_none() {
+    async fn fetch_csrin_playwright_empty_env_returns_err() {
         unsafe { std::env::set_var("CS_PLAYWRIGHT_HTML", "   ") };
         let result = fetch_csrin_playwright_html("test", None).await;
         unsafe { std::env::remove_var("CS_PLAYWRIGHT_HTML") };
         // Empty env is treated as not set, script doesn't exist in test env
-        assert!(result.is_none());
+        assert!(result.is_err());
     }
 }
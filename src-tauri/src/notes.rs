@@ -0,0 +1,63 @@
+//! Statuses and notes on result URLs, kept in `notes.json` in the user data
+//! directory and shared with the CLI's `annotate` command (see [`Notes`]).
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use website_searcher_core::error::ApiError;
+use website_searcher_core::models::SearchResult;
+use website_searcher_core::notes::{Note, NoteStatus, Notes};
+use website_searcher_core::paths;
+
+/// Serializes load/modify/save of the file between commands
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Attach the saved notes to `results` and drop those whose status is in `hide`
+pub fn apply(results: &mut Vec<SearchResult>, hide: &[NoteStatus]) {
+    Notes::load(&paths::notes_file()).apply(results, hide);
+}
+
+fn save(notes: &Notes) -> Result<(), ApiError> {
+    notes
+        .save(&paths::notes_file())
+        .map_err(|e| ApiError::storage(format!("failed to save notes: {e:#}")))
+}
+
+/// Every note, by URL
+#[tauri::command]
+pub async fn get_notes() -> Result<BTreeMap<String, Note>, ApiError> {
+    let notes = Notes::load(&paths::notes_file());
+    Ok(notes
+        .iter()
+        .map(|(url, note)| (url.to_string(), note.clone()))
+        .collect())
+}
+
+/// Replace the note on `url`; with neither status nor text it is removed and
+/// `None` returned
+#[tauri::command]
+pub async fn set_note(
+    url: String,
+    status: Option<NoteStatus>,
+    text: Option<String>,
+) -> Result<Option<Note>, ApiError> {
+    if url.trim().is_empty() {
+        return Err(ApiError::invalid_input("empty result URL"));
+    }
+    let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut notes = Notes::load(&paths::notes_file());
+    let saved = notes.set(&url, status, text);
+    save(&notes)?;
+    Ok(saved)
+}
+
+#[tauri::command]
+pub async fn delete_note(url: String) -> Result<bool, ApiError> {
+    let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut notes = Notes::load(&paths::notes_file());
+    let removed = notes.remove(&url);
+    if removed {
+        save(&notes)?;
+    }
+    Ok(removed)
+}
@@ -0,0 +1,229 @@
+//! "Load more" for streaming searches: `search_more` continues one site of a
+//! finished search from where its results stopped, returning only results the
+//! search has not shown yet.
+//!
+//! Results past the per-site limit are kept from the search itself and handed
+//! out first; after that each call fetches the site's next page.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use website_searcher_core::error::ApiError;
+use website_searcher_core::models::{Provenance, SearchResult};
+use website_searcher_core::settings::{Settings, default_settings_path};
+use website_searcher_core::{pagination, parser, sanitize, steam};
+
+use crate::state::AppState;
+
+/// Searches whose pages are kept; older ones can no longer load more
+const KEPT_JOBS: usize = 8;
+
+/// Where one site's results stopped
+#[derive(Debug, Clone, Default)]
+pub struct PageCursor {
+    /// Last page fetched (1-based)
+    pub page: usize,
+    /// URL of the next page; `None` once the site has no more
+    pub next: Option<String>,
+    /// Results already found but not shown yet
+    pub leftover: Vec<SearchResult>,
+    /// URLs already shown
+    pub seen: HashSet<String>,
+}
+
+impl PageCursor {
+    fn has_more(&self) -> bool {
+        !self.leftover.is_empty() || self.next.is_some()
+    }
+}
+
+struct JobPages {
+    args: crate::SearchArgs,
+    sites: HashMap<String, PageCursor>,
+}
+
+/// Page cursors of recent streaming searches, by job id
+#[derive(Default)]
+pub struct SearchPages(Mutex<BTreeMap<u64, JobPages>>);
+
+impl SearchPages {
+    /// Start keeping pages for a new search, forgetting the oldest ones
+    pub fn start(&self, job_id: u64, args: crate::SearchArgs) {
+        let mut jobs = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        jobs.insert(
+            job_id,
+            JobPages {
+                args,
+                sites: HashMap::new(),
+            },
+        );
+        while jobs.len() > KEPT_JOBS {
+            jobs.pop_first();
+        }
+    }
+
+    /// Save where `site` stopped, once its results have been shown
+    pub fn record(&self, job_id: u64, site: &str, mut cursor: PageCursor, shown: &[SearchResult]) {
+        cursor.seen.extend(shown.iter().map(|r| r.url.clone()));
+        let mut jobs = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(job) = jobs.get_mut(&job_id) {
+            job.sites.insert(site.to_lowercase(), cursor);
+        }
+    }
+
+    pub fn forget(&self, job_id: u64) {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&job_id);
+    }
+
+    fn cursor(&self, job_id: u64, site: &str) -> Option<(crate::SearchArgs, PageCursor)> {
+        let jobs = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let job = jobs.get(&job_id)?;
+        let cursor = job.sites.get(&site.to_lowercase())?;
+        Some((job.args.clone(), cursor.clone()))
+    }
+
+    fn update(&self, job_id: u64, site: &str, cursor: PageCursor) {
+        let mut jobs = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(job) = jobs.get_mut(&job_id) {
+            job.sites.insert(site.to_lowercase(), cursor);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MoreResults {
+    /// Results not shown before, in the site's order
+    pub results: Vec<SearchResult>,
+    /// Whether another call may find more
+    pub has_more: bool,
+}
+
+/// Take up to `limit` results from `found` that have not been seen, marking them seen
+fn take_new(cursor: &mut PageCursor, found: Vec<SearchResult>, limit: usize) -> Vec<SearchResult> {
+    let mut new = Vec::new();
+    let mut rest = Vec::new();
+    for result in found {
+        if cursor.seen.contains(&result.url) {
+            continue;
+        }
+        if new.len() < limit {
+            cursor.seen.insert(result.url.clone());
+            new.push(result);
+        } else {
+            rest.push(result);
+        }
+    }
+    cursor.leftover = rest;
+    new
+}
+
+/// Next results of `site` for a finished streaming search
+#[tauri::command]
+pub async fn search_more(
+    state: tauri::State<'_, AppState>,
+    pages: tauri::State<'_, SearchPages>,
+    job_id: u64,
+    site: String,
+) -> Result<MoreResults, ApiError> {
+    let Some((args, mut cursor)) = pages.cursor(job_id, &site) else {
+        return Err(ApiError::not_found(format!(
+            "no finished search {job_id} with results from {site}"
+        )));
+    };
+    let site_config = crate::find_site(&site)?;
+    let defaults = Settings::load(&default_settings_path()).search;
+    let ctx = crate::SiteSearch::new(&args, &defaults, &state);
+
+    let mut results = if !cursor.leftover.is_empty() {
+        let leftover = std::mem::take(&mut cursor.leftover);
+        let leftover =
+            crate::filter_site_results(ctx.multi_query.as_ref(), &site_config.name, leftover);
+        take_new(&mut cursor, leftover, ctx.limit)
+    } else if let Some(url) = cursor.next.take() {
+        let query = ctx.query_for(&site_config.name);
+        let html = ctx
+            .fetch(&site_config, &url)
+            .await
+            .map_err(|e| ApiError::from_fetch(&e).for_site(&site_config.name))?
+            .body;
+        let next = pagination::next_page(&site_config, &query, cursor.page + 1, &url, &html);
+        let mut found = parser::parse_results_blocking(&site_config, html, &query).await;
+        parser::stamp_source(
+            &mut found,
+            &url,
+            parser::fetched_now(),
+            Provenance::of_page(site_config.search_kind, ctx.uses_solver(&site_config)),
+        );
+        sanitize::sanitize_results(&mut found);
+        if site_config.name.eq_ignore_ascii_case("gog-games") {
+            crate::filter_results_by_query_strict(&mut found, &query);
+        }
+        ctx.retain_wanted(&site_config, &query, &mut found);
+        let found = crate::filter_site_results(ctx.multi_query.as_ref(), &site_config.name, found);
+        let new = take_new(&mut cursor, found, ctx.limit);
+        cursor.page += 1;
+        // A page with nothing new means the site has run out (or ignores paging)
+        if !new.is_empty() {
+            cursor.next = next;
+        }
+        new
+    } else {
+        Vec::new()
+    };
+    if defaults.steam_appids {
+        steam::annotate(&ctx.client, &mut results).await;
+    }
+    crate::ignore::apply(&mut results);
+    crate::notes::apply(&mut results, args.hidden_statuses());
+    let has_more = cursor.has_more();
+    pages.update(job_id, &site, cursor);
+    Ok(MoreResults { results, has_more })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(url: &str) -> SearchResult {
+        SearchResult {
+            site: "example".into(),
+            title: url.into(),
+            url: url.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn only_unseen_results_are_taken_up_to_the_limit() {
+        let mut cursor = PageCursor::default();
+        cursor.seen.insert("a".into());
+        let found = ["a", "b", "c", "d"].map(result).to_vec();
+        let new = take_new(&mut cursor, found, 2);
+        assert_eq!(new, ["b", "c"].map(result));
+        assert_eq!(cursor.leftover, ["d"].map(result));
+        assert!(cursor.seen.contains("c") && !cursor.seen.contains("d"));
+        assert!(cursor.has_more());
+
+        let leftover = std::mem::take(&mut cursor.leftover);
+        assert_eq!(take_new(&mut cursor, leftover, 2), ["d"].map(result));
+        assert!(!cursor.has_more());
+    }
+
+    #[test]
+    fn only_recent_jobs_are_kept() {
+        let pages = SearchPages::default();
+        for job_id in 1..=(KEPT_JOBS as u64 + 1) {
+            pages.start(job_id, crate::SearchArgs::default());
+            pages.record(job_id, "FitGirl", PageCursor::default(), &[result("a")]);
+        }
+        assert!(pages.cursor(1, "fitgirl").is_none());
+        let (_, cursor) = pages.cursor(2, "fitgirl").unwrap();
+        assert!(cursor.seen.contains("a"));
+        pages.forget(2);
+        assert!(pages.cursor(2, "fitgirl").is_none());
+    }
+}
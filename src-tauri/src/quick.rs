@@ -0,0 +1,241 @@
+//! Tray icon, global shortcut and the small always-on-top quick-search window.
+//!
+//! Quick searches only go to sites that answer a plain request (no solver,
+//! JavaScript or Playwright) and return whatever arrived by a short deadline.
+//! With the tray in place, closing the main window hides it instead of quitting.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::stream::{FuturesUnordered, StreamExt};
+use serde::Serialize;
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_global_shortcut::ShortcutState;
+use website_searcher_core::error::ApiError;
+use website_searcher_core::models::{SearchResult, SiteConfig};
+use website_searcher_core::settings::{Settings, default_settings_path};
+
+use crate::deep_link::{self, DeepLinkSearch};
+use crate::state::AppState;
+
+/// Opens or hides the quick-search window from anywhere
+pub const SHORTCUT: &str = "CommandOrControl+Alt+Space";
+const WINDOW_LABEL: &str = "quick";
+/// Quick searches return what has arrived by then
+const DEADLINE: Duration = Duration::from_secs(6);
+/// Results per site in a quick search
+const LIMIT: usize = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuickSearchResponse {
+    pub results: Vec<SearchResult>,
+    /// Sites that had not answered by the deadline
+    pub timed_out: Vec<String>,
+}
+
+/// Sites a quick search can use: no solver, JavaScript or Playwright needed
+fn is_fast(site: &SiteConfig) -> bool {
+    !site.requires_cloudflare && !site.requires_js && !site.name.eq_ignore_ascii_case("csrin")
+}
+
+/// Fast sites among the default ones, or every fast site if none of those are
+fn fast_sites(sites: Vec<SiteConfig>, enabled: impl Fn(&str) -> bool) -> Vec<SiteConfig> {
+    let fast: Vec<SiteConfig> = sites.into_iter().filter(is_fast).collect();
+    let preferred: Vec<SiteConfig> = fast.iter().filter(|s| enabled(&s.name)).cloned().collect();
+    if preferred.is_empty() {
+        fast
+    } else {
+        preferred
+    }
+}
+
+/// Search the fast sites with a few results each, stopping at the deadline
+#[tauri::command]
+pub async fn quick_search(
+    state: tauri::State<'_, AppState>,
+    query: String,
+) -> Result<QuickSearchResponse, ApiError> {
+    if query.trim().is_empty() {
+        return Err(ApiError::invalid_input("empty search phrase"));
+    }
+    let defaults = Settings::load(&default_settings_path()).search;
+    let sites = fast_sites(crate::known_sites(), |name| defaults.site_enabled(name));
+    let args = crate::SearchArgs {
+        query,
+        limit: Some(LIMIT),
+        no_cf: Some(true),
+        no_playwright: Some(true),
+        ..Default::default()
+    };
+    let ctx = Arc::new(crate::SiteSearch::new(&args, &defaults, &state));
+
+    let mut tasks: FuturesUnordered<_> = sites
+        .into_iter()
+        .map(|site| {
+            let ctx = ctx.clone();
+            async move {
+                let found = tokio::time::timeout(DEADLINE, crate::search_site(&ctx, &site)).await;
+                (site.name, found)
+            }
+        })
+        .collect();
+    let mut combined = Vec::new();
+    let mut timed_out = Vec::new();
+    while let Some((site, found)) = tasks.next().await {
+        match found {
+            Ok(mut results) => combined.append(&mut results),
+            Err(_) => timed_out.push(site),
+        }
+    }
+    timed_out.sort();
+    Ok(QuickSearchResponse {
+        results: crate::finish_results(combined, ctx.multi_query.as_ref(), None),
+        timed_out,
+    })
+}
+
+/// Run `query` as a full search in the main window and hide the quick window
+#[tauri::command]
+pub fn open_in_main_window(app_handle: tauri::AppHandle, query: String) -> Result<(), ApiError> {
+    let query = query.trim().to_string();
+    if query.is_empty() {
+        return Err(ApiError::invalid_input("empty search phrase"));
+    }
+    hide_quick_window(&app_handle);
+    deep_link::queue_search(&app_handle, DeepLinkSearch { query, sites: None });
+    Ok(())
+}
+
+#[tauri::command]
+pub fn hide_quick_search(app_handle: tauri::AppHandle) {
+    hide_quick_window(&app_handle);
+}
+
+fn hide_quick_window(app_handle: &tauri::AppHandle) {
+    if let Some(window) = app_handle.get_webview_window(WINDOW_LABEL) {
+        let _ = window.hide();
+    }
+}
+
+/// Show the quick-search window, creating it on first use; hide it if focused
+fn toggle_quick_window(app_handle: &tauri::AppHandle) {
+    if let Some(window) = app_handle.get_webview_window(WINDOW_LABEL) {
+        if window.is_visible().unwrap_or(false) && window.is_focused().unwrap_or(false) {
+            let _ = window.hide();
+        } else {
+            let _ = window.center();
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+        return;
+    }
+    let built = WebviewWindowBuilder::new(
+        app_handle,
+        WINDOW_LABEL,
+        WebviewUrl::App("index.html?view=quick".into()),
+    )
+    .title("Quick search")
+    .inner_size(560.0, 380.0)
+    .resizable(false)
+    .decorations(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .center()
+    .build();
+    if let Err(e) = built {
+        log::warn!("Failed to open quick search: {}", e);
+    }
+}
+
+fn show_main_window(app_handle: &tauri::AppHandle) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Add the tray icon and the global shortcut, and keep the app in the tray
+/// when the main window is closed
+pub fn init(app: &tauri::App) -> tauri::Result<()> {
+    let quick = MenuItem::with_id(app, "quick", "Quick search", true, None::<&str>)?;
+    let show = MenuItem::with_id(app, "show", "Show window", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let menu = Menu::with_items(app, &[&quick, &show, &separator, &quit])?;
+
+    let mut tray = TrayIconBuilder::with_id("main")
+        .tooltip("Website Searcher")
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app_handle, event| match event.id.as_ref() {
+            "quick" => toggle_quick_window(app_handle),
+            "show" => show_main_window(app_handle),
+            "quit" => app_handle.exit(0),
+            _ => {}
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        tray = tray.icon(icon.clone());
+    }
+    tray.build(app)?;
+
+    // Another program may already own the shortcut; the tray still works then
+    let registered = tauri_plugin_global_shortcut::Builder::new()
+        .with_shortcut(SHORTCUT)
+        .map_err(|e| e.to_string())
+        .and_then(|builder| {
+            let plugin = builder
+                .with_handler(|app_handle, _shortcut, event| {
+                    if event.state() == ShortcutState::Pressed {
+                        toggle_quick_window(app_handle);
+                    }
+                })
+                .build();
+            app.handle().plugin(plugin).map_err(|e| e.to_string())
+        });
+    if let Err(e) = registered {
+        log::warn!("Failed to register {}: {}", SHORTCUT, e);
+    }
+
+    if let Some(window) = app.get_webview_window("main") {
+        let hidden = window.clone();
+        window.on_window_event(move |event| {
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                api.prevent_close();
+                let _ = hidden.hide();
+            }
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use website_searcher_core::config;
+
+    #[test]
+    fn quick_searches_skip_slow_sites_and_prefer_defaults() {
+        let all = fast_sites(config::site_configs(), |_| false);
+        assert!(!all.is_empty());
+        assert!(all.iter().all(is_fast));
+        assert!(!all.iter().any(|s| s.name == "csrin"));
+
+        let first = all[0].name.clone();
+        let preferred = fast_sites(config::site_configs(), |name| name == first);
+        assert_eq!(preferred.len(), 1);
+        assert_eq!(preferred[0].name, first);
+    }
+}
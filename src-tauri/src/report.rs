@@ -0,0 +1,141 @@
+//! "Report broken parser": fetch a site's search page once and save the raw
+//! HTML with parser diagnostics to a zip users can attach to an issue.
+//!
+//! Reports are only written to `reports/` in the app data directory; nothing is
+//! uploaded.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tauri::Manager;
+use website_searcher_core::error::ApiError;
+use website_searcher_core::parser::{self, ParserDiagnostics};
+use website_searcher_core::settings::{Settings, default_settings_path};
+use zip::write::SimpleFileOptions;
+
+use crate::state::AppState;
+
+/// What goes in `report.json` next to the page
+#[derive(Debug, Clone, Serialize)]
+struct ReportInfo<'a> {
+    app_version: &'a str,
+    /// Unix timestamp (seconds) of the capture
+    captured_at: u64,
+    url: &'a str,
+    diagnostics: &'a ParserDiagnostics,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ParserReport {
+    /// Where the zip was written
+    pub path: String,
+    pub diagnostics: ParserDiagnostics,
+}
+
+/// Write `page.html` and `report.json` into a new zip at `path`
+fn write_report(path: &Path, html: &str, info: &ReportInfo) -> Result<(), ApiError> {
+    let json = serde_json::to_string_pretty(info).map_err(|e| ApiError::internal(e.to_string()))?;
+    let written = (|| -> zip::result::ZipResult<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(path)?);
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        zip.start_file("page.html", options)?;
+        zip.write_all(html.as_bytes())?;
+        zip.start_file("report.json", options)?;
+        zip.write_all(json.as_bytes())?;
+        zip.finish()?;
+        Ok(())
+    })();
+    written.map_err(|e| ApiError::storage(format!("failed to write {}: {}", path.display(), e)))
+}
+
+fn reports_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, ApiError> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join("reports"))
+        .map_err(|e| ApiError::storage(e.to_string()))
+}
+
+/// Fetch `site`'s search page for `query` and save it with parser diagnostics
+#[tauri::command]
+pub async fn capture_parser_report(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    site: String,
+    query: String,
+) -> Result<ParserReport, ApiError> {
+    let query = query.trim().to_string();
+    if query.is_empty() {
+        return Err(ApiError::invalid_input("empty search phrase"));
+    }
+    let site = crate::find_site(&site)?;
+    let defaults = Settings::load(&default_settings_path()).search;
+    let url = crate::search_page_url(&site, &query);
+    let html = crate::fetch_page(&state.client, &defaults, &site.name, &url).await?;
+    let diagnostics = parser::diagnose(&site, &html, &query);
+
+    let captured_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let path = reports_dir(&app_handle)?.join(format!("{}-{}.zip", site.name, captured_at));
+    let info = ReportInfo {
+        app_version: env!("CARGO_PKG_VERSION"),
+        captured_at,
+        url: &url,
+        diagnostics: &diagnostics,
+    };
+    write_report(&path, &html, &info)?;
+    Ok(ParserReport {
+        path: path.display().to_string(),
+        diagnostics,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use website_searcher_core::config;
+
+    #[test]
+    fn report_zip_holds_page_and_diagnostics() {
+        let site = config::site_configs().remove(0);
+        let html = "<html><head><title>No results</title></head></html>";
+        let diagnostics = parser::diagnose(&site, html, "hades");
+        let info = ReportInfo {
+            app_version: "1.0.0",
+            captured_at: 1,
+            url: "https://example.com/?s=hades",
+            diagnostics: &diagnostics,
+        };
+        let dir = std::env::temp_dir().join(format!("ws-report-{}", std::process::id()));
+        let path = dir.join("reports").join("site-1.zip");
+        write_report(&path, html, &info).unwrap();
+
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let mut page = String::new();
+        archive
+            .by_name("page.html")
+            .unwrap()
+            .read_to_string(&mut page)
+            .unwrap();
+        assert_eq!(page, html);
+        let mut json = String::new();
+        archive
+            .by_name("report.json")
+            .unwrap()
+            .read_to_string(&mut json)
+            .unwrap();
+        let report: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(report["url"], "https://example.com/?s=hades");
+        assert_eq!(report["diagnostics"]["page_title"], "No results");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
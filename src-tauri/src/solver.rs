@@ -0,0 +1,157 @@
+//! FlareSolverr commands: see which solver searches use, point them at another
+//! one and clear sessions that got stuck, all without restarting the app.
+//!
+//! The URL is kept in `config.toml` (`search.cf_url`), which every search reads,
+//! so a change applies to the next search.
+
+use serde::Serialize;
+use website_searcher_core::cf;
+use website_searcher_core::error::{ApiError, ApiErrorCategory};
+use website_searcher_core::settings::{SearchDefaults, Settings, default_settings_path};
+
+use crate::state::AppState;
+
+/// Where the solver URL in use came from
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UrlSource {
+    Settings,
+    /// The `CF_URL` environment variable
+    Env,
+    Default,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SolverStatus {
+    pub url: String,
+    pub source: UrlSource,
+    pub reachable: bool,
+    pub version: Option<String>,
+    /// Ids of the sessions open in the solver
+    pub sessions: Vec<String>,
+    /// Why the solver could not be reached
+    pub error: Option<String>,
+}
+
+/// Mirrors `resolve_cf_url`: a saved non-default URL wins over `CF_URL`
+fn url_source(defaults: &SearchDefaults, env_cf: Option<&str>) -> UrlSource {
+    let env_set = env_cf.is_some_and(|v| !v.trim().is_empty());
+    match defaults.cf_url.as_deref() {
+        Some(url) if url != crate::DEFAULT_CF_URL => UrlSource::Settings,
+        _ if env_set => UrlSource::Env,
+        Some(_) => UrlSource::Settings,
+        None => UrlSource::Default,
+    }
+}
+
+/// Check a URL typed by the user; empty means "use the default"
+fn parse_solver_url(url: Option<String>) -> Result<Option<String>, ApiError> {
+    let Some(url) = url.map(|u| u.trim().to_string()).filter(|u| !u.is_empty()) else {
+        return Ok(None);
+    };
+    let parsed = reqwest::Url::parse(&url)
+        .map_err(|e| ApiError::invalid_input(format!("invalid solver URL: {e}")))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(ApiError::invalid_input(format!(
+            "solver URL must be http or https, not {}",
+            parsed.scheme()
+        )));
+    }
+    Ok(Some(url))
+}
+
+/// The solver searches will use, and whether it answers
+#[tauri::command]
+pub async fn get_solver_status(
+    state: tauri::State<'_, AppState>,
+) -> Result<SolverStatus, ApiError> {
+    let defaults = Settings::load(&default_settings_path()).search;
+    let url = crate::resolve_cf_url(None, &defaults);
+    let source = url_source(&defaults, std::env::var("CF_URL").ok().as_deref());
+    let status = match cf::list_sessions(&state.client, &url).await {
+        Ok(open) => SolverStatus {
+            url,
+            source,
+            reachable: true,
+            version: open.version,
+            sessions: open.sessions,
+            error: None,
+        },
+        Err(e) => SolverStatus {
+            url,
+            source,
+            reachable: false,
+            version: None,
+            sessions: Vec::new(),
+            error: Some(format!("{e:#}")),
+        },
+    };
+    Ok(status)
+}
+
+/// Save the solver URL for later searches; `None` or empty goes back to the default
+#[tauri::command]
+pub async fn set_solver_url(
+    state: tauri::State<'_, AppState>,
+    url: Option<String>,
+) -> Result<SolverStatus, ApiError> {
+    let url = parse_solver_url(url)?;
+    let path = default_settings_path();
+    let mut settings = Settings::load(&path);
+    settings.search.cf_url = url;
+    crate::save_settings(&settings, &path)?;
+    get_solver_status(state).await
+}
+
+/// Destroy all solver sessions and return how many there were
+#[tauri::command]
+pub async fn reset_solver_sessions(state: tauri::State<'_, AppState>) -> Result<usize, ApiError> {
+    let defaults = Settings::load(&default_settings_path()).search;
+    let url = crate::resolve_cf_url(None, &defaults);
+    cf::reset_sessions(&state.client, &url)
+        .await
+        .map_err(|e| ApiError::new(ApiErrorCategory::SolverUnavailable, format!("{e:#}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_follows_resolve_order() {
+        let mut defaults = SearchDefaults::default();
+        assert_eq!(url_source(&defaults, None), UrlSource::Default);
+        assert_eq!(url_source(&defaults, Some(" ")), UrlSource::Default);
+        assert_eq!(
+            url_source(&defaults, Some("http://env:8191/v1")),
+            UrlSource::Env
+        );
+
+        defaults.cf_url = Some(crate::DEFAULT_CF_URL.to_string());
+        assert_eq!(
+            url_source(&defaults, Some("http://env:8191/v1")),
+            UrlSource::Env
+        );
+        assert_eq!(url_source(&defaults, None), UrlSource::Settings);
+
+        defaults.cf_url = Some("http://solver.lan:8191/v1".to_string());
+        assert_eq!(
+            url_source(&defaults, Some("http://env:8191/v1")),
+            UrlSource::Settings
+        );
+    }
+
+    #[test]
+    fn solver_urls_are_validated() {
+        assert_eq!(parse_solver_url(None), Ok(None));
+        let invalid = parse_solver_url(Some("solver.lan".into())).unwrap_err();
+        assert_eq!(invalid.category, ApiErrorCategory::InvalidInput);
+        assert_eq!(parse_solver_url(Some("  ".into())), Ok(None));
+        assert_eq!(
+            parse_solver_url(Some(" http://solver.lan:8191/v1 ".into())),
+            Ok(Some("http://solver.lan:8191/v1".to_string()))
+        );
+        assert!(parse_solver_url(Some("solver.lan".into())).is_err());
+        assert!(parse_solver_url(Some("ftp://solver.lan/v1".into())).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+//! State shared by every command for the lifetime of the app: the result cache,
+//! one HTTP client, the rate limiter and per-site circuit breakers.
+//!
+//! The cache is read from disk once in `setup` and written back after each
+//! change, so commands never re-read the file.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use website_searcher_core::cache::SearchCache;
+use website_searcher_core::error::ApiError;
+use website_searcher_core::fetcher;
+use website_searcher_core::paths;
+use website_searcher_core::rate_limiter::RateLimiter;
+use website_searcher_core::resilience::{CircuitBreaker, CircuitState};
+use website_searcher_core::result_index;
+
+pub struct AppState {
+    cache: tokio::sync::Mutex<SearchCache>,
+    cache_path: PathBuf,
+    /// Set when writing the cache failed, so the exit flush tries again
+    cache_dirty: AtomicBool,
+    pub client: reqwest::Client,
+    pub rate_limiter: Arc<tokio::sync::Mutex<RateLimiter>>,
+    pub breakers: Arc<Breakers>,
+}
+
+impl AppState {
+    /// Load the cache from `cache_path`, starting empty if it is missing or invalid
+    pub fn load(cache_path: PathBuf) -> Self {
+        let cache = if cache_path.exists() {
+            SearchCache::load_from_file_sync(&cache_path).unwrap_or_else(|e| {
+                log::warn!("Ignoring invalid cache file {:?}: {}", cache_path, e);
+                SearchCache::with_default_size()
+            })
+        } else {
+            SearchCache::with_default_size()
+        };
+        Self {
+            cache: tokio::sync::Mutex::new(cache),
+            cache_path,
+            cache_dirty: AtomicBool::new(false),
+            client: fetcher::build_http_client(),
+            rate_limiter: Arc::new(tokio::sync::Mutex::new(RateLimiter::new())),
+            breakers: Arc::new(Breakers::default()),
+        }
+    }
+
+    pub async fn cache(&self) -> tokio::sync::MutexGuard<'_, SearchCache> {
+        self.cache.lock().await
+    }
+
+    /// Write the cache after a change; call while still holding its guard
+    pub async fn save_cache(&self, cache: &SearchCache) -> Result<(), ApiError> {
+        let saved = cache.save_to_file(&self.cache_path).await;
+        self.cache_dirty.store(saved.is_err(), Ordering::Release);
+        if saved.is_ok()
+            && let Err(e) = result_index::index_cache(&paths::result_index_file(), cache)
+        {
+            log::warn!("Failed to update the result index: {}", e);
+        }
+        saved.map_err(|e| ApiError::storage(format!("failed to save cache: {e:#}")))
+    }
+
+    /// Write the cache if the last save failed; used when the app exits
+    pub fn flush(&self) {
+        if !self.cache_dirty.load(Ordering::Acquire) {
+            return;
+        }
+        let Ok(cache) = self.cache.try_lock() else {
+            return;
+        };
+        if let Err(e) = cache.save_to_file_sync(&self.cache_path) {
+            log::warn!("Failed to write cache on exit: {}", e);
+        }
+    }
+}
+
+/// One circuit breaker per site, created on first use
+#[derive(Default)]
+pub struct Breakers {
+    by_site: std::sync::Mutex<HashMap<String, Arc<CircuitBreaker>>>,
+}
+
+impl Breakers {
+    /// Breaker for `site`; names are matched case-insensitively
+    pub fn get(&self, site: &str) -> Arc<CircuitBreaker> {
+        let mut by_site = self.by_site.lock().unwrap_or_else(|e| e.into_inner());
+        by_site
+            .entry(site.to_lowercase())
+            .or_insert_with(|| Arc::new(CircuitBreaker::new(site)))
+            .clone()
+    }
+
+    /// State of every breaker created so far, sorted by site
+    pub fn snapshot(&self) -> Vec<BreakerStatus> {
+        let by_site = self.by_site.lock().unwrap_or_else(|e| e.into_inner());
+        let mut statuses: Vec<BreakerStatus> = by_site
+            .values()
+            .map(|b| BreakerStatus {
+                site: b.site_name().to_string(),
+                state: b.state(),
+                failures: b.failure_count(),
+            })
+            .collect();
+        statuses.sort_by(|a, b| a.site.cmp(&b.site));
+        statuses
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, PartialEq, Eq)]
+pub struct BreakerStatus {
+    pub site: String,
+    pub state: CircuitState,
+    /// Consecutive failures counted towards tripping
+    pub failures: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use website_searcher_core::models::SearchResult;
+
+    #[test]
+    fn breakers_are_shared_per_site() {
+        let breakers = Breakers::default();
+        let fitgirl = breakers.get("fitgirl");
+        fitgirl.record_failure();
+        assert_eq!(breakers.get("FitGirl").failure_count(), 1);
+        assert_eq!(breakers.get("dodi").failure_count(), 0);
+
+        let snapshot = breakers.snapshot();
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot[0].site, "dodi");
+        assert_eq!(snapshot[1].failures, 1);
+        assert_eq!(snapshot[1].state, CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn cache_changes_are_written_and_reloaded() {
+        let dir = std::env::temp_dir().join(format!("ws-state-{}", std::process::id()));
+        let path = dir.join("search_cache.json");
+        let state = AppState::load(path.clone());
+        {
+            let mut cache = state.cache().await;
+            assert!(cache.is_empty());
+            cache.add(
+                "hades".into(),
+                vec![SearchResult {
+                    site: "fitgirl".into(),
+                    title: "Hades".into(),
+                    url: "https://a.example/hades".into(),
+                    ..Default::default()
+                }],
+            );
+            state.save_cache(&cache).await.unwrap();
+        }
+
+        let reloaded = AppState::load(path);
+        assert_eq!(reloaded.cache().await.len(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
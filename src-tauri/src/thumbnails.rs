@@ -0,0 +1,121 @@
+//! Result images for the cards, fetched once through the site's route and
+//! kept in the cache directory (see [`ThumbnailCache`]), so drawing a card
+//! loads nothing from the site.
+
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use reqwest::header::CONTENT_TYPE;
+use website_searcher_core::error::ApiError;
+use website_searcher_core::paths;
+use website_searcher_core::route::Routes;
+use website_searcher_core::settings::{Settings, default_settings_path};
+use website_searcher_core::thumbnails::{self, Thumbnail, ThumbnailCache};
+
+use crate::state::AppState;
+
+/// How long one image may take to download
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// The image at `url`, found on a result of `site`, as a `data:` URL; fetched
+/// and saved the first time it is asked for
+#[tauri::command]
+pub async fn get_thumbnail(
+    state: tauri::State<'_, AppState>,
+    url: String,
+    site: String,
+) -> Result<String, ApiError> {
+    let url = reqwest::Url::parse(url.trim())
+        .map_err(|e| ApiError::invalid_input(format!("invalid image URL: {e}")))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(ApiError::invalid_input("only web images are fetched"));
+    }
+    let cache = ThumbnailCache::new(paths::thumbnails_dir());
+    let thumbnail = match cache.get(url.as_str()) {
+        Some(thumbnail) => thumbnail,
+        None => {
+            let (content_type, bytes) =
+                download(&state.client, &site, &url, cache.max_image_bytes()).await?;
+            cache
+                .put(url.as_str(), &content_type, bytes)
+                .map_err(|e| ApiError::storage(format!("failed to save image: {e:#}")))?
+        }
+    };
+    Ok(data_url(&thumbnail))
+}
+
+/// Delete every saved image
+pub fn clear() {
+    ThumbnailCache::new(paths::thumbnails_dir()).clear();
+}
+
+fn data_url(thumbnail: &Thumbnail) -> String {
+    format!(
+        "data:{};base64,{}",
+        thumbnail.content_type,
+        STANDARD.encode(&thumbnail.bytes)
+    )
+}
+
+/// Content type and body of the image at `url`, through `site`'s route;
+/// anything but a kept image type, or over `max_bytes`, is refused
+async fn download(
+    client: &reqwest::Client,
+    site: &str,
+    url: &reqwest::Url,
+    max_bytes: u64,
+) -> Result<(String, Vec<u8>), ApiError> {
+    let defaults = Settings::load(&default_settings_path()).search;
+    let client = Routes::new(&defaults.routes)
+        .client_for(site, client)
+        .map_err(|e| ApiError::invalid_input(e.to_string()))?;
+    let fetch_error = |e: reqwest::Error| ApiError::from_fetch(&e.into()).for_site(site);
+    let mut response = client
+        .get(url.clone())
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(fetch_error)?;
+
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    if thumbnails::image_type(&content_type).is_none() {
+        return Err(ApiError::invalid_input(format!(
+            "not an image ({content_type})"
+        )));
+    }
+    let too_large =
+        || ApiError::invalid_input(format!("image larger than {} KiB", max_bytes / 1024));
+    if response.content_length().is_some_and(|len| len > max_bytes) {
+        return Err(too_large());
+    }
+    // The length header can be missing or wrong, so the body is capped too
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(fetch_error)? {
+        if (bytes.len() + chunk.len()) as u64 > max_bytes {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok((content_type, bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn images_are_served_as_data_urls() {
+        let thumbnail = Thumbnail {
+            content_type: "image/png",
+            bytes: vec![0x89, b'P', b'N', b'G'],
+        };
+        assert_eq!(data_url(&thumbnail), "data:image/png;base64,iVBORw==");
+    }
+}
@@ -0,0 +1,595 @@
+//! Watched searches, kept in `watchlist.json` in the user data directory
+//! (see [`paths`]).
+//!
+//! A background task re-runs each watch on its interval and raises a desktop
+//! notification when results show up that earlier checks had not seen. Watched
+//! result pages are fetched the same way and reported as updated when the hash
+//! of their content changes (a new build posted in a forum thread, a repack
+//! re-uploaded). The task belongs to the app process, not to a window, so it
+//! keeps running while the window is hidden.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+use website_searcher_core::details;
+use website_searcher_core::error::ApiError;
+use website_searcher_core::models::SearchResult;
+use website_searcher_core::paths;
+use website_searcher_core::settings::{Settings, default_settings_path};
+
+/// Shortest allowed time between two checks of one watch
+pub const MIN_INTERVAL_MINUTES: u64 = 15;
+const DEFAULT_INTERVAL_MINUTES: u64 = 60;
+/// How often the background task looks for due watches
+const TICK: Duration = Duration::from_secs(60);
+
+/// Serializes load/modify/save of the file between commands and the checker
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WatchEntry {
+    pub id: u64,
+    pub query: String,
+    /// Sites to search; empty means the saved default sites
+    pub sites: Vec<String>,
+    pub interval_minutes: u64,
+    /// Unix timestamp (seconds) when the watch was created
+    pub created_at: u64,
+    /// Unix timestamp of the last finished check
+    pub last_checked: Option<u64>,
+    /// Results that were new on the most recent check that found any
+    pub new_results: Vec<SearchResult>,
+    /// URLs seen by earlier checks
+    #[serde(default)]
+    pub known_urls: Vec<String>,
+}
+
+impl WatchEntry {
+    fn is_due(&self, now: u64) -> bool {
+        self.last_checked
+            .is_none_or(|last| now >= last + self.interval_minutes * 60)
+    }
+}
+
+/// A result page checked for changes to its content
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PageWatch {
+    pub id: u64,
+    pub url: String,
+    /// Site the result came from, for its cookie and solver settings
+    pub site: String,
+    pub title: String,
+    pub interval_minutes: u64,
+    /// Unix timestamp (seconds) when the watch was created
+    pub created_at: u64,
+    /// Unix timestamp of the last successful check
+    pub last_checked: Option<u64>,
+    /// [`details::content_hash`] of the page at the last check
+    pub content_hash: Option<String>,
+    /// Unix timestamp of the last check that found the content changed
+    pub last_changed: Option<u64>,
+}
+
+impl PageWatch {
+    fn is_due(&self, now: u64) -> bool {
+        self.last_checked
+            .is_none_or(|last| now >= last + self.interval_minutes * 60)
+    }
+}
+
+/// Sent as "watchlist://page_updated" when a watched page changes
+#[derive(Debug, Clone, Serialize)]
+pub struct PageUpdate {
+    pub id: u64,
+    pub url: String,
+    pub title: String,
+    pub changed_at: u64,
+}
+
+/// Sent as "watchlist://updated" when a check finds new results
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchUpdate {
+    pub id: u64,
+    pub query: String,
+    pub new_results: Vec<SearchResult>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Watchlist {
+    entries: Vec<WatchEntry>,
+    #[serde(default)]
+    pages: Vec<PageWatch>,
+    next_id: u64,
+}
+
+impl Watchlist {
+    /// Load the watchlist, starting empty if the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Add a watch; the interval is raised to `MIN_INTERVAL_MINUTES` if shorter
+    pub fn add(
+        &mut self,
+        query: &str,
+        sites: Vec<String>,
+        interval_minutes: u64,
+        now: u64,
+    ) -> WatchEntry {
+        self.next_id += 1;
+        let entry = WatchEntry {
+            id: self.next_id,
+            query: query.trim().to_string(),
+            sites,
+            interval_minutes: interval_minutes.max(MIN_INTERVAL_MINUTES),
+            created_at: now,
+            last_checked: None,
+            new_results: Vec::new(),
+            known_urls: Vec::new(),
+        };
+        self.entries.push(entry.clone());
+        entry
+    }
+
+    /// Remove the watch with `id`; returns false if there was none
+    pub fn remove(&mut self, id: u64) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.id != id);
+        self.entries.len() != before
+    }
+
+    /// Watches in the order they were added
+    pub fn list(&self) -> Vec<WatchEntry> {
+        self.entries.clone()
+    }
+
+    /// Watches that were never checked or whose interval has passed
+    pub fn due(&self, now: u64) -> Vec<WatchEntry> {
+        self.entries
+            .iter()
+            .filter(|e| e.is_due(now))
+            .cloned()
+            .collect()
+    }
+
+    /// Store the results of a check and return the ones not seen before.
+    ///
+    /// The first check only records a baseline, so it never reports anything new.
+    pub fn apply(&mut self, id: u64, results: &[SearchResult], now: u64) -> Vec<SearchResult> {
+        let Some(entry) = self.entries.iter_mut().find(|e| e.id == id) else {
+            return Vec::new();
+        };
+        let baseline = entry.last_checked.is_none();
+        let mut new = Vec::new();
+        for r in results {
+            if !entry.known_urls.contains(&r.url) {
+                entry.known_urls.push(r.url.clone());
+                if !baseline {
+                    new.push(r.clone());
+                }
+            }
+        }
+        if !new.is_empty() {
+            entry.new_results = new.clone();
+        }
+        entry.last_checked = Some(now);
+        new
+    }
+
+    /// Watch a result page; watching a URL again returns the existing watch
+    pub fn add_page(
+        &mut self,
+        url: &str,
+        site: &str,
+        title: &str,
+        interval_minutes: u64,
+        now: u64,
+    ) -> PageWatch {
+        let url = url.trim();
+        if let Some(page) = self.pages.iter().find(|p| p.url == url) {
+            return page.clone();
+        }
+        self.next_id += 1;
+        let page = PageWatch {
+            id: self.next_id,
+            url: url.to_string(),
+            site: site.to_string(),
+            title: title.trim().to_string(),
+            interval_minutes: interval_minutes.max(MIN_INTERVAL_MINUTES),
+            created_at: now,
+            last_checked: None,
+            content_hash: None,
+            last_changed: None,
+        };
+        self.pages.push(page.clone());
+        page
+    }
+
+    /// Remove the page watch with `id`; returns false if there was none
+    pub fn remove_page(&mut self, id: u64) -> bool {
+        let before = self.pages.len();
+        self.pages.retain(|p| p.id != id);
+        self.pages.len() != before
+    }
+
+    /// Page watches in the order they were added
+    pub fn pages(&self) -> Vec<PageWatch> {
+        self.pages.clone()
+    }
+
+    /// Page watches that were never checked or whose interval has passed
+    pub fn due_pages(&self, now: u64) -> Vec<PageWatch> {
+        self.pages
+            .iter()
+            .filter(|p| p.is_due(now))
+            .cloned()
+            .collect()
+    }
+
+    /// Store the content hash of a check and return whether it changed.
+    ///
+    /// The first check only records a baseline, so it never reports a change.
+    pub fn apply_page(&mut self, id: u64, content_hash: String, now: u64) -> bool {
+        let Some(page) = self.pages.iter_mut().find(|p| p.id == id) else {
+            return false;
+        };
+        let changed = page
+            .content_hash
+            .as_ref()
+            .is_some_and(|old| *old != content_hash);
+        if changed {
+            page.last_changed = Some(now);
+        }
+        page.content_hash = Some(content_hash);
+        page.last_checked = Some(now);
+        changed
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// The watchlist file, after moving one older versions kept in Tauri's app
+/// data directory
+fn watchlist_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, ApiError> {
+    let path = paths::watchlist_file();
+    if let Ok(dir) = app_handle.path().app_data_dir() {
+        let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        paths::migrate_file(&dir.join("watchlist.json"), &path)
+            .map_err(|e| ApiError::storage(format!("failed to move watchlist: {e}")))?;
+    }
+    Ok(path)
+}
+
+/// Load, change and save the watchlist while holding the file lock
+fn update<T>(path: &Path, f: impl FnOnce(&mut Watchlist) -> T) -> Result<T, ApiError> {
+    let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut list = Watchlist::load(path);
+    let out = f(&mut list);
+    list.save(path)
+        .map_err(|e| ApiError::storage(format!("failed to save watchlist: {e}")))?;
+    Ok(out)
+}
+
+#[tauri::command]
+pub async fn add_watch(
+    app_handle: tauri::AppHandle,
+    query: String,
+    sites: Option<Vec<String>>,
+    interval_minutes: Option<u64>,
+) -> Result<WatchEntry, ApiError> {
+    if query.trim().is_empty() {
+        return Err(ApiError::invalid_input("empty search phrase"));
+    }
+    let path = watchlist_path(&app_handle)?;
+    update(&path, |list| {
+        list.add(
+            &query,
+            sites.unwrap_or_default(),
+            interval_minutes.unwrap_or(DEFAULT_INTERVAL_MINUTES),
+            now_secs(),
+        )
+    })
+}
+
+#[tauri::command]
+pub async fn list_watches(app_handle: tauri::AppHandle) -> Result<Vec<WatchEntry>, ApiError> {
+    let path = watchlist_path(&app_handle)?;
+    let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(Watchlist::load(&path).list())
+}
+
+#[tauri::command]
+pub async fn delete_watch(app_handle: tauri::AppHandle, id: u64) -> Result<bool, ApiError> {
+    let path = watchlist_path(&app_handle)?;
+    update(&path, |list| list.remove(id))
+}
+
+/// Watch a result page for changes; `site` picks its cookie and solver settings
+#[tauri::command]
+pub async fn watch_page(
+    app_handle: tauri::AppHandle,
+    url: String,
+    site: String,
+    title: String,
+    interval_minutes: Option<u64>,
+) -> Result<PageWatch, ApiError> {
+    let parsed = crate::validate_open_url(&url)?;
+    if parsed.scheme() == "magnet" {
+        return Err(ApiError::invalid_input(
+            "magnet links have no page to watch",
+        ));
+    }
+    let path = watchlist_path(&app_handle)?;
+    update(&path, |list| {
+        list.add_page(
+            &url,
+            &site,
+            &title,
+            interval_minutes.unwrap_or(DEFAULT_INTERVAL_MINUTES),
+            now_secs(),
+        )
+    })
+}
+
+#[tauri::command]
+pub async fn list_page_watches(app_handle: tauri::AppHandle) -> Result<Vec<PageWatch>, ApiError> {
+    let path = watchlist_path(&app_handle)?;
+    let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(Watchlist::load(&path).pages())
+}
+
+#[tauri::command]
+pub async fn delete_page_watch(app_handle: tauri::AppHandle, id: u64) -> Result<bool, ApiError> {
+    let path = watchlist_path(&app_handle)?;
+    update(&path, |list| list.remove_page(id))
+}
+
+/// Start the background task that re-runs due watches
+pub fn spawn_checker(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut tick = tokio::time::interval(TICK);
+        loop {
+            tick.tick().await;
+            if let Err(e) = check_due(&app_handle).await {
+                log::warn!("Watchlist check failed: {}", e);
+            }
+        }
+    });
+}
+
+async fn check_due(app_handle: &tauri::AppHandle) -> Result<(), ApiError> {
+    let path = watchlist_path(app_handle)?;
+    let (due, due_pages) = {
+        let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let list = Watchlist::load(&path);
+        (list.due(now_secs()), list.due_pages(now_secs()))
+    };
+    for entry in due {
+        let results = match search(app_handle, &entry).await {
+            Ok(results) => results,
+            Err(e) => {
+                log::warn!("Watch {:?} failed: {}", entry.query, e);
+                continue;
+            }
+        };
+        // Re-load so a watch deleted during the search stays deleted
+        let new = update(&path, |list| list.apply(entry.id, &results, now_secs()))?;
+        if new.is_empty() {
+            continue;
+        }
+        notify(app_handle, &entry.query, &new);
+        let _ = app_handle.emit(
+            "watchlist://updated",
+            WatchUpdate {
+                id: entry.id,
+                query: entry.query.clone(),
+                new_results: new,
+            },
+        );
+    }
+    for page in due_pages {
+        let hash = match fetch_content_hash(app_handle, &page).await {
+            Ok(hash) => hash,
+            Err(e) => {
+                // Retried on the next tick; a failed fetch is not a change
+                log::warn!("Page watch {:?} failed: {}", page.url, e);
+                continue;
+            }
+        };
+        let now = now_secs();
+        if !update(&path, |list| list.apply_page(page.id, hash, now))? {
+            continue;
+        }
+        notify_page(app_handle, &page.title);
+        let _ = app_handle.emit(
+            "watchlist://page_updated",
+            PageUpdate {
+                id: page.id,
+                url: page.url.clone(),
+                title: page.title.clone(),
+                changed_at: now,
+            },
+        );
+    }
+    Ok(())
+}
+
+/// Fetch a watched page the way its site is searched and hash its content
+async fn fetch_content_hash(
+    app_handle: &tauri::AppHandle,
+    page: &PageWatch,
+) -> Result<String, ApiError> {
+    let defaults = Settings::load(&default_settings_path()).search;
+    let state = app_handle.state::<crate::AppState>();
+    let html = crate::fetch_page(&state.client, &defaults, &page.site, &page.url).await?;
+    Ok(details::content_hash(&html))
+}
+
+/// Run a watch like a search from the form, without recording it in the history
+async fn search(
+    app_handle: &tauri::AppHandle,
+    entry: &WatchEntry,
+) -> Result<Vec<SearchResult>, ApiError> {
+    let args = crate::SearchArgs {
+        query: entry.query.clone(),
+        sites: (!entry.sites.is_empty()).then(|| entry.sites.clone()),
+        ..Default::default()
+    };
+    let defaults = Settings::load(&default_settings_path()).search;
+    let sites = crate::selected_sites(&args, &defaults);
+    let state = app_handle.state::<crate::AppState>();
+    crate::search_sites(&state, &args, &defaults, &sites)
+        .await
+        .map(|(results, _)| results)
+}
+
+fn notify(app_handle: &tauri::AppHandle, query: &str, new: &[SearchResult]) {
+    let body = match new {
+        [only] => only.title.clone(),
+        [first, rest @ ..] => format!("{} and {} more", first.title, rest.len()),
+        [] => return,
+    };
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title(format!("New results for \"{}\"", query))
+        .body(body)
+        .show()
+    {
+        log::warn!("Failed to show notification: {}", e);
+    }
+}
+
+fn notify_page(app_handle: &tauri::AppHandle, title: &str) {
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title("Watched page updated")
+        .body(title)
+        .show()
+    {
+        log::warn!("Failed to show notification: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(title: &str) -> SearchResult {
+        SearchResult {
+            site: "fitgirl".into(),
+            title: title.into(),
+            url: format!("https://a.example/{}", title.to_lowercase()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn first_check_is_a_baseline_and_later_checks_report_new_urls() {
+        let mut list = Watchlist::default();
+        let id = list.add("elden ring", vec![], 60, 1_000).id;
+
+        assert!(list.apply(id, &[result("Base")], 1_000).is_empty());
+        let new = list.apply(id, &[result("Base"), result("Dlc")], 5_000);
+        assert_eq!(new, [result("Dlc")]);
+        assert!(list.apply(id, &[result("Dlc")], 9_000).is_empty());
+
+        let entry = &list.list()[0];
+        assert_eq!(entry.new_results, [result("Dlc")]);
+        assert_eq!(entry.last_checked, Some(9_000));
+        assert!(list.apply(id + 1, &[result("Other")], 9_000).is_empty());
+    }
+
+    #[test]
+    fn watches_are_due_after_their_interval() {
+        let mut list = Watchlist::default();
+        let entry = list.add("hades", vec!["dodi".into()], 1, 0);
+        assert_eq!(entry.interval_minutes, MIN_INTERVAL_MINUTES);
+        assert_eq!(list.due(0).len(), 1);
+
+        list.apply(entry.id, &[], 100);
+        assert!(list.due(100 + MIN_INTERVAL_MINUTES * 60 - 1).is_empty());
+        assert_eq!(list.due(100 + MIN_INTERVAL_MINUTES * 60).len(), 1);
+
+        assert!(list.remove(entry.id));
+        assert!(!list.remove(entry.id));
+        assert!(list.due(u64::MAX / 2).is_empty());
+    }
+
+    #[test]
+    fn page_changes_are_reported_after_the_baseline() {
+        let mut list = Watchlist::default();
+        let page = list.add_page(
+            " https://a.example/elden-ring/ ",
+            "fitgirl",
+            "Elden Ring",
+            60,
+            0,
+        );
+        assert_eq!(page.url, "https://a.example/elden-ring/");
+        assert_eq!(
+            list.add_page(
+                "https://a.example/elden-ring/",
+                "fitgirl",
+                "Elden Ring",
+                30,
+                5
+            )
+            .id,
+            page.id
+        );
+        assert_eq!(list.due_pages(0).len(), 1);
+
+        assert!(!list.apply_page(page.id, "aaaa".into(), 100));
+        assert!(!list.apply_page(page.id, "aaaa".into(), 4_000));
+        assert!(list.due_pages(4_000).is_empty());
+        assert!(list.apply_page(page.id, "bbbb".into(), 8_000));
+
+        let checked = &list.pages()[0];
+        assert_eq!(checked.last_checked, Some(8_000));
+        assert_eq!(checked.last_changed, Some(8_000));
+        assert_eq!(checked.content_hash.as_deref(), Some("bbbb"));
+        assert!(!list.apply_page(page.id + 1, "cccc".into(), 9_000));
+
+        // Page and search watches share ids
+        assert!(list.add("hades", vec![], 60, 0).id > page.id);
+        assert!(list.remove_page(page.id));
+        assert!(!list.remove_page(page.id));
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let dir = std::env::temp_dir().join(format!("ws-watchlist-{}", std::process::id()));
+        let path = dir.join("watchlist.json");
+        let mut list = Watchlist::default();
+        let first = list.add("celeste", vec![], 30, 10).id;
+        list.save(&path).unwrap();
+
+        let mut loaded = Watchlist::load(&path);
+        assert_eq!(loaded.list(), list.list());
+        assert!(loaded.add("hollow knight", vec![], 30, 20).id > first);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(Watchlist::load(&path).list().is_empty());
+    }
+}